@@ -1,6 +1,13 @@
-use clap::ArgMatches;
+use crate::validator::enable::normalize;
+use crate::YES_I_KNOW_FLAG;
+use clap::{Arg, ArgMatches};
 use std::fs::create_dir_all;
+use std::io::{self, BufRead};
 use std::path::{Path, PathBuf};
+use types::PublicKey;
+
+pub const CONFIRMATION_PROMPT: &str =
+    "Enter the full public key of the validator to confirm, or anything else to abort:";
 
 pub fn ensure_dir_exists<P: AsRef<Path>>(path: P) -> Result<(), String> {
     let path = path.as_ref();
@@ -19,3 +26,44 @@ pub fn base_wallet_dir(matches: &ArgMatches, arg: &'static str) -> Result<PathBu
         PathBuf::new().join(".lighthouse").join("wallets"),
     )
 }
+
+/// Returns the flag which skips the confirmation of a destructive command.
+pub fn yes_i_know_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(YES_I_KNOW_FLAG).long(YES_I_KNOW_FLAG).help(
+        "If present, do not ask for the public key of the validator to be retyped before \
+        performing this irreversible operation.",
+    )
+}
+
+/// Require the user to confirm an irreversible operation on the validator with `pubkey` by
+/// retyping its public key on stdin, unless the `--yes-i-know` flag is present.
+///
+/// The `warning` is printed in either case.
+pub fn confirm_destructive(
+    matches: &ArgMatches,
+    pubkey: &PublicKey,
+    warning: &str,
+) -> Result<(), String> {
+    eprintln!("");
+    eprintln!("WARNING: {}", warning);
+    eprintln!("");
+
+    if matches.is_present(YES_I_KNOW_FLAG) {
+        eprintln!("Skipping confirmation, --{} is present.", YES_I_KNOW_FLAG);
+        return Ok(());
+    }
+
+    eprintln!("{}", CONFIRMATION_PROMPT);
+
+    let mut confirmation = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut confirmation)
+        .map_err(|e| format!("Error reading confirmation: {}", e))?;
+
+    if normalize(confirmation.trim()) != pubkey.to_hex_string() {
+        return Err("Public key did not match, nothing was changed".to_string());
+    }
+
+    Ok(())
+}
@@ -11,6 +11,7 @@ pub const CMD: &str = "account_manager";
 pub const SECRETS_DIR_FLAG: &str = "secrets-dir";
 pub const VALIDATOR_DIR_FLAG: &str = "validator-dir";
 pub const BASE_DIR_FLAG: &str = "base-dir";
+pub const YES_I_KNOW_FLAG: &str = "yes-i-know";
 
 pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
     App::new(CMD)
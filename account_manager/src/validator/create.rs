@@ -1,14 +1,24 @@
 use crate::{common::ensure_dir_exists, SECRETS_DIR_FLAG, VALIDATOR_DIR_FLAG};
-use account_utils::{random_password, strip_off_newlines, validator_definitions};
+use account_utils::{
+    default_keystore_password_path, random_password, strip_off_newlines,
+    validator_definitions::{ValidatorDefinition, ValidatorDefinitions, CONFIG_FILENAME},
+};
 use clap::{App, Arg, ArgMatches};
 use environment::Environment;
-use eth2_wallet::PlainText;
-use eth2_wallet_manager::WalletManager;
-use std::ffi::OsStr;
-use std::fs;
+use eth2_keystore::{keypair_from_secret, Keystore, KeystoreBuilder};
+use eth2_wallet::{
+    bip39::{Language, Mnemonic},
+    recover_validator_secret, KeyType, PlainText, ValidatorKeystores, Wallet, WalletBuilder,
+};
+use eth2_wallet_manager::{LockedWallet, WalletManager};
+use serde_derive::Serialize;
+use ssz::Encode;
+use std::fs::{self, File};
 use std::path::{Path, PathBuf};
-use types::EthSpec;
-use validator_dir::Builder as ValidatorDirBuilder;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tree_hash::TreeHash;
+use types::{ChainSpec, DepositData, EthSpec};
+use validator_dir::{Builder as ValidatorDirBuilder, VOTING_KEYSTORE_FILE};
 
 pub const CMD: &str = "create";
 pub const BASE_DIR_FLAG: &str = "base-dir";
@@ -18,12 +28,17 @@ pub const DEPOSIT_GWEI_FLAG: &str = "deposit-gwei";
 pub const STORE_WITHDRAW_FLAG: &str = "store-withdrawal-keystore";
 pub const COUNT_FLAG: &str = "count";
 pub const AT_MOST_FLAG: &str = "at-most";
+pub const MNEMONIC_FLAG: &str = "mnemonic-path";
+pub const AT_INDEX_FLAG: &str = "at-index";
+/// The prefix of the file in the validator directory which holds the deposit data of all the
+/// validators created at once, in the format of the Eth2 launchpad.
+pub const DEPOSIT_DATA_FILE_PREFIX: &str = "deposit_data-";
 
 pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
     App::new(CMD)
         .about(
-            "Creates new validators from an existing EIP-2386 wallet using the EIP-2333 HD key \
-            derivation scheme.",
+            "Creates new validators from an existing EIP-2386 wallet or a BIP-39 mnemonic using \
+            the EIP-2333 HD key derivation scheme.",
         )
         .arg(
             Arg::with_name(WALLET_NAME_FLAG)
@@ -31,7 +46,8 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("WALLET_NAME")
                 .help("Use the wallet identified by this name")
                 .takes_value(true)
-                .required(true),
+                .required_unless(MNEMONIC_FLAG)
+                .conflicts_with(MNEMONIC_FLAG),
         )
         .arg(
             Arg::with_name(WALLET_PASSWORD_FLAG)
@@ -39,7 +55,29 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .value_name("WALLET_PASSWORD_PATH")
                 .help("A path to a file containing the password which will unlock the wallet.")
                 .takes_value(true)
-                .required(true),
+                .required_unless(MNEMONIC_FLAG)
+                .conflicts_with(MNEMONIC_FLAG),
+        )
+        .arg(
+            Arg::with_name(MNEMONIC_FLAG)
+                .long(MNEMONIC_FLAG)
+                .value_name("MNEMONIC_PATH")
+                .help(
+                    "A path to a file containing a BIP-39 mnemonic from which to derive the \
+                    validator keys, instead of using a wallet.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(AT_INDEX_FLAG)
+                .long(AT_INDEX_FLAG)
+                .value_name("INDEX")
+                .help(
+                    "The EIP-2334 index of the first validator to derive. Defaults to the next \
+                    account of the wallet, or 0 when using --mnemonic-path. Deriving validators \
+                    at a given index does not update the next account of the wallet.",
+                )
+                .takes_value(true),
         )
         .arg(
             Arg::with_name(VALIDATOR_DIR_FLAG)
@@ -108,8 +146,11 @@ pub fn cli_run<T: EthSpec>(
 ) -> Result<(), String> {
     let spec = env.core_context().eth2_config.spec;
 
-    let name: String = clap_utils::parse_required(matches, WALLET_NAME_FLAG)?;
-    let wallet_password_path: PathBuf = clap_utils::parse_required(matches, WALLET_PASSWORD_FLAG)?;
+    let name: Option<String> = clap_utils::parse_optional(matches, WALLET_NAME_FLAG)?;
+    let wallet_password_path: Option<PathBuf> =
+        clap_utils::parse_optional(matches, WALLET_PASSWORD_FLAG)?;
+    let mnemonic_path: Option<PathBuf> = clap_utils::parse_optional(matches, MNEMONIC_FLAG)?;
+    let at_index: Option<u32> = clap_utils::parse_optional(matches, AT_INDEX_FLAG)?;
     let validator_dir = clap_utils::parse_path_with_default_in_home_dir(
         matches,
         VALIDATOR_DIR_FLAG,
@@ -151,32 +192,83 @@ pub fn cli_run<T: EthSpec>(
         return Ok(());
     }
 
-    let wallet_password = fs::read(&wallet_password_path)
-        .map_err(|e| format!("Unable to read {:?}: {:?}", wallet_password_path, e))
-        .map(|bytes| PlainText::from(strip_off_newlines(bytes)))?;
+    let (mut key_source, wallet_password) = match (name, wallet_password_path, mnemonic_path) {
+        (_, _, Some(mnemonic_path)) => {
+            let phrase = fs::read_to_string(&mnemonic_path)
+                .map_err(|e| format!("Unable to read {:?}: {:?}", mnemonic_path, e))?;
+            let mnemonic = Mnemonic::from_phrase(phrase.trim(), Language::English)
+                .map_err(|e| format!("Unable to parse mnemonic: {:?}", e))?;
 
-    let mgr = WalletManager::open(&wallet_base_dir)
-        .map_err(|e| format!("Unable to open --{}: {:?}", BASE_DIR_FLAG, e))?;
+            // The wallet is never saved, so its password is never needed again.
+            let wallet_password = random_password();
+            let wallet = WalletBuilder::from_mnemonic(
+                &mnemonic,
+                wallet_password.as_bytes(),
+                "mnemonic".to_string(),
+            )
+            .and_then(WalletBuilder::build)
+            .map_err(|e| format!("Unable to derive keys from mnemonic: {:?}", e))?;
+
+            (KeySource::Mnemonic(wallet), wallet_password)
+        }
+        (Some(name), Some(wallet_password_path), None) => {
+            let wallet_password = fs::read(&wallet_password_path)
+                .map_err(|e| format!("Unable to read {:?}: {:?}", wallet_password_path, e))
+                .map(|bytes| PlainText::from(strip_off_newlines(bytes)))?;
+
+            let mgr = WalletManager::open(&wallet_base_dir)
+                .map_err(|e| format!("Unable to open --{}: {:?}", BASE_DIR_FLAG, e))?;
+
+            let wallet = mgr
+                .wallet_by_name(&name)
+                .map_err(|e| format!("Unable to open wallet: {:?}", e))?;
 
-    let mut wallet = mgr
-        .wallet_by_name(&name)
-        .map_err(|e| format!("Unable to open wallet: {:?}", e))?;
+            (KeySource::Wallet(wallet), wallet_password)
+        }
+        _ => {
+            return Err(format!(
+                "Must supply either --{} and --{}, or --{}",
+                WALLET_NAME_FLAG, WALLET_PASSWORD_FLAG, MNEMONIC_FLAG
+            ))
+        }
+    };
+
+    let mut defs = ValidatorDefinitions::open_or_create(&validator_dir)
+        .map_err(|e| format!("Unable to open {}: {:?}", CONFIG_FILENAME, e))?;
+    let mut deposits = Vec::with_capacity(n);
 
     for i in 0..n {
         let voting_password = random_password();
         let withdrawal_password = random_password();
 
-        let keystores = wallet
-            .next_validator(
-                wallet_password.as_bytes(),
-                voting_password.as_bytes(),
-                withdrawal_password.as_bytes(),
-            )
-            .map_err(|e| format!("Unable to create validator keys: {:?}", e))?;
+        let keystores = match (&mut key_source, at_index) {
+            (KeySource::Wallet(wallet), None) => wallet
+                .next_validator(
+                    wallet_password.as_bytes(),
+                    voting_password.as_bytes(),
+                    withdrawal_password.as_bytes(),
+                )
+                .map_err(|e| format!("Unable to create validator keys: {:?}", e))?,
+            (key_source, at_index) => {
+                let index = at_index
+                    .unwrap_or(0)
+                    .checked_add(i as u32)
+                    .ok_or_else(|| "Validator index overflow".to_string())?;
+
+                keystores_at_index(
+                    key_source.wallet(),
+                    wallet_password.as_bytes(),
+                    index,
+                    voting_password.as_bytes(),
+                    withdrawal_password.as_bytes(),
+                )?
+            }
+        };
 
         let voting_pubkey = keystores.voting.pubkey().to_string();
+        let voting_password_path = default_keystore_password_path(&keystores.voting, &secrets_dir);
 
-        ValidatorDirBuilder::new(validator_dir.clone(), secrets_dir.clone())
+        let validator = ValidatorDirBuilder::new(validator_dir.clone(), secrets_dir.clone())
             .voting_keystore(keystores.voting, voting_password.as_bytes())
             .withdrawal_keystore(keystores.withdrawal, withdrawal_password.as_bytes())
             .create_eth1_tx_data(deposit_gwei, &spec)
@@ -184,22 +276,124 @@ pub fn cli_run<T: EthSpec>(
             .build()
             .map_err(|e| format!("Unable to build validator directory: {:?}", e))?;
 
+        if let Some(deposit) = validator
+            .eth1_deposit_data()
+            .map_err(|e| format!("Unable to read deposit data: {:?}", e))?
+        {
+            deposits.push(LaunchpadDeposit::new(&deposit.deposit_data, &spec));
+        }
+
+        let def = ValidatorDefinition::new_keystore_with_password_path(
+            validator.dir().join(VOTING_KEYSTORE_FILE),
+            voting_password_path,
+        )
+        .map_err(|e| format!("Unable to create new validator definition: {:?}", e))?;
+        defs.push(def);
+        defs.save(&validator_dir)
+            .map_err(|e| format!("Unable to save {}: {:?}", CONFIG_FILENAME, e))?;
+
         println!("{}/{}\t0x{}", i + 1, n, voting_pubkey);
     }
 
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("Unable to read system time: {:?}", e))?
+        .as_secs();
+    let deposit_data_path =
+        validator_dir.join(format!("{}{}.json", DEPOSIT_DATA_FILE_PREFIX, timestamp));
+    File::create(&deposit_data_path)
+        .map_err(|e| format!("Unable to create {:?}: {:?}", deposit_data_path, e))
+        .and_then(|file| {
+            serde_json::to_writer(file, &deposits)
+                .map_err(|e| format!("Unable to write {:?}: {:?}", deposit_data_path, e))
+        })?;
+
+    // Only the public keys are written to stdout, so that they may be parsed.
+    eprintln!("Deposit data saved to {:?}", deposit_data_path);
+
     Ok(())
 }
 
+/// The seed from which validator keys are derived.
+enum KeySource {
+    /// A wallet on disk, which keeps track of the next validator to derive.
+    Wallet(LockedWallet),
+    /// A wallet built in memory from a mnemonic supplied by the user.
+    Mnemonic(Wallet),
+}
+
+impl KeySource {
+    fn wallet(&self) -> &Wallet {
+        match self {
+            KeySource::Wallet(wallet) => wallet.wallet(),
+            KeySource::Mnemonic(wallet) => wallet,
+        }
+    }
+}
+
+/// Derive the keystores of the validator at `index`, without updating the `nextaccount` of
+/// `wallet`.
+fn keystores_at_index(
+    wallet: &Wallet,
+    wallet_password: &[u8],
+    index: u32,
+    voting_password: &[u8],
+    withdrawal_password: &[u8],
+) -> Result<ValidatorKeystores, String> {
+    let derive = |key_type: KeyType, password: &[u8]| -> Result<Keystore, String> {
+        let (secret, path) = recover_validator_secret(wallet, wallet_password, index, key_type)
+            .map_err(|e| format!("Unable to derive validator key: {:?}", e))?;
+        let keypair = keypair_from_secret(secret.as_bytes())
+            .map_err(|e| format!("Unable to derive validator keypair: {:?}", e))?;
+
+        KeystoreBuilder::new(&keypair, password, format!("{}", path))
+            .and_then(|builder| builder.build())
+            .map_err(|e| format!("Unable to build keystore: {:?}", e))
+    };
+
+    Ok(ValidatorKeystores {
+        voting: derive(KeyType::Voting, voting_password)?,
+        withdrawal: derive(KeyType::Withdrawal, withdrawal_password)?,
+    })
+}
+
+/// The deposit data of a single validator, in the format used by the Eth2 launchpad.
+#[derive(Serialize)]
+struct LaunchpadDeposit {
+    pubkey: String,
+    withdrawal_credentials: String,
+    amount: u64,
+    signature: String,
+    deposit_message_root: String,
+    deposit_data_root: String,
+    fork_version: String,
+}
+
+impl LaunchpadDeposit {
+    fn new(deposit_data: &DepositData, spec: &ChainSpec) -> Self {
+        let deposit_message = deposit_data.as_deposit_message();
+
+        Self {
+            pubkey: hex::encode(deposit_data.pubkey.as_ssz_bytes()),
+            withdrawal_credentials: hex::encode(deposit_data.withdrawal_credentials.as_bytes()),
+            amount: deposit_data.amount,
+            signature: hex::encode(deposit_data.signature.as_ssz_bytes()),
+            deposit_message_root: hex::encode(deposit_message.tree_hash_root().as_bytes()),
+            deposit_data_root: hex::encode(deposit_data.tree_hash_root().as_bytes()),
+            fork_version: hex::encode(spec.genesis_fork_version),
+        }
+    }
+}
+
 /// Returns the number of validators that exist in the given `validator_dir`.
 ///
-/// This function just assumes all files and directories, excluding the validator definitions YAML,
-/// are validator directories, making it likely to return a higher number than accurate
-/// but never a lower one.
+/// This function just assumes all directories are validator directories, making it likely to
+/// return a higher number than accurate but never a lower one.
 fn existing_validator_count<P: AsRef<Path>>(validator_dir: P) -> Result<usize, String> {
     fs::read_dir(validator_dir.as_ref())
         .map(|iter| {
             iter.filter_map(|e| e.ok())
-                .filter(|e| e.file_name() != OsStr::new(validator_definitions::CONFIG_FILENAME))
+                .filter(|e| e.path().is_dir())
                 .count()
         })
         .map_err(|e| format!("Unable to read {:?}: {}", validator_dir.as_ref(), e))
@@ -0,0 +1,222 @@
+use crate::common::{confirm_destructive, yes_i_know_arg};
+use crate::validator::enable::{find_single, normalize};
+use crate::validator::slashing_protection::{genesis_validators_root, open_db_read_only};
+use crate::VALIDATOR_DIR_FLAG;
+use account_utils::validator_definitions::{
+    SigningDefinition, ValidatorDefinitions, CONFIG_FILENAME,
+};
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use slashing_protection::SLASHING_PROTECTION_FILENAME;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use types::{EthSpec, PublicKey};
+
+pub const CMD: &str = "delete";
+pub const PUBKEY_ARG: &str = "pubkey";
+/// The directory within the validator directory where slashing protection data is archived.
+pub const ARCHIVE_DIR: &str = "slashing_protection_archive";
+
+pub const DELETE_WARNING: &str = "DELETING A VALIDATOR CANNOT BE REVERSED. UNLESS THE KEYSTORE IS \
+                                  BACKED UP ELSEWHERE, THE VALIDATOR WILL BE UNABLE TO SIGN AGAIN.";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Deletes a validator from the validator_definitions.yml file along with its keystore. \
+            Its slashing protection history is first archived as an EIP-3076 interchange file. \
+            The public key of the validator must be retyped to confirm, unless --yes-i-know is \
+            present.",
+        )
+        .arg(
+            Arg::with_name(PUBKEY_ARG)
+                .value_name("PUBKEY")
+                .help(
+                    "The public key of the validator, or a prefix of it which matches a single \
+                    validator.",
+                )
+                .required(true),
+        )
+        .arg(
+            Arg::with_name(VALIDATOR_DIR_FLAG)
+                .long(VALIDATOR_DIR_FLAG)
+                .value_name("VALIDATOR_DIRECTORY")
+                .help(
+                    "The path where the validator directories are stored. \
+                    Defaults to ~/.lighthouse/validators",
+                )
+                .takes_value(true),
+        )
+        .arg(yes_i_know_arg())
+}
+
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
+    let validator_dir = clap_utils::parse_path_with_default_in_home_dir(
+        matches,
+        VALIDATOR_DIR_FLAG,
+        PathBuf::new().join(".lighthouse").join("validators"),
+    )?;
+    let pattern = normalize(&clap_utils::parse_required::<String>(matches, PUBKEY_ARG)?);
+
+    if !validator_dir.join(CONFIG_FILENAME).exists() {
+        return Err(format!(
+            "No {} found in {:?}",
+            CONFIG_FILENAME, validator_dir
+        ));
+    }
+
+    let mut defs = ValidatorDefinitions::open(&validator_dir)
+        .map_err(|e| format!("Unable to open {}: {:?}", CONFIG_FILENAME, e))?;
+    let index = find_single(&defs, &pattern)?;
+    let def = &defs.as_slice()[index];
+    let pubkey = def.voting_public_key.clone();
+
+    // The files which belong to the validator alone, and are removed with it.
+    let mut paths_to_remove = vec![];
+    match &def.signing_definition {
+        SigningDefinition::LocalKeystore {
+            voting_keystore_path,
+            voting_keystore_password_path,
+            ..
+        }
+        | SigningDefinition::ThresholdShare {
+            voting_keystore_path,
+            voting_keystore_password_path,
+            ..
+        } => {
+            let lockfile_path = voting_keystore_path.with_file_name(format!(
+                "{}.lock",
+                voting_keystore_path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or_default()
+            ));
+            if lockfile_path.exists() {
+                return Err(format!(
+                    "{:?} exists, ensure the validator client is not running",
+                    lockfile_path
+                ));
+            }
+
+            // Validators created or imported by Lighthouse have a directory of their own, which
+            // is named after the public key.
+            match voting_keystore_path.parent() {
+                Some(dir) if dir == validator_dir.join(pubkey.to_hex_string()) => {
+                    paths_to_remove.push(dir.to_path_buf())
+                }
+                _ => paths_to_remove.push(voting_keystore_path.clone()),
+            }
+
+            // Only remove a password file named after the validator, which no other validator
+            // can be using.
+            if let Some(path) = voting_keystore_password_path {
+                if path.ends_with(pubkey.to_hex_string()) {
+                    paths_to_remove.push(path.clone());
+                }
+            }
+        }
+        SigningDefinition::Web3Signer { .. } => (),
+    }
+
+    eprintln!("");
+    eprintln!("Deleting validator {:?}", pubkey);
+    eprintln!(" - Definition in {:?}", validator_dir.join(CONFIG_FILENAME));
+    for path in &paths_to_remove {
+        eprintln!(" - {:?}", path);
+    }
+
+    confirm_destructive(matches, &pubkey, DELETE_WARNING)?;
+
+    let db_path = validator_dir.join(SLASHING_PROTECTION_FILENAME);
+    if db_path.exists() {
+        archive_slashing_protection(&env, &db_path, &validator_dir, &pubkey)?;
+    }
+
+    defs.remove(index);
+    defs.save(&validator_dir)
+        .map_err(|e| format!("Unable to save {}: {:?}", CONFIG_FILENAME, e))?;
+
+    // The definition is already gone, so carry on removing files after an error rather than
+    // leaving more behind than necessary.
+    let mut failed = vec![];
+    for path in &paths_to_remove {
+        let result = if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        };
+        if let Err(e) = result {
+            eprintln!("Unable to remove {:?}: {:?}", path, e);
+            failed.push(path);
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(format!(
+            "Removed validator {:?} from {}, but {} file(s) could not be removed",
+            pubkey,
+            CONFIG_FILENAME,
+            failed.len()
+        ));
+    }
+
+    eprintln!("");
+    eprintln!("Successfully deleted validator {:?}", pubkey);
+
+    Ok(())
+}
+
+/// Export the slashing protection history of `pubkey` from the database at `db_path` to a new
+/// file in the `ARCHIVE_DIR` of `validator_dir`.
+///
+/// The history is not removed from the database, so that it continues to protect the validator if
+/// its key is imported again.
+fn archive_slashing_protection<T: EthSpec>(
+    env: &Environment<T>,
+    db_path: &Path,
+    validator_dir: &Path,
+    pubkey: &PublicKey,
+) -> Result<(), String> {
+    let db = open_db_read_only(db_path)?;
+    let genesis_validators_root = match db
+        .genesis_validators_root()
+        .map_err(|e| format!("Unable to read slashing protection database: {:?}", e))?
+    {
+        Some(root) => root,
+        None => genesis_validators_root(env)?,
+    };
+
+    // Exporting every validator avoids an error when this one was never registered.
+    let mut interchange = db
+        .export_interchange_info(genesis_validators_root, None)
+        .map_err(|e| format!("Unable to export slashing protection data: {:?}", e))?;
+    interchange.data.retain(|data| data.pubkey == *pubkey);
+
+    if interchange.is_empty() {
+        eprintln!("No slashing protection data to archive.");
+        return Ok(());
+    }
+
+    let archive_dir = validator_dir.join(ARCHIVE_DIR);
+    fs::create_dir_all(&archive_dir)
+        .map_err(|e| format!("Unable to create {:?}: {:?}", archive_dir, e))?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("Unable to read system time: {:?}", e))?
+        .as_secs();
+    let archive_path = archive_dir.join(format!("{:?}-{}.json", pubkey, timestamp));
+
+    File::create(&archive_path)
+        .map_err(|e| format!("Unable to create {:?}: {:?}", archive_path, e))
+        .and_then(|file| {
+            interchange
+                .write_to(file)
+                .map_err(|e| format!("Unable to write {:?}: {:?}", archive_path, e))
+        })?;
+
+    eprintln!("Archived slashing protection data to {:?}", archive_path);
+
+    Ok(())
+}
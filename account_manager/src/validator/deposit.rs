@@ -6,6 +6,7 @@ use futures::{
     compat::Future01CompatExt,
     stream::{FuturesUnordered, StreamExt},
 };
+use serde_derive::Serialize;
 use slog::{info, Logger};
 use state_processing::per_block_processing::verify_deposit_signature;
 use std::path::PathBuf;
@@ -13,6 +14,7 @@ use tokio::time::{delay_until, Duration, Instant};
 use types::EthSpec;
 use validator_dir::{Eth1DepositData, Manager as ValidatorManager, ValidatorDir};
 use web3::{
+    confirm::wait_for_transaction_confirmation,
     transports::Http,
     transports::Ipc,
     types::{Address, SyncInfo, SyncState, TransactionRequest, U256},
@@ -26,6 +28,7 @@ pub const ETH1_HTTP_FLAG: &str = "eth1-http";
 pub const FROM_ADDRESS_FLAG: &str = "from-address";
 pub const CONFIRMATION_COUNT_FLAG: &str = "confirmation-count";
 pub const CONFIRMATION_BATCH_SIZE_FLAG: &str = "confirmation-batch-size";
+pub const OFFLINE_FLAG: &str = "offline";
 
 const GWEI: u64 = 1_000_000_000;
 
@@ -41,8 +44,9 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             have been created and exist on the file-system. The process will exit immediately \
             with an error if any error occurs. After each deposit is submitted to the Eth1 \
             node, a file will be saved in the validator directory with the transaction hash. \
-            If confirmations are set to non-zero then the application will wait for confirmations \
-            before saving the transaction hash and moving onto the next batch of deposits. \
+            Validators which already have this file are skipped, so that a deposit is never \
+            submitted twice. If confirmations are set to non-zero then the application will \
+            wait for confirmations before moving onto the next batch of deposits. \
             The deposit contract address will be determined by the --testnet-dir flag on the \
             primary Lighthouse binary.",
         )
@@ -92,7 +96,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     Must be unlocked on the node at --eth1-ipc.",
                 )
                 .takes_value(true)
-                .required(true),
+                .required_unless(OFFLINE_FLAG),
         )
         .arg(
             Arg::with_name(CONFIRMATION_COUNT_FLAG)
@@ -117,6 +121,22 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .takes_value(true)
                 .default_value("10"),
         )
+        .arg(Arg::with_name(OFFLINE_FLAG).long(OFFLINE_FLAG).help(
+            "If present, don't connect to an Eth1 node. Instead, print the unsigned \
+                    deposit transactions as JSON so that they can be signed and submitted \
+                    elsewhere. Deposits printed this way are not recorded as submitted.",
+        ))
+}
+
+/// An unsigned deposit transaction, as printed with `--offline`.
+#[derive(Serialize)]
+struct UnsignedDepositTransaction {
+    validator_dir: PathBuf,
+    voting_pubkey: String,
+    to: String,
+    value: String,
+    gas: String,
+    data: String,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -157,36 +177,56 @@ where
                 let log = log.clone();
                 futures.push(async move {
                     let tx_hash = web3
-                        .send_transaction_with_confirmation(
-                            TransactionRequest {
-                                from: from_address,
-                                to: Some(deposit_contract),
-                                gas: Some(DEPOSIT_GAS.into()),
-                                gas_price: None,
-                                value: Some(from_gwei(eth1_deposit_data.deposit_data.amount)),
-                                data: Some(eth1_deposit_data.rlp.clone().into()),
-                                nonce: None,
-                                condition: None,
-                            },
-                            CONFIRMATIONS_POLL_TIME,
-                            confirmation_count,
-                        )
+                        .eth()
+                        .send_transaction(TransactionRequest {
+                            from: from_address,
+                            to: Some(deposit_contract),
+                            gas: Some(DEPOSIT_GAS.into()),
+                            gas_price: None,
+                            value: Some(from_gwei(eth1_deposit_data.deposit_data.amount)),
+                            data: Some(eth1_deposit_data.rlp.clone().into()),
+                            nonce: None,
+                            condition: None,
+                        })
                         .compat()
                         .await
                         .map_err(|e| format!("Failed to send transaction: {:?}", e))?;
 
+                    // Record the deposit as soon as the eth1 node has accepted it, so that it
+                    // won't be submitted again if we're interrupted whilst waiting for
+                    // confirmations.
+                    validator_dir
+                        .save_eth1_deposit_tx_hash(&format!("{:?}", tx_hash))
+                        .map_err(|e| {
+                            format!("Failed to save tx hash {:?} to disk: {:?}", tx_hash, e)
+                        })?;
+
                     info!(
                         log,
                         "Submitted deposit";
                         "tx_hash" => format!("{:?}", tx_hash),
                     );
 
-                    validator_dir
-                        .save_eth1_deposit_tx_hash(&format!("{:?}", tx_hash))
+                    if confirmation_count > 0 {
+                        wait_for_transaction_confirmation(
+                            web3.transport().clone(),
+                            tx_hash,
+                            CONFIRMATIONS_POLL_TIME,
+                            confirmation_count,
+                        )
+                        .compat()
+                        .await
                         .map_err(|e| {
-                            format!("Failed to save tx hash {:?} to disk: {:?}", tx_hash, e)
+                            format!("Failed to confirm transaction {:?}: {:?}", tx_hash, e)
                         })?;
 
+                        info!(
+                            log,
+                            "Deposit confirmed";
+                            "tx_hash" => format!("{:?}", tx_hash),
+                        );
+                    }
+
                     Ok::<(), String>(())
                 });
             }
@@ -220,10 +260,11 @@ pub fn cli_run<T: EthSpec>(
     let validator: String = clap_utils::parse_required(matches, VALIDATOR_FLAG)?;
     let eth1_ipc_path: Option<PathBuf> = clap_utils::parse_optional(matches, ETH1_IPC_FLAG)?;
     let eth1_http_url: Option<String> = clap_utils::parse_optional(matches, ETH1_HTTP_FLAG)?;
-    let from_address: Address = clap_utils::parse_required(matches, FROM_ADDRESS_FLAG)?;
+    let from_address: Option<Address> = clap_utils::parse_optional(matches, FROM_ADDRESS_FLAG)?;
     let confirmation_count: usize = clap_utils::parse_required(matches, CONFIRMATION_COUNT_FLAG)?;
     let confirmation_batch_size: usize =
         clap_utils::parse_required(matches, CONFIRMATION_BATCH_SIZE_FLAG)?;
+    let offline = matches.is_present(OFFLINE_FLAG);
 
     let manager = ValidatorManager::open(&data_dir)
         .map_err(|e| format!("Unable to read --{}: {:?}", VALIDATOR_DIR_FLAG, e))?;
@@ -252,9 +293,20 @@ pub fn cli_run<T: EthSpec>(
         }
     }?;
 
+    let (deposited, validators): (Vec<_>, Vec<_>) = validators
+        .into_iter()
+        .partition(|v| v.eth1_deposit_tx_hash_exists());
+
+    if !deposited.is_empty() {
+        info!(
+            log,
+            "Skipping validators with a previous deposit";
+            "count" => deposited.len(),
+        );
+    }
+
     let eth1_deposit_datas = validators
         .into_iter()
-        .filter(|v| !v.eth1_deposit_tx_hash_exists())
         .map(|v| match v.eth1_deposit_data() {
             Ok(Some(data)) => Ok((v, data)),
             Ok(None) => Err(format!(
@@ -298,6 +350,13 @@ pub fn cli_run<T: EthSpec>(
         return Err("Refusing to deposit to the zero address. Check testnet configuration.".into());
     }
 
+    if offline {
+        return print_deposit_transactions(&eth1_deposit_datas, deposit_contract, &env);
+    }
+
+    let from_address =
+        from_address.ok_or_else(|| format!("error: Must supply --{}", FROM_ADDRESS_FLAG))?;
+
     match (eth1_ipc_path, eth1_http_url) {
         (Some(_), Some(_)) => Err(format!(
             "error: Cannot supply both --{} and --{}",
@@ -338,6 +397,44 @@ pub fn cli_run<T: EthSpec>(
     }
 }
 
+/// Print the deposit transactions for `eth1_deposit_datas` to stdout as JSON, without submitting
+/// them.
+fn print_deposit_transactions<T: EthSpec>(
+    eth1_deposit_datas: &[(ValidatorDir, Eth1DepositData)],
+    deposit_contract: Address,
+    env: &Environment<T>,
+) -> Result<(), String> {
+    let spec = &env.eth2_config.spec;
+
+    let transactions = eth1_deposit_datas
+        .iter()
+        .map(|(validator_dir, eth1_deposit_data)| {
+            verify_deposit_signature(&eth1_deposit_data.deposit_data, spec).map_err(|e| {
+                format!(
+                    "Deposit for {:?} fails verification, \
+                     are you using the correct testnet configuration?\nError: {:?}",
+                    eth1_deposit_data.deposit_data.pubkey, e
+                )
+            })?;
+
+            Ok(UnsignedDepositTransaction {
+                validator_dir: validator_dir.dir().clone(),
+                voting_pubkey: format!("{:?}", eth1_deposit_data.deposit_data.pubkey),
+                to: format!("{:?}", deposit_contract),
+                value: format!("0x{:x}", from_gwei(eth1_deposit_data.deposit_data.amount)),
+                gas: format!("0x{:x}", DEPOSIT_GAS),
+                data: format!("0x{}", hex::encode(&eth1_deposit_data.rlp)),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let json = serde_json::to_string_pretty(&transactions)
+        .map_err(|e| format!("Unable to encode transactions as JSON: {:?}", e))?;
+    println!("{}", json);
+
+    Ok(())
+}
+
 /// Converts gwei to wei.
 fn from_gwei(gwei: u64) -> U256 {
     U256::from(gwei) * U256::exp10(9)
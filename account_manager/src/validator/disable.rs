@@ -0,0 +1,16 @@
+use crate::validator::enable::{set_enabled, set_enabled_cli_app};
+use clap::{App, ArgMatches};
+
+pub const CMD: &str = "disable";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    set_enabled_cli_app(
+        CMD,
+        "Disables validators in the validator_definitions.yml file, so that they are not loaded \
+        by the validator client when it next starts. Their keystores are left in place.",
+    )
+}
+
+pub fn cli_run(matches: &ArgMatches) -> Result<(), String> {
+    set_enabled(matches, false)
+}
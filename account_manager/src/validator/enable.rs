@@ -0,0 +1,223 @@
+use crate::VALIDATOR_DIR_FLAG;
+use account_utils::validator_definitions::{
+    SigningDefinition, ValidatorDefinitions, CONFIG_FILENAME,
+};
+use clap::{App, Arg, ArgMatches};
+use std::path::PathBuf;
+
+pub const CMD: &str = "enable";
+pub const PUBKEYS_ARG: &str = "pubkeys";
+pub const ALL_FLAG: &str = "all";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    set_enabled_cli_app(
+        CMD,
+        "Enables validators in the validator_definitions.yml file, so that they are loaded by \
+        the validator client when it next starts.",
+    )
+}
+
+pub fn cli_run(matches: &ArgMatches) -> Result<(), String> {
+    set_enabled(matches, true)
+}
+
+/// Returns the arguments shared by the `enable` and `disable` commands.
+pub fn set_enabled_cli_app<'a, 'b>(cmd: &str, about: &'b str) -> App<'a, 'b> {
+    App::new(cmd)
+        .about(about)
+        .arg(
+            Arg::with_name(PUBKEYS_ARG)
+                .value_name("PUBKEY")
+                .help(
+                    "The public keys of the validators. Each may be a full public key, a prefix \
+                    of one (which must match a single validator) or a pattern where `*` \
+                    matches any characters (e.g. `0xa5*`).",
+                )
+                .multiple(true)
+                .required_unless(ALL_FLAG)
+                .conflicts_with(ALL_FLAG),
+        )
+        .arg(
+            Arg::with_name(ALL_FLAG)
+                .long(ALL_FLAG)
+                .help("If present, apply to every validator."),
+        )
+        .arg(
+            Arg::with_name(VALIDATOR_DIR_FLAG)
+                .long(VALIDATOR_DIR_FLAG)
+                .value_name("VALIDATOR_DIRECTORY")
+                .help(
+                    "The path where the validator directories are stored. \
+                    Defaults to ~/.lighthouse/validators",
+                )
+                .takes_value(true),
+        )
+}
+
+/// Set the `enabled` flag of each validator matched by `matches` to `enabled`, saving the
+/// definitions file only if every pattern is valid.
+pub fn set_enabled(matches: &ArgMatches, enabled: bool) -> Result<(), String> {
+    let validator_dir = clap_utils::parse_path_with_default_in_home_dir(
+        matches,
+        VALIDATOR_DIR_FLAG,
+        PathBuf::new().join(".lighthouse").join("validators"),
+    )?;
+    let all = matches.is_present(ALL_FLAG);
+    let patterns = matches
+        .values_of(PUBKEYS_ARG)
+        .map(|values| values.map(normalize).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    if !validator_dir.join(CONFIG_FILENAME).exists() {
+        return Err(format!(
+            "No {} found in {:?}",
+            CONFIG_FILENAME, validator_dir
+        ));
+    }
+
+    let mut defs = ValidatorDefinitions::open(&validator_dir)
+        .map_err(|e| format!("Unable to open {}: {:?}", CONFIG_FILENAME, e))?;
+
+    let pubkeys = defs
+        .as_slice()
+        .iter()
+        .map(|def| normalize(&def.voting_public_key.to_hex_string()))
+        .collect::<Vec<_>>();
+
+    let mut selected = vec![all; pubkeys.len()];
+    for pattern in &patterns {
+        let matching = pubkeys
+            .iter()
+            .enumerate()
+            .filter(|(_, pubkey)| matches_pattern(pattern, pubkey))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+
+        match matching.len() {
+            0 => return Err(format!("No validator matches {}", pattern)),
+            // A plain prefix could otherwise select more validators than intended.
+            n if n > 1 && !pattern.contains('*') => {
+                return Err(format!(
+                    "{} matches {} validators, use a longer prefix or a `*` pattern",
+                    pattern, n
+                ))
+            }
+            _ => matching.into_iter().for_each(|i| selected[i] = true),
+        }
+    }
+
+    // The validator client refuses to start with an enabled threshold share, since it cannot yet
+    // sign with one. Shares are skipped by `--all`, but naming one is an error.
+    if enabled {
+        for (i, def) in defs.as_slice().iter().enumerate() {
+            let is_share = matches!(
+                def.signing_definition,
+                SigningDefinition::ThresholdShare { .. }
+            );
+            if selected[i] && is_share {
+                if !all {
+                    return Err(format!(
+                        "{} is a threshold share, which the validator client cannot yet sign with",
+                        def.voting_public_key.to_hex_string()
+                    ));
+                }
+                eprintln!(
+                    "Skipped threshold share {}",
+                    def.voting_public_key.to_hex_string()
+                );
+                selected[i] = false;
+            }
+        }
+    }
+
+    let mut num_changed = 0;
+    for (def, _) in defs
+        .as_mut_slice()
+        .iter_mut()
+        .zip(selected)
+        .filter(|(_, selected)| *selected)
+    {
+        if def.enabled != enabled {
+            def.enabled = enabled;
+            num_changed += 1;
+        }
+        println!(
+            "{} {}",
+            if enabled { "Enabled" } else { "Disabled" },
+            def.voting_public_key.to_hex_string()
+        );
+    }
+
+    defs.save(&validator_dir)
+        .map_err(|e| format!("Unable to save {}: {:?}", CONFIG_FILENAME, e))?;
+
+    eprintln!(
+        "Updated {} validators in {}. Restart the validator client for the change to take effect.",
+        num_changed, CONFIG_FILENAME
+    );
+
+    Ok(())
+}
+
+/// Returns the index of the single validator in `defs` whose public key matches `pattern`, which is
+/// a full public key or a prefix of one.
+pub(crate) fn find_single(defs: &ValidatorDefinitions, pattern: &str) -> Result<usize, String> {
+    let matching = defs
+        .as_slice()
+        .iter()
+        .enumerate()
+        .filter(|(_, def)| {
+            matches_pattern(pattern, &normalize(&def.voting_public_key.to_hex_string()))
+        })
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+
+    match matching.as_slice() {
+        [i] => Ok(*i),
+        [] => Err(format!("No validator matches {}", pattern)),
+        _ => Err(format!(
+            "{} matches {} validators, use a longer prefix",
+            pattern,
+            matching.len()
+        )),
+    }
+}
+
+/// Lower-case `pubkey` and add the `0x` prefix if it is missing.
+pub(crate) fn normalize(pubkey: &str) -> String {
+    let pubkey = pubkey.to_lowercase();
+    if pubkey.starts_with("0x") {
+        pubkey
+    } else {
+        format!("0x{}", pubkey)
+    }
+}
+
+/// Returns `true` if `pubkey` matches `pattern`.
+///
+/// A pattern without a `*` matches any public key that it is a prefix of, whilst each `*` in a
+/// pattern matches any sequence of characters.
+pub(crate) fn matches_pattern(pattern: &str, pubkey: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    if !pubkey.starts_with(first) {
+        return false;
+    }
+
+    let mut rest = &pubkey[first.len()..];
+    let mut parts = parts.collect::<Vec<_>>();
+    let last = match parts.pop() {
+        Some(last) => last,
+        // There is no `*`, so `pattern` is a prefix.
+        None => return true,
+    };
+
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(last)
+}
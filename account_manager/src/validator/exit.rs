@@ -0,0 +1,171 @@
+use crate::common::{confirm_destructive, yes_i_know_arg};
+use account_utils::read_password_from_user;
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use eth2_keystore::Keystore;
+use remote_beacon_node::RemoteBeaconNode;
+use std::path::PathBuf;
+use types::{ChainSpec, EthSpec, Keypair, VoluntaryExit};
+
+pub const CMD: &str = "exit";
+pub const KEYSTORE_FLAG: &str = "keystore";
+pub const BEACON_SERVER_FLAG: &str = "beacon-node";
+pub const STDIN_INPUTS_FLAG: &str = "stdin-inputs";
+pub const DEFAULT_BEACON_NODE: &str = "http://localhost:5052/";
+
+pub const EXIT_WARNING: &str = "AN EXIT CANNOT BE REVERSED. THE VALIDATOR WILL STOP EARNING \
+                                REWARDS AND ITS FUNDS CANNOT BE WITHDRAWN UNTIL TRANSFERS ARE \
+                                ENABLED.";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Submits a signed voluntary exit for a validator to a beacon node, which publishes \
+            it to the network. The public key of the validator must be retyped to confirm \
+            before anything is signed, unless --yes-i-know is present.",
+        )
+        .arg(
+            Arg::with_name(KEYSTORE_FLAG)
+                .long(KEYSTORE_FLAG)
+                .value_name("KEYSTORE_PATH")
+                .help("Path to the EIP-2335 voting keystore of the validator to exit.")
+                .required(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(BEACON_SERVER_FLAG)
+                .long(BEACON_SERVER_FLAG)
+                .value_name("NETWORK_ADDRESS")
+                .help("Address of the beacon node HTTP API.")
+                .default_value(DEFAULT_BEACON_NODE)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(STDIN_INPUTS_FLAG)
+                .long(STDIN_INPUTS_FLAG)
+                .help("If present, read the keystore password from stdin instead of tty."),
+        )
+        .arg(yes_i_know_arg())
+}
+
+pub fn cli_run<E: EthSpec>(matches: &ArgMatches, mut env: Environment<E>) -> Result<(), String> {
+    let keystore_path: PathBuf = clap_utils::parse_required(matches, KEYSTORE_FLAG)?;
+    let beacon_node_url: String = clap_utils::parse_required(matches, BEACON_SERVER_FLAG)?;
+    let stdin_inputs = matches.is_present(STDIN_INPUTS_FLAG);
+    let spec = env.eth2_config.spec.clone();
+
+    let keystore = Keystore::from_json_file(&keystore_path)
+        .map_err(|e| format!("Unable to read keystore JSON {:?}: {:?}", keystore_path, e))?;
+    let keypair = decrypt_keypair(&keystore, stdin_inputs)?;
+
+    let beacon_node = RemoteBeaconNode::<E>::new(beacon_node_url)?;
+
+    env.runtime().block_on(publish_voluntary_exit(
+        matches,
+        &keypair,
+        &beacon_node,
+        &spec,
+    ))
+}
+
+/// Prompt for the password of `keystore` until the correct one is entered.
+///
+/// When reading from stdin, an incorrect password is an error since prompting again would consume
+/// the confirmation.
+fn decrypt_keypair(keystore: &Keystore, stdin_inputs: bool) -> Result<Keypair, String> {
+    loop {
+        eprintln!("");
+        eprintln!(
+            "Enter the keystore password for validator 0x{}:",
+            keystore.pubkey()
+        );
+
+        let password = read_password_from_user(stdin_inputs)?;
+
+        match keystore.decrypt_keypair(password.as_ref()) {
+            Ok(keypair) => {
+                eprintln!("Password is correct.");
+                return Ok(keypair);
+            }
+            Err(eth2_keystore::Error::InvalidPassword) => {
+                eprintln!("Invalid password");
+                if stdin_inputs {
+                    return Err("Invalid password read from stdin".to_string());
+                }
+            }
+            Err(e) => return Err(format!("Error whilst decrypting keypair: {:?}", e)),
+        }
+    }
+}
+
+/// Sign a voluntary exit for the validator of `keypair` at the current epoch of `beacon_node`,
+/// and publish it once the user has confirmed.
+async fn publish_voluntary_exit<E: EthSpec>(
+    matches: &ArgMatches<'_>,
+    keypair: &Keypair,
+    beacon_node: &RemoteBeaconNode<E>,
+    spec: &ChainSpec,
+) -> Result<(), String> {
+    let beacon = beacon_node.http.beacon();
+
+    let genesis_validators_root = beacon
+        .get_genesis_validators_root()
+        .await
+        .map_err(|e| format!("Unable to get genesis validators root: {:?}", e))?;
+    let fork = beacon
+        .get_fork()
+        .await
+        .map_err(|e| format!("Unable to get fork: {:?}", e))?;
+    let head = beacon
+        .get_head()
+        .await
+        .map_err(|e| format!("Unable to get head: {:?}", e))?;
+    let validator = beacon
+        .get_validators(vec![keypair.pk.clone()], None)
+        .await
+        .map_err(|e| format!("Unable to get validator: {:?}", e))?
+        .pop()
+        .ok_or_else(|| "Beacon node returned no validators".to_string())?;
+
+    let validator_index = validator.validator_index.ok_or_else(|| {
+        format!(
+            "Validator {:?} is unknown to the beacon node, its deposit may not be processed yet",
+            keypair.pk
+        )
+    })?;
+    if let Some(validator) = validator.validator {
+        if validator.exit_epoch != spec.far_future_epoch {
+            return Err(format!(
+                "Validator {:?} is already exiting at epoch {}",
+                keypair.pk, validator.exit_epoch
+            ));
+        }
+    }
+
+    let exit = VoluntaryExit {
+        epoch: head.slot.epoch(E::slots_per_epoch()),
+        validator_index: validator_index as u64,
+    };
+
+    eprintln!("");
+    eprintln!("Publishing a voluntary exit for validator {:?}", keypair.pk);
+    eprintln!(" - Validator index: {}", exit.validator_index);
+    eprintln!(" - Epoch: {}", exit.epoch);
+
+    confirm_destructive(matches, &keypair.pk, EXIT_WARNING)?;
+
+    let signed_exit = exit.sign(&keypair.sk, &fork, genesis_validators_root, spec);
+
+    beacon
+        .voluntary_exit(signed_exit)
+        .await
+        .map_err(|e| format!("Beacon node refused the voluntary exit: {:?}", e))?;
+
+    eprintln!("");
+    eprintln!(
+        "Successfully published a voluntary exit for validator {:?}",
+        keypair.pk
+    );
+
+    Ok(())
+}
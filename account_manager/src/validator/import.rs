@@ -1,22 +1,43 @@
+use super::slashing_protection::{
+    genesis_validators_root,
+    import::{import_interchange, read_interchange},
+    parse_pubkeys, PUBKEYS_FLAG,
+};
 use crate::{common::ensure_dir_exists, VALIDATOR_DIR_FLAG};
 use account_utils::{
     eth2_keystore::Keystore,
-    read_password_from_user,
+    read_password_from_user, strip_off_newlines,
     validator_definitions::{
-        recursively_find_voting_keystores, ValidatorDefinition, ValidatorDefinitions,
-        CONFIG_FILENAME,
+        recursively_find_keystores_with_passwords, recursively_find_prysm_wallets, FoundKeystore,
+        SigningDefinition, ValidatorDefinition, ValidatorDefinitions, CONFIG_FILENAME,
     },
+    ZeroizeString,
 };
 use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use rayon::prelude::*;
+use slashing_protection::{interchange::Interchange, SLASHING_PROTECTION_FILENAME};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::thread::sleep;
 use std::time::Duration;
+use types::{EthSpec, Hash256};
 
 pub const CMD: &str = "import";
 pub const KEYSTORE_FLAG: &str = "keystore";
 pub const DIR_FLAG: &str = "directory";
-pub const STDIN_PASSWORD_FLAG: &str = "stdin-passwords";
+pub const STDIN_INPUTS_FLAG: &str = "stdin-inputs";
+pub const REUSE_PASSWORD_FLAG: &str = "reuse-password";
+pub const PASSWORD_FILE_FLAG: &str = "password-file";
+pub const NO_PASSWORD_FLAG: &str = "no-password";
+pub const DRY_RUN_FLAG: &str = "dry-run";
+pub const SLASHING_PROTECTION_FLAG: &str = "slashing-protection";
+pub const WEB3SIGNER_URL_FLAG: &str = "web3signer-url";
+pub const WEB3SIGNER_ROOT_CERTIFICATE_FLAG: &str = "web3signer-root-certificate";
+pub const WEB3SIGNER_CLIENT_IDENTITY_FLAG: &str = "web3signer-client-identity";
+pub const WEB3SIGNER_CLIENT_IDENTITY_PASSWORD_FILE_FLAG: &str =
+    "web3signer-client-identity-password-file";
 
 pub const PASSWORD_PROMPT: &str = "Enter the keystore password, or press enter to omit it:";
 pub const KEYSTORE_REUSE_WARNING: &str = "DO NOT USE THE ORIGINAL KEYSTORES TO VALIDATE WITH \
@@ -28,15 +49,15 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             "Imports one or more EIP-2335 passwords into a Lighthouse VC directory, \
             requesting passwords interactively. The directory flag provides a convenient \
             method for importing a directory of keys generated by the eth2-deposit-cli \
-            Python utility.",
+            Python utility, or the keys of a Prysm, Teku or Nimbus validator client.",
         )
         .arg(
             Arg::with_name(KEYSTORE_FLAG)
                 .long(KEYSTORE_FLAG)
                 .value_name("KEYSTORE_PATH")
                 .help("Path to a single keystore to be imported.")
-                .conflicts_with(DIR_FLAG)
-                .required_unless(DIR_FLAG)
+                .conflicts_with_all(&[DIR_FLAG, WEB3SIGNER_URL_FLAG])
+                .required_unless_one(&[DIR_FLAG, WEB3SIGNER_URL_FLAG])
                 .takes_value(true),
         )
         .arg(
@@ -47,10 +68,12 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     "Path to a directory which contains zero or more keystores \
                     for import. This directory and all sub-directories will be \
                     searched and any file name which contains 'keystore' and \
-                    has the '.json' extension will be attempted to be imported.",
+                    has the '.json' extension will be attempted to be imported. \
+                    The directory layouts of Teku and Nimbus are also recognised, \
+                    and the passwords they store are used instead of prompting.",
                 )
-                .conflicts_with(KEYSTORE_FLAG)
-                .required_unless(KEYSTORE_FLAG)
+                .conflicts_with_all(&[KEYSTORE_FLAG, WEB3SIGNER_URL_FLAG])
+                .required_unless_one(&[KEYSTORE_FLAG, WEB3SIGNER_URL_FLAG])
                 .takes_value(true),
         )
         .arg(
@@ -64,13 +87,119 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .takes_value(true),
         )
         .arg(
-            Arg::with_name(STDIN_PASSWORD_FLAG)
-                .long(STDIN_PASSWORD_FLAG)
-                .help("If present, read passwords from stdin instead of tty."),
+            Arg::with_name(STDIN_INPUTS_FLAG)
+                .long(STDIN_INPUTS_FLAG)
+                .alias("stdin-passwords")
+                .help(
+                    "If present, read passwords from stdin instead of tty, one per line. \
+                    Allows passwords to be piped in from another program.",
+                ),
+        )
+        .arg(
+            Arg::with_name(REUSE_PASSWORD_FLAG)
+                .long(REUSE_PASSWORD_FLAG)
+                .help(
+                    "If present, the first password entered is also tried for each \
+                    subsequent keystore, only prompting again if it is incorrect.",
+                ),
+        )
+        .arg(
+            Arg::with_name(PASSWORD_FILE_FLAG)
+                .long(PASSWORD_FILE_FLAG)
+                .value_name("PASSWORD_FILE_PATH")
+                .help(
+                    "A path to a file containing the password for all of the keystores. \
+                    The import fails without modifying anything if the password is \
+                    incorrect for any keystore.",
+                )
+                .conflicts_with_all(&[STDIN_INPUTS_FLAG, REUSE_PASSWORD_FLAG, NO_PASSWORD_FLAG])
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(NO_PASSWORD_FLAG)
+                .long(NO_PASSWORD_FLAG)
+                .help(
+                    "If present, import the keystores without storing their passwords. The \
+                    password of each keystore must then be supplied to the validator client.",
+                )
+                .conflicts_with_all(&[STDIN_INPUTS_FLAG, REUSE_PASSWORD_FLAG]),
+        )
+        .arg(Arg::with_name(DRY_RUN_FLAG).long(DRY_RUN_FLAG).help(
+            "If present, validate the keystores and print the files that would be \
+                    copied and the definitions that would be added, without modifying \
+                    anything.",
+        ))
+        .arg(
+            Arg::with_name(SLASHING_PROTECTION_FLAG)
+                .long(SLASHING_PROTECTION_FLAG)
+                .value_name("INTERCHANGE_FILE")
+                .help(
+                    "An EIP-3076 slashing protection interchange file exported by the client \
+                    the keystores are being migrated from. It is imported into the slashing \
+                    protection database before any keystore is imported.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(WEB3SIGNER_URL_FLAG)
+                .long(WEB3SIGNER_URL_FLAG)
+                .value_name("URL")
+                .help(
+                    "Instead of importing keystores, define the validators given by --pubkeys \
+                    as held by the Web3Signer at this URL. The validator client sends the \
+                    signer each message to sign, after checking it for slashing.",
+                )
+                .requires(PUBKEYS_FLAG)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(PUBKEYS_FLAG)
+                .long(PUBKEYS_FLAG)
+                .value_name("PUBKEYS")
+                .help(
+                    "A comma-separated list of the 0x-prefixed public keys of the validators \
+                    held by the --web3signer-url signer.",
+                )
+                .requires(WEB3SIGNER_URL_FLAG)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(WEB3SIGNER_ROOT_CERTIFICATE_FLAG)
+                .long(WEB3SIGNER_ROOT_CERTIFICATE_FLAG)
+                .value_name("PEM_FILE")
+                .help(
+                    "A PEM certificate to trust as the root of the TLS certificate of the \
+                    --web3signer-url signer, in addition to the system roots.",
+                )
+                .requires(WEB3SIGNER_URL_FLAG)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(WEB3SIGNER_CLIENT_IDENTITY_FLAG)
+                .long(WEB3SIGNER_CLIENT_IDENTITY_FLAG)
+                .value_name("PKCS12_FILE")
+                .help(
+                    "A PKCS#12 archive holding the TLS certificate and key that the validator \
+                    client presents to the --web3signer-url signer.",
+                )
+                .requires(WEB3SIGNER_URL_FLAG)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(WEB3SIGNER_CLIENT_IDENTITY_PASSWORD_FILE_FLAG)
+                .long(WEB3SIGNER_CLIENT_IDENTITY_PASSWORD_FILE_FLAG)
+                .value_name("PASSWORD_FILE_PATH")
+                .help(
+                    "A file containing the password of the --web3signer-client-identity \
+                    archive. The password is stored as plain-text in the \
+                    validator_definitions.yml file.",
+                )
+                .requires(WEB3SIGNER_CLIENT_IDENTITY_FLAG)
+                .takes_value(true),
         )
 }
 
-pub fn cli_run(matches: &ArgMatches) -> Result<(), String> {
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
     let keystore: Option<PathBuf> = clap_utils::parse_optional(matches, KEYSTORE_FLAG)?;
     let keystores_dir: Option<PathBuf> = clap_utils::parse_optional(matches, DIR_FLAG)?;
     let validator_dir = clap_utils::parse_path_with_default_in_home_dir(
@@ -78,24 +207,69 @@ pub fn cli_run(matches: &ArgMatches) -> Result<(), String> {
         VALIDATOR_DIR_FLAG,
         PathBuf::new().join(".lighthouse").join("validators"),
     )?;
-    let stdin_password = matches.is_present(STDIN_PASSWORD_FLAG);
+    let stdin_inputs = matches.is_present(STDIN_INPUTS_FLAG);
+    let reuse_password = matches.is_present(REUSE_PASSWORD_FLAG);
+    let password_file: Option<PathBuf> = clap_utils::parse_optional(matches, PASSWORD_FILE_FLAG)?;
+    let no_password = matches.is_present(NO_PASSWORD_FLAG);
+    let slashing_protection_file: Option<PathBuf> =
+        clap_utils::parse_optional(matches, SLASHING_PROTECTION_FLAG)?;
 
-    ensure_dir_exists(&validator_dir)?;
+    let dry_run = matches.is_present(DRY_RUN_FLAG);
 
-    let mut defs = ValidatorDefinitions::open_or_create(&validator_dir)
-        .map_err(|e| format!("Unable to open {}: {:?}", CONFIG_FILENAME, e))?;
+    // Read the interchange file first, so that an invalid file does not result in keystores
+    // being imported without their slashing protection.
+    let interchange = match &slashing_protection_file {
+        Some(path) => {
+            let path_str = path
+                .to_str()
+                .ok_or_else(|| format!("Badly formatted path: {:?}", path))?;
+            Some((
+                read_interchange(Some(path_str))?,
+                genesis_validators_root(&env)?,
+            ))
+        }
+        None => None,
+    };
+
+    let mut defs = if dry_run {
+        // Avoid creating any files during a dry run.
+        if validator_dir.join(CONFIG_FILENAME).exists() {
+            ValidatorDefinitions::open(&validator_dir)
+        } else {
+            Ok(ValidatorDefinitions::default())
+        }
+    } else {
+        ensure_dir_exists(&validator_dir)?;
+        ValidatorDefinitions::open_or_create(&validator_dir)
+    }
+    .map_err(|e| format!("Unable to open {}: {:?}", CONFIG_FILENAME, e))?;
+
+    if let Some(url) = matches.value_of(WEB3SIGNER_URL_FLAG) {
+        return import_web3signer_validators(
+            matches,
+            url,
+            defs,
+            &validator_dir,
+            interchange.as_ref(),
+            dry_run,
+        );
+    }
 
     // Collect the paths for the keystores that should be imported.
     let keystore_paths = match (keystore, keystores_dir) {
-        (Some(keystore), None) => vec![keystore],
+        (Some(keystore), None) => vec![FoundKeystore {
+            keystore_path: keystore,
+            password_path: None,
+        }],
         (None, Some(keystores_dir)) => {
             let mut keystores = vec![];
 
-            recursively_find_voting_keystores(&keystores_dir, &mut keystores)
+            recursively_find_keystores_with_passwords(&keystores_dir, &mut keystores)
                 .map_err(|e| format!("Unable to search {:?}: {:?}", keystores_dir, e))?;
 
             if keystores.is_empty() {
                 eprintln!("No keystores found in {:?}", keystores_dir);
+                print_prysm_wallet_hint(&keystores_dir);
                 return Ok(());
             }
 
@@ -109,57 +283,52 @@ pub fn cli_run(matches: &ArgMatches) -> Result<(), String> {
         }
     };
 
-    eprintln!("WARNING: {}", KEYSTORE_REUSE_WARNING);
-
-    // For each keystore:
-    //
-    // - Obtain the keystore password, if the user desires.
-    // - Copy the keystore into the `validator_dir`.
-    // - Add the keystore to the validator definitions file.
-    //
-    // Skip keystores that already exist, but exit early if any operation fails.
-    let mut num_imported_keystores = 0;
-    for src_keystore in &keystore_paths {
-        let keystore = Keystore::from_json_file(src_keystore)
-            .map_err(|e| format!("Unable to read keystore JSON {:?}: {:?}", src_keystore, e))?;
-
-        eprintln!("");
-        eprintln!("Keystore found at {:?}:", src_keystore);
-        eprintln!("");
-        eprintln!(" - Public key: 0x{}", keystore.pubkey());
-        eprintln!(" - UUID: {}", keystore.uuid());
-        eprintln!("");
-        eprintln!(
-            "If you enter the password it will be stored as plain-text in {} so that it is not \
-             required each time the validator client starts.",
-            CONFIG_FILENAME
-        );
+    // Read and validate every keystore before importing any of them, so that a bad keystore does
+    // not result in a partial import.
+    let keystores = keystore_paths
+        .par_iter()
+        .map(|found| {
+            let src_keystore = &found.keystore_path;
+            let keystore = Keystore::from_json_file(src_keystore)
+                .map_err(|e| format!("Unable to read keystore JSON {:?}: {:?}", src_keystore, e))?;
+            keystore
+                .validate()
+                .map_err(|e| format!("Invalid keystore {:?}: {:?}", src_keystore, e))?;
+            Ok((found, keystore))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
 
-        let password_opt = loop {
-            eprintln!("");
-            eprintln!("{}", PASSWORD_PROMPT);
+    // Map each public key that is already defined to a description of how it signs, so that the
+    // same validator is never added twice (even if its keystore is stored under a different path).
+    let mut existing_keystores = defs
+        .as_slice()
+        .iter()
+        .map(|def| {
+            (
+                def.voting_public_key.clone(),
+                describe_signing_definition(&def.signing_definition),
+            )
+        })
+        .collect::<HashMap<_, _>>();
 
-            let password = read_password_from_user(stdin_password)?;
+    // Determine which keystores should be imported, and where they should be copied to.
+    let mut imports = vec![];
+    let mut password_paths = vec![];
+    for (found, keystore) in keystores {
+        let src_keystore = &found.keystore_path;
+        let voting_public_key = keystore
+            .public_key()
+            .ok_or_else(|| format!("Invalid public key in keystore {:?}", src_keystore))?;
 
-            if password.as_ref().is_empty() {
-                eprintln!("Continuing without password.");
-                sleep(Duration::from_secs(1)); // Provides nicer UX.
-                break None;
-            }
-
-            match keystore.decrypt_keypair(password.as_ref()) {
-                Ok(_) => {
-                    eprintln!("Password is correct.");
-                    eprintln!("");
-                    sleep(Duration::from_secs(1)); // Provides nicer UX.
-                    break Some(password);
-                }
-                Err(eth2_keystore::Error::InvalidPassword) => {
-                    eprintln!("Invalid password");
-                }
-                Err(e) => return Err(format!("Error whilst decrypting keypair: {:?}", e)),
-            }
-        };
+        if let Some(existing) = existing_keystores.get(&voting_public_key) {
+            eprintln!(
+                "Skipping import of keystore {:?}, public key 0x{} is already defined by {}",
+                src_keystore,
+                keystore.pubkey(),
+                existing
+            );
+            continue;
+        }
 
         // The keystore is placed in a directory that matches the name of the public key. This
         // provides some loose protection against adding the same keystore twice.
@@ -172,9 +341,6 @@ pub fn cli_run(matches: &ArgMatches) -> Result<(), String> {
             continue;
         }
 
-        fs::create_dir_all(&dest_dir)
-            .map_err(|e| format!("Unable to create import directory: {:?}", e))?;
-
         // Retain the keystore file name, but place it in the new directory.
         let dest_keystore = src_keystore
             .file_name()
@@ -182,25 +348,218 @@ pub fn cli_run(matches: &ArgMatches) -> Result<(), String> {
             .map(|file_name_str| dest_dir.join(file_name_str))
             .ok_or_else(|| format!("Badly formatted file name: {:?}", src_keystore))?;
 
-        // Copy the keystore to the new location.
-        fs::copy(&src_keystore, &dest_keystore)
-            .map_err(|e| format!("Unable to copy keystore: {:?}", e))?;
+        existing_keystores.insert(
+            voting_public_key,
+            format!("the keystore {:?}", dest_keystore),
+        );
+        imports.push((src_keystore, keystore, dest_dir, dest_keystore));
+        password_paths.push(found.password_path.as_ref());
+    }
 
-        eprintln!("Successfully imported keystore.");
-        num_imported_keystores += 1;
+    if let Some((interchange, _)) = &interchange {
+        let protected = interchange
+            .data
+            .iter()
+            .map(|data| &data.pubkey)
+            .collect::<HashSet<_>>();
+        for (_, keystore, _, _) in &imports {
+            if keystore
+                .public_key()
+                .map_or(true, |pubkey| !protected.contains(&pubkey))
+            {
+                eprintln!(
+                    "WARNING: The slashing protection file has no data for validator 0x{}",
+                    keystore.pubkey()
+                );
+            }
+        }
+    }
 
-        let validator_def =
-            ValidatorDefinition::new_keystore_with_password(&dest_keystore, password_opt)
-                .map_err(|e| format!("Unable to create new validator definition: {:?}", e))?;
+    // Read and check the password file before importing anything, so that an incorrect password
+    // does not result in a partial import.
+    let file_password = match password_file {
+        Some(path) => {
+            let password = read_password_file(&path)?;
 
-        defs.push(validator_def);
+            // Decryption is deliberately slow, so check the keystores in parallel.
+            imports
+                .par_iter()
+                .try_for_each(|(src_keystore, keystore, _, _)| {
+                    keystore
+                        .decrypt_keypair(password.as_ref())
+                        .map(|_| ())
+                        .map_err(|e| {
+                            format!(
+                                "Unable to decrypt {:?} with the password in {:?}: {:?}",
+                                src_keystore, path, e
+                            )
+                        })
+                })?;
+
+            Some(password)
+        }
+        None => None,
+    };
+
+    // Use the password stored alongside each keystore by the client that created it, unless
+    // the user has decided the passwords some other way.
+    let stored_passwords = if no_password || file_password.is_some() {
+        vec![None; imports.len()]
+    } else {
+        imports
+            .par_iter()
+            .zip(password_paths.par_iter())
+            .map(|((src_keystore, keystore, _, _), password_path)| {
+                let path = (*password_path)?;
+                match read_password_file(path) {
+                    Ok(password) if keystore.decrypt_keypair(password.as_ref()).is_ok() => {
+                        eprintln!(
+                            "Found the password for keystore {:?} in {:?}",
+                            src_keystore, path
+                        );
+                        Some(password)
+                    }
+                    Ok(_) => {
+                        eprintln!(
+                            "The password in {:?} does not decrypt keystore {:?}",
+                            path, src_keystore
+                        );
+                        None
+                    }
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        None
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    if dry_run {
+        print_dry_run(&imports, keystore_paths.len());
+        if let Some((interchange, _)) = &interchange {
+            eprintln!(
+                "Would import slashing protection data for {} validators.",
+                interchange.data.len()
+            );
+        }
+        return Ok(());
+    }
+
+    eprintln!("WARNING: {}", KEYSTORE_REUSE_WARNING);
+
+    // Obtain the password for each keystore, if the user desires, before modifying anything. An
+    // entry is `None` until the password for that keystore has been decided.
+    let mut passwords: Vec<Option<Option<ZeroizeString>>> = if no_password {
+        vec![Some(None); imports.len()]
+    } else if let Some(password) = &file_password {
+        // The password has already been checked against every keystore.
+        vec![Some(Some(password.clone())); imports.len()]
+    } else {
+        stored_passwords
+            .into_iter()
+            .map(|password| password.map(Some))
+            .collect()
+    };
+
+    for (i, (src_keystore, keystore, _, _)) in imports.iter().enumerate() {
+        if passwords[i].is_some() {
+            continue;
+        }
+
+        eprintln!("");
+        eprintln!("Keystore found at {:?}:", src_keystore);
+        eprintln!("");
+        eprintln!(" - Public key: 0x{}", keystore.pubkey());
+        eprintln!(" - UUID: {}", keystore.uuid());
+        eprintln!("");
+        eprintln!(
+            "If you enter the password it will be stored as plain-text in {} so that it is \
+             not required each time the validator client starts.",
+            CONFIG_FILENAME
+        );
+
+        let password_opt = prompt_for_password(keystore, stdin_inputs)?;
+
+        // Try the new password on all of the remaining keystores at once, rather than decrypting
+        // each of them in turn as they are reached.
+        if reuse_password {
+            if let Some(password) = &password_opt {
+                let reused = imports[i + 1..]
+                    .par_iter()
+                    .zip(passwords[i + 1..].par_iter_mut())
+                    .filter(|(_, entry)| entry.is_none())
+                    .filter(|((_, keystore, _, _), _)| {
+                        keystore.decrypt_keypair(password.as_ref()).is_ok()
+                    })
+                    .map(|(_, entry)| *entry = Some(Some(password.clone())))
+                    .count();
+                if reused > 0 {
+                    eprintln!("Reusing the password for {} other keystores.", reused);
+                }
+            }
+        }
 
-        defs.save(&validator_dir)
-            .map_err(|e| format!("Unable to save {}: {:?}", CONFIG_FILENAME, e))?;
+        passwords[i] = Some(password_opt);
+    }
 
-        eprintln!("Successfully updated {}.", CONFIG_FILENAME);
+    // Import the slashing protection data before any keystore, so that a validator is never
+    // defined without the protection it had with its previous client.
+    if let Some((interchange, genesis_validators_root)) = &interchange {
+        import_interchange(
+            interchange,
+            *genesis_validators_root,
+            &validator_dir.join(SLASHING_PROTECTION_FILENAME),
+            false,
+        )?;
     }
 
+    // Copy each keystore into the `validator_dir`, then add all of them to the validator
+    // definitions file with a single save. If any copy fails, the keystores copied so far are
+    // removed so that nothing is left half-imported.
+    let mut new_defs = Vec::with_capacity(imports.len());
+    for ((src_keystore, _, dest_dir, dest_keystore), password_opt) in imports.iter().zip(passwords)
+    {
+        let result = fs::create_dir_all(&dest_dir)
+            .map_err(|e| format!("Unable to create import directory: {:?}", e))
+            .and_then(|_| {
+                fs::copy(src_keystore, dest_keystore)
+                    .map_err(|e| format!("Unable to copy keystore: {:?}", e))
+            })
+            .and_then(|_| {
+                ValidatorDefinition::new_keystore_with_password(
+                    dest_keystore,
+                    password_opt.flatten(),
+                )
+                .map_err(|e| format!("Unable to create new validator definition: {:?}", e))
+            });
+
+        match result {
+            Ok(validator_def) => new_defs.push(validator_def),
+            Err(e) => {
+                remove_imported_dirs(
+                    imports
+                        .iter()
+                        .map(|(_, _, dest_dir, _)| dest_dir)
+                        .take(new_defs.len() + 1),
+                );
+                return Err(e);
+            }
+        }
+    }
+
+    let num_imported_keystores = new_defs.len();
+    for validator_def in new_defs {
+        defs.push(validator_def);
+    }
+
+    if let Err(e) = defs.save(&validator_dir) {
+        remove_imported_dirs(imports.iter().map(|(_, _, dest_dir, _)| dest_dir));
+        return Err(format!("Unable to save {}: {:?}", CONFIG_FILENAME, e));
+    }
+
+    eprintln!("Successfully updated {}.", CONFIG_FILENAME);
+
     eprintln!("");
     eprintln!(
         "Successfully imported {} validators ({} skipped).",
@@ -212,3 +571,260 @@ pub fn cli_run(matches: &ArgMatches) -> Result<(), String> {
 
     Ok(())
 }
+
+/// Define each validator given by `--pubkeys` as held by the Web3Signer at `url`.
+///
+/// No keystores are read or copied, and no passwords are requested.
+fn import_web3signer_validators(
+    matches: &ArgMatches,
+    url: &str,
+    mut defs: ValidatorDefinitions,
+    validator_dir: &Path,
+    interchange: Option<&(Interchange, Hash256)>,
+    dry_run: bool,
+) -> Result<(), String> {
+    let pubkeys =
+        parse_pubkeys(matches)?.ok_or_else(|| format!("--{} is required", PUBKEYS_FLAG))?;
+    let root_certificate_path: Option<PathBuf> =
+        clap_utils::parse_optional(matches, WEB3SIGNER_ROOT_CERTIFICATE_FLAG)?;
+    let client_identity_path: Option<PathBuf> =
+        clap_utils::parse_optional(matches, WEB3SIGNER_CLIENT_IDENTITY_FLAG)?;
+    let client_identity_password_path: Option<PathBuf> =
+        clap_utils::parse_optional(matches, WEB3SIGNER_CLIENT_IDENTITY_PASSWORD_FILE_FLAG)?;
+    let client_identity_password = client_identity_password_path
+        .map(|path| read_password_file(&path))
+        .transpose()?;
+
+    // The files are only read by the validator client, so check they exist now rather than
+    // leaving the validator client unable to start.
+    for path in root_certificate_path.iter().chain(&client_identity_path) {
+        if !path.exists() {
+            return Err(format!("{:?} does not exist", path));
+        }
+    }
+
+    let existing = defs
+        .as_slice()
+        .iter()
+        .map(|def| {
+            (
+                def.voting_public_key.clone(),
+                describe_signing_definition(&def.signing_definition),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut new_defs: Vec<ValidatorDefinition> = vec![];
+    for voting_public_key in &pubkeys {
+        if let Some(existing) = existing.get(voting_public_key) {
+            eprintln!(
+                "Skipping validator {:?}, it is already defined by {}",
+                voting_public_key, existing
+            );
+            continue;
+        }
+        if new_defs
+            .iter()
+            .any(|def| def.voting_public_key == *voting_public_key)
+        {
+            continue;
+        }
+
+        new_defs.push(ValidatorDefinition {
+            enabled: true,
+            enable_at: None,
+            disable_at: None,
+            graffiti: None,
+            suggested_fee_recipient: None,
+            voting_public_key: voting_public_key.clone(),
+            signing_definition: SigningDefinition::Web3Signer {
+                url: url.to_string(),
+                root_certificate_path: root_certificate_path.clone(),
+                client_identity_path: client_identity_path.clone(),
+                client_identity_password: client_identity_password.clone(),
+            },
+        });
+    }
+
+    if let Some((interchange, _)) = interchange {
+        let protected = interchange
+            .data
+            .iter()
+            .map(|data| &data.pubkey)
+            .collect::<HashSet<_>>();
+        for def in &new_defs {
+            if !protected.contains(&def.voting_public_key) {
+                eprintln!(
+                    "WARNING: The slashing protection file has no data for validator {:?}",
+                    def.voting_public_key
+                );
+            }
+        }
+    }
+
+    if dry_run {
+        for def in &new_defs {
+            eprintln!(
+                "Would define validator {:?}, signed by {}",
+                def.voting_public_key, url
+            );
+        }
+        eprintln!(
+            "Would import {} validators ({} skipped).",
+            new_defs.len(),
+            pubkeys.len() - new_defs.len()
+        );
+        return Ok(());
+    }
+
+    if let Some((interchange, genesis_validators_root)) = interchange {
+        import_interchange(
+            interchange,
+            *genesis_validators_root,
+            &validator_dir.join(SLASHING_PROTECTION_FILENAME),
+            false,
+        )?;
+    }
+
+    let num_imported = new_defs.len();
+    for def in new_defs {
+        defs.push(def);
+    }
+    defs.save(validator_dir)
+        .map_err(|e| format!("Unable to save {}: {:?}", CONFIG_FILENAME, e))?;
+
+    eprintln!("Successfully updated {}.", CONFIG_FILENAME);
+    eprintln!("");
+    eprintln!(
+        "Successfully imported {} validators ({} skipped).",
+        num_imported,
+        pubkeys.len() - num_imported
+    );
+
+    Ok(())
+}
+
+/// A short description of how a validator signs, for messages about duplicate validators.
+fn describe_signing_definition(signing_definition: &SigningDefinition) -> String {
+    match signing_definition {
+        SigningDefinition::LocalKeystore {
+            voting_keystore_path,
+            ..
+        } => format!("the keystore {:?}", voting_keystore_path),
+        SigningDefinition::ThresholdShare {
+            voting_keystore_path,
+            share_index,
+            ..
+        } => format!(
+            "share {} in the keystore {:?}",
+            share_index, voting_keystore_path
+        ),
+        SigningDefinition::Web3Signer { url, .. } => format!("the Web3Signer at {}", url),
+    }
+}
+
+/// Prompt the user for the password of `keystore` until they enter the correct one, or an empty
+/// password to omit it.
+///
+/// When reading from stdin, an incorrect password is an error since prompting again would consume
+/// the input intended for the next keystore.
+fn prompt_for_password(
+    keystore: &Keystore,
+    stdin_inputs: bool,
+) -> Result<Option<ZeroizeString>, String> {
+    loop {
+        eprintln!("");
+        eprintln!("{}", PASSWORD_PROMPT);
+
+        let password = read_password_from_user(stdin_inputs)?;
+
+        if password.as_ref().is_empty() {
+            eprintln!("Continuing without password.");
+            if !stdin_inputs {
+                sleep(Duration::from_secs(1)); // Provides nicer UX.
+            }
+            return Ok(None);
+        }
+
+        match keystore.decrypt_keypair(password.as_ref()) {
+            Ok(_) => {
+                eprintln!("Password is correct.");
+                eprintln!("");
+                if !stdin_inputs {
+                    sleep(Duration::from_secs(1)); // Provides nicer UX.
+                }
+                return Ok(Some(password));
+            }
+            Err(eth2_keystore::Error::InvalidPassword) => {
+                eprintln!("Invalid password");
+                if stdin_inputs {
+                    return Err("Invalid password read from stdin".to_string());
+                }
+            }
+            Err(e) => return Err(format!("Error whilst decrypting keypair: {:?}", e)),
+        }
+    }
+}
+
+/// Read a password from the file at `path`, ignoring any trailing newlines.
+pub fn read_password_file(path: &Path) -> Result<ZeroizeString, String> {
+    fs::read(path)
+        .map(strip_off_newlines)
+        .map_err(|e| format!("Unable to read {:?}: {:?}", path, e))
+        .and_then(|bytes| {
+            String::from_utf8(bytes)
+                .map_err(|_| format!("Password in {:?} is not valid UTF-8", path))
+        })
+        .map(ZeroizeString::from)
+}
+
+/// Print instructions for importing from any Prysm wallet in `dir`, which cannot be imported
+/// directly.
+fn print_prysm_wallet_hint(dir: &Path) {
+    let mut wallets = vec![];
+    if recursively_find_prysm_wallets(dir, &mut wallets).is_err() {
+        return;
+    }
+
+    for wallet in wallets {
+        eprintln!(
+            "Found a Prysm wallet at {:?}, export its keystores with `prysm validator accounts \
+             backup` and import the backup directory instead.",
+            wallet
+        );
+    }
+}
+
+/// Remove the directories created for imported keystores, after a failed import.
+///
+/// Errors are printed rather than returned, since the import has already failed.
+fn remove_imported_dirs<'a>(dest_dirs: impl Iterator<Item = &'a PathBuf>) {
+    for dest_dir in dest_dirs {
+        if dest_dir.exists() {
+            if let Err(e) = fs::remove_dir_all(dest_dir) {
+                eprintln!("Unable to remove {:?}: {:?}", dest_dir, e);
+            }
+        }
+    }
+}
+
+/// Print the files that would be copied and the definitions that would be added by an import.
+fn print_dry_run(imports: &[(&PathBuf, Keystore, PathBuf, PathBuf)], num_keystores: usize) {
+    eprintln!("");
+    eprintln!("Dry run, no changes have been made.");
+
+    for (src_keystore, keystore, _dest_dir, dest_keystore) in imports {
+        eprintln!("");
+        eprintln!("Keystore found at {:?}:", src_keystore);
+        eprintln!(" - Public key: 0x{}", keystore.pubkey());
+        eprintln!(" - Would be copied to: {:?}", dest_keystore);
+        eprintln!(" - Would be added to {}", CONFIG_FILENAME);
+    }
+
+    eprintln!("");
+    eprintln!(
+        "Would import {} validators ({} skipped).",
+        imports.len(),
+        num_keystores - imports.len()
+    );
+}
@@ -1,9 +1,13 @@
 use crate::VALIDATOR_DIR_FLAG;
+use account_utils::validator_definitions::{
+    SigningDefinition, ValidatorDefinition, ValidatorDefinitions, CONFIG_FILENAME,
+};
 use clap::{App, Arg, ArgMatches};
+use serde_derive::Serialize;
 use std::path::PathBuf;
-use validator_dir::Manager as ValidatorManager;
 
 pub const CMD: &str = "list";
+pub const JSON_FLAG: &str = "json";
 
 pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
     App::new(CMD)
@@ -17,25 +21,158 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 )
                 .takes_value(true),
         )
-        .about("Lists the names of all validators.")
+        .arg(
+            Arg::with_name(JSON_FLAG)
+                .long(JSON_FLAG)
+                .help("If present, print the validators as a JSON array."),
+        )
+        .about(
+            "Lists the validators in the validator_definitions.yml file, which are the \
+            validators that the validator client will load.",
+        )
+}
+
+/// A validator definition, as printed by this command.
+///
+/// Passwords are never printed, only whether or not one is stored.
+#[derive(Serialize)]
+struct ValidatorListing {
+    voting_public_key: String,
+    enabled: bool,
+    voting_keystore_path: Option<PathBuf>,
+    voting_keystore_password_path: Option<PathBuf>,
+    password_stored: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    web3signer_url: Option<String>,
+    /// The index of the held share and the threshold, for a threshold validator.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    threshold_share: Option<(u64, u64)>,
+}
+
+impl From<&ValidatorDefinition> for ValidatorListing {
+    fn from(def: &ValidatorDefinition) -> Self {
+        match &def.signing_definition {
+            SigningDefinition::LocalKeystore {
+                voting_keystore_path,
+                voting_keystore_password_path,
+                voting_keystore_password,
+            } => Self {
+                voting_public_key: def.voting_public_key.to_hex_string(),
+                enabled: def.enabled,
+                voting_keystore_path: Some(voting_keystore_path.clone()),
+                voting_keystore_password_path: voting_keystore_password_path.clone(),
+                password_stored: voting_keystore_password.is_some()
+                    || voting_keystore_password_path.is_some(),
+                web3signer_url: None,
+                threshold_share: None,
+            },
+            SigningDefinition::ThresholdShare {
+                voting_keystore_path,
+                voting_keystore_password_path,
+                voting_keystore_password,
+                share_index,
+                threshold,
+                ..
+            } => Self {
+                voting_public_key: def.voting_public_key.to_hex_string(),
+                enabled: def.enabled,
+                voting_keystore_path: Some(voting_keystore_path.clone()),
+                voting_keystore_password_path: voting_keystore_password_path.clone(),
+                password_stored: voting_keystore_password.is_some()
+                    || voting_keystore_password_path.is_some(),
+                web3signer_url: None,
+                threshold_share: Some((*share_index, *threshold)),
+            },
+            SigningDefinition::Web3Signer { url, .. } => Self {
+                voting_public_key: def.voting_public_key.to_hex_string(),
+                enabled: def.enabled,
+                voting_keystore_path: None,
+                voting_keystore_password_path: None,
+                password_stored: false,
+                web3signer_url: Some(url.clone()),
+                threshold_share: None,
+            },
+        }
+    }
 }
 
 pub fn cli_run(matches: &ArgMatches<'_>) -> Result<(), String> {
-    let data_dir = clap_utils::parse_path_with_default_in_home_dir(
+    let validator_dir = clap_utils::parse_path_with_default_in_home_dir(
         matches,
         VALIDATOR_DIR_FLAG,
         PathBuf::new().join(".lighthouse").join("validators"),
     )?;
+    let json = matches.is_present(JSON_FLAG);
+
+    if !validator_dir.join(CONFIG_FILENAME).exists() {
+        return Err(format!(
+            "No {} found in {:?}",
+            CONFIG_FILENAME, validator_dir
+        ));
+    }
+
+    let defs = ValidatorDefinitions::open(&validator_dir)
+        .map_err(|e| format!("Unable to open {}: {:?}", CONFIG_FILENAME, e))?;
 
-    let mgr = ValidatorManager::open(&data_dir)
-        .map_err(|e| format!("Unable to read --{}: {:?}", VALIDATOR_DIR_FLAG, e))?;
+    let listings = defs
+        .as_slice()
+        .iter()
+        .map(ValidatorListing::from)
+        .collect::<Vec<_>>();
 
-    for (name, _path) in mgr
-        .directory_names()
-        .map_err(|e| format!("Unable to list wallets: {:?}", e))?
-    {
-        println!("{}", name)
+    if json {
+        let json = serde_json::to_string_pretty(&listings)
+            .map_err(|e| format!("Unable to encode validators as JSON: {:?}", e))?;
+        println!("{}", json);
+        return Ok(());
     }
 
+    for def in defs.as_slice() {
+        println!("{}", def.voting_public_key.to_hex_string());
+        println!(" - Enabled: {}", if def.enabled { "yes" } else { "no" });
+        match &def.signing_definition {
+            SigningDefinition::LocalKeystore {
+                voting_keystore_path,
+                voting_keystore_password_path,
+                voting_keystore_password,
+            }
+            | SigningDefinition::ThresholdShare {
+                voting_keystore_path,
+                voting_keystore_password_path,
+                voting_keystore_password,
+                ..
+            } => {
+                if let SigningDefinition::ThresholdShare {
+                    share_index,
+                    threshold,
+                    co_signers,
+                    ..
+                } = &def.signing_definition
+                {
+                    println!(
+                        " - Threshold share: {} of {}, {} required to sign",
+                        share_index,
+                        co_signers.len() + 1,
+                        threshold
+                    );
+                }
+                println!(" - Keystore: {:?}", voting_keystore_path);
+                // A password in the definitions file takes precedence over a password file.
+                match (voting_keystore_password, voting_keystore_password_path) {
+                    (Some(_), _) => println!(" - Password stored: yes, in {}", CONFIG_FILENAME),
+                    (None, Some(path)) => println!(" - Password stored: yes, in {:?}", path),
+                    (None, None) => println!(" - Password stored: no"),
+                }
+            }
+            SigningDefinition::Web3Signer { url, .. } => println!(" - Web3Signer: {}", url),
+        }
+    }
+
+    eprintln!(
+        "{} validators ({} enabled).",
+        listings.len(),
+        listings.iter().filter(|listing| listing.enabled).count()
+    );
+
     Ok(())
 }
@@ -1,7 +1,14 @@
 pub mod create;
+pub mod delete;
 pub mod deposit;
+pub mod disable;
+pub mod enable;
+pub mod exit;
 pub mod import;
 pub mod list;
+pub mod modify_password;
+pub mod slashing_protection;
+pub mod threshold;
 
 use crate::common::base_wallet_dir;
 use clap::{App, Arg, ArgMatches};
@@ -21,9 +28,16 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .takes_value(true),
         )
         .subcommand(create::cli_app())
+        .subcommand(delete::cli_app())
         .subcommand(deposit::cli_app())
+        .subcommand(disable::cli_app())
+        .subcommand(enable::cli_app())
+        .subcommand(exit::cli_app())
         .subcommand(import::cli_app())
         .subcommand(list::cli_app())
+        .subcommand(modify_password::cli_app())
+        .subcommand(slashing_protection::cli_app())
+        .subcommand(threshold::cli_app())
 }
 
 pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
@@ -31,9 +45,18 @@ pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<
 
     match matches.subcommand() {
         (create::CMD, Some(matches)) => create::cli_run::<T>(matches, env, base_wallet_dir),
+        (delete::CMD, Some(matches)) => delete::cli_run::<T>(matches, env),
         (deposit::CMD, Some(matches)) => deposit::cli_run::<T>(matches, env),
-        (import::CMD, Some(matches)) => import::cli_run(matches),
+        (disable::CMD, Some(matches)) => disable::cli_run(matches),
+        (enable::CMD, Some(matches)) => enable::cli_run(matches),
+        (exit::CMD, Some(matches)) => exit::cli_run::<T>(matches, env),
+        (import::CMD, Some(matches)) => import::cli_run::<T>(matches, env),
         (list::CMD, Some(matches)) => list::cli_run(matches),
+        (modify_password::CMD, Some(matches)) => modify_password::cli_run(matches),
+        (slashing_protection::CMD, Some(matches)) => {
+            slashing_protection::cli_run::<T>(matches, env)
+        }
+        (threshold::CMD, Some(matches)) => threshold::cli_run(matches),
         (unknown, _) => Err(format!(
             "{} does not have a {} command. See --help",
             CMD, unknown
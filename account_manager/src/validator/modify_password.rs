@@ -0,0 +1,232 @@
+use crate::validator::enable::{find_single, normalize};
+use crate::VALIDATOR_DIR_FLAG;
+use account_utils::{
+    create_with_600_perms,
+    eth2_keystore::{Keystore, KeystoreBuilder},
+    read_password, read_password_from_user,
+    validator_definitions::{SigningDefinition, ValidatorDefinitions, CONFIG_FILENAME},
+    PlainText, ZeroizeString,
+};
+use clap::{App, Arg, ArgMatches};
+use std::fs;
+use std::path::{Path, PathBuf};
+use types::Keypair;
+
+pub const CMD: &str = "modify-password";
+pub const PUBKEY_ARG: &str = "pubkey";
+pub const STDIN_INPUTS_FLAG: &str = "stdin-inputs";
+pub const STORE_PASSWORD_FLAG: &str = "store-password";
+
+pub const OLD_PASSWORD_PROMPT: &str = "Enter the current keystore password:";
+pub const NEW_PASSWORD_PROMPT: &str = "Enter the new keystore password:";
+pub const CONFIRM_PASSWORD_PROMPT: &str = "Enter the new keystore password again:";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Re-encrypts the keystore of a validator in the validator_definitions.yml file with \
+            a new password. If the old password is stored, the new password replaces it.",
+        )
+        .arg(
+            Arg::with_name(PUBKEY_ARG)
+                .value_name("PUBKEY")
+                .help(
+                    "The public key of the validator, or a prefix of it which matches a single \
+                    validator.",
+                )
+                .required(true),
+        )
+        .arg(
+            Arg::with_name(VALIDATOR_DIR_FLAG)
+                .long(VALIDATOR_DIR_FLAG)
+                .value_name("VALIDATOR_DIRECTORY")
+                .help(
+                    "The path where the validator directories are stored. \
+                    Defaults to ~/.lighthouse/validators",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(STDIN_INPUTS_FLAG)
+                .long(STDIN_INPUTS_FLAG)
+                .help("If present, read all user inputs from stdin instead of tty."),
+        )
+        .arg(
+            Arg::with_name(STORE_PASSWORD_FLAG)
+                .long(STORE_PASSWORD_FLAG)
+                .help(
+                    "If present and no password is stored for the validator, store the new \
+                    password in the validator_definitions.yml file.",
+                ),
+        )
+}
+
+pub fn cli_run(matches: &ArgMatches) -> Result<(), String> {
+    let validator_dir = clap_utils::parse_path_with_default_in_home_dir(
+        matches,
+        VALIDATOR_DIR_FLAG,
+        PathBuf::new().join(".lighthouse").join("validators"),
+    )?;
+    let pattern = normalize(&clap_utils::parse_required::<String>(matches, PUBKEY_ARG)?);
+    let stdin_inputs = matches.is_present(STDIN_INPUTS_FLAG);
+    let store_password = matches.is_present(STORE_PASSWORD_FLAG);
+
+    if !validator_dir.join(CONFIG_FILENAME).exists() {
+        return Err(format!(
+            "No {} found in {:?}",
+            CONFIG_FILENAME, validator_dir
+        ));
+    }
+
+    let mut defs = ValidatorDefinitions::open(&validator_dir)
+        .map_err(|e| format!("Unable to open {}: {:?}", CONFIG_FILENAME, e))?;
+
+    let index = find_single(&defs, &pattern)?;
+    let def = &mut defs.as_mut_slice()[index];
+
+    let (voting_keystore_path, voting_keystore_password_path, voting_keystore_password) =
+        match &mut def.signing_definition {
+            SigningDefinition::LocalKeystore {
+                voting_keystore_path,
+                voting_keystore_password_path,
+                voting_keystore_password,
+            }
+            | SigningDefinition::ThresholdShare {
+                voting_keystore_path,
+                voting_keystore_password_path,
+                voting_keystore_password,
+                ..
+            } => (
+                voting_keystore_path.clone(),
+                voting_keystore_password_path.clone(),
+                voting_keystore_password,
+            ),
+            SigningDefinition::Web3Signer { url, .. } => {
+                return Err(format!(
+                    "Validator {:?} has no keystore, it is signed by the Web3Signer at {}",
+                    def.voting_public_key, url
+                ))
+            }
+        };
+
+    let keystore = Keystore::from_json_file(&voting_keystore_path).map_err(|e| {
+        format!(
+            "Unable to read keystore {:?}: {:?}",
+            voting_keystore_path, e
+        )
+    })?;
+
+    let stored_password = match (&voting_keystore_password, &voting_keystore_password_path) {
+        (Some(password), _) => Some(PlainText::from(password.as_ref().to_vec())),
+        (None, Some(path)) => Some(
+            read_password(path)
+                .map_err(|e| format!("Unable to read password file {:?}: {:?}", path, e))?,
+        ),
+        (None, None) => None,
+    };
+
+    let keypair = match stored_password
+        .and_then(|password| keystore.decrypt_keypair(password.as_bytes()).ok())
+    {
+        Some(keypair) => keypair,
+        None => decrypt_keypair(&keystore, stdin_inputs)?,
+    };
+
+    let new_password = read_new_password(stdin_inputs)?;
+
+    let new_keystore = KeystoreBuilder::new(
+        &keypair,
+        new_password.as_ref(),
+        keystore.path().unwrap_or_default(),
+    )
+    .map_err(|e| format!("Unable to create keystore builder: {:?}", e))?
+    .uuid(*keystore.uuid())
+    .build()
+    .map_err(|e| format!("Unable to encrypt keystore: {:?}", e))?;
+    let new_keystore_json = new_keystore
+        .to_json_string()
+        .map_err(|e| format!("Unable to encode keystore: {:?}", e))?;
+
+    // Write each file to a temporary path before moving them into place, so that an error can't
+    // leave a truncated keystore or password file behind.
+    let keystore_temp_path = temp_path(&voting_keystore_path);
+    create_with_600_perms(&keystore_temp_path, new_keystore_json.as_bytes())
+        .map_err(|e| format!("Unable to write {:?}: {:?}", keystore_temp_path, e))?;
+
+    if let Some(password_path) = &voting_keystore_password_path {
+        let password_temp_path = temp_path(password_path);
+        create_with_600_perms(&password_temp_path, new_password.as_ref())
+            .map_err(|e| format!("Unable to write {:?}: {:?}", password_temp_path, e))?;
+        fs::rename(&password_temp_path, password_path)
+            .map_err(|e| format!("Unable to replace {:?}: {:?}", password_path, e))?;
+    }
+
+    fs::rename(&keystore_temp_path, &voting_keystore_path)
+        .map_err(|e| format!("Unable to replace {:?}: {:?}", voting_keystore_path, e))?;
+
+    // A password stored in the definitions file takes precedence over a password file, so it
+    // must always be replaced.
+    if voting_keystore_password.is_some()
+        || (voting_keystore_password_path.is_none() && store_password)
+    {
+        *voting_keystore_password = Some(new_password);
+        defs.save(&validator_dir)
+            .map_err(|e| format!("Unable to save {}: {:?}", CONFIG_FILENAME, e))?;
+    }
+
+    eprintln!("");
+    eprintln!(
+        "Changed the keystore password of validator 0x{}.",
+        keystore.pubkey()
+    );
+
+    Ok(())
+}
+
+/// Prompt for the current password of `keystore` until the correct one is entered.
+fn decrypt_keypair(keystore: &Keystore, stdin_inputs: bool) -> Result<Keypair, String> {
+    loop {
+        eprintln!("");
+        eprintln!("{}", OLD_PASSWORD_PROMPT);
+
+        let password = read_password_from_user(stdin_inputs)?;
+
+        match keystore.decrypt_keypair(password.as_ref()) {
+            Ok(keypair) => return Ok(keypair),
+            Err(account_utils::eth2_keystore::Error::InvalidPassword) => {
+                eprintln!("Invalid password");
+                if stdin_inputs {
+                    return Err("Invalid password read from stdin".to_string());
+                }
+            }
+            Err(e) => return Err(format!("Error whilst decrypting keypair: {:?}", e)),
+        }
+    }
+}
+
+/// Prompt for the new password twice, returning it if both entries match.
+fn read_new_password(stdin_inputs: bool) -> Result<ZeroizeString, String> {
+    eprintln!("");
+    eprintln!("{}", NEW_PASSWORD_PROMPT);
+    let password = read_password_from_user(stdin_inputs)?;
+
+    if password.as_ref().is_empty() {
+        return Err("The new password must not be empty".to_string());
+    }
+
+    eprintln!("{}", CONFIRM_PASSWORD_PROMPT);
+    let confirmation = read_password_from_user(stdin_inputs)?;
+
+    if password != confirmation {
+        return Err("The new passwords do not match".to_string());
+    }
+
+    Ok(password)
+}
+
+/// Returns the path used whilst replacing the file at `path`.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
@@ -0,0 +1,162 @@
+use super::{open_db_read_only, parse_pubkeys, pubkeys_arg};
+use clap::{App, ArgMatches};
+use slashing_protection::{Inconsistency, LowerBound, SignedAttestation};
+use std::fmt::Display;
+use std::path::Path;
+
+pub const CMD: &str = "check";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Checks the integrity and consistency of the slashing protection database and \
+            prints a summary of the signing history of each validator, followed by any \
+            inconsistencies found. Exits with an error if any are found.",
+        )
+        .arg(pubkeys_arg())
+}
+
+pub fn cli_run(matches: &ArgMatches, db_path: &Path) -> Result<(), String> {
+    let selected_pubkeys = parse_pubkeys(matches)?;
+
+    if !db_path.exists() {
+        return Err(format!(
+            "No slashing protection database exists at {:?}",
+            db_path
+        ));
+    }
+
+    let slashing_db = open_db_read_only(db_path)?;
+
+    slashing_db
+        .integrity_check()
+        .map_err(|e| format!("Database failed integrity check: {:?}", e))?;
+
+    let summaries = slashing_db
+        .validator_summaries(selected_pubkeys.as_deref())
+        .map_err(|e| format!("Unable to read validators: {:?}", e))?;
+
+    for summary in &summaries {
+        eprintln!("{:?}", summary.pubkey);
+        eprintln!(
+            "  blocks:       {} (max slot: {}, lower bound: {})",
+            summary.num_signed_blocks,
+            display_option(summary.max_block_slot),
+            display_option(summary.lower_bound.block_proposal_slot),
+        );
+        eprintln!(
+            "  attestations: {} (max source: {}, max target: {}, lower bound: {}/{})",
+            summary.num_signed_attestations,
+            display_option(summary.max_source_epoch),
+            display_option(summary.max_target_epoch),
+            display_option(summary.lower_bound.attestation_source_epoch),
+            display_option(summary.lower_bound.attestation_target_epoch),
+        );
+    }
+
+    let inconsistencies = slashing_db
+        .validate()
+        .map_err(|e| format!("Unable to validate database: {:?}", e))?;
+
+    if !inconsistencies.is_empty() {
+        for inconsistency in &inconsistencies {
+            eprintln!("Inconsistency: {}", describe(inconsistency));
+        }
+        return Err(format!(
+            "Database is inconsistent, {} problem(s) found. Do not use it to sign messages \
+             until it has been repaired or replaced.",
+            inconsistencies.len()
+        ));
+    }
+
+    eprintln!(
+        "Database integrity check passed, {} validator(s) checked",
+        summaries.len()
+    );
+
+    Ok(())
+}
+
+fn describe(inconsistency: &Inconsistency) -> String {
+    match inconsistency {
+        Inconsistency::OrphanedRecords {
+            table,
+            validator_id,
+            count,
+        } => format!(
+            "{} record(s) in {} belong to unknown validator ID {}",
+            count,
+            table,
+            display_option(*validator_id)
+        ),
+        Inconsistency::DuplicateValidator { pubkey } => {
+            format!("{:?} is registered more than once", pubkey)
+        }
+        Inconsistency::SourceExceedsTarget {
+            pubkey,
+            attestation,
+        } => format!(
+            "{:?} has an attestation with source greater than target: {}",
+            pubkey,
+            display_attestation(attestation)
+        ),
+        Inconsistency::SurroundingVote {
+            pubkey,
+            surrounding,
+            surrounded,
+        } => format!(
+            "{:?} has an attestation {} surrounding another {}",
+            pubkey,
+            display_attestation(surrounding),
+            display_attestation(surrounded)
+        ),
+        Inconsistency::InvalidLowerBound {
+            pubkey,
+            lower_bound,
+        } => format!(
+            "{:?} has a lower bound with source greater than target: {}",
+            pubkey,
+            display_lower_bound(lower_bound)
+        ),
+        Inconsistency::BlockAboveLowerBound {
+            pubkey,
+            block,
+            lower_bound,
+        } => format!(
+            "{:?} has a block at slot {} with an unknown signing root above its lower bound {}",
+            pubkey,
+            block.slot,
+            display_lower_bound(lower_bound)
+        ),
+        Inconsistency::AttestationAboveLowerBound {
+            pubkey,
+            attestation,
+            lower_bound,
+        } => format!(
+            "{:?} has an attestation {} with an unknown signing root above its lower bound {}",
+            pubkey,
+            display_attestation(attestation),
+            display_lower_bound(lower_bound)
+        ),
+    }
+}
+
+fn display_attestation(attestation: &SignedAttestation) -> String {
+    format!(
+        "(source: {}, target: {})",
+        attestation.source_epoch, attestation.target_epoch
+    )
+}
+
+fn display_lower_bound(lower_bound: &LowerBound) -> String {
+    format!(
+        "(slot: {}, source: {}, target: {})",
+        display_option(lower_bound.block_proposal_slot),
+        display_option(lower_bound.attestation_source_epoch),
+        display_option(lower_bound.attestation_target_epoch)
+    )
+}
+
+fn display_option<T: Display>(value: Option<T>) -> String {
+    value.map_or_else(|| "none".to_string(), |v| v.to_string())
+}
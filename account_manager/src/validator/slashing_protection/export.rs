@@ -0,0 +1,59 @@
+use super::{open_db_read_only, parse_pubkeys, pubkeys_arg, STDIO_FILE};
+use clap::{App, Arg, ArgMatches};
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use types::Hash256;
+
+pub const CMD: &str = "export";
+pub const FILE_ARG: &str = "FILE";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Exports the contents of the slashing protection database as an EIP-3076 \
+            interchange file.",
+        )
+        .arg(
+            Arg::with_name(FILE_ARG)
+                .value_name("FILE")
+                .help("The file to write the interchange to. Writes to stdout if omitted or '-'.")
+                .takes_value(true)
+                .index(1),
+        )
+        .arg(pubkeys_arg())
+}
+
+pub fn cli_run(
+    matches: &ArgMatches,
+    genesis_validators_root: Hash256,
+    db_path: &Path,
+) -> Result<(), String> {
+    let selected_pubkeys = parse_pubkeys(matches)?;
+
+    if !db_path.exists() {
+        return Err(format!(
+            "No slashing protection database exists at {:?}",
+            db_path
+        ));
+    }
+
+    let interchange = open_db_read_only(db_path)?
+        .export_interchange_info(genesis_validators_root, selected_pubkeys.as_deref())
+        .map_err(|e| format!("Error during export: {:?}", e))?;
+
+    match matches.value_of(FILE_ARG) {
+        None | Some(STDIO_FILE) => interchange.write_to(io::stdout().lock()),
+        Some(path) => File::create(path)
+            .map_err(|e| format!("Unable to create {}: {:?}", path, e))
+            .map(|file| interchange.write_to(file))?,
+    }
+    .map_err(|e| format!("Unable to write interchange file: {:?}", e))?;
+
+    eprintln!(
+        "Successfully exported slashing protection data for {} validator(s)",
+        interchange.data.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use super::{open_db_for_writing, STDIO_FILE};
+use clap::{App, Arg, ArgMatches};
+use slashing_protection::{interchange::Interchange, InterchangeError, NotSafe, SlashingDatabase};
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use types::Hash256;
+
+pub const CMD: &str = "import";
+pub const FILE_ARG: &str = "FILE";
+pub const FORCE_NETWORK_CHANGE_FLAG: &str = "force-network-change";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Imports an EIP-3076 slashing protection interchange file into the slashing \
+            protection database, creating the database if it does not exist.",
+        )
+        .arg(
+            Arg::with_name(FILE_ARG)
+                .value_name("FILE")
+                .help("The interchange file to import. Reads from stdin if omitted or '-'.")
+                .takes_value(true)
+                .index(1),
+        )
+        .arg(
+            Arg::with_name(FORCE_NETWORK_CHANGE_FLAG)
+                .long(FORCE_NETWORK_CHANGE_FLAG)
+                .help(
+                    "Import into a database previously used on a different network, associating \
+                    it with the current network instead. DO NOT use this flag unless you \
+                    are certain the database contains no data from another network that you \
+                    still need.",
+                ),
+        )
+}
+
+pub fn cli_run(
+    matches: &ArgMatches,
+    genesis_validators_root: Hash256,
+    db_path: &Path,
+) -> Result<(), String> {
+    let interchange = read_interchange(matches.value_of(FILE_ARG))?;
+
+    import_interchange(
+        &interchange,
+        genesis_validators_root,
+        db_path,
+        matches.is_present(FORCE_NETWORK_CHANGE_FLAG),
+    )
+}
+
+/// Read an interchange file from `path`, or from stdin if `path` is omitted or '-'.
+pub fn read_interchange(path: Option<&str>) -> Result<Interchange, String> {
+    match path {
+        None | Some(STDIO_FILE) => Interchange::from_json_reader(io::stdin().lock()),
+        Some(path) => File::open(path)
+            .map_err(|e| format!("Unable to open {}: {:?}", path, e))
+            .map(Interchange::from_json_reader)?,
+    }
+    .map_err(|e| format!("Unable to parse interchange file: {:?}", e))
+}
+
+/// Import `interchange` into the slashing protection database at `db_path`, creating the
+/// database if it does not exist.
+///
+/// Returns an error if the data for any validator is rejected.
+pub fn import_interchange(
+    interchange: &Interchange,
+    genesis_validators_root: Hash256,
+    db_path: &Path,
+    force_network_change: bool,
+) -> Result<(), String> {
+    let slashing_db = if db_path.exists() {
+        open_db_for_writing(db_path)?
+    } else {
+        SlashingDatabase::create(db_path).map_err(|e| {
+            format!(
+                "Unable to create slashing protection database at {:?}: {:?}",
+                db_path, e
+            )
+        })?
+    };
+
+    if force_network_change {
+        slashing_db
+            .set_genesis_validators_root(genesis_validators_root)
+            .map_err(|e| format!("Unable to change the network of the database: {:?}", e))?;
+    }
+
+    let report = slashing_db
+        .import_interchange_info(interchange, genesis_validators_root)
+        .map_err(|e| match e {
+            InterchangeError::NotSafe(NotSafe::GenesisValidatorsRootMismatch {
+                database,
+                network,
+            }) => format!(
+                "The slashing protection database was used on a different network (genesis \
+                 validators root {:?}) to the one being imported ({:?}). If you are certain the \
+                 database holds no data you still need, use `slashing-protection {} --{}`.",
+                database, network, CMD, FORCE_NETWORK_CHANGE_FLAG
+            ),
+            e => format!("Error during import: {:?}", e),
+        })?;
+
+    for imported in &report.imported {
+        eprintln!(
+            "Imported {:?}: {} block(s), {} attestation(s), {} record(s) skipped as duplicates",
+            imported.pubkey, imported.num_blocks, imported.num_attestations, imported.num_skipped
+        );
+    }
+    for rejected in &report.rejected {
+        eprintln!(
+            "Rejected {:?}, none of its records were imported: {:?}",
+            rejected.pubkey, rejected.reason
+        );
+    }
+
+    if report.is_success() {
+        eprintln!(
+            "Successfully imported slashing protection data for {} validator(s)",
+            report.imported.len()
+        );
+        Ok(())
+    } else {
+        Err(format!(
+            "Slashing protection data for {} validator(s) was rejected, DO NOT start these \
+             validators until the conflict is resolved",
+            report.rejected.len()
+        ))
+    }
+}
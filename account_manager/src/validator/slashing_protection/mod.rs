@@ -0,0 +1,134 @@
+pub mod check;
+pub mod export;
+pub mod import;
+pub mod prune;
+
+use crate::VALIDATOR_DIR_FLAG;
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use slashing_protection::{SlashingDatabase, SLASHING_PROTECTION_FILENAME};
+use std::path::{Path, PathBuf};
+use types::{EthSpec, Hash256, PublicKey};
+
+pub const CMD: &str = "slashing-protection";
+pub const PUBKEYS_FLAG: &str = "pubkeys";
+/// The value of a file argument which refers to stdin or stdout.
+pub const STDIO_FILE: &str = "-";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about("Import, export and manage the slashing protection database.")
+        .arg(
+            Arg::with_name(VALIDATOR_DIR_FLAG)
+                .long(VALIDATOR_DIR_FLAG)
+                .value_name("VALIDATOR_DIRECTORY")
+                .help(
+                    "The path to the validator client data directory, which contains the \
+                    slashing protection database. Defaults to ~/.lighthouse/validators",
+                )
+                .takes_value(true)
+                .global(true),
+        )
+        .subcommand(check::cli_app())
+        .subcommand(export::cli_app())
+        .subcommand(import::cli_app())
+        .subcommand(prune::cli_app())
+}
+
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
+    let validator_dir = clap_utils::parse_path_with_default_in_home_dir(
+        matches,
+        VALIDATOR_DIR_FLAG,
+        PathBuf::new().join(".lighthouse").join("validators"),
+    )?;
+    let db_path = validator_dir.join(SLASHING_PROTECTION_FILENAME);
+
+    match matches.subcommand() {
+        (check::CMD, Some(matches)) => check::cli_run(matches, &db_path),
+        (export::CMD, Some(matches)) => {
+            export::cli_run(matches, genesis_validators_root(&env)?, &db_path)
+        }
+        (import::CMD, Some(matches)) => {
+            import::cli_run(matches, genesis_validators_root(&env)?, &db_path)
+        }
+        (prune::CMD, Some(matches)) => prune::cli_run::<T>(matches, &db_path),
+        (unknown, _) => Err(format!(
+            "{} does not have a {} command. See --help",
+            CMD, unknown
+        )),
+    }
+}
+
+/// An argument to select a subset of validators by public key.
+pub fn pubkeys_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(PUBKEYS_FLAG)
+        .long(PUBKEYS_FLAG)
+        .value_name("PUBKEYS")
+        .help(
+            "A comma-separated list of 0x-prefixed validator public keys. If omitted, all \
+            validators in the database are included.",
+        )
+        .takes_value(true)
+}
+
+/// Parse the optional list of public keys supplied via `--pubkeys`.
+pub fn parse_pubkeys(matches: &ArgMatches) -> Result<Option<Vec<PublicKey>>, String> {
+    matches
+        .value_of(PUBKEYS_FLAG)
+        .map(|list| {
+            list.split(',')
+                .map(|pubkey_str| {
+                    let pubkey_str = pubkey_str.trim();
+                    pubkey_str
+                        .strip_prefix("0x")
+                        .ok_or_else(|| format!("Public key must be 0x-prefixed: {}", pubkey_str))
+                        .and_then(|hex_str| {
+                            hex::decode(hex_str)
+                                .map_err(|e| format!("Invalid hex in {}: {:?}", pubkey_str, e))
+                        })
+                        .and_then(|bytes| {
+                            PublicKey::deserialize(&bytes)
+                                .map_err(|e| format!("Invalid public key {}: {:?}", pubkey_str, e))
+                        })
+                })
+                .collect()
+        })
+        .transpose()
+}
+
+/// Read the genesis validators root from the genesis state of the testnet in use.
+///
+/// Slashing protection data is only meaningful for a single network, so it is tagged with this
+/// root to prevent data from being imported into the wrong network.
+pub fn genesis_validators_root<T: EthSpec>(env: &Environment<T>) -> Result<Hash256, String> {
+    env.testnet
+        .as_ref()
+        .and_then(|testnet| testnet.genesis_state.as_ref())
+        .map(|state| state.genesis_validators_root)
+        .ok_or_else(|| {
+            "Unable to determine the genesis validators root, the testnet config must include a \
+             genesis state"
+                .to_string()
+        })
+}
+
+/// Open the slashing protection database at `db_path` for writing, failing if it is in use.
+pub fn open_db_for_writing(db_path: &Path) -> Result<SlashingDatabase, String> {
+    SlashingDatabase::open(db_path).map_err(|e| {
+        format!(
+            "Unable to open slashing protection database at {:?}, ensure the validator client \
+             is not running: {:?}",
+            db_path, e
+        )
+    })
+}
+
+/// Open the slashing protection database at `db_path` for reading only.
+pub fn open_db_read_only(db_path: &Path) -> Result<SlashingDatabase, String> {
+    SlashingDatabase::open_read_only(db_path).map_err(|e| {
+        format!(
+            "Unable to open slashing protection database at {:?}: {:?}",
+            db_path, e
+        )
+    })
+}
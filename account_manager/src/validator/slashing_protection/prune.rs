@@ -0,0 +1,56 @@
+use super::{open_db_for_writing, parse_pubkeys, pubkeys_arg};
+use clap::{App, Arg, ArgMatches};
+use std::path::Path;
+use types::{Epoch, EthSpec};
+
+pub const CMD: &str = "prune";
+pub const EPOCH_FLAG: &str = "epoch";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Deletes the blocks and attestations signed before the given epoch from the \
+            slashing protection database. The validator client must not be running. Pruned \
+            messages remain protected: nothing may be signed at or below the highest pruned \
+            slot or epoch.",
+        )
+        .arg(
+            Arg::with_name(EPOCH_FLAG)
+                .long(EPOCH_FLAG)
+                .value_name("EPOCH")
+                .help(
+                    "Delete blocks from slots before the start of this epoch, and attestations \
+                    with target epochs before this epoch.",
+                )
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(pubkeys_arg())
+}
+
+pub fn cli_run<T: EthSpec>(matches: &ArgMatches, db_path: &Path) -> Result<(), String> {
+    let prune_epoch = Epoch::new(clap_utils::parse_required(matches, EPOCH_FLAG)?);
+    let selected_pubkeys = parse_pubkeys(matches)?;
+
+    if !db_path.exists() {
+        return Err(format!(
+            "No slashing protection database exists at {:?}",
+            db_path
+        ));
+    }
+
+    open_db_for_writing(db_path)?
+        .prune(
+            selected_pubkeys.as_deref(),
+            prune_epoch.start_slot(T::slots_per_epoch()),
+            prune_epoch,
+        )
+        .map_err(|e| format!("Error during pruning: {:?}", e))?;
+
+    eprintln!(
+        "Successfully pruned slashing protection data before epoch {}",
+        prune_epoch
+    );
+
+    Ok(())
+}
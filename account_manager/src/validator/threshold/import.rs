@@ -0,0 +1,140 @@
+use super::{decrypt_keypair, STDIN_INPUTS_FLAG};
+use crate::common::ensure_dir_exists;
+use crate::validator::import::read_password_file;
+use account_utils::{
+    eth2_keystore::Keystore,
+    threshold::{ShareDefinition, SHARE_DEFINITION_FILE, SHARE_KEYSTORE_FILE, SHARE_PASSWORD_FILE},
+    validator_definitions::{
+        SigningDefinition, ValidatorDefinition, ValidatorDefinitions, CONFIG_FILENAME,
+    },
+};
+use clap::{App, Arg, ArgMatches};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CMD: &str = "import";
+pub const SHARE_DIR_FLAG: &str = "share-dir";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Imports a share created by the split command into the validator_definitions.yml \
+            file. The validator client cannot yet sign with a share, so the share is imported \
+            disabled and cannot be enabled: importing it does not run the validator.",
+        )
+        .arg(
+            Arg::with_name(SHARE_DIR_FLAG)
+                .long(SHARE_DIR_FLAG)
+                .value_name("SHARE_DIRECTORY")
+                .help("A share-<index> directory created by the split command.")
+                .required(true)
+                .takes_value(true),
+        )
+}
+
+pub fn cli_run(matches: &ArgMatches, validator_dir: &Path) -> Result<(), String> {
+    let share_dir: PathBuf = clap_utils::parse_required(matches, SHARE_DIR_FLAG)?;
+    let stdin_inputs = matches.is_present(STDIN_INPUTS_FLAG);
+
+    let definition_path = share_dir.join(SHARE_DEFINITION_FILE);
+    let share = ShareDefinition::open(&definition_path)
+        .map_err(|e| format!("Unable to read {:?}: {:?}", definition_path, e))?;
+
+    let src_keystore = share_dir.join(SHARE_KEYSTORE_FILE);
+    let keystore = Keystore::from_json_file(&src_keystore)
+        .map_err(|e| format!("Unable to read keystore JSON {:?}: {:?}", src_keystore, e))?;
+    if keystore.public_key().as_ref() != Some(&share.share_public_key) {
+        return Err(format!(
+            "The keystore {:?} does not hold share {} of {:?}",
+            src_keystore, share.share_index, definition_path
+        ));
+    }
+
+    // Use the password written by the split command, if it is still present.
+    let password_path = share_dir.join(SHARE_PASSWORD_FILE);
+    let stored_password = if password_path.exists() {
+        Some(read_password_file(&password_path)?)
+            .filter(|password| keystore.decrypt_keypair(password.as_ref()).is_ok())
+    } else {
+        None
+    };
+    let password = match stored_password {
+        Some(password) => password,
+        None => decrypt_keypair(&keystore, stdin_inputs)?.1,
+    };
+
+    ensure_dir_exists(validator_dir)?;
+    let mut defs = ValidatorDefinitions::open_or_create(validator_dir)
+        .map_err(|e| format!("Unable to open {}: {:?}", CONFIG_FILENAME, e))?;
+
+    if defs
+        .as_slice()
+        .iter()
+        .any(|def| def.voting_public_key == share.voting_public_key)
+    {
+        return Err(format!(
+            "Validator {:?} is already defined in {}",
+            share.voting_public_key, CONFIG_FILENAME
+        ));
+    }
+
+    // The keystore is placed in a directory named after the validator, not the share, so that
+    // two shares of the same validator cannot be imported.
+    let dest_dir = validator_dir.join(format!("{:?}", share.voting_public_key));
+    if dest_dir.exists() {
+        return Err(format!("{:?} already exists", dest_dir));
+    }
+    let dest_keystore = dest_dir.join(SHARE_KEYSTORE_FILE);
+
+    let result = fs::create_dir(&dest_dir)
+        .map_err(|e| format!("Unable to create {:?}: {:?}", dest_dir, e))
+        .and_then(|_| {
+            fs::copy(&src_keystore, &dest_keystore)
+                .map_err(|e| format!("Unable to copy keystore: {:?}", e))
+        })
+        .and_then(|_| {
+            defs.push(ValidatorDefinition {
+                enabled: false,
+                enable_at: None,
+                disable_at: None,
+                graffiti: None,
+                suggested_fee_recipient: None,
+                voting_public_key: share.voting_public_key.clone(),
+                signing_definition: SigningDefinition::ThresholdShare {
+                    voting_keystore_path: dest_keystore,
+                    voting_keystore_password_path: None,
+                    voting_keystore_password: Some(password),
+                    share_index: share.share_index,
+                    threshold: share.threshold,
+                    co_signers: share.co_signers.clone(),
+                },
+            });
+            defs.save(validator_dir)
+                .map_err(|e| format!("Unable to save {}: {:?}", CONFIG_FILENAME, e))
+        });
+
+    if let Err(e) = result {
+        if dest_dir.exists() {
+            if let Err(e) = fs::remove_dir_all(&dest_dir) {
+                eprintln!("Unable to remove {:?}: {:?}", dest_dir, e);
+            }
+        }
+        return Err(e);
+    }
+
+    eprintln!("Successfully updated {}.", CONFIG_FILENAME);
+    eprintln!("");
+    eprintln!(
+        "Imported share {} of validator {:?}, {} of {} shares are required to sign.",
+        share.share_index,
+        share.voting_public_key,
+        share.threshold,
+        share.co_signers.len() + 1
+    );
+    eprintln!(
+        "The share is disabled and cannot be enabled, since the validator client cannot yet sign \
+        with a share. Importing it does not run the validator."
+    );
+
+    Ok(())
+}
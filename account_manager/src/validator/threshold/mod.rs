@@ -0,0 +1,83 @@
+pub mod import;
+pub mod split;
+
+use crate::VALIDATOR_DIR_FLAG;
+use account_utils::{eth2_keystore::Keystore, read_password_from_user, ZeroizeString};
+use clap::{App, Arg, ArgMatches};
+use std::path::PathBuf;
+use types::Keypair;
+
+pub const CMD: &str = "threshold";
+pub const STDIN_INPUTS_FLAG: &str = "stdin-inputs";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "EXPERIMENTAL. Split validator keys into shares for threshold signing, and import \
+            a share. The validator client cannot yet sign with a share, so imported shares are \
+            disabled.",
+        )
+        .arg(
+            Arg::with_name(VALIDATOR_DIR_FLAG)
+                .long(VALIDATOR_DIR_FLAG)
+                .value_name("VALIDATOR_DIRECTORY")
+                .help(
+                    "The path where the validator directories are stored. \
+                    Defaults to ~/.lighthouse/validators",
+                )
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name(STDIN_INPUTS_FLAG)
+                .long(STDIN_INPUTS_FLAG)
+                .help("If present, read all user inputs from stdin instead of tty.")
+                .global(true),
+        )
+        .subcommand(import::cli_app())
+        .subcommand(split::cli_app())
+}
+
+pub fn cli_run(matches: &ArgMatches) -> Result<(), String> {
+    let validator_dir = clap_utils::parse_path_with_default_in_home_dir(
+        matches,
+        VALIDATOR_DIR_FLAG,
+        PathBuf::new().join(".lighthouse").join("validators"),
+    )?;
+
+    match matches.subcommand() {
+        (import::CMD, Some(matches)) => import::cli_run(matches, &validator_dir),
+        (split::CMD, Some(matches)) => split::cli_run(matches),
+        (unknown, _) => Err(format!(
+            "{} does not have a {} command. See --help",
+            CMD, unknown
+        )),
+    }
+}
+
+/// Prompt for the password of `keystore` until the correct one is entered.
+fn decrypt_keypair(
+    keystore: &Keystore,
+    stdin_inputs: bool,
+) -> Result<(Keypair, ZeroizeString), String> {
+    loop {
+        eprintln!("");
+        eprintln!(
+            "Enter the keystore password for public key 0x{}:",
+            keystore.pubkey()
+        );
+
+        let password = read_password_from_user(stdin_inputs)?;
+
+        match keystore.decrypt_keypair(password.as_ref()) {
+            Ok(keypair) => return Ok((keypair, password)),
+            Err(account_utils::eth2_keystore::Error::InvalidPassword) => {
+                eprintln!("Invalid password");
+                if stdin_inputs {
+                    return Err("Invalid password read from stdin".to_string());
+                }
+            }
+            Err(e) => return Err(format!("Error whilst decrypting keypair: {:?}", e)),
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use super::{decrypt_keypair, STDIN_INPUTS_FLAG};
+use account_utils::{
+    create_with_600_perms,
+    eth2_keystore::{Keystore, KeystoreBuilder},
+    random_password,
+    threshold::{
+        recover_secret_key, split_secret_key, ShareDefinition, SHARE_DEFINITION_FILE,
+        SHARE_KEYSTORE_FILE, SHARE_PASSWORD_FILE,
+    },
+};
+use clap::{App, Arg, ArgMatches};
+use std::fs;
+use std::path::PathBuf;
+
+pub const CMD: &str = "split";
+pub const KEYSTORE_FLAG: &str = "keystore";
+pub const THRESHOLD_FLAG: &str = "threshold";
+pub const SHARES_FLAG: &str = "shares";
+pub const OUTPUT_DIR_FLAG: &str = "output-dir";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Splits the key in a keystore into shares, any --threshold of which are required to \
+            sign. Each share is written to its own directory, with a new keystore, a random \
+            password and a description of the other shares.",
+        )
+        .arg(
+            Arg::with_name(KEYSTORE_FLAG)
+                .long(KEYSTORE_FLAG)
+                .value_name("KEYSTORE_PATH")
+                .help("Path to the EIP-2335 voting keystore of the validator to split.")
+                .required(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(THRESHOLD_FLAG)
+                .long(THRESHOLD_FLAG)
+                .value_name("THRESHOLD")
+                .help("The number of shares required to sign.")
+                .required(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(SHARES_FLAG)
+                .long(SHARES_FLAG)
+                .value_name("SHARES")
+                .help("The number of shares to create.")
+                .required(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(OUTPUT_DIR_FLAG)
+                .long(OUTPUT_DIR_FLAG)
+                .value_name("OUTPUT_DIRECTORY")
+                .help(
+                    "A new or empty directory, in which a share-<index> directory is created \
+                    for each share.",
+                )
+                .required(true)
+                .takes_value(true),
+        )
+}
+
+pub fn cli_run(matches: &ArgMatches) -> Result<(), String> {
+    let keystore_path: PathBuf = clap_utils::parse_required(matches, KEYSTORE_FLAG)?;
+    let threshold: u64 = clap_utils::parse_required(matches, THRESHOLD_FLAG)?;
+    let shares: u64 = clap_utils::parse_required(matches, SHARES_FLAG)?;
+    let output_dir: PathBuf = clap_utils::parse_required(matches, OUTPUT_DIR_FLAG)?;
+    let stdin_inputs = matches.is_present(STDIN_INPUTS_FLAG);
+
+    if output_dir.exists()
+        && fs::read_dir(&output_dir)
+            .map_err(|e| format!("Unable to read {:?}: {:?}", output_dir, e))?
+            .next()
+            .is_some()
+    {
+        return Err(format!("{:?} is not empty", output_dir));
+    }
+
+    let keystore = Keystore::from_json_file(&keystore_path)
+        .map_err(|e| format!("Unable to read keystore JSON {:?}: {:?}", keystore_path, e))?;
+    let (keypair, _) = decrypt_keypair(&keystore, stdin_inputs)?;
+
+    let key_shares = split_secret_key(&keypair.sk, threshold, shares)
+        .map_err(|e| format!("Unable to split key: {:?}", e))?;
+
+    // Check the shares before writing anything, since a mistake here would lose the validator.
+    let recovered = recover_secret_key(&key_shares[..threshold as usize])
+        .map_err(|e| format!("Unable to recover the key from its shares: {:?}", e))?;
+    if recovered.public_key() != keypair.pk {
+        return Err("The shares do not recover the original key".to_string());
+    }
+
+    fs::create_dir_all(&output_dir)
+        .map_err(|e| format!("Unable to create {:?}: {:?}", output_dir, e))?;
+
+    let definitions = ShareDefinition::for_shares(&keypair.pk, threshold, &key_shares);
+    for (share, definition) in key_shares.iter().zip(&definitions) {
+        let share_dir = output_dir.join(format!("share-{}", share.index));
+        fs::create_dir(&share_dir)
+            .map_err(|e| format!("Unable to create {:?}: {:?}", share_dir, e))?;
+
+        let password = random_password();
+        let share_keystore = KeystoreBuilder::new(&share.keypair, password.as_bytes(), "".into())
+            .map_err(|e| format!("Unable to create keystore builder: {:?}", e))?
+            .build()
+            .map_err(|e| format!("Unable to encrypt keystore: {:?}", e))?
+            .to_json_string()
+            .map_err(|e| format!("Unable to encode keystore: {:?}", e))?;
+
+        create_with_600_perms(
+            share_dir.join(SHARE_KEYSTORE_FILE),
+            share_keystore.as_bytes(),
+        )
+        .map_err(|e| format!("Unable to write keystore in {:?}: {:?}", share_dir, e))?;
+        create_with_600_perms(share_dir.join(SHARE_PASSWORD_FILE), password.as_bytes())
+            .map_err(|e| format!("Unable to write password in {:?}: {:?}", share_dir, e))?;
+        definition
+            .save(share_dir.join(SHARE_DEFINITION_FILE))
+            .map_err(|e| format!("Unable to write definition in {:?}: {:?}", share_dir, e))?;
+    }
+
+    eprintln!("");
+    eprintln!(
+        "Split validator {:?} into {} shares in {:?}, {} of which are required to sign.",
+        keypair.pk, shares, output_dir, threshold
+    );
+    eprintln!("");
+    eprintln!(
+        "Give each share-<index> directory to a different co-signer. Any {} of them can \
+         recover the original key, so keep the original keystore offline or destroy it.",
+        threshold
+    );
+
+    Ok(())
+}
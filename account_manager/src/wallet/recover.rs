@@ -0,0 +1,97 @@
+use crate::wallet::create::{
+    create_wallet_from_mnemonic, HD_TYPE, NAME_FLAG, PASSWORD_FLAG, TYPE_FLAG,
+};
+use clap::{App, Arg, ArgMatches};
+use eth2_wallet::bip39::{Language, Mnemonic};
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+
+pub const CMD: &str = "recover";
+pub const MNEMONIC_FLAG: &str = "mnemonic-path";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about("Recovers an EIP-2386 wallet from an existing BIP-39 mnemonic.")
+        .arg(
+            Arg::with_name(NAME_FLAG)
+                .long(NAME_FLAG)
+                .value_name("WALLET_NAME")
+                .help(
+                    "The wallet will be created with this name. It is not allowed to \
+                    create two wallets with the same name for the same --base-dir.",
+                )
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name(PASSWORD_FLAG)
+                .long(PASSWORD_FLAG)
+                .value_name("PASSWORD_FILE_PATH")
+                .help(
+                    "A path to a file containing the password which will unlock the wallet. \
+                    If the file does not exist, a random password will be generated and \
+                    saved at that path. To avoid confusion, if the file does not already \
+                    exist it must include a '.pass' suffix.",
+                )
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name(MNEMONIC_FLAG)
+                .long(MNEMONIC_FLAG)
+                .value_name("MNEMONIC_PATH")
+                .help(
+                    "If present, the mnemonic will be read from this file. Otherwise, it is \
+                    read from stdin.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(TYPE_FLAG)
+                .long(TYPE_FLAG)
+                .value_name("WALLET_TYPE")
+                .help(
+                    "The type of wallet to create. Only HD (hierarchical-deterministic) \
+                    wallets are supported presently..",
+                )
+                .takes_value(true)
+                .possible_values(&[HD_TYPE])
+                .default_value(HD_TYPE),
+        )
+}
+
+pub fn cli_run(matches: &ArgMatches, base_dir: PathBuf) -> Result<(), String> {
+    let mnemonic_path: Option<PathBuf> = clap_utils::parse_optional(matches, MNEMONIC_FLAG)?;
+
+    let phrase = match mnemonic_path {
+        Some(path) => {
+            fs::read_to_string(&path).map_err(|e| format!("Unable to read {:?}: {:?}", path, e))?
+        }
+        None => {
+            eprintln!("Enter the mnemonic phrase:");
+
+            let mut phrase = String::new();
+            io::stdin()
+                .lock()
+                .read_line(&mut phrase)
+                .map_err(|e| format!("Unable to read mnemonic from stdin: {:?}", e))?;
+            phrase
+        }
+    };
+
+    let mnemonic = Mnemonic::from_phrase(phrase.trim(), Language::English)
+        .map_err(|e| format!("Unable to parse mnemonic: {:?}", e))?;
+
+    let wallet = create_wallet_from_mnemonic(matches, &base_dir, &mnemonic)?;
+
+    println!("Your wallet has been successfully recovered.");
+    println!();
+    println!("Your wallet's UUID is:");
+    println!();
+    println!("\t{}", wallet.wallet().uuid());
+    println!();
+    println!("You do not need to backup your UUID or keep it secret.");
+
+    Ok(())
+}
@@ -37,6 +37,7 @@ use crate::{
     BeaconChain, BeaconChainError, BeaconChainTypes,
 };
 use bls::verify_signature_sets;
+use proto_array::Block as ProtoBlock;
 use slog::debug;
 use slot_clock::SlotClock;
 use state_processing::{
@@ -51,8 +52,8 @@ use state_processing::{
 use std::borrow::Cow;
 use tree_hash::TreeHash;
 use types::{
-    Attestation, BeaconCommittee, CommitteeIndex, Epoch, EthSpec, Hash256, IndexedAttestation,
-    RelativeEpoch, SelectionProof, SignedAggregateAndProof, Slot, SubnetId,
+    Attestation, BeaconCommittee, BeaconState, Checkpoint, CommitteeCache, Epoch, EthSpec, Hash256,
+    IndexedAttestation, RelativeEpoch, SelectionProof, SignedAggregateAndProof, Slot, SubnetId,
 };
 
 /// Returned when an attestation was not successfully verified. It might not have been verified for
@@ -164,7 +165,7 @@ pub enum Error {
     /// ## Peer scoring
     ///
     /// The peer has sent an invalid message.
-    NoCommitteeForSlotAndIndex { slot: Slot, index: CommitteeIndex },
+    NoCommitteeForSlotAndIndex { slot: Slot, index: u64 },
     /// The unaggregated attestation doesn't have only one aggregation bit set.
     ///
     /// ## Peer scoring
@@ -790,54 +791,17 @@ where
             "target_block_epoch" => target_block.slot.epoch(T::EthSpec::slots_per_epoch()).as_u64(),
         );
 
-        let state_read_timer =
-            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_STATE_READ_TIMES);
-
-        let mut state = chain
-            .store
-            .get_inconsistent_state_for_attestation_verification_only(
-                &target_block.state_root,
-                Some(target_block.slot),
-            )
-            .map_err(BeaconChainError::from)?
-            .ok_or_else(|| BeaconChainError::MissingBeaconState(target_block.state_root))?;
-
-        metrics::stop_timer(state_read_timer);
-        let state_skip_timer =
-            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_STATE_SKIP_TIMES);
-
-        while state.current_epoch() + 1 < attestation_epoch {
-            // Here we tell `per_slot_processing` to skip hashing the state and just
-            // use the zero hash instead.
-            //
-            // The state roots are not useful for the shuffling, so there's no need to
-            // compute them.
-            per_slot_processing(&mut state, Some(Hash256::zero()), &chain.spec)
-                .map_err(BeaconChainError::from)?;
-        }
-
-        metrics::stop_timer(state_skip_timer);
-        let committee_building_timer =
-            metrics::start_timer(&metrics::ATTESTATION_PROCESSING_COMMITTEE_BUILDING_TIMES);
-
-        let relative_epoch = RelativeEpoch::from_epoch(state.current_epoch(), attestation_epoch)
-            .map_err(BeaconChainError::IncorrectStateForAttestation)?;
-
-        state
-            .build_committee_cache(relative_epoch, &chain.spec)
-            .map_err(BeaconChainError::from)?;
-
-        let committee_cache = state
-            .committee_cache(relative_epoch)
-            .map_err(BeaconChainError::from)?;
+        let checkpoint = Checkpoint {
+            epoch: attestation_epoch,
+            root: target.root,
+        };
+        let committee_cache = checkpoint_committee_cache(chain, checkpoint, &target_block)?;
 
         chain
             .shuffling_cache
             .try_write_for(ATTESTATION_CACHE_LOCK_TIMEOUT)
             .ok_or_else(|| BeaconChainError::AttestationCacheLockTimeout)?
-            .insert(attestation_epoch, target.root, committee_cache);
-
-        metrics::stop_timer(committee_building_timer);
+            .insert(attestation_epoch, target.root, &committee_cache);
 
         let committees_per_slot = committee_cache.committees_per_slot();
         committee_cache
@@ -851,3 +815,79 @@ where
             })
     }
 }
+
+/// Returns the committee cache of `checkpoint.epoch`, computed from the state of the
+/// `target_block` (which has the root `checkpoint.root`).
+///
+/// The state is read from the `checkpoint_state_cache`, or otherwise read from disk and advanced
+/// to the epoch prior to `checkpoint.epoch`, before being added to the `checkpoint_state_cache`.
+fn checkpoint_committee_cache<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    checkpoint: Checkpoint,
+    target_block: &ProtoBlock,
+) -> Result<CommitteeCache, Error> {
+    let committee_cache_of = |state: &BeaconState<T::EthSpec>| -> Result<_, BeaconChainError> {
+        let relative_epoch = RelativeEpoch::from_epoch(state.current_epoch(), checkpoint.epoch)
+            .map_err(BeaconChainError::IncorrectStateForAttestation)?;
+        state
+            .committee_cache(relative_epoch)
+            .map(|committee_cache| committee_cache.clone())
+            .map_err(BeaconChainError::from)
+    };
+
+    if let Some(state) = chain
+        .checkpoint_state_cache
+        .try_write_for(ATTESTATION_CACHE_LOCK_TIMEOUT)
+        .ok_or_else(|| BeaconChainError::AttestationCacheLockTimeout)?
+        .get(&checkpoint)
+    {
+        return committee_cache_of(state).map_err(Into::into);
+    }
+
+    let state_read_timer = metrics::start_timer(&metrics::ATTESTATION_PROCESSING_STATE_READ_TIMES);
+
+    let mut state = chain
+        .store
+        .get_inconsistent_state_for_attestation_verification_only(
+            &target_block.state_root,
+            Some(target_block.slot),
+        )
+        .map_err(BeaconChainError::from)?
+        .ok_or_else(|| BeaconChainError::MissingBeaconState(target_block.state_root))?;
+
+    metrics::stop_timer(state_read_timer);
+    let state_skip_timer = metrics::start_timer(&metrics::ATTESTATION_PROCESSING_STATE_SKIP_TIMES);
+
+    while state.current_epoch() + 1 < checkpoint.epoch {
+        // Here we tell `per_slot_processing` to skip hashing the state and just
+        // use the zero hash instead.
+        //
+        // The state roots are not useful for the shuffling, so there's no need to
+        // compute them.
+        per_slot_processing(&mut state, Some(Hash256::zero()), &chain.spec)
+            .map_err(BeaconChainError::from)?;
+    }
+
+    metrics::stop_timer(state_skip_timer);
+    let committee_building_timer =
+        metrics::start_timer(&metrics::ATTESTATION_PROCESSING_COMMITTEE_BUILDING_TIMES);
+
+    let relative_epoch = RelativeEpoch::from_epoch(state.current_epoch(), checkpoint.epoch)
+        .map_err(BeaconChainError::IncorrectStateForAttestation)?;
+
+    state
+        .build_committee_cache(relative_epoch, &chain.spec)
+        .map_err(BeaconChainError::from)?;
+
+    let committee_cache = committee_cache_of(&state)?;
+
+    metrics::stop_timer(committee_building_timer);
+
+    chain
+        .checkpoint_state_cache
+        .try_write_for(ATTESTATION_CACHE_LOCK_TIMEOUT)
+        .ok_or_else(|| BeaconChainError::AttestationCacheLockTimeout)?
+        .insert(checkpoint, state);
+
+    Ok(committee_cache)
+}
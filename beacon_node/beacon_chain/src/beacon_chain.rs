@@ -8,10 +8,13 @@ use crate::block_verification::{
     IntoFullyVerifiedBlock,
 };
 use crate::chain_config::ChainConfig;
+use crate::checkpoint_state_cache::CheckpointStateCache;
 use crate::errors::{BeaconChainError as Error, BlockProductionError};
 use crate::eth1_chain::{Eth1Chain, Eth1ChainBackend};
 use crate::events::{EventHandler, EventKind};
 use crate::head_tracker::HeadTracker;
+use crate::head_watchdog::{HeadWatchdog, HeadWatchdogTransition};
+use crate::late_blocks::LateBlockTracker;
 use crate::metrics;
 use crate::migrate::Migrate;
 use crate::naive_aggregation_pool::{Error as NaiveAggregationError, NaiveAggregationPool};
@@ -119,7 +122,7 @@ pub enum AttestationProcessingOutcome {
     InvalidSignature,
     NoCommitteeForSlotAndIndex {
         slot: Slot,
-        index: CommitteeIndex,
+        index: u64,
     },
     Invalid(AttestationValidationError),
 }
@@ -213,10 +216,16 @@ pub struct BeaconChain<T: BeaconChainTypes> {
     pub event_handler: T::EventHandler,
     /// Used to track the heads of the beacon chain.
     pub(crate) head_tracker: Arc<HeadTracker>,
+    /// Raises an alarm if the head is persistently updated late in the slot.
+    pub head_watchdog: HeadWatchdog,
+    /// Collects statistics about blocks imported after the attestation deadline.
+    pub late_blocks: LateBlockTracker,
     /// A cache dedicated to block processing.
     pub(crate) snapshot_cache: TimeoutRwLock<SnapshotCache<T::EthSpec>>,
     /// Caches the shuffling for a given epoch and state root.
     pub(crate) shuffling_cache: TimeoutRwLock<ShufflingCache>,
+    /// Caches the states used to compute the shuffling of attestation targets.
+    pub(crate) checkpoint_state_cache: TimeoutRwLock<CheckpointStateCache<T::EthSpec>>,
     /// Caches a map of `validator_index -> validator_pubkey`.
     pub(crate) validator_pubkey_cache: TimeoutRwLock<ValidatorPubkeyCache>,
     /// A list of any hard-coded forks that have been disabled.
@@ -229,6 +238,15 @@ pub struct BeaconChain<T: BeaconChainTypes> {
 
 type BeaconBlockAndState<T> = (BeaconBlock<T>, BeaconState<T>);
 
+/// The operations from the operation pool which are packed into a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackedOperations<T: EthSpec> {
+    pub proposer_slashings: Vec<ProposerSlashing>,
+    pub attester_slashings: Vec<AttesterSlashing<T>>,
+    pub attestations: Vec<Attestation<T>>,
+    pub voluntary_exits: Vec<SignedVoluntaryExit>,
+}
+
 impl<T: BeaconChainTypes> BeaconChain<T> {
     /// Persists the core `BeaconChain` components (including the head block) and the fork choice.
     ///
@@ -782,7 +800,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     pub fn produce_unaggregated_attestation(
         &self,
         slot: Slot,
-        index: CommitteeIndex,
+        index: u64,
     ) -> Result<Attestation<T::EthSpec>, Error> {
         // Note: we're taking a lock on the head. The work involved here should be trivial enough
         // that the lock should not be held for long.
@@ -844,7 +862,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     pub fn produce_unaggregated_attestation_for_block(
         &self,
         slot: Slot,
-        index: CommitteeIndex,
+        index: u64,
         beacon_block_root: Hash256,
         mut state: Cow<BeaconState<T::EthSpec>>,
     ) -> Result<Attestation<T::EthSpec>, Error> {
@@ -940,8 +958,12 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
         self.fork_choice
             .write()
-            .on_attestation(self.slot()?, verified.indexed_attestation())
-            .map_err(Into::into)
+            .on_attestation(self.slot()?, verified.indexed_attestation())?;
+
+        self.late_blocks
+            .observe_attestation(verified.indexed_attestation().data.beacon_block_root);
+
+        Ok(())
     }
 
     /// Accepts an `VerifiedUnaggregatedAttestation` and attempts to apply it to the "naive
@@ -1582,6 +1604,8 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         self.head_tracker
             .register_block(block_root, parent_root, slot);
 
+        self.observe_block_import_delay(slot, block_root);
+
         metrics::stop_timer(db_write_timer);
 
         metrics::inc_counter(&metrics::BLOCK_PROCESSING_SUCCESSES);
@@ -1606,6 +1630,86 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         self.produce_block_on_state(state, slot, randao_reveal, validator_graffiti)
     }
 
+    /// Select the operations from the operation pool to pack into a block on `state`, according
+    /// to `self.config.block_packing`.
+    ///
+    /// The `state` must be at the slot of the block and have a built committee cache for the
+    /// current epoch.
+    fn pack_operations(
+        &self,
+        state: &BeaconState<T::EthSpec>,
+    ) -> Result<PackedOperations<T::EthSpec>, BlockProductionError> {
+        let (proposer_slashings, attester_slashings, voluntary_exits) = self
+            .op_pool
+            .get_slashings_and_exits(state, &self.config.block_packing, &self.spec);
+
+        // Map from attestation head block root to shuffling compatibility.
+        // Used to memoize the `attestation_shuffling_is_compatible` function.
+        let mut shuffling_filter_cache = HashMap::new();
+        let attestation_filter = |att: &&Attestation<T::EthSpec>| -> bool {
+            *shuffling_filter_cache
+                .entry((att.data.beacon_block_root, att.data.target.epoch))
+                .or_insert_with(|| {
+                    self.shuffling_is_compatible(
+                        &att.data.beacon_block_root,
+                        att.data.target.epoch,
+                        state,
+                    )
+                })
+        };
+
+        // Iterate through the naive aggregation pool and ensure all the attestations from there
+        // are included in the operation pool.
+        for attestation in self.naive_aggregation_pool.read().iter() {
+            if let Err(e) = self.op_pool.insert_attestation(
+                attestation.clone(),
+                &state.fork,
+                state.genesis_validators_root,
+                &self.spec,
+            ) {
+                // Don't stop block production if there's an error, just create a log.
+                error!(
+                    self.log,
+                    "Attestation did not transfer to op pool";
+                    "reason" => format!("{:?}", e)
+                );
+            }
+        }
+
+        let attestations = self
+            .op_pool
+            .get_attestations(state, attestation_filter, &self.spec)
+            .map_err(BlockProductionError::OpPoolError)?;
+
+        Ok(PackedOperations {
+            proposer_slashings,
+            attester_slashings,
+            attestations,
+            voluntary_exits,
+        })
+    }
+
+    /// Returns the operations which would be packed into a block produced at the current slot,
+    /// without producing the block.
+    pub fn block_packing_preview(
+        &self,
+    ) -> Result<(Slot, PackedOperations<T::EthSpec>), BlockProductionError> {
+        let slot = self
+            .slot()
+            .map_err(|_| BlockProductionError::UnableToReadSlot)?;
+        let mut state = self
+            .state_at_slot(slot - 1, StateSkipConfig::WithStateRoots)
+            .map_err(|_| BlockProductionError::UnableToProduceAtSlot(slot))?;
+
+        while state.slot < slot {
+            per_slot_processing(&mut state, None, &self.spec)?;
+        }
+        state.build_committee_cache(RelativeEpoch::Current, &self.spec)?;
+
+        self.pack_operations(&state)
+            .map(|operations| (slot, operations))
+    }
+
     /// Produce a block for some `slot` upon the given `state`.
     ///
     /// Typically the `self.produce_block()` function should be used, instead of calling this
@@ -1647,45 +1751,17 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             state.latest_block_header.canonical_root()
         };
 
-        let (proposer_slashings, attester_slashings) = self.op_pool.get_slashings(&state);
-
         let eth1_data = eth1_chain.eth1_data_for_block_production(&state, &self.spec)?;
         let deposits = eth1_chain
             .deposits_for_block_inclusion(&state, &eth1_data, &self.spec)?
             .into();
 
-        // Map from attestation head block root to shuffling compatibility.
-        // Used to memoize the `attestation_shuffling_is_compatible` function.
-        let mut shuffling_filter_cache = HashMap::new();
-        let attestation_filter = |att: &&Attestation<T::EthSpec>| -> bool {
-            *shuffling_filter_cache
-                .entry((att.data.beacon_block_root, att.data.target.epoch))
-                .or_insert_with(|| {
-                    self.shuffling_is_compatible(
-                        &att.data.beacon_block_root,
-                        att.data.target.epoch,
-                        &state,
-                    )
-                })
-        };
-
-        // Iterate through the naive aggregation pool and ensure all the attestations from there
-        // are included in the operation pool.
-        for attestation in self.naive_aggregation_pool.read().iter() {
-            if let Err(e) = self.op_pool.insert_attestation(
-                attestation.clone(),
-                &state.fork,
-                state.genesis_validators_root,
-                &self.spec,
-            ) {
-                // Don't stop block production if there's an error, just create a log.
-                error!(
-                    self.log,
-                    "Attestation did not transfer to op pool";
-                    "reason" => format!("{:?}", e)
-                );
-            }
-        }
+        let PackedOperations {
+            proposer_slashings,
+            attester_slashings,
+            attestations,
+            voluntary_exits,
+        } = self.pack_operations(&state)?;
 
         // Override the beacon node's graffiti with graffiti from the validator, if present.
         let graffiti = match validator_graffiti {
@@ -1705,13 +1781,9 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                     graffiti,
                     proposer_slashings: proposer_slashings.into(),
                     attester_slashings: attester_slashings.into(),
-                    attestations: self
-                        .op_pool
-                        .get_attestations(&state, attestation_filter, &self.spec)
-                        .map_err(BlockProductionError::OpPoolError)?
-                        .into(),
+                    attestations: attestations.into(),
                     deposits,
-                    voluntary_exits: self.op_pool.get_voluntary_exits(&state, &self.spec).into(),
+                    voluntary_exits: voluntary_exits.into(),
                 },
             },
             // The block is not signed here, that is the task of a validator client.
@@ -1738,6 +1810,9 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             "Produced beacon block";
             "parent" => format!("{}", block.message.parent_root),
             "attestations" => block.message.body.attestations.len(),
+            "deposits" => block.message.body.deposits.len(),
+            // Deposits which are known to the chain but not yet included in a block.
+            "pending_deposits" => state.eth1_data.deposit_count.saturating_sub(state.eth1_deposit_index),
             "slot" => block.message.slot
         );
 
@@ -1769,6 +1844,9 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             return Ok(());
         }
 
+        let old_duties_dependency =
+            self.with_head(|head| Ok(duties_dependency(&head.beacon_state)))?;
+
         // At this point we know that the new head block is not the same as the previous one
         metrics::inc_counter(&metrics::FORK_CHOICE_CHANGED_HEAD);
 
@@ -1882,6 +1960,8 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             self.persist_head_and_fork_choice()?;
         }
 
+        let new_head_slot = new_head.beacon_block.slot();
+        let new_duties_dependency = duties_dependency(&new_head.beacon_state);
         let update_head_timer = metrics::start_timer(&metrics::UPDATE_HEAD_TIMES);
 
         // Update the snapshot that stores the head of the chain at the time it received the
@@ -1921,9 +2001,91 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             current_head_beacon_block_root: beacon_block_root,
         });
 
+        // Duties for the head epoch (and the next) may have changed if the head has moved to a
+        // new epoch, or if a re-org has changed the block that the duties depend upon.
+        if new_duties_dependency != old_duties_dependency {
+            let _ = self.event_handler.register(EventKind::DutiesChanged {
+                epoch: new_duties_dependency.epoch,
+                dependent_root: new_duties_dependency.dependent_root,
+                previous_dependent_root: new_duties_dependency.previous_dependent_root,
+            });
+        }
+
+        self.observe_head_update_delay(new_head_slot);
+
         Ok(())
     }
 
+    /// Record how long after the start of its slot the head was updated to a block, raising an
+    /// alarm if the node is persistently late.
+    ///
+    /// Blocks which only become the head after their slot has ended are recorded too, since a node
+    /// which cannot keep up with the chain is the one the alarm is meant to catch.
+    fn observe_head_update_delay(&self, head_slot: Slot) {
+        let delay = match self.slot_clock.duration_since_start_of(head_slot) {
+            Some(delay) => delay,
+            None => return,
+        };
+
+        metrics::observe(
+            &metrics::BEACON_HEAD_UPDATE_DELAY_TIMES,
+            delay.as_secs_f64(),
+        );
+
+        match self.head_watchdog.observe(delay) {
+            Some(HeadWatchdogTransition::Lagging { median_delay }) => {
+                metrics::set_gauge(&metrics::BEACON_HEAD_LAGGING, 1);
+                warn!(
+                    self.log,
+                    "Head is being updated late";
+                    "info" => "validators may miss head votes, check system load and peer count",
+                    "median_delay_ms" => median_delay.as_millis() as u64,
+                    "threshold_ms" => self.head_watchdog.threshold().as_millis() as u64,
+                );
+                let _ = self.event_handler.register(EventKind::BeaconHeadLagging {
+                    median_delay_ms: median_delay.as_millis() as u64,
+                    threshold_ms: self.head_watchdog.threshold().as_millis() as u64,
+                });
+            }
+            Some(HeadWatchdogTransition::Recovered { median_delay }) => {
+                metrics::set_gauge(&metrics::BEACON_HEAD_LAGGING, 0);
+                info!(
+                    self.log,
+                    "Head updates are timely again";
+                    "median_delay_ms" => median_delay.as_millis() as u64,
+                );
+            }
+            None => (),
+        }
+    }
+
+    /// Record how long after the start of its slot the block with `block_root` was imported.
+    ///
+    /// Blocks from prior slots are ignored, as they are either being synced or are so late that
+    /// their timing is of no interest.
+    fn observe_block_import_delay(&self, slot: Slot, block_root: Hash256) {
+        if self.slot_clock.now() != Some(slot) {
+            return;
+        }
+
+        let delay = match self.slot_clock.duration_since_start_of(slot) {
+            Some(delay) => delay,
+            None => return,
+        };
+
+        if self.late_blocks.observe_block(slot, block_root, delay) {
+            metrics::inc_counter(&metrics::BEACON_LATE_BLOCKS);
+            debug!(
+                self.log,
+                "Imported block after attestation deadline";
+                "slot" => slot,
+                "block_root" => format!("{}", block_root),
+                "delay_ms" => delay.as_millis() as u64,
+                "deadline_ms" => self.late_blocks.deadline().as_millis() as u64,
+            );
+        }
+    }
+
     /// Called by the timer on every slot.
     ///
     /// Performs slot-based pruning.
@@ -1965,6 +2127,20 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 );
             });
 
+        self.checkpoint_state_cache
+            .try_write_for(ATTESTATION_CACHE_LOCK_TIMEOUT)
+            .map(|mut checkpoint_state_cache| {
+                checkpoint_state_cache.prune(new_finalized_checkpoint.epoch);
+            })
+            .unwrap_or_else(|| {
+                error!(
+                    self.log,
+                    "Failed to obtain cache write lock";
+                    "lock" => "checkpoint_state_cache",
+                    "task" => "prune"
+                );
+            });
+
         let finalized_state = self
             .get_state(&new_finalized_state_root, None)?
             .ok_or_else(|| Error::MissingBeaconState(new_finalized_state_root))?;
@@ -2172,6 +2348,37 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     }
 }
 
+/// The blocks upon which the validator duties of the epoch of some head (and the next epoch)
+/// depend. Duties only need to be recomputed when this changes.
+#[derive(PartialEq)]
+struct DutiesDependency {
+    epoch: Epoch,
+    /// The root of the block at the last slot of the epoch prior to `epoch`, which determines the
+    /// proposer duties of `epoch` and the attester duties of `epoch + 1`.
+    dependent_root: Hash256,
+    /// The root of the block at the last slot of the epoch two prior to `epoch`, which determines
+    /// the attester duties of `epoch`.
+    previous_dependent_root: Hash256,
+}
+
+/// Returns the blocks upon which the duties of the epoch of `state` depend.
+fn duties_dependency<E: EthSpec>(state: &BeaconState<E>) -> DutiesDependency {
+    let epoch = state.current_epoch();
+    let root_prior_to = |epoch: Epoch| {
+        state
+            .get_block_root(epoch.start_slot(E::slots_per_epoch()).saturating_sub(1_u64))
+            .map(|root| *root)
+            // The genesis state has no prior block, its duties depend only upon itself.
+            .unwrap_or_else(|_| Hash256::zero())
+    };
+
+    DutiesDependency {
+        epoch,
+        dependent_root: root_prior_to(epoch),
+        previous_dependent_root: root_prior_to(epoch.saturating_sub(1_u64)),
+    }
+}
+
 impl<T: BeaconChainTypes> Drop for BeaconChain<T> {
     fn drop(&mut self) {
         let drop = || -> Result<(), Error> {
@@ -1,9 +1,14 @@
 use crate::beacon_chain::{
     BEACON_CHAIN_DB_KEY, ETH1_CACHE_DB_KEY, FORK_CHOICE_DB_KEY, OP_POOL_DB_KEY,
 };
+use crate::checkpoint_state_cache::CheckpointStateCache;
 use crate::eth1_chain::{CachingEth1Backend, SszEth1};
 use crate::events::NullEventHandler;
 use crate::head_tracker::HeadTracker;
+use crate::head_watchdog::{
+    HeadWatchdog, DEFAULT_HEAD_WATCHDOG_WINDOW, HEAD_WATCHDOG_THRESHOLD_SLOT_FRACTION,
+};
+use crate::late_blocks::{LateBlockTracker, DEFAULT_LATE_BLOCKS_HISTORY};
 use crate::migrate::Migrate;
 use crate::persisted_beacon_chain::PersistedBeaconChain;
 use crate::persisted_fork_choice::PersistedForkChoice;
@@ -505,6 +510,14 @@ where
                 .map_err(|e| format!("Unable to build initialize ForkChoice: {:?}", e))?
         };
 
+        let attestation_deadline = self
+            .chain_config
+            .attestation_deadline_ms
+            .map(Duration::from_millis)
+            .unwrap_or_else(|| slot_clock.slot_duration() / HEAD_WATCHDOG_THRESHOLD_SLOT_FRACTION);
+        let head_watchdog = HeadWatchdog::new(attestation_deadline, DEFAULT_HEAD_WATCHDOG_WINDOW);
+        let late_blocks = LateBlockTracker::new(attestation_deadline, DEFAULT_LATE_BLOCKS_HISTORY);
+
         let beacon_chain = BeaconChain {
             spec: self.spec,
             config: self.chain_config,
@@ -541,11 +554,14 @@ where
                 .event_handler
                 .ok_or_else(|| "Cannot build without an event handler".to_string())?,
             head_tracker: Arc::new(self.head_tracker.unwrap_or_default()),
+            head_watchdog,
+            late_blocks,
             snapshot_cache: TimeoutRwLock::new(SnapshotCache::new(
                 DEFAULT_SNAPSHOT_CACHE_SIZE,
                 canonical_head,
             )),
             shuffling_cache: TimeoutRwLock::new(ShufflingCache::new()),
+            checkpoint_state_cache: TimeoutRwLock::new(CheckpointStateCache::new()),
             validator_pubkey_cache: TimeoutRwLock::new(validator_pubkey_cache),
             disabled_forks: self.disabled_forks,
             log: log.clone(),
@@ -1,3 +1,4 @@
+use operation_pool::BlockPackingConfig;
 use serde_derive::{Deserialize, Serialize};
 
 /// There is a 693 block skip in the current canonical Medalla chain, we use 700 to be safe.
@@ -10,12 +11,21 @@ pub struct ChainConfig {
     ///
     /// If `None`, there is no limit.
     pub import_max_skip_slots: Option<u64>,
+    /// The time into a slot, in milliseconds, after which validators are expected to have
+    /// attested. Blocks imported after this deadline are considered late.
+    ///
+    /// If `None`, one third of the slot duration is used.
+    pub attestation_deadline_ms: Option<u64>,
+    /// Controls the slashings and voluntary exits included in produced blocks.
+    pub block_packing: BlockPackingConfig,
 }
 
 impl Default for ChainConfig {
     fn default() -> Self {
         Self {
             import_max_skip_slots: Some(DEFAULT_IMPORT_BLOCK_MAX_SKIP_SLOTS),
+            attestation_deadline_ms: None,
+            block_packing: BlockPackingConfig::default(),
         }
     }
 }
@@ -0,0 +1,114 @@
+use crate::metrics;
+use lru::LruCache;
+use types::{BeaconState, Checkpoint, Epoch, EthSpec};
+
+/// The size of the LRU cache that stores checkpoint states.
+///
+/// Each entry is a full `BeaconState`, so this cache is kept much smaller than the
+/// `ShufflingCache`. An entry is only needed when an epoch's first attestations arrive, since the
+/// shuffling of the epoch is then held by the `ShufflingCache`.
+const CACHE_SIZE: usize = 4;
+
+/// Provides an LRU cache of the states used to verify attestations, keyed by the target
+/// checkpoint of the attestations.
+///
+/// Each state is the state of the target block, advanced far enough to compute the shuffling of
+/// the target epoch, with the committee cache of that epoch built. Caching the state avoids
+/// reading it from disk and advancing it again when the shuffling of the epoch is evicted from
+/// the `ShufflingCache`.
+pub struct CheckpointStateCache<T: EthSpec> {
+    cache: LruCache<Checkpoint, BeaconState<T>>,
+}
+
+impl<T: EthSpec> CheckpointStateCache<T> {
+    pub fn new() -> Self {
+        Self {
+            cache: LruCache::new(CACHE_SIZE),
+        }
+    }
+
+    pub fn get(&mut self, checkpoint: &Checkpoint) -> Option<&BeaconState<T>> {
+        let opt = self.cache.get(checkpoint);
+
+        if opt.is_some() {
+            metrics::inc_counter(&metrics::CHECKPOINT_STATE_CACHE_HITS);
+        } else {
+            metrics::inc_counter(&metrics::CHECKPOINT_STATE_CACHE_MISSES);
+        }
+
+        opt
+    }
+
+    pub fn insert(&mut self, checkpoint: Checkpoint, state: BeaconState<T>) {
+        if !self.cache.contains(&checkpoint) {
+            self.cache.put(checkpoint, state);
+        }
+    }
+
+    /// Removes the states of checkpoints prior to the `finalized_epoch`, since attestations
+    /// which target them are no longer verified.
+    pub fn prune(&mut self, finalized_epoch: Epoch) {
+        let stale = self
+            .cache
+            .iter()
+            .map(|(checkpoint, _)| *checkpoint)
+            .filter(|checkpoint| checkpoint.epoch < finalized_epoch)
+            .collect::<Vec<_>>();
+
+        for checkpoint in stale {
+            self.cache.pop(&checkpoint);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{test_utils::TestingBeaconStateBuilder, Hash256, MinimalEthSpec};
+
+    fn get_state() -> BeaconState<MinimalEthSpec> {
+        let spec = MinimalEthSpec::default_spec();
+
+        let state_builder = TestingBeaconStateBuilder::from_deterministic_keypairs(1, &spec);
+        let (beacon_state, _keypairs) = state_builder.build();
+        beacon_state
+    }
+
+    fn checkpoint(epoch: u64) -> Checkpoint {
+        Checkpoint {
+            epoch: Epoch::new(epoch),
+            root: Hash256::from_low_u64_be(epoch),
+        }
+    }
+
+    #[test]
+    fn insert_get_and_prune() {
+        let mut cache = CheckpointStateCache::new();
+        let state = get_state();
+
+        for epoch in 0..3 {
+            cache.insert(checkpoint(epoch), state.clone());
+        }
+        assert_eq!(cache.cache.len(), 3);
+        assert!(cache.get(&checkpoint(1)).is_some());
+        assert!(cache.get(&checkpoint(3)).is_none());
+
+        cache.prune(Epoch::new(2));
+        assert_eq!(cache.cache.len(), 1);
+        assert!(cache.get(&checkpoint(1)).is_none());
+        assert!(cache.get(&checkpoint(2)).is_some());
+    }
+
+    #[test]
+    fn lru_eviction() {
+        let mut cache = CheckpointStateCache::new();
+        let state = get_state();
+
+        for epoch in 0..CACHE_SIZE as u64 + 1 {
+            cache.insert(checkpoint(epoch), state.clone());
+        }
+        assert_eq!(cache.cache.len(), CACHE_SIZE);
+        assert!(cache.get(&checkpoint(0)).is_none());
+        assert!(cache.get(&checkpoint(CACHE_SIZE as u64)).is_some());
+    }
+}
@@ -1,6 +1,6 @@
 use crate::metrics;
 use environment::TaskExecutor;
-use eth1::{Config as Eth1Config, Eth1Block, Service as HttpService};
+use eth1::{Config as Eth1Config, DepositTreeSnapshot, Eth1Block, Service as HttpService};
 use eth2_hashing::hash;
 use slog::{debug, error, trace, Logger};
 use ssz::{Decode, Encode};
@@ -143,6 +143,19 @@ where
         }
     }
 
+    /// Returns a snapshot of the deposit tree as of `eth1_data`, or `None` if the dummy backend is
+    /// in use.
+    pub fn deposit_snapshot(
+        &self,
+        eth1_data: &Eth1Data,
+    ) -> Result<Option<DepositTreeSnapshot>, Error> {
+        if self.use_dummy_backend {
+            Ok(None)
+        } else {
+            self.backend.deposit_snapshot(eth1_data)
+        }
+    }
+
     /// Instantiate `Eth1Chain` from a persisted `SszEth1`.
     ///
     /// The `Eth1Chain` will have the same caches as the persisted `SszEth1`.
@@ -195,6 +208,12 @@ pub trait Eth1ChainBackend<T: EthSpec>: Sized + Send + Sync {
         spec: &ChainSpec,
     ) -> Result<Vec<Deposit>, Error>;
 
+    /// Returns a snapshot of the deposit tree after `eth1_data.deposit_count` deposits, at the
+    /// block `eth1_data.block_hash`.
+    ///
+    /// Returns `None` if the backend does not track deposits, or no longer knows the block.
+    fn deposit_snapshot(&self, eth1_data: &Eth1Data) -> Result<Option<DepositTreeSnapshot>, Error>;
+
     /// Encode the `Eth1ChainBackend` instance to bytes.
     fn as_bytes(&self) -> Vec<u8>;
 
@@ -241,6 +260,11 @@ impl<T: EthSpec> Eth1ChainBackend<T> for DummyEth1ChainBackend<T> {
         Ok(vec![])
     }
 
+    /// The dummy back-end does not track deposits.
+    fn deposit_snapshot(&self, _: &Eth1Data) -> Result<Option<DepositTreeSnapshot>, Error> {
+        Ok(None)
+    }
+
     /// Return empty Vec<u8> for dummy backend.
     fn as_bytes(&self) -> Vec<u8> {
         Vec::new()
@@ -400,6 +424,42 @@ impl<T: EthSpec> Eth1ChainBackend<T> for CachingEth1Backend<T> {
         }
     }
 
+    fn deposit_snapshot(&self, eth1_data: &Eth1Data) -> Result<Option<DepositTreeSnapshot>, Error> {
+        let execution_block_height = match self
+            .core
+            .blocks()
+            .read()
+            .iter()
+            .find(|block| block.hash == eth1_data.block_hash)
+        {
+            Some(block) => block.number,
+            None => return Ok(None),
+        };
+
+        let (finalized, deposit_root) = self
+            .core
+            .deposits()
+            .read()
+            .cache
+            .get_finalized_branch(eth1_data.deposit_count)
+            .map_err(|e| Error::BackendError(format!("Failed to get deposit tree: {:?}", e)))?;
+
+        if deposit_root != eth1_data.deposit_root {
+            return Err(Error::BackendError(format!(
+                "Deposit root mismatch, cache: {:?}, eth1_data: {:?}",
+                deposit_root, eth1_data.deposit_root
+            )));
+        }
+
+        Ok(Some(DepositTreeSnapshot {
+            finalized,
+            deposit_root,
+            deposit_count: eth1_data.deposit_count,
+            execution_block_hash: eth1_data.block_hash,
+            execution_block_height,
+        }))
+    }
+
     /// Return encoded byte representation of the block and deposit caches.
     fn as_bytes(&self) -> Vec<u8> {
         self.core.as_bytes()
@@ -22,16 +22,35 @@ impl<T: EthSpec> EventHandler<T> for WebSocketSender<T> {
     }
 }
 
+/// An event which is streamed to server-sent event subscribers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServerSentEvent {
+    /// The canonical head has changed to the given block.
+    HeadChanged(SignedBeaconBlockHash),
+    /// The head is persistently being updated late in the slot.
+    Lagging {
+        median_delay_ms: u64,
+        threshold_ms: u64,
+    },
+    /// Validator duties for `epoch` (and the next epoch) may have changed, since they now depend
+    /// upon the blocks with `dependent_root` and `previous_dependent_root`.
+    DutiesChanged {
+        epoch: Epoch,
+        dependent_root: Hash256,
+        previous_dependent_root: Hash256,
+    },
+}
+
 pub struct ServerSentEvents<T: EthSpec> {
     // Bus<> is itself Sync + Send.  We use Mutex<> here only because of the surrounding code does
     // not enforce mutability statically (i.e. relies on interior mutability).
-    head_changed_queue: Arc<Mutex<Bus<SignedBeaconBlockHash>>>,
+    head_changed_queue: Arc<Mutex<Bus<ServerSentEvent>>>,
     log: Logger,
     _phantom: PhantomData<T>,
 }
 
 impl<T: EthSpec> ServerSentEvents<T> {
-    pub fn new(log: Logger) -> (Self, Arc<Mutex<Bus<SignedBeaconBlockHash>>>) {
+    pub fn new(log: Logger) -> (Self, Arc<Mutex<Bus<ServerSentEvent>>>) {
         let bus = Bus::new(T::slots_per_epoch() as usize);
         let mutex = Mutex::new(bus);
         let arc = Arc::new(mutex);
@@ -53,7 +72,9 @@ impl<T: EthSpec> EventHandler<T> for ServerSentEvents<T> {
             } => {
                 let mut guard = self.head_changed_queue.lock();
                 if guard
-                    .try_broadcast(current_head_beacon_block_root.into())
+                    .try_broadcast(ServerSentEvent::HeadChanged(
+                        current_head_beacon_block_root.into(),
+                    ))
                     .is_err()
                 {
                     error!(
@@ -64,6 +85,48 @@ impl<T: EthSpec> EventHandler<T> for ServerSentEvents<T> {
                 }
                 Ok(())
             }
+            EventKind::BeaconHeadLagging {
+                median_delay_ms,
+                threshold_ms,
+            } => {
+                let mut guard = self.head_changed_queue.lock();
+                if guard
+                    .try_broadcast(ServerSentEvent::Lagging {
+                        median_delay_ms,
+                        threshold_ms,
+                    })
+                    .is_err()
+                {
+                    error!(
+                        self.log,
+                        "Event streaming queue full";
+                        "dropped_event" => "lagging",
+                    );
+                }
+                Ok(())
+            }
+            EventKind::DutiesChanged {
+                epoch,
+                dependent_root,
+                previous_dependent_root,
+            } => {
+                let mut guard = self.head_changed_queue.lock();
+                if guard
+                    .try_broadcast(ServerSentEvent::DutiesChanged {
+                        epoch,
+                        dependent_root,
+                        previous_dependent_root,
+                    })
+                    .is_err()
+                {
+                    error!(
+                        self.log,
+                        "Event streaming queue full";
+                        "dropped_event" => "duties_changed",
+                    );
+                }
+                Ok(())
+            }
             _ => Ok(()),
         }
     }
@@ -82,7 +145,7 @@ impl<E: EthSpec> TeeEventHandler<E> {
     pub fn new(
         log: Logger,
         websockets_handler: WebSocketSender<E>,
-    ) -> Result<(Self, Arc<Mutex<Bus<SignedBeaconBlockHash>>>), String> {
+    ) -> Result<(Self, Arc<Mutex<Bus<ServerSentEvent>>>), String> {
         let (sse_handler, bus) = ServerSentEvents::new(log);
         let result = Self {
             websockets_handler,
@@ -144,4 +207,13 @@ pub enum EventKind<T: EthSpec> {
         reason: String,
         attestation: Box<Attestation<T>>,
     },
+    BeaconHeadLagging {
+        median_delay_ms: u64,
+        threshold_ms: u64,
+    },
+    DutiesChanged {
+        epoch: Epoch,
+        dependent_root: Hash256,
+        previous_dependent_root: Hash256,
+    },
 }
@@ -0,0 +1,175 @@
+//! Tracks how long after the start of a slot the canonical head is updated, raising an alarm when
+//! the node persistently imports blocks late.
+//!
+//! A node which imports the block for the current slot after the attestation deadline will cause
+//! its validators to attest to the wrong head. Since this tends to degrade gradually (e.g., as a
+//! machine becomes overloaded), the watchdog considers the median of recent delays rather than
+//! reacting to any single late block.
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// The number of recent head updates to consider.
+pub const DEFAULT_HEAD_WATCHDOG_WINDOW: usize = 16;
+/// Head updates later than `slot_duration / HEAD_WATCHDOG_THRESHOLD_SLOT_FRACTION` into the slot
+/// are considered late. This matches the point at which validators produce attestations.
+pub const HEAD_WATCHDOG_THRESHOLD_SLOT_FRACTION: u32 = 3;
+
+/// A change in the lagging status of the node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeadWatchdogTransition {
+    /// The median delay has exceeded the threshold.
+    Lagging { median_delay: Duration },
+    /// The median delay has returned to within the threshold.
+    Recovered { median_delay: Duration },
+}
+
+struct Inner {
+    delays: VecDeque<Duration>,
+    lagging: bool,
+}
+
+/// Keeps a sliding window of the delays between the start of a slot and the head being updated to
+/// the block for that slot.
+pub struct HeadWatchdog {
+    /// Head updates later than this into the slot are considered late.
+    threshold: Duration,
+    /// The number of samples to keep.
+    window: usize,
+    inner: Mutex<Inner>,
+}
+
+impl HeadWatchdog {
+    /// Create a watchdog which considers the `window` most recent head updates and raises an alarm
+    /// when their median delay exceeds `threshold`.
+    pub fn new(threshold: Duration, window: usize) -> Self {
+        Self {
+            threshold,
+            window,
+            inner: Mutex::new(Inner {
+                delays: VecDeque::with_capacity(window),
+                lagging: false,
+            }),
+        }
+    }
+
+    /// Returns the threshold after which head updates are considered late.
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    /// Returns `true` if the node is currently considered to be lagging.
+    pub fn is_lagging(&self) -> bool {
+        self.inner.lock().lagging
+    }
+
+    /// Returns the median of the recorded delays, or `None` if there are none.
+    pub fn median_delay(&self) -> Option<Duration> {
+        median(&self.inner.lock().delays)
+    }
+
+    /// Record that the head was updated `delay` after the start of the head block's slot.
+    ///
+    /// Returns a transition if this observation changed whether the node is lagging. No alarm is
+    /// raised until the window is full, to avoid false alarms from a handful of late blocks after
+    /// startup.
+    pub fn observe(&self, delay: Duration) -> Option<HeadWatchdogTransition> {
+        let mut inner = self.inner.lock();
+
+        if inner.delays.len() >= self.window {
+            inner.delays.pop_front();
+        }
+        inner.delays.push_back(delay);
+
+        if inner.delays.len() < self.window {
+            return None;
+        }
+
+        let median_delay = median(&inner.delays)?;
+        let lagging = median_delay > self.threshold;
+
+        if lagging == inner.lagging {
+            None
+        } else {
+            inner.lagging = lagging;
+            if lagging {
+                Some(HeadWatchdogTransition::Lagging { median_delay })
+            } else {
+                Some(HeadWatchdogTransition::Recovered { median_delay })
+            }
+        }
+    }
+}
+
+fn median(delays: &VecDeque<Duration>) -> Option<Duration> {
+    let mut sorted = delays.iter().copied().collect::<Vec<_>>();
+    sorted.sort();
+    sorted.get(sorted.len() / 2).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLD: Duration = Duration::from_secs(4);
+    const WINDOW: usize = 4;
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    #[test]
+    fn no_alarm_until_window_full() {
+        let watchdog = HeadWatchdog::new(THRESHOLD, WINDOW);
+
+        for _ in 0..WINDOW - 1 {
+            assert_eq!(watchdog.observe(secs(10)), None);
+        }
+        assert!(!watchdog.is_lagging());
+
+        assert_eq!(
+            watchdog.observe(secs(10)),
+            Some(HeadWatchdogTransition::Lagging {
+                median_delay: secs(10)
+            })
+        );
+        assert!(watchdog.is_lagging());
+    }
+
+    #[test]
+    fn single_late_block_is_ignored() {
+        let watchdog = HeadWatchdog::new(THRESHOLD, WINDOW);
+
+        for _ in 0..WINDOW {
+            assert_eq!(watchdog.observe(secs(1)), None);
+        }
+        assert_eq!(watchdog.observe(secs(11)), None);
+        assert!(!watchdog.is_lagging());
+    }
+
+    #[test]
+    fn lagging_then_recovered() {
+        let watchdog = HeadWatchdog::new(THRESHOLD, WINDOW);
+
+        let transitions = (0..WINDOW)
+            .filter_map(|_| watchdog.observe(secs(6)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            transitions,
+            vec![HeadWatchdogTransition::Lagging {
+                median_delay: secs(6)
+            }]
+        );
+
+        let transitions = (0..WINDOW)
+            .filter_map(|_| watchdog.observe(secs(1)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            transitions,
+            vec![HeadWatchdogTransition::Recovered {
+                median_delay: secs(1)
+            }]
+        );
+        assert!(!watchdog.is_lagging());
+    }
+}
@@ -0,0 +1,104 @@
+//! Checks of invariants which must hold between the components of a `BeaconChain`.
+//!
+//! A violation indicates either a bug or a corrupt database. The checks are too expensive to run
+//! on every operation, so they are instead run periodically during development and on long-lived
+//! devnets (see the `--invariant-checker` flag).
+use crate::{BeaconChain, BeaconChainError, BeaconChainTypes};
+use std::collections::HashSet;
+use types::{Checkpoint, Epoch, Hash256, Slot};
+
+/// A relationship between components of the `BeaconChain` which was found not to hold.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvariantViolation {
+    /// The head block is not known to fork choice.
+    HeadNotInForkChoice { head_root: Hash256, head_slot: Slot },
+    /// The block of the finalized checkpoint is not in the store.
+    FinalizedBlockMissing { checkpoint: Checkpoint },
+    /// The state of the finalized checkpoint is not in the store.
+    FinalizedStateMissing {
+        checkpoint: Checkpoint,
+        state_root: Hash256,
+    },
+    /// An attestation in the operation pool references a block which is known to neither fork
+    /// choice nor the store.
+    AttestationForUnknownBlock {
+        beacon_block_root: Hash256,
+        slot: Slot,
+    },
+}
+
+/// Checks that:
+///
+/// - The head block exists in fork choice.
+/// - The block and state of the finalized checkpoint exist in the store.
+/// - All attestations in the operation pool reference known blocks.
+///
+/// Returns all of the violations found, which is empty if the chain is consistent.
+///
+/// ## Errors
+///
+/// Returns an error if the chain could not be read, which does not itself indicate a violation.
+pub fn check_invariants<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+) -> Result<Vec<InvariantViolation>, BeaconChainError> {
+    let mut violations = vec![];
+    let head_info = chain.head_info()?;
+
+    if !chain
+        .fork_choice
+        .read()
+        .contains_block(&head_info.block_root)
+    {
+        violations.push(InvariantViolation::HeadNotInForkChoice {
+            head_root: head_info.block_root,
+            head_slot: head_info.slot,
+        });
+    }
+
+    let checkpoint = head_info.finalized_checkpoint;
+    // The root of the finalized checkpoint is zero until the first epoch is finalized.
+    let finalized_root = if checkpoint.epoch == Epoch::new(0) {
+        chain.genesis_block_root
+    } else {
+        checkpoint.root
+    };
+    match chain.get_block(&finalized_root)? {
+        Some(block) => {
+            let state_root = block.state_root();
+            if chain.get_state(&state_root, Some(block.slot()))?.is_none() {
+                violations.push(InvariantViolation::FinalizedStateMissing {
+                    checkpoint,
+                    state_root,
+                });
+            }
+        }
+        None => violations.push(InvariantViolation::FinalizedBlockMissing { checkpoint }),
+    }
+
+    // Find the attestations for blocks unknown to fork choice before reading from the store, to
+    // avoid holding the fork choice lock during disk access.
+    let mut checked_roots = HashSet::new();
+    let unknown_to_fork_choice = {
+        let fork_choice = chain.fork_choice.read();
+        chain
+            .op_pool
+            .get_all_attestations()
+            .into_iter()
+            .filter(|attestation| checked_roots.insert(attestation.data.beacon_block_root))
+            .filter(|attestation| !fork_choice.contains_block(&attestation.data.beacon_block_root))
+            .collect::<Vec<_>>()
+    };
+    for attestation in unknown_to_fork_choice {
+        if chain
+            .get_block(&attestation.data.beacon_block_root)?
+            .is_none()
+        {
+            violations.push(InvariantViolation::AttestationForUnknownBlock {
+                beacon_block_root: attestation.data.beacon_block_root,
+                slot: attestation.data.slot,
+            });
+        }
+    }
+
+    Ok(violations)
+}
@@ -0,0 +1,205 @@
+//! Collects statistics about blocks which are imported after the attestation deadline of their
+//! slot, and about the attestations which vote for them.
+//!
+//! Validators attest to their view of the head at the attestation deadline, so a block which
+//! arrives after it will receive few votes from its own slot and is at risk of being re-orged out.
+//! These statistics are purely observational: they don't affect fork choice, but provide the data
+//! needed to evaluate policies for handling late blocks.
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::time::Duration;
+use types::{Hash256, Slot};
+
+/// The number of recent late blocks to keep.
+pub const DEFAULT_LATE_BLOCKS_HISTORY: usize = 64;
+
+/// A block which was imported after the attestation deadline of its slot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LateBlock {
+    pub slot: Slot,
+    pub block_root: Hash256,
+    /// The time between the start of the slot and the block being imported.
+    pub delay: Duration,
+    /// The number of attestations voting for the block as the head.
+    pub attestations: u64,
+}
+
+/// A summary of the blocks observed by a `LateBlockTracker`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LateBlocksSummary {
+    /// Blocks imported later than this into their slot are considered late.
+    pub deadline: Duration,
+    /// The number of blocks observed, late or not.
+    pub blocks_observed: u64,
+    /// The number of late blocks observed.
+    pub late_blocks: u64,
+    /// The number of attestations voting for one of the `recent` late blocks as the head.
+    pub attestations_to_late_blocks: u64,
+    /// The most recent late blocks, oldest first.
+    pub recent: Vec<LateBlock>,
+}
+
+#[derive(Default)]
+struct Inner {
+    blocks_observed: u64,
+    late_blocks: u64,
+    attestations_to_late_blocks: u64,
+    recent: VecDeque<LateBlock>,
+}
+
+/// Counts the blocks imported after the attestation deadline, keeping the most recent of them.
+pub struct LateBlockTracker {
+    /// Blocks imported later than this into their slot are considered late.
+    deadline: Duration,
+    /// The number of late blocks to keep.
+    history: usize,
+    inner: Mutex<Inner>,
+}
+
+impl LateBlockTracker {
+    /// Create a tracker which considers blocks imported later than `deadline` into their slot to
+    /// be late, and keeps the `history` most recent of them.
+    pub fn new(deadline: Duration, history: usize) -> Self {
+        Self {
+            deadline,
+            history,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    /// Returns the deadline after which blocks are considered late.
+    pub fn deadline(&self) -> Duration {
+        self.deadline
+    }
+
+    /// Record that the block with `block_root` was imported `delay` after the start of `slot`.
+    ///
+    /// Returns `true` if the block is late.
+    pub fn observe_block(&self, slot: Slot, block_root: Hash256, delay: Duration) -> bool {
+        let mut inner = self.inner.lock();
+        inner.blocks_observed += 1;
+
+        if delay <= self.deadline {
+            return false;
+        }
+
+        inner.late_blocks += 1;
+        if inner.recent.len() >= self.history {
+            inner.recent.pop_front();
+        }
+        inner.recent.push_back(LateBlock {
+            slot,
+            block_root,
+            delay,
+            attestations: 0,
+        });
+
+        true
+    }
+
+    /// Record an attestation voting for `block_root` as the head.
+    ///
+    /// Only attestations to one of the recent late blocks are counted.
+    pub fn observe_attestation(&self, block_root: Hash256) {
+        let mut inner = self.inner.lock();
+
+        if let Some(late_block) = inner
+            .recent
+            .iter_mut()
+            .rev()
+            .find(|late_block| late_block.block_root == block_root)
+        {
+            late_block.attestations += 1;
+            inner.attestations_to_late_blocks += 1;
+        }
+    }
+
+    /// Returns a summary of the blocks observed so far.
+    pub fn summary(&self) -> LateBlocksSummary {
+        let inner = self.inner.lock();
+
+        LateBlocksSummary {
+            deadline: self.deadline,
+            blocks_observed: inner.blocks_observed,
+            late_blocks: inner.late_blocks,
+            attestations_to_late_blocks: inner.attestations_to_late_blocks,
+            recent: inner.recent.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEADLINE: Duration = Duration::from_secs(4);
+    const HISTORY: usize = 2;
+
+    fn root(i: u64) -> Hash256 {
+        Hash256::from_low_u64_be(i)
+    }
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    #[test]
+    fn counts_late_blocks() {
+        let tracker = LateBlockTracker::new(DEADLINE, HISTORY);
+
+        assert!(!tracker.observe_block(Slot::new(1), root(1), secs(1)));
+        assert!(!tracker.observe_block(Slot::new(2), root(2), DEADLINE));
+        assert!(tracker.observe_block(Slot::new(3), root(3), secs(5)));
+
+        let summary = tracker.summary();
+        assert_eq!(summary.deadline, DEADLINE);
+        assert_eq!(summary.blocks_observed, 3);
+        assert_eq!(summary.late_blocks, 1);
+        assert_eq!(
+            summary.recent,
+            vec![LateBlock {
+                slot: Slot::new(3),
+                block_root: root(3),
+                delay: secs(5),
+                attestations: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn keeps_recent_late_blocks() {
+        let tracker = LateBlockTracker::new(DEADLINE, HISTORY);
+
+        for i in 1..=3 {
+            tracker.observe_block(Slot::new(i), root(i), secs(5));
+        }
+
+        let summary = tracker.summary();
+        assert_eq!(summary.late_blocks, 3);
+        assert_eq!(
+            summary
+                .recent
+                .iter()
+                .map(|late_block| late_block.slot)
+                .collect::<Vec<_>>(),
+            vec![Slot::new(2), Slot::new(3)]
+        );
+    }
+
+    #[test]
+    fn counts_attestations_to_late_blocks() {
+        let tracker = LateBlockTracker::new(DEADLINE, HISTORY);
+
+        tracker.observe_block(Slot::new(1), root(1), secs(1));
+        tracker.observe_block(Slot::new(2), root(2), secs(5));
+
+        tracker.observe_attestation(root(1));
+        tracker.observe_attestation(root(2));
+        tracker.observe_attestation(root(2));
+        tracker.observe_attestation(root(3));
+
+        let summary = tracker.summary();
+        assert_eq!(summary.attestations_to_late_blocks, 2);
+        assert_eq!(summary.recent[0].attestations, 2);
+    }
+}
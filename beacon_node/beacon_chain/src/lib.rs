@@ -13,10 +13,14 @@ mod beacon_snapshot;
 mod block_verification;
 pub mod builder;
 pub mod chain_config;
+mod checkpoint_state_cache;
 mod errors;
 pub mod eth1_chain;
 pub mod events;
 mod head_tracker;
+pub mod head_watchdog;
+pub mod invariants;
+pub mod late_blocks;
 mod metrics;
 pub mod migrate;
 mod naive_aggregation_pool;
@@ -34,7 +38,7 @@ mod validator_pubkey_cache;
 
 pub use self::beacon_chain::{
     AttestationProcessingOutcome, BeaconChain, BeaconChainTypes, ChainSegmentResult,
-    ForkChoiceError, StateSkipConfig,
+    ForkChoiceError, PackedOperations, StateSkipConfig,
 };
 pub use self::beacon_snapshot::BeaconSnapshot;
 pub use self::chain_config::ChainConfig;
@@ -180,6 +180,14 @@ lazy_static! {
     pub static ref SHUFFLING_CACHE_MISSES: Result<IntCounter> =
         try_create_int_counter("beacon_shuffling_cache_misses_total", "Count of times shuffling cache fulfils request");
 
+    /*
+     * Checkpoint state cache
+     */
+    pub static ref CHECKPOINT_STATE_CACHE_HITS: Result<IntCounter> =
+        try_create_int_counter("beacon_checkpoint_state_cache_hits_total", "Count of times checkpoint state cache fulfils request");
+    pub static ref CHECKPOINT_STATE_CACHE_MISSES: Result<IntCounter> =
+        try_create_int_counter("beacon_checkpoint_state_cache_misses_total", "Count of times checkpoint state cache does not fulfil request");
+
     /*
      * Attestation Production
      */
@@ -258,6 +266,18 @@ lazy_static! {
      */
     pub static ref UPDATE_HEAD_TIMES: Result<Histogram> =
         try_create_histogram("beacon_update_head_seconds", "Time taken to update the canonical head");
+    pub static ref BEACON_HEAD_UPDATE_DELAY_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_head_update_delay_seconds",
+        "Duration between the start of a block's slot and the head being updated to that block"
+    );
+    pub static ref BEACON_HEAD_LAGGING: Result<IntGauge> = try_create_int_gauge(
+        "beacon_head_lagging",
+        "Set to 1 if the head is persistently being updated late in the slot"
+    );
+    pub static ref BEACON_LATE_BLOCKS: Result<IntCounter> = try_create_int_counter(
+        "beacon_late_blocks_total",
+        "Count of blocks from the current slot imported after the attestation deadline"
+    );
     pub static ref HEAD_STATE_SLOT: Result<IntGauge> =
         try_create_int_gauge("beacon_head_state_slot", "Slot of the block at the head of the chain");
     pub static ref HEAD_STATE_ROOT: Result<IntGauge> =
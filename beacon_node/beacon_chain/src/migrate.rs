@@ -36,6 +36,10 @@ pub trait Migrate<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>:
 {
     fn new(db: Arc<HotColdDB<E, Hot, Cold>>, log: Logger) -> Self;
 
+    /// Stops accepting new migrations and waits for any in-progress migration to complete, so
+    /// that shutting down can't interrupt it part-way through.
+    fn shutdown(&self) {}
+
     fn process_finalization(
         &self,
         _finalized_state_root: BeaconStateHash,
@@ -333,7 +337,9 @@ type MpscSender<E> = mpsc::Sender<(
 /// Migrator that runs a background thread to migrate state from the hot to the cold database.
 pub struct BackgroundMigrator<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> {
     db: Arc<HotColdDB<E, Hot, Cold>>,
-    tx_thread: Mutex<(MpscSender<E>, thread::JoinHandle<()>)>,
+    /// The channel to, and handle of, the migration thread. Only `None` once the migrator has been
+    /// shut down or dropped.
+    tx_thread: Mutex<Option<(MpscSender<E>, thread::JoinHandle<()>)>>,
     log: Logger,
 }
 
@@ -341,10 +347,25 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> Migrate<E, Hot, Cold>
     for BackgroundMigrator<E, Hot, Cold>
 {
     fn new(db: Arc<HotColdDB<E, Hot, Cold>>, log: Logger) -> Self {
-        let tx_thread = Mutex::new(Self::spawn_thread(db.clone(), log.clone()));
+        let tx_thread = Mutex::new(Some(Self::spawn_thread(db.clone(), log.clone())));
         Self { db, tx_thread, log }
     }
 
+    fn shutdown(&self) {
+        let tx_thread = self.tx_thread.lock().take();
+        if let Some((tx, thread)) = tx_thread {
+            // Dropping the sender causes the thread to exit once it has finished its current work.
+            drop(tx);
+            if let Err(e) = thread.join() {
+                warn!(
+                    self.log,
+                    "Migration thread failed during shutdown";
+                    "reason" => format!("{:?}", e)
+                );
+            }
+        }
+    }
+
     fn process_finalization(
         &self,
         finalized_state_root: BeaconStateHash,
@@ -353,7 +374,11 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> Migrate<E, Hot, Cold>
         old_finalized_checkpoint: Checkpoint,
         new_finalized_checkpoint: Checkpoint,
     ) -> Result<(), BeaconChainError> {
-        let (ref mut tx, ref mut thread) = *self.tx_thread.lock();
+        let mut tx_thread = self.tx_thread.lock();
+        let (tx, thread) = match tx_thread.as_mut() {
+            Some(tx_thread) => tx_thread,
+            None => return Ok(()),
+        };
 
         if let Err(tx_err) = tx.send((
             finalized_state_root,
@@ -403,6 +428,9 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> BackgroundMigrator<E, Ho
                 new_finalized_checkpoint,
             )) = rx.recv()
             {
+                // Pruning is maintenance too, and may take as long as the migration itself.
+                let _maintenance = db.begin_maintenance();
+
                 match Self::prune_abandoned_forks(
                     db.clone(),
                     head_tracker,
@@ -439,3 +467,18 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> BackgroundMigrator<E, Ho
         (tx, thread)
     }
 }
+
+impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> Drop for BackgroundMigrator<E, Hot, Cold> {
+    /// Signal the migration thread to exit, in case the migrator was not shut down already.
+    ///
+    /// The thread is not joined, since dropping may happen on an async task. Waiting for an
+    /// in-progress migration is left to `shutdown`, which the client runs in the `Store` shutdown
+    /// stage.
+    fn drop(&mut self) {
+        // Dropping the sender causes the thread to exit once it has finished its current work. The
+        // thread holds its own reference to the database, so it remains open until then.
+        if let Some((tx, _thread)) = self.tx_thread.get_mut().take() {
+            drop(tx);
+        }
+    }
+}
@@ -86,6 +86,7 @@ fn full_participation_no_skips() {
     check_split_slot(&harness, store);
     check_chain_dump(&harness, num_blocks_produced + 1);
     check_iterators(&harness);
+    check_root_index(&harness);
 }
 
 #[test]
@@ -124,6 +125,7 @@ fn randomised_skips() {
     check_split_slot(&harness, store);
     check_chain_dump(&harness, num_blocks_produced + 1);
     check_iterators(&harness);
+    check_root_index(&harness);
 }
 
 #[test]
@@ -834,6 +836,15 @@ fn prunes_abandoned_fork_between_two_finalized_checkpoints() {
             "abandoned block {} should have been pruned",
             block_hash
         );
+        assert!(
+            rig.chain
+                .store
+                .get_root_index_entry(&block_hash.into())
+                .unwrap()
+                .is_none(),
+            "abandoned block {} should have been removed from the root index",
+            block_hash
+        );
     }
 
     for (&slot, &state_hash) in &stray_states {
@@ -1728,6 +1739,34 @@ fn check_chain_dump(harness: &TestHarness, expected_len: u64) {
     }
 }
 
+/// Check that the root index has the slot of every block and state in the canonical chain, and
+/// marks them canonical if and only if they have been finalized.
+fn check_root_index(harness: &TestHarness) {
+    let split_slot = harness.chain.store.get_split_slot();
+
+    for checkpoint in harness.chain.chain_dump().unwrap() {
+        let block_slot = checkpoint.beacon_block.slot();
+        let block_entry = harness
+            .chain
+            .store
+            .get_root_index_entry(&checkpoint.beacon_block_root)
+            .expect("no error")
+            .expect("block is indexed");
+        assert_eq!(block_entry.slot, block_slot);
+        assert_eq!(block_entry.canonical, block_slot < split_slot);
+
+        let state_slot = checkpoint.beacon_state.slot;
+        let state_entry = harness
+            .chain
+            .store
+            .get_root_index_entry(&checkpoint.beacon_state_root)
+            .expect("no error")
+            .expect("state is indexed");
+        assert_eq!(state_entry.slot, state_slot);
+        assert_eq!(state_entry.canonical, state_slot < split_slot);
+    }
+}
+
 /// Check that every state from the canonical chain is in the database, and that the
 /// reverse state and block root iterators reach genesis.
 fn check_iterators(harness: &TestHarness) {
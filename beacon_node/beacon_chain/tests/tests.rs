@@ -619,3 +619,26 @@ fn produces_and_processes_with_genesis_skip_slots() {
         run_skip_slot_test(i)
     }
 }
+
+#[test]
+fn invariants_hold() {
+    let mut harness = get_harness(VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        MinimalEthSpec::slots_per_epoch() as usize * 5,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let head_info = harness.chain.head_info().expect("should get head info");
+    assert!(
+        head_info.finalized_checkpoint.epoch > 0,
+        "chain should have finalized"
+    );
+    assert_eq!(
+        beacon_chain::invariants::check_invariants(&harness.chain)
+            .expect("should check invariants"),
+        vec![],
+        "no invariants should be violated"
+    );
+}
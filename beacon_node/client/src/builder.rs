@@ -1,7 +1,8 @@
 use crate::config::{ClientGenesis, Config as ClientConfig};
+use crate::invariant_checker::spawn_invariant_checker;
 use crate::notifier::spawn_notifier;
 use crate::Client;
-use beacon_chain::events::TeeEventHandler;
+use beacon_chain::events::{ServerSentEvent, TeeEventHandler};
 use beacon_chain::{
     builder::{BeaconChainBuilder, Witness},
     eth1_chain::{CachingEth1Backend, Eth1Chain},
@@ -11,7 +12,7 @@ use beacon_chain::{
     BeaconChain, BeaconChainTypes, Eth1ChainBackend, EventHandler,
 };
 use bus::Bus;
-use environment::RuntimeContext;
+use environment::{RuntimeContext, ShutdownStage};
 use eth1::{Config as Eth1Config, Service as Eth1Service};
 use eth2_config::Eth2Config;
 use eth2_libp2p::NetworkGlobals;
@@ -26,10 +27,7 @@ use std::sync::Arc;
 use std::time::Duration;
 use timer::spawn_timer;
 use tokio::sync::mpsc::UnboundedSender;
-use types::{
-    test_utils::generate_deterministic_keypairs, BeaconState, ChainSpec, EthSpec,
-    SignedBeaconBlockHash,
-};
+use types::{test_utils::generate_deterministic_keypairs, BeaconState, ChainSpec, EthSpec};
 use websocket_server::{Config as WebSocketConfig, WebSocketSender};
 
 /// Interval between polling the eth1 node for genesis information.
@@ -162,6 +160,13 @@ where
             .store_contains_beacon_chain()
             .unwrap_or_else(|_| false);
 
+        if config.api_only && !chain_exists {
+            return Err(format!(
+                "API-only mode requires an existing database, none was found in {:?}",
+                config.data_dir
+            ));
+        }
+
         // If the client is expect to resume but there's no beacon chain in the database,
         // use the `DepositContract` method. This scenario is quite common when the client
         // is shutdown before finding genesis via eth1.
@@ -235,6 +240,9 @@ where
     }
 
     /// Starts the networking stack.
+    ///
+    /// The networking stack is optional. Without it, the client does not sync and serves only the
+    /// data already in its database.
     pub async fn network(mut self, config: &NetworkConfig) -> Result<Self, String> {
         let beacon_chain = self
             .beacon_chain
@@ -246,10 +254,13 @@ where
             .ok_or_else(|| "network requires a runtime_context")?
             .clone();
 
-        let (network_globals, network_send) =
-            NetworkService::start(beacon_chain, config, context.executor)
-                .await
-                .map_err(|e| format!("Failed to start network: {:?}", e))?;
+        let (network_globals, network_send) = NetworkService::start(
+            beacon_chain,
+            config,
+            context.executor.with_shutdown_stage(ShutdownStage::Network),
+        )
+        .await
+        .map_err(|e| format!("Failed to start network: {:?}", e))?;
 
         self.network_globals = Some(network_globals);
         self.network_send = Some(network_send);
@@ -285,7 +296,7 @@ where
         mut self,
         client_config: &ClientConfig,
         eth2_config: &Eth2Config,
-        events: Arc<Mutex<Bus<SignedBeaconBlockHash>>>,
+        events: Arc<Mutex<Bus<ServerSentEvent>>>,
     ) -> Result<Self, String> {
         let beacon_chain = self
             .beacon_chain
@@ -296,22 +307,17 @@ where
             .as_ref()
             .ok_or_else(|| "http_server requires a runtime_context")?
             .service_context("http".into());
-        let network_globals = self
-            .network_globals
-            .clone()
-            .ok_or_else(|| "http_server requires a libp2p network")?;
-        let network_send = self
-            .network_send
-            .clone()
-            .ok_or_else(|| "http_server requires a libp2p network sender")?;
-
-        let network_info = rest_api::NetworkInfo {
-            network_globals,
-            network_chan: network_send,
+        // The network is optional, the endpoints which require it are unavailable without it.
+        let network_info = match (self.network_globals.clone(), self.network_send.clone()) {
+            (Some(network_globals), Some(network_chan)) => Some(rest_api::NetworkInfo {
+                network_globals,
+                network_chan,
+            }),
+            _ => None,
         };
 
         let listening_addr = rest_api::start_server(
-            context.executor,
+            context.executor.with_shutdown_stage(ShutdownStage::Api),
             &client_config.rest_api,
             beacon_chain,
             network_info,
@@ -363,6 +369,29 @@ where
         Ok(self)
     }
 
+    /// Immediately starts the service that periodically checks the invariants of the beacon chain.
+    pub fn invariant_checker(self) -> Result<Self, String> {
+        let context = self
+            .runtime_context
+            .as_ref()
+            .ok_or_else(|| "invariant_checker requires a runtime_context")?
+            .service_context("invariant_checker".into());
+        let beacon_chain = self
+            .beacon_chain
+            .clone()
+            .ok_or_else(|| "invariant_checker requires a beacon chain")?;
+        let milliseconds_per_slot = self
+            .chain_spec
+            .as_ref()
+            .ok_or_else(|| "invariant_checker requires a chain spec".to_string())?
+            .milliseconds_per_slot;
+
+        spawn_invariant_checker(context.executor, beacon_chain, milliseconds_per_slot)
+            .map_err(|e| format!("Unable to start invariant checker: {}", e))?;
+
+        Ok(self)
+    }
+
     /// Consumers the builder, returning a `Client` if all necessary components have been
     /// specified.
     ///
@@ -427,7 +456,31 @@ where
             .build()
             .map_err(|e| format!("Failed to build beacon chain: {}", e))?;
 
-        self.beacon_chain = Some(Arc::new(chain));
+        let chain = Arc::new(chain);
+
+        // Allow any in-progress database migration to complete during shutdown, once the services
+        // which write to the database have stopped.
+        let executor = self
+            .runtime_context
+            .as_ref()
+            .ok_or_else(|| "beacon_chain requires a runtime_context")?
+            .executor
+            .clone()
+            .with_shutdown_stage(ShutdownStage::Store);
+        let exit = executor.exit();
+        let migrator_chain = chain.clone();
+        let runtime_handle = executor.runtime_handle();
+        executor.spawn_without_exit(
+            async move {
+                let _ = exit.await;
+                let _ = runtime_handle
+                    .spawn_blocking(move || migrator_chain.store_migrator.shutdown())
+                    .await;
+            },
+            "store_migrator",
+        );
+
+        self.beacon_chain = Some(chain);
         self.beacon_chain_builder = None;
         self.event_handler = None;
 
@@ -461,7 +514,7 @@ where
     pub fn tee_event_handler(
         mut self,
         config: WebSocketConfig,
-    ) -> Result<(Self, Arc<Mutex<Bus<SignedBeaconBlockHash>>>), String> {
+    ) -> Result<(Self, Arc<Mutex<Bus<ServerSentEvent>>>), String> {
         let context = self
             .runtime_context
             .as_ref()
@@ -470,8 +523,10 @@ where
 
         let log = context.log().clone();
         let (sender, listening_addr): (WebSocketSender<TEthSpec>, Option<_>) = if config.enabled {
-            let (sender, listening_addr) =
-                websocket_server::start_server(context.executor, &config)?;
+            let (sender, listening_addr) = websocket_server::start_server(
+                context.executor.with_shutdown_stage(ShutdownStage::Api),
+                &config,
+            )?;
             (sender, Some(listening_addr))
         } else {
             (WebSocketSender::dummy(), None)
@@ -647,7 +702,7 @@ where
         self.eth1_service = None;
 
         // Starts the service that connects to an eth1 node and periodically updates caches.
-        backend.start(context.executor);
+        backend.start(context.executor.with_shutdown_stage(ShutdownStage::Eth1));
 
         self.beacon_chain_builder = Some(beacon_chain_builder.eth1_backend(Some(backend)));
 
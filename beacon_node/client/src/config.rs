@@ -53,6 +53,11 @@ pub struct Config {
     /// This is the method used for the 2019 client interop in Canada.
     pub dummy_eth1_backend: bool,
     pub sync_eth1_chain: bool,
+    /// If true, the invariants of the beacon chain are checked once per epoch.
+    pub invariant_checker: bool,
+    /// If true, the network is not started and the node only serves the HTTP API from an existing
+    /// database.
+    pub api_only: bool,
     /// A list of hard-coded forks that will be disabled.
     pub disabled_forks: Vec<String>,
     /// Graffiti to be inserted everytime we create a block.
@@ -85,6 +90,8 @@ impl Default for Config {
             spec_constants: TESTNET_SPEC_CONSTANTS.into(),
             dummy_eth1_backend: false,
             sync_eth1_chain: false,
+            invariant_checker: false,
+            api_only: false,
             eth1: <_>::default(),
             disabled_forks: Vec::new(),
             graffiti: Graffiti::default(),
@@ -0,0 +1,59 @@
+use beacon_chain::{invariants::check_invariants, BeaconChain, BeaconChainTypes};
+use futures::prelude::*;
+use slog::{crit, debug, error};
+use slot_clock::SlotClock;
+use std::sync::Arc;
+use std::time::Duration;
+use types::EthSpec;
+
+/// Spawns a service which checks the invariants of the `beacon_chain` once per epoch, logging a
+/// critical error for each violation found.
+///
+/// Intended for development and devnets, since the checks read the finalized state from disk.
+pub fn spawn_invariant_checker<T: BeaconChainTypes>(
+    executor: environment::TaskExecutor,
+    beacon_chain: Arc<BeaconChain<T>>,
+    milliseconds_per_slot: u64,
+) -> Result<(), String> {
+    let slot_duration = Duration::from_millis(milliseconds_per_slot);
+    let duration_to_next_slot = beacon_chain
+        .slot_clock
+        .duration_to_next_slot()
+        .ok_or_else(|| "invariant_checker unable to determine time to next slot")?;
+
+    // Run this half way through a slot, when the chain is least likely to be changing.
+    let start_instant = tokio::time::Instant::now() + duration_to_next_slot + (slot_duration / 2);
+    let interval_duration = slot_duration * T::EthSpec::slots_per_epoch() as u32;
+
+    let log = executor.log().clone();
+    let mut interval = tokio::time::interval_at(start_instant, interval_duration);
+
+    let interval_future = async move {
+        while interval.next().await.is_some() {
+            match check_invariants(&beacon_chain) {
+                Ok(violations) if violations.is_empty() => {
+                    debug!(log, "Chain invariants hold");
+                }
+                Ok(violations) => {
+                    for violation in violations {
+                        crit!(
+                            log,
+                            "Chain invariant violated";
+                            "violation" => format!("{:?}", violation),
+                            "head" => format!("{:?}", beacon_chain.head_info().ok()),
+                        );
+                    }
+                }
+                Err(e) => error!(
+                    log,
+                    "Unable to check chain invariants";
+                    "error" => format!("{:?}", e)
+                ),
+            }
+        }
+    };
+
+    executor.spawn(interval_future, "invariant_checker");
+
+    Ok(())
+}
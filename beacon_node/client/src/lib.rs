@@ -1,6 +1,7 @@
 extern crate slog;
 
 pub mod config;
+mod invariant_checker;
 mod metrics;
 mod notifier;
 
@@ -1,4 +1,6 @@
 use crate::DepositLog;
+use merkle_proof::MerkleTree;
+use serde::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
 use state_processing::common::DepositDataTree;
 use std::cmp::Ordering;
@@ -73,6 +75,23 @@ impl SszDepositCache {
     }
 }
 
+/// A snapshot of the deposit tree after its first `deposit_count` deposits, as specified by
+/// EIP-4881.
+///
+/// The tree can be rebuilt from `finalized` alone, without downloading the preceding deposits.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepositTreeSnapshot {
+    /// The roots of the largest full subtrees which cover the deposits, from left to right.
+    pub finalized: Vec<Hash256>,
+    /// The root of the deposit tree, including the mixed-in length.
+    pub deposit_root: Hash256,
+    pub deposit_count: u64,
+    /// The hash of the eth1 block at which the deposit tree had this state.
+    pub execution_block_hash: Hash256,
+    /// The number of the eth1 block at which the deposit tree had this state.
+    pub execution_block_height: u64,
+}
+
 /// Mirrors the merkle tree of deposits in the eth1 deposit contract.
 ///
 /// Provides `Deposit` objects with merkle proofs included.
@@ -243,6 +262,46 @@ impl DepositCache {
         }
     }
 
+    /// Returns the roots of the largest full subtrees which cover the first `deposit_count`
+    /// deposits (from left to right), and the deposit root after those deposits.
+    ///
+    /// These are the `finalized` and `deposit_root` fields of a `DepositTreeSnapshot`.
+    ///
+    /// ## Errors
+    ///
+    /// - If there are fewer than `deposit_count` deposits in the cache.
+    pub fn get_finalized_branch(
+        &self,
+        deposit_count: u64,
+    ) -> Result<(Vec<Hash256>, Hash256), Error> {
+        let count = deposit_count as usize;
+        let leaves = self
+            .leaves
+            .get(0..count)
+            .ok_or_else(|| Error::InsufficientDeposits {
+                requested: deposit_count,
+                known_deposits: self.logs.len(),
+            })?;
+        let deposit_root = *self
+            .deposit_roots
+            .get(count)
+            .ok_or_else(|| Error::InternalError("Unable to get known deposit root".into()))?;
+
+        // Each set bit of `count` corresponds to a full subtree, largest first.
+        let mut finalized = vec![];
+        let mut start = 0;
+        for depth in (0..=DEPOSIT_TREE_DEPTH).rev() {
+            let width = 1 << depth;
+            if count & width != 0 {
+                let subtree = MerkleTree::create(&leaves[start..start + width], depth);
+                finalized.push(subtree.hash());
+                start += width;
+            }
+        }
+
+        Ok((finalized, deposit_root))
+    }
+
     /// Returns the number of deposits with valid signatures that have been observed up to and
     /// including the block at `block_number`.
     ///
@@ -318,6 +377,43 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn finalized_branch() {
+        let mut tree = DepositCache::default();
+
+        for i in 0..7 {
+            let mut log = example_log();
+            log.index = i;
+            tree.insert_log(log).expect("should add consecutive logs")
+        }
+
+        let leaves = tree.leaves.clone();
+        let subtree_root =
+            |range: std::ops::Range<usize>, depth| MerkleTree::create(&leaves[range], depth).hash();
+
+        let (finalized, deposit_root) = tree.get_finalized_branch(0).unwrap();
+        assert!(finalized.is_empty());
+        assert_eq!(deposit_root, tree.deposit_roots[0]);
+
+        let (finalized, _) = tree.get_finalized_branch(4).unwrap();
+        assert_eq!(finalized, vec![subtree_root(0..4, 2)]);
+
+        let (finalized, deposit_root) = tree.get_finalized_branch(7).unwrap();
+        assert_eq!(
+            finalized,
+            vec![subtree_root(0..4, 2), subtree_root(4..6, 1), leaves[6]]
+        );
+        assert_eq!(deposit_root, tree.deposit_roots[7]);
+
+        assert_eq!(
+            tree.get_finalized_branch(8),
+            Err(Error::InsufficientDeposits {
+                requested: 8,
+                known_deposits: 7
+            })
+        );
+    }
+
     #[test]
     fn insert_log_invalid() {
         let mut tree = DepositCache::default();
@@ -10,6 +10,7 @@ use crate::{
 };
 use futures::{future::TryFutureExt, stream, stream::TryStreamExt, StreamExt};
 use parking_lot::{RwLock, RwLockReadGuard};
+use retry::{retry_with_backoff, RetryConfig};
 use serde::{Deserialize, Serialize};
 use slog::{debug, error, info, trace, Logger};
 use std::ops::{Range, RangeInclusive};
@@ -105,6 +106,8 @@ pub struct Config {
     pub max_log_requests_per_update: Option<usize>,
     /// The maximum number of log requests per update.
     pub max_blocks_per_update: Option<usize>,
+    /// Controls how many times a failed request to the Eth1 node is retried within an update.
+    pub retry: RetryConfig,
 }
 
 impl Default for Config {
@@ -121,6 +124,7 @@ impl Default for Config {
             blocks_per_log_query: 1_000,
             max_log_requests_per_update: None,
             max_blocks_per_update: None,
+            retry: RetryConfig::default(),
         }
     }
 }
@@ -416,6 +420,7 @@ impl Service {
         let endpoint = self.config().endpoint.clone();
         let follow_distance = self.config().follow_distance;
         let deposit_contract_address = self.config().deposit_contract_address.clone();
+        let retry = self.config().retry;
 
         let blocks_per_log_query = self.config().blocks_per_log_query;
         let max_log_requests_per_update = self
@@ -430,7 +435,8 @@ impl Service {
             .map(|n| n + 1)
             .unwrap_or_else(|| self.config().deposit_contract_deploy_block);
 
-        let range = get_new_block_numbers(&endpoint, next_required_block, follow_distance).await?;
+        let range =
+            get_new_block_numbers(&endpoint, next_required_block, follow_distance, &retry).await?;
 
         let block_number_chunks = if let Some(range) = range {
             range
@@ -452,11 +458,17 @@ impl Service {
                 match chunks.next() {
                     Some(chunk) => {
                         let chunk_1 = chunk.clone();
-                        match get_deposit_logs_in_range(
-                            &endpoint,
-                            &deposit_contract_address,
-                            chunk,
-                            Duration::from_millis(GET_DEPOSIT_LOG_TIMEOUT_MILLIS),
+                        match retry_with_backoff(
+                            &retry,
+                            |_| true,
+                            || {
+                                get_deposit_logs_in_range(
+                                    &endpoint,
+                                    &deposit_contract_address,
+                                    chunk.clone(),
+                                    Duration::from_millis(GET_DEPOSIT_LOG_TIMEOUT_MILLIS),
+                                )
+                            },
                         )
                         .await
                         {
@@ -564,8 +576,10 @@ impl Service {
 
         let endpoint = self.config().endpoint.clone();
         let follow_distance = self.config().follow_distance;
+        let retry = self.config().retry;
 
-        let range = get_new_block_numbers(&endpoint, next_required_block, follow_distance).await?;
+        let range =
+            get_new_block_numbers(&endpoint, next_required_block, follow_distance, &retry).await?;
         // Map the range of required blocks into a Vec.
         //
         // If the required range is larger than the size of the cache, drop the exiting cache
@@ -703,11 +717,15 @@ async fn get_new_block_numbers<'a>(
     endpoint: &str,
     next_required_block: u64,
     follow_distance: u64,
+    retry: &RetryConfig,
 ) -> Result<Option<RangeInclusive<u64>>, Error> {
-    let remote_highest_block =
-        get_block_number(endpoint, Duration::from_millis(BLOCK_NUMBER_TIMEOUT_MILLIS))
-            .map_err(Error::GetBlockNumberFailed)
-            .await?;
+    let remote_highest_block = retry_with_backoff(
+        retry,
+        |_| true,
+        || get_block_number(endpoint, Duration::from_millis(BLOCK_NUMBER_TIMEOUT_MILLIS)),
+    )
+    .map_err(Error::GetBlockNumberFailed)
+    .await?;
     let remote_follow_block = remote_highest_block.saturating_sub(follow_distance);
 
     if next_required_block <= remote_follow_block {
@@ -735,6 +753,7 @@ async fn get_new_block_numbers<'a>(
 /// Performs three async calls to an Eth1 HTTP JSON RPC endpoint.
 async fn download_eth1_block(cache: Arc<Inner>, block_number: u64) -> Result<Eth1Block, Error> {
     let endpoint = cache.config.read().endpoint.clone();
+    let retry = cache.config.read().retry;
 
     let deposit_root = cache
         .deposit_cache
@@ -749,10 +768,16 @@ async fn download_eth1_block(cache: Arc<Inner>, block_number: u64) -> Result<Eth
         .get_deposit_count_from_cache(block_number);
 
     // Performs a `get_blockByNumber` call to an eth1 node.
-    let http_block = get_block(
-        &endpoint,
-        block_number,
-        Duration::from_millis(GET_BLOCK_TIMEOUT_MILLIS),
+    let http_block = retry_with_backoff(
+        &retry,
+        |_| true,
+        || {
+            get_block(
+                &endpoint,
+                block_number,
+                Duration::from_millis(GET_BLOCK_TIMEOUT_MILLIS),
+            )
+        },
     )
     .map_err(Error::BlockDownloadFailed)
     .await?;
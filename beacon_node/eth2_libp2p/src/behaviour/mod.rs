@@ -22,10 +22,12 @@ use libp2p::{
     },
     PeerId,
 };
-use slog::{crit, debug, o, trace, warn};
+use lru::LruCache;
+use slog::{crit, debug, info, o, trace, warn};
 use ssz::{Decode, Encode};
 use std::fs::File;
 use std::io::{Read, Write};
+use std::net::Ipv4Addr;
 use std::path::PathBuf;
 use std::{
     collections::VecDeque,
@@ -39,6 +41,8 @@ mod handler;
 
 const MAX_IDENTIFY_ADDRESSES: usize = 10;
 const METADATA_FILENAME: &str = "metadata";
+/// The number of received gossip messages whose topic is remembered until they are validated.
+const MAX_UNVALIDATED_MESSAGE_TOPICS: usize = 4096;
 
 /// Builds the network behaviour that manages the core protocols of eth2.
 /// This core behaviour is managed by `Behaviour` which adds peer management to all core
@@ -70,6 +74,8 @@ pub struct Behaviour<TSpec: EthSpec> {
     waker: Option<std::task::Waker>,
     /// Directory where metadata is stored
     network_dir: PathBuf,
+    /// The topics of the received gossip messages awaiting validation, for the validation metrics.
+    unvalidated_message_topics: LruCache<MessageId, String>,
     /// Logger for behaviour actions.
     log: slog::Logger,
 }
@@ -120,6 +126,7 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
             enr_fork_id,
             waker: None,
             network_dir: net_conf.network_dir.clone(),
+            unvalidated_message_topics: LruCache::new(MAX_UNVALIDATED_MESSAGE_TOPICS),
             log: behaviour_log,
         })
     }
@@ -249,6 +256,7 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
             for topic in message.topics(GossipEncoding::default(), self.enr_fork_id.fork_digest) {
                 match message.encode(GossipEncoding::default()) {
                     Ok(message_data) => {
+                        metrics::inc_protocol_bytes("gossipsub", false, message_data.len());
                         if let Err(e) = self.gossipsub.publish(topic.clone().into(), message_data) {
                             slog::warn!(self.log, "Could not publish message"; "error" => format!("{:?}", e));
 
@@ -281,12 +289,27 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
 
     /// Informs the gossipsub about the result of a message validation.
     /// If the message is valid it will get propagated by gossipsub.
+    ///
+    /// Gossipsub scores the propagation source of a rejected message itself, so the peer manager
+    /// is not informed here. Faults which are unambiguously the peer's are reported separately.
     pub fn report_message_validation_result(
         &mut self,
         propagation_source: &PeerId,
         message_id: MessageId,
         validation_result: MessageAcceptance,
     ) {
+        if let Some(topic) = self.unvalidated_message_topics.pop(&message_id) {
+            let result = match validation_result {
+                MessageAcceptance::Accept => "accept",
+                MessageAcceptance::Ignore => "ignore",
+                MessageAcceptance::Reject => "reject",
+            };
+            metrics::inc_counter_vec(
+                &metrics::GOSSIPSUB_MESSAGE_VALIDATION_PER_TOPIC,
+                &[&topic, result],
+            );
+        }
+
         if let Err(e) = self.gossipsub.report_message_validation_result(
             &message_id,
             propagation_source,
@@ -351,6 +374,11 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
         self.peer_manager.discovery_mut().add_enr(enr);
     }
 
+    /// Writes the peers which are worth redialing after a restart to disk.
+    pub fn persist_known_peers(&self) {
+        self.peer_manager.persist_known_peers();
+    }
+
     /// Updates a subnet value to the ENR bitfield.
     ///
     /// The `value` is `true` if a subnet is being added and false otherwise.
@@ -366,6 +394,30 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
         self.update_metadata();
     }
 
+    /// Sets the address of the local ENR to the `ip` at which we can be reached from outside of
+    /// a NAT, along with the ports which are forwarded to our listening ports.
+    pub fn update_enr_external_address(
+        &mut self,
+        ip: Ipv4Addr,
+        tcp_port: Option<u16>,
+        udp_port: Option<u16>,
+    ) {
+        match self
+            .peer_manager
+            .discovery_mut()
+            .update_enr_external_address(ip, tcp_port, udp_port)
+        {
+            Ok(()) => info!(
+                self.log,
+                "Updated ENR address";
+                "ip" => format!("{}", ip),
+                "tcp_port" => format!("{:?}", tcp_port),
+                "udp_port" => format!("{:?}", udp_port),
+            ),
+            Err(e) => warn!(self.log, "Could not update ENR address"; "error" => e),
+        }
+    }
+
     /// Attempts to discover new peers for a given subnet. The `min_ttl` gives the time at which we
     /// would like to retain the peers for.
     pub fn discover_subnet_peers(&mut self, subnet_subscriptions: Vec<SubnetDiscovery>) {
@@ -465,19 +517,33 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
                 message_id: id,
                 message: gs_msg,
             } => {
+                let topic = gs_msg
+                    .topics
+                    .first()
+                    .and_then(|topic| GossipTopic::decode(topic.as_str()).ok())
+                    .map_or_else(|| "unknown".to_string(), |topic| topic.kind().to_string());
+                metrics::inc_counter_vec(
+                    &metrics::GOSSIPSUB_MESSAGES_RECEIVED_PER_TOPIC,
+                    &[&topic],
+                );
+                metrics::inc_protocol_bytes("gossipsub", true, gs_msg.data.len());
+                self.unvalidated_message_topics.put(id.clone(), topic);
+
                 // Note: We are keeping track here of the peer that sent us the message, not the
                 // peer that originally published the message.
                 match PubsubMessage::decode(&gs_msg.topics, &gs_msg.data) {
                     Err(e) => {
                         debug!(self.log, "Could not decode gossipsub message"; "error" => e);
+                        // A message which cannot be decoded is always the fault of the peer
+                        // which forwarded it.
+                        self.peer_manager
+                            .report_peer(&propagation_source, PeerAction::LowToleranceError);
                         //reject the message
-                        if let Err(e) = self.gossipsub.report_message_validation_result(
-                            &id,
+                        self.report_message_validation_result(
                             &propagation_source,
+                            id,
                             MessageAcceptance::Reject,
-                        ) {
-                            warn!(self.log, "Failed to report message validation"; "message_id" => id.to_string(), "peer_id" => propagation_source.to_string(), "error" => format!("{:?}", e));
-                        }
+                        );
                     }
                     Ok(msg) => {
                         // Notify the network
@@ -803,7 +869,10 @@ impl<TSpec: EthSpec> NetworkBehaviour for Behaviour<TSpec> {
         conn_id: &ConnectionId,
         endpoint: &ConnectedPoint,
     ) {
-        let goodbye_reason: Option<GoodbyeReason> = if self.peer_manager.is_banned(peer_id) {
+        let goodbye_reason: Option<GoodbyeReason> = if self.peer_manager.is_trusted(peer_id) {
+            // Trusted peers are always accepted
+            None
+        } else if self.peer_manager.is_banned(peer_id) {
             // If the peer is banned, send goodbye with reason banned.
             Some(GoodbyeReason::Banned)
         } else if self.peer_manager.peer_limit_reached()
@@ -1,3 +1,4 @@
+use crate::connection_gater::IpRange;
 use crate::types::GossipKind;
 use crate::Enr;
 use discv5::{Discv5Config, Discv5ConfigBuilder};
@@ -5,6 +6,7 @@ use libp2p::gossipsub::{
     GossipsubConfig, GossipsubConfigBuilder, GossipsubMessage, MessageId, ValidationMode,
 };
 use libp2p::Multiaddr;
+use retry::RetryConfig;
 use serde_derive::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::path::PathBuf;
@@ -41,6 +43,13 @@ pub struct Config {
     /// Target number of connected peers.
     pub target_peers: usize,
 
+    /// The number of seconds a peer is banned for before its score begins to recover.
+    pub ban_duration_secs: u64,
+
+    /// Controls how many times sync re-requests a failed batch of blocks or parent block before
+    /// giving up. Each retry is directed at another peer, so the backoff is usually zero.
+    pub sync_retry: RetryConfig,
+
     /// Gossipsub configuration parameters.
     #[serde(skip)]
     pub gs_config: GossipsubConfig,
@@ -58,12 +67,28 @@ pub struct Config {
     /// List of libp2p nodes to initially connect to.
     pub libp2p_nodes: Vec<Multiaddr>,
 
+    /// List of peers which are always dialed and never banned or disconnected for their score or
+    /// to make room for other peers. Each address ends with the `/p2p` id of the peer.
+    pub trusted_peers: Vec<Multiaddr>,
+
+    /// If not empty, connections are only made with IP addresses in these ranges.
+    pub allowed_ips: Vec<IpRange>,
+
+    /// Connections are never made with IP addresses in these ranges.
+    pub denied_ips: Vec<IpRange>,
+
+    /// The maximum number of connections with each IP address, if any.
+    pub max_connections_per_ip: Option<usize>,
+
     /// Client version
     pub client_version: String,
 
     /// Disables the discovery protocol from starting.
     pub disable_discovery: bool,
 
+    /// Attempts to map the listening ports on the local gateway with UPnP.
+    pub upnp_enabled: bool,
+
     /// List of extra topics to initially subscribe to as strings.
     pub topics: Vec<GossipKind>,
 }
@@ -138,13 +163,20 @@ impl Default for Config {
             enr_udp_port: None,
             enr_tcp_port: None,
             target_peers: 50,
+            ban_duration_secs: 1800,
+            sync_retry: RetryConfig::without_backoff(5),
             gs_config,
             discv5_config,
             boot_nodes_enr: vec![],
             boot_nodes_multiaddr: vec![],
             libp2p_nodes: vec![],
+            trusted_peers: vec![],
+            allowed_ips: vec![],
+            denied_ips: vec![],
+            max_connections_per_ip: None,
             client_version: lighthouse_version::version_with_platform(),
             disable_discovery: false,
+            upnp_enabled: false,
             topics,
         }
     }
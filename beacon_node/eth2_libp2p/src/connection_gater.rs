@@ -0,0 +1,320 @@
+//! Rejects connections to and from IP addresses which the operator has denied, or which already
+//! have too many connections, before any handshake is performed.
+//!
+//! The gater is applied to the TCP transport, so that both dials and inbound connections are
+//! checked against the resolved address of the remote.
+use crate::metrics;
+use futures::io::{AsyncRead, AsyncWrite};
+use libp2p::core::{multiaddr::Protocol, ConnectedPoint};
+use libp2p::Multiaddr;
+use parking_lot::Mutex;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use slog::debug;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// A range of IP addresses in CIDR notation, e.g. `10.0.0.0/8`. A single address is a range of
+/// one address.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IpRange {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpRange {
+    /// Returns true if `ip` is in the range. IPv4 and IPv6 addresses are never in the same range.
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = mask(self.prefix_len, 32) as u32;
+                u32::from(network) & mask == u32::from(*ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = mask(self.prefix_len, 128);
+                u128::from(network) & mask == u128::from(*ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The mask of the `prefix_len` leading bits of an address of `bits` bits.
+fn mask(prefix_len: u8, bits: u8) -> u128 {
+    if prefix_len == 0 {
+        0
+    } else {
+        (u128::max_value() << (bits - prefix_len)) & (u128::max_value() >> (128 - bits as u32))
+    }
+}
+
+impl FromStr for IpRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '/');
+        let network: IpAddr = parts
+            .next()
+            .unwrap_or_default()
+            .parse()
+            .map_err(|_| format!("Invalid IP address in range: {}", s))?;
+        let max_prefix_len = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match parts.next() {
+            Some(prefix_len) => prefix_len
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix_len| *prefix_len <= max_prefix_len)
+                .ok_or_else(|| format!("Invalid prefix length in range: {}", s))?,
+            None => max_prefix_len,
+        };
+        Ok(Self {
+            network,
+            prefix_len,
+        })
+    }
+}
+
+impl fmt::Display for IpRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix_len)
+    }
+}
+
+impl Serialize for IpRange {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for IpRange {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Returns the IP address of a multiaddr, if it starts with one.
+fn multiaddr_ip(multiaddr: &Multiaddr) -> Option<IpAddr> {
+    match multiaddr.iter().next()? {
+        Protocol::Ip4(ip) => Some(ip.into()),
+        Protocol::Ip6(ip) => Some(ip.into()),
+        _ => None,
+    }
+}
+
+/// Decides which connections are allowed, and counts the open connections of each IP address.
+pub struct ConnectionGater {
+    /// If not empty, only addresses in these ranges are allowed.
+    allowed: Vec<IpRange>,
+    /// Addresses in these ranges are never allowed.
+    denied: Vec<IpRange>,
+    /// The maximum number of connections with each IP address.
+    max_connections_per_ip: Option<usize>,
+    /// The number of open connections with each IP address.
+    connections: Mutex<HashMap<IpAddr, usize>>,
+    log: slog::Logger,
+}
+
+impl ConnectionGater {
+    pub fn new(
+        allowed: Vec<IpRange>,
+        denied: Vec<IpRange>,
+        max_connections_per_ip: Option<usize>,
+        log: slog::Logger,
+    ) -> Self {
+        Self {
+            allowed,
+            denied,
+            max_connections_per_ip,
+            connections: Mutex::new(HashMap::new()),
+            log,
+        }
+    }
+
+    /// Returns true if connections with `ip` are allowed by the allow and deny lists.
+    pub fn is_allowed(&self, ip: &IpAddr) -> bool {
+        (self.allowed.is_empty() || self.allowed.iter().any(|range| range.contains(ip)))
+            && !self.denied.iter().any(|range| range.contains(ip))
+    }
+
+    /// Accepts a new connection with the remote at `endpoint`, returning a guard which holds its
+    /// place in the connection limit of its IP address until it is dropped.
+    ///
+    /// Addresses which are not IP addresses are always accepted.
+    fn accept(
+        self: &Arc<Self>,
+        endpoint: &ConnectedPoint,
+    ) -> Result<Option<ConnectionGuard>, &'static str> {
+        let address = match endpoint {
+            ConnectedPoint::Dialer { address } => address,
+            ConnectedPoint::Listener { send_back_addr, .. } => send_back_addr,
+        };
+        let ip = match multiaddr_ip(address) {
+            Some(ip) => ip,
+            None => return Ok(None),
+        };
+
+        if !self.is_allowed(&ip) {
+            return Err("denied");
+        }
+
+        let mut connections = self.connections.lock();
+        let count = connections.entry(ip).or_default();
+        if self
+            .max_connections_per_ip
+            .map_or(false, |max| *count >= max)
+        {
+            return Err("ip_limit");
+        }
+        *count += 1;
+
+        Ok(Some(ConnectionGuard {
+            ip,
+            gater: self.clone(),
+        }))
+    }
+
+    /// Wraps `stream` if the connection is accepted, otherwise fails the connection.
+    pub fn gate<S>(
+        self: &Arc<Self>,
+        stream: S,
+        endpoint: ConnectedPoint,
+    ) -> futures::future::Ready<Result<GatedStream<S>, io::Error>> {
+        let result = match self.accept(&endpoint) {
+            Ok(guard) => Ok(GatedStream {
+                stream,
+                _guard: guard,
+            }),
+            Err(reason) => {
+                debug!(self.log, "Rejected connection"; "endpoint" => format!("{:?}", endpoint), "reason" => reason);
+                metrics::inc_counter_vec(&metrics::GATED_CONNECTIONS, &[reason]);
+                Err(io::Error::new(
+                    io::ErrorKind::ConnectionRefused,
+                    format!("Connection {}", reason),
+                ))
+            }
+        };
+        futures::future::ready(result)
+    }
+}
+
+/// Releases the place of a connection in the limit of its IP address when dropped.
+pub struct ConnectionGuard {
+    ip: IpAddr,
+    gater: Arc<ConnectionGater>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let mut connections = self.gater.connections.lock();
+        if let Some(count) = connections.get_mut(&self.ip) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                connections.remove(&self.ip);
+            }
+        }
+    }
+}
+
+/// A stream of a connection accepted by the `ConnectionGater`.
+pub struct GatedStream<S> {
+    stream: S,
+    _guard: Option<ConnectionGuard>,
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for GatedStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for GatedStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::o;
+
+    fn range(s: &str) -> IpRange {
+        s.parse().unwrap()
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn dialer(address: &str) -> ConnectedPoint {
+        ConnectedPoint::Dialer {
+            address: address.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn ip_ranges() {
+        assert!(range("10.0.0.0/8").contains(&ip("10.1.2.3")));
+        assert!(!range("10.0.0.0/8").contains(&ip("11.0.0.1")));
+        assert!(range("10.0.0.1").contains(&ip("10.0.0.1")));
+        assert!(!range("10.0.0.1").contains(&ip("10.0.0.2")));
+        assert!(range("0.0.0.0/0").contains(&ip("192.168.0.1")));
+        assert!(!range("0.0.0.0/0").contains(&ip("::1")));
+        assert!(range("2001:db8::/32").contains(&ip("2001:db8::1")));
+        assert!(!range("2001:db8::/32").contains(&ip("2001:db9::1")));
+        assert_eq!(range("10.0.0.1").to_string(), "10.0.0.1/32");
+
+        assert!("10.0.0.0/33".parse::<IpRange>().is_err());
+        assert!("10.0.0/8".parse::<IpRange>().is_err());
+    }
+
+    #[test]
+    fn allow_and_deny_lists() {
+        let log = slog::Logger::root(slog::Discard, o!());
+        let gater = ConnectionGater::new(
+            vec![range("10.0.0.0/8")],
+            vec![range("10.0.0.0/24")],
+            None,
+            log,
+        );
+
+        assert!(gater.is_allowed(&ip("10.1.0.1")));
+        assert!(!gater.is_allowed(&ip("10.0.0.1")));
+        assert!(!gater.is_allowed(&ip("192.168.0.1")));
+    }
+
+    #[test]
+    fn connection_limit_per_ip() {
+        let log = slog::Logger::root(slog::Discard, o!());
+        let gater = Arc::new(ConnectionGater::new(vec![], vec![], Some(1), log));
+
+        let guard = gater.accept(&dialer("/ip4/10.0.0.1/tcp/9000")).unwrap();
+        assert!(gater.accept(&dialer("/ip4/10.0.0.1/tcp/9001")).is_err());
+        assert!(gater.accept(&dialer("/ip4/10.0.0.2/tcp/9000")).is_ok());
+
+        drop(guard);
+        assert!(gater.accept(&dialer("/ip4/10.0.0.1/tcp/9001")).is_ok());
+    }
+}
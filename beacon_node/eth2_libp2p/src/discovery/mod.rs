@@ -20,7 +20,7 @@ use ssz::{Decode, Encode};
 use ssz_types::BitVector;
 use std::{
     collections::{HashMap, VecDeque},
-    net::SocketAddr,
+    net::{Ipv4Addr, SocketAddr},
     path::Path,
     pin::Pin,
     sync::Arc,
@@ -397,6 +397,36 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
         Ok(())
     }
 
+    /// Sets the address of our local ENR to the `ip` at which we can be reached from outside of a
+    /// NAT, along with the ports which are forwarded to our listening ports.
+    pub fn update_enr_external_address(
+        &mut self,
+        ip: Ipv4Addr,
+        tcp_port: Option<u16>,
+        udp_port: Option<u16>,
+    ) -> Result<(), String> {
+        self.discv5
+            .enr_insert("ip", ip.octets().to_vec())
+            .map_err(|e| format!("{:?}", e))?;
+        if let Some(port) = tcp_port {
+            self.discv5
+                .enr_insert("tcp", port.to_be_bytes().to_vec())
+                .map_err(|e| format!("{:?}", e))?;
+        }
+        if let Some(port) = udp_port {
+            self.discv5
+                .enr_insert("udp", port.to_be_bytes().to_vec())
+                .map_err(|e| format!("{:?}", e))?;
+        }
+
+        // replace the global version
+        *self.network_globals.local_enr.write() = self.discv5.local_enr();
+
+        // persist modified enr to disk
+        enr::save_enr_to_disk(Path::new(&self.enr_dir), &self.local_enr(), &self.log);
+        Ok(())
+    }
+
     /// Updates the `eth2` field of our local ENR.
     pub fn update_eth2_enr(&mut self, enr_fork_id: EnrForkId) {
         // to avoid having a reference to the spec constant, for the logging we assume
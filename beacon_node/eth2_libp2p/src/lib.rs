@@ -7,6 +7,7 @@ extern crate lazy_static;
 
 pub mod behaviour;
 mod config;
+mod connection_gater;
 pub mod discovery;
 mod metrics;
 mod peer_manager;
@@ -17,6 +18,7 @@ pub mod types;
 pub use crate::types::{error, Enr, GossipTopic, NetworkGlobals, PubsubMessage, SubnetDiscovery};
 pub use behaviour::{BehaviourEvent, PeerRequestId, Request, Response};
 pub use config::Config as NetworkConfig;
+pub use connection_gater::IpRange;
 pub use discovery::{CombinedKeyExt, EnrExt, Eth2Enr};
 pub use discv5;
 pub use libp2p::gossipsub::{Gossipsub, MessageAcceptance, MessageId, Topic, TopicHash};
@@ -50,6 +50,51 @@ lazy_static! {
         "Failed gossip publishes",
         &["topic_hash"]
     );
+    pub static ref PEERS_PER_CLIENT_VERSION: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "libp2p_peers_per_client_version",
+        "The connected peers via client implementation and version",
+        &["Client", "Version"]
+    );
+    pub static ref GATED_CONNECTIONS: Result<IntCounterVec> = try_create_int_counter_vec(
+        "libp2p_gated_connections_total",
+        "Count of the connections rejected by the IP allow and deny lists or per-IP limit",
+        &["reason"]
+    );
+    pub static ref PEERS_PER_DIRECTION: Result<IntGaugeVec> = try_create_int_gauge_vec(
+        "libp2p_peers_per_direction",
+        "The connected peers by the direction of their first connection",
+        &["direction"]
+    );
+    pub static ref BYTES_PER_PROTOCOL: Result<IntCounterVec> = try_create_int_counter_vec(
+        "libp2p_bytes_per_protocol_total",
+        "Count of the bytes of the gossip messages and RPC chunks sent and received per protocol",
+        &["protocol", "direction"]
+    );
+    pub static ref GOSSIPSUB_MESSAGES_RECEIVED_PER_TOPIC: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "gossipsub_messages_received_per_topic_total",
+            "Count of the gossip messages received per topic",
+            &["topic"]
+        );
+    pub static ref GOSSIPSUB_MESSAGE_VALIDATION_PER_TOPIC: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "gossipsub_message_validation_per_topic_total",
+            "Count of the results of validating the gossip messages received per topic",
+            &["topic", "result"]
+        );
+    pub static ref RPC_RESPONSE_TIME: Result<HistogramVec> = try_create_histogram_vec(
+        "libp2p_rpc_response_time_seconds",
+        "Time from sending an RPC request until its response has been received in full",
+        &["protocol"]
+    );
+}
+
+/// Adds `bytes` to the bytes received (if `inbound`) or sent over `protocol`.
+pub fn inc_protocol_bytes(protocol: &str, inbound: bool, bytes: usize) {
+    let direction = if inbound { "inbound" } else { "outbound" };
+    if let Some(counter) = get_int_counter(&BYTES_PER_PROTOCOL, &[protocol, direction]) {
+        counter.inc_by(bytes as i64);
+    }
 }
 
 pub fn scrape_discovery_metrics() {
@@ -2,9 +2,28 @@
 //!
 //! Currently using identify to fingerprint.
 
+use crate::rpc::Protocol;
 use libp2p::identify::IdentifyInfo;
 use serde::Serialize;
 
+/// A protocol which some versions of a client are known not to support, so that failing it is
+/// tolerated rather than penalised like a peer which is misbehaving.
+struct KnownUnsupported {
+    kind: ClientKind,
+    /// The versions of the client, by prefix. An empty prefix matches all versions.
+    version_prefix: &'static str,
+    protocol: Protocol,
+}
+
+const KNOWN_UNSUPPORTED_PROTOCOLS: &[KnownUnsupported] = &[
+    // Lodestar may not support ping, which is otherwise fatal.
+    KnownUnsupported {
+        kind: ClientKind::Lodestar,
+        version_prefix: "",
+        protocol: Protocol::Ping,
+    },
+];
+
 /// Various client and protocol information related to a node.
 #[derive(Clone, Debug, Serialize)]
 pub struct Client {
@@ -61,6 +80,15 @@ impl Client {
             agent_string: Some(info.agent_version.clone()),
         }
     }
+
+    /// Returns true if this version of the client is known not to support `protocol`.
+    pub fn is_known_unsupported(&self, protocol: Protocol) -> bool {
+        KNOWN_UNSUPPORTED_PROTOCOLS.iter().any(|unsupported| {
+            unsupported.kind == self.kind
+                && unsupported.protocol == protocol
+                && self.version.starts_with(unsupported.version_prefix)
+        })
+    }
 }
 
 impl std::fmt::Display for Client {
@@ -184,3 +212,35 @@ fn client_from_agent_version(agent_version: &str) -> (ClientKind, String, String
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agent_versions() {
+        let (kind, version, os_version) =
+            client_from_agent_version("Lighthouse/v0.2.8-4fd8ab48/x86_64-linux");
+        assert_eq!(kind, ClientKind::Lighthouse);
+        assert_eq!(version, "v0.2.8-4fd8ab48");
+        assert_eq!(os_version, "x86_64-linux");
+
+        let (kind, version, _) = client_from_agent_version("teku/teku/v0.12.5/linux-x86_64");
+        assert_eq!(kind, ClientKind::Teku);
+        assert_eq!(version, "v0.12.5");
+
+        let (kind, _, _) = client_from_agent_version("rust-libp2p/0.22.0");
+        assert_eq!(kind, ClientKind::Unknown);
+    }
+
+    #[test]
+    fn known_unsupported_protocols() {
+        let client = |kind| Client {
+            kind,
+            ..Client::default()
+        };
+        assert!(client(ClientKind::Lodestar).is_known_unsupported(Protocol::Ping));
+        assert!(!client(ClientKind::Lodestar).is_known_unsupported(Protocol::Status));
+        assert!(!client(ClientKind::Lighthouse).is_known_unsupported(Protocol::Ping));
+    }
+}
@@ -0,0 +1,266 @@
+//! Tracks failed dial attempts, so that unreachable peers are redialed with an exponential
+//! backoff rather than on every discovery query.
+//!
+//! The history is persisted to the network directory so that a restarted node does not
+//! immediately redial peers which were recently unreachable.
+use crate::PeerId;
+use slog::{debug, warn};
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The file in the network directory which holds the dial history.
+pub const DIAL_HISTORY_FILENAME: &str = "dial_history";
+/// The time we wait before redialing a peer after its first failed dial.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(30);
+/// The longest time we wait before redialing a peer.
+const MAX_BACKOFF: Duration = Duration::from_secs(3600);
+/// A peer's failures are forgotten once it has been dialable for this long without being dialed.
+const FORGET_AFTER: Duration = Duration::from_secs(3600);
+
+/// The failed dials of a single peer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Backoff {
+    /// The number of consecutive failed dials.
+    failures: u32,
+    /// The peer is not dialed again before this time.
+    retry_at: Instant,
+}
+
+#[derive(Default)]
+pub struct DialHistory {
+    backoffs: HashMap<PeerId, Backoff>,
+    /// True if the history has changed since it was last persisted.
+    dirty: bool,
+}
+
+impl DialHistory {
+    /// Returns true if the peer may be dialed, i.e it is not waiting out a backoff.
+    pub fn can_dial(&self, peer_id: &PeerId) -> bool {
+        self.backoffs
+            .get(peer_id)
+            .map_or(true, |backoff| backoff.retry_at <= Instant::now())
+    }
+
+    /// Records a failed dial, doubling the time before the peer is dialed again.
+    ///
+    /// Returns the time until the peer may be redialed.
+    pub fn dial_failed(&mut self, peer_id: &PeerId) -> Duration {
+        let failures = self
+            .backoffs
+            .get(peer_id)
+            .map_or(1, |backoff| backoff.failures.saturating_add(1));
+        let delay = backoff_delay(failures);
+
+        self.backoffs.insert(
+            peer_id.clone(),
+            Backoff {
+                failures,
+                retry_at: Instant::now() + delay,
+            },
+        );
+        self.dirty = true;
+        delay
+    }
+
+    /// Forgets the failures of a peer which has connected.
+    pub fn connected(&mut self, peer_id: &PeerId) {
+        if self.backoffs.remove(peer_id).is_some() {
+            self.dirty = true;
+        }
+    }
+
+    /// Forgets the failures of peers which have been dialable for a long time.
+    pub fn prune(&mut self) {
+        let now = Instant::now();
+        let len = self.backoffs.len();
+        self.backoffs
+            .retain(|_, backoff| backoff.retry_at + FORGET_AFTER > now);
+        if self.backoffs.len() != len {
+            self.dirty = true;
+        }
+    }
+
+    /// Loads the history from `network_dir`, or returns an empty history if it does not exist or
+    /// cannot be decoded.
+    pub fn load(network_dir: &Path, log: &slog::Logger) -> Self {
+        let path = network_dir.join(DIAL_HISTORY_FILENAME);
+        let mut bytes = vec![];
+        if File::open(&path)
+            .and_then(|mut file| file.read_to_end(&mut bytes))
+            .is_err()
+        {
+            return Self::default();
+        }
+
+        match PersistedDialHistory::from_ssz_bytes(&bytes) {
+            Ok(persisted) => {
+                let history = Self::from_persisted(persisted, SystemTime::now(), Instant::now());
+                debug!(log, "Loaded dial history from disk"; "backed_off_peers" => history.backoffs.len());
+                history
+            }
+            Err(e) => {
+                debug!(
+                    log,
+                    "Dial history from file could not be decoded";
+                    "error" => format!("{:?}", e),
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Writes the history to `network_dir` if it has changed since it was last written.
+    pub fn persist(&mut self, network_dir: &Path, log: &slog::Logger) {
+        if !self.dirty {
+            return;
+        }
+
+        let bytes = self
+            .to_persisted(SystemTime::now(), Instant::now())
+            .as_ssz_bytes();
+        let _ = std::fs::create_dir_all(network_dir);
+        match File::create(network_dir.join(DIAL_HISTORY_FILENAME))
+            .and_then(|mut f| f.write_all(&bytes))
+        {
+            Ok(_) => self.dirty = false,
+            Err(e) => {
+                warn!(
+                    log,
+                    "Could not write dial history to disk";
+                    "file" => format!("{:?}{:?}", network_dir, DIAL_HISTORY_FILENAME),
+                    "error" => format!("{}", e)
+                );
+            }
+        }
+    }
+
+    fn to_persisted(&self, system_now: SystemTime, now: Instant) -> PersistedDialHistory {
+        let unix_now = system_now
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_else(|_| Duration::from_secs(0));
+
+        PersistedDialHistory {
+            peers: self
+                .backoffs
+                .iter()
+                .map(|(peer_id, backoff)| {
+                    let retry_at = unix_now + backoff.retry_at.saturating_duration_since(now);
+                    PersistedBackoff {
+                        peer_id: peer_id.as_bytes().to_vec(),
+                        failures: u64::from(backoff.failures),
+                        retry_at_unix_secs: retry_at.as_secs(),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    fn from_persisted(
+        persisted: PersistedDialHistory,
+        system_now: SystemTime,
+        now: Instant,
+    ) -> Self {
+        let unix_now = system_now
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_else(|_| Duration::from_secs(0));
+
+        let backoffs = persisted
+            .peers
+            .into_iter()
+            .filter_map(|persisted| {
+                let peer_id = PeerId::from_bytes(persisted.peer_id).ok()?;
+                let remaining = Duration::from_secs(persisted.retry_at_unix_secs)
+                    .checked_sub(unix_now)
+                    .unwrap_or_else(|| Duration::from_secs(0));
+                let backoff = Backoff {
+                    failures: persisted.failures.min(u64::from(u32::max_value())) as u32,
+                    retry_at: now + std::cmp::min(remaining, MAX_BACKOFF),
+                };
+                Some((peer_id, backoff))
+            })
+            .collect();
+
+        Self {
+            backoffs,
+            dirty: false,
+        }
+    }
+}
+
+/// The time to wait before redialing a peer which has failed `failures` consecutive dials.
+fn backoff_delay(failures: u32) -> Duration {
+    let exponent = failures.saturating_sub(1).min(16);
+    std::cmp::min(INITIAL_BACKOFF * 2_u32.pow(exponent), MAX_BACKOFF)
+}
+
+/// The on-disk form of `DialHistory`, which replaces each `Instant` with a unix timestamp.
+#[derive(Debug, PartialEq, Encode, Decode)]
+struct PersistedDialHistory {
+    peers: Vec<PersistedBackoff>,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+struct PersistedBackoff {
+    peer_id: Vec<u8>,
+    failures: u64,
+    retry_at_unix_secs: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        assert_eq!(backoff_delay(1), INITIAL_BACKOFF);
+        assert_eq!(backoff_delay(2), INITIAL_BACKOFF * 2);
+        assert_eq!(backoff_delay(3), INITIAL_BACKOFF * 4);
+        assert_eq!(backoff_delay(8), MAX_BACKOFF);
+        assert_eq!(backoff_delay(u32::max_value()), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn failed_peers_are_not_dialed() {
+        let mut history = DialHistory::default();
+        let peer_id = PeerId::random();
+
+        assert!(history.can_dial(&peer_id));
+        assert_eq!(history.dial_failed(&peer_id), INITIAL_BACKOFF);
+        assert!(!history.can_dial(&peer_id));
+        assert_eq!(history.dial_failed(&peer_id), INITIAL_BACKOFF * 2);
+
+        history.connected(&peer_id);
+        assert!(history.can_dial(&peer_id));
+        assert_eq!(history.dial_failed(&peer_id), INITIAL_BACKOFF);
+    }
+
+    #[test]
+    fn persisted_round_trip() {
+        let mut history = DialHistory::default();
+        let peer_id = PeerId::random();
+        history.dial_failed(&peer_id);
+        history.dial_failed(&peer_id);
+
+        let system_now = SystemTime::now();
+        let now = Instant::now();
+        let bytes = history.to_persisted(system_now, now).as_ssz_bytes();
+        let decoded = PersistedDialHistory::from_ssz_bytes(&bytes).unwrap();
+
+        // The node restarts a minute later.
+        let restored = DialHistory::from_persisted(
+            decoded,
+            system_now + Duration::from_secs(60),
+            now + Duration::from_secs(60),
+        );
+
+        let backoff = restored.backoffs[&peer_id];
+        assert_eq!(backoff.failures, 2);
+        assert!(backoff.retry_at <= history.backoffs[&peer_id].retry_at);
+        assert!(backoff.retry_at + Duration::from_secs(1) >= history.backoffs[&peer_id].retry_at);
+    }
+}
@@ -0,0 +1,181 @@
+//! Persists the peers that we were on good terms with, so that a restarted node can redial them
+//! rather than waiting on discovery to find peers again.
+use super::peerdb::PeerDB;
+use crate::{Multiaddr, PeerId};
+use libp2p::core::multiaddr::Protocol;
+use slog::{debug, warn};
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use types::EthSpec;
+
+/// The file in the network directory which holds the known peers.
+pub const KNOWN_PEERS_FILENAME: &str = "known_peers";
+/// The maximum number of peers which are persisted.
+const MAX_KNOWN_PEERS: usize = 100;
+
+/// Loads the known peers from `network_dir`, best scored first, returning the address at which
+/// each should be dialed.
+///
+/// Returns an empty list if the file does not exist or cannot be decoded.
+pub fn load_known_peers(network_dir: &Path, log: &slog::Logger) -> Vec<(PeerId, Multiaddr)> {
+    let path = network_dir.join(KNOWN_PEERS_FILENAME);
+    let mut bytes = vec![];
+    if File::open(&path)
+        .and_then(|mut file| file.read_to_end(&mut bytes))
+        .is_err()
+    {
+        return vec![];
+    }
+
+    match PersistedKnownPeers::from_ssz_bytes(&bytes) {
+        Ok(persisted) => {
+            let peers = persisted.into_dial_addresses();
+            debug!(log, "Loaded known peers from disk"; "peers" => peers.len());
+            peers
+        }
+        Err(e) => {
+            debug!(
+                log,
+                "Known peers from file could not be decoded";
+                "error" => format!("{:?}", e),
+            );
+            vec![]
+        }
+    }
+}
+
+/// Writes the peers of `peers` which are worth redialing to `network_dir`.
+pub fn persist_known_peers<TSpec: EthSpec>(
+    peers: &PeerDB<TSpec>,
+    network_dir: &Path,
+    log: &slog::Logger,
+) {
+    let persisted = PersistedKnownPeers::from_peer_db(peers);
+    let bytes = persisted.as_ssz_bytes();
+    let _ = std::fs::create_dir_all(network_dir);
+    match File::create(network_dir.join(KNOWN_PEERS_FILENAME)).and_then(|mut f| f.write_all(&bytes))
+    {
+        Ok(_) => debug!(log, "Saved known peers to disk"; "peers" => persisted.peers.len()),
+        Err(e) => {
+            warn!(
+                log,
+                "Could not write known peers to disk";
+                "file" => format!("{:?}{:?}", network_dir, KNOWN_PEERS_FILENAME),
+                "error" => format!("{}", e)
+            );
+        }
+    }
+}
+
+/// Returns true if `multiaddr` can be dialed, i.e it is a TCP address.
+fn is_dialable(multiaddr: &Multiaddr) -> bool {
+    multiaddr
+        .iter()
+        .any(|proto| matches!(proto, Protocol::Tcp(_)))
+}
+
+/// The on-disk form of the known peers.
+#[derive(Debug, PartialEq, Encode, Decode)]
+struct PersistedKnownPeers {
+    peers: Vec<PersistedPeer>,
+}
+
+#[derive(Debug, PartialEq, Encode, Decode)]
+struct PersistedPeer {
+    peer_id: Vec<u8>,
+    /// The bytes of the multiaddr at which the peer was listening.
+    address: Vec<u8>,
+    /// The bits of the peer's `f64` score.
+    score: u64,
+}
+
+impl PersistedKnownPeers {
+    /// Selects the peers which are not banned, do not have a negative score and have a dialable
+    /// listening address, keeping the `MAX_KNOWN_PEERS` with the best scores.
+    fn from_peer_db<TSpec: EthSpec>(peers: &PeerDB<TSpec>) -> Self {
+        let mut known = peers
+            .peers()
+            .filter(|(peer_id, info)| !peers.is_banned(peer_id) && info.score.score() >= 0.0)
+            .filter_map(|(peer_id, info)| {
+                let address = info.listening_addresses.iter().find(|a| is_dialable(a))?;
+                Some((peer_id, address, info.score.score()))
+            })
+            .collect::<Vec<_>>();
+
+        known.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(Ordering::Equal));
+        known.truncate(MAX_KNOWN_PEERS);
+
+        Self {
+            peers: known
+                .into_iter()
+                .map(|(peer_id, address, score)| PersistedPeer {
+                    peer_id: peer_id.as_bytes().to_vec(),
+                    address: address.to_vec(),
+                    score: score.to_bits(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Decodes the peers, best scored first, skipping any which are invalid.
+    fn into_dial_addresses(self) -> Vec<(PeerId, Multiaddr)> {
+        let mut peers = self
+            .peers
+            .into_iter()
+            .filter_map(|persisted| {
+                let peer_id = PeerId::from_bytes(persisted.peer_id).ok()?;
+                let address = Multiaddr::try_from(persisted.address).ok()?;
+                Some((peer_id, address, f64::from_bits(persisted.score)))
+            })
+            .filter(|(_, address, _)| is_dialable(address))
+            .collect::<Vec<_>>();
+
+        peers.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(Ordering::Equal));
+        peers
+            .into_iter()
+            .map(|(peer_id, address, _)| (peer_id, address))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::o;
+    use types::MinimalEthSpec;
+
+    fn add_peer(db: &mut PeerDB<MinimalEthSpec>, address: &str, score: f64) -> (PeerId, Multiaddr) {
+        let peer_id = PeerId::random();
+        let address: Multiaddr = address.parse().unwrap();
+        db.connect_ingoing(&peer_id);
+        let info = db.peer_info_mut(&peer_id).unwrap();
+        info.listening_addresses = vec![address.clone()];
+        info.score.add(score);
+        (peer_id, address)
+    }
+
+    #[test]
+    fn persists_good_peers_best_first() {
+        let log = slog::Logger::root(slog::Discard, o!());
+        let mut db = PeerDB::<MinimalEthSpec>::new(&log);
+
+        let good = add_peer(&mut db, "/ip4/10.0.0.1/tcp/9000", 0.0);
+        let best = add_peer(&mut db, "/ip4/10.0.0.2/tcp/9000", 10.0);
+        // Peers with a negative score, or without a TCP address, are not persisted.
+        add_peer(&mut db, "/ip4/10.0.0.3/tcp/9000", -10.0);
+        add_peer(&mut db, "/ip4/10.0.0.4/udp/9000", 5.0);
+        // Banned peers are not persisted.
+        let (banned, _) = add_peer(&mut db, "/ip4/10.0.0.5/tcp/9000", 5.0);
+        db.ban(&banned);
+
+        let bytes = PersistedKnownPeers::from_peer_db(&db).as_ssz_bytes();
+        let decoded = PersistedKnownPeers::from_ssz_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded.into_dial_addresses(), vec![best, good]);
+    }
+}
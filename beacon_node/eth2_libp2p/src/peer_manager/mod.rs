@@ -14,6 +14,7 @@ use slog::{crit, debug, error};
 use smallvec::SmallVec;
 use std::{
     net::SocketAddr,
+    path::PathBuf,
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
@@ -24,11 +25,15 @@ use types::EthSpec;
 pub use libp2p::core::{identity::Keypair, Multiaddr};
 
 pub mod client;
+mod dial_history;
+mod known_peers;
 mod peer_info;
 mod peer_sync_status;
 mod peerdb;
 pub(crate) mod score;
 
+use dial_history::DialHistory;
+pub use known_peers::load_known_peers;
 pub use peer_info::{PeerConnectionStatus::*, PeerInfo};
 pub use peer_sync_status::{PeerSyncStatus, SyncInfo};
 use score::{PeerAction, ScoreState};
@@ -62,6 +67,15 @@ pub struct PeerManager<TSpec: EthSpec> {
     target_peers: usize,
     /// The maximum number of peers we allow (exceptions for subnet peers)
     max_peers: usize,
+    /// How long a banned peer remains banned before its score begins to decay.
+    ban_duration: Duration,
+    /// The failed dials of each peer, used to back off from unreachable peers.
+    dial_history: DialHistory,
+    /// The directory in which the dial history and known peers are persisted.
+    network_dir: PathBuf,
+    /// The peers which are always dialed and never banned or pruned, with the address at which
+    /// each is dialed.
+    trusted_peers: HashMap<PeerId, Multiaddr>,
     /// The discovery service.
     discovery: Discovery<TSpec>,
     /// The heartbeat interval to perform routine maintenance.
@@ -102,6 +116,8 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
 
         let heartbeat = tokio::time::interval(tokio::time::Duration::from_secs(HEARTBEAT_INTERVAL));
 
+        let trusted_peers = parse_trusted_peers(&config.trusted_peers, log);
+
         Ok(PeerManager {
             network_globals,
             events: SmallVec::new(),
@@ -109,6 +125,10 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             status_peers: HashSetDelay::new(Duration::from_secs(STATUS_INTERVAL)),
             target_peers: config.target_peers,
             max_peers: (config.target_peers as f32 * (1.0 + PEER_EXCESS_FACTOR)).ceil() as usize,
+            ban_duration: Duration::from_secs(config.ban_duration_secs),
+            dial_history: DialHistory::load(&config.network_dir, log),
+            network_dir: config.network_dir.clone(),
+            trusted_peers,
             discovery,
             heartbeat,
             log: log.clone(),
@@ -119,12 +139,41 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
 
     /// Attempts to connect to a peer.
     ///
+    /// The peer is not dialed if it is already connected or being dialed, or if it is backing off
+    /// from failed dials and is not trusted.
+    ///
     /// Returns true if the peer was accepted into the database.
     pub fn dial_peer(&mut self, peer_id: &PeerId) -> bool {
+        if self
+            .network_globals
+            .peers
+            .read()
+            .is_connected_or_dialing(peer_id)
+        {
+            return false;
+        }
+        if !self.is_trusted(peer_id) && !self.dial_history.can_dial(peer_id) {
+            debug!(self.log, "Not dialing peer during backoff"; "peer_id" => peer_id.to_string());
+            return false;
+        }
         self.events.push(PeerManagerEvent::Dial(peer_id.clone()));
         self.connect_peer(peer_id, ConnectingType::Dialing)
     }
 
+    /// Reports if a peer is trusted, in which case it is never banned or pruned.
+    pub fn is_trusted(&self, peer_id: &PeerId) -> bool {
+        self.trusted_peers.contains_key(peer_id)
+    }
+
+    /// Writes the peers which are worth redialing after a restart to the network directory.
+    pub fn persist_known_peers(&self) {
+        known_peers::persist_known_peers(
+            &self.network_globals.peers.read(),
+            &self.network_dir,
+            &self.log,
+        );
+    }
+
     /// The application layer wants to disconnect from a peer for a particular reason.
     ///
     /// All instant disconnections are fatal and we ban the associated peer.
@@ -132,10 +181,13 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
     /// This will send a goodbye and disconnect the peer if it is connected or dialing.
     pub fn goodbye_peer(&mut self, peer_id: &PeerId, reason: GoodbyeReason) {
         // get the peer info
+        let trusted = self.is_trusted(peer_id);
         if let Some(info) = self.network_globals.peers.write().peer_info_mut(peer_id) {
             debug!(self.log, "Sending goodbye to peer"; "peer_id" => peer_id.to_string(), "reason" => reason.to_string(), "score" => info.score.to_string());
-            // Goodbye's are fatal
-            info.score.apply_peer_action(PeerAction::Fatal);
+            // Goodbye's are fatal, except to trusted peers which are redialed
+            if !trusted {
+                info.score.apply_peer_action(PeerAction::Fatal);
+            }
             if info.connection_status.is_connected_or_dialing() {
                 self.events
                     .push(PeerManagerEvent::DisconnectPeer(peer_id.clone(), reason));
@@ -146,7 +198,14 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
     /// Reports a peer for some action.
     ///
     /// If the peer doesn't exist, log a warning and insert defaults.
+    ///
+    /// Reports of trusted peers are ignored, so that they are never banned or disconnected.
     pub fn report_peer(&mut self, peer_id: &PeerId, action: PeerAction) {
+        if self.is_trusted(peer_id) {
+            debug!(self.log, "Ignoring report of trusted peer"; "peer_id" => peer_id.to_string(), "action" => action.to_string());
+            return;
+        }
+
         // TODO: Remove duplicate code  - This is duplicated in the update_peer_scores()
         // function.
 
@@ -266,10 +325,7 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         self.ping_peers.remove(peer_id);
         self.status_peers.remove(peer_id);
         metrics::inc_counter(&metrics::PEER_DISCONNECT_EVENT_COUNT);
-        metrics::set_gauge(
-            &metrics::PEERS_CONNECTED,
-            self.network_globals.connected_peers() as i64,
-        );
+        self.update_connected_peer_metrics();
     }
 
     /// A dial attempt has failed.
@@ -279,6 +335,8 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
     /// the peer is not already connected.
     pub fn notify_dial_failure(&mut self, peer_id: &PeerId) {
         if !self.network_globals.peers.read().is_connected(peer_id) {
+            let backoff = self.dial_history.dial_failed(peer_id);
+            debug!(self.log, "Backing off from failed peer"; "peer_id" => peer_id.to_string(), "retry_in_secs" => backoff.as_secs());
             self.notify_disconnect(peer_id);
         }
     }
@@ -339,6 +397,7 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         } else {
             crit!(self.log, "Received an Identify response from an unknown peer"; "peer_id" => peer_id.to_string());
         }
+        self.update_connected_peer_metrics();
     }
 
     /// An error has occured in the RPC.
@@ -382,6 +441,10 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
                 // information could be stored and used to prevent sending requests for the given
                 // protocol to this peer. Similarly, to avoid blacklisting a peer for a protocol
                 // forever, if stored this information should expire.
+                if client.is_known_unsupported(protocol) {
+                    debug!(self.log, "Tolerating protocol known to be unsupported by client"; "protocol" => protocol.to_string(), "client" => client.to_string(), "peer_id" => peer_id.to_string());
+                    return;
+                }
                 match protocol {
                     Protocol::Ping => PeerAction::Fatal,
                     Protocol::BlocksByRange => return,
@@ -497,7 +560,10 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
 
     // Handles the libp2p request to obtain multiaddrs for peer_id's in order to dial them.
     pub fn addresses_of_peer(&mut self, peer_id: &PeerId) -> Vec<Multiaddr> {
-        if let Some(enr) = self.discovery.enr_of_peer(peer_id) {
+        if let Some(address) = self.trusted_peers.get(peer_id) {
+            // the address of a trusted peer is given by the user
+            vec![address.clone()]
+        } else if let Some(enr) = self.discovery.enr_of_peer(peer_id) {
             // ENR's may have multiple Multiaddrs. The multi-addr associated with the UDP
             // port is removed, which is assumed to be associated with the discv5 protocol (and
             // therefore irrelevant for other libp2p components).
@@ -555,6 +621,7 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
                     .peers
                     .read()
                     .is_banned_or_disconnected(&peer_id)
+                && self.dial_history.can_dial(&peer_id)
             {
                 // TODO: Update output
                 // This should be updated with the peer dialing. In fact created once the peer is
@@ -593,8 +660,8 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
                     peerdb.dialing_peer(peer_id);
                     return true;
                 }
-                ConnectingType::IngoingConnected => peerdb.connect_outgoing(peer_id),
-                ConnectingType::OutgoingConnected => peerdb.connect_ingoing(peer_id),
+                ConnectingType::IngoingConnected => peerdb.connect_ingoing(peer_id),
+                ConnectingType::OutgoingConnected => peerdb.connect_outgoing(peer_id),
             }
         }
 
@@ -602,12 +669,12 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         self.ping_peers.insert(peer_id.clone());
         self.status_peers.insert(peer_id.clone());
 
+        // the peer is reachable, so forget any failed dials
+        self.dial_history.connected(peer_id);
+
         // increment prometheus metrics
         metrics::inc_counter(&metrics::PEER_CONNECT_EVENT_COUNT);
-        metrics::set_gauge(
-            &metrics::PEERS_CONNECTED,
-            self.network_globals.connected_peers() as i64,
-        );
+        self.update_connected_peer_metrics();
 
         // Increment the PEERS_PER_CLIENT metric
         if let Some(kind) = self
@@ -627,12 +694,46 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         true
     }
 
+    /// Sets the metrics of the number of connected peers, in total, by the direction of their
+    /// connections and by client version. A peer we have dialed counts as outbound, even if it has
+    /// also dialed us.
+    fn update_connected_peer_metrics(&self) {
+        let peers = self.network_globals.peers.read();
+        let (mut inbound, mut outbound) = (0, 0);
+        let mut per_version = HashMap::new();
+        for (_, info) in peers.connected_peers() {
+            match info.connection_status.connections() {
+                (_, 0) => inbound += 1,
+                _ => outbound += 1,
+            }
+            *per_version
+                .entry((info.client.kind.to_string(), info.client.version.clone()))
+                .or_insert(0) += 1;
+        }
+        metrics::set_gauge(&metrics::PEERS_CONNECTED, inbound + outbound);
+        metrics::set_int_gauge(&metrics::PEERS_PER_DIRECTION, &["inbound"], inbound);
+        metrics::set_int_gauge(&metrics::PEERS_PER_DIRECTION, &["outbound"], outbound);
+
+        // versions come and go, so the counts are rebuilt rather than updated
+        if let Ok(gauge_vec) = &*metrics::PEERS_PER_CLIENT_VERSION {
+            gauge_vec.reset();
+        }
+        for ((kind, version), count) in per_version {
+            metrics::set_int_gauge(
+                &metrics::PEERS_PER_CLIENT_VERSION,
+                &[&kind, &version],
+                count,
+            );
+        }
+    }
+
     /// Updates the scores of known peers according to their connection
     /// status and the time that has passed.
     /// NOTE: This is experimental and will likely be adjusted
     fn update_peer_scores(&mut self) {
         /* Check how long have peers been in this state and update their reputations if needed */
         let mut pdb = self.network_globals.peers.write();
+        let ban_duration = self.ban_duration;
 
         let mut to_ban_peers = Vec::new();
         let mut to_unban_peers = Vec::new();
@@ -640,7 +741,7 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         for (peer_id, info) in pdb.peers_mut() {
             let previous_state = info.score.state();
             // Update scores
-            info.score.update();
+            info.score.update(ban_duration);
 
             /* TODO: Implement logic about connection lifetimes
             match info.connection_status {
@@ -754,19 +855,34 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             self.discovery.discover_peers();
         }
 
+        // Redial the trusted peers which are not connected.
+        let trusted_peers = self.trusted_peers.keys().cloned().collect::<Vec<_>>();
+        for peer_id in trusted_peers {
+            if self.dial_peer(&peer_id) {
+                debug!(self.log, "Dialing trusted peer"; "peer_id" => peer_id.to_string());
+            }
+        }
+
         // Updates peer's scores.
         self.update_peer_scores();
 
+        // Forget old dial failures and persist the remainder.
+        self.dial_history.prune();
+        self.dial_history.persist(&self.network_dir, &self.log);
+
         let connected_peer_count = self.network_globals.connected_peers();
         if connected_peer_count > self.target_peers {
-            //remove excess peers with the worst scores, but keep subnet peers
+            let trusted_peers = &self.trusted_peers;
+            //remove excess peers with the worst scores, but keep subnet and trusted peers
             for (peer_id, _) in self
                 .network_globals
                 .peers
                 .read()
                 .worst_connected_peers()
                 .iter()
-                .filter(|(_, info)| !info.has_future_duty())
+                .filter(|(peer_id, info)| {
+                    !info.has_future_duty() && !trusted_peers.contains_key(*peer_id)
+                })
                 .take(connected_peer_count - self.target_peers)
                 //we only need to disconnect peers with healthy scores, since the others got already
                 //disconnected in update_peer_scores
@@ -847,3 +963,64 @@ enum ConnectingType {
     /// We have successfully dialed a peer.
     OutgoingConnected,
 }
+
+/// Maps the peer id at the end of each trusted peer's address to the address it is dialed on.
+///
+/// Addresses without a valid peer id are logged and skipped.
+fn parse_trusted_peers(addresses: &[Multiaddr], log: &slog::Logger) -> HashMap<PeerId, Multiaddr> {
+    let mut trusted_peers = HashMap::new();
+    for address in addresses {
+        let mut address = address.clone();
+        match address.pop() {
+            Some(MProtocol::P2p(multihash)) => match PeerId::from_multihash(multihash) {
+                Ok(peer_id) => {
+                    trusted_peers.insert(peer_id, address);
+                }
+                Err(_) => {
+                    error!(log, "Invalid peer id of trusted peer"; "address" => address.to_string())
+                }
+            },
+            _ => {
+                error!(log, "Trusted peer is missing its peer id"; "address" => address.to_string())
+            }
+        }
+    }
+    trusted_peers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::o;
+
+    #[test]
+    fn trusted_peers_are_dialed_without_their_peer_id() {
+        let log = slog::Logger::root(slog::Discard, o!());
+        let peer_id = PeerId::random();
+        let address: Multiaddr = "/ip4/10.0.0.1/tcp/9000".parse().unwrap();
+        let with_id = address.clone().with(MProtocol::P2p(peer_id.clone().into()));
+
+        let trusted_peers = parse_trusted_peers(&[with_id], &log);
+
+        assert_eq!(trusted_peers.len(), 1);
+        assert_eq!(trusted_peers.get(&peer_id), Some(&address));
+    }
+
+    #[test]
+    fn trusted_peers_without_a_peer_id_are_skipped() {
+        let log = slog::Logger::root(slog::Discard, o!());
+        let peer_id = PeerId::random();
+        let addresses: Vec<Multiaddr> = vec![
+            "/ip4/10.0.0.1/tcp/9000".parse().unwrap(),
+            "/ip4/10.0.0.2/tcp/9000"
+                .parse::<Multiaddr>()
+                .unwrap()
+                .with(MProtocol::P2p(peer_id.clone().into())),
+        ];
+
+        let trusted_peers = parse_trusted_peers(&addresses, &log);
+
+        assert_eq!(trusted_peers.len(), 1);
+        assert!(trusted_peers.contains_key(&peer_id));
+    }
+}
@@ -6,7 +6,7 @@
 //!
 //! The scoring algorithms are currently experimental.
 use serde::Serialize;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 lazy_static! {
     static ref HALFLIFE_DECAY: f64 = -(2.0f64.ln()) / SCORE_HALFLIFE;
@@ -24,8 +24,6 @@ const MAX_SCORE: f64 = 100.0;
 const MIN_SCORE: f64 = -100.0;
 /// The halflife of a peer's score. I.e the number of seconds it takes for the score to decay to half its value.
 const SCORE_HALFLIFE: f64 = 600.0;
-/// The number of seconds we ban a peer for before their score begins to decay.
-const BANNED_BEFORE_DECAY: u64 = 1800;
 
 /// A collection of actions a peer can perform which will adjust its score.
 /// Each variant has an associated score change.
@@ -153,7 +151,7 @@ impl Score {
     /// Modifies the score based on a peer's action.
     pub fn apply_peer_action(&mut self, peer_action: PeerAction) {
         match peer_action {
-            PeerAction::Fatal => self.score = MIN_SCORE, // The worst possible score
+            PeerAction::Fatal => self.add(MIN_SCORE - MAX_SCORE), // The worst possible score
             PeerAction::LowToleranceError => self.add(-10.0),
             PeerAction::MidToleranceError => self.add(-5.0),
             PeerAction::HighToleranceError => self.add(-1.0),
@@ -180,28 +178,34 @@ impl Score {
             new_score = MIN_SCORE;
         }
 
+        // The ban starts when the score first falls below the threshold.
+        if new_score <= MIN_SCORE_BEFORE_BAN && self.score > MIN_SCORE_BEFORE_BAN {
+            self.last_updated = Instant::now();
+        }
+
         self.score = new_score;
     }
 
     /// Applies time-based logic such as decay rates to the score.
     /// This function should be called periodically.
-    pub fn update(&mut self) {
+    ///
+    /// A banned peer's score does not decay until `ban_duration` has passed since it was banned.
+    pub fn update(&mut self, ban_duration: Duration) {
         // Apply decay logic
         //
         // There is two distinct decay processes. One for banned peers and one for all others. If
-        // the score is below the banning threshold and the duration since it was last update is
-        // shorter than the banning threshold, we do nothing.
+        // the score is below the banning threshold and the ban started less than `ban_duration`
+        // ago, we do nothing.
         let now = Instant::now();
-        if self.score <= MIN_SCORE_BEFORE_BAN
-            && now
-                .checked_duration_since(self.last_updated)
-                .map(|d| d.as_secs())
-                <= Some(BANNED_BEFORE_DECAY)
-        {
-            // The peer is banned and still within the ban timeout. Do not update it's score.
-            // Update last_updated so that the decay begins correctly when ready.
-            self.last_updated = now;
-            return;
+        if self.score <= MIN_SCORE_BEFORE_BAN {
+            match now.checked_duration_since(self.last_updated) {
+                // The peer is banned and still within the ban timeout. Do not update its score,
+                // nor `last_updated`, which records the start of the ban.
+                Some(banned_for) if banned_for <= ban_duration => return,
+                // The ban has ended, the score decays from the end of the ban.
+                Some(_) => self.last_updated += ban_duration,
+                None => return,
+            }
         }
 
         // Decay the current score
@@ -250,4 +254,22 @@ mod tests {
         score.add(change);
         assert_eq!(score.score(), DEFAULT_SCORE + change);
     }
+
+    #[test]
+    fn test_ban_duration() {
+        let mut score = Score::default();
+        score.apply_peer_action(PeerAction::Fatal);
+        assert_eq!(score.state(), ScoreState::Banned);
+
+        // The score does not decay during the ban.
+        score.update(Duration::from_secs(60));
+        assert_eq!(score.score(), MIN_SCORE);
+        assert_eq!(score.state(), ScoreState::Banned);
+
+        // The score decays once the ban is over, starting from the end of the ban.
+        score.last_updated -= Duration::from_secs(90);
+        score.update(Duration::from_secs(60));
+        assert!(score.score() > MIN_SCORE);
+        assert!(score.score() < MIN_SCORE / 2.0);
+    }
 }
@@ -1,3 +1,4 @@
+use crate::metrics;
 use crate::rpc::methods::*;
 use crate::rpc::{
     codec::base::OutboundCodec,
@@ -87,6 +88,11 @@ impl<TSpec: EthSpec> Encoder<RPCCodedResponse<TSpec>> for SSZSnappyInboundCodec<
 
         // Write compressed bytes to `dst`
         dst.extend_from_slice(writer.get_ref());
+        metrics::inc_protocol_bytes(
+            &self.protocol.message_name.to_string(),
+            false,
+            writer.get_ref().len(),
+        );
         Ok(())
     }
 }
@@ -122,6 +128,11 @@ impl<TSpec: EthSpec> Decoder for SSZSnappyInboundCodec<TSpec> {
                 let n = reader.get_ref().position();
                 self.len = None;
                 let _read_bytes = src.split_to(n as usize);
+                metrics::inc_protocol_bytes(
+                    &self.protocol.message_name.to_string(),
+                    true,
+                    n as usize,
+                );
                 match self.protocol.message_name {
                     Protocol::Status => match self.protocol.version {
                         Version::V1 => {
@@ -261,6 +272,11 @@ impl<TSpec: EthSpec> Encoder<RPCRequest<TSpec>> for SSZSnappyOutboundCodec<TSpec
 
         // Write compressed bytes to `dst`
         dst.extend_from_slice(writer.get_ref());
+        metrics::inc_protocol_bytes(
+            &self.protocol.message_name.to_string(),
+            false,
+            writer.get_ref().len(),
+        );
         Ok(())
     }
 }
@@ -299,6 +315,11 @@ impl<TSpec: EthSpec> Decoder for SSZSnappyOutboundCodec<TSpec> {
                 let n = reader.get_ref().position();
                 self.len = None;
                 let _read_byts = src.split_to(n as usize);
+                metrics::inc_protocol_bytes(
+                    &self.protocol.message_name.to_string(),
+                    true,
+                    n as usize,
+                );
                 match self.protocol.message_name {
                     Protocol::Status => match self.protocol.version {
                         Version::V1 => {
@@ -401,6 +422,11 @@ impl<TSpec: EthSpec> OutboundCodec<RPCRequest<TSpec>> for SSZSnappyOutboundCodec
                 let n = reader.get_ref().position();
                 self.len = None;
                 let _read_bytes = src.split_to(n as usize);
+                metrics::inc_protocol_bytes(
+                    &self.protocol.message_name.to_string(),
+                    true,
+                    n as usize,
+                );
                 Ok(Some(
                     String::from_utf8_lossy(&<Vec<u8>>::from_ssz_bytes(&decoded_buffer)?).into(),
                 ))
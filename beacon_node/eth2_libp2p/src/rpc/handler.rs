@@ -4,6 +4,7 @@
 use super::methods::{RPCCodedResponse, RPCResponseErrorCode, RequestId, ResponseTermination};
 use super::protocol::{Protocol, RPCError, RPCProtocol, RPCRequest};
 use super::{RPCReceived, RPCSend};
+use crate::metrics;
 use crate::rpc::protocol::{InboundFramed, OutboundFramed};
 use fnv::FnvHashMap;
 use futures::prelude::*;
@@ -20,7 +21,7 @@ use std::{
     collections::hash_map::Entry,
     pin::Pin,
     task::{Context, Poll},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tokio::time::{delay_queue, delay_until, Delay, DelayQueue, Instant as TInstant};
 use types::EthSpec;
@@ -168,6 +169,19 @@ struct OutboundInfo<TSpec: EthSpec> {
     remaining_chunks: Option<u64>,
     /// `RequestId` as given by the application that sent the request.
     req_id: RequestId,
+    /// When the request was sent.
+    sent: Instant,
+}
+
+impl<TSpec: EthSpec> OutboundInfo<TSpec> {
+    /// Records the time taken for the response to the request to be received in full.
+    fn observe_response_time(&self) {
+        if let Some(histogram) =
+            metrics::get_histogram(&metrics::RPC_RESPONSE_TIME, &[&self.proto.to_string()])
+        {
+            histogram.observe(self.sent.elapsed().as_secs_f64());
+        }
+    }
 }
 
 /// State of an inbound substream connection.
@@ -438,6 +452,7 @@ where
                         proto,
                         remaining_chunks: expected_responses,
                         req_id: id,
+                        sent: Instant::now(),
                     },
                 )
                 .is_some()
@@ -761,6 +776,11 @@ where
                             entry.get_mut().state = OutboundSubstreamState::Closing(substream);
                         }
 
+                        if let OutboundSubstreamState::Closing(_) = entry.get().state {
+                            // the response has been received in full
+                            entry.get().observe_response_time();
+                        }
+
                         // Check what type of response we got and report it accordingly
                         let id = entry.get().req_id;
                         let proto = entry.get().proto;
@@ -788,6 +808,9 @@ where
                         let delay_key = &entry.get().delay_key;
                         let request_id = entry.get().req_id;
                         self.outbound_substreams_delay.remove(delay_key);
+                        if request.expected_responses() > 1 {
+                            entry.get().observe_response_time();
+                        }
                         entry.remove_entry();
                         // notify the application error
                         if request.expected_responses() > 1 {
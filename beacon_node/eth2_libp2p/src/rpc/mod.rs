@@ -13,6 +13,7 @@ use libp2p::swarm::{
 };
 use libp2p::{Multiaddr, PeerId};
 use rate_limiter::{RPCRateLimiter as RateLimiter, RPCRateLimiterBuilder, RateLimitedErr};
+use self_limiter::{QueuedRequest, SelfRateLimiter};
 use slog::{crit, debug, o};
 use std::marker::PhantomData;
 use std::task::{Context, Poll};
@@ -35,6 +36,7 @@ mod handler;
 pub mod methods;
 mod protocol;
 mod rate_limiter;
+mod self_limiter;
 
 /// RPC events sent from Lighthouse.
 #[derive(Debug, Clone)]
@@ -94,6 +96,8 @@ pub struct RPCMessage<TSpec: EthSpec> {
 pub struct RPC<TSpec: EthSpec> {
     /// Rate limiter
     limiter: RateLimiter,
+    /// Rate limiter of the requests we send, which keeps them within the quotas of `limiter`.
+    self_limiter: SelfRateLimiter<TSpec>,
     /// Queue of events to be processed.
     events: Vec<NetworkBehaviourAction<RPCSend<TSpec>, RPCMessage<TSpec>>>,
     /// Slog logger for RPC behaviour.
@@ -103,7 +107,19 @@ pub struct RPC<TSpec: EthSpec> {
 impl<TSpec: EthSpec> RPC<TSpec> {
     pub fn new(log: slog::Logger) -> Self {
         let log = log.new(o!("service" => "libp2p_rpc"));
-        let limiter = RPCRateLimiterBuilder::new()
+        // Peers are assumed to apply the same quotas to our requests as we apply to theirs.
+        let self_limiter = SelfRateLimiter::new(Self::rate_limiter(), log.clone());
+        RPC {
+            limiter: Self::rate_limiter(),
+            self_limiter,
+            events: Vec::new(),
+            log,
+        }
+    }
+
+    /// Builds a rate limiter with the quotas of the requests of each peer.
+    fn rate_limiter() -> RateLimiter {
+        RPCRateLimiterBuilder::new()
             .n_every(Protocol::MetaData, 2, Duration::from_secs(5))
             .one_every(Protocol::Ping, Duration::from_secs(5))
             .n_every(Protocol::Status, 5, Duration::from_secs(15))
@@ -119,12 +135,7 @@ impl<TSpec: EthSpec> RPC<TSpec> {
                 Duration::from_secs(10),
             )
             .build()
-            .unwrap();
-        RPC {
-            limiter,
-            events: Vec::new(),
-            log,
-        }
+            .unwrap()
     }
 
     /// Sends an RPC response.
@@ -145,17 +156,24 @@ impl<TSpec: EthSpec> RPC<TSpec> {
 
     /// Submits an RPC request.
     ///
-    /// The peer must be connected for this to succeed.
+    /// The request is delayed if sending it now would exceed the peer's rate limit. The peer must
+    /// be connected for this to succeed.
     pub fn send_request(
         &mut self,
         peer_id: PeerId,
         request_id: RequestId,
         event: RPCRequest<TSpec>,
     ) {
+        if let Some(request) = self.self_limiter.allows(peer_id, request_id, event) {
+            self.notify_request(request);
+        }
+    }
+
+    fn notify_request(&mut self, request: QueuedRequest<TSpec>) {
         self.events.push(NetworkBehaviourAction::NotifyHandler {
-            peer_id,
+            peer_id: request.peer_id,
             handler: NotifyHandler::Any,
-            event: RPCSend::Request(request_id, event),
+            event: RPCSend::Request(request.request_id, request.request),
         });
     }
 }
@@ -188,15 +206,16 @@ where
     fn inject_connected(&mut self, peer_id: &PeerId) {
         // find the peer's meta-data
         debug!(self.log, "Requesting new peer's metadata"; "peer_id" => format!("{}",peer_id));
-        let rpc_event = RPCSend::Request(RequestId::Behaviour, RPCRequest::MetaData(PhantomData));
-        self.events.push(NetworkBehaviourAction::NotifyHandler {
-            peer_id: peer_id.clone(),
-            handler: NotifyHandler::Any,
-            event: rpc_event,
-        });
+        self.send_request(
+            peer_id.clone(),
+            RequestId::Behaviour,
+            RPCRequest::MetaData(PhantomData),
+        );
     }
 
-    fn inject_disconnected(&mut self, _peer_id: &PeerId) {}
+    fn inject_disconnected(&mut self, peer_id: &PeerId) {
+        self.self_limiter.peer_disconnected(peer_id);
+    }
 
     fn inject_connection_established(
         &mut self,
@@ -274,6 +293,11 @@ where
     > {
         // let the rate limiter prune
         let _ = self.limiter.poll_unpin(cx);
+
+        // send the delayed requests which now fit in the peers' rate limits
+        while let Poll::Ready(request) = self.self_limiter.poll_ready(cx) {
+            self.notify_request(request);
+        }
         if !self.events.is_empty() {
             return Poll::Ready(self.events.remove(0));
         }
@@ -0,0 +1,226 @@
+//! Limits the rate of the requests we send to each peer, so that they stay within the quotas which
+//! peers apply to inbound requests rather than being rejected with a `RateLimited` error.
+//!
+//! A request which does not fit in the quota is delayed until it does. Requests of the same peer
+//! and protocol are sent in the order in which they were made.
+use super::rate_limiter::{RPCRateLimiter as RateLimiter, RateLimitedErr};
+use super::{Protocol, RPCRequest, RequestId};
+use fnv::FnvHashMap;
+use futures::{FutureExt, StreamExt};
+use libp2p::PeerId;
+use slog::{crit, debug};
+use std::collections::VecDeque;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::DelayQueue;
+use types::EthSpec;
+
+/// A request which may be sent to a peer.
+pub(crate) struct QueuedRequest<TSpec: EthSpec> {
+    pub peer_id: PeerId,
+    pub request_id: RequestId,
+    pub request: RPCRequest<TSpec>,
+}
+
+pub(crate) struct SelfRateLimiter<TSpec: EthSpec> {
+    /// The quotas of the requests sent to each peer.
+    limiter: RateLimiter,
+    /// Requests waiting for their quota, per peer and protocol, in the order in which they were
+    /// made.
+    delayed_requests: FnvHashMap<(PeerId, Protocol), VecDeque<(RequestId, RPCRequest<TSpec>)>>,
+    /// When the first delayed request of each peer and protocol is expected to fit in the quota.
+    next_ready: DelayQueue<(PeerId, Protocol)>,
+    /// Delayed requests which now fit in the quota.
+    ready_requests: VecDeque<QueuedRequest<TSpec>>,
+    log: slog::Logger,
+}
+
+impl<TSpec: EthSpec> SelfRateLimiter<TSpec> {
+    pub fn new(limiter: RateLimiter, log: slog::Logger) -> Self {
+        Self {
+            limiter,
+            delayed_requests: FnvHashMap::default(),
+            next_ready: DelayQueue::new(),
+            ready_requests: VecDeque::new(),
+            log,
+        }
+    }
+
+    /// Returns the request if it may be sent now, otherwise delays it until it fits in the quota,
+    /// when it is returned by `poll_ready`.
+    pub fn allows(
+        &mut self,
+        peer_id: PeerId,
+        request_id: RequestId,
+        request: RPCRequest<TSpec>,
+    ) -> Option<QueuedRequest<TSpec>> {
+        let key = (peer_id, request.protocol());
+
+        // Requests are not sent ahead of older requests of the same protocol.
+        if let Some(queue) = self.delayed_requests.get_mut(&key) {
+            queue.push_back((request_id, request));
+            return None;
+        }
+
+        match self.try_send(&key.0, request_id, request) {
+            Ok(queued) => Some(queued),
+            Err((request, wait_time)) => {
+                debug!(self.log, "Delaying request to stay within the peer's rate limit";
+                    "request" => request.to_string(), "peer_id" => key.0.to_string(),
+                    "wait_time_ms" => wait_time.as_millis());
+                self.next_ready.insert(key.clone(), wait_time);
+                self.delayed_requests
+                    .entry(key)
+                    .or_default()
+                    .push_back((request_id, request));
+                None
+            }
+        }
+    }
+
+    /// Drops the delayed requests of a peer which has disconnected.
+    pub fn peer_disconnected(&mut self, peer_id: &PeerId) {
+        self.delayed_requests.retain(|(id, _), _| id != peer_id);
+        self.ready_requests
+            .retain(|queued| &queued.peer_id != peer_id);
+    }
+
+    /// Returns the next delayed request which may now be sent.
+    pub fn poll_ready(&mut self, cx: &mut Context) -> Poll<QueuedRequest<TSpec>> {
+        // let the rate limiter prune
+        let _ = self.limiter.poll_unpin(cx);
+
+        while let Poll::Ready(Some(Ok(expired))) = self.next_ready.poll_next_unpin(cx) {
+            self.release(expired.into_inner());
+        }
+
+        match self.ready_requests.pop_front() {
+            Some(queued) => Poll::Ready(queued),
+            None => Poll::Pending,
+        }
+    }
+
+    /// Moves the delayed requests of a peer and protocol which fit in the quota to
+    /// `ready_requests`, waiting again for the first which does not.
+    fn release(&mut self, key: (PeerId, Protocol)) {
+        let mut queue = match self.delayed_requests.remove(&key) {
+            Some(queue) => queue,
+            // The peer has disconnected.
+            None => return,
+        };
+
+        while let Some((request_id, request)) = queue.pop_front() {
+            match self.try_send(&key.0, request_id, request) {
+                Ok(queued) => self.ready_requests.push_back(queued),
+                Err((request, wait_time)) => {
+                    queue.push_front((request_id, request));
+                    self.next_ready.insert(key.clone(), wait_time);
+                    self.delayed_requests.insert(key, queue);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Consumes the quota of the request if it fits, otherwise returns the time until it may.
+    fn try_send(
+        &mut self,
+        peer_id: &PeerId,
+        request_id: RequestId,
+        request: RPCRequest<TSpec>,
+    ) -> Result<QueuedRequest<TSpec>, (RPCRequest<TSpec>, Duration)> {
+        match self.limiter.allows(peer_id, &request) {
+            Ok(()) => {}
+            Err(RateLimitedErr::TooSoon(wait_time)) => return Err((request, wait_time)),
+            Err(RateLimitedErr::TooLarge) => {
+                // we set the batch sizes, so this is a coding/config err. The request is sent
+                // regardless, since it would otherwise never complete.
+                crit!(self.log, "Batch too large to ever be processed";
+                    "protocol" => format!("{}", request.protocol()));
+            }
+        }
+
+        Ok(QueuedRequest {
+            peer_id: peer_id.clone(),
+            request_id,
+            request,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::rate_limiter::RPCRateLimiterBuilder;
+    use crate::rpc::Ping;
+    use slog::o;
+    use std::time::Instant;
+    use types::MinimalEthSpec;
+
+    const PING_PERIOD: Duration = Duration::from_millis(50);
+
+    fn limiter() -> SelfRateLimiter<MinimalEthSpec> {
+        let limiter = RPCRateLimiterBuilder::new()
+            .one_every(Protocol::MetaData, PING_PERIOD)
+            .one_every(Protocol::Ping, PING_PERIOD)
+            .one_every(Protocol::Status, PING_PERIOD)
+            .one_every(Protocol::Goodbye, PING_PERIOD)
+            .one_every(Protocol::BlocksByRange, PING_PERIOD)
+            .one_every(Protocol::BlocksByRoot, PING_PERIOD)
+            .build()
+            .unwrap();
+        SelfRateLimiter::new(limiter, slog::Logger::root(slog::Discard, o!()))
+    }
+
+    fn ping(data: u64) -> RPCRequest<MinimalEthSpec> {
+        RPCRequest::Ping(Ping { data })
+    }
+
+    #[tokio::test]
+    async fn delays_requests_in_order() {
+        let mut limiter = limiter();
+        let peer_id = PeerId::random();
+        let other_peer_id = PeerId::random();
+        let start = Instant::now();
+
+        assert!(limiter
+            .allows(peer_id.clone(), RequestId::Sync(1), ping(1))
+            .is_some());
+        assert!(limiter
+            .allows(peer_id.clone(), RequestId::Sync(2), ping(2))
+            .is_none());
+        assert!(limiter
+            .allows(peer_id.clone(), RequestId::Sync(3), ping(3))
+            .is_none());
+        // The quotas of each peer are independent.
+        assert!(limiter
+            .allows(other_peer_id, RequestId::Sync(4), ping(4))
+            .is_some());
+
+        for expected in &[2, 3] {
+            let queued = futures::future::poll_fn(|cx| limiter.poll_ready(cx)).await;
+            assert_eq!(queued.peer_id, peer_id);
+            assert_eq!(queued.request_id, RequestId::Sync(*expected));
+            assert_eq!(queued.request, ping(*expected));
+        }
+        assert!(start.elapsed() >= PING_PERIOD * 2);
+    }
+
+    #[tokio::test]
+    async fn drops_requests_of_disconnected_peers() {
+        let mut limiter = limiter();
+        let peer_id = PeerId::random();
+
+        limiter.allows(peer_id.clone(), RequestId::Sync(1), ping(1));
+        limiter.allows(peer_id.clone(), RequestId::Sync(2), ping(2));
+        limiter.peer_disconnected(&peer_id);
+
+        assert!(limiter.delayed_requests.is_empty());
+        tokio::time::delay_for(PING_PERIOD * 2).await;
+        assert!(
+            futures::future::poll_fn(|cx| Poll::Ready(limiter.poll_ready(cx)))
+                .await
+                .is_pending()
+        );
+    }
+}
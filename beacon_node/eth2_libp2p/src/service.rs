@@ -1,6 +1,8 @@
 use crate::behaviour::{Behaviour, BehaviourEvent, PeerRequestId, Request, Response};
+use crate::connection_gater::ConnectionGater;
 use crate::discovery::enr;
 use crate::multiaddr::Protocol;
+use crate::peer_manager::load_known_peers;
 use crate::rpc::{GoodbyeReason, RPCResponseErrorCode, RequestId};
 use crate::types::{error, GossipKind};
 use crate::EnrExt;
@@ -88,7 +90,13 @@ impl<TSpec: EthSpec> Service<TSpec> {
 
         let mut swarm = {
             // Set up the transport - tcp/ws with noise and mplex
-            let transport = build_transport(local_keypair.clone())
+            let gater = Arc::new(ConnectionGater::new(
+                config.allowed_ips.clone(),
+                config.denied_ips.clone(),
+                config.max_connections_per_ip,
+                log.clone(),
+            ));
+            let transport = build_transport(local_keypair.clone(), gater)
                 .map_err(|e| format!("Failed to build transport: {:?}", e))?;
             // Lighthouse network behaviour
             let behaviour =
@@ -153,6 +161,20 @@ impl<TSpec: EthSpec> Service<TSpec> {
             dial_addr(multiaddr.clone());
         }
 
+        // attempt to reconnect to the peers we knew before the node was restarted
+        for (peer_id, multiaddr) in load_known_peers(&config.network_dir, &log)
+            .into_iter()
+            .take(config.target_peers)
+        {
+            if !network_globals
+                .peers
+                .read()
+                .is_connected_or_dialing(&peer_id)
+            {
+                dial_addr(multiaddr);
+            }
+        }
+
         // attempt to connect to any specified boot-nodes
         let mut boot_nodes = config.boot_nodes_enr.clone();
         boot_nodes.dedup();
@@ -309,8 +331,12 @@ impl<TSpec: EthSpec> Service<TSpec> {
 /// mplex as the multiplexing layer.
 fn build_transport(
     local_private_key: Keypair,
+    gater: Arc<ConnectionGater>,
 ) -> Result<Boxed<(PeerId, StreamMuxerBox), Error>, Error> {
-    let transport = libp2p::tcp::TokioTcpConfig::new().nodelay(true);
+    // Connections are gated on the resolved addresses, before any handshake
+    let transport = libp2p::tcp::TokioTcpConfig::new()
+        .nodelay(true)
+        .and_then(move |stream, endpoint| gater.gate(stream, endpoint));
     let transport = libp2p::dns::DnsConfig::new(transport)?;
     #[cfg(feature = "libp2p-websocket")]
     let transport = {
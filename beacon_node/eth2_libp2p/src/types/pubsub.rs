@@ -191,3 +191,58 @@ impl<T: EthSpec> std::fmt::Display for PubsubMessage<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::gossipsub::IdentTopic;
+    use types::test_utils::test_random_instance;
+    use types::MinimalEthSpec;
+
+    type E = MinimalEthSpec;
+
+    fn topic_hash(message: &PubsubMessage<E>) -> TopicHash {
+        let topic: IdentTopic = message
+            .topics(GossipEncoding::SSZSnappy, [1, 2, 3, 4])
+            .remove(0)
+            .into();
+        topic.hash()
+    }
+
+    fn messages() -> Vec<PubsubMessage<E>> {
+        vec![
+            PubsubMessage::BeaconBlock(Box::new(test_random_instance())),
+            PubsubMessage::AggregateAndProofAttestation(Box::new(test_random_instance())),
+            PubsubMessage::Attestation(Box::new((SubnetId::new(3), test_random_instance()))),
+            PubsubMessage::VoluntaryExit(Box::new(test_random_instance())),
+            PubsubMessage::ProposerSlashing(Box::new(test_random_instance())),
+            PubsubMessage::AttesterSlashing(Box::new(test_random_instance())),
+        ]
+    }
+
+    #[test]
+    fn round_trip() {
+        for message in messages() {
+            let data = message
+                .encode(GossipEncoding::SSZSnappy)
+                .expect("should encode");
+            let decoded =
+                PubsubMessage::decode(&[topic_hash(&message)], &data).expect("should decode");
+            assert_eq!(decoded, message, "{} should round-trip", message);
+        }
+    }
+
+    #[test]
+    fn decodes_according_to_topic() {
+        let block = PubsubMessage::BeaconBlock(Box::new(test_random_instance()));
+        let exit = PubsubMessage::VoluntaryExit(Box::new(test_random_instance()));
+        let data = block
+            .encode(GossipEncoding::SSZSnappy)
+            .expect("should encode");
+
+        assert!(
+            PubsubMessage::<E>::decode(&[topic_hash(&exit)], &data).is_err(),
+            "a block should not decode on the voluntary exit topic"
+        );
+    }
+}
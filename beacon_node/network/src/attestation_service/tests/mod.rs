@@ -131,7 +131,7 @@ mod tests {
             .map(|validator_index| {
                 get_subscription(
                     validator_index,
-                    validator_index,
+                    CommitteeIndex::new(validator_index),
                     slot,
                     committee_count_at_slot,
                 )
@@ -171,7 +171,7 @@ mod tests {
     async fn subscribe_current_slot() {
         // subscription config
         let validator_index = 1;
-        let committee_index = 1;
+        let committee_index = CommitteeIndex::new(1);
         let subscription_slot = 0;
         let no_events_expected = 4;
         let committee_count = 1;
@@ -222,7 +222,7 @@ mod tests {
     async fn subscribe_current_slot_wait_for_unsubscribe() {
         // subscription config
         let validator_index = 1;
-        let committee_index = 1;
+        let committee_index = CommitteeIndex::new(1);
         let subscription_slot = 0;
         let no_events_expected = 5;
         let committee_count = 1;
@@ -275,7 +275,7 @@ mod tests {
     async fn subscribe_five_slots_ahead() {
         // subscription config
         let validator_index = 1;
-        let committee_index = 1;
+        let committee_index = CommitteeIndex::new(1);
         let subscription_slot = 5;
         let no_events_expected = 4;
         let committee_count = 1;
@@ -336,7 +336,7 @@ mod tests {
     async fn subscribe_five_slots_ahead_wait_five_slots() {
         // subscription config
         let validator_index = 1;
-        let committee_index = 1;
+        let committee_index = CommitteeIndex::new(1);
         let subscription_slot = 5;
         let no_events_expected = 5;
         let committee_count = 1;
@@ -397,7 +397,7 @@ mod tests {
     async fn subscribe_7_slots_ahead() {
         // subscription config
         let validator_index = 1;
-        let committee_index = 1;
+        let committee_index = CommitteeIndex::new(1);
         let subscription_slot = 7;
         let no_events_expected = 3;
         let committee_count = 1;
@@ -441,7 +441,7 @@ mod tests {
     async fn subscribe_ten_slots_ahead_wait_five_slots() {
         // subscription config
         let validator_index = 1;
-        let committee_index = 1;
+        let committee_index = CommitteeIndex::new(1);
         let subscription_slot = 10;
         let no_events_expected = 4;
         let committee_count = 1;
@@ -7,7 +7,7 @@ use beacon_chain::{
     attestation_verification::Error as AttnError, observed_operations::ObservationOutcome,
     BeaconChain, BeaconChainError, BeaconChainTypes, BlockError, ForkChoiceError,
 };
-use eth2_libp2p::{MessageAcceptance, MessageId, PeerId};
+use eth2_libp2p::{MessageAcceptance, MessageId, PeerAction, PeerId};
 use slog::{crit, debug, error, info, trace, warn, Logger};
 use ssz::Encode;
 use std::sync::Arc;
@@ -234,6 +234,12 @@ impl<T: BeaconChainTypes> Worker<T> {
             | Err(e @ BlockError::GenesisBlock) => {
                 warn!(self.log, "Could not verify block for gossip, rejecting the block";
                             "error" => e.to_string());
+                if matches!(
+                    e,
+                    BlockError::ProposalSignatureInvalid | BlockError::InvalidSignature
+                ) {
+                    self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
+                }
                 self.propagate_validation_result(message_id, peer_id, MessageAcceptance::Reject);
                 return;
             }
@@ -497,6 +503,22 @@ impl<T: BeaconChainTypes> Worker<T> {
             });
     }
 
+    /// Lowers the score of `peer_id` in the peer manager.
+    ///
+    /// Gossipsub already scores the sender of every rejected message, so this is reserved for
+    /// faults which cannot be explained by a difference in clocks or views of the chain, such as
+    /// an invalid signature.
+    fn penalize_peer(&self, peer_id: PeerId, action: PeerAction) {
+        self.network_tx
+            .send(NetworkMessage::ReportPeer { peer_id, action })
+            .unwrap_or_else(|_| {
+                warn!(
+                    self.log,
+                    "Could not send peer action to the network service"
+                )
+            });
+    }
+
     /// Send a message to `sync_tx`.
     ///
     /// Creates a log if there is an interal error.
@@ -545,6 +567,7 @@ impl<T: BeaconChainTypes> Worker<T> {
                     peer_id.clone(),
                     MessageAcceptance::Reject,
                 );
+                self.penalize_peer(peer_id.clone(), PeerAction::LowToleranceError);
             }
             AttnError::EmptyAggregationBitfield => {
                 /*
@@ -0,0 +1,487 @@
+//! Maps the listening ports of the node on the local gateway with UPnP, so that a node behind a
+//! NAT can be dialed by its peers without any manual configuration of the router.
+//!
+//! IPv4 Internet Gateway Devices are found and configured through the `igd` crate. If no such
+//! device responds, or it fails to provide an external address, the ports are mapped with NAT-PMP
+//! (RFC 6886) on the default gateway instead, which is only found on Linux. PCP, the successor of
+//! NAT-PMP, is not supported. The mappings are leased, and are renewed for as long as the node
+//! runs.
+use crate::{NetworkConfig, NetworkMessage};
+use igd::{AddPortError, PortMappingProtocol, SearchOptions};
+use slog::{debug, info, warn};
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use types::EthSpec;
+
+/// The time we wait for a gateway to respond to a search.
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// The lease duration of each mapping. Mappings are renewed halfway through their lease.
+const LEASE_DURATION: Duration = Duration::from_secs(3600);
+/// The time we wait before searching again after failing to find a gateway.
+const RETRY_DELAY: Duration = Duration::from_secs(600);
+/// Identifies our mappings in the configuration of the gateway.
+const MAPPING_DESCRIPTION: &str = "lighthouse";
+/// The port on which NAT-PMP gateways listen.
+const NAT_PMP_PORT: u16 = 5351;
+/// The time we wait for the first response to a NAT-PMP request, doubled on each retransmission.
+const NAT_PMP_INITIAL_TIMEOUT: Duration = Duration::from_millis(250);
+/// The number of times a NAT-PMP request is sent before giving up on the gateway.
+const NAT_PMP_ATTEMPTS: u32 = 5;
+/// The NAT-PMP opcodes for external address, UDP mapping and TCP mapping requests. The opcode of a
+/// response is that of its request plus `NAT_PMP_RESPONSE`.
+const NAT_PMP_EXTERNAL_ADDRESS: u8 = 0;
+const NAT_PMP_MAP_UDP: u8 = 1;
+const NAT_PMP_MAP_TCP: u8 = 2;
+const NAT_PMP_RESPONSE: u8 = 128;
+
+/// The ports to map. UPnP maps them to the same external port, whilst a NAT-PMP gateway may choose
+/// another.
+#[derive(Debug, Clone, Copy)]
+pub struct UPnPConfig {
+    /// The TCP port that libp2p listens on.
+    pub tcp_port: u16,
+    /// The UDP port that discovery listens on, if discovery is enabled.
+    pub udp_port: Option<u16>,
+    /// If false, the ENR was configured by the user and is not updated with the external address.
+    pub update_enr: bool,
+}
+
+impl From<&NetworkConfig> for UPnPConfig {
+    fn from(config: &NetworkConfig) -> Self {
+        UPnPConfig {
+            tcp_port: config.libp2p_port,
+            udp_port: if config.disable_discovery {
+                None
+            } else {
+                Some(config.discovery_port)
+            },
+            update_enr: config.enr_address.is_none()
+                && config.enr_tcp_port.is_none()
+                && config.enr_udp_port.is_none(),
+        }
+    }
+}
+
+/// The external address of the node, and the external ports which were successfully mapped to it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Mapping {
+    /// Either `"UPnP"` or `"NAT-PMP"`.
+    method: &'static str,
+    external_ip: Ipv4Addr,
+    tcp_port: Option<u16>,
+    udp_port: Option<u16>,
+}
+
+/// Spawns a task which maps the ports of `config` on the local gateway, renewing the mappings
+/// for as long as the node runs.
+///
+/// Each time the external address or mapped ports change, the network service is sent a
+/// `NetworkMessage::UPnPMappingEstablished` so that it may update the ENR.
+pub fn spawn_upnp_mapping<T: EthSpec>(
+    config: UPnPConfig,
+    network_send: mpsc::UnboundedSender<NetworkMessage<T>>,
+    executor: &environment::TaskExecutor,
+) {
+    let log = executor.log().clone();
+
+    let upnp_fut = async move {
+        let mut established = None;
+
+        loop {
+            let mapping_log = log.clone();
+            let result = tokio::task::spawn_blocking(move || map_ports(config, &mapping_log)).await;
+
+            let delay = match result {
+                Ok(Ok(mapping)) => {
+                    if established != Some(mapping) {
+                        info!(
+                            log,
+                            "Port mapping established";
+                            "method" => mapping.method,
+                            "external_ip" => format!("{}", mapping.external_ip),
+                            "tcp_port" => format!("{:?}", mapping.tcp_port),
+                            "udp_port" => format!("{:?}", mapping.udp_port),
+                        );
+                        established = Some(mapping);
+                        send_mapping(config, mapping, &network_send, &log);
+                    }
+                    LEASE_DURATION / 2
+                }
+                Ok(Err(e)) => {
+                    warn!(log, "Port mapping failed"; "error" => e);
+                    RETRY_DELAY
+                }
+                Err(e) => {
+                    warn!(log, "UPnP task failed"; "error" => format!("{:?}", e));
+                    RETRY_DELAY
+                }
+            };
+
+            tokio::time::delay_for(delay).await;
+        }
+    };
+
+    executor.spawn(upnp_fut, "upnp");
+}
+
+/// Informs the network service of a new `mapping`, if the ENR should be updated with it.
+fn send_mapping<T: EthSpec>(
+    config: UPnPConfig,
+    mapping: Mapping,
+    network_send: &mpsc::UnboundedSender<NetworkMessage<T>>,
+    log: &slog::Logger,
+) {
+    if !config.update_enr {
+        debug!(log, "Not updating the ENR, as its address was configured");
+        return;
+    }
+
+    if !is_global(mapping.external_ip) {
+        warn!(
+            log,
+            "Gateway is behind another NAT";
+            "external_ip" => format!("{}", mapping.external_ip),
+            "info" => "the ENR is not updated",
+        );
+        return;
+    }
+
+    let _ = network_send.send(NetworkMessage::UPnPMappingEstablished {
+        ip: mapping.external_ip,
+        tcp_port: mapping.tcp_port,
+        udp_port: mapping.udp_port,
+    });
+}
+
+/// Returns false if `ip` is not reachable from the internet.
+fn is_global(ip: Ipv4Addr) -> bool {
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        // Shared address space, as used by carrier-grade NATs.
+        || (ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64))
+}
+
+/// Maps the ports of `config` with UPnP, falling back to NAT-PMP if UPnP fails.
+///
+/// A port which cannot be mapped is logged and omitted from the result. Returns an error if no
+/// port could be mapped, since the external address is then of no use to peers.
+fn map_ports(config: UPnPConfig, log: &slog::Logger) -> Result<Mapping, String> {
+    let mapping = match map_ports_upnp(config, log) {
+        Ok(mapping) => mapping,
+        Err(upnp_error) => {
+            debug!(log, "Falling back to NAT-PMP"; "upnp_error" => upnp_error.as_str());
+            map_ports_nat_pmp(config, log).map_err(|nat_pmp_error| {
+                format!("UPnP: {}, NAT-PMP: {}", upnp_error, nat_pmp_error)
+            })?
+        }
+    };
+
+    if mapping.tcp_port.is_none() && mapping.udp_port.is_none() {
+        return Err(format!(
+            "{} gateway at {} did not map any port",
+            mapping.method, mapping.external_ip
+        ));
+    }
+
+    Ok(mapping)
+}
+
+/// Finds an Internet Gateway Device and maps the ports of `config`, returning the external
+/// address.
+fn map_ports_upnp(config: UPnPConfig, log: &slog::Logger) -> Result<Mapping, String> {
+    let gateway = igd::search_gateway(SearchOptions {
+        timeout: Some(SEARCH_TIMEOUT),
+        ..SearchOptions::default()
+    })
+    .map_err(|e| format!("Unable to find gateway: {}", e))?;
+    let local_ip = local_ip(gateway.addr)
+        .map_err(|e| format!("Unable to find local address of gateway: {}", e))?;
+    let external_ip = gateway
+        .get_external_ip()
+        .map_err(|e| format!("Unable to get external address: {}", e))?;
+
+    let map = |protocol: PortMappingProtocol, port: u16| {
+        let local_addr = SocketAddrV4::new(local_ip, port);
+        let add = |lease: Duration| {
+            gateway.add_port(
+                protocol,
+                port,
+                local_addr,
+                lease.as_secs() as u32,
+                MAPPING_DESCRIPTION,
+            )
+        };
+
+        // Fall back to a mapping without an expiry if the gateway does not support leases.
+        let result = match add(LEASE_DURATION) {
+            Err(AddPortError::OnlyPermanentLeasesSupported) => add(Duration::from_secs(0)),
+            result => result,
+        };
+
+        match result {
+            Ok(()) => Some(port),
+            Err(e) => {
+                warn!(
+                    log,
+                    "UPnP could not map port";
+                    "protocol" => format!("{}", protocol),
+                    "port" => port,
+                    "error" => format!("{}", e),
+                );
+                None
+            }
+        }
+    };
+
+    Ok(Mapping {
+        method: "UPnP",
+        external_ip,
+        tcp_port: map(PortMappingProtocol::TCP, config.tcp_port),
+        udp_port: config
+            .udp_port
+            .and_then(|port| map(PortMappingProtocol::UDP, port)),
+    })
+}
+
+/// Maps the ports of `config` on the default gateway with NAT-PMP, returning the external address.
+fn map_ports_nat_pmp(config: UPnPConfig, log: &slog::Logger) -> Result<Mapping, String> {
+    let gateway = default_gateway()?;
+    let socket = UdpSocket::bind("0.0.0.0:0")
+        .and_then(|socket| {
+            socket.connect(SocketAddrV4::new(gateway, NAT_PMP_PORT))?;
+            Ok(socket)
+        })
+        .map_err(|e| format!("Unable to open socket to gateway: {}", e))?;
+
+    let response = nat_pmp_request(&socket, &[0, NAT_PMP_EXTERNAL_ADDRESS])?;
+    let external_ip = parse_nat_pmp_external_address(&response)?;
+
+    let map = |protocol: PortMappingProtocol, port: u16| {
+        let opcode = match protocol {
+            PortMappingProtocol::TCP => NAT_PMP_MAP_TCP,
+            PortMappingProtocol::UDP => NAT_PMP_MAP_UDP,
+        };
+        let request = nat_pmp_mapping_request(opcode, port, LEASE_DURATION);
+        let result = nat_pmp_request(&socket, &request)
+            .and_then(|response| parse_nat_pmp_mapping(&response, opcode, port));
+
+        match result {
+            Ok(external_port) => Some(external_port),
+            Err(e) => {
+                warn!(
+                    log,
+                    "NAT-PMP could not map port";
+                    "protocol" => format!("{}", protocol),
+                    "port" => port,
+                    "error" => e,
+                );
+                None
+            }
+        }
+    };
+
+    Ok(Mapping {
+        method: "NAT-PMP",
+        external_ip,
+        tcp_port: map(PortMappingProtocol::TCP, config.tcp_port),
+        udp_port: config
+            .udp_port
+            .and_then(|port| map(PortMappingProtocol::UDP, port)),
+    })
+}
+
+/// Sends `request` to the gateway that `socket` is connected to, retransmitting with an
+/// exponentially increasing timeout until a response with the matching opcode is received.
+fn nat_pmp_request(socket: &UdpSocket, request: &[u8]) -> Result<Vec<u8>, String> {
+    let mut timeout = NAT_PMP_INITIAL_TIMEOUT;
+    let mut buf = [0; 16];
+
+    for _ in 0..NAT_PMP_ATTEMPTS {
+        socket
+            .send(request)
+            .and_then(|_| socket.set_read_timeout(Some(timeout)))
+            .map_err(|e| format!("Unable to send request: {}", e))?;
+
+        match socket.recv(&mut buf) {
+            Ok(len) if len >= 2 && buf[1] == request[1] + NAT_PMP_RESPONSE => {
+                return Ok(buf[..len].to_vec())
+            }
+            // Responses to earlier requests may still arrive, they are simply ignored.
+            Ok(_) => (),
+            Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                timeout *= 2
+            }
+            Err(e) => return Err(format!("Unable to receive response: {}", e)),
+        }
+    }
+
+    Err("No response from gateway".to_string())
+}
+
+/// Returns a request to map the internal `port` to the same external `port` for `lifetime`.
+fn nat_pmp_mapping_request(opcode: u8, port: u16, lifetime: Duration) -> [u8; 12] {
+    let mut request = [0; 12];
+    request[1] = opcode;
+    request[4..6].copy_from_slice(&port.to_be_bytes());
+    request[6..8].copy_from_slice(&port.to_be_bytes());
+    request[8..12].copy_from_slice(&(lifetime.as_secs() as u32).to_be_bytes());
+    request
+}
+
+/// Checks the version, opcode, length and result code of a NAT-PMP `response`.
+fn check_nat_pmp_response(response: &[u8], opcode: u8, len: usize) -> Result<(), String> {
+    if response.len() < len {
+        return Err(format!("Response too short: {} bytes", response.len()));
+    }
+    if response[0] != 0 || response[1] != opcode + NAT_PMP_RESPONSE {
+        return Err(format!(
+            "Unexpected response version {} or opcode {}",
+            response[0], response[1]
+        ));
+    }
+    match u16::from_be_bytes([response[2], response[3]]) {
+        0 => Ok(()),
+        result => Err(format!(
+            "Gateway refused request with result code {}",
+            result
+        )),
+    }
+}
+
+/// Returns the external address from the response to an external address request.
+fn parse_nat_pmp_external_address(response: &[u8]) -> Result<Ipv4Addr, String> {
+    check_nat_pmp_response(response, NAT_PMP_EXTERNAL_ADDRESS, 12)?;
+    Ok(Ipv4Addr::new(
+        response[8],
+        response[9],
+        response[10],
+        response[11],
+    ))
+}
+
+/// Returns the external port from the response to a request to map the internal `port`.
+fn parse_nat_pmp_mapping(response: &[u8], opcode: u8, port: u16) -> Result<u16, String> {
+    check_nat_pmp_response(response, opcode, 16)?;
+    let internal_port = u16::from_be_bytes([response[8], response[9]]);
+    if internal_port != port {
+        return Err(format!("Gateway mapped port {} instead", internal_port));
+    }
+    Ok(u16::from_be_bytes([response[10], response[11]]))
+}
+
+/// Returns the IPv4 default gateway, from the routing table of the kernel.
+#[cfg(target_os = "linux")]
+fn default_gateway() -> Result<Ipv4Addr, String> {
+    let routes = std::fs::read_to_string("/proc/net/route")
+        .map_err(|e| format!("Unable to read routing table: {}", e))?;
+    parse_default_gateway(&routes).ok_or_else(|| "No default gateway".to_string())
+}
+
+/// Returns the IPv4 default gateway, which is only known on Linux.
+#[cfg(not(target_os = "linux"))]
+fn default_gateway() -> Result<Ipv4Addr, String> {
+    Err("Unable to find the default gateway on this platform".to_string())
+}
+
+/// Returns the gateway of the default route in the contents of `/proc/net/route`.
+///
+/// Addresses are printed as the hex of the address in network order, read as a native integer.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_default_gateway(routes: &str) -> Option<Ipv4Addr> {
+    /// The route uses a gateway.
+    const RTF_GATEWAY: u16 = 0x2;
+
+    routes.lines().skip(1).find_map(|line| {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        let destination = fields.get(1)?;
+        let gateway = u32::from_str_radix(fields.get(2)?, 16).ok()?;
+        let flags = u16::from_str_radix(fields.get(3)?, 16).ok()?;
+
+        if *destination == "00000000" && flags & RTF_GATEWAY != 0 {
+            Some(Ipv4Addr::from(gateway.to_ne_bytes()))
+        } else {
+            None
+        }
+    })
+}
+
+/// Returns the address of the local interface through which `gateway` is reached.
+fn local_ip(gateway: SocketAddrV4) -> std::io::Result<Ipv4Addr> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.connect(gateway)?;
+    match socket.local_addr()? {
+        SocketAddr::V4(addr) => Ok(*addr.ip()),
+        SocketAddr::V6(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "gateway is not IPv4",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn global_addresses() {
+        assert!(is_global("1.2.3.4".parse().unwrap()));
+        assert!(!is_global("192.168.1.2".parse().unwrap()));
+        assert!(!is_global("100.64.0.1".parse().unwrap()));
+        assert!(is_global("100.128.0.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn nat_pmp_mapping() {
+        let request = nat_pmp_mapping_request(NAT_PMP_MAP_TCP, 9000, Duration::from_secs(3600));
+        assert_eq!(
+            request,
+            [0, 2, 0, 0, 0x23, 0x28, 0x23, 0x28, 0, 0, 0x0e, 0x10]
+        );
+
+        let mut response = [
+            0, 130, 0, 0, 0, 0, 0, 1, 0x23, 0x28, 0x23, 0x29, 0, 0, 0x0e, 0x10,
+        ];
+        // The gateway may choose another external port.
+        assert_eq!(
+            parse_nat_pmp_mapping(&response, NAT_PMP_MAP_TCP, 9000),
+            Ok(9001)
+        );
+        assert!(parse_nat_pmp_mapping(&response, NAT_PMP_MAP_UDP, 9000).is_err());
+        assert!(parse_nat_pmp_mapping(&response, NAT_PMP_MAP_TCP, 9001).is_err());
+        assert!(parse_nat_pmp_mapping(&response[..12], NAT_PMP_MAP_TCP, 9000).is_err());
+        // Not authorized.
+        response[3] = 2;
+        assert!(parse_nat_pmp_mapping(&response, NAT_PMP_MAP_TCP, 9000).is_err());
+    }
+
+    #[test]
+    fn nat_pmp_external_address() {
+        let response = [0, 128, 0, 0, 0, 0, 0, 1, 1, 2, 3, 4];
+        assert_eq!(
+            parse_nat_pmp_external_address(&response),
+            Ok("1.2.3.4".parse().unwrap())
+        );
+        assert!(parse_nat_pmp_external_address(&response[..8]).is_err());
+    }
+
+    #[test]
+    fn default_gateway_from_routing_table() {
+        let gateway = u32::from_ne_bytes([192, 168, 1, 1]);
+        let routes = format!(
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\n\
+             eth0\t0001A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\n\
+             eth0\t00000000\t{:08X}\t0003\t0\t0\t0\t00000000\n",
+            gateway
+        );
+        assert_eq!(
+            parse_default_gateway(&routes),
+            Some(Ipv4Addr::new(192, 168, 1, 1))
+        );
+        assert_eq!(parse_default_gateway("Iface\tDestination\tGateway\n"), None);
+    }
+}
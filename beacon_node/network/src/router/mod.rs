@@ -16,6 +16,7 @@ use eth2_libp2p::{
 };
 use futures::prelude::*;
 use processor::Processor;
+use retry::RetryConfig;
 use slog::{debug, o, trace};
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -74,6 +75,7 @@ impl<T: BeaconChainTypes> Router<T> {
         beacon_chain: Arc<BeaconChain<T>>,
         network_globals: Arc<NetworkGlobals<T::EthSpec>>,
         network_send: mpsc::UnboundedSender<NetworkMessage<T::EthSpec>>,
+        sync_retry: RetryConfig,
         executor: environment::TaskExecutor,
         log: slog::Logger,
     ) -> error::Result<mpsc::UnboundedSender<RouterMessage<T::EthSpec>>> {
@@ -88,6 +90,7 @@ impl<T: BeaconChainTypes> Router<T> {
             beacon_chain,
             network_globals.clone(),
             network_send,
+            sync_retry,
             &log,
         );
 
@@ -4,11 +4,13 @@ use crate::beacon_processor::{
 use crate::service::NetworkMessage;
 use crate::sync::{PeerSyncInfo, SyncMessage};
 use beacon_chain::{BeaconChain, BeaconChainTypes};
+use environment::ShutdownStage;
 use eth2_libp2p::rpc::*;
 use eth2_libp2p::{
     MessageId, NetworkGlobals, PeerAction, PeerId, PeerRequestId, Request, Response,
 };
 use itertools::process_results;
+use retry::RetryConfig;
 use slog::{debug, error, o, trace, warn};
 use std::cmp;
 use std::sync::Arc;
@@ -44,6 +46,7 @@ impl<T: BeaconChainTypes> Processor<T> {
         beacon_chain: Arc<BeaconChain<T>>,
         network_globals: Arc<NetworkGlobals<T::EthSpec>>,
         network_send: mpsc::UnboundedSender<NetworkMessage<T::EthSpec>>,
+        sync_retry: RetryConfig,
         log: &slog::Logger,
     ) -> Self {
         let sync_logger = log.new(o!("service"=> "sync"));
@@ -52,11 +55,12 @@ impl<T: BeaconChainTypes> Processor<T> {
 
         // spawn the sync thread
         let sync_send = crate::sync::manager::spawn(
-            executor.clone(),
+            executor.clone().with_shutdown_stage(ShutdownStage::Sync),
             beacon_chain.clone(),
             network_globals.clone(),
             network_send.clone(),
             beacon_processor_send.clone(),
+            sync_retry,
             sync_logger,
         );
 
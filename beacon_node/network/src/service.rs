@@ -1,3 +1,4 @@
+use crate::nat::{spawn_upnp_mapping, UPnPConfig};
 use crate::persisted_dht::{load_dht, persist_dht};
 use crate::router::{Router, RouterMessage};
 use crate::{
@@ -6,6 +7,7 @@ use crate::{
 };
 use crate::{error, metrics};
 use beacon_chain::{BeaconChain, BeaconChainTypes};
+use environment::ShutdownReason;
 use eth2_libp2p::{
     rpc::{GoodbyeReason, RPCResponseErrorCode, RequestId},
     Libp2pEvent, PeerAction, PeerRequestId, PubsubMessage, Request, Response,
@@ -17,7 +19,7 @@ use eth2_libp2p::{MessageAcceptance, Service as LibP2PService};
 use futures::prelude::*;
 use rest_types::ValidatorSubscription;
 use slog::{debug, error, info, o, trace, warn};
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{collections::HashMap, net::Ipv4Addr, sync::Arc, time::Duration};
 use store::HotColdDB;
 use tokio::sync::mpsc;
 use tokio::time::Delay;
@@ -74,6 +76,13 @@ pub enum NetworkMessage<T: EthSpec> {
         peer_id: PeerId,
         reason: GoodbyeReason,
     },
+    /// The listening ports have been mapped on the local gateway with UPnP, making the node
+    /// reachable at the external `ip`.
+    UPnPMappingEstablished {
+        ip: Ipv4Addr,
+        tcp_port: Option<u16>,
+        udp_port: Option<u16>,
+    },
 }
 
 /// Service that handles communication between internal services and the `eth2_libp2p` network service.
@@ -144,10 +153,16 @@ impl<T: BeaconChainTypes> NetworkService<T> {
             beacon_chain.clone(),
             network_globals.clone(),
             network_send.clone(),
+            config.sync_retry,
             executor.clone(),
             network_log.clone(),
         )?;
 
+        // map the listening ports on the local gateway
+        if config.upnp_enabled {
+            spawn_upnp_mapping(UPnPConfig::from(config), network_send.clone(), &executor);
+        }
+
         // attestation service
         let attestation_service =
             AttestationService::new(beacon_chain.clone(), network_globals.clone(), &network_log);
@@ -212,6 +227,8 @@ fn spawn_service<T: BeaconChainTypes>(
                         ),
                     }
 
+                    service.libp2p.swarm.persist_known_peers();
+
                     info!(service.log, "Network service shutdown");
                     return;
                 }
@@ -272,6 +289,9 @@ fn spawn_service<T: BeaconChainTypes>(
                         }
                         NetworkMessage::ReportPeer { peer_id, action } => service.libp2p.report_peer(&peer_id, action),
                         NetworkMessage::GoodbyePeer { peer_id, reason } => service.libp2p.goodbye_peer(&peer_id, reason),
+                        NetworkMessage::UPnPMappingEstablished { ip, tcp_port, udp_port } => {
+                            service.libp2p.swarm.update_enr_external_address(ip, tcp_port, udp_port);
+                        }
                         NetworkMessage::Subscribe { subscriptions } => {
                             if let Err(e) = service
                                 .attestation_service
@@ -403,7 +423,7 @@ fn spawn_service<T: BeaconChainTypes>(
                             service.network_globals.listen_multiaddrs.write().push(multiaddr);
                         }
                         Libp2pEvent::ZeroListeners => {
-                            let _ = shutdown_sender.send("All listeners are closed. Unable to listen").await.map_err(|e| {
+                            let _ = shutdown_sender.send(ShutdownReason::Failure("All listeners are closed. Unable to listen")).await.map_err(|e| {
                                 warn!(service.log, "failed to send a shutdown signal"; "error" => e.to_string()
                                 )
                             });
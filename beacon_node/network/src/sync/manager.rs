@@ -24,8 +24,8 @@
 //!
 //! When a block with an unknown parent is received and we are in `Regular` sync mode, the block is
 //! queued for lookup. A round-robin approach is used to request the parent from the known list of
-//! fully sync'd peers. If `PARENT_FAIL_TOLERANCE` attempts at requesting the block fails, we
-//! drop the propagated block and downvote the peer that sent it to us.
+//! fully sync'd peers. If the retries allowed by the `sync_retry` of the network config are
+//! exhausted, we drop the propagated block and downvote the peer that sent it to us.
 //!
 //! Block Lookup
 //!
@@ -45,6 +45,7 @@ use eth2_libp2p::types::NetworkGlobals;
 use eth2_libp2p::{PeerAction, PeerId};
 use fnv::FnvHashMap;
 use lru_cache::LRUCache;
+use retry::RetryConfig;
 use slog::{crit, debug, error, info, trace, warn, Logger};
 use smallvec::SmallVec;
 use ssz_types::VariableList;
@@ -58,8 +59,6 @@ use types::{Epoch, EthSpec, Hash256, SignedBeaconBlock, Slot};
 /// from a peer. If a peer is within this tolerance (forwards or backwards), it is treated as a
 /// fully sync'd peer.
 pub const SLOT_IMPORT_TOLERANCE: usize = 20;
-/// How many attempts we try to find a parent of a block before we give up trying .
-const PARENT_FAIL_TOLERANCE: usize = 5;
 /// The maximum depth we will search for a parent block. In principle we should have sync'd any
 /// canonical chain to its head once the peer connects. A chain should not appear where it's depth
 /// is further back than the most recent head slot.
@@ -176,6 +175,9 @@ pub struct SyncManager<T: BeaconChainTypes> {
     /// The flag allows us to determine if the peer returned data or sent us nothing.
     single_block_lookups: FnvHashMap<RequestId, SingleBlockRequest>,
 
+    /// How many times we retry finding the parent of a block before we give up trying.
+    parent_lookup_retry: RetryConfig,
+
     /// The logger for the import manager.
     log: Logger,
 
@@ -209,6 +211,7 @@ pub fn spawn<T: BeaconChainTypes>(
     network_globals: Arc<NetworkGlobals<T::EthSpec>>,
     network_send: mpsc::UnboundedSender<NetworkMessage<T::EthSpec>>,
     beacon_processor_send: mpsc::Sender<BeaconWorkEvent<T::EthSpec>>,
+    retry: RetryConfig,
     log: slog::Logger,
 ) -> mpsc::UnboundedSender<SyncMessage<T::EthSpec>> {
     assert!(
@@ -224,6 +227,7 @@ pub fn spawn<T: BeaconChainTypes>(
             beacon_chain.clone(),
             network_globals.clone(),
             beacon_processor_send.clone(),
+            retry,
             log.clone(),
         ),
         network: SyncNetworkContext::new(network_send, network_globals.clone(), log.clone()),
@@ -233,6 +237,7 @@ pub fn spawn<T: BeaconChainTypes>(
         parent_queue: SmallVec::new(),
         failed_chains: LRUCache::new(500),
         single_block_lookups: FnvHashMap::default(),
+        parent_lookup_retry: retry,
         log: log.clone(),
         beacon_processor_send,
     };
@@ -803,10 +808,11 @@ impl<T: BeaconChainTypes> SyncManager<T> {
     /// initiates requests.
     fn request_parent(&mut self, mut parent_request: ParentRequests<T::EthSpec>) {
         // check to make sure this request hasn't failed
-        if parent_request.failed_attempts >= PARENT_FAIL_TOLERANCE
-            || parent_request.downloaded_blocks.len() >= PARENT_DEPTH_TOLERANCE
-        {
-            let error = if parent_request.failed_attempts >= PARENT_FAIL_TOLERANCE {
+        let retries_exhausted = self
+            .parent_lookup_retry
+            .is_exhausted(parent_request.failed_attempts);
+        if retries_exhausted || parent_request.downloaded_blocks.len() >= PARENT_DEPTH_TOLERANCE {
+            let error = if retries_exhausted {
                 // This is a peer-specific error and the chain could be continued with another
                 // peer. We don't consider this chain a failure and prevent retries with another
                 // peer.
@@ -6,6 +6,7 @@ use crate::sync::{network_context::SyncNetworkContext, BatchProcessResult};
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_libp2p::{PeerAction, PeerId};
 use rand::prelude::*;
+use retry::RetryConfig;
 use slog::{crit, debug, error, warn};
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -20,9 +21,6 @@ use types::{Epoch, EthSpec, Hash256, SignedBeaconBlock, Slot};
 /// to do so.
 pub const EPOCHS_PER_BATCH: u64 = 2;
 
-/// The number of times to retry a batch before the chain is considered failed and removed.
-const MAX_BATCH_RETRIES: u8 = 5;
-
 /// The maximum number of batches to queue before requesting more.
 const BATCH_BUFFER_SIZE: u8 = 5;
 
@@ -92,6 +90,9 @@ pub struct SyncingChain<T: BeaconChainTypes> {
     /// A reference to the underlying beacon chain.
     chain: Arc<BeaconChain<T>>,
 
+    /// The number of times to retry a batch before the chain is considered failed and removed.
+    batch_retry: RetryConfig,
+
     /// The chain's log.
     log: slog::Logger,
 }
@@ -114,6 +115,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
         peer_id: PeerId,
         beacon_processor_send: mpsc::Sender<BeaconWorkEvent<T::EthSpec>>,
         chain: Arc<BeaconChain<T>>,
+        batch_retry: RetryConfig,
         log: slog::Logger,
     ) -> Self {
         let mut peer_pool = HashSet::new();
@@ -134,6 +136,7 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             current_processing_batch: None,
             beacon_processor_send,
             chain,
+            batch_retry,
             log,
         }
     }
@@ -654,19 +657,19 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
     /// These events do not indicate a malicious peer, more likely simple networking issues.
     ///
     /// Attempts to re-request from another peer in the peer pool (if possible) and returns
-    /// `ProcessingResult::RemoveChain` if the number of retries on the batch exceeds
-    /// `MAX_BATCH_RETRIES`.
+    /// `ProcessingResult::RemoveChain` if the batch has exhausted the retries allowed by
+    /// `self.batch_retry`.
     pub fn failed_batch(
         &mut self,
         network: &mut SyncNetworkContext<T::EthSpec>,
         mut batch: Batch<T::EthSpec>,
     ) -> ProcessingResult {
-        batch.retries += 1;
-
-        if batch.retries > MAX_BATCH_RETRIES || self.peer_pool.is_empty() {
+        if self.batch_retry.is_exhausted(batch.retries as usize) || self.peer_pool.is_empty() {
             // chain is unrecoverable, remove it
             ProcessingResult::RemoveChain
         } else {
+            batch.retries += 1;
+
             // try to re-process the request using a different peer, if possible
             let current_peer = &batch.current_peer;
             let new_peer = self
@@ -88,6 +88,8 @@ pub struct ChainCollection<T: BeaconChainTypes> {
     head_chains: Vec<SyncingChain<T>>,
     /// The current sync state of the process.
     state: RangeSyncState,
+    /// The retries allowed for each batch of the chains.
+    batch_retry: RetryConfig,
     /// Logger for the collection.
     log: slog::Logger,
 }
@@ -96,6 +98,7 @@ impl<T: BeaconChainTypes> ChainCollection<T> {
     pub fn new(
         beacon_chain: Arc<BeaconChain<T>>,
         network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+        batch_retry: RetryConfig,
         log: slog::Logger,
     ) -> Self {
         ChainCollection {
@@ -104,6 +107,7 @@ impl<T: BeaconChainTypes> ChainCollection<T> {
             finalized_chains: Vec::new(),
             head_chains: Vec::new(),
             state: RangeSyncState::Idle,
+            batch_retry,
             log,
         }
     }
@@ -393,6 +397,7 @@ impl<T: BeaconChainTypes> ChainCollection<T> {
             peer_id,
             beacon_processor_send,
             self.beacon_chain.clone(),
+            self.batch_retry,
             self.log.new(o!("chain" => chain_id)),
         ));
     }
@@ -423,6 +428,7 @@ impl<T: BeaconChainTypes> ChainCollection<T> {
             peer_id,
             beacon_processor_send,
             self.beacon_chain.clone(),
+            self.batch_retry,
             self.log.clone(),
         );
         self.head_chains.push(new_head_chain);
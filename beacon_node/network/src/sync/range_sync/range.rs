@@ -49,6 +49,7 @@ use crate::sync::PeerSyncInfo;
 use crate::sync::RequestId;
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_libp2p::{NetworkGlobals, PeerId};
+use retry::RetryConfig;
 use slog::{debug, error, trace};
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -79,11 +80,12 @@ impl<T: BeaconChainTypes> RangeSync<T> {
         beacon_chain: Arc<BeaconChain<T>>,
         network_globals: Arc<NetworkGlobals<T::EthSpec>>,
         beacon_processor_send: mpsc::Sender<BeaconWorkEvent<T::EthSpec>>,
+        batch_retry: RetryConfig,
         log: slog::Logger,
     ) -> Self {
         RangeSync {
             beacon_chain: beacon_chain.clone(),
-            chains: ChainCollection::new(beacon_chain, network_globals, log.clone()),
+            chains: ChainCollection::new(beacon_chain, network_globals, batch_retry, log.clone()),
             awaiting_head_peers: HashSet::new(),
             beacon_processor_send,
             log,
@@ -1,8 +1,10 @@
 mod attestation;
 mod attestation_id;
 mod max_cover;
+mod packing;
 mod persistence;
 
+pub use packing::{BlockPackingConfig, PackingPriority};
 pub use persistence::PersistedOperationPool;
 
 use attestation::AttMaxCover;
@@ -95,6 +97,15 @@ impl<T: EthSpec> OperationPool<T> {
         self.attestations.read().values().map(Vec::len).sum()
     }
 
+    /// Returns all attestations in the pool, regardless of their validity.
+    pub fn get_all_attestations(&self) -> Vec<Attestation<T>> {
+        self.attestations
+            .read()
+            .values()
+            .flat_map(|attestations| attestations.iter().cloned())
+            .collect()
+    }
+
     /// Get a list of attestations for inclusion in a block.
     ///
     /// The `validity_filter` is a closure that provides extra filtering of the attestations
@@ -201,13 +212,33 @@ impl<T: EthSpec> OperationPool<T> {
         &self,
         state: &BeaconState<T>,
     ) -> (Vec<ProposerSlashing>, Vec<AttesterSlashing<T>>) {
+        let (proposer_slashings, attester_slashings, _) =
+            self.get_slashings_sparing(state, &HashSet::new());
+        (proposer_slashings, attester_slashings)
+    }
+
+    /// Get proposer and attester slashings for inclusion in a block, none of which slash a
+    /// validator in `spared`.
+    ///
+    /// The indices of the validators which the slashings slash are also returned.
+    fn get_slashings_sparing(
+        &self,
+        state: &BeaconState<T>,
+        spared: &HashSet<u64>,
+    ) -> (
+        Vec<ProposerSlashing>,
+        Vec<AttesterSlashing<T>>,
+        HashSet<u64>,
+    ) {
         let proposer_slashings = filter_limit_operations(
             self.proposer_slashings.read().values(),
             |slashing| {
-                state
-                    .validators
-                    .get(slashing.signed_header_1.message.proposer_index as usize)
-                    .map_or(false, |validator| !validator.slashed)
+                let proposer_index = slashing.signed_header_1.message.proposer_index;
+                !spared.contains(&proposer_index)
+                    && state
+                        .validators
+                        .get(proposer_index as usize)
+                        .map_or(false, |validator| !validator.slashed)
             },
             T::MaxProposerSlashings::to_usize(),
         );
@@ -229,6 +260,16 @@ impl<T: EthSpec> OperationPool<T> {
                     return false;
                 }
 
+                // Skip slashings that would slash a spared validator.
+                if !spared.is_empty()
+                    && get_slashable_indices_modular(state, slashing, |index, validator| {
+                        validator.is_slashable_at(epoch) && spared.contains(&index)
+                    })
+                    .is_ok()
+                {
+                    return false;
+                }
+
                 // Take all slashings that will slash 1 or more validators.
                 let slashed_validators =
                     get_slashable_indices_modular(state, slashing, |index, validator| {
@@ -248,7 +289,7 @@ impl<T: EthSpec> OperationPool<T> {
             .map(|(slashing, _)| slashing.clone())
             .collect();
 
-        (proposer_slashings, attester_slashings)
+        (proposer_slashings, attester_slashings, to_be_slashed)
     }
 
     /// Prune proposer slashings for all slashed or withdrawn validators.
@@ -303,14 +344,75 @@ impl<T: EthSpec> OperationPool<T> {
         &self,
         state: &BeaconState<T>,
         spec: &ChainSpec,
+    ) -> Vec<SignedVoluntaryExit> {
+        self.get_voluntary_exits_sparing(state, &HashSet::new(), spec)
+    }
+
+    /// Get a list of voluntary exits for inclusion in a block, none of which exit a validator in
+    /// `spared`.
+    fn get_voluntary_exits_sparing(
+        &self,
+        state: &BeaconState<T>,
+        spared: &HashSet<u64>,
+        spec: &ChainSpec,
     ) -> Vec<SignedVoluntaryExit> {
         filter_limit_operations(
             self.voluntary_exits.read().values(),
-            |exit| verify_exit(state, exit, VerifySignatures::False, spec).is_ok(),
+            |exit| {
+                !spared.contains(&exit.message.validator_index)
+                    && verify_exit(state, exit, VerifySignatures::False, spec).is_ok()
+            },
             T::MaxVoluntaryExits::to_usize(),
         )
     }
 
+    /// Get the slashings and voluntary exits for inclusion in a block, according to `config`.
+    ///
+    /// A validator is never both slashed and exited, since the block would be invalid.
+    pub fn get_slashings_and_exits(
+        &self,
+        state: &BeaconState<T>,
+        config: &BlockPackingConfig,
+        spec: &ChainSpec,
+    ) -> (
+        Vec<ProposerSlashing>,
+        Vec<AttesterSlashing<T>>,
+        Vec<SignedVoluntaryExit>,
+    ) {
+        let pack_slashings = |spared: &HashSet<u64>| {
+            if config.include_slashings {
+                self.get_slashings_sparing(state, spared)
+            } else {
+                (vec![], vec![], HashSet::new())
+            }
+        };
+        let pack_exits = |spared: &HashSet<u64>| {
+            if config.include_exits {
+                self.get_voluntary_exits_sparing(state, spared, spec)
+            } else {
+                vec![]
+            }
+        };
+
+        match config.priority {
+            PackingPriority::Slashings => {
+                let (proposer_slashings, attester_slashings, slashed) =
+                    pack_slashings(&HashSet::new());
+                let voluntary_exits = pack_exits(&slashed);
+                (proposer_slashings, attester_slashings, voluntary_exits)
+            }
+            PackingPriority::Exits => {
+                let voluntary_exits = pack_exits(&HashSet::new());
+                let exiting = voluntary_exits
+                    .iter()
+                    .map(|exit| exit.message.validator_index)
+                    .collect::<HashSet<_>>();
+                let (proposer_slashings, attester_slashings, _) = pack_slashings(&exiting);
+                (proposer_slashings, attester_slashings, voluntary_exits)
+            }
+        }
+    }
+
     /// Prune if validator has already exited at the last finalized state.
     pub fn prune_voluntary_exits(&self, finalized_state: &BeaconState<T>) {
         prune_validator_hash_map(
@@ -332,6 +434,11 @@ impl<T: EthSpec> OperationPool<T> {
     pub fn num_voluntary_exits(&self) -> usize {
         self.voluntary_exits.read().len()
     }
+
+    /// Returns the voluntary exit of the given validator, if there is one in the pool.
+    pub fn get_voluntary_exit(&self, validator_index: u64) -> Option<SignedVoluntaryExit> {
+        self.voluntary_exits.read().get(&validator_index).cloned()
+    }
 }
 
 /// Filter up to a maximum number of operations out of an iterator.
@@ -889,6 +996,39 @@ mod release_tests {
             }
         }
 
+        /// Create a context at an epoch in which every validator has been active for long enough
+        /// to exit.
+        fn new_with_exitable_validators() -> Self {
+            let spec = MainnetEthSpec::default_spec();
+            let num_validators = 32;
+            let mut state_builder =
+                TestingBeaconStateBuilder::<MainnetEthSpec>::from_deterministic_keypairs(
+                    num_validators,
+                    &spec,
+                );
+            let epoch = Epoch::new(spec.shard_committee_period);
+            state_builder.teleport_to_slot(epoch.start_slot(MainnetEthSpec::slots_per_epoch()));
+            state_builder.build_caches(&spec).unwrap();
+            let (state, keypairs) = state_builder.build();
+            let op_pool = OperationPool::new();
+
+            TestContext {
+                spec,
+                state,
+                keypairs,
+                op_pool,
+            }
+        }
+
+        fn voluntary_exit(&self, validator_index: u64) -> SignedVoluntaryExit {
+            TestingVoluntaryExitBuilder::new(self.state.current_epoch(), validator_index).build(
+                &self.keypairs[validator_index as usize].sk,
+                &self.state.fork,
+                self.state.genesis_validators_root,
+                &self.spec,
+            )
+        }
+
         fn proposer_slashing(&self, proposer_index: u64) -> ProposerSlashing {
             TestingProposerSlashingBuilder::double_vote::<MainnetEthSpec>(
                 ProposerSlashingTestTask::Valid,
@@ -959,4 +1099,63 @@ mod release_tests {
         op_pool.prune_attester_slashings(state, state.fork);
         assert_eq!(op_pool.get_slashings(state).1, vec![slashing]);
     }
+
+    /// A validator which is both slashed and exited is packed once, according to the priority.
+    #[test]
+    fn proposer_slashing_and_exit_priority() {
+        let ctxt = TestContext::new_with_exitable_validators();
+        let (op_pool, state, spec) = (&ctxt.op_pool, &ctxt.state, &ctxt.spec);
+        let slashing = ctxt.proposer_slashing(0);
+        let exit = ctxt.voluntary_exit(0);
+        let other_exit = ctxt.voluntary_exit(1);
+        op_pool.insert_proposer_slashing(slashing.clone().validate(state, spec).unwrap());
+        op_pool.insert_voluntary_exit(exit.clone().validate(state, spec).unwrap());
+        op_pool.insert_voluntary_exit(other_exit.clone().validate(state, spec).unwrap());
+
+        let mut config = BlockPackingConfig::default();
+        let (proposer_slashings, _, exits) = op_pool.get_slashings_and_exits(state, &config, spec);
+        assert_eq!(proposer_slashings, vec![slashing.clone()]);
+        assert_eq!(exits, vec![other_exit.clone()]);
+
+        config.priority = PackingPriority::Exits;
+        let (proposer_slashings, _, mut exits) =
+            op_pool.get_slashings_and_exits(state, &config, spec);
+        exits.sort_by_key(|exit| exit.message.validator_index);
+        assert!(proposer_slashings.is_empty());
+        assert_eq!(exits, vec![exit, other_exit]);
+
+        config.include_exits = false;
+        let (proposer_slashings, _, exits) = op_pool.get_slashings_and_exits(state, &config, spec);
+        assert_eq!(proposer_slashings, vec![slashing]);
+        assert!(exits.is_empty());
+    }
+
+    /// An attester slashing is not packed if it would slash an exiting validator and exits have
+    /// priority.
+    #[test]
+    fn attester_slashing_and_exit_priority() {
+        let ctxt = TestContext::new_with_exitable_validators();
+        let (op_pool, state, spec) = (&ctxt.op_pool, &ctxt.state, &ctxt.spec);
+        let slashing = ctxt.attester_slashing(&[1, 3, 5]);
+        let exit = ctxt.voluntary_exit(3);
+        op_pool
+            .insert_attester_slashing(slashing.clone().validate(state, spec).unwrap(), state.fork);
+        op_pool.insert_voluntary_exit(exit.clone().validate(state, spec).unwrap());
+
+        let mut config = BlockPackingConfig::default();
+        let (_, attester_slashings, exits) = op_pool.get_slashings_and_exits(state, &config, spec);
+        assert_eq!(attester_slashings, vec![slashing.clone()]);
+        assert!(exits.is_empty());
+
+        config.priority = PackingPriority::Exits;
+        let (_, attester_slashings, exits) = op_pool.get_slashings_and_exits(state, &config, spec);
+        assert!(attester_slashings.is_empty());
+        assert_eq!(exits, vec![exit]);
+
+        config.include_slashings = false;
+        config.priority = PackingPriority::Slashings;
+        let (_, attester_slashings, exits) = op_pool.get_slashings_and_exits(state, &config, spec);
+        assert!(attester_slashings.is_empty());
+        assert_eq!(exits.len(), 1);
+    }
 }
@@ -0,0 +1,53 @@
+use serde_derive::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Decides which operation is packed into a block when a validator could be both slashed and
+/// exited by it.
+///
+/// Slashing a validator initiates its exit, so a block which does both is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackingPriority {
+    /// Omit the voluntary exits of validators which are slashed by the block.
+    Slashings,
+    /// Omit the slashings which would slash a validator that exits in the block.
+    Exits,
+}
+
+impl FromStr for PackingPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "slashings" => Ok(PackingPriority::Slashings),
+            "exits" => Ok(PackingPriority::Exits),
+            other => Err(format!(
+                "Unknown packing priority {:?}, expected \"slashings\" or \"exits\"",
+                other
+            )),
+        }
+    }
+}
+
+/// Controls the slashings and voluntary exits which are packed into a block, within the
+/// per-block limit of each.
+///
+/// Attestations are always packed, in order of their reward to the proposer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockPackingConfig {
+    /// If false, no proposer or attester slashings are packed.
+    pub include_slashings: bool,
+    /// If false, no voluntary exits are packed.
+    pub include_exits: bool,
+    pub priority: PackingPriority,
+}
+
+impl Default for BlockPackingConfig {
+    fn default() -> Self {
+        Self {
+            include_slashings: true,
+            include_exits: true,
+            priority: PackingPriority::Slashings,
+        }
+    }
+}
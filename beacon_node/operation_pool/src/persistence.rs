@@ -65,6 +65,28 @@ impl<T: EthSpec> PersistedOperationPool<T> {
         }
     }
 
+    /// All attestations in the pool.
+    pub fn attestations(&self) -> impl Iterator<Item = &Attestation<T>> {
+        self.attestations
+            .iter()
+            .flat_map(|(_, attestations)| attestations)
+    }
+
+    /// All attester slashings in the pool.
+    pub fn attester_slashings(&self) -> impl Iterator<Item = &AttesterSlashing<T>> {
+        self.attester_slashings.iter().map(|(slashing, _)| slashing)
+    }
+
+    /// All proposer slashings in the pool.
+    pub fn proposer_slashings(&self) -> &[ProposerSlashing] {
+        &self.proposer_slashings
+    }
+
+    /// All voluntary exits in the pool.
+    pub fn voluntary_exits(&self) -> &[SignedVoluntaryExit] {
+        &self.voluntary_exits
+    }
+
     /// Reconstruct an `OperationPool`.
     pub fn into_operation_pool(self) -> OperationPool<T> {
         let attestations = RwLock::new(self.attestations.into_iter().collect());
@@ -3,22 +3,26 @@ use crate::validator::get_state_for_epoch;
 use crate::Context;
 use crate::{ApiError, UrlQuery};
 use beacon_chain::{
-    observed_operations::ObservationOutcome, BeaconChain, BeaconChainTypes, StateSkipConfig,
+    events::ServerSentEvent, observed_operations::ObservationOutcome, BeaconChain,
+    BeaconChainTypes, StateSkipConfig,
 };
+use eth1::DepositTreeSnapshot;
 use futures::executor::block_on;
 use hyper::body::Bytes;
 use hyper::{Body, Request};
+use itertools::process_results;
 use rest_types::{
-    BlockResponse, CanonicalHeadResponse, Committee, HeadBeaconBlock, StateResponse,
-    ValidatorRequest, ValidatorResponse,
+    BlockResponse, BlockSummary, CanonicalHeadResponse, Committee, DutiesDependencyEvent,
+    HeadBeaconBlock, HeadLaggingEvent, HeaderResponse, StateResponse, ValidatorRequest,
+    ValidatorResponse,
 };
 use std::io::Write;
 use std::sync::Arc;
 
 use slog::error;
 use types::{
-    AttesterSlashing, BeaconState, EthSpec, Hash256, ProposerSlashing, PublicKeyBytes,
-    RelativeEpoch, SignedBeaconBlockHash, Slot,
+    AttesterSlashing, BeaconState, Epoch, EthSpec, Hash256, ProposerSlashing, PublicKeyBytes,
+    RelativeEpoch, SignedBeaconBlockHeader, SignedVoluntaryExit, Slot,
 };
 
 /// Returns a summary of the head of the beacon chain.
@@ -66,38 +70,29 @@ pub fn get_heads<T: BeaconChainTypes>(ctx: Arc<Context<T>>) -> Vec<HeadBeaconBlo
 }
 
 /// HTTP handler to return a `BeaconBlock` at a given `root` or `slot`.
+///
+/// A `BlockSummary` is included if `summary=true` is supplied.
 pub fn get_block<T: BeaconChainTypes>(
     req: Request<Vec<u8>>,
     ctx: Arc<Context<T>>,
 ) -> Result<BlockResponse<T::EthSpec>, ApiError> {
     let beacon_chain = &ctx.beacon_chain;
+    let query = UrlQuery::from_request(&req)?;
     let query_params = ["root", "slot"];
-    let (key, value) = UrlQuery::from_request(&req)?.first_of(&query_params)?;
-
-    let block_root = match (key.as_ref(), value) {
-        ("slot", value) => {
-            let target = parse_slot(&value)?;
-
-            block_root_at_slot(beacon_chain, target)?.ok_or_else(|| {
-                ApiError::NotFound(format!(
-                    "Unable to find SignedBeaconBlock for slot {:?}",
-                    target
-                ))
-            })?
-        }
-        ("root", value) => parse_root(&value)?,
-        _ => return Err(ApiError::ServerError("Unexpected query parameter".into())),
-    };
+    let (key, value) = query.first_of(&query_params)?;
+    let summary = query.summary()?;
 
-    let block = beacon_chain.store.get_block(&block_root)?.ok_or_else(|| {
-        ApiError::NotFound(format!(
-            "Unable to find SignedBeaconBlock for root {:?}",
-            block_root
-        ))
-    })?;
+    let (resolved, block) = BlockId::from_query(&key, &value)?.resolve(beacon_chain)?;
 
     Ok(BlockResponse {
-        root: block_root,
+        root: resolved.root,
+        slot: resolved.slot,
+        canonical: resolved.canonical,
+        summary: if summary {
+            Some(BlockSummary::from_block(&block.message))
+        } else {
+            None
+        },
         beacon_block: block,
     })
 }
@@ -108,33 +103,163 @@ pub fn get_block_root<T: BeaconChainTypes>(
     ctx: Arc<Context<T>>,
 ) -> Result<Hash256, ApiError> {
     let slot_string = UrlQuery::from_request(&req)?.only_one("slot")?;
-    let target = parse_slot(&slot_string)?;
 
-    block_root_at_slot(&ctx.beacon_chain, target)?.ok_or_else(|| {
-        ApiError::NotFound(format!(
-            "Unable to find SignedBeaconBlock for slot {:?}",
-            target
-        ))
-    })
+    BlockId::Slot(parse_slot(&slot_string)?).root(&ctx.beacon_chain)
 }
 
-fn make_sse_response_chunk(new_head_hash: SignedBeaconBlockHash) -> std::io::Result<Bytes> {
+/// HTTP handler to return the headers of the canonical blocks in a given `epoch`, in order of
+/// ascending slot.
+///
+/// Skipped slots and slots beyond the head are omitted, so fewer than `SLOTS_PER_EPOCH` headers
+/// may be returned. A `BlockSummary` is included with each header if `summary=true` is supplied.
+pub fn get_headers<T: BeaconChainTypes>(
+    req: Request<Vec<u8>>,
+    ctx: Arc<Context<T>>,
+) -> Result<Vec<HeaderResponse>, ApiError> {
+    let query = UrlQuery::from_request(&req)?;
+    let epoch = query.epoch()?;
+    let summary = query.summary()?;
+    let start_slot = epoch.start_slot(T::EthSpec::slots_per_epoch());
+    let end_slot = epoch.end_slot(T::EthSpec::slots_per_epoch());
+
+    let beacon_chain = &ctx.beacon_chain;
+
+    // The iterator returns the prior block root for skipped slots, so roots may repeat.
+    let mut roots = process_results(beacon_chain.rev_iter_block_roots()?, |iter| {
+        iter.take_while(|(_, slot)| *slot >= start_slot)
+            .filter(|(_, slot)| *slot <= end_slot)
+            .map(|(root, _)| root)
+            .collect::<Vec<_>>()
+    })?;
+    roots.dedup();
+
+    let mut headers = Vec::with_capacity(roots.len());
+    for root in roots.into_iter().rev() {
+        let block = beacon_chain.store.get_block(&root)?.ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Unable to find SignedBeaconBlock for root {:?}",
+                root
+            ))
+        })?;
+
+        // The first root may be that of a block prior to the epoch, if its first slot was skipped.
+        if block.message.slot < start_slot {
+            continue;
+        }
+
+        headers.push(HeaderResponse {
+            root,
+            header: SignedBeaconBlockHeader {
+                message: block.message.block_header(),
+                signature: block.signature,
+            },
+            summary: if summary {
+                Some(BlockSummary::from_block(&block.message))
+            } else {
+                None
+            },
+        });
+    }
+
+    Ok(headers)
+}
+
+/// A topic of the fork stream, which a client may select with the `topic` query parameter.
+#[derive(Clone, Copy, PartialEq)]
+enum StreamTopic {
+    /// The root of each new head, sent as unnamed events.
+    Head,
+    /// `lagging` events, sent when the head is persistently being updated late in the slot.
+    Lagging,
+    /// `duties_changed` events, sent when validator duties may have changed.
+    DutiesChanged,
+}
+
+impl StreamTopic {
+    fn parse(topic: &str) -> Result<Self, ApiError> {
+        match topic {
+            "head" => Ok(StreamTopic::Head),
+            "lagging" => Ok(StreamTopic::Lagging),
+            "duties_changed" => Ok(StreamTopic::DutiesChanged),
+            other => Err(ApiError::BadRequest(format!(
+                "Unknown topic {:?}, expected one of head, lagging or duties_changed",
+                other
+            ))),
+        }
+    }
+
+    fn of(event: &ServerSentEvent) -> Self {
+        match event {
+            ServerSentEvent::HeadChanged(_) => StreamTopic::Head,
+            ServerSentEvent::Lagging { .. } => StreamTopic::Lagging,
+            ServerSentEvent::DutiesChanged { .. } => StreamTopic::DutiesChanged,
+        }
+    }
+}
+
+fn make_sse_response_chunk(event: ServerSentEvent) -> std::io::Result<Bytes> {
     let mut buffer = Vec::new();
     {
         let mut sse_message = uhttp_sse::SseMessage::new(&mut buffer);
-        let untyped_hash: Hash256 = new_head_hash.into();
-        write!(sse_message.data()?, "{:?}", untyped_hash)?;
+        match event {
+            ServerSentEvent::HeadChanged(new_head_hash) => {
+                let untyped_hash: Hash256 = new_head_hash.into();
+                write!(sse_message.data()?, "{:?}", untyped_hash)?;
+            }
+            ServerSentEvent::Lagging {
+                median_delay_ms,
+                threshold_ms,
+            } => {
+                write!(sse_message.event()?, "lagging")?;
+                let event = HeadLaggingEvent {
+                    median_delay_ms,
+                    threshold_ms,
+                };
+                serde_json::to_writer(sse_message.data()?, &event)?;
+            }
+            ServerSentEvent::DutiesChanged {
+                epoch,
+                dependent_root,
+                previous_dependent_root,
+            } => {
+                write!(sse_message.event()?, "duties_changed")?;
+                let event = DutiesDependencyEvent {
+                    epoch,
+                    dependent_root,
+                    previous_dependent_root,
+                };
+                serde_json::to_writer(sse_message.data()?, &event)?;
+            }
+        }
     }
     let bytes: Bytes = buffer.into();
     Ok(bytes)
 }
 
-pub fn stream_forks<T: BeaconChainTypes>(ctx: Arc<Context<T>>) -> Result<Body, ApiError> {
+/// HTTP handler to stream server-sent events for the topics selected by the `topic` query
+/// parameter, which may be repeated. Only the `head` topic is streamed if none is selected.
+pub fn stream_forks<T: BeaconChainTypes>(
+    req: Request<()>,
+    ctx: Arc<Context<T>>,
+) -> Result<Body, ApiError> {
+    let mut topics = UrlQuery::from_request(&req)?
+        .all_of("topic")?
+        .iter()
+        .map(|topic| StreamTopic::parse(topic))
+        .collect::<Result<Vec<_>, _>>()?;
+    if topics.is_empty() {
+        topics.push(StreamTopic::Head);
+    }
+
     let mut events = ctx.events.lock().add_rx();
     let (mut sender, body) = Body::channel();
     std::thread::spawn(move || {
-        while let Ok(new_head_hash) = events.recv() {
-            let chunk = match make_sse_response_chunk(new_head_hash) {
+        while let Ok(event) = events.recv() {
+            if !topics.contains(&StreamTopic::of(&event)) {
+                continue;
+            }
+
+            let chunk = match make_sse_response_chunk(event) {
                 Ok(chunk) => chunk,
                 Err(e) => {
                     error!(ctx.log, "Failed to make SSE chunk"; "error" => e.to_string());
@@ -234,7 +359,7 @@ pub fn post_validators<T: BeaconChainTypes>(
     req: Request<Vec<u8>>,
     ctx: Arc<Context<T>>,
 ) -> Result<Vec<ValidatorResponse>, ApiError> {
-    serde_json::from_slice::<ValidatorRequest>(&req.into_body())
+    ctx.parse_json::<ValidatorRequest>(&req.into_body())
         .map_err(|e| {
             ApiError::BadRequest(format!(
                 "Unable to parse JSON into ValidatorRequest: {:?}",
@@ -257,15 +382,7 @@ fn get_state_from_root_opt<T: BeaconChainTypes>(
     state_root_opt: Option<Hash256>,
 ) -> Result<BeaconState<T::EthSpec>, ApiError> {
     if let Some(state_root) = state_root_opt {
-        beacon_chain
-            .get_state(&state_root, None)
-            .map_err(|e| {
-                ApiError::ServerError(format!(
-                    "Database error when reading state root {}: {:?}",
-                    state_root, e
-                ))
-            })?
-            .ok_or_else(|| ApiError::NotFound(format!("No state exists with root: {}", state_root)))
+        StateId::Root(state_root).state(beacon_chain)
     } else {
         Ok(beacon_chain.head()?.beacon_state)
     }
@@ -334,7 +451,30 @@ pub fn get_committees<T: BeaconChainTypes>(
 ) -> Result<Vec<Committee>, ApiError> {
     let query = UrlQuery::from_request(&req)?;
 
-    let epoch = query.epoch()?;
+    let slot = query
+        .first_of_opt(&["slot"])
+        .map(|(_key, value)| parse_slot(&value))
+        .transpose()?;
+    let index = query
+        .first_of_opt(&["index"])
+        .map(|(_key, value)| parse_committee_index(&value))
+        .transpose()?;
+    let epoch = match query.first_of_opt(&["epoch"]) {
+        Some((_key, value)) => parse_epoch(&value)?,
+        None => match slot {
+            Some(slot) => slot.epoch(T::EthSpec::slots_per_epoch()),
+            None => ctx.beacon_chain.epoch()?,
+        },
+    };
+
+    if let Some(slot) = slot {
+        if slot.epoch(T::EthSpec::slots_per_epoch()) != epoch {
+            return Err(ApiError::BadRequest(format!(
+                "Slot {} is not in epoch {}",
+                slot, epoch
+            )));
+        }
+    }
 
     let mut state =
         get_state_for_epoch(&ctx.beacon_chain, epoch, StateSkipConfig::WithoutStateRoots)?;
@@ -351,9 +491,11 @@ pub fn get_committees<T: BeaconChainTypes>(
         .get_beacon_committees_at_epoch(relative_epoch)
         .map_err(|e| ApiError::ServerError(format!("Unable to get all committees: {:?}", e)))?
         .into_iter()
+        .filter(|c| slot.map_or(true, |slot| c.slot == slot))
+        .filter(|c| index.map_or(true, |index| c.index == *index))
         .map(|c| Committee {
             slot: c.slot,
-            index: c.index,
+            index: c.index.into(),
             committee: c.committee.to_vec(),
         })
         .collect::<Vec<_>>())
@@ -384,24 +526,12 @@ pub fn get_state<T: BeaconChainTypes>(
         }
     };
 
-    let (root, state): (Hash256, BeaconState<T::EthSpec>) = match (key.as_ref(), value) {
-        ("slot", value) => state_at_slot(&ctx.beacon_chain, parse_slot(&value)?)?,
-        ("root", value) => {
-            let root = &parse_root(&value)?;
-
-            let state = ctx
-                .beacon_chain
-                .store
-                .get_state(root, None)?
-                .ok_or_else(|| ApiError::NotFound(format!("No state for root: {:?}", root)))?;
-
-            (*root, state)
-        }
-        _ => return Err(ApiError::ServerError("Unexpected query parameter".into())),
-    };
+    let (resolved, state) = StateId::from_query(&key, &value)?.resolve(&ctx.beacon_chain)?;
 
     Ok(StateResponse {
-        root,
+        root: resolved.root,
+        slot: resolved.slot,
+        canonical: resolved.canonical,
         beacon_state: state,
     })
 }
@@ -415,9 +545,8 @@ pub fn get_state_root<T: BeaconChainTypes>(
     ctx: Arc<Context<T>>,
 ) -> Result<Hash256, ApiError> {
     let slot_string = UrlQuery::from_request(&req)?.only_one("slot")?;
-    let slot = parse_slot(&slot_string)?;
 
-    state_root_at_slot(&ctx.beacon_chain, slot, StateSkipConfig::WithStateRoots)
+    StateId::Slot(parse_slot(&slot_string)?).root(&ctx.beacon_chain)
 }
 
 /// HTTP handler to return a `BeaconState` at the genesis block.
@@ -427,7 +556,43 @@ pub fn get_state_root<T: BeaconChainTypes>(
 pub fn get_genesis_state<T: BeaconChainTypes>(
     ctx: Arc<Context<T>>,
 ) -> Result<BeaconState<T::EthSpec>, ApiError> {
-    state_at_slot(&ctx.beacon_chain, Slot::new(0)).map(|(_root, state)| state)
+    StateId::Slot(Slot::new(0)).state(&ctx.beacon_chain)
+}
+
+/// HTTP handler to return a snapshot of the deposit contract tree at the finalized checkpoint.
+///
+/// The snapshot allows a node to initialize its deposit tree without downloading every deposit
+/// log up to the finalized eth1 block.
+pub fn get_deposit_snapshot<T: BeaconChainTypes>(
+    ctx: Arc<Context<T>>,
+) -> Result<DepositTreeSnapshot, ApiError> {
+    let beacon_chain = &ctx.beacon_chain;
+    let eth1_chain = beacon_chain.eth1_chain.as_ref().ok_or_else(|| {
+        ApiError::NotFound("Deposit snapshot unavailable without Eth1 connection.".to_string())
+    })?;
+
+    let checkpoint = beacon_chain.head_info()?.finalized_checkpoint;
+    // The root of the finalized checkpoint is zero until the first epoch is finalized.
+    let finalized_root = if checkpoint.epoch == Epoch::new(0) {
+        beacon_chain.genesis_block_root
+    } else {
+        checkpoint.root
+    };
+    let block = beacon_chain.get_block(&finalized_root)?.ok_or_else(|| {
+        ApiError::ServerError(format!("Missing finalized block {:?}", finalized_root))
+    })?;
+    let state = beacon_chain
+        .get_state(&block.state_root(), Some(block.slot()))?
+        .ok_or_else(|| {
+            ApiError::ServerError(format!("Missing finalized state {:?}", block.state_root()))
+        })?;
+
+    eth1_chain
+        .deposit_snapshot(&state.eth1_data)
+        .map_err(|e| ApiError::ServerError(format!("Unable to build deposit snapshot: {:?}", e)))?
+        .ok_or_else(|| {
+            ApiError::NotFound("Finalized eth1 block is not in the deposit cache.".to_string())
+        })
 }
 
 pub fn proposer_slashing<T: BeaconChainTypes>(
@@ -436,7 +601,7 @@ pub fn proposer_slashing<T: BeaconChainTypes>(
 ) -> Result<bool, ApiError> {
     let body = req.into_body();
 
-    serde_json::from_slice::<ProposerSlashing>(&body)
+    ctx.parse_json::<ProposerSlashing>(&body)
         .map_err(|e| format!("Unable to parse JSON into ProposerSlashing: {:?}", e))
         .and_then(move |proposer_slashing| {
             if ctx.beacon_chain.eth1_chain.is_some() {
@@ -465,7 +630,7 @@ pub fn attester_slashing<T: BeaconChainTypes>(
     ctx: Arc<Context<T>>,
 ) -> Result<bool, ApiError> {
     let body = req.into_body();
-    serde_json::from_slice::<AttesterSlashing<T::EthSpec>>(&body)
+    ctx.parse_json::<AttesterSlashing<T::EthSpec>>(&body)
         .map_err(|e| {
             ApiError::BadRequest(format!(
                 "Unable to parse JSON into AttesterSlashing: {:?}",
@@ -498,3 +663,34 @@ pub fn attester_slashing<T: BeaconChainTypes>(
 
     Ok(true)
 }
+
+/// HTTP handler to import a `SignedVoluntaryExit` into the operation pool and publish it to the
+/// network.
+pub fn voluntary_exit<T: BeaconChainTypes>(
+    req: Request<Vec<u8>>,
+    ctx: Arc<Context<T>>,
+) -> Result<bool, ApiError> {
+    let body = req.into_body();
+
+    let exit = ctx.parse_json::<SignedVoluntaryExit>(&body).map_err(|e| {
+        ApiError::BadRequest(format!(
+            "Unable to parse JSON into SignedVoluntaryExit: {:?}",
+            e
+        ))
+    })?;
+
+    let outcome = ctx
+        .beacon_chain
+        .verify_voluntary_exit_for_gossip(exit.clone())
+        .map_err(|e| ApiError::BadRequest(format!("Error while verifying exit: {:?}", e)))?;
+
+    if let ObservationOutcome::New(verified_exit) = outcome {
+        publish_voluntary_exit_to_network::<T>(ctx.network_chan()?, exit)?;
+        ctx.beacon_chain.import_voluntary_exit(verified_exit);
+        Ok(true)
+    } else {
+        Err(ApiError::BadRequest(
+            "Voluntary exit for that validator index already known".to_string(),
+        ))
+    }
+}
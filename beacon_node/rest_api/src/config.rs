@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::fs::File;
 use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
 
 /// Defines the encoding for the API.
 #[derive(Clone, Serialize, Deserialize, Copy)]
@@ -38,9 +40,12 @@ pub struct Config {
     pub listen_address: Ipv4Addr,
     /// The port the REST API HTTP server will listen on.
     pub port: u16,
-    /// If something else than "", a 'Access-Control-Allow-Origin' header will be present in
-    /// responses.  Put *, to allow any origin.
-    pub allow_origin: String,
+    /// The settings which may be changed whilst the server is running.
+    #[serde(flatten)]
+    pub reloadable: ReloadableConfig,
+    /// A YAML file containing a `ReloadableConfig`, which is read again when the node receives a
+    /// `SIGHUP` or a request to `/lighthouse/http_config/reload`.
+    pub config_file: Option<PathBuf>,
 }
 
 impl Default for Config {
@@ -49,7 +54,71 @@ impl Default for Config {
             enabled: false,
             listen_address: Ipv4Addr::new(127, 0, 0, 1),
             port: 5052,
-            allow_origin: "".to_string(),
+            reloadable: ReloadableConfig::default(),
+            config_file: None,
         }
     }
 }
+
+/// The subset of the HTTP REST API configuration which may be reloaded without restarting the
+/// server.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReloadableConfig {
+    /// If something else than "", a 'Access-Control-Allow-Origin' header will be present in
+    /// responses.  Put *, to allow any origin.
+    pub allow_origin: String,
+    /// Requests for any path starting with one of these prefixes are refused, e.g. `/advanced/`.
+    pub disabled_routes: Vec<String>,
+    /// Log each request at the `INFO` level, rather than `DEBUG`.
+    pub log_requests: bool,
+    /// How requests for a path with a trailing slash or upper-case letters are handled.
+    pub path_normalization: PathNormalization,
+    /// Accept integers in JSON request bodies whether or not they are quoted, for clients which
+    /// do not follow the format of the API. Responses are not affected.
+    pub lenient_json_numbers: bool,
+}
+
+/// How a request is handled when its path differs from the canonical form of the path, which is
+/// lower-case and has no empty segments (e.g., `/Beacon//head/` becomes `/beacon/head`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathNormalization {
+    /// Handle the request as if it were for the canonical path.
+    Rewrite,
+    /// Respond with a `308 Permanent Redirect` to the canonical path.
+    Redirect,
+    /// Only match the canonical path exactly, responding to any other form with a 404.
+    Disabled,
+}
+
+impl Default for PathNormalization {
+    fn default() -> Self {
+        PathNormalization::Rewrite
+    }
+}
+
+impl ReloadableConfig {
+    /// Read a `ReloadableConfig` from the YAML file at `path`.
+    ///
+    /// Fields which are absent from the file take their default values.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let file = File::open(path.as_ref())
+            .map_err(|e| format!("Unable to open {:?}: {:?}", path.as_ref(), e))?;
+        let config: Self = serde_yaml::from_reader(file)
+            .map_err(|e| format!("Unable to parse {:?}: {:?}", path.as_ref(), e))?;
+
+        // Validate the origin now, rather than when the first response is produced.
+        hyper::header::HeaderValue::from_str(&config.allow_origin)
+            .map_err(|_| format!("Invalid allow_origin value: {}", config.allow_origin))?;
+
+        Ok(config)
+    }
+
+    /// Returns `true` if requests for `path` should be refused.
+    pub fn is_disabled(&self, path: &str) -> bool {
+        self.disabled_routes
+            .iter()
+            .any(|prefix| path.starts_with(prefix.as_str()))
+    }
+}
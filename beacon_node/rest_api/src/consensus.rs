@@ -58,7 +58,7 @@ pub fn get_vote_count<T: BeaconChainTypes>(
     // This is the last slot of the given epoch (one prior to the first slot of the next epoch).
     let target_slot = (epoch + 1).start_slot(T::EthSpec::slots_per_epoch()) - 1;
 
-    let (_root, state) = state_at_slot(&ctx.beacon_chain, target_slot)?;
+    let state = StateId::Slot(target_slot).state(&ctx.beacon_chain)?;
     let spec = &ctx.beacon_chain.spec;
 
     let mut validator_statuses = ValidatorStatuses::new(&state, spec)?;
@@ -73,7 +73,7 @@ pub fn post_individual_votes<T: BeaconChainTypes>(
 ) -> Result<Vec<IndividualVotesResponse>, ApiError> {
     let body = req.into_body();
 
-    serde_json::from_slice::<IndividualVotesRequest>(&body)
+    ctx.parse_json::<IndividualVotesRequest>(&body)
         .map_err(|e| {
             ApiError::BadRequest(format!(
                 "Unable to parse JSON into ValidatorDutiesRequest: {:?}",
@@ -86,7 +86,7 @@ pub fn post_individual_votes<T: BeaconChainTypes>(
             // This is the last slot of the given epoch (one prior to the first slot of the next epoch).
             let target_slot = (epoch + 1).start_slot(T::EthSpec::slots_per_epoch()) - 1;
 
-            let (_root, mut state) = state_at_slot(&ctx.beacon_chain, target_slot)?;
+            let mut state = StateId::Slot(target_slot).state(&ctx.beacon_chain)?;
             let spec = &ctx.beacon_chain.spec;
 
             let mut validator_statuses = ValidatorStatuses::new(&state, spec)?;
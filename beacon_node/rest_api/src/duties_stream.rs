@@ -0,0 +1,383 @@
+//! Pushes notifications of changes in validator duties to validator clients, so they need not poll
+//! the beacon node for duties every slot.
+//!
+//! A validator client registers the public keys of its validators, and is issued an API token in
+//! return. It then opens the `duties` stream with that token, supplied as a bearer token in the
+//! `Authorization` header, and may replace its validators by registering again with the token.
+//! Each time the chain reports that duties may have changed (i.e., the head has moved into a new
+//! epoch or a re-org has changed the blocks upon which duties depend), the duties of all registered
+//! validators are recomputed once and an event listing the validators whose duties differ from
+//! those previously sent is written to each stream.
+//!
+//! A token is dropped when its stream closes, or if no stream is opened with it soon after it was
+//! issued. At most `MAX_DUTIES_SUBSCRIPTIONS` tokens exist at once.
+use crate::validator::return_validator_duties;
+use crate::{ApiError, Context};
+use beacon_chain::{events::ServerSentEvent, BeaconChainTypes};
+use bls::PublicKeyBytes;
+use futures::channel::mpsc;
+use futures::StreamExt;
+use hyper::body::Bytes;
+use hyper::header::AUTHORIZATION;
+use hyper::{Body, Request};
+use parking_lot::Mutex;
+use rand::Rng;
+use rest_types::{
+    DutiesChangedEvent, DutiesSubscriptionRequest, DutiesSubscriptionResponse, ValidatorDutyBytes,
+};
+use slog::{debug, error, warn};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use types::{Epoch, Hash256};
+
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// The maximum number of API tokens, whether or not their streams are open.
+pub const MAX_DUTIES_SUBSCRIPTIONS: usize = 64;
+/// A token is dropped if its stream is not opened within this time of it being issued.
+const UNOPENED_SUBSCRIPTION_TIMEOUT: Duration = Duration::from_secs(60);
+/// The number of events which may be waiting to be written to a stream before it is closed.
+const STREAM_BUFFER: usize = 16;
+
+/// The duties most recently sent for each epoch, keyed by validator public key.
+type SentDuties = HashMap<Epoch, HashMap<PublicKeyBytes, ValidatorDutyBytes>>;
+
+struct Subscription {
+    pubkeys: Vec<PublicKeyBytes>,
+    /// The sender of the open stream, if any.
+    sender: Option<mpsc::Sender<Bytes>>,
+    sent_duties: SentDuties,
+    issued: Instant,
+}
+
+/// The validators registered against each API token.
+#[derive(Default)]
+pub struct DutiesSubscriptions {
+    subscriptions: Mutex<HashMap<String, Subscription>>,
+}
+
+impl DutiesSubscriptions {
+    /// Registers `pubkeys` against a new token, which is returned.
+    fn issue(&self, pubkeys: Vec<PublicKeyBytes>) -> Result<String, ApiError> {
+        let mut subscriptions = self.subscriptions.lock();
+        subscriptions.retain(|_, subscription| {
+            subscription.sender.is_some()
+                || subscription.issued.elapsed() < UNOPENED_SUBSCRIPTION_TIMEOUT
+        });
+
+        if subscriptions.len() >= MAX_DUTIES_SUBSCRIPTIONS {
+            return Err(ApiError::ServiceUnavailable(format!(
+                "No more than {} duties streams may be registered",
+                MAX_DUTIES_SUBSCRIPTIONS
+            )));
+        }
+
+        let token = hex::encode(rand::thread_rng().gen::<[u8; 32]>());
+        subscriptions.insert(
+            token.clone(),
+            Subscription {
+                pubkeys,
+                sender: None,
+                sent_duties: HashMap::new(),
+                issued: Instant::now(),
+            },
+        );
+        Ok(token)
+    }
+
+    /// Replaces the validators registered against `token`.
+    fn replace(&self, token: &str, pubkeys: Vec<PublicKeyBytes>) -> Result<(), ApiError> {
+        let mut subscriptions = self.subscriptions.lock();
+        let subscription = subscriptions.get_mut(token).ok_or_else(unknown_token)?;
+        subscription.pubkeys = pubkeys;
+        Ok(())
+    }
+
+    /// Opens the stream of `token`, to which events are sent through `sender`.
+    fn open(&self, token: &str, sender: mpsc::Sender<Bytes>) -> Result<(), ApiError> {
+        let mut subscriptions = self.subscriptions.lock();
+        let subscription = subscriptions.get_mut(token).ok_or_else(unknown_token)?;
+        if subscription.sender.is_some() {
+            return Err(ApiError::BadRequest(
+                "The duties stream of this API token is already open".to_string(),
+            ));
+        }
+        subscription.sender = Some(sender);
+        Ok(())
+    }
+
+    /// Drops `token`, since its stream has closed.
+    fn close(&self, token: &str) {
+        self.subscriptions.lock().remove(token);
+    }
+
+    /// The validators registered against tokens whose streams are open.
+    fn streamed_pubkeys(&self) -> Vec<PublicKeyBytes> {
+        self.subscriptions
+            .lock()
+            .values()
+            .filter(|subscription| subscription.sender.is_some())
+            .flat_map(|subscription| subscription.pubkeys.iter().cloned())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Drops the API token of a stream when the stream is dropped by the server.
+struct StreamGuard<T: BeaconChainTypes> {
+    ctx: Arc<Context<T>>,
+    token: String,
+}
+
+impl<T: BeaconChainTypes> Drop for StreamGuard<T> {
+    fn drop(&mut self) {
+        debug!(self.ctx.log, "Duties stream closed");
+        self.ctx.duties_subscriptions.close(&self.token);
+    }
+}
+
+fn unknown_token() -> ApiError {
+    ApiError::Unauthorized("Unknown API token".to_string())
+}
+
+/// Reads the API token from the `Authorization: Bearer <token>` header of `req`, if any.
+fn api_token<U>(req: &Request<U>) -> Result<Option<String>, ApiError> {
+    let header = match req.headers().get(AUTHORIZATION) {
+        Some(header) => header.to_str().map_err(|e| {
+            ApiError::BadRequest(format!(
+                "The Authorization header contains invalid characters: {:?}",
+                e
+            ))
+        })?,
+        None => return Ok(None),
+    };
+
+    match header.strip_prefix(BEARER_PREFIX) {
+        Some(token) if !token.is_empty() => Ok(Some(token.to_string())),
+        _ => Err(ApiError::Unauthorized(
+            "The Authorization header must contain a bearer token".to_string(),
+        )),
+    }
+}
+
+/// HTTP handler to register the validators whose duties are reported on a `duties` stream.
+///
+/// A new API token is issued, unless the request supplies an existing token, in which case the
+/// validators registered against it are replaced.
+pub fn post_duties_subscription<T: BeaconChainTypes>(
+    req: Request<Vec<u8>>,
+    ctx: Arc<Context<T>>,
+) -> Result<DutiesSubscriptionResponse, ApiError> {
+    let token = api_token(&req)?;
+    let request = ctx
+        .parse_json::<DutiesSubscriptionRequest>(req.body())
+        .map_err(|e| {
+            ApiError::BadRequest(format!(
+                "Unable to parse JSON into DutiesSubscriptionRequest: {:?}",
+                e
+            ))
+        })?;
+
+    debug!(
+        ctx.log,
+        "Registered duties stream";
+        "validators" => request.pubkeys.len(),
+        "new_token" => token.is_none(),
+    );
+
+    let token = match token {
+        Some(token) => {
+            ctx.duties_subscriptions.replace(&token, request.pubkeys)?;
+            token
+        }
+        None => ctx.duties_subscriptions.issue(request.pubkeys)?,
+    };
+
+    Ok(DutiesSubscriptionResponse { token })
+}
+
+/// HTTP handler to open the `duties` stream for the API token supplied with the request.
+///
+/// Validators must first be registered via `post_duties_subscription`.
+pub fn stream_duties<T: BeaconChainTypes>(
+    req: Request<()>,
+    ctx: Arc<Context<T>>,
+) -> Result<Body, ApiError> {
+    let token = api_token(&req)?
+        .ok_or_else(|| ApiError::Unauthorized("Missing Authorization header".to_string()))?;
+
+    let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+    ctx.duties_subscriptions.open(&token, sender)?;
+
+    let guard = StreamGuard { ctx, token };
+    let stream = receiver.map(move |chunk| {
+        let _ = &guard;
+        Ok::<_, std::io::Error>(chunk)
+    });
+    Ok(Body::wrap_stream(stream))
+}
+
+/// Spawns a task which writes an event to each open `duties` stream whenever the duties of its
+/// validators change.
+pub fn spawn_duties_notifier<T: BeaconChainTypes>(ctx: Arc<Context<T>>) {
+    let mut events = ctx.events.lock().add_rx();
+    let executor = ctx.executor.clone();
+
+    // The bus can only be read by blocking.
+    executor.spawn_blocking(
+        move || {
+            while let Ok(event) = events.recv() {
+                if let ServerSentEvent::DutiesChanged {
+                    epoch,
+                    dependent_root,
+                    previous_dependent_root,
+                } = event
+                {
+                    notify_subscribers(&ctx, epoch, dependent_root, previous_dependent_root);
+                }
+            }
+        },
+        "duties_stream",
+    );
+}
+
+/// Computes the duties of all validators registered against an open stream for `epoch` and the
+/// next epoch, and writes an event to each stream whose validators' duties have changed.
+fn notify_subscribers<T: BeaconChainTypes>(
+    ctx: &Context<T>,
+    epoch: Epoch,
+    dependent_root: Hash256,
+    previous_dependent_root: Hash256,
+) {
+    let pubkeys = ctx.duties_subscriptions.streamed_pubkeys();
+    if pubkeys.is_empty() {
+        return;
+    }
+
+    let mut duties = HashMap::new();
+    for &duties_epoch in &[epoch, epoch + 1] {
+        match return_validator_duties(&ctx.beacon_chain, duties_epoch, pubkeys.clone()) {
+            Ok(epoch_duties) => {
+                duties.insert(duties_epoch, epoch_duties);
+            }
+            Err(e) => {
+                error!(
+                    ctx.log,
+                    "Failed to compute duties for stream";
+                    "error" => format!("{:?}", e),
+                    "epoch" => duties_epoch.as_u64(),
+                );
+                return;
+            }
+        }
+    }
+
+    let mut subscriptions = ctx.duties_subscriptions.subscriptions.lock();
+    subscriptions.retain(|_, subscription| {
+        let sender = match &mut subscription.sender {
+            Some(sender) => sender,
+            None => return true,
+        };
+
+        let validator_indices = changed_validators(
+            &mut subscription.sent_duties,
+            &duties,
+            epoch,
+            &subscription.pubkeys,
+        );
+        if validator_indices.is_empty() {
+            return true;
+        }
+
+        let chunk = match make_duties_chunk(
+            epoch,
+            dependent_root,
+            previous_dependent_root,
+            validator_indices,
+        ) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                error!(ctx.log, "Failed to make SSE chunk"; "error" => e.to_string());
+                return true;
+            }
+        };
+
+        match sender.try_send(chunk) {
+            Ok(()) => true,
+            Err(e) if e.is_disconnected() => false,
+            Err(_) => {
+                // The client is not reading its stream, so it is closed rather than allowed to
+                // miss events.
+                warn!(ctx.log, "Closing duties stream which is not being read");
+                false
+            }
+        }
+    });
+}
+
+/// Returns the indices of the validators in `pubkeys` whose duties in `duties` differ from those
+/// in `sent_duties`. `sent_duties` is updated with the new duties and pruned of epochs prior to
+/// `epoch`.
+fn changed_validators(
+    sent_duties: &mut SentDuties,
+    duties: &HashMap<Epoch, Vec<ValidatorDutyBytes>>,
+    epoch: Epoch,
+    pubkeys: &[PublicKeyBytes],
+) -> Vec<u64> {
+    let pubkeys = pubkeys.iter().collect::<HashSet<_>>();
+    let mut validator_indices = vec![];
+
+    for (duties_epoch, epoch_duties) in duties {
+        let previous = sent_duties.entry(*duties_epoch).or_default();
+
+        for duty in epoch_duties {
+            if !pubkeys.contains(&duty.validator_pubkey) {
+                continue;
+            }
+
+            // Validators unknown to the chain have no duties to report.
+            let validator_index = match duty.validator_index {
+                Some(index) => index,
+                None => continue,
+            };
+
+            if previous.get(&duty.validator_pubkey) != Some(duty) {
+                validator_indices.push(validator_index);
+                previous.insert(duty.validator_pubkey.clone(), duty.clone());
+            }
+        }
+    }
+
+    sent_duties.retain(|duties_epoch, _| *duties_epoch >= epoch);
+
+    validator_indices.sort_unstable();
+    validator_indices.dedup();
+
+    validator_indices
+}
+
+fn make_duties_chunk(
+    epoch: Epoch,
+    dependent_root: Hash256,
+    previous_dependent_root: Hash256,
+    validator_indices: Vec<u64>,
+) -> std::io::Result<Bytes> {
+    let event = DutiesChangedEvent {
+        epoch,
+        dependent_root,
+        previous_dependent_root,
+        validator_indices,
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut sse_message = uhttp_sse::SseMessage::new(&mut buffer);
+        write!(sse_message.event()?, "duties")?;
+        serde_json::to_writer(sse_message.data()?, &event)?;
+    }
+    let bytes: Bytes = buffer.into();
+    Ok(bytes)
+}
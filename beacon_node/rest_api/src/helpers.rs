@@ -4,12 +4,58 @@ use bls::PublicKeyBytes;
 use eth2_libp2p::PubsubMessage;
 use itertools::process_results;
 use network::NetworkMessage;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 use ssz::Decode;
 use store::iter::AncestorIter;
 use types::{
-    BeaconState, CommitteeIndex, Epoch, EthSpec, Hash256, RelativeEpoch, SignedBeaconBlock, Slot,
+    BeaconState, CommitteeIndex, Epoch, EthSpec, Hash256, RelativeEpoch, SignedBeaconBlock,
+    SignedVoluntaryExit, Slot,
 };
 
+/// Parse a JSON request body.
+///
+/// If `lenient_numbers` is `true` and the body cannot be parsed as it is, each string of decimal
+/// digits is replaced by a plain number and the body is parsed again, so that integers may be sent
+/// with or without quotes. Fields which expect quoted integers already accept plain numbers.
+///
+/// The error from parsing the body as it was sent is returned if both attempts fail.
+pub fn parse_json_body<T: DeserializeOwned>(
+    body: &[u8],
+    lenient_numbers: bool,
+) -> Result<T, serde_json::Error> {
+    let error = match serde_json::from_slice(body) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+
+    if !lenient_numbers {
+        return Err(error);
+    }
+
+    serde_json::from_slice::<Value>(body)
+        .ok()
+        .and_then(|mut value| {
+            unquote_integers(&mut value);
+            serde_json::from_value(value).ok()
+        })
+        .ok_or(error)
+}
+
+/// Replaces each string of `value` which holds a `u64` in decimal with a plain number.
+fn unquote_integers(value: &mut Value) {
+    match value {
+        Value::String(s) if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) => {
+            if let Ok(n) = s.parse::<u64>() {
+                *value = Value::from(n);
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(unquote_integers),
+        Value::Object(map) => map.values_mut().for_each(unquote_integers),
+        _ => (),
+    }
+}
+
 /// Parse a slot.
 ///
 /// E.g., `"1234"`
@@ -93,6 +139,349 @@ pub fn parse_pubkey_bytes(string: &str) -> Result<PublicKeyBytes, ApiError> {
     }
 }
 
+/// Returns the canonical form of a request `path`, which is lower-case and has no empty segments.
+///
+/// E.g., `"/Beacon//head/"` becomes `"/beacon/head"`, whilst the root path remains `"/"`.
+pub fn canonical_path(path: &str) -> String {
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+
+    format!("/{}", segments.join("/")).to_ascii_lowercase()
+}
+
+/// The result of resolving a `BlockId` or `StateId` against the beacon chain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResolvedId {
+    /// The root of the block or state.
+    pub root: Hash256,
+    /// The slot of the block or state.
+    pub slot: Slot,
+    /// `true` if the block or state is in the canonical chain.
+    pub canonical: bool,
+}
+
+/// Returns the slot of the most recently finalized checkpoint of `beacon_chain`.
+fn finalized_slot<T: BeaconChainTypes>(beacon_chain: &BeaconChain<T>) -> Result<Slot, ApiError> {
+    Ok(beacon_chain
+        .head_info()?
+        .finalized_checkpoint
+        .epoch
+        .start_slot(T::EthSpec::slots_per_epoch()))
+}
+
+/// Identifies a `SignedBeaconBlock` by either its root, a slot in the canonical chain or one of the
+/// names `head`, `finalized` and `genesis`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockId {
+    Head,
+    Finalized,
+    Genesis,
+    Slot(Slot),
+    Root(Hash256),
+}
+
+impl BlockId {
+    /// Parse a `BlockId` from a query `key` of either `"slot"` or `"root"`.
+    ///
+    /// The `"slot"` key also accepts the names `head`, `finalized` and `genesis`, in any case.
+    pub fn from_query(key: &str, value: &str) -> Result<Self, ApiError> {
+        match key {
+            "slot" => match Self::from_name(value) {
+                Some(id) => Ok(id),
+                None => parse_slot(value).map(BlockId::Slot),
+            },
+            "root" => parse_root(value).map(BlockId::Root),
+            _ => Err(ApiError::ServerError("Unexpected query parameter".into())),
+        }
+    }
+
+    /// Parse a `BlockId` from a path segment, which is either a `0x` prefixed root, a slot or one
+    /// of the names `head`, `finalized` and `genesis`, in any case.
+    pub fn from_path_segment(segment: &str) -> Result<Self, ApiError> {
+        if let Some(id) = Self::from_name(segment) {
+            Ok(id)
+        } else if segment.starts_with("0x") {
+            parse_root(segment).map(BlockId::Root)
+        } else {
+            parse_slot(segment).map(BlockId::Slot)
+        }
+    }
+
+    /// Parse one of the names `head`, `finalized` and `genesis`, ignoring case.
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "head" => Some(BlockId::Head),
+            "finalized" => Some(BlockId::Finalized),
+            "genesis" => Some(BlockId::Genesis),
+            _ => None,
+        }
+    }
+
+    /// Replace a named id with the `Slot` or `Root` id which it currently refers to.
+    fn unalias<T: BeaconChainTypes>(self, beacon_chain: &BeaconChain<T>) -> Result<Self, ApiError> {
+        Ok(match self {
+            BlockId::Head => BlockId::Root(beacon_chain.head_info()?.block_root),
+            // The finalized checkpoint refers to the most recent block at or prior to its slot.
+            BlockId::Finalized => BlockId::Slot(finalized_slot(beacon_chain)?),
+            BlockId::Genesis => BlockId::Root(beacon_chain.genesis_block_root),
+            id => id,
+        })
+    }
+
+    /// Returns the root of the identified block, without loading the block from the database.
+    ///
+    /// In the case of a skip slot, returns the root of the most recent prior block.
+    pub fn root<T: BeaconChainTypes>(
+        &self,
+        beacon_chain: &BeaconChain<T>,
+    ) -> Result<Hash256, ApiError> {
+        match self {
+            BlockId::Slot(slot) => block_root_at_slot(beacon_chain, *slot)?.ok_or_else(|| {
+                ApiError::NotFound(format!(
+                    "Unable to find SignedBeaconBlock for slot {:?}",
+                    slot
+                ))
+            }),
+            BlockId::Root(root) => Ok(*root),
+            named => named.unalias(beacon_chain)?.root(beacon_chain),
+        }
+    }
+
+    /// Returns the root and slot of the identified block, and whether it is canonical.
+    ///
+    /// The block is only loaded from the database if it is missing from the store's root index.
+    pub fn locate<T: BeaconChainTypes>(
+        &self,
+        beacon_chain: &BeaconChain<T>,
+    ) -> Result<ResolvedId, ApiError> {
+        let id = self.unalias(beacon_chain)?;
+        let root = id.root(beacon_chain)?;
+        let index_entry = beacon_chain.store.get_root_index_entry(&root)?;
+        let slot = match index_entry {
+            Some(index_entry) => index_entry.slot,
+            None => get_block(beacon_chain, root)?.message.slot,
+        };
+
+        let canonical = match id {
+            // Blocks found by slot were found by iterating the canonical chain.
+            BlockId::Slot(_) => true,
+            // Named ids have been replaced by slots or roots.
+            _ => match index_entry {
+                // Finalized blocks are known to be canonical.
+                Some(index_entry) if index_entry.canonical => true,
+                _ => block_root_at_slot(beacon_chain, slot)? == Some(root),
+            },
+        };
+
+        Ok(ResolvedId {
+            root,
+            slot,
+            canonical,
+        })
+    }
+
+    /// Load the identified block, without determining whether it is canonical.
+    ///
+    /// In the case of a skip slot, returns the most recent prior block.
+    pub fn block<T: BeaconChainTypes>(
+        &self,
+        beacon_chain: &BeaconChain<T>,
+    ) -> Result<SignedBeaconBlock<T::EthSpec>, ApiError> {
+        get_block(beacon_chain, self.root(beacon_chain)?)
+    }
+
+    /// Load the identified block, along with its root, slot and whether it is canonical.
+    pub fn resolve<T: BeaconChainTypes>(
+        &self,
+        beacon_chain: &BeaconChain<T>,
+    ) -> Result<(ResolvedId, SignedBeaconBlock<T::EthSpec>), ApiError> {
+        let resolved = self.locate(beacon_chain)?;
+        let block = get_block(beacon_chain, resolved.root)?;
+
+        Ok((resolved, block))
+    }
+}
+
+/// Load the `SignedBeaconBlock` with the given `root`, which must exist.
+fn get_block<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    root: Hash256,
+) -> Result<SignedBeaconBlock<T::EthSpec>, ApiError> {
+    beacon_chain.store.get_block(&root)?.ok_or_else(|| {
+        ApiError::NotFound(format!(
+            "Unable to find SignedBeaconBlock for root {:?}",
+            root
+        ))
+    })
+}
+
+/// Identifies a `BeaconState` by either its root, a slot in the canonical chain or one of the
+/// names `head`, `finalized` and `genesis`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StateId {
+    Head,
+    Finalized,
+    Genesis,
+    Slot(Slot),
+    Root(Hash256),
+}
+
+impl StateId {
+    /// Parse a `StateId` from a query `key` of either `"slot"` or `"root"`.
+    ///
+    /// The `"slot"` key also accepts the names `head`, `finalized` and `genesis`, in any case.
+    pub fn from_query(key: &str, value: &str) -> Result<Self, ApiError> {
+        match key {
+            "slot" => match Self::from_name(value) {
+                Some(id) => Ok(id),
+                None => parse_slot(value).map(StateId::Slot),
+            },
+            "root" => parse_root(value).map(StateId::Root),
+            _ => Err(ApiError::ServerError("Unexpected query parameter".into())),
+        }
+    }
+
+    /// Parse one of the names `head`, `finalized` and `genesis`, ignoring case.
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "head" => Some(StateId::Head),
+            "finalized" => Some(StateId::Finalized),
+            "genesis" => Some(StateId::Genesis),
+            _ => None,
+        }
+    }
+
+    /// Replace a named id with the `Slot` or `Root` id which it currently refers to.
+    fn unalias<T: BeaconChainTypes>(self, beacon_chain: &BeaconChain<T>) -> Result<Self, ApiError> {
+        Ok(match self {
+            StateId::Head => StateId::Root(beacon_chain.head_info()?.state_root),
+            StateId::Finalized => StateId::Slot(finalized_slot(beacon_chain)?),
+            StateId::Genesis => StateId::Slot(beacon_chain.spec.genesis_slot),
+            id => id,
+        })
+    }
+
+    /// Returns the root of the identified state, without loading the state from the database.
+    ///
+    /// Will not return a root if the request slot is in the future. Will return roots of states
+    /// higher than the current head by skipping slots.
+    pub fn root<T: BeaconChainTypes>(
+        &self,
+        beacon_chain: &BeaconChain<T>,
+    ) -> Result<Hash256, ApiError> {
+        match self {
+            StateId::Slot(slot) => {
+                state_root_at_slot(beacon_chain, *slot, StateSkipConfig::WithStateRoots)
+            }
+            StateId::Root(root) => Ok(*root),
+            named => named.unalias(beacon_chain)?.root(beacon_chain),
+        }
+    }
+
+    /// Load the identified state, without determining whether it is canonical.
+    ///
+    /// Will not return a state if the request slot is in the future. Will return states higher
+    /// than the current head by skipping slots.
+    pub fn state<T: BeaconChainTypes>(
+        &self,
+        beacon_chain: &BeaconChain<T>,
+    ) -> Result<BeaconState<T::EthSpec>, ApiError> {
+        match self {
+            StateId::Slot(slot) => state_at_slot(beacon_chain, *slot).map(|(_root, state)| state),
+            StateId::Root(root) => get_state(beacon_chain, *root),
+            named => named.unalias(beacon_chain)?.state(beacon_chain),
+        }
+    }
+
+    /// Returns the root and slot of the identified state, and whether it is canonical.
+    ///
+    /// The state is only loaded from the database if it is missing from the store's root index.
+    pub fn locate<T: BeaconChainTypes>(
+        &self,
+        beacon_chain: &BeaconChain<T>,
+    ) -> Result<ResolvedId, ApiError> {
+        match self {
+            StateId::Slot(slot) => Ok(ResolvedId {
+                root: self.root(beacon_chain)?,
+                slot: *slot,
+                canonical: true,
+            }),
+            StateId::Root(root) => {
+                let index_entry = beacon_chain.store.get_root_index_entry(root)?;
+                let slot = match index_entry {
+                    Some(index_entry) => index_entry.slot,
+                    None => get_state(beacon_chain, *root)?.slot,
+                };
+
+                let canonical = match index_entry {
+                    // Finalized states are known to be canonical.
+                    Some(index_entry) if index_entry.canonical => true,
+                    // Only states up to the head are stored, avoid skipping slots to find a root
+                    // which cannot match.
+                    _ => {
+                        slot <= beacon_chain.head_info()?.slot
+                            && state_root_at_slot(
+                                beacon_chain,
+                                slot,
+                                StateSkipConfig::WithStateRoots,
+                            )? == *root
+                    }
+                };
+
+                Ok(ResolvedId {
+                    root: *root,
+                    slot,
+                    canonical,
+                })
+            }
+            named => named.unalias(beacon_chain)?.locate(beacon_chain),
+        }
+    }
+
+    /// Load the identified state, along with its root, slot and whether it is canonical.
+    ///
+    /// Will not return a state if the request slot is in the future. Will return states higher
+    /// than the current head by skipping slots.
+    pub fn resolve<T: BeaconChainTypes>(
+        &self,
+        beacon_chain: &BeaconChain<T>,
+    ) -> Result<(ResolvedId, BeaconState<T::EthSpec>), ApiError> {
+        match self {
+            StateId::Slot(slot) => {
+                let (root, state) = state_at_slot(beacon_chain, *slot)?;
+                Ok((
+                    ResolvedId {
+                        root,
+                        slot: *slot,
+                        canonical: true,
+                    },
+                    state,
+                ))
+            }
+            StateId::Root(_) => {
+                let resolved = self.locate(beacon_chain)?;
+                let state = self.state(beacon_chain)?;
+
+                Ok((resolved, state))
+            }
+            named => named.unalias(beacon_chain)?.resolve(beacon_chain),
+        }
+    }
+}
+
+/// Load the `BeaconState` with the given `root`, which must exist.
+fn get_state<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    root: Hash256,
+) -> Result<BeaconState<T::EthSpec>, ApiError> {
+    beacon_chain
+        .store
+        .get_state(&root, None)?
+        .ok_or_else(|| ApiError::NotFound(format!("No state for root: {:?}", root)))
+}
+
 /// Returns the root of the `SignedBeaconBlock` in the canonical chain of `beacon_chain` at the given
 /// `slot`, if possible.
 ///
@@ -229,6 +618,23 @@ pub fn publish_beacon_block_to_network<T: BeaconChainTypes + 'static>(
     Ok(())
 }
 
+pub fn publish_voluntary_exit_to_network<T: BeaconChainTypes + 'static>(
+    chan: &NetworkChannel<T::EthSpec>,
+    exit: SignedVoluntaryExit,
+) -> Result<(), ApiError> {
+    let messages = vec![PubsubMessage::VoluntaryExit(Box::new(exit))];
+
+    // Publish the exit to the p2p network via gossipsub.
+    if let Err(e) = chan.send(NetworkMessage::Publish { messages }) {
+        return Err(ApiError::ServerError(format!(
+            "Unable to send voluntary exit to network: {:?}",
+            e
+        )));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -250,6 +656,56 @@ mod test {
         assert!(parse_root("0x00").is_err());
     }
 
+    #[test]
+    fn block_id_from_path_segment() {
+        assert_eq!(
+            BlockId::from_path_segment("42"),
+            Ok(BlockId::Slot(Slot::new(42)))
+        );
+        assert_eq!(
+            BlockId::from_path_segment(
+                "0x000000000000000000000000000000000000000000000000000000000000002a"
+            ),
+            Ok(BlockId::Root(Hash256::from_low_u64_be(42)))
+        );
+        assert!(BlockId::from_path_segment("0x2a").is_err());
+        assert!(BlockId::from_path_segment("cats").is_err());
+    }
+
+    #[test]
+    fn named_ids_ignore_case() {
+        assert_eq!(BlockId::from_query("slot", "head"), Ok(BlockId::Head));
+        assert_eq!(BlockId::from_query("slot", "HEAD"), Ok(BlockId::Head));
+        assert_eq!(
+            BlockId::from_query("slot", "Finalized"),
+            Ok(BlockId::Finalized)
+        );
+        assert_eq!(BlockId::from_query("slot", "Genesis"), Ok(BlockId::Genesis));
+        assert_eq!(StateId::from_query("slot", "HEAD"), Ok(StateId::Head));
+        assert_eq!(
+            StateId::from_query("slot", "finalized"),
+            Ok(StateId::Finalized)
+        );
+        assert_eq!(StateId::from_query("slot", "gEnEsIs"), Ok(StateId::Genesis));
+        // Names are not roots.
+        assert!(BlockId::from_query("root", "head").is_err());
+        assert!(StateId::from_query("root", "head").is_err());
+    }
+
+    #[test]
+    fn canonical_path_works() {
+        assert_eq!(canonical_path("/beacon/head"), "/beacon/head");
+        assert_eq!(canonical_path("/beacon/head/"), "/beacon/head");
+        assert_eq!(canonical_path("/BEACON/Head"), "/beacon/head");
+        assert_eq!(canonical_path("//beacon///head//"), "/beacon/head");
+        assert_eq!(canonical_path("/"), "/");
+        assert_eq!(canonical_path(""), "/");
+        assert_eq!(
+            canonical_path("/lighthouse/blocks/0xABCD/proofs/Deposits/0/"),
+            "/lighthouse/blocks/0xabcd/proofs/deposits/0"
+        );
+    }
+
     #[test]
     fn parse_slot_works() {
         assert_eq!(parse_slot("0"), Ok(Slot::new(0)));
@@ -257,4 +713,26 @@ mod test {
         assert_eq!(parse_slot("10000000"), Ok(Slot::new(10_000_000)));
         assert!(parse_slot("cats").is_err());
     }
+
+    #[test]
+    fn parse_json_body_works() {
+        let plain = br#"{"slot": 42, "epoch": 1}"#;
+        let quoted = br#"{"slot": "42", "epoch": "1"}"#;
+
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Body {
+            slot: Slot,
+            epoch: Epoch,
+        }
+        let expected = Body {
+            slot: Slot::new(42),
+            epoch: Epoch::new(1),
+        };
+
+        assert_eq!(parse_json_body::<Body>(plain, false).unwrap(), expected);
+        assert!(parse_json_body::<Body>(quoted, false).is_err());
+        assert_eq!(parse_json_body::<Body>(plain, true).unwrap(), expected);
+        assert_eq!(parse_json_body::<Body>(quoted, true).unwrap(), expected);
+        assert!(parse_json_body::<Body>(br#"{"slot": "0x2a", "epoch": "1"}"#, true).is_err());
+    }
 }
@@ -6,6 +6,7 @@ extern crate network as client_network;
 mod beacon;
 pub mod config;
 mod consensus;
+mod duties_stream;
 mod helpers;
 mod lighthouse;
 mod metrics;
@@ -13,10 +14,12 @@ mod node;
 mod url_query;
 mod validator;
 
-use beacon_chain::{BeaconChain, BeaconChainTypes};
+use arc_swap::ArcSwap;
+use beacon_chain::{events::ServerSentEvent, BeaconChain, BeaconChainTypes};
 use bus::Bus;
 use client_network::NetworkMessage;
-pub use config::ApiEncodingFormat;
+pub use config::{ApiEncodingFormat, PathNormalization, ReloadableConfig};
+use duties_stream::DutiesSubscriptions;
 use eth2_config::Eth2Config;
 use eth2_libp2p::NetworkGlobals;
 use futures::future::TryFutureExt;
@@ -28,14 +31,14 @@ use rest_types::ApiError;
 use slog::{info, warn};
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
-use tokio::sync::mpsc;
-use types::SignedBeaconBlockHash;
+use tokio::sync::{mpsc, Semaphore};
 use url_query::UrlQuery;
 
 pub use crate::helpers::parse_pubkey_bytes;
 pub use config::Config;
-pub use router::Context;
+pub use router::{Context, MAINTENANCE_CONCURRENT_REQUESTS};
 
 pub type NetworkChannel<T> = mpsc::UnboundedSender<NetworkMessage<T>>;
 
@@ -50,11 +53,11 @@ pub fn start_server<T: BeaconChainTypes>(
     executor: environment::TaskExecutor,
     config: &Config,
     beacon_chain: Arc<BeaconChain<T>>,
-    network_info: NetworkInfo<T>,
+    network_info: Option<NetworkInfo<T>>,
     db_path: PathBuf,
     freezer_db_path: PathBuf,
     eth2_config: Eth2Config,
-    events: Arc<Mutex<Bus<SignedBeaconBlockHash>>>,
+    events: Arc<Mutex<Bus<ServerSentEvent>>>,
 ) -> Result<SocketAddr, hyper::Error> {
     let log = executor.log();
     let eth2_config = Arc::new(eth2_config);
@@ -63,15 +66,30 @@ pub fn start_server<T: BeaconChainTypes>(
         executor: executor.clone(),
         config: config.clone(),
         beacon_chain,
-        network_globals: network_info.network_globals.clone(),
-        network_chan: network_info.network_chan,
+        network_info,
         eth2_config,
         log: log.clone(),
         db_path,
         freezer_db_path,
         events,
+        duties_subscriptions: DutiesSubscriptions::default(),
+        maintenance_permits: Semaphore::new(MAINTENANCE_CONCURRENT_REQUESTS),
+        maintenance_queue_depth: AtomicUsize::new(0),
+        reloadable_config: ArcSwap::from_pointee(config.reloadable.clone()),
     });
 
+    duties_stream::spawn_duties_notifier(context.clone());
+
+    #[cfg(unix)]
+    {
+        if config.config_file.is_some() {
+            executor.spawn(
+                reload_config_on_hangup(context.clone()),
+                "http_config_reload",
+            );
+        }
+    }
+
     // Define the function that will build the request handler.
     let make_service = make_service_fn(move |_socket: &AddrStream| {
         let ctx = context.clone();
@@ -125,3 +143,39 @@ pub fn start_server<T: BeaconChainTypes>(
 
     Ok(actual_listen_addr)
 }
+
+/// Reload the HTTP API config file each time the process receives a `SIGHUP`.
+#[cfg(unix)]
+async fn reload_config_on_hangup<T: BeaconChainTypes>(ctx: Arc<Context<T>>) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(e) => {
+            warn!(
+                ctx.log,
+                "Unable to listen for SIGHUP";
+                "error" => format!("{:?}", e),
+            );
+            return;
+        }
+    };
+
+    while hangups.recv().await.is_some() {
+        match ctx.reload_config() {
+            Ok(config) => info!(
+                ctx.log,
+                "Reloaded HTTP API config";
+                "allow_origin" => config.allow_origin,
+                "disabled_routes" => format!("{:?}", config.disabled_routes),
+                "log_requests" => config.log_requests,
+                "lenient_json_numbers" => config.lenient_json_numbers,
+            ),
+            Err(e) => warn!(
+                ctx.log,
+                "Unable to reload HTTP API config";
+                "error" => e,
+            ),
+        }
+    }
+}
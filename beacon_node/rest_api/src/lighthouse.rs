@@ -1,16 +1,47 @@
 //! This contains a collection of lighthouse specific HTTP endpoints.
 
-use crate::{ApiError, Context};
-use beacon_chain::BeaconChainTypes;
+use crate::helpers::{parse_pubkey_bytes, parse_root, parse_slot, BlockId};
+use crate::validator::return_validator_duties;
+use crate::{ApiError, Context, UrlQuery};
+use beacon_chain::{observed_operations::ObservationOutcome, BeaconChainTypes};
+use bls::PublicKeyBytes;
 use eth2_libp2p::PeerInfo;
+use hyper::Request;
+use itertools::process_results;
+use operation_pool::{BlockPackingConfig, PersistedOperationPool};
+use rest_types::{ChainSegment, ValidatorDutyBytes};
 use serde::Serialize;
+use slog::info;
+use state_processing::common::{get_attesting_indices, get_base_reward};
+use state_processing::per_block_processing::{
+    verify_attestation_for_block_inclusion, VerifySignatures,
+};
+use state_processing::{per_block_processing, per_slot_processing, BlockSignatureStrategy};
+use std::collections::HashSet;
 use std::sync::Arc;
-use types::EthSpec;
+use types::{
+    Attestation, AttesterSlashing, BeaconBlockBodyProof, CommitteeIndex, Epoch, EthSpec, Hash256,
+    ProposerSlashing, RelativeEpoch, SignedVoluntaryExit, Slot,
+};
+
+/// The path of `upcoming_duties` is `VALIDATORS_PATH_PREFIX` + validator id +
+/// `UPCOMING_DUTIES_PATH_SUFFIX`.
+pub const VALIDATORS_PATH_PREFIX: &str = "/lighthouse/validators/";
+pub const UPCOMING_DUTIES_PATH_SUFFIX: &str = "/upcoming_duties";
+/// The path of `exit_status` is `VALIDATORS_PATH_PREFIX` + validator id + `EXIT_STATUS_PATH_SUFFIX`.
+pub const EXIT_STATUS_PATH_SUFFIX: &str = "/exit_status";
+/// The path of `block_proof` is `BLOCKS_PATH_PREFIX` + block root + `/proofs/` + operation kind +
+/// `/` + operation index.
+pub const BLOCKS_PATH_PREFIX: &str = "/lighthouse/blocks/";
+/// The path of `block_rewards` is `BLOCKS_PATH_PREFIX` + block id + `BLOCK_REWARDS_PATH_SUFFIX`.
+pub const BLOCK_REWARDS_PATH_SUFFIX: &str = "/rewards";
+/// The maximum number of slots which may be requested from `chain_segments` at once.
+pub const MAX_CHAIN_SEGMENT_SLOTS: u64 = 1_024;
 
 /// Returns all known peers and corresponding information
 pub fn peers<T: BeaconChainTypes>(ctx: Arc<Context<T>>) -> Result<Vec<Peer<T::EthSpec>>, ApiError> {
     Ok(ctx
-        .network_globals
+        .network_globals()?
         .peers
         .read()
         .peers()
@@ -26,7 +57,7 @@ pub fn connected_peers<T: BeaconChainTypes>(
     ctx: Arc<Context<T>>,
 ) -> Result<Vec<Peer<T::EthSpec>>, ApiError> {
     Ok(ctx
-        .network_globals
+        .network_globals()?
         .peers
         .read()
         .connected_peers()
@@ -46,3 +77,703 @@ pub struct Peer<T: EthSpec> {
     /// The PeerInfo associated with the peer.
     peer_info: PeerInfo<T>,
 }
+
+/// Returns the next duties of a single validator, identified in the request path by either its
+/// index or its `0x`-prefixed public key.
+///
+/// The attestation duty is taken from the current epoch if it has not yet passed, otherwise from
+/// the next epoch. Proposer duties are only known for the current epoch.
+pub fn upcoming_duties<T: BeaconChainTypes>(
+    req: Request<Vec<u8>>,
+    ctx: Arc<Context<T>>,
+) -> Result<UpcomingDuties, ApiError> {
+    let validator_id = validator_id_from_path(req.uri().path(), UPCOMING_DUTIES_PATH_SUFFIX)?;
+
+    let beacon_chain = &ctx.beacon_chain;
+    let validator_pubkey = if validator_id.starts_with("0x") {
+        parse_pubkey_bytes(validator_id)?
+    } else {
+        let validator_index = validator_id.parse::<usize>().map_err(|e| {
+            ApiError::BadRequest(format!("Unable to parse validator index: {:?}", e))
+        })?;
+        beacon_chain
+            .head()?
+            .beacon_state
+            .validators
+            .get(validator_index)
+            .map(|validator| validator.pubkey.clone())
+            .ok_or_else(|| {
+                ApiError::NotFound(format!("Unknown validator index: {}", validator_index))
+            })?
+    };
+
+    let current_slot = beacon_chain.slot()?;
+    let current_epoch = current_slot.epoch(T::EthSpec::slots_per_epoch());
+
+    let duty_for_epoch = |epoch: Epoch| -> Result<ValidatorDutyBytes, ApiError> {
+        return_validator_duties(beacon_chain, epoch, vec![validator_pubkey.clone()])?
+            .pop()
+            .ok_or_else(|| ApiError::ServerError("No duties returned for validator".to_string()))
+    };
+
+    let current_duty = duty_for_epoch(current_epoch)?;
+    let validator_index = current_duty
+        .validator_index
+        .ok_or_else(|| ApiError::NotFound(format!("Unknown validator: {}", validator_id)))?;
+
+    let attestation_duty = if current_duty
+        .attestation_slot
+        .map_or(false, |slot| slot >= current_slot)
+    {
+        current_duty.clone()
+    } else {
+        duty_for_epoch(current_epoch + 1)?
+    };
+
+    let block_proposal_slots = current_duty
+        .block_proposal_slots
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|slot| *slot >= current_slot)
+        .collect();
+
+    Ok(UpcomingDuties {
+        validator_index,
+        validator_pubkey,
+        attestation_slot: attestation_duty.attestation_slot,
+        attestation_committee_index: attestation_duty.attestation_committee_index,
+        aggregator_modulo: attestation_duty.aggregator_modulo,
+        block_proposal_slots,
+    })
+}
+
+/// Information returned by `upcoming_duties`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct UpcomingDuties {
+    /// The validator's index in `state.validators`.
+    validator_index: u64,
+    /// The validator's BLS public key.
+    validator_pubkey: PublicKeyBytes,
+    /// The next slot, at or after the current slot, at which the validator must attest.
+    ///
+    /// `None` if the validator is not active in the current or next epoch.
+    attestation_slot: Option<Slot>,
+    /// The index of the committee within `attestation_slot` of which the validator is a member.
+    attestation_committee_index: Option<CommitteeIndex>,
+    /// The validator is selected to aggregate at `attestation_slot` with a probability of
+    /// `1 / aggregator_modulo`. A value of `1` means the validator is certain to aggregate.
+    aggregator_modulo: Option<u64>,
+    /// The slots in the current epoch, at or after the current slot, at which the validator must
+    /// propose a block.
+    block_proposal_slots: Vec<Slot>,
+}
+
+/// Returns the validator id between `VALIDATORS_PATH_PREFIX` and `suffix` in `path`.
+fn validator_id_from_path<'a>(path: &'a str, suffix: &str) -> Result<&'a str, ApiError> {
+    path.strip_prefix(VALIDATORS_PATH_PREFIX)
+        .and_then(|path| path.strip_suffix(suffix))
+        .filter(|id| !id.is_empty() && !id.contains('/'))
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid path: {}", path)))
+}
+
+/// Returns the progress of a single validator's exit, identified in the request path by either
+/// its index or its `0x`-prefixed public key.
+///
+/// The exit fields are read from the head state, and the stage is relative to the current epoch.
+/// An exit which is in the operation pool but not yet in a block is also reported.
+pub fn exit_status<T: BeaconChainTypes>(
+    req: Request<Vec<u8>>,
+    ctx: Arc<Context<T>>,
+) -> Result<ExitStatus, ApiError> {
+    let validator_id = validator_id_from_path(req.uri().path(), EXIT_STATUS_PATH_SUFFIX)?;
+
+    let beacon_chain = &ctx.beacon_chain;
+    let validator_index = if validator_id.starts_with("0x") {
+        let pubkey = parse_pubkey_bytes(validator_id)?;
+        beacon_chain
+            .validator_index(&pubkey)?
+            .ok_or_else(|| ApiError::NotFound(format!("Unknown validator: {}", validator_id)))?
+    } else {
+        validator_id.parse::<usize>().map_err(|e| {
+            ApiError::BadRequest(format!("Unable to parse validator index: {:?}", e))
+        })?
+    };
+
+    let validator = beacon_chain
+        .head()?
+        .beacon_state
+        .validators
+        .get(validator_index)
+        .cloned()
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Unknown validator index: {}", validator_index))
+        })?;
+
+    let current_epoch = beacon_chain.epoch()?;
+    let far_future_epoch = beacon_chain.spec.far_future_epoch;
+    let pool_exit_epoch = beacon_chain
+        .op_pool
+        .get_voluntary_exit(validator_index as u64)
+        .map(|exit| exit.message.epoch);
+
+    let stage = if validator.exit_epoch == far_future_epoch {
+        if pool_exit_epoch.is_some() {
+            ExitStage::ExitInPool
+        } else {
+            ExitStage::NotExiting
+        }
+    } else if current_epoch < validator.exit_epoch {
+        ExitStage::Exiting
+    } else if current_epoch < validator.withdrawable_epoch {
+        ExitStage::Exited
+    } else {
+        ExitStage::Withdrawable
+    };
+
+    let unless_far_future = |epoch: Epoch| Some(epoch).filter(|epoch| *epoch != far_future_epoch);
+
+    Ok(ExitStatus {
+        validator_index: validator_index as u64,
+        validator_pubkey: validator.pubkey,
+        current_epoch,
+        stage,
+        slashed: validator.slashed,
+        pool_exit_epoch,
+        exit_epoch: unless_far_future(validator.exit_epoch),
+        withdrawable_epoch: unless_far_future(validator.withdrawable_epoch),
+    })
+}
+
+/// The progress of a validator's exit, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitStage {
+    /// No exit is known for the validator.
+    NotExiting,
+    /// A voluntary exit is in the operation pool, but has not yet been included in a block.
+    ExitInPool,
+    /// The exit has been initiated and the validator is still active until its exit epoch.
+    Exiting,
+    /// The validator has exited, but its balance is not yet withdrawable.
+    Exited,
+    /// The validator's balance is withdrawable.
+    Withdrawable,
+}
+
+/// Information returned by `exit_status`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ExitStatus {
+    /// The validator's index in `state.validators`.
+    validator_index: u64,
+    /// The validator's BLS public key.
+    validator_pubkey: PublicKeyBytes,
+    /// The epoch relative to which `stage` is determined.
+    current_epoch: Epoch,
+    stage: ExitStage,
+    /// If true, the validator was exited by a slashing.
+    slashed: bool,
+    /// The epoch of the validator's voluntary exit in the operation pool, if any.
+    pool_exit_epoch: Option<Epoch>,
+    /// The epoch at which the validator stops validating, once its exit has been initiated.
+    exit_epoch: Option<Epoch>,
+    /// The epoch at which the validator's balance becomes withdrawable, once its exit has been
+    /// initiated.
+    withdrawable_epoch: Option<Epoch>,
+}
+
+/// Returns a Merkle proof of the inclusion of an attestation or deposit within the body of a block,
+/// identified in the request path by the block root, the kind of operation (`attestations` or
+/// `deposits`) and the index of the operation within the block.
+pub fn block_proof<T: BeaconChainTypes>(
+    req: Request<Vec<u8>>,
+    ctx: Arc<Context<T>>,
+) -> Result<BlockInclusionProof, ApiError> {
+    let path = req.uri().path();
+    let parts = path
+        .strip_prefix(BLOCKS_PATH_PREFIX)
+        .map(|path| path.split('/').collect::<Vec<_>>())
+        .filter(|parts| parts.len() == 4 && parts[1] == "proofs")
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid path: {}", path)))?;
+
+    let block_root = parse_root(parts[0])?;
+    let index = parts[3]
+        .parse::<usize>()
+        .map_err(|e| ApiError::BadRequest(format!("Unable to parse operation index: {:?}", e)))?;
+
+    let block = BlockId::Root(block_root).block(&ctx.beacon_chain)?.message;
+
+    let proof = match parts[2] {
+        "attestations" => block.body.attestation_proof(index),
+        "deposits" => block.body.deposit_proof(index),
+        kind => {
+            return Err(ApiError::BadRequest(format!(
+                "Unknown operation kind: {}",
+                kind
+            )))
+        }
+    }
+    .ok_or_else(|| ApiError::NotFound(format!("Block has no {} at index {}", parts[2], index)))?;
+
+    Ok(BlockInclusionProof {
+        block_root,
+        slot: block.slot,
+        body_root: block.block_header().body_root,
+        index,
+        proof,
+    })
+}
+
+/// Information returned by `block_proof`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BlockInclusionProof {
+    block_root: Hash256,
+    slot: Slot,
+    /// The root against which `proof` is verified, as found in the header of the block.
+    body_root: Hash256,
+    /// The index of the operation within its list in the block body.
+    index: usize,
+    proof: BeaconBlockBodyProof,
+}
+
+/// Returns the rewards earned by the proposer of a block, found by replaying the block against
+/// its pre-state. The block is identified by its root or, for canonical blocks, its slot.
+///
+/// Attestation inclusion rewards are not paid until the end of the epoch after the attestation's
+/// epoch, so they are estimated from the pre-state. They are only paid to the first proposer to
+/// include each attester, so attesters already included in the pre-state are not counted.
+pub fn block_rewards<T: BeaconChainTypes>(
+    req: Request<Vec<u8>>,
+    ctx: Arc<Context<T>>,
+) -> Result<BlockRewards, ApiError> {
+    let path = req.uri().path();
+    let block_id = path
+        .strip_prefix(BLOCKS_PATH_PREFIX)
+        .and_then(|path| path.strip_suffix(BLOCK_REWARDS_PATH_SUFFIX))
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid path: {}", path)))?;
+
+    let beacon_chain = &ctx.beacon_chain;
+    let spec = &beacon_chain.spec;
+
+    let block_id = BlockId::from_path_segment(block_id)?;
+    let (resolved, block) = block_id.resolve(beacon_chain)?;
+    let block_root = resolved.root;
+    // A slot identifies the most recent block at or prior to it, rewards are only reported for a
+    // block at exactly that slot.
+    if let BlockId::Slot(slot) = block_id {
+        if resolved.slot != slot {
+            return Err(ApiError::NotFound(format!(
+                "No canonical block at slot {}",
+                slot
+            )));
+        }
+    }
+    if block.slot() == spec.genesis_slot {
+        return Err(ApiError::BadRequest(
+            "The genesis block has no rewards".to_string(),
+        ));
+    }
+
+    let parent = beacon_chain
+        .get_block(&block.parent_root())?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Unknown parent block: {:?}", block.parent_root()))
+        })?;
+    let mut state = beacon_chain
+        .get_state(&parent.state_root(), Some(parent.slot()))?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Unknown parent state: {:?}", parent.state_root()))
+        })?;
+
+    // Advance through any skipped slots to the slot of the block.
+    while state.slot < block.slot() {
+        // Ensure the next epoch state caches are built in case of an epoch transition.
+        state.build_committee_cache(RelativeEpoch::Next, spec)?;
+        per_slot_processing(&mut state, None, spec)?;
+    }
+    state.build_committee_cache(RelativeEpoch::Previous, spec)?;
+    state.build_committee_cache(RelativeEpoch::Current, spec)?;
+
+    let proposer_index = block.message.proposer_index as usize;
+    let total_active_balance = state.get_total_balance(
+        &state.get_active_validator_indices(state.current_epoch(), spec)?,
+        spec,
+    )?;
+
+    // The attesters which an earlier block has already included, by target epoch.
+    let mut included = HashSet::new();
+    for pending in state
+        .previous_epoch_attestations
+        .iter()
+        .chain(state.current_epoch_attestations.iter())
+    {
+        let committee = state.get_beacon_committee(pending.data.slot, pending.data.index)?;
+        for index in
+            get_attesting_indices::<T::EthSpec>(committee.committee, &pending.aggregation_bits)?
+        {
+            included.insert((pending.data.target.epoch, index));
+        }
+    }
+
+    let mut attestation_inclusion_rewards = 0;
+    for attestation in block.message.body.attestations.iter() {
+        let committee =
+            state.get_beacon_committee(attestation.data.slot, attestation.data.index)?;
+        for index in
+            get_attesting_indices::<T::EthSpec>(committee.committee, &attestation.aggregation_bits)?
+        {
+            let slashed = state.validators.get(index).map_or(true, |v| v.slashed);
+            if !slashed && included.insert((attestation.data.target.epoch, index)) {
+                let base_reward = get_base_reward(&state, index, total_active_balance, spec)?;
+                attestation_inclusion_rewards += base_reward / spec.proposer_reward_quotient;
+            }
+        }
+    }
+
+    // Whistleblower rewards are paid during block processing, so they are the increase in the
+    // balance of the proposer, less any deposits which top it up.
+    let proposer = state
+        .validators
+        .get(proposer_index)
+        .ok_or_else(|| ApiError::ServerError(format!("Unknown proposer: {}", proposer_index)))?;
+    let deposits = block
+        .message
+        .body
+        .deposits
+        .iter()
+        .filter(|deposit| deposit.data.pubkey == proposer.pubkey)
+        .map(|deposit| deposit.data.amount)
+        .sum::<u64>();
+    let balance_before = state.balances[proposer_index];
+
+    per_block_processing(
+        &mut state,
+        &block,
+        Some(block_root),
+        BlockSignatureStrategy::NoVerification,
+        spec,
+    )
+    .map_err(|e| ApiError::ServerError(format!("Unable to replay block: {:?}", e)))?;
+
+    let slashing_rewards = state.balances[proposer_index]
+        .saturating_sub(balance_before)
+        .saturating_sub(deposits);
+
+    Ok(BlockRewards {
+        block_root,
+        slot: block.slot(),
+        proposer_index: proposer_index as u64,
+        total: attestation_inclusion_rewards + slashing_rewards,
+        attestation_inclusion_rewards,
+        slashing_rewards,
+    })
+}
+
+/// Information returned by `block_rewards`. All rewards are in Gwei.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BlockRewards {
+    block_root: Hash256,
+    slot: Slot,
+    proposer_index: u64,
+    total: u64,
+    /// The rewards for including attestations from attesters which no earlier block included.
+    attestation_inclusion_rewards: u64,
+    /// The whistleblower rewards for the proposer and attester slashings in the block.
+    slashing_rewards: u64,
+}
+
+/// Returns the canonical blocks in the `count` slots from `start_slot`, along with their roots.
+///
+/// Intended to be requested as SSZ, for bulk export of the chain.
+pub fn chain_segments<T: BeaconChainTypes>(
+    req: Request<Vec<u8>>,
+    ctx: Arc<Context<T>>,
+) -> Result<ChainSegment<T::EthSpec>, ApiError> {
+    let start_slot = UrlQuery::from_request(&req)?
+        .only_one("start_slot")
+        .and_then(|value| parse_slot(&value))?;
+    let count = UrlQuery::from_request(&req)?
+        .only_one("count")
+        .and_then(|value| {
+            value
+                .parse::<u64>()
+                .map_err(|e| ApiError::BadRequest(format!("Invalid count: {:?}", e)))
+        })?;
+
+    if count == 0 || count > MAX_CHAIN_SEGMENT_SLOTS {
+        return Err(ApiError::BadRequest(format!(
+            "The count must be between 1 and {}",
+            MAX_CHAIN_SEGMENT_SLOTS
+        )));
+    }
+
+    let beacon_chain = &ctx.beacon_chain;
+    let end_slot = start_slot + count;
+    let head_slot = beacon_chain.head_info()?.slot;
+
+    if start_slot > head_slot {
+        return Ok(ChainSegment {
+            roots: vec![],
+            blocks: vec![],
+        });
+    }
+
+    // The iterator returns the prior block root for skipped slots, so roots may repeat.
+    let block_roots = beacon_chain.forwards_iter_block_roots(start_slot)?;
+    let mut roots = process_results(block_roots, |iter| {
+        iter.take_while(|(_, slot)| *slot < end_slot)
+            .map(|(root, _)| root)
+            .collect::<Vec<_>>()
+    })?;
+    roots.dedup();
+
+    let mut segment = ChainSegment {
+        roots: Vec::with_capacity(roots.len()),
+        blocks: Vec::with_capacity(roots.len()),
+    };
+    for root in roots {
+        let block = beacon_chain.store.get_block(&root)?.ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Unable to find SignedBeaconBlock for root {:?}",
+                root
+            ))
+        })?;
+
+        // The first root may be that of a block prior to `start_slot`, if that slot was skipped.
+        if block.message.slot < start_slot {
+            continue;
+        }
+
+        segment.roots.push(root);
+        segment.blocks.push(block);
+    }
+
+    Ok(segment)
+}
+
+/// Returns statistics about blocks imported after the attestation deadline of their slot.
+pub fn late_blocks<T: BeaconChainTypes>(ctx: Arc<Context<T>>) -> Result<LateBlocks, ApiError> {
+    let beacon_chain = &ctx.beacon_chain;
+    let summary = beacon_chain.late_blocks.summary();
+
+    let recent = summary
+        .recent
+        .into_iter()
+        .map(|late_block| {
+            Ok(LateBlock {
+                slot: late_block.slot,
+                block_root: late_block.block_root,
+                delay_ms: late_block.delay.as_millis() as u64,
+                attestations: late_block.attestations,
+                canonical: beacon_chain.root_at_slot(late_block.slot)?
+                    == Some(late_block.block_root),
+            })
+        })
+        .collect::<Result<_, ApiError>>()?;
+
+    Ok(LateBlocks {
+        attestation_deadline_ms: summary.deadline.as_millis() as u64,
+        blocks_observed: summary.blocks_observed,
+        late_blocks: summary.late_blocks,
+        attestations_to_late_blocks: summary.attestations_to_late_blocks,
+        recent,
+    })
+}
+
+/// Information returned by `late_blocks`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LateBlocks {
+    /// Blocks imported later than this into their slot are considered late.
+    attestation_deadline_ms: u64,
+    /// The number of blocks imported during their own slot since the node started.
+    blocks_observed: u64,
+    /// The number of those blocks imported after the attestation deadline.
+    late_blocks: u64,
+    /// The number of attestations received for the blocks in `recent`.
+    attestations_to_late_blocks: u64,
+    /// The most recent late blocks, oldest first.
+    recent: Vec<LateBlock>,
+}
+
+/// A late block, as returned by `late_blocks`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LateBlock {
+    slot: Slot,
+    block_root: Hash256,
+    /// The time between the start of the slot and the block being imported.
+    delay_ms: u64,
+    /// The number of attestations received which vote for the block as the head.
+    attestations: u64,
+    /// Whether the block is in the canonical chain.
+    canonical: bool,
+}
+
+/// Returns the operations which would be packed into a block produced at the current slot.
+pub fn block_packing_preview<T: BeaconChainTypes>(
+    ctx: Arc<Context<T>>,
+) -> Result<BlockPackingPreview<T::EthSpec>, ApiError> {
+    let beacon_chain = &ctx.beacon_chain;
+    let (slot, operations) = beacon_chain
+        .block_packing_preview()
+        .map_err(|e| ApiError::ServerError(format!("Unable to pack operations: {:?}", e)))?;
+
+    Ok(BlockPackingPreview {
+        slot,
+        config: beacon_chain.config.block_packing,
+        proposer_slashings: operations.proposer_slashings,
+        attester_slashings: operations.attester_slashings,
+        attestations: operations.attestations,
+        voluntary_exits: operations.voluntary_exits,
+    })
+}
+
+/// Information returned by `block_packing_preview`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(bound = "T: EthSpec")]
+pub struct BlockPackingPreview<T: EthSpec> {
+    /// The slot of the block which the operations would be packed into.
+    slot: Slot,
+    /// The policy which selected the slashings and exits.
+    config: BlockPackingConfig,
+    proposer_slashings: Vec<ProposerSlashing>,
+    attester_slashings: Vec<AttesterSlashing<T>>,
+    attestations: Vec<Attestation<T>>,
+    voluntary_exits: Vec<SignedVoluntaryExit>,
+}
+
+/// Imports a snapshot of the operation pool of another node, in the format returned by
+/// `/advanced/operation_pool`.
+///
+/// Every operation is verified before it is added to the pool, so that a bad snapshot can't cause
+/// the node to produce invalid blocks. Operations which fail verification are counted as rejected
+/// rather than failing the whole import.
+pub fn import_pool<T: BeaconChainTypes>(
+    req: Request<Vec<u8>>,
+    ctx: Arc<Context<T>>,
+) -> Result<PoolImportReport, ApiError> {
+    let snapshot = ctx
+        .parse_json::<PersistedOperationPool<T::EthSpec>>(req.body())
+        .map_err(|e| {
+            ApiError::BadRequest(format!(
+                "Unable to parse JSON into PersistedOperationPool: {:?}",
+                e
+            ))
+        })?;
+
+    let beacon_chain = &ctx.beacon_chain;
+    let spec = &beacon_chain.spec;
+
+    // Without an eth1 chain the node can't produce blocks, so it has no use for an op pool.
+    if beacon_chain.eth1_chain.is_none() {
+        return Err(ApiError::BadRequest(
+            "Cannot import operations on node without Eth1 connection.".to_string(),
+        ));
+    }
+
+    let mut report = PoolImportReport::default();
+
+    // The op pool doesn't check signatures when packing blocks, so they must be checked here.
+    let mut state = beacon_chain.wall_clock_state()?;
+    state.build_committee_cache(RelativeEpoch::Previous, spec)?;
+    state.build_committee_cache(RelativeEpoch::Current, spec)?;
+
+    for attestation in snapshot.attestations() {
+        let valid = verify_attestation_for_block_inclusion(
+            &state,
+            attestation,
+            VerifySignatures::True,
+            spec,
+        )
+        .is_ok();
+        let imported = valid
+            && beacon_chain
+                .op_pool
+                .insert_attestation(
+                    attestation.clone(),
+                    &state.fork,
+                    state.genesis_validators_root,
+                    spec,
+                )
+                .is_ok();
+        report.attestations.observe(imported);
+    }
+
+    for slashing in snapshot.attester_slashings() {
+        match beacon_chain.verify_attester_slashing_for_gossip(slashing.clone()) {
+            Ok(ObservationOutcome::New(verified)) => report
+                .attester_slashings
+                .observe(beacon_chain.import_attester_slashing(verified).is_ok()),
+            Ok(ObservationOutcome::AlreadyKnown) => report.attester_slashings.already_known += 1,
+            Err(_) => report.attester_slashings.observe(false),
+        }
+    }
+
+    for slashing in snapshot.proposer_slashings() {
+        match beacon_chain.verify_proposer_slashing_for_gossip(slashing.clone()) {
+            Ok(ObservationOutcome::New(verified)) => {
+                beacon_chain.import_proposer_slashing(verified);
+                report.proposer_slashings.observe(true)
+            }
+            Ok(ObservationOutcome::AlreadyKnown) => report.proposer_slashings.already_known += 1,
+            Err(_) => report.proposer_slashings.observe(false),
+        }
+    }
+
+    for exit in snapshot.voluntary_exits() {
+        match beacon_chain.verify_voluntary_exit_for_gossip(exit.clone()) {
+            Ok(ObservationOutcome::New(verified)) => {
+                beacon_chain.import_voluntary_exit(verified);
+                report.voluntary_exits.observe(true)
+            }
+            Ok(ObservationOutcome::AlreadyKnown) => report.voluntary_exits.already_known += 1,
+            Err(_) => report.voluntary_exits.observe(false),
+        }
+    }
+
+    info!(
+        ctx.log,
+        "Imported operation pool snapshot";
+        "attestations" => report.attestations.imported,
+        "attester_slashings" => report.attester_slashings.imported,
+        "proposer_slashings" => report.proposer_slashings.imported,
+        "voluntary_exits" => report.voluntary_exits.imported,
+        "rejected" => report.attestations.rejected
+            + report.attester_slashings.rejected
+            + report.proposer_slashings.rejected
+            + report.voluntary_exits.rejected,
+    );
+
+    Ok(report)
+}
+
+/// Information returned by `import_pool`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct PoolImportReport {
+    attestations: ImportCounts,
+    attester_slashings: ImportCounts,
+    proposer_slashings: ImportCounts,
+    voluntary_exits: ImportCounts,
+}
+
+/// The outcome of importing a single kind of operation.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ImportCounts {
+    /// Operations added to the pool.
+    imported: u64,
+    /// Operations which the node had already seen, and were skipped.
+    ///
+    /// Attestations are always counted as imported, since they are aggregated with any copies
+    /// already in the pool.
+    already_known: u64,
+    /// Operations which failed verification, e.g. because they are invalid or too old.
+    rejected: u64,
+}
+
+impl ImportCounts {
+    fn observe(&mut self, imported: bool) {
+        if imported {
+            self.imported += 1;
+        } else {
+            self.rejected += 1;
+        }
+    }
+}
@@ -1,19 +1,39 @@
 use crate::{ApiError, Context};
 use beacon_chain::BeaconChainTypes;
 use eth2_libp2p::types::SyncState;
-use rest_types::{SyncingResponse, SyncingStatus};
+use eth2_libp2p::Client;
+use rest_types::{Capabilities, NamespaceCapability, SyncingResponse, SyncingStatus};
+use serde::Serialize;
 use std::sync::Arc;
 use types::Slot;
 
+/// The namespaces of the API, and the endpoints which are optional in their own right, reported by
+/// `/node/capabilities`.
+pub const NAMESPACES: &[&str] = &[
+    "/node/",
+    "/network/",
+    "/beacon/",
+    "/validator/",
+    "/consensus/",
+    "/spec",
+    "/config/",
+    "/advanced/",
+    "/metrics",
+    "/lighthouse/",
+    "/beacon/fork/stream",
+    "/validator/duties/stream",
+];
+
 /// Returns a syncing status.
 pub fn syncing<T: BeaconChainTypes>(ctx: Arc<Context<T>>) -> Result<SyncingResponse, ApiError> {
+    let network_globals = ctx.network_globals()?;
     let current_slot = ctx
         .beacon_chain
         .head_info()
         .map_err(|e| ApiError::ServerError(format!("Unable to read head slot: {:?}", e)))?
         .slot;
 
-    let (starting_slot, highest_slot) = match ctx.network_globals.sync_state() {
+    let (starting_slot, highest_slot) = match network_globals.sync_state() {
         SyncState::SyncingFinalized {
             start_slot,
             head_slot,
@@ -33,7 +53,56 @@ pub fn syncing<T: BeaconChainTypes>(ctx: Arc<Context<T>>) -> Result<SyncingRespo
     };
 
     Ok(SyncingResponse {
-        is_syncing: ctx.network_globals.is_syncing(),
+        is_syncing: network_globals.is_syncing(),
         sync_status,
+        store_maintenance: ctx.beacon_chain.store.maintenance_in_progress(),
+    })
+}
+
+/// Returns the optional parts of the API which are enabled.
+pub fn capabilities<T: BeaconChainTypes>(ctx: Arc<Context<T>>) -> Result<Capabilities, ApiError> {
+    let reloadable_config = ctx.reloadable_config.load_full();
+
+    let namespaces = NAMESPACES
+        .iter()
+        .map(|path| NamespaceCapability {
+            path: path.to_string(),
+            enabled: !reloadable_config.is_disabled(path),
+        })
+        .collect();
+
+    Ok(Capabilities {
+        namespaces,
+        disabled_routes: reloadable_config.disabled_routes.clone(),
+        network: ctx.network_info.is_some(),
+        config_reload: ctx.config.config_file.is_some(),
     })
 }
+
+/// A connected peer, as returned by `/node/peers`.
+#[derive(Clone, Debug, Serialize)]
+pub struct NodePeer {
+    pub peer_id: String,
+    /// `outbound` if we dialed the peer, otherwise `inbound`.
+    pub direction: &'static str,
+    /// The client the peer identified itself as running.
+    pub client: Client,
+}
+
+/// Returns the connected peers and the clients they run.
+pub fn peers<T: BeaconChainTypes>(ctx: Arc<Context<T>>) -> Result<Vec<NodePeer>, ApiError> {
+    Ok(ctx
+        .network_globals()?
+        .peers
+        .read()
+        .connected_peers()
+        .map(|(peer_id, info)| NodePeer {
+            peer_id: peer_id.to_string(),
+            direction: match info.connection_status.connections() {
+                (_, 0) => "inbound",
+                _ => "outbound",
+            },
+            client: info.client.clone(),
+        })
+        .collect())
+}
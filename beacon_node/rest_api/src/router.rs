@@ -1,50 +1,190 @@
 use crate::{
-    beacon, config::Config, consensus, lighthouse, metrics, node, validator, NetworkChannel,
+    beacon,
+    config::{Config, PathNormalization, ReloadableConfig},
+    consensus, duties_stream,
+    duties_stream::DutiesSubscriptions,
+    helpers::{canonical_path, parse_json_body},
+    lighthouse, metrics, node, validator, NetworkChannel, NetworkInfo,
 };
-use beacon_chain::{BeaconChain, BeaconChainTypes};
+use arc_swap::ArcSwap;
+use beacon_chain::{events::ServerSentEvent, BeaconChain, BeaconChainTypes};
 use bus::Bus;
 use environment::TaskExecutor;
 use eth2_config::Eth2Config;
 use eth2_libp2p::{NetworkGlobals, PeerId};
 use hyper::header::HeaderValue;
-use hyper::{Body, Method, Request, Response};
+use hyper::{Body, Method, Request, Response, StatusCode, Uri};
 use lighthouse_version::version_with_platform;
 use operation_pool::PersistedOperationPool;
 use parking_lot::Mutex;
-use rest_types::{ApiError, Handler, Health};
-use slog::debug;
+use rest_types::{ApiError, Handler, Health, ThrottleInfo};
+use serde::de::DeserializeOwned;
+use slog::{log, Level};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
-use types::{EthSpec, SignedBeaconBlockHash};
+use tokio::sync::Semaphore;
+use types::{EthSpec, YamlConfig};
+
+/// The number of requests which may be served concurrently while the database is undergoing
+/// maintenance. Further requests are queued until one completes.
+pub const MAINTENANCE_CONCURRENT_REQUESTS: usize = 4;
+/// The number of requests which may be queued while the database is undergoing maintenance.
+/// Further requests are rejected with a 503 until the queue shortens.
+const MAINTENANCE_QUEUED_REQUESTS: usize = 64;
+/// The number of seconds a client is asked to wait before retrying a rejected request.
+const MAINTENANCE_RETRY_AFTER_SECS: u64 = 5;
 
 pub struct Context<T: BeaconChainTypes> {
     pub executor: TaskExecutor,
     pub config: Config,
     pub beacon_chain: Arc<BeaconChain<T>>,
-    pub network_globals: Arc<NetworkGlobals<T::EthSpec>>,
-    pub network_chan: NetworkChannel<T::EthSpec>,
+    /// The libp2p network, which is absent when the beacon node is not connected to the network.
+    pub network_info: Option<NetworkInfo<T>>,
     pub eth2_config: Arc<Eth2Config>,
     pub log: slog::Logger,
     pub db_path: PathBuf,
     pub freezer_db_path: PathBuf,
-    pub events: Arc<Mutex<Bus<SignedBeaconBlockHash>>>,
+    pub events: Arc<Mutex<Bus<ServerSentEvent>>>,
+    pub duties_subscriptions: DutiesSubscriptions,
+    /// Limits the concurrent requests during database maintenance.
+    pub maintenance_permits: Semaphore,
+    /// The number of requests waiting for one of the `maintenance_permits`.
+    pub maintenance_queue_depth: AtomicUsize,
+    /// The current value of `config.reloadable`, which may be replaced whilst the server runs.
+    pub reloadable_config: ArcSwap<ReloadableConfig>,
+}
+
+impl<T: BeaconChainTypes> Context<T> {
+    /// Returns the globals of the libp2p network, or an error if the network is disabled.
+    pub fn network_globals(&self) -> Result<&Arc<NetworkGlobals<T::EthSpec>>, ApiError> {
+        self.network_info
+            .as_ref()
+            .map(|info| &info.network_globals)
+            .ok_or_else(network_disabled)
+    }
+
+    /// Returns the channel to the libp2p network, or an error if the network is disabled.
+    pub fn network_chan(&self) -> Result<&NetworkChannel<T::EthSpec>, ApiError> {
+        self.network_info
+            .as_ref()
+            .map(|info| &info.network_chan)
+            .ok_or_else(network_disabled)
+    }
+
+    /// Parse a JSON request body, accepting quoted and unquoted integers alike if
+    /// `lenient_json_numbers` is enabled.
+    pub fn parse_json<D: DeserializeOwned>(&self, body: &[u8]) -> Result<D, serde_json::Error> {
+        parse_json_body(body, self.reloadable_config.load().lenient_json_numbers)
+    }
+
+    /// Read `config.config_file` again, replacing the `reloadable_config` used by subsequent
+    /// requests.
+    ///
+    /// The current config is left unchanged if the file is invalid.
+    pub fn reload_config(&self) -> Result<ReloadableConfig, String> {
+        let path = self
+            .config
+            .config_file
+            .as_ref()
+            .ok_or_else(|| "The HTTP API was started without a config file".to_string())?;
+
+        let config = ReloadableConfig::from_file(path)?;
+        self.reloadable_config.store(Arc::new(config.clone()));
+
+        Ok(config)
+    }
+}
+
+fn network_disabled() -> ApiError {
+    ApiError::ServiceUnavailable("The beacon node is not connected to the network".to_string())
 }
 
 pub async fn on_http_request<T: BeaconChainTypes>(
-    req: Request<Body>,
+    mut req: Request<Body>,
     ctx: Arc<Context<T>>,
 ) -> Result<Response<Body>, ApiError> {
-    let path = req.uri().path().to_string();
+    let reloadable_config = ctx.reloadable_config.load_full();
+
+    // Requests for a non-canonical form of a path are either redirected to the canonical path,
+    // or handled as if they were for the canonical path (which is also used to decide whether the
+    // path is disabled and to label metrics).
+    let mut redirect = None;
+    let path = match reloadable_config.path_normalization {
+        PathNormalization::Disabled => req.uri().path().to_string(),
+        normalization => {
+            let path = canonical_path(req.uri().path());
+            if path != req.uri().path() {
+                let path_and_query = match req.uri().query() {
+                    Some(query) => format!("{}?{}", path, query),
+                    None => path.clone(),
+                };
+                if normalization == PathNormalization::Redirect {
+                    redirect = Some(path_and_query);
+                } else {
+                    *req.uri_mut() = path_and_query.parse::<Uri>().map_err(|e| {
+                        ApiError::BadRequest(format!("Invalid request path: {:?}", e))
+                    })?;
+                }
+            }
+            path
+        }
+    };
 
     let _timer = metrics::start_timer_vec(&metrics::BEACON_HTTP_API_TIMES_TOTAL, &[&path]);
     metrics::inc_counter_vec(&metrics::BEACON_HTTP_API_REQUESTS_TOTAL, &[&path]);
 
     let received_instant = Instant::now();
     let log = ctx.log.clone();
-    let allow_origin = ctx.config.allow_origin.clone();
+    let allow_origin = reloadable_config.allow_origin.clone();
+    let log_level = if reloadable_config.log_requests {
+        Level::Info
+    } else {
+        Level::Debug
+    };
+
+    // Apply backpressure whilst the database is busy with maintenance, rather than competing with
+    // it for disk access. The `/node` endpoints don't read the database, and always stay
+    // responsive so that clients can observe the maintenance. The `/validator` endpoints are
+    // needed to perform duties on time, so they are never delayed behind other queries.
+    //
+    // Once the queue is full, requests are shed rather than queued without limit.
+    let mut overloaded = None;
+    let throttled = !(path.starts_with("/node/") || path.starts_with("/validator/"));
+    let _permit = if ctx.beacon_chain.store.maintenance_in_progress() && throttled {
+        let queued = QueuedRequest::new(&ctx.maintenance_queue_depth);
+        if queued.depth > MAINTENANCE_QUEUED_REQUESTS {
+            overloaded = Some(ThrottleInfo {
+                reason: "The database is undergoing maintenance".to_string(),
+                queue_depth: queued.depth as u64,
+                retry_after_secs: MAINTENANCE_RETRY_AFTER_SECS,
+            });
+            None
+        } else {
+            Some(ctx.maintenance_permits.acquire().await)
+        }
+    } else {
+        None
+    };
 
-    match route(req, ctx).await {
+    let result = if let Some(info) = overloaded {
+        Err(ApiError::Overloaded(info))
+    } else if reloadable_config.is_disabled(&path) {
+        Err(ApiError::NotFound(
+            "Request path has been disabled by the node operator.".to_owned(),
+        ))
+    } else if let Some(location) = redirect {
+        Response::builder()
+            .status(StatusCode::PERMANENT_REDIRECT)
+            .header(hyper::header::LOCATION, location)
+            .body(Body::empty())
+            .map_err(|e| ApiError::ServerError(format!("Unable to build redirect: {:?}", e)))
+    } else {
+        route(req, ctx.clone()).await
+    };
+
+    match result {
         Ok(mut response) => {
             metrics::inc_counter_vec(&metrics::BEACON_HTTP_API_SUCCESS_TOTAL, &[&path]);
 
@@ -57,8 +197,10 @@ pub async fn on_http_request<T: BeaconChainTypes>(
                 headers.insert(hyper::header::VARY, HeaderValue::from_static("Origin"));
             }
 
-            debug!(
+            log!(
                 log,
+                log_level,
+                "",
                 "HTTP API request successful";
                 "path" => path,
                 "duration_ms" => Instant::now().duration_since(received_instant).as_millis()
@@ -69,8 +211,10 @@ pub async fn on_http_request<T: BeaconChainTypes>(
         Err(error) => {
             metrics::inc_counter_vec(&metrics::BEACON_HTTP_API_ERROR_TOTAL, &[&path]);
 
-            debug!(
+            log!(
                 log,
+                log_level,
+                "",
                 "HTTP API request failure";
                 "path" => path,
                 "duration_ms" => Instant::now().duration_since(received_instant).as_millis()
@@ -80,6 +224,27 @@ pub async fn on_http_request<T: BeaconChainTypes>(
     }
 }
 
+/// Counts a request in a queue for as long as it is held, including if the request is dropped
+/// whilst waiting.
+struct QueuedRequest<'a> {
+    counter: &'a AtomicUsize,
+    /// The number of requests in the queue, including this one.
+    depth: usize,
+}
+
+impl<'a> QueuedRequest<'a> {
+    fn new(counter: &'a AtomicUsize) -> Self {
+        let depth = counter.fetch_add(1, Ordering::SeqCst) + 1;
+        Self { counter, depth }
+    }
+}
+
+impl<'a> Drop for QueuedRequest<'a> {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
 async fn route<T: BeaconChainTypes>(
     req: Request<Body>,
     ctx: Arc<Context<T>>,
@@ -99,27 +264,35 @@ async fn route<T: BeaconChainTypes>(
             .static_value(Health::observe().map_err(ApiError::ServerError)?)
             .await?
             .serde_encodings(),
+        (Method::GET, "/node/capabilities") => handler
+            .in_core_task(|_, ctx| node::capabilities(ctx))
+            .await?
+            .serde_encodings(),
         (Method::GET, "/node/syncing") => handler
             .allow_body()
             .in_blocking_task(|_, ctx| node::syncing(ctx))
             .await?
             .serde_encodings(),
+        (Method::GET, "/node/peers") => handler
+            .in_blocking_task(|_, ctx| node::peers(ctx))
+            .await?
+            .serde_encodings(),
         (Method::GET, "/network/enr") => handler
-            .in_core_task(|_, ctx| Ok(ctx.network_globals.local_enr().to_base64()))
+            .in_core_task(|_, ctx| Ok(ctx.network_globals()?.local_enr().to_base64()))
             .await?
             .serde_encodings(),
         (Method::GET, "/network/peer_count") => handler
-            .in_core_task(|_, ctx| Ok(ctx.network_globals.connected_peers()))
+            .in_core_task(|_, ctx| Ok(ctx.network_globals()?.connected_peers()))
             .await?
             .serde_encodings(),
         (Method::GET, "/network/peer_id") => handler
-            .in_core_task(|_, ctx| Ok(ctx.network_globals.local_peer_id().to_base58()))
+            .in_core_task(|_, ctx| Ok(ctx.network_globals()?.local_peer_id().to_base58()))
             .await?
             .serde_encodings(),
         (Method::GET, "/network/peers") => handler
             .in_blocking_task(|_, ctx| {
                 Ok(ctx
-                    .network_globals
+                    .network_globals()?
                     .peers
                     .read()
                     .connected_peer_ids()
@@ -129,11 +302,11 @@ async fn route<T: BeaconChainTypes>(
             .await?
             .serde_encodings(),
         (Method::GET, "/network/listen_port") => handler
-            .in_core_task(|_, ctx| Ok(ctx.network_globals.listen_port_tcp()))
+            .in_core_task(|_, ctx| Ok(ctx.network_globals()?.listen_port_tcp()))
             .await?
             .serde_encodings(),
         (Method::GET, "/network/listen_addresses") => handler
-            .in_blocking_task(|_, ctx| Ok(ctx.network_globals.listen_multiaddrs()))
+            .in_blocking_task(|_, ctx| Ok(ctx.network_globals()?.listen_multiaddrs()))
             .await?
             .serde_encodings(),
         (Method::GET, "/beacon/head") => handler
@@ -148,6 +321,10 @@ async fn route<T: BeaconChainTypes>(
             .in_blocking_task(beacon::get_block)
             .await?
             .all_encodings(),
+        (Method::GET, "/beacon/headers") => handler
+            .in_blocking_task(beacon::get_headers)
+            .await?
+            .all_encodings(),
         (Method::GET, "/beacon/block_root") => handler
             .in_blocking_task(beacon::get_block_root)
             .await?
@@ -156,9 +333,7 @@ async fn route<T: BeaconChainTypes>(
             .in_blocking_task(|_, ctx| Ok(ctx.beacon_chain.head_info()?.fork))
             .await?
             .all_encodings(),
-        (Method::GET, "/beacon/fork/stream") => {
-            handler.sse_stream(|_, ctx| beacon::stream_forks(ctx)).await
-        }
+        (Method::GET, "/beacon/fork/stream") => handler.sse_stream(beacon::stream_forks).await,
         (Method::GET, "/beacon/genesis_time") => handler
             .in_blocking_task(|_, ctx| Ok(ctx.beacon_chain.head_info()?.genesis_time))
             .await?
@@ -167,6 +342,10 @@ async fn route<T: BeaconChainTypes>(
             .in_blocking_task(|_, ctx| Ok(ctx.beacon_chain.head_info()?.genesis_validators_root))
             .await?
             .all_encodings(),
+        (Method::GET, "/beacon/deposit_snapshot") => handler
+            .in_blocking_task(|_, ctx| beacon::get_deposit_snapshot(ctx))
+            .await?
+            .serde_encodings(),
         (Method::GET, "/beacon/validators") => handler
             .in_blocking_task(beacon::get_validators)
             .await?
@@ -205,6 +384,11 @@ async fn route<T: BeaconChainTypes>(
             .in_blocking_task(beacon::proposer_slashing)
             .await?
             .serde_encodings(),
+        (Method::POST, "/beacon/voluntary_exit") => handler
+            .allow_body()
+            .in_blocking_task(beacon::voluntary_exit)
+            .await?
+            .serde_encodings(),
         (Method::POST, "/beacon/attester_slashing") => handler
             .allow_body()
             .in_blocking_task(beacon::attester_slashing)
@@ -228,6 +412,14 @@ async fn route<T: BeaconChainTypes>(
             .in_blocking_task(validator::get_active_validator_duties)
             .await?
             .serde_encodings(),
+        (Method::POST, "/validator/duties/stream") => handler
+            .allow_body()
+            .in_blocking_task(duties_stream::post_duties_subscription)
+            .await?
+            .serde_encodings(),
+        (Method::GET, "/validator/duties/stream") => {
+            handler.sse_stream(duties_stream::stream_duties).await
+        }
         (Method::GET, "/validator/block") => handler
             .in_blocking_task(validator::get_new_beacon_block)
             .await?
@@ -279,6 +471,16 @@ async fn route<T: BeaconChainTypes>(
             .in_blocking_task(|_, ctx| Ok(ctx.eth2_config.as_ref().clone()))
             .await?
             .serde_encodings(),
+        (Method::GET, "/spec/yaml_config") => handler
+            .in_blocking_task(|_, ctx| {
+                Ok(YamlConfig::from_spec::<T::EthSpec>(&ctx.beacon_chain.spec))
+            })
+            .await?
+            .serde_encodings(),
+        (Method::GET, "/config/fork_schedule") => handler
+            .in_blocking_task(|_, ctx| Ok(vec![ctx.beacon_chain.head_info()?.fork]))
+            .await?
+            .serde_encodings(),
         (Method::GET, "/advanced/fork_choice") => handler
             .in_blocking_task(|_, ctx| {
                 Ok(ctx
@@ -304,7 +506,7 @@ async fn route<T: BeaconChainTypes>(
             .await?
             .text_encoding(),
         (Method::GET, "/lighthouse/syncing") => handler
-            .in_blocking_task(|_, ctx| Ok(ctx.network_globals.sync_state()))
+            .in_blocking_task(|_, ctx| Ok(ctx.network_globals()?.sync_state()))
             .await?
             .serde_encodings(),
         (Method::GET, "/lighthouse/peers") => handler
@@ -315,6 +517,62 @@ async fn route<T: BeaconChainTypes>(
             .in_blocking_task(|_, ctx| lighthouse::connected_peers(ctx))
             .await?
             .serde_encodings(),
+        (Method::GET, "/lighthouse/chain_segments") => handler
+            .in_blocking_task(lighthouse::chain_segments)
+            .await?
+            .all_encodings(),
+        (Method::GET, "/lighthouse/late_blocks") => handler
+            .in_blocking_task(|_, ctx| lighthouse::late_blocks(ctx))
+            .await?
+            .serde_encodings(),
+        (Method::GET, "/lighthouse/block_packing_preview") => handler
+            .in_blocking_task(|_, ctx| lighthouse::block_packing_preview(ctx))
+            .await?
+            .serde_encodings(),
+        (Method::GET, "/lighthouse/http_config") => handler
+            .in_core_task(|_, ctx| Ok(ctx.reloadable_config.load_full().as_ref().clone()))
+            .await?
+            .serde_encodings(),
+        (Method::POST, "/lighthouse/http_config/reload") => handler
+            .in_blocking_task(|_, ctx| ctx.reload_config().map_err(ApiError::BadRequest))
+            .await?
+            .serde_encodings(),
+        (Method::POST, "/lighthouse/pool/import") => handler
+            .allow_body()
+            .in_blocking_task(lighthouse::import_pool)
+            .await?
+            .serde_encodings(),
+        (Method::GET, path)
+            if path.starts_with(lighthouse::VALIDATORS_PATH_PREFIX)
+                && path.ends_with(lighthouse::UPCOMING_DUTIES_PATH_SUFFIX) =>
+        {
+            handler
+                .in_blocking_task(lighthouse::upcoming_duties)
+                .await?
+                .serde_encodings()
+        }
+        (Method::GET, path)
+            if path.starts_with(lighthouse::VALIDATORS_PATH_PREFIX)
+                && path.ends_with(lighthouse::EXIT_STATUS_PATH_SUFFIX) =>
+        {
+            handler
+                .in_blocking_task(lighthouse::exit_status)
+                .await?
+                .serde_encodings()
+        }
+        (Method::GET, path)
+            if path.starts_with(lighthouse::BLOCKS_PATH_PREFIX)
+                && path.ends_with(lighthouse::BLOCK_REWARDS_PATH_SUFFIX) =>
+        {
+            handler
+                .in_blocking_task(lighthouse::block_rewards)
+                .await?
+                .serde_encodings()
+        }
+        (Method::GET, path) if path.starts_with(lighthouse::BLOCKS_PATH_PREFIX) => handler
+            .in_blocking_task(lighthouse::block_proof)
+            .await?
+            .serde_encodings(),
         _ => Err(ApiError::NotFound(
             "Request path and/or method not found.".to_owned(),
         )),
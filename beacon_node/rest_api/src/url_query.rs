@@ -85,6 +85,16 @@ impl<'a> UrlQuery<'a> {
         Ok(queries)
     }
 
+    /// Returns the value of the first occurrence of the `summary` key, or `false` if it is absent.
+    pub fn summary(self) -> Result<bool, ApiError> {
+        self.first_of_opt(&["summary"])
+            .map_or(Ok(false), |(_, value)| {
+                value.parse::<bool>().map_err(|e| {
+                    ApiError::BadRequest(format!("Unable to parse summary {}: {:?}", value, e))
+                })
+            })
+    }
+
     /// Returns the value of the first occurrence of the `epoch` key.
     pub fn epoch(self) -> Result<Epoch, ApiError> {
         self.first_of(&["epoch"])
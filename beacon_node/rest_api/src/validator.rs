@@ -13,8 +13,8 @@ use slog::{error, info, trace, warn, Logger};
 use std::sync::Arc;
 use types::beacon_state::EthSpec;
 use types::{
-    Attestation, AttestationData, BeaconBlock, BeaconState, Epoch, RelativeEpoch, SelectionProof,
-    SignedAggregateAndProof, SignedBeaconBlock, SubnetId,
+    Attestation, AttestationData, BeaconBlock, BeaconState, CommitteeIndex, Epoch, RelativeEpoch,
+    SelectionProof, SignedAggregateAndProof, SignedBeaconBlock, SubnetId,
 };
 
 /// HTTP Handler to retrieve the duties for a set of validators during a particular epoch. This
@@ -26,7 +26,7 @@ pub fn post_validator_duties<T: BeaconChainTypes>(
 ) -> Result<Vec<ValidatorDutyBytes>, ApiError> {
     let body = req.into_body();
 
-    serde_json::from_slice::<ValidatorDutiesRequest>(&body)
+    ctx.parse_json::<ValidatorDutiesRequest>(&body)
         .map_err(|e| {
             ApiError::BadRequest(format!(
                 "Unable to parse JSON into ValidatorDutiesRequest: {:?}",
@@ -50,7 +50,7 @@ pub fn post_validator_subscriptions<T: BeaconChainTypes>(
 ) -> Result<(), ApiError> {
     let body = req.into_body();
 
-    serde_json::from_slice(&body)
+    ctx.parse_json(&body)
         .map_err(|e| {
             ApiError::BadRequest(format!(
                 "Unable to parse JSON into ValidatorSubscriptions: {:?}",
@@ -58,7 +58,20 @@ pub fn post_validator_subscriptions<T: BeaconChainTypes>(
             ))
         })
         .and_then(move |subscriptions: Vec<ValidatorSubscription>| {
-            ctx.network_chan
+            for subscription in &subscriptions {
+                CommitteeIndex::new_checked(
+                    subscription.attestation_committee_index.into(),
+                    &ctx.beacon_chain.spec,
+                )
+                .map_err(|e| {
+                    ApiError::BadRequest(format!(
+                        "Invalid committee index for validator {}: {:?}",
+                        subscription.validator_index, e
+                    ))
+                })?;
+            }
+
+            ctx.network_chan()?
                 .send(NetworkMessage::Subscribe { subscriptions })
                 .map_err(|e| {
                     ApiError::ServerError(format!(
@@ -143,7 +156,7 @@ pub fn get_state_for_epoch<T: BeaconChainTypes>(
 }
 
 /// Helper function to get the duties for some `validator_pubkeys` in some `epoch`.
-fn return_validator_duties<T: BeaconChainTypes>(
+pub(crate) fn return_validator_duties<T: BeaconChainTypes>(
     beacon_chain: &BeaconChain<T>,
     epoch: Epoch,
     validator_pubkeys: Vec<PublicKeyBytes>,
@@ -236,7 +249,7 @@ fn return_validator_duties<T: BeaconChainTypes>(
                     validator_pubkey,
                     validator_index: Some(validator_index as u64),
                     attestation_slot: duties.map(|d| d.slot),
-                    attestation_committee_index: duties.map(|d| d.index),
+                    attestation_committee_index: duties.map(|d| d.index.into()),
                     committee_count_at_slot,
                     attestation_committee_position: duties.map(|d| d.committee_position),
                     block_proposal_slots,
@@ -300,7 +313,7 @@ pub fn publish_beacon_block<T: BeaconChainTypes>(
 ) -> Result<(), ApiError> {
     let body = req.into_body();
 
-    serde_json::from_slice(&body).map_err(|e| {
+    ctx.parse_json(&body).map_err(|e| {
                     ApiError::BadRequest(format!("Unable to parse JSON into SignedBeaconBlock: {:?}", e))
                 })
             .and_then(move |block: SignedBeaconBlock<T::EthSpec>| {
@@ -315,7 +328,7 @@ pub fn publish_beacon_block<T: BeaconChainTypes>(
                             "block_slot" => slot,
                         );
 
-                        publish_beacon_block_to_network::<T>(&ctx.network_chan, block)?;
+                        publish_beacon_block_to_network::<T>(ctx.network_chan()?, block)?;
 
                         // Run the fork choice algorithm and enshrine a new canonical head, if
                         // found.
@@ -385,10 +398,12 @@ pub fn get_new_attestation<T: BeaconChainTypes>(
     let query = UrlQuery::from_request(&req)?;
 
     let slot = query.slot()?;
-    let index = query.committee_index()?;
+    let index =
+        CommitteeIndex::new_checked(query.committee_index()?.into(), &ctx.beacon_chain.spec)
+            .map_err(|e| ApiError::BadRequest(format!("Invalid committee index: {:?}", e)))?;
 
     ctx.beacon_chain
-        .produce_unaggregated_attestation(slot, index)
+        .produce_unaggregated_attestation(slot, index.into())
         .map_err(|e| ApiError::BadRequest(format!("Unable to produce attestation: {:?}", e)))
 }
 
@@ -424,8 +439,9 @@ pub fn publish_attestations<T: BeaconChainTypes>(
     ctx: Arc<Context<T>>,
 ) -> Result<(), ApiError> {
     let bytes = req.into_body();
+    let network_chan = ctx.network_chan()?.clone();
 
-    serde_json::from_slice(&bytes)
+    ctx.parse_json(&bytes)
         .map_err(|e| {
             ApiError::BadRequest(format!(
                 "Unable to deserialize JSON into a list of attestations: {:?}",
@@ -441,7 +457,7 @@ pub fn publish_attestations<T: BeaconChainTypes>(
                     .map(|(i, (attestation, subnet_id))| {
                         process_unaggregated_attestation(
                             &ctx.beacon_chain,
-                            ctx.network_chan.clone(),
+                            network_chan.clone(),
                             attestation,
                             subnet_id,
                             i,
@@ -533,8 +549,9 @@ pub fn publish_aggregate_and_proofs<T: BeaconChainTypes>(
     ctx: Arc<Context<T>>,
 ) -> Result<(), ApiError> {
     let body = req.into_body();
+    let network_chan = ctx.network_chan()?.clone();
 
-    serde_json::from_slice(&body)
+    ctx.parse_json(&body)
         .map_err(|e| {
             ApiError::BadRequest(format!(
                 "Unable to deserialize JSON into a list of SignedAggregateAndProof: {:?}",
@@ -550,7 +567,7 @@ pub fn publish_aggregate_and_proofs<T: BeaconChainTypes>(
                     .map(|(i, signed_aggregate)| {
                         process_aggregated_attestation(
                             &ctx.beacon_chain,
-                            ctx.network_chan.clone(),
+                            network_chan.clone(),
                             signed_aggregate,
                             i,
                             &ctx.log,
@@ -4,14 +4,17 @@
 extern crate assert_matches;
 
 use beacon_chain::{BeaconChain, BeaconChainTypes, StateSkipConfig};
+use futures::{StreamExt, TryStreamExt};
 use node_test_rig::{
     environment::{Environment, EnvironmentBuilder},
     testing_client_config, ClientConfig, ClientGenesis, LocalBeaconNode,
 };
 use remote_beacon_node::{
-    Committee, HeadBeaconBlock, PersistedOperationPool, PublishStatus, ValidatorResponse,
+    Committee, CommitteesQuery, HeadBeaconBlock, PersistedOperationPool, PublishStatus,
+    RemoteBeaconNode, StreamConfig, ValidatorResponse,
 };
-use rest_types::ValidatorDutyBytes;
+use rest_api::MAINTENANCE_CONCURRENT_REQUESTS;
+use rest_types::{BlockSummary, ValidatorDutyBytes};
 use std::convert::TryInto;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -20,9 +23,9 @@ use types::{
         build_double_vote_attester_slashing, build_proposer_slashing,
         generate_deterministic_keypair, AttesterSlashingTestTask, ProposerSlashingTestTask,
     },
-    BeaconBlock, BeaconState, ChainSpec, Domain, Epoch, EthSpec, MinimalEthSpec, PublicKey,
-    RelativeEpoch, Signature, SignedAggregateAndProof, SignedBeaconBlock, SignedRoot, Slot,
-    SubnetId, Validator,
+    BeaconBlock, BeaconState, ChainSpec, CommitteeIndex, Domain, Epoch, EthSpec, MinimalEthSpec,
+    ProposerSlashing, PublicKey, RelativeEpoch, Signature, SignedAggregateAndProof,
+    SignedBeaconBlock, SignedRoot, Slot, SubnetId, Validator, VoluntaryExit, YamlConfig,
 };
 
 type E = MinimalEthSpec;
@@ -44,6 +47,50 @@ fn build_node<E: EthSpec>(env: &mut Environment<E>, config: ClientConfig) -> Loc
         .expect("should block until node created")
 }
 
+/// Fetches `path`, for the Lighthouse-specific endpoints which have no method in
+/// `remote_beacon_node`.
+fn lighthouse_get(
+    env: &mut Environment<E>,
+    remote_node: &RemoteBeaconNode<E>,
+    path: &str,
+) -> Result<serde_json::Value, remote_beacon_node::Error> {
+    let url = url::Url::parse(remote_node.http.server_url())
+        .and_then(|url| url.join(path))
+        .expect("should build url");
+    env.runtime()
+        .block_on(remote_node.http.json_get(url, vec![]))
+}
+
+/// Posts `body` to `path`, returning the status code and body of the response.
+fn lighthouse_post(
+    env: &mut Environment<E>,
+    remote_node: &RemoteBeaconNode<E>,
+    path: &str,
+    body: serde_json::Value,
+) -> (u16, String) {
+    let url = url::Url::parse(remote_node.http.server_url())
+        .and_then(|url| url.join(path))
+        .expect("should build url");
+    env.runtime().block_on(async {
+        let response = remote_node
+            .http
+            .json_post(url, body)
+            .await
+            .expect("should send request");
+        let status = response.status().as_u16();
+        let body = response.text().await.expect("should read response body");
+        (status, body)
+    })
+}
+
+/// Returns the status code of a request which did not succeed.
+fn error_status(error: remote_beacon_node::Error) -> u16 {
+    match error {
+        remote_beacon_node::Error::DidNotSucceed { status, .. } => status.as_u16(),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
 /// Returns the randao reveal for the given slot (assuming the given `beacon_chain` uses
 /// deterministic keypairs).
 fn get_randao_reveal<T: BeaconChainTypes>(
@@ -117,7 +164,7 @@ fn validator_produce_attestation() {
             remote_node
                 .http
                 .validator()
-                .produce_attestation(duties.slot, duties.index),
+                .produce_attestation(duties.slot, duties.index.into()),
         )
         .expect("should fetch attestation from http api");
 
@@ -148,7 +195,7 @@ fn validator_produce_attestation() {
         .expect("should have committee count");
     let subnet_id = SubnetId::compute_subnet::<E>(
         attestation.data.slot,
-        attestation.data.index,
+        attestation.data.index.into(),
         committee_count,
         spec,
     )
@@ -378,7 +425,7 @@ fn check_duties<T: BeaconChainTypes>(
             );
 
             assert_eq!(
-                Some(attestation_duty.index),
+                Some(attestation_duty.index.into()),
                 duty.attestation_committee_index,
                 "attestation index should match"
             );
@@ -460,7 +507,7 @@ fn validator_block_post() {
             remote_node
                 .http
                 .validator()
-                .produce_block(slot, randao_reveal, None),
+                .produce_block(slot, randao_reveal, None, None, None),
         )
         .expect("should fetch block from http api");
 
@@ -543,12 +590,13 @@ fn validator_block_get() {
 
     let block = env
         .runtime()
-        .block_on(
-            remote_node
-                .http
-                .validator()
-                .produce_block(slot, randao_reveal.clone(), None),
-        )
+        .block_on(remote_node.http.validator().produce_block(
+            slot,
+            randao_reveal.clone(),
+            None,
+            None,
+            None,
+        ))
         .expect("should fetch block from http api");
 
     let (expected_block, _state) = node
@@ -587,6 +635,8 @@ fn validator_block_get_with_graffiti() {
             slot,
             randao_reveal.clone(),
             Some(*b"test-graffiti-test-graffiti-test"),
+            None,
+            None,
         ))
         .expect("should fetch block from http api");
 
@@ -770,6 +820,33 @@ fn fork() {
     );
 }
 
+#[test]
+fn fork_schedule() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let schedule = env
+        .runtime()
+        .block_on(remote_node.http.config().get_fork_schedule())
+        .expect("should fetch from http api");
+
+    assert_eq!(
+        schedule,
+        vec![
+            node.client
+                .beacon_chain()
+                .expect("should have beacon chain")
+                .head()
+                .expect("should get head")
+                .beacon_state
+                .fork
+        ],
+        "should only contain the fork of the head state"
+    );
+}
+
 #[test]
 fn eth2_config() {
     let mut env = build_env();
@@ -794,6 +871,33 @@ fn eth2_config() {
     );
 }
 
+#[test]
+fn yaml_config() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let yaml_config = env
+        .runtime()
+        .block_on(remote_node.http.spec().get_yaml_config())
+        .expect("should fetch yaml config from http api");
+
+    let spec = node
+        .client
+        .beacon_chain()
+        .expect("should have beacon chain")
+        .spec
+        .clone();
+
+    assert_eq!(yaml_config, YamlConfig::from_spec::<E>(&spec));
+    assert_eq!(
+        yaml_config.seconds_per_slot(),
+        spec.milliseconds_per_slot / 1000
+    );
+    assert_eq!(yaml_config.slots_per_epoch(), E::slots_per_epoch());
+}
+
 #[test]
 fn get_version() {
     let mut env = build_env();
@@ -921,6 +1025,263 @@ fn get_all_validators() {
         .for_each(|(response, validator)| compare_validator_response(state, response, validator));
 }
 
+#[test]
+fn stream_validators() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+    let state = &chain.head().expect("should get head").beacon_state;
+
+    let pubkeys = state
+        .validators
+        .iter()
+        .map(|v| (&v.pubkey).try_into().expect("should decode pubkey bytes"))
+        .collect::<Vec<PublicKey>>();
+
+    let config = StreamConfig {
+        chunk_size: 3,
+        ..StreamConfig::default()
+    };
+    let result = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .stream_validators(pubkeys, None, config)
+                .try_collect::<Vec<_>>(),
+        )
+        .expect("should fetch from http api");
+
+    assert_eq!(result.len(), state.validators.len());
+    result
+        .iter()
+        .zip(state.validators.iter())
+        .for_each(|(response, validator)| compare_validator_response(state, response, validator));
+}
+
+#[test]
+fn stream_headers_across_chunks() {
+    let mut env = build_env();
+
+    let spec = &E::default_spec();
+
+    let six_slots_secs = (spec.milliseconds_per_slot / 1_000) * 6;
+
+    let mut config = testing_client_config();
+    config.genesis = ClientGenesis::Interop {
+        validator_count: 8,
+        genesis_time: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            - six_slots_secs,
+    };
+
+    let node = build_node(&mut env, config);
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let beacon_chain = node
+        .client
+        .beacon_chain()
+        .expect("client should have beacon chain");
+
+    // Slot 3 is skipped.
+    let block_slots = [1, 2, 4, 5];
+    for slot in block_slots.iter().copied().map(Slot::new) {
+        let randao_reveal = get_randao_reveal(beacon_chain.clone(), slot, spec);
+        let block = env
+            .runtime()
+            .block_on(remote_node.http.validator().produce_block(
+                slot,
+                randao_reveal,
+                None,
+                None,
+                None,
+            ))
+            .expect("should fetch block from http api");
+        let signed_block = sign_block(beacon_chain.clone(), block, spec);
+        env.runtime()
+            .block_on(remote_node.http.validator().publish_block(signed_block))
+            .expect("should publish block");
+    }
+
+    let expected = std::iter::once(0)
+        .chain(block_slots.iter().copied())
+        .map(|slot| {
+            let block = beacon_chain
+                .block_at_slot(Slot::new(slot))
+                .expect("should find block")
+                .expect("block should not be none");
+            (block.message.block_header(), block.canonical_root())
+        })
+        .collect::<Vec<_>>();
+
+    // Two slots are requested at a time, so the range spans several chunks.
+    let config = StreamConfig {
+        chunk_size: 2,
+        ..StreamConfig::default()
+    };
+    let stream_headers = |env: &mut Environment<E>, start: u64, limit: usize| {
+        env.runtime()
+            .block_on(
+                remote_node
+                    .http
+                    .beacon()
+                    .stream_headers(Slot::new(start)..Slot::new(6), config)
+                    .take(limit)
+                    .map_ok(|(header, root)| (header.message, root))
+                    .try_collect::<Vec<_>>(),
+            )
+            .expect("should fetch from http api")
+    };
+
+    assert_eq!(
+        stream_headers(&mut env, 0, usize::max_value()),
+        expected,
+        "skipped slots should be omitted"
+    );
+
+    // Stop part-way through the chunk containing the skipped slot, then resume after the last
+    // header returned.
+    let mut headers = stream_headers(&mut env, 0, 3);
+    let last_slot = headers.last().expect("should return headers").0.slot;
+    assert_eq!(last_slot, Slot::new(2));
+    headers.extend(stream_headers(
+        &mut env,
+        last_slot.as_u64() + 1,
+        usize::max_value(),
+    ));
+    assert_eq!(
+        headers, expected,
+        "the resumed stream should continue in order"
+    );
+}
+
+#[test]
+fn stream_headers() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let headers = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .stream_headers(Slot::new(0)..Slot::new(4), StreamConfig::default())
+                .try_collect::<Vec<_>>(),
+        )
+        .expect("should fetch from http api");
+
+    let db_block = chain
+        .block_at_slot(Slot::new(0))
+        .expect("should find block")
+        .expect("block should not be none");
+
+    assert_eq!(
+        headers.len(),
+        1,
+        "only the genesis block should be returned"
+    );
+    assert_eq!(headers[0].0.message, db_block.message.block_header());
+    assert_eq!(headers[0].1, db_block.canonical_root());
+
+    let summaries = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .get_header_summaries(Epoch::new(0)),
+        )
+        .expect("should fetch from http api");
+
+    assert_eq!(
+        summaries.len(),
+        1,
+        "should return a summary for each header"
+    );
+    assert_eq!(summaries[0].root, db_block.canonical_root());
+    assert_eq!(
+        summaries[0].summary,
+        Some(BlockSummary::from_block(&db_block.message)),
+        "summary should match the block"
+    );
+}
+
+#[test]
+fn get_headers() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let headers = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_headers(Epoch::new(0)))
+        .expect("should fetch from http api");
+
+    let db_block = chain
+        .block_at_slot(Slot::new(0))
+        .expect("should find block")
+        .expect("block should not be none");
+
+    assert_eq!(
+        headers.len(),
+        1,
+        "only the genesis block should be returned"
+    );
+    assert_eq!(headers[0].0.message, db_block.message.block_header());
+    assert_eq!(headers[0].1, db_block.canonical_root());
+
+    let summaries = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .get_header_summaries(Epoch::new(0)),
+        )
+        .expect("should fetch from http api");
+
+    assert_eq!(
+        summaries.len(),
+        1,
+        "should return a summary for each header"
+    );
+    assert_eq!(summaries[0].root, db_block.canonical_root());
+    assert_eq!(
+        summaries[0].summary,
+        Some(BlockSummary::from_block(&db_block.message)),
+        "summary should match the block"
+    );
+
+    let headers = env
+        .runtime()
+        .block_on(remote_node.http.beacon().get_headers(Epoch::new(1)))
+        .expect("should fetch from http api");
+
+    assert!(
+        headers.is_empty(),
+        "no headers should be returned for a future epoch"
+    );
+}
+
 #[test]
 fn get_active_validators() {
     let mut env = build_env();
@@ -984,7 +1345,7 @@ fn get_committees() {
         .iter()
         .map(|c| Committee {
             slot: c.slot,
-            index: c.index,
+            index: c.index.into(),
             committee: c.committee.to_vec(),
         })
         .collect::<Vec<_>>();
@@ -992,6 +1353,62 @@ fn get_committees() {
     assert_eq!(result, expected, "result should be as expected");
 }
 
+#[test]
+fn query_committees() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+
+    let slot = Slot::new(1);
+    let index = CommitteeIndex::new(0);
+
+    let result = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .query_committees(CommitteesQuery::default().slot(slot).index(index)),
+        )
+        .expect("should fetch from http api");
+
+    let committee = chain
+        .head()
+        .expect("should get head")
+        .beacon_state
+        .get_beacon_committee(slot, *index)
+        .expect("should get committee")
+        .committee
+        .to_vec();
+
+    assert_eq!(
+        result,
+        vec![Committee {
+            slot,
+            index,
+            committee
+        }],
+        "result should only contain the requested committee"
+    );
+
+    let result = env.runtime().block_on(
+        remote_node
+            .http
+            .beacon()
+            .query_committees(CommitteesQuery::default().epoch(Epoch::new(1)).slot(slot)),
+    );
+
+    assert!(
+        result.is_err(),
+        "should not return committees for a slot outside of the epoch"
+    );
+}
+
 #[test]
 fn get_fork_choice() {
     let mut env = build_env();
@@ -1281,7 +1698,7 @@ mod validator_attestation {
 
         assert_matches!(
             attestation.expect_err("should not succeed"),
-            DidNotSucceed { status, body } => {
+            DidNotSucceed { status, body, .. } => {
                 assert_eq!(status, StatusCode::BAD_REQUEST);
                 assert_eq!(body, "URL query must be valid and contain at least one of the following keys: [\"slot\"]".to_owned());
             }
@@ -1302,7 +1719,7 @@ mod validator_attestation {
 
         assert_matches!(
             attestation.expect_err("should not succeed"),
-            DidNotSucceed { status, body } => {
+            DidNotSucceed { status, body, .. } => {
                 assert_eq!(status, StatusCode::BAD_REQUEST);
                 assert_eq!(body, "URL query must be valid and contain at least one of the following keys: [\"slot\"]".to_owned());
             }
@@ -1323,7 +1740,7 @@ mod validator_attestation {
 
         assert_matches!(
             attestation.expect_err("should not succeed"),
-            DidNotSucceed { status, body } => {
+            DidNotSucceed { status, body, .. } => {
                 assert_eq!(status, StatusCode::BAD_REQUEST);
                 assert_eq!(body, "URL query must be valid and contain at least one of the following keys: [\"committee_index\"]".to_owned());
             }
@@ -1343,3 +1760,468 @@ fn get_health() {
         .block_on(remote_node.http.node().get_health())
         .unwrap();
 }
+
+#[test]
+fn get_capabilities() {
+    let mut env = build_env();
+
+    let mut config = testing_client_config();
+    config.rest_api.reloadable.disabled_routes =
+        vec!["/advanced/".to_string(), "/lighthouse/peers".to_string()];
+    let node = build_node(&mut env, config);
+    let remote_node = node.remote_node().expect("should produce remote node");
+
+    let capabilities = env
+        .runtime()
+        .block_on(remote_node.http.node().get_capabilities())
+        .expect("should fetch capabilities");
+
+    let enabled = |path: &str| {
+        capabilities
+            .namespaces
+            .iter()
+            .find(|namespace| namespace.path == path)
+            .expect("namespace should be listed")
+            .enabled
+    };
+    assert!(enabled("/beacon/"));
+    assert!(!enabled("/advanced/"));
+    // Only part of the namespace is disabled.
+    assert!(enabled("/lighthouse/"));
+    assert_eq!(
+        capabilities.disabled_routes,
+        vec!["/advanced/".to_string(), "/lighthouse/peers".to_string()]
+    );
+    assert!(capabilities.network);
+    assert!(!capabilities.config_reload);
+}
+
+#[test]
+fn queries_during_store_maintenance() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("client should have beacon chain");
+
+    let syncing = env
+        .runtime()
+        .block_on(remote_node.http.node().syncing_status())
+        .expect("should fetch syncing status");
+    assert!(!syncing.store_maintenance);
+
+    // Simulate a long-running migration.
+    let maintenance = chain.store.begin_maintenance();
+
+    let syncing = env
+        .runtime()
+        .block_on(remote_node.http.node().syncing_status())
+        .expect("should fetch syncing status during maintenance");
+    assert!(syncing.store_maintenance);
+
+    // Issue more concurrent queries than may be served during maintenance, all of which should be
+    // queued and eventually served.
+    let queries = (0..MAINTENANCE_CONCURRENT_REQUESTS * 2)
+        .map(|_| remote_node.http.beacon().get_block_by_slot(Slot::new(0)));
+    let blocks = env
+        .runtime()
+        .block_on(futures::future::try_join_all(queries))
+        .expect("should fetch blocks during maintenance");
+    assert!(blocks
+        .iter()
+        .all(|(block, _)| block.message.slot == Slot::new(0)));
+
+    drop(maintenance);
+
+    let syncing = env
+        .runtime()
+        .block_on(remote_node.http.node().syncing_status())
+        .expect("should fetch syncing status");
+    assert!(!syncing.store_maintenance);
+}
+
+#[test]
+fn upcoming_duties() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let beacon_chain = node
+        .client
+        .beacon_chain()
+        .expect("client should have beacon chain");
+    let current_epoch = beacon_chain.epoch().expect("should get current epoch");
+
+    let by_index = lighthouse_get(
+        &mut env,
+        &remote_node,
+        "/lighthouse/validators/3/upcoming_duties",
+    )
+    .expect("should fetch duties by index");
+    assert_eq!(by_index["validator_index"], 3);
+
+    let pubkey = by_index["validator_pubkey"]
+        .as_str()
+        .expect("pubkey should be a string")
+        .to_string();
+    let by_pubkey = lighthouse_get(
+        &mut env,
+        &remote_node,
+        &format!("/lighthouse/validators/{}/upcoming_duties", pubkey),
+    )
+    .expect("should fetch duties by pubkey");
+    assert_eq!(by_pubkey, by_index, "both ids should give the same duties");
+
+    // At genesis, no duty of the current epoch has passed.
+    let duty = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .validator()
+                .get_duties(current_epoch, &[generate_deterministic_keypair(3).pk]),
+        )
+        .expect("should fetch duties from http api")
+        .pop()
+        .expect("should have a duty");
+    assert_eq!(
+        by_index["attestation_slot"],
+        serde_json::to_value(duty.attestation_slot).unwrap()
+    );
+    assert_eq!(
+        by_index["attestation_committee_index"],
+        serde_json::to_value(duty.attestation_committee_index).unwrap()
+    );
+    assert_eq!(
+        by_index["block_proposal_slots"],
+        serde_json::to_value(duty.block_proposal_slots.unwrap_or_default()).unwrap()
+    );
+
+    let error = lighthouse_get(
+        &mut env,
+        &remote_node,
+        "/lighthouse/validators/1000000/upcoming_duties",
+    )
+    .expect_err("should not find an unknown validator");
+    assert_eq!(error_status(error), 404);
+
+    let error = lighthouse_get(
+        &mut env,
+        &remote_node,
+        "/lighthouse/validators/0x/upcoming_duties",
+    )
+    .expect_err("should not parse an invalid pubkey");
+    assert_eq!(error_status(error), 400);
+}
+
+#[test]
+fn duties_stream_tokens() {
+    use remote_beacon_node::Error::{self, DidNotSucceed};
+
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let validator = remote_node.http.validator();
+    let pubkeys = vec![generate_deterministic_keypair(0).pk];
+
+    let token = env
+        .runtime()
+        .block_on(validator.register_duties_stream(None, &pubkeys))
+        .expect("should issue token");
+    let other_token = env
+        .runtime()
+        .block_on(validator.register_duties_stream(None, &pubkeys))
+        .expect("should issue another token");
+    assert_ne!(token, other_token, "tokens should be unique");
+
+    let replaced = env
+        .runtime()
+        .block_on(validator.register_duties_stream(Some(&token), &[]))
+        .expect("should replace validators");
+    assert_eq!(replaced, token, "should keep the same token");
+
+    let status_of = |error: Error| match error {
+        DidNotSucceed { status, .. } => status.as_u16(),
+        other => panic!("unexpected error: {:?}", other),
+    };
+
+    let error = env
+        .runtime()
+        .block_on(validator.register_duties_stream(Some("unknown"), &pubkeys))
+        .expect_err("should not register against an unknown token");
+    assert_eq!(status_of(error), 401);
+
+    let error = env
+        .runtime()
+        .block_on(validator.stream_duties("unknown"))
+        .err()
+        .expect("should not open the stream of an unknown token");
+    assert_eq!(status_of(error), 401);
+
+    let stream = env
+        .runtime()
+        .block_on(validator.stream_duties(&token))
+        .expect("should open the stream");
+
+    let error = env
+        .runtime()
+        .block_on(validator.stream_duties(&token))
+        .err()
+        .expect("should not open the same stream twice");
+    assert_eq!(status_of(error), 400);
+
+    drop(stream);
+}
+
+#[test]
+fn import_operation_pool() {
+    let mut env = build_env();
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+    let state = chain
+        .head()
+        .expect("should have retrieved state")
+        .beacon_state;
+
+    let proposer_index = chain
+        .block_proposer(state.slot)
+        .expect("should get proposer index");
+    let keypair = generate_deterministic_keypair(proposer_index);
+    let build_slashing = || {
+        build_proposer_slashing::<E>(
+            ProposerSlashingTestTask::Valid,
+            proposer_index as u64,
+            &keypair.sk,
+            &state.fork,
+            state.genesis_validators_root,
+            &chain.spec,
+        )
+    };
+    let valid_slashing = build_slashing();
+    let mut invalid_slashing = build_slashing();
+    invalid_slashing.signed_header_2 = invalid_slashing.signed_header_1.clone();
+
+    // A snapshot in the format of `/advanced/operation_pool`.
+    let snapshot = |proposer_slashings: Vec<&ProposerSlashing>| {
+        serde_json::json!({
+            "attestations": [],
+            "attester_slashings": [],
+            "proposer_slashings": proposer_slashings,
+            "voluntary_exits": [],
+        })
+    };
+    let import = |env: &mut Environment<E>, body| {
+        let (status, body) = lighthouse_post(env, &remote_node, "/lighthouse/pool/import", body);
+        assert_eq!(status, 200, "should import pool: {}", body);
+        serde_json::from_str::<serde_json::Value>(&body).expect("should parse report")
+    };
+
+    let report = import(&mut env, snapshot(vec![&valid_slashing, &invalid_slashing]));
+    assert_eq!(
+        report["proposer_slashings"],
+        serde_json::json!({"imported": 1, "already_known": 0, "rejected": 1})
+    );
+    assert_eq!(
+        report["attestations"],
+        serde_json::json!({"imported": 0, "already_known": 0, "rejected": 0})
+    );
+    let (proposer_slashings, _) = chain.op_pool.get_slashings(&state);
+    assert_eq!(proposer_slashings, vec![valid_slashing.clone()]);
+
+    let report = import(&mut env, snapshot(vec![&valid_slashing]));
+    assert_eq!(
+        report["proposer_slashings"],
+        serde_json::json!({"imported": 0, "already_known": 1, "rejected": 0})
+    );
+
+    let (status, _) = lighthouse_post(
+        &mut env,
+        &remote_node,
+        "/lighthouse/pool/import",
+        serde_json::json!("not a pool"),
+    );
+    assert_eq!(status, 400, "should not import an invalid snapshot");
+}
+
+#[test]
+fn voluntary_exit() {
+    let mut env = build_env();
+    // Allow validators to exit in the genesis epoch.
+    env.eth2_config.spec.shard_committee_period = 0;
+
+    let node = build_node(&mut env, testing_client_config());
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let chain = node
+        .client
+        .beacon_chain()
+        .expect("node should have beacon chain");
+    let state = chain
+        .head()
+        .expect("should have retrieved state")
+        .beacon_state;
+
+    let sign_exit = |validator_index: u64, signer: usize| {
+        VoluntaryExit {
+            epoch: Epoch::new(0),
+            validator_index,
+        }
+        .sign(
+            &generate_deterministic_keypair(signer).sk,
+            &state.fork,
+            state.genesis_validators_root,
+            &chain.spec,
+        )
+    };
+    let exit_stage = |env: &mut Environment<E>, validator_index: u64| {
+        lighthouse_get(
+            env,
+            &remote_node,
+            &format!("/lighthouse/validators/{}/exit_status", validator_index),
+        )
+        .expect("should fetch exit status")["stage"]
+            .clone()
+    };
+
+    assert_eq!(exit_stage(&mut env, 1), "not_exiting");
+
+    let exit = sign_exit(1, 1);
+    let result = env
+        .runtime()
+        .block_on(remote_node.http.beacon().voluntary_exit(exit.clone()))
+        .expect("should publish exit");
+    assert!(result);
+    assert_eq!(chain.op_pool.get_voluntary_exit(1), Some(exit.clone()));
+    assert_eq!(exit_stage(&mut env, 1), "exit_in_pool");
+
+    let error = env
+        .runtime()
+        .block_on(remote_node.http.beacon().voluntary_exit(exit))
+        .expect_err("should not publish a known exit");
+    assert_eq!(error_status(error), 400);
+
+    // An exit signed by another validator is invalid.
+    let error = env
+        .runtime()
+        .block_on(remote_node.http.beacon().voluntary_exit(sign_exit(2, 3)))
+        .expect_err("should not publish an invalid exit");
+    assert_eq!(error_status(error), 400);
+    assert_eq!(chain.op_pool.get_voluntary_exit(2), None);
+    assert_eq!(exit_stage(&mut env, 2), "not_exiting");
+}
+
+#[test]
+fn api_only_requires_existing_database() {
+    let mut env = build_env();
+
+    let mut config = testing_client_config();
+    config.api_only = true;
+
+    let context = env.core_context();
+    let result = env
+        .runtime()
+        .block_on(LocalBeaconNode::production(context, config));
+
+    assert!(
+        result.is_err(),
+        "should not start in API-only mode without a database"
+    );
+}
+
+#[test]
+fn block_rewards() {
+    let mut env = build_env();
+
+    let spec = &E::default_spec();
+
+    let two_slots_secs = (spec.milliseconds_per_slot / 1_000) * 2;
+
+    let mut config = testing_client_config();
+    config.genesis = ClientGenesis::Interop {
+        validator_count: 8,
+        genesis_time: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            - two_slots_secs,
+    };
+
+    let node = build_node(&mut env, config);
+    let remote_node = node.remote_node().expect("should produce remote node");
+    let beacon_chain = node
+        .client
+        .beacon_chain()
+        .expect("client should have beacon chain");
+    let state = beacon_chain.head().expect("should get head").beacon_state;
+
+    let slot = Slot::new(1);
+    let proposer_index = beacon_chain
+        .block_proposer(slot)
+        .expect("should get proposer index");
+
+    // Include the slashing of another validator in the block, so that its proposer is rewarded as
+    // the whistleblower.
+    let slashed_index = (proposer_index + 1) % 8;
+    let proposer_slashing = build_proposer_slashing::<E>(
+        ProposerSlashingTestTask::Valid,
+        slashed_index as u64,
+        &generate_deterministic_keypair(slashed_index).sk,
+        &state.fork,
+        state.genesis_validators_root,
+        spec,
+    );
+    env.runtime()
+        .block_on(
+            remote_node
+                .http
+                .beacon()
+                .proposer_slashing(proposer_slashing),
+        )
+        .expect("should publish proposer slashing");
+
+    let randao_reveal = get_randao_reveal(beacon_chain.clone(), slot, spec);
+    let block = env
+        .runtime()
+        .block_on(
+            remote_node
+                .http
+                .validator()
+                .produce_block(slot, randao_reveal, None, None, None),
+        )
+        .expect("should fetch block from http api");
+    assert_eq!(block.body.proposer_slashings.len(), 1);
+    let signed_block = sign_block(beacon_chain.clone(), block, spec);
+    let block_root = signed_block.canonical_root();
+    env.runtime()
+        .block_on(remote_node.http.validator().publish_block(signed_block))
+        .expect("should publish block");
+
+    let by_slot = lighthouse_get(&mut env, &remote_node, "/lighthouse/blocks/1/rewards")
+        .expect("should fetch rewards by slot");
+    let by_root = lighthouse_get(
+        &mut env,
+        &remote_node,
+        &format!("/lighthouse/blocks/{:?}/rewards", block_root),
+    )
+    .expect("should fetch rewards by root");
+    assert_eq!(by_slot, by_root, "both ids should give the same rewards");
+
+    let slashing_rewards =
+        state.validators[slashed_index].effective_balance / spec.whistleblower_reward_quotient;
+    assert_eq!(by_slot["proposer_index"], proposer_index as u64);
+    assert_eq!(by_slot["attestation_inclusion_rewards"], 0);
+    assert_eq!(by_slot["slashing_rewards"], slashing_rewards);
+    assert_eq!(by_slot["total"], slashing_rewards);
+
+    let error = lighthouse_get(&mut env, &remote_node, "/lighthouse/blocks/0/rewards")
+        .expect_err("should not report rewards for the genesis block");
+    assert_eq!(error_status(error), 400);
+}
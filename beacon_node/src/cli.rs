@@ -67,6 +67,15 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .default_value("50")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("ban-duration")
+                .long("ban-duration")
+                .value_name("SECONDS")
+                .help("The number of seconds a misbehaving peer is banned for before its score \
+                       begins to recover.")
+                .default_value("1800")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("boot-nodes")
                 .long("boot-nodes")
@@ -125,12 +134,54 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        without an ENR.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("trusted-peers")
+                .long("trusted-peers")
+                .value_name("MULTIADDR")
+                .help("One or more comma-delimited multiaddrs of peers which are always dialed and \
+                       are never banned or disconnected for their score or to make room for other \
+                       peers. Each multiaddr must end with the /p2p id of the peer.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("allowed-ips")
+                .long("allowed-ips")
+                .value_name("CIDR")
+                .help("One or more comma-delimited IP addresses or CIDR ranges, e.g. 10.0.0.0/8. \
+                       If set, libp2p connections are only made with addresses in these ranges.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("denied-ips")
+                .long("denied-ips")
+                .value_name("CIDR")
+                .help("One or more comma-delimited IP addresses or CIDR ranges, e.g. 10.0.0.0/8. \
+                       Dials to and connections from addresses in these ranges are rejected.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("max-connections-per-ip")
+                .long("max-connections-per-ip")
+                .value_name("NUM")
+                .help("The maximum number of libp2p connections with each IP address. Unlimited \
+                       by default.")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("disable-discovery")
                 .long("disable-discovery")
                 .help("Disables the discv5 discovery protocol. The node will not search for new peers or participate in the discovery protocol.")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("upnp")
+                .long("upnp")
+                .help("Attempts to map the TCP and UDP listening ports on the local gateway with \
+                       UPnP, or NAT-PMP if no UPnP gateway responds, so that the node can be \
+                       reached by peers from behind a NAT. Unless the ENR address or ports are \
+                       set, the ENR is updated with the external address of the gateway.")
+                .takes_value(false),
+        )
 
         /* REST API related arguments */
         .arg(
@@ -163,6 +214,34 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .default_value("")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("http-lenient-json-numbers")
+                .long("http-lenient-json-numbers")
+                .help("Accept integers in the JSON bodies of HTTP API requests with or without \
+                       quotes, for third-party clients which send integers in the other form. \
+                       Responses are unchanged.")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("http-config-file")
+                .long("http-config-file")
+                .value_name("PATH")
+                .help("A YAML file containing the HTTP API settings which may be changed whilst \
+                       the node is running (allow_origin, disabled_routes, log_requests and \
+                       lenient_json_numbers). The file is read again when the node receives a \
+                       SIGHUP or a POST request to /lighthouse/http_config/reload. Replaces \
+                       --http-allow-origin and --http-lenient-json-numbers.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("api-only")
+                .long("api-only")
+                .help("Serve the HTTP API from an existing database without connecting to the \
+                       network. No blocks are downloaded, so the head does not advance, and \
+                       endpoints which require the network return 503. Implies --http. Cannot \
+                       be used with an eth1 backend.")
+                .takes_value(false),
+        )
         /* Websocket related arguments */
         .arg(
             Arg::with_name("ws")
@@ -262,4 +341,52 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .takes_value(true)
                 .default_value("700")
         )
+        .arg(
+            Arg::with_name("attestation-deadline")
+                .long("attestation-deadline")
+                .help(
+                    "The time into a slot after which validators are expected to have attested. \
+                    Blocks imported after this deadline are recorded as late, see \
+                    /lighthouse/late_blocks. Defaults to one third of the slot duration."
+                )
+                .value_name("MILLISECONDS")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("exclude-slashings")
+                .long("exclude-slashings")
+                .help("Do not include proposer or attester slashings in produced blocks.")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("exclude-exits")
+                .long("exclude-exits")
+                .help("Do not include voluntary exits in produced blocks.")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("packing-priority")
+                .long("packing-priority")
+                .help(
+                    "Decides which operation is included in a produced block when a validator \
+                    could be both slashed and exited by it, since a block can't do both. The \
+                    operations which would currently be included can be inspected at \
+                    /lighthouse/block_packing_preview."
+                )
+                .value_name("OPERATION")
+                .possible_values(&["slashings", "exits"])
+                .default_value("slashings")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("invariant-checker")
+                .long("invariant-checker")
+                .help(
+                    "Once per epoch, check that the head is known to fork choice, that the \
+                    finalized state is in the database and that the operation pool only \
+                    references known blocks. Violations are logged as critical errors. Intended \
+                    for development and devnets, since each check reads a state from disk."
+                )
+                .takes_value(false)
+        )
 }
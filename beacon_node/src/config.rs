@@ -2,7 +2,7 @@ use beacon_chain::builder::PUBKEY_CACHE_FILENAME;
 use clap::ArgMatches;
 use clap_utils::BAD_TESTNET_DIR_MESSAGE;
 use client::{config::DEFAULT_DATADIR, ClientConfig, ClientGenesis};
-use eth2_libp2p::{multiaddr::Protocol, Enr, Multiaddr, NetworkConfig};
+use eth2_libp2p::{multiaddr::Protocol, Enr, IpRange, Multiaddr, NetworkConfig};
 use eth2_testnet_config::Eth2TestnetConfig;
 use slog::{crit, info, Logger};
 use ssz::Encode;
@@ -109,7 +109,17 @@ pub fn get_config<E: EthSpec>(
         hyper::header::HeaderValue::from_str(allow_origin)
             .map_err(|_| "Invalid allow-origin value")?;
 
-        client_config.rest_api.allow_origin = allow_origin.to_string();
+        client_config.rest_api.reloadable.allow_origin = allow_origin.to_string();
+    }
+
+    if cli_args.is_present("http-lenient-json-numbers") {
+        client_config.rest_api.reloadable.lenient_json_numbers = true;
+    }
+
+    if let Some(config_file) = cli_args.value_of("http-config-file") {
+        let config_file = PathBuf::from(config_file);
+        client_config.rest_api.reloadable = rest_api::ReloadableConfig::from_file(&config_file)?;
+        client_config.rest_api.config_file = Some(config_file);
     }
 
     /*
@@ -261,6 +271,46 @@ pub fn get_config<E: EthSpec>(
         };
     }
 
+    if let Some(attestation_deadline) = cli_args.value_of("attestation-deadline") {
+        client_config.chain.attestation_deadline_ms = Some(
+            attestation_deadline
+                .parse()
+                .map_err(|_| "Invalid attestation-deadline".to_string())?,
+        );
+    }
+
+    if cli_args.is_present("exclude-slashings") {
+        client_config.chain.block_packing.include_slashings = false;
+    }
+
+    if cli_args.is_present("exclude-exits") {
+        client_config.chain.block_packing.include_exits = false;
+    }
+
+    if let Some(priority) = cli_args.value_of("packing-priority") {
+        client_config.chain.block_packing.priority = priority.parse()?;
+    }
+
+    if cli_args.is_present("invariant-checker") {
+        client_config.invariant_checker = true;
+    }
+
+    /*
+     * API-only mode
+     *
+     * Replaces the genesis and http flags, since the node can only resume from its database and
+     * has no other purpose than to serve the HTTP API.
+     */
+    if cli_args.is_present("api-only") {
+        if client_config.sync_eth1_chain || client_config.dummy_eth1_backend {
+            return Err("--api-only cannot be used with an eth1 backend".into());
+        }
+
+        client_config.api_only = true;
+        client_config.rest_api.enabled = true;
+        client_config.genesis = ClientGenesis::FromStore;
+    }
+
     Ok(client_config)
 }
 
@@ -292,6 +342,12 @@ pub fn set_network_config(
             .map_err(|_| format!("Invalid number of target peers: {}", target_peers_str))?;
     }
 
+    if let Some(ban_duration_str) = cli_args.value_of("ban-duration") {
+        config.ban_duration_secs = ban_duration_str
+            .parse::<u64>()
+            .map_err(|_| format!("Invalid ban duration: {}", ban_duration_str))?;
+    }
+
     if let Some(port_str) = cli_args.value_of("port") {
         let port = port_str
             .parse::<u16>()
@@ -343,6 +399,46 @@ pub fn set_network_config(
             .collect::<Result<Vec<Multiaddr>, _>>()?;
     }
 
+    if let Some(trusted_peers_str) = cli_args.value_of("trusted-peers") {
+        config.trusted_peers = trusted_peers_str
+            .split(',')
+            .map(|multiaddr| {
+                let multiaddr: Multiaddr = multiaddr
+                    .parse()
+                    .map_err(|_| format!("Invalid Multiaddr: {}", multiaddr))?;
+                if !matches!(multiaddr.iter().last(), Some(Protocol::P2p(_))) {
+                    return Err(format!(
+                        "Trusted peer is missing its /p2p id: {}",
+                        multiaddr
+                    ));
+                }
+                Ok(multiaddr)
+            })
+            .collect::<Result<Vec<Multiaddr>, _>>()?;
+    }
+
+    if let Some(allowed_ips_str) = cli_args.value_of("allowed-ips") {
+        config.allowed_ips = allowed_ips_str
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<IpRange>, _>>()?;
+    }
+
+    if let Some(denied_ips_str) = cli_args.value_of("denied-ips") {
+        config.denied_ips = denied_ips_str
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<IpRange>, _>>()?;
+    }
+
+    if let Some(max_connections_str) = cli_args.value_of("max-connections-per-ip") {
+        config.max_connections_per_ip = Some(
+            max_connections_str
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid connections per IP: {}", max_connections_str))?,
+        );
+    }
+
     if let Some(enr_udp_port_str) = cli_args.value_of("enr-udp-port") {
         config.enr_udp_port = Some(
             enr_udp_port_str
@@ -418,6 +514,10 @@ pub fn set_network_config(
         slog::warn!(log, "Discovery is disabled. New peers will not be found");
     }
 
+    if cli_args.is_present("upnp") {
+        config.upnp_enabled = true;
+    }
+
     Ok(())
 }
 
@@ -123,14 +123,27 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
             .system_time_slot_clock()?
             .tee_event_handler(client_config.websocket_server.clone())?;
 
-        // Inject the executor into the discv5 network config.
-        client_config.network.discv5_config.executor = Some(Box::new(executor));
+        let builder = builder.build_beacon_chain()?;
 
-        let builder = builder
-            .build_beacon_chain()?
-            .network(&client_config.network)
-            .await?
-            .notifier()?;
+        let builder = if client_config.api_only {
+            info!(
+                log,
+                "Network disabled";
+                "reason" => "the node is in API-only mode"
+            );
+            builder
+        } else {
+            // Inject the executor into the discv5 network config.
+            client_config.network.discv5_config.executor = Some(Box::new(executor));
+
+            builder.network(&client_config.network).await?.notifier()?
+        };
+
+        let builder = if client_config.invariant_checker {
+            builder.invariant_checker()?
+        } else {
+            builder
+        };
 
         let builder = if client_config.rest_api.enabled {
             builder.http_server(&client_config, &http_eth2_config, events)?
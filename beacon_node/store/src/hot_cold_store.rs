@@ -4,7 +4,7 @@ use crate::chunked_vector::{
 use crate::config::StoreConfig;
 use crate::forwards_iter::HybridForwardsBlockRootsIterator;
 use crate::impls::beacon_state::{get_full_state, store_full_state};
-use crate::iter::{ParentRootBlockIterator, StateRootsIterator};
+use crate::iter::{BlockRootsIterator, ParentRootBlockIterator, StateRootsIterator};
 use crate::leveldb_store::LevelDB;
 use crate::memory_store::MemoryStore;
 use crate::metrics;
@@ -24,6 +24,7 @@ use state_processing::{
 use std::convert::TryInto;
 use std::marker::PhantomData;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use types::*;
 
@@ -60,6 +61,8 @@ pub struct HotColdDB<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> {
     pub(crate) hot_db: Hot,
     /// LRU cache of deserialized blocks. Updated whenever a block is loaded.
     block_cache: Mutex<LruCache<Hash256, SignedBeaconBlock<E>>>,
+    /// The number of maintenance tasks (e.g., freezer migrations) currently running.
+    maintenance_tasks: AtomicUsize,
     /// Chain spec.
     spec: ChainSpec,
     /// Logger.
@@ -97,6 +100,20 @@ pub enum HotColdDBError {
     RestorePointBlockHashError(BeaconStateError),
 }
 
+/// Marks a maintenance task as running for as long as it is held.
+///
+/// See `HotColdDB::begin_maintenance`.
+#[must_use = "maintenance ends when the guard is dropped"]
+pub struct MaintenanceGuard<'a> {
+    maintenance_tasks: &'a AtomicUsize,
+}
+
+impl<'a> Drop for MaintenanceGuard<'a> {
+    fn drop(&mut self) {
+        self.maintenance_tasks.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
 impl<E: EthSpec> HotColdDB<E, MemoryStore<E>, MemoryStore<E>> {
     pub fn open_ephemeral(
         config: StoreConfig,
@@ -110,6 +127,7 @@ impl<E: EthSpec> HotColdDB<E, MemoryStore<E>, MemoryStore<E>> {
             cold_db: MemoryStore::open(),
             hot_db: MemoryStore::open(),
             block_cache: Mutex::new(LruCache::new(config.block_cache_size)),
+            maintenance_tasks: AtomicUsize::new(0),
             config,
             spec,
             log,
@@ -138,6 +156,7 @@ impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
             cold_db: LevelDB::open(cold_path)?,
             hot_db: LevelDB::open(hot_path)?,
             block_cache: Mutex::new(LruCache::new(config.block_cache_size)),
+            maintenance_tasks: AtomicUsize::new(0),
             config,
             spec,
             log,
@@ -160,6 +179,32 @@ impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
 }
 
 impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold> {
+    /// Mark the start of a long-running maintenance task, which ends when the returned guard is
+    /// dropped.
+    ///
+    /// The database remains fully usable during maintenance, but reads may be slow. Users such as
+    /// the HTTP API may use `maintenance_in_progress` to report this, or to reduce their load.
+    pub fn begin_maintenance(&self) -> MaintenanceGuard<'_> {
+        self.maintenance_tasks.fetch_add(1, Ordering::SeqCst);
+        MaintenanceGuard {
+            maintenance_tasks: &self.maintenance_tasks,
+        }
+    }
+
+    /// Returns `true` if any maintenance task is currently running.
+    pub fn maintenance_in_progress(&self) -> bool {
+        self.maintenance_tasks.load(Ordering::SeqCst) > 0
+    }
+
+    /// Fetch the slot of the block or state with the given `root`, and whether it is known to be
+    /// canonical, without loading it.
+    ///
+    /// Returns `None` for unknown roots, and for blocks and states stored before the index was
+    /// introduced.
+    pub fn get_root_index_entry(&self, root: &Hash256) -> Result<Option<RootIndexEntry>, Error> {
+        self.hot_db.get(root)
+    }
+
     /// Store a block and update the LRU cache.
     pub fn put_block(
         &self,
@@ -168,6 +213,8 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     ) -> Result<(), Error> {
         // Store on disk.
         self.hot_db.put(block_root, &block)?;
+        self.hot_db
+            .put(block_root, &RootIndexEntry::new(block.message.slot))?;
 
         // Update cache.
         self.block_cache.lock().put(*block_root, block);
@@ -215,7 +262,14 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         if state.slot < self.get_split_slot() {
             let mut ops: Vec<KeyValueStoreOp> = Vec::new();
             self.store_cold_state(state_root, &state, &mut ops)?;
-            self.cold_db.do_atomically(ops)
+            self.cold_db.do_atomically(ops)?;
+
+            // Only finalized states are stored in the freezer.
+            let index_entry = RootIndexEntry {
+                slot: state.slot,
+                canonical: true,
+            };
+            self.hot_db.put(state_root, &index_entry)
         } else {
             let mut ops: Vec<KeyValueStoreOp> = Vec::new();
             self.store_hot_state(state_root, state, &mut ops)?;
@@ -366,6 +420,8 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
                 StoreOp::PutBlock(block_hash, block) => {
                     let untyped_hash: Hash256 = (*block_hash).into();
                     key_value_batch.push(block.as_kv_store_op(untyped_hash));
+                    key_value_batch
+                        .push(RootIndexEntry::new(block.message.slot).as_kv_store_op(untyped_hash));
                 }
 
                 StoreOp::PutState(state_hash, state) => {
@@ -376,6 +432,12 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
                 StoreOp::PutStateSummary(state_hash, summary) => {
                     let untyped_hash: Hash256 = (*state_hash).into();
                     key_value_batch.push(summary.as_kv_store_op(untyped_hash));
+                    key_value_batch
+                        .push(RootIndexEntry::new(summary.slot).as_kv_store_op(untyped_hash));
+                }
+
+                StoreOp::PutRootIndexEntry(root, index_entry) => {
+                    key_value_batch.push(index_entry.as_kv_store_op(*root));
                 }
 
                 StoreOp::DeleteBlock(block_hash) => {
@@ -383,6 +445,7 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
                     let key =
                         get_key_for_col(DBColumn::BeaconBlock.into(), untyped_hash.as_bytes());
                     key_value_batch.push(KeyValueStoreOp::DeleteKey(key));
+                    key_value_batch.push(delete_root_index_entry_op(untyped_hash));
                 }
 
                 StoreOp::DeleteState(state_hash, slot) => {
@@ -392,6 +455,7 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
                         untyped_hash.as_bytes(),
                     );
                     key_value_batch.push(KeyValueStoreOp::DeleteKey(state_summary_key));
+                    key_value_batch.push(delete_root_index_entry_op(untyped_hash));
 
                     if *slot % E::slots_per_epoch() == 0 {
                         let state_key =
@@ -414,6 +478,8 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
 
                 StoreOp::PutStateSummary(_, _) => (),
 
+                StoreOp::PutRootIndexEntry(_, _) => (),
+
                 StoreOp::DeleteBlock(block_hash) => {
                     let untyped_hash: Hash256 = (*block_hash).into();
                     guard.pop(&untyped_hash);
@@ -451,6 +517,7 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         let hot_state_summary = HotStateSummary::new(state_root, state)?;
         let op = hot_state_summary.as_kv_store_op(*state_root);
         ops.push(op);
+        ops.push(RootIndexEntry::new(state.slot).as_kv_store_op(*state_root));
 
         Ok(())
     }
@@ -830,6 +897,8 @@ pub fn migrate_database<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
     frozen_head_root: Hash256,
     frozen_head: &BeaconState<E>,
 ) -> Result<(), Error> {
+    let _maintenance = store.begin_maintenance();
+
     debug!(
         store.log,
         "Freezer migration started";
@@ -885,6 +954,40 @@ pub fn migrate_database<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
 
         // Delete the old summary, and the full state if we lie on an epoch boundary.
         hot_db_ops.push(StoreOp::DeleteState(state_root.into(), slot));
+
+        // The state is finalized, and remains indexed. This must follow the `DeleteState`, which
+        // removes the index entry of the hot state.
+        hot_db_ops.push(StoreOp::PutRootIndexEntry(
+            state_root,
+            RootIndexEntry {
+                slot,
+                canonical: true,
+            },
+        ));
+    }
+
+    // 2. Mark the newly finalized blocks as canonical.
+    //
+    // Blocks remain in the hot database, so only their index entries need updating. Skipped slots
+    // repeat the root of the prior block, which may be below the split slot and already marked.
+    let block_root_iter = BlockRootsIterator::new(store.clone(), frozen_head);
+    for maybe_pair in block_root_iter.take_while(|result| match result {
+        Ok((_, slot)) => slot >= &current_split_slot,
+        Err(_) => true,
+    }) {
+        let (block_root, _) = maybe_pair?;
+
+        if let Some(index_entry) = store.get_root_index_entry(&block_root)? {
+            if !index_entry.canonical {
+                hot_db_ops.push(StoreOp::PutRootIndexEntry(
+                    block_root,
+                    RootIndexEntry {
+                        canonical: true,
+                        ..index_entry
+                    },
+                ));
+            }
+        }
     }
 
     // Warning: Critical section.  We have to take care not to put any of the two databases in an
@@ -970,6 +1073,51 @@ impl StoreItem for Split {
     }
 }
 
+/// The slot of a block or state, and whether it is known to be in the canonical chain.
+///
+/// Stored in the hot database for every block and state, keyed by its root, so that it can be
+/// located without being loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode)]
+pub struct RootIndexEntry {
+    pub slot: Slot,
+    /// `true` once the block or state has been finalized.
+    ///
+    /// Entries are created non-canonical on import, so `false` doesn't imply that the block or
+    /// state is absent from the current canonical chain.
+    pub canonical: bool,
+}
+
+impl RootIndexEntry {
+    /// Construct the entry of a block or state which has not been finalized.
+    pub fn new(slot: Slot) -> Self {
+        Self {
+            slot,
+            canonical: false,
+        }
+    }
+}
+
+impl StoreItem for RootIndexEntry {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconRootIndex
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
+fn delete_root_index_entry_op(root: Hash256) -> KeyValueStoreOp {
+    KeyValueStoreOp::DeleteKey(get_key_for_col(
+        DBColumn::BeaconRootIndex.into(),
+        root.as_bytes(),
+    ))
+}
+
 /// Struct for summarising a state in the hot database.
 ///
 /// Allows full reconstruction by replaying blocks.
@@ -27,7 +27,9 @@ pub mod iter;
 use std::borrow::Cow;
 
 pub use self::config::StoreConfig;
-pub use self::hot_cold_store::{BlockReplay, HotColdDB, HotStateSummary, Split};
+pub use self::hot_cold_store::{
+    BlockReplay, HotColdDB, HotStateSummary, MaintenanceGuard, RootIndexEntry, Split,
+};
 pub use self::leveldb_store::LevelDB;
 pub use self::memory_store::MemoryStore;
 pub use self::partial_beacon_state::PartialBeaconState;
@@ -125,6 +127,7 @@ pub enum StoreOp<'a, E: EthSpec> {
     PutBlock(SignedBeaconBlockHash, SignedBeaconBlock<E>),
     PutState(BeaconStateHash, Cow<'a, BeaconState<E>>),
     PutStateSummary(BeaconStateHash, HotStateSummary),
+    PutRootIndexEntry(Hash256, RootIndexEntry),
     DeleteBlock(SignedBeaconBlockHash),
     DeleteState(BeaconStateHash, Slot),
 }
@@ -150,6 +153,8 @@ pub enum DBColumn {
     BeaconHistoricalRoots,
     BeaconRandaoMixes,
     DhtEnrs,
+    /// For the mapping from block and state roots to their slots.
+    BeaconRootIndex,
 }
 
 impl Into<&'static str> for DBColumn {
@@ -170,6 +175,7 @@ impl Into<&'static str> for DBColumn {
             DBColumn::BeaconHistoricalRoots => "bhr",
             DBColumn::BeaconRandaoMixes => "brm",
             DBColumn::DhtEnrs => "dht",
+            DBColumn::BeaconRootIndex => "bri",
         }
     }
 }
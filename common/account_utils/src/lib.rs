@@ -12,6 +12,7 @@ use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use zeroize::Zeroize;
 
+pub mod threshold;
 pub mod validator_definitions;
 
 pub use eth2_keystore;
@@ -121,6 +122,13 @@ impl From<String> for ZeroizeString {
     }
 }
 
+impl ZeroizeString {
+    /// Returns the wrapped string, for APIs which do not accept bytes.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 impl AsRef<[u8]> for ZeroizeString {
     fn as_ref(&self) -> &[u8] {
         self.0.as_bytes()
@@ -0,0 +1,317 @@
+//! Provides threshold (t-of-n) validator keys, where the secret key of a validator is split into
+//! `n` shares and any `t` of them are required to sign for it.
+//!
+//! The key is split with Shamir's secret sharing over the scalar field of BLS12-381. Each share is
+//! an ordinary BLS secret key, so it may be stored in an EIP-2335 keystore. A signature by the
+//! validator is the Lagrange interpolation of `t` signatures by shares, which is performed by the
+//! software that coordinates the co-signers rather than by Lighthouse.
+
+use num_bigint_dig::BigUint;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+use types::{Keypair, PublicKey, SecretKey};
+use zeroize::Zeroize;
+
+/// The order of the BLS12-381 scalar field.
+const R: &str = "52435875175126190479447740508185965837690552500527637822603658699938581184513";
+
+/// The file name of the `ShareDefinition` in a share directory.
+pub const SHARE_DEFINITION_FILE: &str = "threshold_share.yml";
+/// The file name of the keystore in a share directory.
+pub const SHARE_KEYSTORE_FILE: &str = "share-keystore.json";
+/// The file name of the keystore password in a share directory.
+pub const SHARE_PASSWORD_FILE: &str = "share-password.txt";
+
+#[derive(Debug)]
+pub enum Error {
+    /// The threshold must be at least one, and no greater than the number of shares.
+    InvalidThreshold { threshold: u64, shares: u64 },
+    /// Share indices start at one, since the share at zero is the secret key.
+    ZeroShareIndex,
+    /// Two shares have the same index.
+    DuplicateShareIndex(u64),
+    /// A share or recovered key is not a valid secret key.
+    InvalidSecretKey(String),
+    /// The share definition file could not be opened.
+    UnableToOpenFile(io::Error),
+    /// The share definition file could not be parsed as YAML.
+    UnableToParseFile(serde_yaml::Error),
+    /// The share definition could not be serialized as YAML.
+    UnableToEncodeFile(serde_yaml::Error),
+    /// The share definition file could not be written.
+    UnableToWriteFile(io::Error),
+}
+
+/// One share of the secret key of a threshold validator.
+pub struct KeyShare {
+    /// The point at which the polynomial was evaluated, starting at one.
+    pub index: u64,
+    pub keypair: Keypair,
+}
+
+/// Another holder of a share of the key of a threshold validator.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoSigner {
+    pub share_index: u64,
+    pub share_public_key: PublicKey,
+    /// Where the co-signer may be reached. Not used by Lighthouse.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Describes one share of a threshold validator and the holders of the other shares.
+///
+/// Contains no secret material, it is stored alongside the keystore of the share.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShareDefinition {
+    /// The public key of the validator, which corresponds to the secret key that was split.
+    pub voting_public_key: PublicKey,
+    pub share_index: u64,
+    pub share_public_key: PublicKey,
+    pub threshold: u64,
+    pub co_signers: Vec<CoSigner>,
+}
+
+impl ShareDefinition {
+    /// Returns a definition for each of `shares`, split from the key of `voting_public_key`.
+    pub fn for_shares(
+        voting_public_key: &PublicKey,
+        threshold: u64,
+        shares: &[KeyShare],
+    ) -> Vec<Self> {
+        shares
+            .iter()
+            .map(|share| ShareDefinition {
+                voting_public_key: voting_public_key.clone(),
+                share_index: share.index,
+                share_public_key: share.keypair.pk.clone(),
+                threshold,
+                co_signers: shares
+                    .iter()
+                    .filter(|other| other.index != share.index)
+                    .map(|other| CoSigner {
+                        share_index: other.index,
+                        share_public_key: other.keypair.pk.clone(),
+                        url: None,
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Returns an error if the shares described by `self` could not sign for the validator.
+    pub fn validate(&self) -> Result<(), Error> {
+        let shares = self.co_signers.len() as u64 + 1;
+        if self.threshold == 0 || self.threshold > shares {
+            return Err(Error::InvalidThreshold {
+                threshold: self.threshold,
+                shares,
+            });
+        }
+        check_indices(
+            std::iter::once(self.share_index).chain(self.co_signers.iter().map(|c| c.share_index)),
+        )
+    }
+
+    /// Read and validate the definition in the YAML file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = File::open(path).map_err(Error::UnableToOpenFile)?;
+        let definition: Self = serde_yaml::from_reader(file).map_err(Error::UnableToParseFile)?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// Write `self` as YAML to a new file at `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let bytes = serde_yaml::to_vec(self).map_err(Error::UnableToEncodeFile)?;
+        fs::write(path, &bytes).map_err(Error::UnableToWriteFile)
+    }
+}
+
+/// Splits `secret_key` into `shares` shares, any `threshold` of which recover it.
+///
+/// The share with index `i` is the evaluation at `i` of a random polynomial of degree
+/// `threshold - 1`, whose constant term is `secret_key`. Indices run from one to `shares`.
+pub fn split_secret_key(
+    secret_key: &SecretKey,
+    threshold: u64,
+    shares: u64,
+) -> Result<Vec<KeyShare>, Error> {
+    if threshold == 0 || threshold > shares {
+        return Err(Error::InvalidThreshold { threshold, shares });
+    }
+
+    let r = curve_order();
+    let mut coefficients = std::iter::once(to_scalar(secret_key))
+        .chain((1..threshold).map(|_| to_scalar(&SecretKey::random())))
+        .collect::<Vec<_>>();
+
+    let result = (1..=shares)
+        .map(|index| {
+            let x = BigUint::from(index);
+            // Horner's method, from the highest degree coefficient down.
+            let mut y = coefficients
+                .iter()
+                .rev()
+                .fold(BigUint::from(0_u64), |acc, coefficient| {
+                    (acc * &x + coefficient) % &r
+                });
+            let secret_key = from_scalar(&y);
+            y.zeroize();
+
+            let secret_key = secret_key?;
+            Ok(KeyShare {
+                index,
+                keypair: Keypair::from_components(secret_key.public_key(), secret_key),
+            })
+        })
+        .collect();
+
+    coefficients.zeroize();
+    result
+}
+
+/// Recovers the secret key from `shares` by Lagrange interpolation at zero.
+///
+/// At least `threshold` shares must be supplied, otherwise an unrelated key is returned.
+pub fn recover_secret_key(shares: &[KeyShare]) -> Result<SecretKey, Error> {
+    check_indices(shares.iter().map(|share| share.index))?;
+
+    let r = curve_order();
+    let exponent = &r - BigUint::from(2_u64);
+    let mut secret = BigUint::from(0_u64);
+
+    for share in shares {
+        let x_i = BigUint::from(share.index);
+        let (numerator, denominator) = shares
+            .iter()
+            .filter(|other| other.index != share.index)
+            .map(|other| BigUint::from(other.index))
+            .fold(
+                (BigUint::from(1_u64), BigUint::from(1_u64)),
+                |(numerator, denominator), x_j| {
+                    let difference = (&x_j + &r - &x_i) % &r;
+                    (numerator * x_j % &r, denominator * difference % &r)
+                },
+            );
+        // The field order is prime, so the inverse is `denominator ^ (r - 2)`.
+        let coefficient = numerator * denominator.modpow(&exponent, &r) % &r;
+
+        let mut y = to_scalar(&share.keypair.sk);
+        secret = (secret + &y * coefficient) % &r;
+        y.zeroize();
+    }
+
+    let secret_key = from_scalar(&secret);
+    secret.zeroize();
+    secret_key
+}
+
+/// Returns an error if any index is zero or appears more than once.
+fn check_indices<I: Iterator<Item = u64>>(indices: I) -> Result<(), Error> {
+    let mut seen = HashSet::new();
+    for index in indices {
+        if index == 0 {
+            return Err(Error::ZeroShareIndex);
+        }
+        if !seen.insert(index) {
+            return Err(Error::DuplicateShareIndex(index));
+        }
+    }
+    Ok(())
+}
+
+fn curve_order() -> BigUint {
+    BigUint::parse_bytes(R.as_bytes(), 10).expect("must be able to parse R")
+}
+
+/// Interprets the big-endian bytes of `secret_key` as an integer.
+fn to_scalar(secret_key: &SecretKey) -> BigUint {
+    BigUint::from_bytes_be(secret_key.serialize().as_bytes())
+}
+
+/// Interprets `scalar`, which must be less than `R`, as a secret key.
+fn from_scalar(scalar: &BigUint) -> Result<SecretKey, Error> {
+    let mut bytes = scalar.to_bytes_be();
+    let mut padded = [0; 32];
+    padded[32 - bytes.len()..].copy_from_slice(&bytes);
+    bytes.zeroize();
+
+    let secret_key =
+        SecretKey::deserialize(&padded).map_err(|e| Error::InvalidSecretKey(format!("{:?}", e)));
+    padded.zeroize();
+    secret_key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovers_from_any_threshold_of_shares() {
+        let keypair = Keypair::random();
+        let shares = split_secret_key(&keypair.sk, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        let subsets: &[&[usize]] = &[
+            &[0, 1, 2],
+            &[2, 3, 4],
+            &[0, 2, 4],
+            &[4, 1, 3],
+            &[0, 1, 2, 3],
+        ];
+        for subset in subsets {
+            let subset = subset
+                .iter()
+                .map(|i| KeyShare {
+                    index: shares[*i].index,
+                    keypair: shares[*i].keypair.clone(),
+                })
+                .collect::<Vec<_>>();
+            let recovered = recover_secret_key(&subset).unwrap();
+            assert_eq!(recovered.public_key(), keypair.pk);
+        }
+
+        let too_few = recover_secret_key(&shares[..2]).unwrap();
+        assert_ne!(too_few.public_key(), keypair.pk);
+    }
+
+    #[test]
+    fn one_of_one_is_the_secret_key() {
+        let keypair = Keypair::random();
+        let shares = split_secret_key(&keypair.sk, 1, 1).unwrap();
+        assert_eq!(shares[0].keypair.pk, keypair.pk);
+    }
+
+    #[test]
+    fn invalid_threshold() {
+        let sk = SecretKey::random();
+        assert!(split_secret_key(&sk, 0, 3).is_err());
+        assert!(split_secret_key(&sk, 4, 3).is_err());
+    }
+
+    #[test]
+    fn share_definitions() {
+        let keypair = Keypair::random();
+        let shares = split_secret_key(&keypair.sk, 2, 3).unwrap();
+        let definitions = ShareDefinition::for_shares(&keypair.pk, 2, &shares);
+
+        assert_eq!(definitions.len(), 3);
+        for (definition, share) in definitions.iter().zip(&shares) {
+            definition.validate().unwrap();
+            assert_eq!(definition.share_public_key, share.keypair.pk);
+            assert_eq!(definition.co_signers.len(), 2);
+            assert!(definition
+                .co_signers
+                .iter()
+                .all(|co_signer| co_signer.share_index != share.index));
+        }
+
+        let mut duplicate = definitions[0].clone();
+        duplicate.co_signers[0].share_index = duplicate.share_index;
+        assert!(duplicate.validate().is_err());
+    }
+}
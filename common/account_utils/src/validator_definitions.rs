@@ -3,7 +3,9 @@
 //! Serves as the source-of-truth of which validators this validator client should attempt (or not
 //! attempt) to load into the `crate::intialized_validators::InitializedValidators` struct.
 
-use crate::{create_with_600_perms, default_keystore_password_path, ZeroizeString};
+use crate::{
+    create_with_600_perms, default_keystore_password_path, threshold::CoSigner, ZeroizeString,
+};
 use eth2_keystore::Keystore;
 use regex::Regex;
 use serde_derive::{Deserialize, Serialize};
@@ -11,13 +13,15 @@ use slog::{error, Logger};
 use std::collections::HashSet;
 use std::fs::{self, OpenOptions};
 use std::io;
-use std::iter::FromIterator;
 use std::path::{Path, PathBuf};
-use types::PublicKey;
+use std::time::Duration;
+use types::{Address, Epoch, PublicKey};
 use validator_dir::VOTING_KEYSTORE_FILE;
 
 /// The file name for the serialized `ValidatorDefinitions` struct.
 pub const CONFIG_FILENAME: &str = "validator_definitions.yml";
+/// The file name used whilst writing a new `CONFIG_FILENAME`.
+pub const CONFIG_TEMP_FILENAME: &str = "validator_definitions.yml.tmp";
 
 #[derive(Debug)]
 pub enum Error {
@@ -38,9 +42,6 @@ pub enum Error {
 }
 
 /// Defines how the validator client should attempt to sign messages for this validator.
-///
-/// Presently there is only a single variant, however we expect more variants to arise (e.g.,
-/// remote signing).
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum SigningDefinition {
@@ -53,21 +54,105 @@ pub enum SigningDefinition {
         #[serde(skip_serializing_if = "Option::is_none")]
         voting_keystore_password: Option<ZeroizeString>,
     },
+    /// A validator whose secret key is held by a remote Web3Signer instance, which signs over
+    /// HTTP.
+    #[serde(rename = "web3signer")]
+    Web3Signer {
+        /// The base URL of the signer, e.g. `https://signer.example.com:9000`.
+        url: String,
+        /// A PEM certificate to trust as the root of the TLS certificate of the signer, in
+        /// addition to the system roots.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        root_certificate_path: Option<PathBuf>,
+        /// A PKCS#12 identity used to authenticate this validator client to the signer.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        client_identity_path: Option<PathBuf>,
+        /// The password of the file at `client_identity_path`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        client_identity_password: Option<ZeroizeString>,
+    },
+    /// A validator of which this validator client holds one share of the key, in an EIP-2335
+    /// keystore on the local filesystem. Signing requires `threshold` shares, so the holders of
+    /// the other shares are listed as `co_signers`.
+    #[serde(rename = "threshold_share")]
+    ThresholdShare {
+        voting_keystore_path: PathBuf,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        voting_keystore_password_path: Option<PathBuf>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        voting_keystore_password: Option<ZeroizeString>,
+        share_index: u64,
+        threshold: u64,
+        co_signers: Vec<CoSigner>,
+    },
+}
+
+/// A time at which a validator is automatically enabled or disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledTime {
+    /// The start of the given epoch.
+    Epoch(Epoch),
+    /// The given number of seconds since the UNIX epoch.
+    UnixTime(u64),
+}
+
+impl ScheduledTime {
+    /// Returns true if `self` is at or before the current time, which is given as the duration
+    /// since the UNIX epoch and the current epoch.
+    ///
+    /// Returns `None` if `self` is an epoch and the current epoch is not known.
+    pub fn is_reached(&self, unix_time: Duration, epoch: Option<Epoch>) -> Option<bool> {
+        match self {
+            ScheduledTime::Epoch(at) => epoch.map(|epoch| epoch >= *at),
+            ScheduledTime::UnixTime(at) => Some(unix_time >= Duration::from_secs(*at)),
+        }
+    }
 }
 
 /// A validator that may be initialized by this validator client.
-///
-/// Presently there is only a single variant, however we expect more variants to arise (e.g.,
-/// remote signing).
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct ValidatorDefinition {
     pub enabled: bool,
+    /// If set, an `enabled` validator is not initialized before this time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_at: Option<ScheduledTime>,
+    /// If set, an `enabled` validator is not initialized from this time onwards.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable_at: Option<ScheduledTime>,
+    /// If set, the graffiti of this validator's blocks, in place of the `--graffiti` flag.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graffiti: Option<String>,
+    /// If set, the address which should receive the fees of this validator's blocks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested_fee_recipient: Option<Address>,
     pub voting_public_key: PublicKey,
     #[serde(flatten)]
     pub signing_definition: SigningDefinition,
 }
 
 impl ValidatorDefinition {
+    /// Returns true if the validator is `enabled` and the current time lies between its
+    /// `enable_at` and `disable_at` times.
+    ///
+    /// Whilst the current epoch is not known, a validator with an epoch in its schedule is
+    /// never considered to be enabled.
+    pub fn is_enabled_at(&self, unix_time: Duration, epoch: Option<Epoch>) -> bool {
+        let enable_reached = self
+            .enable_at
+            .map_or(Some(true), |at| at.is_reached(unix_time, epoch));
+        let disable_reached = self
+            .disable_at
+            .map_or(Some(false), |at| at.is_reached(unix_time, epoch));
+
+        self.enabled && enable_reached == Some(true) && disable_reached == Some(false)
+    }
+
+    /// Returns true if the validator is enabled or disabled automatically at some time.
+    pub fn is_scheduled(&self) -> bool {
+        self.enable_at.is_some() || self.disable_at.is_some()
+    }
+
     /// Create a new definition for a voting keystore at the given `voting_keystore_path` that can
     /// be unlocked with `voting_keystore_password`.
     ///
@@ -78,15 +163,15 @@ impl ValidatorDefinition {
         voting_keystore_path: P,
         voting_keystore_password: Option<ZeroizeString>,
     ) -> Result<Self, Error> {
-        let voting_keystore_path = voting_keystore_path.as_ref().into();
-        let keystore =
-            Keystore::from_json_file(&voting_keystore_path).map_err(Error::UnableToOpenKeystore)?;
-        let voting_public_key = keystore
-            .public_key()
-            .ok_or_else(|| Error::InvalidKeystorePubkey)?;
+        let voting_keystore_path: PathBuf = voting_keystore_path.as_ref().into();
+        let voting_public_key = keystore_public_key(&voting_keystore_path)?;
 
         Ok(ValidatorDefinition {
             enabled: true,
+            enable_at: None,
+            disable_at: None,
+            graffiti: None,
+            suggested_fee_recipient: None,
             voting_public_key,
             signing_definition: SigningDefinition::LocalKeystore {
                 voting_keystore_path,
@@ -95,6 +180,61 @@ impl ValidatorDefinition {
             },
         })
     }
+
+    /// Create a new definition for a voting keystore at the given `voting_keystore_path` that can
+    /// be unlocked with the password stored at `voting_keystore_password_path`.
+    ///
+    /// ## Notes
+    ///
+    /// This function does not check the password against the keystore.
+    pub fn new_keystore_with_password_path<P: AsRef<Path>, Q: AsRef<Path>>(
+        voting_keystore_path: P,
+        voting_keystore_password_path: Q,
+    ) -> Result<Self, Error> {
+        let voting_keystore_path: PathBuf = voting_keystore_path.as_ref().into();
+        let voting_public_key = keystore_public_key(&voting_keystore_path)?;
+
+        Ok(ValidatorDefinition {
+            enabled: true,
+            enable_at: None,
+            disable_at: None,
+            graffiti: None,
+            suggested_fee_recipient: None,
+            voting_public_key,
+            signing_definition: SigningDefinition::LocalKeystore {
+                voting_keystore_path,
+                voting_keystore_password_path: Some(voting_keystore_password_path.as_ref().into()),
+                voting_keystore_password: None,
+            },
+        })
+    }
+
+    /// Create a new definition for the validator with `voting_public_key` whose messages are
+    /// signed by the Web3Signer at `url`, without any client TLS configuration.
+    pub fn new_web3signer(voting_public_key: PublicKey, url: String) -> Self {
+        ValidatorDefinition {
+            enabled: true,
+            enable_at: None,
+            disable_at: None,
+            graffiti: None,
+            suggested_fee_recipient: None,
+            voting_public_key,
+            signing_definition: SigningDefinition::Web3Signer {
+                url,
+                root_certificate_path: None,
+                client_identity_path: None,
+                client_identity_password: None,
+            },
+        }
+    }
+}
+
+/// Read the public key of the keystore at `path`.
+fn keystore_public_key(path: &Path) -> Result<PublicKey, Error> {
+    Keystore::from_json_file(path)
+        .map_err(Error::UnableToOpenKeystore)?
+        .public_key()
+        .ok_or_else(|| Error::InvalidKeystorePubkey)
 }
 
 /// A list of `ValidatorDefinition` that serves as a serde-able configuration file which defines a
@@ -147,13 +287,21 @@ impl ValidatorDefinitions {
         recursively_find_voting_keystores(validators_dir, &mut keystore_paths)
             .map_err(Error::UnableToSearchForKeystores)?;
 
-        let known_paths: HashSet<&PathBuf> =
-            HashSet::from_iter(self.0.iter().map(|def| match &def.signing_definition {
+        let known_paths: HashSet<&PathBuf> = self
+            .0
+            .iter()
+            .filter_map(|def| match &def.signing_definition {
                 SigningDefinition::LocalKeystore {
                     voting_keystore_path,
                     ..
-                } => voting_keystore_path,
-            }));
+                }
+                | SigningDefinition::ThresholdShare {
+                    voting_keystore_path,
+                    ..
+                } => Some(voting_keystore_path),
+                SigningDefinition::Web3Signer { .. } => None,
+            })
+            .collect();
 
         let mut new_defs = keystore_paths
             .into_iter()
@@ -204,6 +352,10 @@ impl ValidatorDefinitions {
 
                 Some(ValidatorDefinition {
                     enabled: true,
+                    enable_at: None,
+                    disable_at: None,
+                    graffiti: None,
+                    suggested_fee_recipient: None,
                     voting_public_key,
                     signing_definition: SigningDefinition::LocalKeystore {
                         voting_keystore_path,
@@ -224,16 +376,16 @@ impl ValidatorDefinitions {
     /// Encodes `self` as a YAML string it writes it to the `CONFIG_FILENAME` file in the
     /// `validators_dir` directory.
     ///
-    /// Will create a new file if it does not exist or over-write any existing file.
+    /// Will create a new file if it does not exist or over-write any existing file. The file is
+    /// written to `CONFIG_TEMP_FILENAME` and then renamed, so that a failure part-way through
+    /// never leaves a truncated `CONFIG_FILENAME` behind.
     pub fn save<P: AsRef<Path>>(&self, validators_dir: P) -> Result<(), Error> {
         let config_path = validators_dir.as_ref().join(CONFIG_FILENAME);
+        let temp_path = validators_dir.as_ref().join(CONFIG_TEMP_FILENAME);
         let bytes = serde_yaml::to_vec(self).map_err(Error::UnableToEncodeFile)?;
 
-        if config_path.exists() {
-            fs::write(config_path, &bytes).map_err(Error::UnableToWriteFile)
-        } else {
-            create_with_600_perms(&config_path, &bytes).map_err(Error::UnableToWriteFile)
-        }
+        create_with_600_perms(&temp_path, &bytes).map_err(Error::UnableToWriteFile)?;
+        fs::rename(&temp_path, &config_path).map_err(Error::UnableToWriteFile)
     }
 
     /// Adds a new `ValidatorDefinition` to `self`.
@@ -241,6 +393,15 @@ impl ValidatorDefinitions {
         self.0.push(def)
     }
 
+    /// Removes and returns the `ValidatorDefinition` at `index`.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> ValidatorDefinition {
+        self.0.remove(index)
+    }
+
     /// Returns a slice of all `ValidatorDefinition` in `self`.
     pub fn as_slice(&self) -> &[ValidatorDefinition] {
         self.0.as_slice()
@@ -279,6 +440,148 @@ pub fn recursively_find_voting_keystores<P: AsRef<Path>>(
     })
 }
 
+/// The file name of each keystore in a Nimbus `validators` directory.
+pub const NIMBUS_KEYSTORE_FILE: &str = "keystore.json";
+/// The directory which holds the Nimbus password for each keystore, in a file named after the
+/// directory of the keystore.
+pub const NIMBUS_SECRETS_DIR: &str = "secrets";
+/// The file of a Prysm wallet which holds all of its accounts, which is not an EIP-2335 keystore.
+pub const PRYSM_WALLET_ACCOUNTS_FILE: &str = "all-accounts.keystore.json";
+
+/// A voting keystore found by `recursively_find_keystores_with_passwords`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoundKeystore {
+    pub keystore_path: PathBuf,
+    /// The file which holds the password of the keystore, if the client that created it stores
+    /// one alongside.
+    pub password_path: Option<PathBuf>,
+}
+
+/// Perform an exhaustive tree search of `dir`, adding any discovered voting keystores to
+/// `matches` along with their password files.
+///
+/// As well as the keystores found by `recursively_find_voting_keystores`, this recognises the
+/// layouts used by other clients:
+///
+/// - Nimbus: `validators/0x<pubkey>/keystore.json`, with the password in `secrets/0x<pubkey>`.
+/// - Teku: `<name>.json`, with the password in `<name>.txt` in the same directory, a sibling
+///   `passwords` directory or a sibling directory with a `-passwords` suffix.
+/// - Prysm: keystores exported by `validator accounts backup`, which have no password files.
+///
+/// ## Errors
+///
+/// Returns with an error immediately if any filesystem error is raised.
+pub fn recursively_find_keystores_with_passwords<P: AsRef<Path>>(
+    dir: P,
+    matches: &mut Vec<FoundKeystore>,
+) -> Result<(), io::Error> {
+    recursively_visit_files(dir.as_ref(), &mut |path, file_name| {
+        let password_path = if is_voting_keystore(file_name) {
+            teku_password_path(path)
+        } else if file_name == NIMBUS_KEYSTORE_FILE && is_in_pubkey_dir(path) {
+            nimbus_password_path(path)
+        } else if file_name.ends_with(".json") {
+            // Teku does not restrict the names of keystores, so any other JSON file is only
+            // considered to be a keystore if it has a password file.
+            match teku_password_path(path) {
+                Some(password_path) => Some(password_path),
+                None => return,
+            }
+        } else {
+            return;
+        };
+
+        matches.push(FoundKeystore {
+            keystore_path: path.to_path_buf(),
+            password_path,
+        })
+    })
+}
+
+/// Perform an exhaustive tree search of `dir`, adding the path of any Prysm wallet accounts file
+/// to `matches`.
+///
+/// The accounts must be exported from a Prysm wallet with `validator accounts backup` before
+/// they can be imported.
+pub fn recursively_find_prysm_wallets<P: AsRef<Path>>(
+    dir: P,
+    matches: &mut Vec<PathBuf>,
+) -> Result<(), io::Error> {
+    recursively_visit_files(dir.as_ref(), &mut |path, file_name| {
+        if file_name == PRYSM_WALLET_ACCOUNTS_FILE {
+            matches.push(path.to_path_buf())
+        }
+    })
+}
+
+/// Call `visit` with the path and name of every file in the tree beneath `dir`.
+fn recursively_visit_files(
+    dir: &Path,
+    visit: &mut dyn FnMut(&Path, &str),
+) -> Result<(), io::Error> {
+    fs::read_dir(dir)?.try_for_each(|dir_entry| {
+        let dir_entry = dir_entry?;
+        let file_type = dir_entry.file_type()?;
+        if file_type.is_dir() {
+            recursively_visit_files(&dir_entry.path(), visit)?
+        } else if file_type.is_file() {
+            if let Some(file_name) = dir_entry.file_name().to_str() {
+                visit(&dir_entry.path(), file_name)
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Returns the password file of a keystore stored in the Nimbus layout, if it exists.
+fn nimbus_password_path(keystore_path: &Path) -> Option<PathBuf> {
+    let keystore_dir = keystore_path.parent()?;
+    let keystore_dir_name = keystore_dir.file_name()?;
+    let password_path = keystore_dir
+        .parent()?
+        .parent()?
+        .join(NIMBUS_SECRETS_DIR)
+        .join(keystore_dir_name);
+    Some(password_path).filter(|path| path.is_file())
+}
+
+/// Returns the password file of a keystore stored in the Teku layout, if it exists.
+fn teku_password_path(keystore_path: &Path) -> Option<PathBuf> {
+    let mut password_file_name = keystore_path.file_stem()?.to_os_string();
+    password_file_name.push(".txt");
+
+    let keystore_dir = keystore_path.parent()?;
+    let mut candidates = vec![
+        keystore_dir.join(&password_file_name),
+        keystore_dir
+            .with_file_name("passwords")
+            .join(&password_file_name),
+    ];
+    if let Some(keystore_dir_name) = keystore_dir.file_name() {
+        let mut passwords_dir_name = keystore_dir_name.to_os_string();
+        passwords_dir_name.push("-passwords");
+        candidates.push(
+            keystore_dir
+                .with_file_name(passwords_dir_name)
+                .join(&password_file_name),
+        );
+    }
+
+    candidates.into_iter().find(|path| path.is_file())
+}
+
+/// Returns `true` if the file at `path` is in a directory named after a 0x-prefixed, hex-encoded
+/// BLS public key.
+fn is_in_pubkey_dir(path: &Path) -> bool {
+    path.parent()
+        .and_then(Path::file_name)
+        .and_then(|dir_name| dir_name.to_str())
+        .and_then(|dir_name| dir_name.strip_prefix("0x"))
+        .map_or(false, |hex| {
+            hex.len() == 96 && hex.chars().all(|c| c.is_ascii_hexdigit())
+        })
+}
+
 /// Returns `true` if we should consider the `file_name` to represent a voting keystore.
 fn is_voting_keystore(file_name: &str) -> bool {
     // All formats end with `.json`.
@@ -325,6 +628,119 @@ fn is_voting_keystore(file_name: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::tempdir;
+    use types::Keypair;
+
+    #[test]
+    fn save_replaces_existing_file() {
+        let dir = tempdir().unwrap();
+
+        let mut defs = ValidatorDefinitions::open_or_create(dir.path()).unwrap();
+        defs.push(ValidatorDefinition {
+            enabled: true,
+            enable_at: None,
+            disable_at: None,
+            graffiti: None,
+            suggested_fee_recipient: None,
+            voting_public_key: Keypair::random().pk,
+            signing_definition: SigningDefinition::LocalKeystore {
+                voting_keystore_path: dir.path().join("voting-keystore.json"),
+                voting_keystore_password_path: None,
+                voting_keystore_password: None,
+            },
+        });
+        defs.save(dir.path()).unwrap();
+
+        let reopened = ValidatorDefinitions::open(dir.path()).unwrap();
+        assert_eq!(reopened.as_slice().len(), 1);
+        assert!(!dir.path().join(CONFIG_TEMP_FILENAME).exists());
+    }
+
+    #[test]
+    fn web3signer_definition_round_trip() {
+        let voting_public_key = Keypair::random().pk;
+        let yaml = format!(
+            "---\n\
+             - enabled: true\n  \
+               voting_public_key: \"{:?}\"\n  \
+               type: web3signer\n  \
+               url: \"https://signer.example.com:9000\"\n  \
+               root_certificate_path: /certs/ca.pem\n",
+            voting_public_key
+        );
+
+        let defs: Vec<ValidatorDefinition> = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(defs.len(), 1);
+        assert!(defs[0].voting_public_key == voting_public_key);
+        match &defs[0].signing_definition {
+            SigningDefinition::Web3Signer {
+                url,
+                root_certificate_path,
+                client_identity_path,
+                client_identity_password,
+            } => {
+                assert_eq!(url, "https://signer.example.com:9000");
+                assert_eq!(root_certificate_path, &Some(PathBuf::from("/certs/ca.pem")));
+                assert!(client_identity_path.is_none());
+                assert!(client_identity_password.is_none());
+            }
+            _ => panic!("expected a web3signer definition"),
+        }
+
+        let reencoded: Vec<ValidatorDefinition> =
+            serde_yaml::from_str(&serde_yaml::to_string(&defs).unwrap()).unwrap();
+        assert!(reencoded == defs);
+        assert!(!serde_yaml::to_string(&defs)
+            .unwrap()
+            .contains("client_identity"));
+    }
+
+    #[test]
+    fn per_validator_block_settings() {
+        let voting_public_key = Keypair::random().pk;
+        let mut def = ValidatorDefinition::new_web3signer(
+            voting_public_key,
+            "https://signer.example.com:9000".to_string(),
+        );
+        assert!(!serde_yaml::to_string(&def).unwrap().contains("graffiti"));
+
+        def.graffiti = Some("validator {validator_index}".to_string());
+        def.suggested_fee_recipient = Some(Address::repeat_byte(0xab));
+        let yaml = serde_yaml::to_string(&def).unwrap();
+        assert!(yaml.contains("0xabababababababababababababababababababab"));
+
+        let decoded: ValidatorDefinition = serde_yaml::from_str(&yaml).unwrap();
+        assert!(decoded == def);
+    }
+
+    #[test]
+    fn scheduled_definition() {
+        let yaml = format!(
+            "---\n\
+             enabled: true\n\
+             enable_at:\n  \
+               epoch: 10\n\
+             disable_at:\n  \
+               unix_time: 1600000000\n\
+             voting_public_key: \"{:?}\"\n\
+             type: web3signer\n\
+             url: \"https://signer.example.com:9000\"\n",
+            Keypair::random().pk
+        );
+        let mut def: ValidatorDefinition = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(def.enable_at, Some(ScheduledTime::Epoch(Epoch::new(10))));
+        assert_eq!(def.disable_at, Some(ScheduledTime::UnixTime(1_600_000_000)));
+
+        let before_disable = Duration::from_secs(1_599_999_999);
+        let after_disable = Duration::from_secs(1_600_000_000);
+        assert!(!def.is_enabled_at(before_disable, None));
+        assert!(!def.is_enabled_at(before_disable, Some(Epoch::new(9))));
+        assert!(def.is_enabled_at(before_disable, Some(Epoch::new(10))));
+        assert!(!def.is_enabled_at(after_disable, Some(Epoch::new(10))));
+
+        def.enabled = false;
+        assert!(!def.is_enabled_at(before_disable, Some(Epoch::new(10))));
+    }
 
     #[test]
     fn voting_keystore_filename_lighthouse() {
@@ -360,4 +776,87 @@ mod tests {
         assert!(!is_voting_keystore("keystore-0a.json"));
         assert!(!is_voting_keystore("keystore-cats.json"));
     }
+
+    #[test]
+    fn keystores_with_passwords_other_clients() {
+        let dir = tempdir().unwrap();
+        let pubkey_dir_name = format!("0x{}", "ab".repeat(48));
+        let touch = |path: PathBuf| {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, b"").unwrap();
+        };
+
+        // Nimbus.
+        let nimbus = dir.path().join("nimbus");
+        touch(
+            nimbus
+                .join("validators")
+                .join(&pubkey_dir_name)
+                .join(NIMBUS_KEYSTORE_FILE),
+        );
+        touch(nimbus.join(NIMBUS_SECRETS_DIR).join(&pubkey_dir_name));
+        touch(
+            nimbus
+                .join("validators")
+                .join("0xcats")
+                .join(NIMBUS_KEYSTORE_FILE),
+        );
+
+        // Teku.
+        let teku = dir.path().join("teku");
+        touch(teku.join("keys").join("a.json"));
+        touch(teku.join("passwords").join("a.txt"));
+        touch(teku.join("local").join("b.json"));
+        touch(teku.join("local-passwords").join("b.txt"));
+        touch(teku.join("keys").join("c.json"));
+
+        // Prysm.
+        let prysm = dir.path().join("prysm");
+        touch(prysm.join("keystore-0.json"));
+        touch(
+            prysm
+                .join("direct")
+                .join("accounts")
+                .join(PRYSM_WALLET_ACCOUNTS_FILE),
+        );
+
+        let mut found = vec![];
+        recursively_find_keystores_with_passwords(dir.path(), &mut found).unwrap();
+        found.sort_by(|a, b| a.keystore_path.cmp(&b.keystore_path));
+
+        assert_eq!(
+            found,
+            vec![
+                FoundKeystore {
+                    keystore_path: nimbus
+                        .join("validators")
+                        .join(&pubkey_dir_name)
+                        .join(NIMBUS_KEYSTORE_FILE),
+                    password_path: Some(nimbus.join(NIMBUS_SECRETS_DIR).join(&pubkey_dir_name)),
+                },
+                FoundKeystore {
+                    keystore_path: prysm.join("keystore-0.json"),
+                    password_path: None,
+                },
+                FoundKeystore {
+                    keystore_path: teku.join("keys").join("a.json"),
+                    password_path: Some(teku.join("passwords").join("a.txt")),
+                },
+                FoundKeystore {
+                    keystore_path: teku.join("local").join("b.json"),
+                    password_path: Some(teku.join("local-passwords").join("b.txt")),
+                },
+            ]
+        );
+
+        let mut wallets = vec![];
+        recursively_find_prysm_wallets(dir.path(), &mut wallets).unwrap();
+        assert_eq!(
+            wallets,
+            vec![prysm
+                .join("direct")
+                .join("accounts")
+                .join(PRYSM_WALLET_ACCOUNTS_FILE)]
+        );
+    }
 }
@@ -4,8 +4,12 @@ use eth2_wallet::Error as WalletError;
 use eth2_wallet::{Uuid, Wallet};
 use std::fs::{copy as copy_file, remove_file, OpenOptions};
 use std::io;
+use std::os::unix::fs::OpenOptionsExt;
 use std::path::{Path, PathBuf};
 
+/// The permissions of a wallet JSON file: `600 (-rw-------)`.
+pub const WALLET_FILE_MODE: u32 = 0o600;
+
 #[derive(Debug)]
 pub enum Error {
     WalletAlreadyExists(PathBuf),
@@ -73,6 +77,8 @@ pub fn update<P: AsRef<Path>>(wallet_dir: P, wallet: &Wallet) -> Result<(), Erro
 }
 
 /// Writes the `wallet` into the `wallet_dir`, returning an error if it already exists.
+///
+/// The file is only readable and writable by its owner.
 pub fn create<P: AsRef<Path>>(wallet_dir: P, wallet: &Wallet) -> Result<(), Error> {
     let json_path = wallet_json_path(wallet_dir, wallet.uuid());
 
@@ -82,6 +88,7 @@ pub fn create<P: AsRef<Path>>(wallet_dir: P, wallet: &Wallet) -> Result<(), Erro
         OpenOptions::new()
             .write(true)
             .create_new(true)
+            .mode(WALLET_FILE_MODE)
             .open(json_path)
             .map_err(Error::UnableToCreateWallet)
             .and_then(|f| wallet.to_json_writer(f).map_err(Error::JsonWriteError))
@@ -5,10 +5,14 @@ use crate::{
 use eth2_wallet::{bip39::Mnemonic, Error as WalletError, Uuid, Wallet, WalletBuilder};
 use std::collections::HashMap;
 use std::ffi::OsString;
-use std::fs::{create_dir_all, read_dir, OpenOptions};
+use std::fs::{read_dir, DirBuilder, OpenOptions};
 use std::io;
+use std::os::unix::fs::DirBuilderExt;
 use std::path::{Path, PathBuf};
 
+/// The permissions of a wallet directory: `700 (drwx------)`.
+pub const WALLET_DIR_MODE: u32 = 0o700;
+
 #[derive(Debug)]
 pub enum Error {
     DirectoryDoesNotExist(PathBuf),
@@ -131,7 +135,10 @@ impl WalletManager {
             return Err(Error::WalletDirExists(wallet_dir));
         }
 
-        create_dir_all(&wallet_dir)?;
+        DirBuilder::new()
+            .recursive(true)
+            .mode(WALLET_DIR_MODE)
+            .create(&wallet_dir)?;
 
         create(&wallet_dir, &wallet)?;
 
@@ -274,6 +281,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let base_dir = dir.path();
+        let mgr = WalletManager::open(base_dir).unwrap();
+
+        let wallet = create_wallet(&mgr, 0);
+        let uuid = wallet.wallet().uuid();
+
+        let mode = |path: PathBuf| path.metadata().unwrap().permissions().mode() & 0o777;
+
+        assert_eq!(mode(wallet_dir_path(base_dir, uuid)), WALLET_DIR_MODE);
+        assert_eq!(
+            mode(json_path(base_dir, uuid)),
+            crate::filesystem::WALLET_FILE_MODE
+        );
+    }
+
     #[test]
     fn keystore_generation() {
         let dir = tempdir().unwrap();
@@ -4,25 +4,36 @@
 //! Presently, this is only used for testing but it _could_ become a user-facing library.
 
 use eth2_config::Eth2Config;
-use reqwest::{Client, ClientBuilder, Response, StatusCode};
+use futures::{
+    future::{self, Future},
+    stream::{self, Stream, StreamExt, TryStreamExt},
+};
+use reqwest::{Client, ClientBuilder, RequestBuilder, Response, StatusCode};
+use retry::retry_with_backoff_hint;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use ssz::Encode;
 use std::marker::PhantomData;
+use std::ops::Range;
 use std::time::Duration;
 use types::{
-    Attestation, AttestationData, AttesterSlashing, BeaconBlock, BeaconState, CommitteeIndex,
-    Epoch, EthSpec, Fork, Graffiti, Hash256, ProposerSlashing, PublicKey, PublicKeyBytes,
-    Signature, SignedAggregateAndProof, SignedBeaconBlock, Slot, SubnetId,
+    Address, Attestation, AttestationData, AttesterSlashing, BeaconBlock, BeaconState,
+    CommitteeIndex, Epoch, EthSpec, Fork, Graffiti, Hash256, ProposerSlashing, PublicKey,
+    PublicKeyBytes, Signature, SignedAggregateAndProof, SignedBeaconBlock, SignedBeaconBlockHeader,
+    SignedContributionAndProof, SignedVoluntaryExit, Slot, SubnetId, SyncCommitteeContribution,
+    SyncCommitteeMessage, YamlConfig,
 };
 use url::Url;
 
 pub use operation_pool::PersistedOperationPool;
 pub use proto_array::core::ProtoArray;
 pub use rest_types::{
-    CanonicalHeadResponse, Committee, HeadBeaconBlock, Health, IndividualVotesRequest,
-    IndividualVotesResponse, SyncingResponse, ValidatorDutiesRequest, ValidatorDutyBytes,
-    ValidatorRequest, ValidatorResponse, ValidatorSubscription,
+    CanonicalHeadResponse, Capabilities, Committee, CommitteesQuery, DutiesChangedEvent,
+    DutiesSubscriptionRequest, DutiesSubscriptionResponse, HeadBeaconBlock, HeaderResponse, Health,
+    IndividualVotesRequest, IndividualVotesResponse, SyncDuty, SyncingResponse, ThrottleInfo,
+    ValidatorDutiesRequest, ValidatorDutyBytes, ValidatorRequest, ValidatorResponse,
+    ValidatorSubscription,
 };
+pub use retry::RetryConfig;
 
 // Setting a long timeout for debug ensures that crypto-heavy operations can still succeed.
 #[cfg(debug_assertions)]
@@ -60,16 +71,59 @@ pub enum Error {
     /// There was an error when encoding/decoding an object using serde.
     SerdeJsonError(serde_json::Error),
     /// The server responded to the request, however it did not return a 200-type success code.
-    DidNotSucceed { status: StatusCode, body: String },
+    ///
+    /// The `retry_after` is the delay requested by the server's `Retry-After` header, if any.
+    DidNotSucceed {
+        status: StatusCode,
+        body: String,
+        retry_after: Option<Duration>,
+    },
     /// The request input was invalid.
     InvalidInput,
 }
 
+impl Error {
+    /// Returns the reason a request was throttled or shed by the server, if it was.
+    pub fn throttle_info(&self) -> Option<ThrottleInfo> {
+        match self {
+            Error::DidNotSucceed { status, body, .. }
+                if *status == StatusCode::TOO_MANY_REQUESTS
+                    || *status == StatusCode::SERVICE_UNAVAILABLE =>
+            {
+                serde_json::from_str(body).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Controls how the `stream_*` methods split large queries into multiple requests, and how failed
+/// requests are retried.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamConfig {
+    /// The maximum number of items fetched by a single request, or the maximum number of
+    /// concurrent requests for endpoints which return a single item.
+    pub chunk_size: usize,
+    /// Controls how many times a failed request is retried before the stream yields the error.
+    pub retry: RetryConfig,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: 64,
+            retry: RetryConfig::default(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct HttpClient<E> {
     client: Client,
     url: Url,
     timeout: Duration,
+    /// Controls how many times a request shed by the server is resent.
+    retry: RetryConfig,
     _phantom: PhantomData<E>,
 }
 
@@ -83,10 +137,16 @@ impl<E: EthSpec> HttpClient<E> {
                 .expect("should build from static configuration"),
             url: Url::parse(&server_url)?,
             timeout: Duration::from_secs(15),
+            retry: RetryConfig::default(),
             _phantom: PhantomData,
         })
     }
 
+    /// The URL of the beacon node.
+    pub fn server_url(&self) -> &str {
+        self.url.as_str()
+    }
+
     pub fn beacon(&self) -> Beacon<E> {
         Beacon(self.clone())
     }
@@ -99,6 +159,10 @@ impl<E: EthSpec> HttpClient<E> {
         Spec(self.clone())
     }
 
+    pub fn config(&self) -> Config<E> {
+        Config(self.clone())
+    }
+
     pub fn node(&self) -> Node<E> {
         Node(self.clone())
     }
@@ -115,13 +179,40 @@ impl<E: EthSpec> HttpClient<E> {
         self.url.join(path).map_err(|e| e.into())
     }
 
+    /// Sends the request built by `request`, resending it whilst the server sheds it with a
+    /// `Retry-After` header (e.g., during database maintenance).
+    ///
+    /// A shed request has not been processed by the server, so any request may be resent. Other
+    /// responses are returned to the caller, and other errors are not retried. If the request is
+    /// still being shed once `self.retry` is exhausted, the shedding response is returned as an
+    /// `Error::DidNotSucceed`.
+    async fn send<F>(&self, request: F) -> Result<Response, Error>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        retry_with_backoff_hint(
+            &self.retry,
+            |error| retry_after(error).is_some(),
+            retry_after,
+            || {
+                let request = request();
+                async move {
+                    let response = request.send().await.map_err(Error::from)?;
+                    match response.status() {
+                        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => {
+                            error_for_status(response).await
+                        }
+                        _ => Ok(response),
+                    }
+                }
+            },
+        )
+        .await
+    }
+
     pub async fn json_post<T: Serialize>(&self, url: Url, body: T) -> Result<Response, Error> {
-        self.client
-            .post(&url.to_string())
-            .json(&body)
-            .send()
+        self.send(|| self.client.post(&url.to_string()).json(&body))
             .await
-            .map_err(Error::from)
     }
 
     pub async fn json_get<T: DeserializeOwned>(
@@ -133,12 +224,7 @@ impl<E: EthSpec> HttpClient<E> {
             url.query_pairs_mut().append_pair(&key, &param);
         });
 
-        let response = self
-            .client
-            .get(&url.to_string())
-            .send()
-            .await
-            .map_err(Error::from)?;
+        let response = self.send(|| self.client.get(&url.to_string())).await?;
 
         let success = error_for_status(response).await.map_err(Error::from)?;
         success.json::<T>().await.map_err(Error::from)
@@ -155,14 +241,58 @@ async fn error_for_status(response: Response) -> Result<Response, Error> {
     if status.is_success() {
         Ok(response)
     } else {
+        // Only the delay-seconds form of `Retry-After` is supported, which is the form sent by
+        // Lighthouse.
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+
         let text_result = response.text().await;
         match text_result {
             Err(e) => Err(Error::ReqwestError(e)),
-            Ok(body) => Err(Error::DidNotSucceed { status, body }),
+            Ok(body) => Err(Error::DidNotSucceed {
+                status,
+                body,
+                retry_after,
+            }),
         }
     }
 }
 
+/// Returns `true` if retrying the request which produced `error` may succeed.
+fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::ReqwestError(_) => true,
+        Error::DidNotSucceed { status, .. } => {
+            status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+        }
+        _ => false,
+    }
+}
+
+/// Returns the delay requested by the server before the request which produced `error` is retried.
+fn retry_after(error: &Error) -> Option<Duration> {
+    match error {
+        Error::DidNotSucceed { retry_after, .. } => *retry_after,
+        _ => None,
+    }
+}
+
+/// Calls `request` until it succeeds or returns a non-transient error, retrying as specified by
+/// `config.retry`.
+///
+/// A retry waits for at least as long as the server requests with a `Retry-After` header.
+async fn with_backoff<T, F, Fut>(config: StreamConfig, request: F) -> Result<T, Error>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    retry_with_backoff_hint(&config.retry, is_transient, retry_after, request).await
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum PublishStatus {
     /// The object was valid and has been published to the network.
@@ -290,6 +420,87 @@ impl<E: EthSpec> Validator<E> {
         success.json().await.map_err(Error::from)
     }
 
+    /// Registers `validator_pubkeys` for a duties stream, returning the API token with which to
+    /// open the stream.
+    ///
+    /// If `api_token` is supplied, the validators previously registered for that token are
+    /// replaced and the same token is returned. Otherwise, the beacon node issues a new token.
+    pub async fn register_duties_stream(
+        &self,
+        api_token: Option<&str>,
+        validator_pubkeys: &[PublicKey],
+    ) -> Result<String, Error> {
+        let client = self.0.clone();
+
+        let request = DutiesSubscriptionRequest {
+            pubkeys: validator_pubkeys
+                .iter()
+                .map(|pubkey| pubkey.clone().into())
+                .collect(),
+        };
+
+        let url = self.url("duties/stream")?;
+        let response = client
+            .send(|| {
+                let builder = client.client.post(&url.to_string()).json(&request);
+                match api_token {
+                    Some(api_token) => builder.bearer_auth(api_token),
+                    None => builder,
+                }
+            })
+            .await?;
+        let response = error_for_status(response).await?;
+        response
+            .json::<DutiesSubscriptionResponse>()
+            .await
+            .map(|response| response.token)
+            .map_err(Error::from)
+    }
+
+    /// Opens the duties stream of `api_token`, returning a stream of the events sent by the beacon
+    /// node. The validators must first be registered with `register_duties_stream`.
+    ///
+    /// The stream ends when the beacon node closes the connection, or after the first error.
+    pub async fn stream_duties(
+        &self,
+        api_token: &str,
+    ) -> Result<impl Stream<Item = Result<DutiesChangedEvent, Error>>, Error> {
+        let url = self.url("duties/stream")?;
+        // The stream is long-lived, so it must not be subject to the client's request timeout.
+        let response = Client::new()
+            .get(&url.to_string())
+            .bearer_auth(api_token)
+            .send()
+            .await
+            .map_err(Error::from)?;
+        let response = error_for_status(response).await?;
+
+        Ok(stream::unfold(
+            Some((response, String::new())),
+            |state| async move {
+                let (mut response, mut buffer) = state?;
+                loop {
+                    // Server-sent events are terminated by an empty line.
+                    if let Some(end) = buffer.find("\n\n") {
+                        let message = buffer[..end].to_string();
+                        buffer.drain(..end + 2);
+                        match parse_duties_event(&message) {
+                            Ok(Some(event)) => return Some((Ok(event), Some((response, buffer)))),
+                            Ok(None) => continue,
+                            Err(e) => return Some((Err(e), None)),
+                        }
+                    }
+
+                    match response.chunk().await {
+                        Ok(Some(chunk)) => buffer.push_str(&String::from_utf8_lossy(&chunk)),
+                        Ok(None) => return None,
+                        Err(e) => return Some((Err(Error::from(e)), None)),
+                    }
+                }
+            },
+        ))
+    }
+
     /// Posts a block to the beacon node, expecting it to verify it and publish it to the network.
     pub async fn publish_block(&self, block: SignedBeaconBlock<E>) -> Result<PublishStatus, Error> {
         let client = self.0.clone();
@@ -314,6 +525,8 @@ impl<E: EthSpec> Validator<E> {
         slot: Slot,
         randao_reveal: Signature,
         graffiti: Option<Graffiti>,
+        fee_recipient: Option<Address>,
+        gas_limit: Option<u64>,
     ) -> Result<BeaconBlock<E>, Error> {
         let client = self.0.clone();
         let url = self.url("block")?;
@@ -327,9 +540,114 @@ impl<E: EthSpec> Validator<E> {
             query_pairs.push(("graffiti".into(), as_ssz_hex_string(&graffiti_bytes)));
         }
 
+        // Execution payloads are not yet produced, so the beacon node may ignore these.
+        if let Some(fee_recipient) = fee_recipient {
+            query_pairs.push((
+                "fee_recipient".into(),
+                format!("0x{}", hex::encode(fee_recipient.as_bytes())),
+            ));
+        }
+
+        if let Some(gas_limit) = gas_limit {
+            query_pairs.push(("gas_limit".into(), gas_limit.to_string()));
+        }
+
         client.json_get::<BeaconBlock<E>>(url, query_pairs).await
     }
 
+    /// Returns the sync committee memberships of the given validator pubkeys during the sync
+    /// committee period of the given epoch.
+    pub async fn get_sync_committee_duties(
+        &self,
+        epoch: Epoch,
+        validator_pubkeys: &[PublicKey],
+    ) -> Result<Vec<SyncDuty>, Error> {
+        let client = self.0.clone();
+
+        let bulk_request = ValidatorDutiesRequest {
+            epoch,
+            pubkeys: validator_pubkeys
+                .iter()
+                .map(|pubkey| pubkey.clone().into())
+                .collect(),
+        };
+
+        let url = self.url("sync_committee_duties")?;
+        let response = client.json_post::<_>(url, bulk_request).await?;
+        let success = error_for_status(response).await.map_err(Error::from)?;
+        success.json().await.map_err(Error::from)
+    }
+
+    /// Posts a list of sync committee messages to the beacon node, expecting it to verify them
+    /// and publish them to the network.
+    pub async fn publish_sync_committee_messages(
+        &self,
+        messages: Vec<SyncCommitteeMessage>,
+    ) -> Result<PublishStatus, Error> {
+        let client = self.0.clone();
+        let url = self.url("sync_committee_messages")?;
+        let response = client.json_post::<_>(url, messages).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(PublishStatus::Valid),
+            StatusCode::ACCEPTED => Ok(PublishStatus::Invalid(
+                response.text().await.map_err(Error::from)?,
+            )),
+            _ => response
+                .error_for_status()
+                .map_err(Error::from)
+                .map(|_| PublishStatus::Unknown),
+        }
+    }
+
+    /// Produces the aggregate of the sync committee messages of a subcommittee for the given block.
+    pub async fn produce_sync_committee_contribution(
+        &self,
+        slot: Slot,
+        subcommittee_index: u64,
+        beacon_block_root: Hash256,
+    ) -> Result<SyncCommitteeContribution<E>, Error> {
+        let query_params = vec![
+            ("slot".into(), format!("{}", slot)),
+            (
+                "subcommittee_index".into(),
+                format!("{}", subcommittee_index),
+            ),
+            (
+                "beacon_block_root".into(),
+                root_as_string(beacon_block_root),
+            ),
+        ];
+
+        let client = self.0.clone();
+        let url = self.url("sync_committee_contribution")?;
+        client.json_get(url, query_params).await
+    }
+
+    /// Posts a list of signed contributions and proofs to the beacon node, expecting it to verify
+    /// them and publish them to the network.
+    pub async fn publish_contribution_and_proofs(
+        &self,
+        signed_contribution_and_proofs: Vec<SignedContributionAndProof<E>>,
+    ) -> Result<PublishStatus, Error> {
+        let client = self.0.clone();
+        let url = self.url("contribution_and_proofs")?;
+        let response = client
+            .json_post::<_>(url, signed_contribution_and_proofs)
+            .await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(PublishStatus::Valid),
+            StatusCode::ACCEPTED => Ok(PublishStatus::Invalid(
+                response.text().await.map_err(Error::from)?,
+            )),
+            _ => response
+                .error_for_status()
+                .map_err(Error::from)
+                .map(|_| PublishStatus::Unknown),
+        }
+    }
+
     /// Subscribes a list of validators to particular slots for attestation production/publication.
     pub async fn subscribe(
         &self,
@@ -543,14 +861,147 @@ impl<E: EthSpec> Beacon<E> {
         client.json_get(url, query_params).await
     }
 
+    /// Returns a stream of the validators with the given `validator_pubkeys`, in order.
+    ///
+    /// The validators are requested in chunks of `config.chunk_size`, with each failed request
+    /// being retried as per `config`. If `state_root` is `None`, the head state root is resolved
+    /// before the first request and used for every chunk, so all responses are consistent even if
+    /// the head changes.
+    ///
+    /// The stream may be resumed after an error by calling this function again with the
+    /// validators which have not yet been returned and the same `state_root`.
+    pub fn stream_validators(
+        &self,
+        validator_pubkeys: Vec<PublicKey>,
+        state_root: Option<Hash256>,
+        config: StreamConfig,
+    ) -> impl Stream<Item = Result<ValidatorResponse, Error>> {
+        let beacon = Beacon(self.0.clone());
+        let chunks = validator_pubkeys
+            .chunks(std::cmp::max(config.chunk_size, 1))
+            .map(<[PublicKey]>::to_vec)
+            .collect::<Vec<_>>();
+
+        stream::once(async move {
+            let state_root = match state_root {
+                Some(root) => root,
+                None => with_backoff(config, || beacon.get_head()).await?.state_root,
+            };
+            Ok::<_, Error>((beacon, state_root))
+        })
+        .map_ok(move |(beacon, state_root)| {
+            stream::iter(chunks.clone()).then(move |chunk| {
+                let beacon = Beacon(beacon.0.clone());
+                async move {
+                    with_backoff(config, || {
+                        beacon.get_validators(chunk.clone(), Some(state_root))
+                    })
+                    .await
+                }
+            })
+        })
+        .try_flatten()
+        .map_ok(|responses| stream::iter(responses.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// Returns the canonical block headers (and block roots) in the given `epoch`, in order of
+    /// ascending slot. Skipped slots and slots beyond the head are omitted.
+    pub async fn get_headers(
+        &self,
+        epoch: Epoch,
+    ) -> Result<Vec<(SignedBeaconBlockHeader, Hash256)>, Error> {
+        let client = self.0.clone();
+
+        let url = self.url("headers")?;
+        client
+            .json_get::<Vec<HeaderResponse>>(
+                url,
+                vec![("epoch".into(), format!("{}", epoch.as_u64()))],
+            )
+            .await
+            .map(|responses| {
+                responses
+                    .into_iter()
+                    .map(|response| (response.header, response.root))
+                    .collect()
+            })
+    }
+
+    /// Returns the canonical block headers in the given `epoch`, each with a `BlockSummary` of
+    /// the contents of its block.
+    pub async fn get_header_summaries(&self, epoch: Epoch) -> Result<Vec<HeaderResponse>, Error> {
+        let client = self.0.clone();
+
+        let url = self.url("headers")?;
+        client
+            .json_get::<Vec<HeaderResponse>>(
+                url,
+                vec![
+                    ("epoch".into(), format!("{}", epoch.as_u64())),
+                    ("summary".into(), "true".into()),
+                ],
+            )
+            .await
+    }
+
+    /// Returns a stream of the canonical block headers (and block roots) for each slot in
+    /// `slots`, in order. Skipped slots and slots beyond the head are omitted.
+    ///
+    /// Up to `config.chunk_size` blocks are requested concurrently, with each failed request
+    /// being retried as per `config`.
+    ///
+    /// The stream may be resumed after an error by calling this function again with a range
+    /// starting after the slot of the last header returned.
+    pub fn stream_headers(
+        &self,
+        slots: Range<Slot>,
+        config: StreamConfig,
+    ) -> impl Stream<Item = Result<(SignedBeaconBlockHeader, Hash256), Error>> {
+        let beacon = Beacon(self.0.clone());
+
+        stream::iter(slots.start.as_u64()..slots.end.as_u64())
+            .map(move |slot| {
+                let beacon = Beacon(beacon.0.clone());
+                let slot = Slot::new(slot);
+                async move {
+                    match with_backoff(config, || beacon.get_block_by_slot(slot)).await {
+                        // The API returns the prior block for a skipped slot.
+                        Ok((block, root)) if block.message.slot == slot => {
+                            let header = SignedBeaconBlockHeader {
+                                message: block.message.block_header(),
+                                signature: block.signature,
+                            };
+                            Ok(Some((header, root)))
+                        }
+                        Ok(_) => Ok(None),
+                        Err(Error::DidNotSucceed { status, .. })
+                            if status == StatusCode::NOT_FOUND =>
+                        {
+                            Ok(None)
+                        }
+                        Err(e) => Err(e),
+                    }
+                }
+            })
+            .buffered(std::cmp::max(config.chunk_size, 1))
+            .try_filter_map(future::ok)
+    }
+
     /// Returns committees at the given epoch.
+    ///
+    /// Equivalent to `query_committees` with only the epoch set.
     pub async fn get_committees(&self, epoch: Epoch) -> Result<Vec<Committee>, Error> {
+        self.query_committees(CommitteesQuery::default().epoch(epoch))
+            .await
+    }
+
+    /// Returns the committees which match `query`.
+    pub async fn query_committees(&self, query: CommitteesQuery) -> Result<Vec<Committee>, Error> {
         let client = self.0.clone();
 
         let url = self.url("committees")?;
-        client
-            .json_get(url, vec![("epoch".into(), format!("{}", epoch.as_u64()))])
-            .await
+        client.json_get(url, query.query_pairs()).await
     }
 
     pub async fn proposer_slashing(
@@ -576,6 +1027,15 @@ impl<E: EthSpec> Beacon<E> {
         let success = error_for_status(response).await.map_err(Error::from)?;
         success.json().await.map_err(Error::from)
     }
+
+    pub async fn voluntary_exit(&self, exit: SignedVoluntaryExit) -> Result<bool, Error> {
+        let client = self.0.clone();
+
+        let url = self.url("voluntary_exit")?;
+        let response = client.json_post::<_>(url, exit).await?;
+        let success = error_for_status(response).await.map_err(Error::from)?;
+        success.json().await.map_err(Error::from)
+    }
 }
 
 /// Provides the functions on the `/spec` endpoint of the node.
@@ -595,6 +1055,33 @@ impl<E: EthSpec> Spec<E> {
         let url = self.url("eth2_config")?;
         client.json_get(url, vec![]).await
     }
+
+    /// Returns the spec constants of the node, as they appear in the config files of the specs.
+    pub async fn get_yaml_config(&self) -> Result<YamlConfig, Error> {
+        let client = self.0.clone();
+        let url = self.url("yaml_config")?;
+        client.json_get(url, vec![]).await
+    }
+}
+
+/// Provides the functions on the `/config` endpoint of the node.
+#[derive(Clone)]
+pub struct Config<E>(HttpClient<E>);
+
+impl<E: EthSpec> Config<E> {
+    fn url(&self, path: &str) -> Result<Url, Error> {
+        self.0
+            .url("config/")
+            .and_then(move |url| url.join(path).map_err(Error::from))
+            .map_err(Into::into)
+    }
+
+    /// Returns the forks known to the node, in the order in which they activate.
+    pub async fn get_fork_schedule(&self) -> Result<Vec<Fork>, Error> {
+        let client = self.0.clone();
+        let url = self.url("fork_schedule")?;
+        client.json_get(url, vec![]).await
+    }
 }
 
 /// Provides the functions on the `/node` endpoint of the node.
@@ -626,6 +1113,12 @@ impl<E: EthSpec> Node<E> {
         let url = self.url("syncing")?;
         client.json_get(url, vec![]).await
     }
+
+    pub async fn get_capabilities(&self) -> Result<Capabilities, Error> {
+        let client = self.0.clone();
+        let url = self.url("capabilities")?;
+        client.json_get(url, vec![]).await
+    }
 }
 
 /// Provides the functions on the `/advanced` endpoint of the node.
@@ -705,6 +1198,25 @@ pub struct StateResponse<T: EthSpec> {
     pub root: Hash256,
 }
 
+/// Parses a single server-sent event, returning `None` if it is not a `duties` event.
+fn parse_duties_event(message: &str) -> Result<Option<DutiesChangedEvent>, Error> {
+    let mut event = None;
+    let mut data = None;
+
+    for line in message.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            event = Some(value.trim());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data = Some(value.trim());
+        }
+    }
+
+    match (event, data) {
+        (Some("duties"), Some(data)) => serde_json::from_str(data).map(Some).map_err(Error::from),
+        _ => Ok(None),
+    }
+}
+
 fn root_as_string(root: Hash256) -> String {
     format!("0x{:?}", root)
 }
@@ -730,3 +1242,79 @@ impl From<serde_json::Error> for Error {
         Error::SerdeJsonError(e)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use types::MinimalEthSpec;
+
+    const SHED: &str = "HTTP/1.1 503 Service Unavailable\r\nRetry-After: 0\r\n\
+                        Content-Length: 2\r\nConnection: close\r\n\r\n{}";
+    const UNAVAILABLE: &str = "HTTP/1.1 503 Service Unavailable\r\n\
+                               Content-Length: 2\r\nConnection: close\r\n\r\n{}";
+    const OK: &str = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                      Content-Length: 2\r\nConnection: close\r\n\r\n42";
+
+    /// Answers one request with each of `responses` in turn, returning a client for the server.
+    fn serve(responses: Vec<&'static str>) -> HttpClient<MinimalEthSpec> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for (response, stream) in responses.into_iter().zip(listener.incoming()) {
+                let mut stream = stream.unwrap();
+                let mut request = vec![];
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let read = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..read]);
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let mut client = HttpClient::new(url, Duration::from_secs(5)).unwrap();
+        client.retry = RetryConfig::without_backoff(2);
+        client
+    }
+
+    async fn get(client: &HttpClient<MinimalEthSpec>) -> Result<u64, Error> {
+        client.json_get(client.url("")?, vec![]).await
+    }
+
+    #[tokio::test]
+    async fn shed_requests_are_resent() {
+        let client = serve(vec![SHED, SHED, OK]);
+        assert_eq!(get(&client).await.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn shed_requests_fail_once_retries_are_exhausted() {
+        let client = serve(vec![SHED, SHED, SHED, OK]);
+        match get(&client).await {
+            Err(Error::DidNotSucceed {
+                status,
+                retry_after,
+                ..
+            }) => {
+                assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+                assert_eq!(retry_after, Some(Duration::from_secs(0)));
+            }
+            other => panic!("expected the request to be shed, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn unavailable_without_retry_after_is_not_resent() {
+        let client = serve(vec![UNAVAILABLE, OK]);
+        assert!(matches!(
+            get(&client).await,
+            Err(Error::DidNotSucceed {
+                retry_after: None,
+                ..
+            })
+        ));
+    }
+}
@@ -1,4 +1,5 @@
-use hyper::{Body, Response, StatusCode};
+use hyper::{header, Body, Response, StatusCode};
+use serde::{Deserialize, Serialize};
 use std::error::Error as StdError;
 
 #[derive(PartialEq, Debug, Clone)]
@@ -8,10 +9,27 @@ pub enum ApiError {
     NotImplemented(String),
     BadRequest(String),
     NotFound(String),
+    Unauthorized(String),
     UnsupportedType(String),
     ImATeapot(String),       // Just in case.
     ProcessingError(String), // A 202 error, for when a block/attestation cannot be processed, but still transmitted.
     InvalidHeaderValue(String),
+    ServiceUnavailable(String),
+    /// A 503 error for a request which was shed due to load, and may be retried later.
+    Overloaded(ThrottleInfo),
+}
+
+/// The JSON body of a response to a request which was throttled or shed due to load.
+///
+/// The `retry_after_secs` is also sent in the `Retry-After` header.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct ThrottleInfo {
+    /// Why the request was not served.
+    pub reason: String,
+    /// The number of requests waiting to be served when the request was received.
+    pub queue_depth: u64,
+    /// The number of seconds the client should wait before retrying.
+    pub retry_after_secs: u64,
 }
 
 pub type ApiResult = Result<Response<Body>, ApiError>;
@@ -24,16 +42,33 @@ impl ApiError {
             ApiError::NotImplemented(desc) => (StatusCode::NOT_IMPLEMENTED, desc),
             ApiError::BadRequest(desc) => (StatusCode::BAD_REQUEST, desc),
             ApiError::NotFound(desc) => (StatusCode::NOT_FOUND, desc),
+            ApiError::Unauthorized(desc) => (StatusCode::UNAUTHORIZED, desc),
             ApiError::UnsupportedType(desc) => (StatusCode::UNSUPPORTED_MEDIA_TYPE, desc),
             ApiError::ImATeapot(desc) => (StatusCode::IM_A_TEAPOT, desc),
             ApiError::ProcessingError(desc) => (StatusCode::ACCEPTED, desc),
             ApiError::InvalidHeaderValue(desc) => (StatusCode::INTERNAL_SERVER_ERROR, desc),
+            ApiError::ServiceUnavailable(desc) => (StatusCode::SERVICE_UNAVAILABLE, desc),
+            ApiError::Overloaded(info) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                serde_json::to_string(&info).unwrap_or(info.reason),
+            ),
         }
     }
 }
 
 impl Into<Response<Body>> for ApiError {
     fn into(self) -> Response<Body> {
+        if let ApiError::Overloaded(info) = &self {
+            let retry_after = info.retry_after_secs;
+            let (status_code, desc) = self.status_code();
+            return Response::builder()
+                .status(status_code)
+                .header("content-type", "application/json")
+                .header(header::RETRY_AFTER, retry_after)
+                .body(Body::from(desc))
+                .expect("Response should always be created.");
+        }
+
         let (status_code, desc) = self.status_code();
         Response::builder()
             .status(status_code)
@@ -4,7 +4,10 @@ use bls::PublicKeyBytes;
 use serde::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
 use types::beacon_state::EthSpec;
-use types::{BeaconState, CommitteeIndex, Hash256, SignedBeaconBlock, Slot, Validator};
+use types::{
+    BeaconBlock, BeaconState, CommitteeIndex, Epoch, Hash256, SignedBeaconBlock,
+    SignedBeaconBlockHeader, Slot, Validator,
+};
 
 /// Information about a block that is at the head of a chain. May or may not represent the
 /// canonical head.
@@ -14,11 +17,81 @@ pub struct HeadBeaconBlock {
     pub beacon_block_slot: Slot,
 }
 
+/// Sent on the `lagging` topic of the fork stream when the head is persistently being updated
+/// late in the slot.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HeadLaggingEvent {
+    pub median_delay_ms: u64,
+    pub threshold_ms: u64,
+}
+
+/// Sent on the `duties_changed` topic of the fork stream when validator duties for `epoch` (and
+/// the next epoch) may have changed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DutiesDependencyEvent {
+    pub epoch: Epoch,
+    /// The root of the block at the last slot of the epoch prior to `epoch`.
+    pub dependent_root: Hash256,
+    /// The root of the block at the last slot of the epoch two prior to `epoch`.
+    pub previous_dependent_root: Hash256,
+}
+
+/// A contiguous segment of the canonical chain.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Encode, Decode)]
+#[serde(bound = "T: EthSpec")]
+pub struct ChainSegment<T: EthSpec> {
+    /// The root of each block in `blocks`, in the same order.
+    pub roots: Vec<Hash256>,
+    /// The blocks of the segment in ascending slot order. Skipped slots have no block.
+    pub blocks: Vec<SignedBeaconBlock<T>>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Encode, Decode)]
 #[serde(bound = "T: EthSpec")]
 pub struct BlockResponse<T: EthSpec> {
     pub root: Hash256,
+    pub slot: Slot,
+    /// `true` if the block is in the canonical chain.
+    pub canonical: bool,
     pub beacon_block: SignedBeaconBlock<T>,
+    /// Only present if requested with `summary=true`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<BlockSummary>,
+}
+
+/// The header of a block in the canonical chain, and the root of the block.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Encode, Decode)]
+pub struct HeaderResponse {
+    pub root: Hash256,
+    pub header: SignedBeaconBlockHeader,
+    /// Only present if requested with `summary=true`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<BlockSummary>,
+}
+
+/// A summary of the contents of a block, so that a list of blocks can be described without
+/// fetching each of their bodies.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Encode, Decode)]
+pub struct BlockSummary {
+    pub proposer_index: u64,
+    pub attestation_count: u64,
+    pub deposit_count: u64,
+    pub voluntary_exit_count: u64,
+    pub proposer_slashing_count: u64,
+    pub attester_slashing_count: u64,
+}
+
+impl BlockSummary {
+    pub fn from_block<T: EthSpec>(block: &BeaconBlock<T>) -> Self {
+        Self {
+            proposer_index: block.proposer_index,
+            attestation_count: block.body.attestations.len() as u64,
+            deposit_count: block.body.deposits.len() as u64,
+            voluntary_exit_count: block.body.voluntary_exits.len() as u64,
+            proposer_slashing_count: block.body.proposer_slashings.len() as u64,
+            attester_slashing_count: block.body.attester_slashings.len() as u64,
+        }
+    }
 }
 
 /// Information about the block and state that are at head of the beacon chain.
@@ -57,9 +130,59 @@ pub struct Committee {
     pub committee: Vec<usize>,
 }
 
+/// The query parameters of `/beacon/committees`.
+///
+/// Every parameter is optional. The epoch defaults to the epoch of `slot` if it is present, or
+/// the current epoch otherwise. The `slot` and `index` filter the committees of that epoch.
+///
+/// Construct it with `CommitteesQuery::default()` and the builder methods, so that new parameters
+/// may be added without breaking callers.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct CommitteesQuery {
+    pub epoch: Option<Epoch>,
+    pub slot: Option<Slot>,
+    pub index: Option<CommitteeIndex>,
+}
+
+impl CommitteesQuery {
+    pub fn epoch(mut self, epoch: Epoch) -> Self {
+        self.epoch = Some(epoch);
+        self
+    }
+
+    pub fn slot(mut self, slot: Slot) -> Self {
+        self.slot = Some(slot);
+        self
+    }
+
+    pub fn index(mut self, index: CommitteeIndex) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Returns the `(key, value)` pairs of the parameters which are present.
+    pub fn query_pairs(&self) -> Vec<(String, String)> {
+        let mut pairs = vec![];
+        if let Some(epoch) = self.epoch {
+            pairs.push(("epoch".into(), epoch.as_u64().to_string()));
+        }
+        if let Some(slot) = self.slot {
+            pairs.push(("slot".into(), slot.as_u64().to_string()));
+        }
+        if let Some(index) = self.index {
+            pairs.push(("index".into(), index.to_string()));
+        }
+        pairs
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Encode, Decode)]
 #[serde(bound = "T: EthSpec")]
 pub struct StateResponse<T: EthSpec> {
     pub root: Hash256,
+    pub slot: Slot,
+    /// `true` if the state is in the canonical chain.
+    pub canonical: bool,
     pub beacon_state: BeaconState<T>,
 }
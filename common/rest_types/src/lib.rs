@@ -9,14 +9,16 @@ mod handler;
 mod node;
 mod validator;
 
-pub use api_error::{ApiError, ApiResult};
+pub use api_error::{ApiError, ApiResult, ThrottleInfo};
 pub use beacon::{
-    BlockResponse, CanonicalHeadResponse, Committee, HeadBeaconBlock, StateResponse,
+    BlockResponse, BlockSummary, CanonicalHeadResponse, ChainSegment, Committee, CommitteesQuery,
+    DutiesDependencyEvent, HeadBeaconBlock, HeadLaggingEvent, HeaderResponse, StateResponse,
     ValidatorRequest, ValidatorResponse,
 };
 pub use consensus::{IndividualVote, IndividualVotesRequest, IndividualVotesResponse};
 pub use handler::{ApiEncodingFormat, Handler};
-pub use node::{Health, SyncingResponse, SyncingStatus};
+pub use node::{Capabilities, Health, NamespaceCapability, SyncingResponse, SyncingStatus};
 pub use validator::{
+    DutiesChangedEvent, DutiesSubscriptionRequest, DutiesSubscriptionResponse, SyncDuty,
     ValidatorDutiesRequest, ValidatorDuty, ValidatorDutyBytes, ValidatorSubscription,
 };
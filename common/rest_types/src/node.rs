@@ -32,6 +32,38 @@ pub struct SyncingResponse {
     pub is_syncing: bool,
     /// The current sync status.
     pub sync_status: SyncingStatus,
+    /// Is the node performing database maintenance (e.g., migrating finalized states to the
+    /// freezer database), during which responses may be slower than usual.
+    #[serde(default)]
+    pub store_maintenance: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// The response for the /node/capabilities HTTP GET.
+///
+/// Describes which optional parts of the API are available, so that a client may check before
+/// calling an endpoint rather than interpreting a 404.
+pub struct Capabilities {
+    /// Each namespace of the API, and whether it is enabled.
+    pub namespaces: Vec<NamespaceCapability>,
+    /// The path prefixes for which requests are refused, which may disable only part of a
+    /// namespace.
+    pub disabled_routes: Vec<String>,
+    /// Is the node connected to the network. If not, the `/network` endpoints and those which
+    /// publish to the network are unavailable.
+    pub network: bool,
+    /// Can the server's settings be reloaded with `/lighthouse/http_config/reload`.
+    pub config_reload: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// A namespace of the API, e.g. `/advanced`, or an endpoint which is optional in its own right.
+pub struct NamespaceCapability {
+    /// The path prefix of the namespace.
+    pub path: String,
+    /// Is the namespace served. A namespace may be enabled while some of its endpoints are
+    /// listed in `Capabilities::disabled_routes`.
+    pub enabled: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
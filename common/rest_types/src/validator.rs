@@ -1,7 +1,7 @@
 use bls::{PublicKey, PublicKeyBytes};
 use serde::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
-use types::{CommitteeIndex, Epoch, Slot};
+use types::{CommitteeIndex, Epoch, Hash256, Slot};
 
 /// A Validator duty with the validator public key represented a `PublicKeyBytes`.
 pub type ValidatorDutyBytes = ValidatorDutyBase<PublicKeyBytes>;
@@ -73,6 +73,45 @@ pub struct ValidatorSubscription {
     pub is_aggregator: bool,
 }
 
+/// Registers the validators whose duties are reported on a `duties` stream.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Encode, Decode)]
+pub struct DutiesSubscriptionRequest {
+    pub pubkeys: Vec<PublicKeyBytes>,
+}
+
+/// The API token with which to open the `duties` stream of the registered validators.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct DutiesSubscriptionResponse {
+    pub token: String,
+}
+
+/// Sent on the `duties` stream when the duties of some registered validators have changed.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct DutiesChangedEvent {
+    /// The epoch of the head. Duties for this epoch and the next may have changed.
+    pub epoch: Epoch,
+    /// The root of the block at the last slot of the epoch prior to `epoch`, which the proposer
+    /// duties of `epoch` and the attester duties of the next epoch depend upon.
+    pub dependent_root: Hash256,
+    /// The root of the block at the last slot of the epoch two prior to `epoch`, which the
+    /// attester duties of `epoch` depend upon.
+    pub previous_dependent_root: Hash256,
+    /// The indices of the registered validators whose duties have changed.
+    pub validator_indices: Vec<u64>,
+}
+
+/// The membership of a validator in the sync committee of some period.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct SyncDuty {
+    /// The validator's BLS public key, uniquely identifying them.
+    pub validator_pubkey: PublicKeyBytes,
+    /// The validator's index in `state.validators`
+    pub validator_index: u64,
+    /// The positions of the validator in the sync committee. A validator may appear more than
+    /// once.
+    pub validator_sync_committee_indices: Vec<u64>,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -0,0 +1,244 @@
+//! Retries fallible operations, waiting for an exponentially increasing and randomly jittered
+//! delay between attempts.
+//!
+//! Shared by the components which talk to remote services (the eth1 node, the beacon node HTTP
+//! API and peers during sync) so that retry behaviour is defined in one place.
+use rand::Rng;
+use serde_derive::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::delay_for;
+
+/// Controls how many times an operation is retried and how long to wait between attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// The number of times a failed operation is retried before its error is returned.
+    pub max_retries: usize,
+    /// The delay before the first retry, which doubles with each subsequent retry.
+    pub initial_backoff: Duration,
+    /// The upper limit of the delay between two attempts.
+    pub max_backoff: Duration,
+    /// If true, each delay is chosen uniformly at random between half and all of the backoff, so
+    /// that many clients failing at once do not retry in lockstep.
+    pub jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// A config which retries up to `max_retries` times without waiting between attempts.
+    ///
+    /// Useful when each retry is directed at a different server (e.g., another peer).
+    pub const fn without_backoff(max_retries: usize) -> Self {
+        Self {
+            max_retries,
+            initial_backoff: Duration::from_secs(0),
+            max_backoff: Duration::from_secs(0),
+            jitter: false,
+        }
+    }
+
+    /// Returns `true` if an operation which has already been retried `retries` times must not be
+    /// retried again.
+    pub fn is_exhausted(&self, retries: usize) -> bool {
+        retries >= self.max_retries
+    }
+
+    /// Returns the backoff before retry number `retry` (where the first retry is `0`), ignoring
+    /// jitter.
+    pub fn backoff(&self, retry: usize) -> Duration {
+        let multiplier = 1_u32.checked_shl(retry as u32).unwrap_or(u32::MAX);
+
+        self.initial_backoff
+            .checked_mul(multiplier)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+
+    /// Returns the delay before retry number `retry`, including jitter if enabled.
+    pub fn delay(&self, retry: usize) -> Duration {
+        let backoff = self.backoff(retry);
+
+        if self.jitter && backoff > Duration::from_millis(1) {
+            let millis = backoff.as_millis() as u64;
+            Duration::from_millis(rand::thread_rng().gen_range(millis / 2, millis + 1))
+        } else {
+            backoff
+        }
+    }
+}
+
+/// Calls `operation` until it succeeds, returns an error for which `should_retry` is `false`, or
+/// has been retried `config.max_retries` times, waiting for `config.delay(retry)` before each
+/// retry.
+pub async fn retry_with_backoff<T, E, F, Fut, P>(
+    config: &RetryConfig,
+    should_retry: P,
+    operation: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E) -> bool,
+{
+    retry_with_backoff_hint(config, should_retry, |_| None, operation).await
+}
+
+/// As `retry_with_backoff`, except that the delay before a retry is at least the one returned by
+/// `retry_after` for the error, if any.
+///
+/// This allows a server to slow its clients down (e.g., with a `Retry-After` header).
+pub async fn retry_with_backoff_hint<T, E, F, Fut, P, H>(
+    config: &RetryConfig,
+    should_retry: P,
+    retry_after: H,
+    mut operation: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E) -> bool,
+    H: Fn(&E) -> Option<Duration>,
+{
+    let mut retries = 0;
+
+    loop {
+        match operation().await {
+            Err(e) if !config.is_exhausted(retries) && should_retry(&e) => {
+                let delay = match retry_after(&e) {
+                    Some(hint) => std::cmp::max(hint, config.delay(retries)),
+                    None => config.delay(retries),
+                };
+                delay_for(delay).await;
+                retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn config(max_retries: usize) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(4),
+            jitter: false,
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_until_max() {
+        let config = config(0);
+        let backoffs = (0..5).map(|i| config.backoff(i)).collect::<Vec<_>>();
+        let expected = [1, 2, 4, 4, 4]
+            .iter()
+            .map(|ms| Duration::from_millis(*ms))
+            .collect::<Vec<_>>();
+
+        assert_eq!(backoffs, expected);
+        assert_eq!(config.backoff(usize::MAX), config.max_backoff);
+    }
+
+    #[test]
+    fn jitter_within_bounds() {
+        let config = RetryConfig {
+            jitter: true,
+            ..RetryConfig::default()
+        };
+
+        for retry in 0..8 {
+            let backoff = config.backoff(retry);
+            let delay = config.delay(retry);
+            assert!(delay <= backoff, "delay should not exceed backoff");
+            assert!(
+                delay >= backoff / 2,
+                "delay should be at least half backoff"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_until_success() {
+        let attempts = Cell::new(0);
+        let result = retry_with_backoff(
+            &config(3),
+            |_: &()| true,
+            || {
+                attempts.set(attempts.get() + 1);
+                let attempt = attempts.get();
+                async move {
+                    if attempt < 3 {
+                        Err(())
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(result, Ok(3));
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let attempts = Cell::new(0);
+        let result: Result<(), ()> = retry_with_backoff(
+            &config(2),
+            |_| true,
+            || {
+                attempts.set(attempts.get() + 1);
+                async { Err(()) }
+            },
+        )
+        .await;
+
+        assert_eq!(result, Err(()));
+        assert_eq!(attempts.get(), 3, "should make one attempt and two retries");
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_permanent_errors() {
+        let attempts = Cell::new(0);
+        let result: Result<(), bool> = retry_with_backoff(
+            &config(5),
+            |transient| *transient,
+            || {
+                attempts.set(attempts.get() + 1);
+                async { Err(false) }
+            },
+        )
+        .await;
+
+        assert_eq!(result, Err(false));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn waits_for_retry_after_hint() {
+        let hint = Duration::from_millis(50);
+        let start = tokio::time::Instant::now();
+        let result: Result<(), ()> =
+            retry_with_backoff_hint(&config(1), |_| true, |_| Some(hint), || async { Err(()) })
+                .await;
+
+        assert_eq!(result, Err(()));
+        assert!(
+            start.elapsed() >= hint,
+            "should wait for at least the hinted delay"
+        );
+    }
+}
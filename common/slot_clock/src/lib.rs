@@ -43,6 +43,9 @@ pub trait SlotClock: Send + Sync + Sized {
     /// Returns the duration from now until `slot`.
     fn duration_to_slot(&self, slot: Slot) -> Option<Duration>;
 
+    /// Returns the duration between the UNIX epoch and the start of `slot`.
+    fn start_of(&self, slot: Slot) -> Option<Duration>;
+
     /// Returns the duration until the next slot.
     fn duration_to_next_slot(&self) -> Option<Duration>;
 
@@ -62,4 +65,11 @@ pub trait SlotClock: Send + Sync + Sized {
         self.slot_of(self.now_duration()?.checked_sub(tolerance)?)
             .or_else(|| Some(self.genesis_slot()))
     }
+
+    /// Returns the duration elapsed since the start of `slot`.
+    ///
+    /// Returns `None` if `slot` has not yet started.
+    fn duration_since_start_of(&self, slot: Slot) -> Option<Duration> {
+        self.now_duration()?.checked_sub(self.start_of(slot)?)
+    }
 }
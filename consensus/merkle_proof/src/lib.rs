@@ -214,6 +214,44 @@ pub fn verify_merkle_proof(
     }
 }
 
+/// Verify a proof that `leaf` exists at the generalized index `gindex` of a Merkle tree rooted at
+/// `root`.
+///
+/// The depth and index of the leaf are both implied by `gindex`, see `generalized_index`.
+pub fn verify_merkle_proof_at_gindex(leaf: H256, branch: &[H256], gindex: u64, root: H256) -> bool {
+    if gindex == 0 {
+        return false;
+    }
+    let depth = generalized_index_depth(gindex);
+    let index = (gindex - (1 << depth)) as usize;
+    verify_merkle_proof(leaf, branch, depth, index, root)
+}
+
+/// Return the generalized index of the node at `index` of the layer at `depth` of a tree.
+///
+/// Generalized indices number the nodes of a tree from the root (`1`), such that the children of
+/// node `i` are `2 * i` and `2 * i + 1`.
+pub fn generalized_index(depth: usize, index: usize) -> u64 {
+    (1 << depth) + index as u64
+}
+
+/// Return the depth of the node with generalized index `gindex`, i.e. the length of its proof.
+pub fn generalized_index_depth(gindex: u64) -> usize {
+    (63 - gindex.leading_zeros()) as usize
+}
+
+/// Combine generalized indices into a tree, each relative to the node of the one before it, into
+/// a single generalized index relative to the root of the outermost tree.
+///
+/// E.g., this joins the index of a list within a container to the index of an item within that
+/// list.
+pub fn concat_generalized_indices(indices: &[u64]) -> u64 {
+    indices.iter().fold(1, |acc, &gindex| {
+        let depth = generalized_index_depth(gindex);
+        (acc << depth) + (gindex - (1 << depth))
+    })
+}
+
 /// Compute a root hash from a leaf and a Merkle proof.
 fn merkle_root_from_branch(leaf: H256, branch: &[H256], depth: usize, index: usize) -> H256 {
     assert_eq!(branch.len(), depth, "proof length should equal depth");
@@ -286,6 +324,39 @@ mod tests {
         TestResult::from_bool(proofs_ok)
     }
 
+    #[quickcheck]
+    fn quickcheck_verify_at_gindex(int_leaves: Vec<u64>, depth: usize) -> TestResult {
+        if depth > MAX_TREE_DEPTH || int_leaves.len() > 2usize.pow(depth as u32) {
+            return TestResult::discard();
+        }
+
+        let leaves: Vec<_> = int_leaves.into_iter().map(H256::from_low_u64_be).collect();
+        let merkle_tree = MerkleTree::create(&leaves, depth);
+        let merkle_root = merkle_tree.hash();
+
+        let proofs_ok = (0..leaves.len()).all(|i| {
+            let (leaf, branch) = merkle_tree.generate_proof(i, depth);
+            let gindex = generalized_index(depth, i);
+            generalized_index_depth(gindex) == depth
+                && verify_merkle_proof_at_gindex(leaf, &branch, gindex, merkle_root)
+        });
+
+        TestResult::from_bool(proofs_ok)
+    }
+
+    #[test]
+    fn concat_gindices() {
+        // The right child of the root, then the left child of that node.
+        assert_eq!(concat_generalized_indices(&[3, 2]), 6);
+        // The root is the identity.
+        assert_eq!(concat_generalized_indices(&[1, 5, 1]), 5);
+        assert_eq!(concat_generalized_indices(&[]), 1);
+        assert_eq!(
+            concat_generalized_indices(&[generalized_index(3, 5), 2, generalized_index(7, 9)]),
+            generalized_index(11, (5 << 8) + 9)
+        );
+    }
+
     #[test]
     fn sparse_zero_correct() {
         let depth = 2;
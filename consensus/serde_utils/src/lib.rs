@@ -0,0 +1,3 @@
+pub mod quoted_u64;
+pub mod quoted_u64_option;
+pub mod quoted_u64_or_default;
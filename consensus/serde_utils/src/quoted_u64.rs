@@ -0,0 +1,143 @@
+//! Formats `u64` (and types convertible to/from `u64`) as a decimal string, e.g. `"42"`.
+//!
+//! The default `deserialize` function is lenient and will also accept a plain JSON number.
+//! Use the `require_quotes` module to reject unquoted values.
+//!
+//! ## Example
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Foo {
+//!     #[serde(with = "serde_utils::quoted_u64")]
+//!     bar: u64,
+//! }
+//! ```
+use serde::{Deserializer, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Visitor which accepts a quoted integer, and optionally an unquoted one.
+pub struct QuotedIntVisitor<T> {
+    require_quotes: bool,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> QuotedIntVisitor<T> {
+    pub fn new(require_quotes: bool) -> Self {
+        Self {
+            require_quotes,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> serde::de::Visitor<'a> for QuotedIntVisitor<T>
+where
+    T: From<u64> + Into<u64> + Copy,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if self.require_quotes {
+            write!(formatter, "a quoted integer")
+        } else {
+            write!(formatter, "a quoted or unquoted integer")
+        }
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        s.parse::<u64>()
+            .map(T::from)
+            .map_err(serde::de::Error::custom)
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        if self.require_quotes {
+            Err(serde::de::Error::custom(
+                "received unquoted integer when quotes are required",
+            ))
+        } else {
+            Ok(T::from(v))
+        }
+    }
+}
+
+/// Serialize with quotes.
+pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: From<u64> + Into<u64> + Copy,
+{
+    let v: u64 = (*value).into();
+    serializer.serialize_str(&format!("{}", v))
+}
+
+/// Deserialize with or without quotes.
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: From<u64> + Into<u64> + Copy,
+{
+    deserializer.deserialize_any(QuotedIntVisitor::new(false))
+}
+
+/// Requires quotes when deserializing.
+///
+/// Usage: `#[serde(with = "serde_utils::quoted_u64::require_quotes")]`.
+pub mod require_quotes {
+    pub use super::serialize;
+    use super::*;
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<u64> + Into<u64> + Copy,
+    {
+        deserializer.deserialize_any(QuotedIntVisitor::new(true))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(transparent)]
+    struct Lenient(#[serde(with = "super")] u64);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(transparent)]
+    struct Strict(#[serde(with = "super::require_quotes")] u64);
+
+    #[test]
+    fn lenient_quoted_and_unquoted() {
+        assert_eq!(
+            serde_json::from_str::<Lenient>("\"1\"").unwrap(),
+            Lenient(1)
+        );
+        assert_eq!(serde_json::from_str::<Lenient>("1").unwrap(), Lenient(1));
+        assert!(serde_json::from_str::<Lenient>("\"-1\"").is_err());
+        assert!(serde_json::from_str::<Lenient>("\"cats\"").is_err());
+    }
+
+    #[test]
+    fn strict_requires_quotes() {
+        assert_eq!(serde_json::from_str::<Strict>("\"1\"").unwrap(), Strict(1));
+        assert!(serde_json::from_str::<Strict>("1").is_err());
+    }
+
+    #[test]
+    fn serialize_quoted() {
+        assert_eq!(serde_json::to_string(&Lenient(42)).unwrap(), "\"42\"");
+        assert_eq!(
+            serde_json::to_string(&Strict(u64::max_value())).unwrap(),
+            "\"18446744073709551615\""
+        );
+    }
+}
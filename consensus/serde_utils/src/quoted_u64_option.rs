@@ -0,0 +1,159 @@
+//! Formats `Option<u64>` (and `Option` of types convertible to/from `u64`) as a decimal string or
+//! `null`, e.g. `"42"`.
+//!
+//! As with `quoted_u64`, the default `deserialize` function is lenient and will also accept a
+//! plain JSON number. Use the `require_quotes` module to reject unquoted values.
+//!
+//! Serde only treats a missing field as `None` for plain `Option` fields, so fields using this
+//! module should also be marked `#[serde(default)]` if they may be absent.
+//!
+//! ## Example
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Foo {
+//!     #[serde(default, with = "serde_utils::quoted_u64_option")]
+//!     bar: Option<u64>,
+//! }
+//! ```
+use crate::quoted_u64::QuotedIntVisitor;
+use serde::{Deserializer, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Visitor which accepts `null` or a quoted integer, and optionally an unquoted one.
+pub struct QuotedIntOptionVisitor<T> {
+    require_quotes: bool,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> QuotedIntOptionVisitor<T> {
+    pub fn new(require_quotes: bool) -> Self {
+        Self {
+            require_quotes,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> serde::de::Visitor<'a> for QuotedIntOptionVisitor<T>
+where
+    T: From<u64> + Into<u64> + Copy,
+{
+    type Value = Option<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if self.require_quotes {
+            write!(formatter, "null or a quoted integer")
+        } else {
+            write!(formatter, "null or a quoted or unquoted integer")
+        }
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer
+            .deserialize_any(QuotedIntVisitor::new(self.require_quotes))
+            .map(Some)
+    }
+}
+
+/// Serialize `Some` with quotes, and `None` as `null`.
+pub fn serialize<S, T>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: From<u64> + Into<u64> + Copy,
+{
+    match value {
+        Some(value) => crate::quoted_u64::serialize(value, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Deserialize `null` as `None`, and an integer with or without quotes as `Some`.
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: From<u64> + Into<u64> + Copy,
+{
+    deserializer.deserialize_option(QuotedIntOptionVisitor::new(false))
+}
+
+/// Requires quotes when deserializing a `Some` value.
+///
+/// Usage: `#[serde(default, with = "serde_utils::quoted_u64_option::require_quotes")]`.
+pub mod require_quotes {
+    pub use super::serialize;
+    use super::*;
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<u64> + Into<u64> + Copy,
+    {
+        deserializer.deserialize_option(QuotedIntOptionVisitor::new(true))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Lenient {
+        #[serde(default, with = "super")]
+        value: Option<u64>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Strict {
+        #[serde(default, with = "super::require_quotes")]
+        value: Option<u64>,
+    }
+
+    #[test]
+    fn lenient_quoted_unquoted_and_null() {
+        let parse = |json| serde_json::from_str::<Lenient>(json).map(|l| l.value);
+        assert_eq!(parse(r#"{"value": "1"}"#).unwrap(), Some(1));
+        assert_eq!(parse(r#"{"value": 1}"#).unwrap(), Some(1));
+        assert_eq!(parse(r#"{"value": null}"#).unwrap(), None);
+        assert_eq!(parse(r#"{}"#).unwrap(), None);
+        assert!(parse(r#"{"value": "cats"}"#).is_err());
+    }
+
+    #[test]
+    fn strict_requires_quotes() {
+        let parse = |json| serde_json::from_str::<Strict>(json).map(|s| s.value);
+        assert_eq!(parse(r#"{"value": "1"}"#).unwrap(), Some(1));
+        assert_eq!(parse(r#"{"value": null}"#).unwrap(), None);
+        assert!(parse(r#"{"value": 1}"#).is_err());
+    }
+
+    #[test]
+    fn serialize_quoted_or_null() {
+        assert_eq!(
+            serde_json::to_string(&Lenient { value: Some(42) }).unwrap(),
+            r#"{"value":"42"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Strict { value: None }).unwrap(),
+            r#"{"value":null}"#
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Formats `u64` (and types convertible to/from `u64`) as a decimal string, deserializing `null`
+//! as the default value (usually zero).
+//!
+//! The `deserialize` function is lenient and will also accept a plain JSON number. Combine with
+//! `#[serde(default)]` to also use the default value when the field is missing.
+//!
+//! ## Example
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Foo {
+//!     #[serde(default, with = "serde_utils::quoted_u64_or_default")]
+//!     bar: u64,
+//! }
+//! ```
+use crate::quoted_u64_option::QuotedIntOptionVisitor;
+use serde::Deserializer;
+
+pub use crate::quoted_u64::serialize;
+
+/// Deserialize with or without quotes, using `T::default()` for `null`.
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: From<u64> + Into<u64> + Copy + Default,
+{
+    deserializer
+        .deserialize_option(QuotedIntOptionVisitor::new(false))
+        .map(Option::unwrap_or_default)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Foo {
+        #[serde(default, with = "super")]
+        value: u64,
+    }
+
+    #[test]
+    fn missing_and_null_are_default() {
+        let parse = |json| serde_json::from_str::<Foo>(json).map(|foo| foo.value);
+        assert_eq!(parse(r#"{}"#).unwrap(), 0);
+        assert_eq!(parse(r#"{"value": null}"#).unwrap(), 0);
+        assert_eq!(parse(r#"{"value": "7"}"#).unwrap(), 7);
+        assert_eq!(parse(r#"{"value": 7}"#).unwrap(), 7);
+    }
+
+    #[test]
+    fn serialize_quoted() {
+        assert_eq!(
+            serde_json::to_string(&Foo { value: 0 }).unwrap(),
+            r#"{"value":"0"}"#
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use criterion::Criterion;
 use criterion::{black_box, criterion_group, criterion_main, Benchmark};
-use swap_or_not_shuffle::{compute_shuffled_index, shuffle_list as fast_shuffle};
+use swap_or_not_shuffle::{
+    compute_shuffled_index, shuffle_list as fast_shuffle, shuffle_list_with_rounds, ShuffleRound,
+};
 
 const SHUFFLE_ROUND_COUNT: u8 = 90;
 
@@ -86,6 +88,34 @@ fn shuffles(c: &mut Criterion) {
         })
         .sample_size(10),
     );
+
+    c.bench(
+        "_fast_ whole list shuffle",
+        Benchmark::new("precompute rounds for 4m elements", move |b| {
+            let seed = vec![42; 32];
+            b.iter(|| {
+                black_box(
+                    (0..SHUFFLE_ROUND_COUNT)
+                        .map(|round| ShuffleRound::new(4_000_000, round, &seed).unwrap())
+                        .collect::<Vec<_>>(),
+                )
+            })
+        })
+        .sample_size(10),
+    );
+
+    c.bench(
+        "_fast_ whole list shuffle",
+        Benchmark::new("apply precomputed rounds to 4m elements", move |b| {
+            let seed = vec![42; 32];
+            let list: Vec<usize> = (0..4_000_000).collect();
+            let rounds = (0..SHUFFLE_ROUND_COUNT)
+                .map(|round| ShuffleRound::new(list.len(), round, &seed).unwrap())
+                .collect::<Vec<_>>();
+            b.iter(|| black_box(shuffle_list_with_rounds(list.clone(), &rounds, true)))
+        })
+        .sample_size(10),
+    );
 }
 
 criterion_group!(benches, shuffles,);
@@ -89,11 +89,7 @@ pub fn shuffle_list(
 ) -> Option<Vec<usize>> {
     let list_size = input.len();
 
-    if input.is_empty()
-        || list_size > usize::max_value() / 2
-        || list_size > 2_usize.pow(24)
-        || rounds == 0
-    {
+    if !is_valid_list_size(list_size) || rounds == 0 {
         return None;
     }
 
@@ -171,6 +167,121 @@ pub fn shuffle_list(
     Some(input)
 }
 
+/// Returns `false` if a list of `list_size` cannot be shuffled.
+fn is_valid_list_size(list_size: usize) -> bool {
+    list_size != 0 && list_size <= usize::max_value() / 2 && list_size <= 2_usize.pow(24)
+}
+
+/// The pivot and source hashes of a single round of `shuffle_list`.
+///
+/// Computing the hashes is the bulk of the work of a shuffle. Since each round may be computed
+/// independently of the others (e.g. on separate threads), a shuffle may be split into computing
+/// each `ShuffleRound` and then applying them in turn with `shuffle_list_with_rounds`.
+///
+/// A round holds one hash per 256 list elements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShuffleRound {
+    list_size: usize,
+    pivot: usize,
+    /// The source hash of each position, or zero if no element in the position is swapped.
+    sources: Vec<Hash256>,
+}
+
+impl ShuffleRound {
+    /// Computes the given `round` of the shuffling of a list of `list_size` elements.
+    ///
+    /// Returns `None` under the same conditions as `shuffle_list`.
+    pub fn new(list_size: usize, round: u8, seed: &[u8]) -> Option<Self> {
+        if !is_valid_list_size(list_size) {
+            return None;
+        }
+
+        let mut buf = Buf::new(seed);
+        buf.set_round(round);
+        let pivot = buf.raw_pivot() as usize % list_size;
+
+        let mut round = Self {
+            list_size,
+            pivot,
+            sources: vec![Hash256::zero(); ((list_size - 1) >> 8) + 1],
+        };
+
+        // Only hash the positions which hold an element that may be swapped.
+        let (lower, upper) = round.swapped_ranges();
+        for (first, last) in lower.into_iter().chain(upper) {
+            for position in (first >> 8)..=(last >> 8) {
+                buf.mix_in_position(position);
+                round.sources[position] = buf.hash();
+            }
+        }
+
+        Some(round)
+    }
+
+    /// Returns the inclusive ranges of the indices `j` which are swapped with an index `i < j`,
+    /// either side of the pivot.
+    fn swapped_ranges(&self) -> (Option<(usize, usize)>, Option<(usize, usize)>) {
+        let lower_count = (self.pivot + 1) >> 1;
+        let upper_count = ((self.pivot + self.list_size + 1) >> 1) - (self.pivot + 1);
+        let end = self.list_size - 1;
+
+        let lower = Some((self.pivot + 1 - lower_count, self.pivot)).filter(|_| lower_count > 0);
+        let upper = Some((end + 1 - upper_count, end)).filter(|_| upper_count > 0);
+        (lower, upper)
+    }
+
+    /// Returns `true` if the element at `j` is swapped with its mirror.
+    fn bit(&self, j: usize) -> bool {
+        (self.sources[j >> 8][(j & 0xff) >> 3] >> (j & 0x07)) & 0x01 == 1
+    }
+
+    /// Applies the round to `input`, which must have `self.list_size` elements.
+    fn apply(&self, input: &mut [usize]) {
+        let mirror = (self.pivot + 1) >> 1;
+        for i in 0..mirror {
+            let j = self.pivot - i;
+            if self.bit(j) {
+                input.swap(i, j);
+            }
+        }
+
+        let mirror = (self.pivot + self.list_size + 1) >> 1;
+        let end = self.list_size - 1;
+        for (loop_iter, i) in ((self.pivot + 1)..mirror).enumerate() {
+            let j = end - loop_iter;
+            if self.bit(j) {
+                input.swap(i, j);
+            }
+        }
+    }
+}
+
+/// Shuffles an entire list in-place, equivalently to `shuffle_list`, using rounds which have
+/// already been computed. `rounds[r]` must be round `r` for the length of `input`.
+///
+/// Returns `None` if `rounds` is empty or any round was computed for a list of a different length.
+pub fn shuffle_list_with_rounds(
+    mut input: Vec<usize>,
+    rounds: &[ShuffleRound],
+    forwards: bool,
+) -> Option<Vec<usize>> {
+    if rounds.is_empty() || rounds.iter().any(|round| round.list_size != input.len()) {
+        return None;
+    }
+
+    if forwards {
+        for round in rounds {
+            round.apply(&mut input);
+        }
+    } else {
+        for round in rounds.iter().rev() {
+            round.apply(&mut input);
+        }
+    }
+
+    Some(input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -186,4 +297,32 @@ mod tests {
         assert!(TOTAL_SIZE > PIVOT_VIEW_SIZE);
         assert!(mem::size_of::<usize>() >= POSITION_WINDOW_SIZE);
     }
+
+    #[test]
+    fn precomputed_rounds_match_shuffle_list() {
+        let seed = [42; 32];
+        for &list_size in &[1, 2, 3, 255, 256, 257, 1000, 4096] {
+            let rounds = (0..10)
+                .map(|round| ShuffleRound::new(list_size, round, &seed).unwrap())
+                .collect::<Vec<_>>();
+            for &forwards in &[true, false] {
+                let list = (0..list_size).collect::<Vec<_>>();
+                assert_eq!(
+                    shuffle_list_with_rounds(list.clone(), &rounds, forwards),
+                    shuffle_list(list, 10, &seed, forwards),
+                    "list_size: {}, forwards: {}",
+                    list_size,
+                    forwards
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rounds_must_match_list_size() {
+        let round = ShuffleRound::new(8, 0, &[42; 32]).unwrap();
+        assert_eq!(shuffle_list_with_rounds(vec![0; 9], &[round], true), None);
+        assert_eq!(shuffle_list_with_rounds(vec![0; 8], &[], true), None);
+        assert_eq!(ShuffleRound::new(0, 0, &[42; 32]), None);
+    }
 }
@@ -3,8 +3,8 @@ use criterion::{black_box, criterion_group, criterion_main, Benchmark};
 use rayon::prelude::*;
 use ssz::{Decode, Encode};
 use types::{
-    test_utils::generate_deterministic_keypair, BeaconState, Epoch, Eth1Data, EthSpec, Hash256,
-    MainnetEthSpec, Validator,
+    test_utils::generate_deterministic_keypair, BeaconState, CommitteeCache, Epoch, Eth1Data,
+    EthSpec, Hash256, MainnetEthSpec, Validator,
 };
 
 fn get_state<E: EthSpec>(validator_count: usize) -> BeaconState<E> {
@@ -167,6 +167,46 @@ fn all_benches(c: &mut Criterion) {
         )
         .sample_size(10),
     );
+
+    let inner_state = state.clone();
+    let inner_spec = spec.clone();
+    c.bench(
+        &format!("{}_validators", validator_count),
+        Benchmark::new("initialized_sequential/committee_cache", move |b| {
+            b.iter(|| {
+                black_box(
+                    CommitteeCache::initialized_with_parallel_threshold(
+                        &inner_state,
+                        inner_state.current_epoch(),
+                        &inner_spec,
+                        usize::max_value(),
+                    )
+                    .expect("should initialize committee cache"),
+                )
+            })
+        })
+        .sample_size(10),
+    );
+
+    let inner_state = state.clone();
+    let inner_spec = spec.clone();
+    c.bench(
+        &format!("{}_validators", validator_count),
+        Benchmark::new("initialized_parallel/committee_cache", move |b| {
+            b.iter(|| {
+                black_box(
+                    CommitteeCache::initialized_with_parallel_threshold(
+                        &inner_state,
+                        inner_state.current_epoch(),
+                        &inner_spec,
+                        0,
+                    )
+                    .expect("should initialize committee cache"),
+                )
+            })
+        })
+        .sample_size(10),
+    );
 }
 
 criterion_group!(benches, all_benches,);
@@ -7,7 +7,7 @@ pub struct AttestationDuty {
     /// The slot during which the attester must attest.
     pub slot: Slot,
     /// The index of this committee within the committees in `slot`.
-    pub index: CommitteeIndex,
+    pub index: u64,
     /// The position of the attester within the committee.
     pub committee_position: usize,
     /// The total number of attesters in the committee.
@@ -2,12 +2,23 @@ use crate::test_utils::TestRandom;
 use crate::utils::{graffiti_from_hex_str, graffiti_to_hex_str, Graffiti};
 use crate::*;
 
+use merkle_proof::{
+    concat_generalized_indices, generalized_index, verify_merkle_proof_at_gindex, MerkleTree,
+};
 use serde_derive::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
 use ssz_types::VariableList;
 use test_random_derive::TestRandom;
+use tree_hash::TreeHash;
 use tree_hash_derive::TreeHash;
 
+/// The depth of the tree formed by the eight fields of a `BeaconBlockBody`.
+const BODY_FIELDS_DEPTH: usize = 3;
+/// The position of `attestations` within the fields of a `BeaconBlockBody`.
+const ATTESTATIONS_FIELD_INDEX: usize = 5;
+/// The position of `deposits` within the fields of a `BeaconBlockBody`.
+const DEPOSITS_FIELD_INDEX: usize = 6;
+
 /// The body of a `BeaconChain` block, containing operations.
 ///
 /// Spec v0.12.1
@@ -29,9 +40,132 @@ pub struct BeaconBlockBody<T: EthSpec> {
     pub voluntary_exits: VariableList<SignedVoluntaryExit, T::MaxVoluntaryExits>,
 }
 
+impl<T: EthSpec> BeaconBlockBody<T> {
+    /// Returns a proof of the inclusion of the attestation at `index` in this body.
+    ///
+    /// Returns `None` if there is no such attestation.
+    pub fn attestation_proof(&self, index: usize) -> Option<BeaconBlockBodyProof> {
+        self.list_item_proof(&self.attestations, ATTESTATIONS_FIELD_INDEX, index)
+    }
+
+    /// Returns a proof of the inclusion of the deposit at `index` in this body.
+    ///
+    /// Returns `None` if there is no such deposit.
+    pub fn deposit_proof(&self, index: usize) -> Option<BeaconBlockBodyProof> {
+        self.list_item_proof(&self.deposits, DEPOSITS_FIELD_INDEX, index)
+    }
+
+    /// The tree hash roots of each of the fields of this body, in order.
+    fn field_roots(&self) -> Vec<Hash256> {
+        vec![
+            self.randao_reveal.tree_hash_root(),
+            self.eth1_data.tree_hash_root(),
+            self.graffiti.tree_hash_root(),
+            self.proposer_slashings.tree_hash_root(),
+            self.attester_slashings.tree_hash_root(),
+            self.attestations.tree_hash_root(),
+            self.deposits.tree_hash_root(),
+            self.voluntary_exits.tree_hash_root(),
+        ]
+        .into_iter()
+        .map(|root| Hash256::from_slice(&root[..]))
+        .collect()
+    }
+
+    /// Returns a proof of the inclusion of the item at `index` of `list`, which is the field at
+    /// `field_index` of this body.
+    fn list_item_proof<U: TreeHash, N: Unsigned>(
+        &self,
+        list: &VariableList<U, N>,
+        field_index: usize,
+        index: usize,
+    ) -> Option<BeaconBlockBodyProof> {
+        if index >= list.len() {
+            return None;
+        }
+
+        let list_depth = N::to_usize().next_power_of_two().trailing_zeros() as usize;
+        let leaves = list
+            .iter()
+            .map(|item| Hash256::from_slice(&item.tree_hash_root()[..]))
+            .collect::<Vec<_>>();
+        let (leaf, mut branch) =
+            MerkleTree::create(&leaves, list_depth).generate_proof(index, list_depth);
+
+        // The root of a list is the root of its items mixed in with its length, so the length is
+        // the sibling of the items' root.
+        let mut length = [0; 32];
+        length[0..8].copy_from_slice(&(list.len() as u64).to_le_bytes());
+        branch.push(Hash256::from(length));
+
+        let (_, fields_branch) = MerkleTree::create(&self.field_roots(), BODY_FIELDS_DEPTH)
+            .generate_proof(field_index, BODY_FIELDS_DEPTH);
+        branch.extend(fields_branch);
+
+        let gindex = concat_generalized_indices(&[
+            generalized_index(BODY_FIELDS_DEPTH, field_index),
+            generalized_index(1, 0),
+            generalized_index(list_depth, index),
+        ]);
+
+        Some(BeaconBlockBodyProof {
+            leaf,
+            branch,
+            gindex,
+        })
+    }
+}
+
+/// A Merkle proof of the inclusion of some value (e.g., an attestation) in a `BeaconBlockBody`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct BeaconBlockBodyProof {
+    /// The tree hash root of the value.
+    pub leaf: Hash256,
+    /// The sibling nodes on the path from `leaf` to the body root, in bottom-up order.
+    pub branch: Vec<Hash256>,
+    /// The generalized index of `leaf` within the tree of the body.
+    pub gindex: u64,
+}
+
+impl BeaconBlockBodyProof {
+    /// Returns `true` if this proof shows `self.leaf` is included in the body with `body_root`.
+    pub fn verify(&self, body_root: Hash256) -> bool {
+        verify_merkle_proof_at_gindex(self.leaf, &self.branch, self.gindex, body_root)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     ssz_and_tree_hash_tests!(BeaconBlockBody<MainnetEthSpec>);
+
+    #[test]
+    fn operation_proofs() {
+        use crate::test_utils::{SeedableRng, TestRandom, XorShiftRng};
+
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let mut body = BeaconBlockBody::<MainnetEthSpec>::random_for_test(&mut rng);
+        body.attestations = (0..3)
+            .map(|_| Attestation::random_for_test(&mut rng))
+            .collect::<Vec<_>>()
+            .into();
+        body.deposits = vec![Deposit::random_for_test(&mut rng)].into();
+        let body_root = Hash256::from_slice(&body.tree_hash_root()[..]);
+
+        for (i, attestation) in body.attestations.iter().enumerate() {
+            let proof = body.attestation_proof(i).unwrap();
+            assert_eq!(proof.leaf, attestation.tree_hash_root());
+            assert!(proof.verify(body_root));
+            assert!(!proof.verify(Hash256::zero()));
+        }
+        for (i, deposit) in body.deposits.iter().enumerate() {
+            let proof = body.deposit_proof(i).unwrap();
+            assert_eq!(proof.leaf, deposit.tree_hash_root());
+            assert!(proof.verify(body_root));
+        }
+
+        assert_eq!(body.attestation_proof(body.attestations.len()), None);
+        assert_eq!(body.deposit_proof(body.deposits.len()), None);
+    }
 }
@@ -3,7 +3,7 @@ use crate::*;
 #[derive(Default, Clone, Debug, PartialEq)]
 pub struct BeaconCommittee<'a> {
     pub slot: Slot,
-    pub index: CommitteeIndex,
+    pub index: u64,
     pub committee: &'a [usize],
 }
 
@@ -21,6 +21,6 @@ impl<'a> BeaconCommittee<'a> {
 #[derive(Default, Clone, Debug, PartialEq)]
 pub struct OwnedBeaconCommittee {
     pub slot: Slot,
-    pub index: CommitteeIndex,
+    pub index: u64,
     pub committee: Vec<usize>,
 }
@@ -20,7 +20,7 @@ use test_random_derive::TestRandom;
 use tree_hash::TreeHash;
 use tree_hash_derive::TreeHash;
 
-pub use self::committee_cache::CommitteeCache;
+pub use self::committee_cache::{CommitteeCache, PARALLEL_SHUFFLE_THRESHOLD};
 pub use clone_config::CloneConfig;
 pub use eth_spec::*;
 pub use tree_hash_cache::BeaconTreeHashCache;
@@ -55,7 +55,7 @@ pub enum Error {
     InsufficientStateRoots,
     NoCommittee {
         slot: Slot,
-        index: CommitteeIndex,
+        index: u64,
     },
     ZeroSlotsPerEpoch,
     PubkeyCacheInconsistent,
@@ -408,11 +408,7 @@ impl<T: EthSpec> BeaconState<T> {
     /// Utilises the committee cache.
     ///
     /// Spec v0.12.1
-    pub fn get_beacon_committee(
-        &self,
-        slot: Slot,
-        index: CommitteeIndex,
-    ) -> Result<BeaconCommittee, Error> {
+    pub fn get_beacon_committee(&self, slot: Slot, index: u64) -> Result<BeaconCommittee, Error> {
         let epoch = slot.epoch(T::slots_per_epoch());
         let relative_epoch = RelativeEpoch::from_epoch(self.current_epoch(), epoch)?;
         let cache = self.committee_cache(relative_epoch)?;
@@ -492,7 +488,7 @@ impl<T: EthSpec> BeaconState<T> {
     pub fn is_aggregator(
         &self,
         slot: Slot,
-        index: CommitteeIndex,
+        index: u64,
         slot_signature: &Signature,
         spec: &ChainSpec,
     ) -> Result<bool, Error> {
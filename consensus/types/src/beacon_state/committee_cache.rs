@@ -3,13 +3,18 @@
 use super::BeaconState;
 use crate::*;
 use core::num::NonZeroUsize;
+use rayon::prelude::*;
 use serde_derive::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
 use std::ops::Range;
-use swap_or_not_shuffle::shuffle_list;
+use swap_or_not_shuffle::{shuffle_list, shuffle_list_with_rounds, ShuffleRound};
 
 mod tests;
 
+/// The number of active validators at or above which `CommitteeCache::initialized` computes the
+/// rounds of the shuffling in parallel.
+pub const PARALLEL_SHUFFLE_THRESHOLD: usize = 8_192;
+
 /// Computes and stores the shuffling for an epoch. Provides various getters to allow callers to
 /// read the committees for the given epoch.
 #[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize, Encode, Decode)]
@@ -29,6 +34,17 @@ impl CommitteeCache {
         state: &BeaconState<T>,
         epoch: Epoch,
         spec: &ChainSpec,
+    ) -> Result<CommitteeCache, Error> {
+        Self::initialized_with_parallel_threshold(state, epoch, spec, PARALLEL_SHUFFLE_THRESHOLD)
+    }
+
+    /// As `initialized`, but the shuffling is computed in parallel if there are at least
+    /// `parallel_threshold` active validators.
+    pub fn initialized_with_parallel_threshold<T: EthSpec>(
+        state: &BeaconState<T>,
+        epoch: Epoch,
+        spec: &ChainSpec,
+        parallel_threshold: usize,
     ) -> Result<CommitteeCache, Error> {
         RelativeEpoch::from_epoch(state.current_epoch(), epoch)
             .map_err(|_| Error::EpochOutOfBounds)?;
@@ -49,12 +65,22 @@ impl CommitteeCache {
 
         let seed = state.get_seed(epoch, Domain::BeaconAttester, spec)?;
 
-        let shuffling = shuffle_list(
-            active_validator_indices,
-            spec.shuffle_round_count,
-            &seed[..],
-            false,
-        )
+        let shuffling = if active_validator_indices.len() >= parallel_threshold {
+            let list_size = active_validator_indices.len();
+            let rounds = (0..spec.shuffle_round_count)
+                .into_par_iter()
+                .map(|round| ShuffleRound::new(list_size, round, &seed[..]))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| Error::UnableToShuffle)?;
+            shuffle_list_with_rounds(active_validator_indices, &rounds, false)
+        } else {
+            shuffle_list(
+                active_validator_indices,
+                spec.shuffle_round_count,
+                &seed[..],
+                false,
+            )
+        }
         .ok_or_else(|| Error::UnableToShuffle)?;
 
         // The use of `NonZeroUsize` reduces the maximum number of possible validators by one.
@@ -106,11 +132,7 @@ impl CommitteeCache {
     /// Get the Beacon committee for the given `slot` and `index`.
     ///
     /// Return `None` if the cache is uninitialized, or the `slot` or `index` is out of range.
-    pub fn get_beacon_committee(
-        &self,
-        slot: Slot,
-        index: CommitteeIndex,
-    ) -> Option<BeaconCommittee> {
+    pub fn get_beacon_committee(&self, slot: Slot, index: u64) -> Option<BeaconCommittee> {
         if self.initialized_epoch.is_none()
             || !self.is_initialized_at(slot.epoch(self.slots_per_epoch))
             || index >= self.committees_per_slot
@@ -190,10 +212,7 @@ impl CommitteeCache {
     }
 
     /// Convert an index addressing the list of all epoch committees into a slot and per-slot index.
-    fn convert_to_slot_and_index(
-        &self,
-        global_committee_index: u64,
-    ) -> Option<(Slot, CommitteeIndex)> {
+    fn convert_to_slot_and_index(&self, global_committee_index: u64) -> Option<(Slot, u64)> {
         let epoch_start_slot = self.initialized_epoch?.start_slot(self.slots_per_epoch);
         let slot_offset = global_committee_index / self.committees_per_slot;
         let index = global_committee_index % self.committees_per_slot;
@@ -118,3 +118,27 @@ fn shuffles_for_the_right_epoch() {
     assert_eq!(cache.shuffling, shuffling_with_seed(next_seed));
     assert_shuffling_positions_accurate(&cache);
 }
+
+#[test]
+fn parallel_shuffling_matches_sequential() {
+    let num_validators = 1_000;
+    let state = new_state::<MinimalEthSpec>(num_validators, Slot::new(0));
+    let spec = &MinimalEthSpec::default_spec();
+
+    let sequential = CommitteeCache::initialized_with_parallel_threshold(
+        &state,
+        state.current_epoch(),
+        spec,
+        usize::max_value(),
+    )
+    .unwrap();
+    let parallel = CommitteeCache::initialized_with_parallel_threshold(
+        &state,
+        state.current_epoch(),
+        spec,
+        num_validators,
+    )
+    .unwrap();
+
+    assert_eq!(sequential, parallel);
+}
@@ -1,8 +1,11 @@
 use crate::*;
 use int_to_bytes::int_to_bytes4;
+use serde::de::DeserializeOwned;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::File;
 use std::path::Path;
+use std::time::Duration;
 use tree_hash::TreeHash;
 use utils::{
     fork_from_hex_str, fork_to_hex_str, u32_from_hex_str, u32_to_hex_str, u8_from_hex_str,
@@ -21,6 +24,9 @@ pub enum Domain {
     VoluntaryExit,
     SelectionProof,
     AggregateAndProof,
+    SyncCommittee,
+    SyncCommitteeSelectionProof,
+    ContributionAndProof,
 }
 
 /// Holds all the "constants" for a BeaconChain.
@@ -103,6 +109,9 @@ pub struct ChainSpec {
     domain_voluntary_exit: u32,
     domain_selection_proof: u32,
     domain_aggregate_and_proof: u32,
+    domain_sync_committee: u32,
+    domain_sync_committee_selection_proof: u32,
+    domain_contribution_and_proof: u32,
 
     /*
      * Fork choice
@@ -126,6 +135,8 @@ pub struct ChainSpec {
     pub attestation_subnet_count: u64,
     pub random_subnets_per_validator: u64,
     pub epochs_per_random_subnet_subscription: u64,
+    pub sync_committee_subnet_count: u64,
+    pub target_aggregators_per_sync_subcommittee: u64,
 }
 
 impl ChainSpec {
@@ -164,6 +175,9 @@ impl ChainSpec {
             Domain::VoluntaryExit => self.domain_voluntary_exit,
             Domain::SelectionProof => self.domain_selection_proof,
             Domain::AggregateAndProof => self.domain_aggregate_and_proof,
+            Domain::SyncCommittee => self.domain_sync_committee,
+            Domain::SyncCommitteeSelectionProof => self.domain_sync_committee_selection_proof,
+            Domain::ContributionAndProof => self.domain_contribution_and_proof,
         }
     }
 
@@ -313,6 +327,9 @@ impl ChainSpec {
             domain_voluntary_exit: 4,
             domain_selection_proof: 5,
             domain_aggregate_and_proof: 6,
+            domain_sync_committee: 7,
+            domain_sync_committee_selection_proof: 8,
+            domain_contribution_and_proof: 9,
 
             /*
              * Fork choice
@@ -336,6 +353,8 @@ impl ChainSpec {
             maximum_gossip_clock_disparity_millis: 500,
             target_aggregators_per_committee: 16,
             epochs_per_random_subnet_subscription: 256,
+            sync_committee_subnet_count: 4,
+            target_aggregators_per_sync_subcommittee: 16,
         }
     }
 
@@ -434,6 +453,17 @@ mod tests {
             spec.domain_aggregate_and_proof,
             &spec,
         );
+        test_domain(Domain::SyncCommittee, spec.domain_sync_committee, &spec);
+        test_domain(
+            Domain::SyncCommitteeSelectionProof,
+            spec.domain_sync_committee_selection_proof,
+            &spec,
+        );
+        test_domain(
+            Domain::ContributionAndProof,
+            spec.domain_contribution_and_proof,
+            &spec,
+        );
     }
 }
 
@@ -551,6 +581,11 @@ pub struct YamlConfig {
     random_subnets_per_validator: u64,
     epochs_per_random_subnet_subscription: u64,
     seconds_per_eth1_block: u64,
+
+    /// Constants unknown to this version of Lighthouse (e.g., those added by a later version of
+    /// the specs), so that a config containing them can still be read.
+    #[serde(flatten)]
+    extra_fields: HashMap<String, serde_yaml::Value>,
 }
 
 impl Default for YamlConfig {
@@ -631,9 +666,89 @@ impl YamlConfig {
             random_subnets_per_validator: spec.random_subnets_per_validator,
             epochs_per_random_subnet_subscription: spec.epochs_per_random_subnet_subscription,
             seconds_per_eth1_block: spec.seconds_per_eth1_block,
+
+            extra_fields: HashMap::new(),
         }
     }
 
+    pub fn seconds_per_slot(&self) -> u64 {
+        self.seconds_per_slot
+    }
+
+    pub fn slot_duration(&self) -> Duration {
+        Duration::from_secs(self.seconds_per_slot)
+    }
+
+    pub fn slots_per_epoch(&self) -> u64 {
+        self.slots_per_epoch
+    }
+
+    pub fn genesis_slot(&self) -> Slot {
+        Slot::new(self.genesis_slot)
+    }
+
+    pub fn genesis_epoch(&self) -> Epoch {
+        self.genesis_epoch
+    }
+
+    pub fn far_future_epoch(&self) -> Epoch {
+        Epoch::new(self.far_future_epoch)
+    }
+
+    pub fn genesis_fork_version(&self) -> [u8; 4] {
+        self.genesis_fork_version
+    }
+
+    pub fn min_genesis_time(&self) -> u64 {
+        self.min_genesis_time
+    }
+
+    pub fn genesis_delay(&self) -> u64 {
+        self.genesis_delay
+    }
+
+    pub fn min_genesis_active_validator_count(&self) -> u64 {
+        self.min_genesis_active_validator_count
+    }
+
+    pub fn max_effective_balance(&self) -> u64 {
+        self.max_effective_balance
+    }
+
+    pub fn shard_committee_period(&self) -> u64 {
+        self.shard_committee_period
+    }
+
+    pub fn max_validators_per_committee(&self) -> u32 {
+        self.max_validators_per_committee
+    }
+
+    pub fn target_aggregators_per_committee(&self) -> u64 {
+        self.target_aggregators_per_committee
+    }
+
+    pub fn eth1_follow_distance(&self) -> u64 {
+        self.eth1_follow_distance
+    }
+
+    pub fn seconds_per_eth1_block(&self) -> u64 {
+        self.seconds_per_eth1_block
+    }
+
+    /// Returns the value of a constant which is not known to this version of Lighthouse, or
+    /// `None` if the config does not contain it.
+    ///
+    /// Returns an error if the value cannot be parsed as a `T`.
+    pub fn extra_field<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, String> {
+        self.extra_fields
+            .get(name)
+            .map(|value| {
+                serde_yaml::from_value(value.clone())
+                    .map_err(|e| format!("Unable to parse {}: {:?}", name, e))
+            })
+            .transpose()
+    }
+
     pub fn from_file(filename: &Path) -> Result<Self, String> {
         let f = File::open(filename)
             .map_err(|e| format!("Error opening spec at {}: {:?}", filename.display(), e))?;
@@ -762,6 +877,31 @@ mod yaml_tests {
         assert_eq!(from, yamlconfig);
     }
 
+    #[test]
+    fn unknown_fields() {
+        let yamlconfig = YamlConfig::from_spec::<MinimalEthSpec>(&ChainSpec::minimal());
+        let mut yaml = serde_yaml::to_string(&yamlconfig).expect("should serialize");
+        yaml.push_str("\nFUTURE_CONSTANT: 42\nFUTURE_DOMAIN: \"0x0a000000\"\n");
+
+        let from: YamlConfig = serde_yaml::from_str(&yaml).expect("should ignore unknown fields");
+        assert_eq!(from.seconds_per_slot(), yamlconfig.seconds_per_slot());
+        assert_eq!(from.extra_field::<u64>("FUTURE_CONSTANT"), Ok(Some(42)));
+        assert_eq!(
+            from.extra_field::<String>("FUTURE_DOMAIN"),
+            Ok(Some("0x0a000000".to_string()))
+        );
+        assert_eq!(from.extra_field::<u64>("MISSING_CONSTANT"), Ok(None));
+        assert!(from.extra_field::<u64>("FUTURE_DOMAIN").is_err());
+
+        // Unknown fields survive a round trip, e.g. through an API response.
+        let json = serde_json::to_string(&from).expect("should serialize to JSON");
+        let from_json: YamlConfig = serde_json::from_str(&json).expect("should deserialize");
+        assert_eq!(
+            from_json.extra_field::<u64>("FUTURE_CONSTANT"),
+            Ok(Some(42))
+        );
+    }
+
     #[test]
     fn apply_to_spec() {
         let mut spec = ChainSpec::minimal();
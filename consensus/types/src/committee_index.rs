@@ -0,0 +1,110 @@
+//! Identifies a committee within a slot.
+use crate::test_utils::TestRandom;
+use crate::ChainSpec;
+use rand::RngCore;
+use serde_derive::{Deserialize, Serialize};
+use ssz::{ssz_encode, Decode, DecodeError, Encode};
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// An index was not less than the number of committees or subnets that it may refer to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IndexOutOfBounds {
+    pub index: u64,
+    pub limit: u64,
+}
+
+/// The index of a committee within a slot, as in `AttestationData.index`.
+///
+/// Consensus code follows the spec and uses a `u64`. This type is used where a committee index
+/// crosses an API or network boundary, so that it cannot be mistaken for a slot, subnet or
+/// validator index. It is serialized to JSON as a quoted integer.
+#[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct CommitteeIndex(#[serde(with = "serde_utils::quoted_u64")] u64);
+
+impl_from_into_u64!(CommitteeIndex);
+impl_display!(CommitteeIndex);
+impl_ssz!(CommitteeIndex);
+
+impl CommitteeIndex {
+    pub const fn new(index: u64) -> Self {
+        Self(index)
+    }
+
+    /// Returns an error if `index` is not less than `MAX_COMMITTEES_PER_SLOT`, since no slot has
+    /// a committee with that index.
+    pub fn new_checked(index: u64, spec: &ChainSpec) -> Result<Self, IndexOutOfBounds> {
+        let limit = spec.max_committees_per_slot as u64;
+        if index < limit {
+            Ok(Self(index))
+        } else {
+            Err(IndexOutOfBounds { index, limit })
+        }
+    }
+}
+
+impl Deref for CommitteeIndex {
+    type Target = u64;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromStr for CommitteeIndex {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<u64>().map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EthSpec, MainnetEthSpec};
+
+    #[test]
+    fn bounds() {
+        let spec = MainnetEthSpec::default_spec();
+        assert_eq!(spec.max_committees_per_slot, 64);
+
+        assert_eq!(
+            CommitteeIndex::new_checked(63, &spec),
+            Ok(CommitteeIndex::new(63))
+        );
+        assert_eq!(
+            CommitteeIndex::new_checked(64, &spec),
+            Err(IndexOutOfBounds {
+                index: 64,
+                limit: 64
+            })
+        );
+    }
+
+    #[test]
+    fn serde() {
+        let index = CommitteeIndex::new(42);
+        assert_eq!(serde_json::to_string(&index).unwrap(), "\"42\"");
+        assert_eq!(
+            serde_json::from_str::<CommitteeIndex>("\"42\"").unwrap(),
+            index
+        );
+        assert_eq!(serde_json::from_str::<CommitteeIndex>("42").unwrap(), index);
+    }
+
+    #[test]
+    fn ssz() {
+        let index = CommitteeIndex::new(42);
+        assert_eq!(index.as_ssz_bytes(), 42_u64.as_ssz_bytes());
+        assert_eq!(
+            CommitteeIndex::from_ssz_bytes(&index.as_ssz_bytes()).unwrap(),
+            index
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use super::{EthSpec, Signature, SignedRoot, SyncCommitteeContribution};
+use crate::test_utils::TestRandom;
+use serde_derive::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use test_random_derive::TestRandom;
+use tree_hash_derive::TreeHash;
+
+/// A sync committee contribution and the proof that its aggregator was elected to publish it.
+///
+/// Spec v1.1.0 (Altair)
+#[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Encode, Decode, TestRandom, TreeHash)]
+#[serde(bound = "T: EthSpec")]
+pub struct ContributionAndProof<T: EthSpec> {
+    /// The index of the validator that created the contribution.
+    pub aggregator_index: u64,
+    pub contribution: SyncCommitteeContribution<T>,
+    /// The `SyncSelectionProof` of the aggregator.
+    pub selection_proof: Signature,
+}
+
+impl<T: EthSpec> SignedRoot for ContributionAndProof<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    ssz_and_tree_hash_tests!(ContributionAndProof<MainnetEthSpec>);
+}
@@ -3,8 +3,8 @@ use crate::*;
 use safe_arith::SafeArith;
 use serde_derive::{Deserialize, Serialize};
 use ssz_types::typenum::{
-    Unsigned, U0, U1024, U1099511627776, U128, U16, U16777216, U2, U2048, U32, U4, U4096, U64,
-    U65536, U8, U8192,
+    Unsigned, U0, U1024, U1099511627776, U128, U16, U16777216, U2, U2048, U32, U4, U4096, U512,
+    U64, U65536, U8, U8192,
 };
 use std::fmt::Debug;
 
@@ -40,6 +40,14 @@ pub trait EthSpec: 'static + Default + Sync + Send + Clone + Debug + PartialEq +
     type MaxAttestations: Unsigned + Clone + Sync + Send + Debug + PartialEq;
     type MaxDeposits: Unsigned + Clone + Sync + Send + Debug + PartialEq;
     type MaxVoluntaryExits: Unsigned + Clone + Sync + Send + Debug + PartialEq;
+    /*
+     * Sync committees (Altair)
+     */
+    type SyncCommitteeSize: Unsigned + Clone + Sync + Send + Debug + PartialEq;
+    /// The number of members of each of the `SYNC_COMMITTEE_SUBNET_COUNT` subcommittees.
+    ///
+    /// Must be set to `SyncCommitteeSize / SYNC_COMMITTEE_SUBNET_COUNT`
+    type SyncSubcommitteeSize: Unsigned + Clone + Sync + Send + Debug + PartialEq;
     /*
      * Derived values (set these CAREFULLY)
      */
@@ -115,6 +123,13 @@ pub trait EthSpec: 'static + Default + Sync + Send + Clone + Debug + PartialEq +
         Self::EpochsPerHistoricalVector::to_usize()
     }
 
+    /// Returns the number of members in each sync subcommittee.
+    ///
+    /// Spec v1.1.0 (Altair)
+    fn sync_subcommittee_size() -> usize {
+        Self::SyncSubcommitteeSize::to_usize()
+    }
+
     /// Returns the `SLOTS_PER_ETH1_VOTING_PERIOD` constant for this specification.
     ///
     /// Spec v0.12.1
@@ -157,6 +172,8 @@ impl EthSpec for MainnetEthSpec {
     type MaxVoluntaryExits = U16;
     type MaxPendingAttestations = U4096; // 128 max attestations * 32 slots per epoch
     type SlotsPerEth1VotingPeriod = U1024; // 32 epochs * 32 slots per epoch
+    type SyncCommitteeSize = U512;
+    type SyncSubcommitteeSize = U128; // 512 committee size / 4 sync committee subnet count
 
     fn default_spec() -> ChainSpec {
         ChainSpec::mainnet()
@@ -184,6 +201,8 @@ impl EthSpec for MinimalEthSpec {
     type EpochsPerSlashingsVector = U64;
     type MaxPendingAttestations = U1024; // 128 max attestations * 8 slots per epoch
     type SlotsPerEth1VotingPeriod = U32; // 4 epochs * 8 slots per epoch
+    type SyncCommitteeSize = U32;
+    type SyncSubcommitteeSize = U8; // 32 committee size / 4 sync committee subnet count
 
     params_from_eth_spec!(MainnetEthSpec {
         JustificationBitsLength,
@@ -235,7 +254,9 @@ impl EthSpec for InteropEthSpec {
         MaxAttesterSlashings,
         MaxAttestations,
         MaxDeposits,
-        MaxVoluntaryExits
+        MaxVoluntaryExits,
+        SyncCommitteeSize,
+        SyncSubcommitteeSize
     });
 
     fn default_spec() -> ChainSpec {
@@ -20,6 +20,7 @@ pub mod beacon_committee;
 pub mod beacon_state;
 pub mod chain_spec;
 pub mod checkpoint;
+pub mod contribution_and_proof;
 pub mod deposit;
 pub mod deposit_data;
 pub mod deposit_message;
@@ -38,13 +39,19 @@ pub mod selection_proof;
 pub mod signed_aggregate_and_proof;
 pub mod signed_beacon_block;
 pub mod signed_beacon_block_header;
+pub mod signed_contribution_and_proof;
 pub mod signed_voluntary_exit;
 pub mod signing_data;
+pub mod sync_aggregator_selection_data;
+pub mod sync_committee_contribution;
+pub mod sync_committee_message;
+pub mod sync_selection_proof;
 pub mod utils;
 pub mod validator;
 pub mod voluntary_exit;
 #[macro_use]
 pub mod slot_epoch_macros;
+pub mod committee_index;
 pub mod slot_epoch;
 pub mod subnet_id;
 mod tree_hash_impls;
@@ -60,12 +67,14 @@ pub use crate::attestation_data::AttestationData;
 pub use crate::attestation_duty::AttestationDuty;
 pub use crate::attester_slashing::AttesterSlashing;
 pub use crate::beacon_block::BeaconBlock;
-pub use crate::beacon_block_body::BeaconBlockBody;
+pub use crate::beacon_block_body::{BeaconBlockBody, BeaconBlockBodyProof};
 pub use crate::beacon_block_header::BeaconBlockHeader;
 pub use crate::beacon_committee::{BeaconCommittee, OwnedBeaconCommittee};
 pub use crate::beacon_state::{BeaconTreeHashCache, Error as BeaconStateError, *};
 pub use crate::chain_spec::{ChainSpec, Domain, YamlConfig};
 pub use crate::checkpoint::Checkpoint;
+pub use crate::committee_index::{CommitteeIndex, IndexOutOfBounds};
+pub use crate::contribution_and_proof::ContributionAndProof;
 pub use crate::deposit::{Deposit, DEPOSIT_TREE_DEPTH};
 pub use crate::deposit_data::DepositData;
 pub use crate::deposit_message::DepositMessage;
@@ -83,14 +92,18 @@ pub use crate::selection_proof::SelectionProof;
 pub use crate::signed_aggregate_and_proof::SignedAggregateAndProof;
 pub use crate::signed_beacon_block::{SignedBeaconBlock, SignedBeaconBlockHash};
 pub use crate::signed_beacon_block_header::SignedBeaconBlockHeader;
+pub use crate::signed_contribution_and_proof::SignedContributionAndProof;
 pub use crate::signed_voluntary_exit::SignedVoluntaryExit;
 pub use crate::signing_data::{SignedRoot, SigningData};
 pub use crate::slot_epoch::{Epoch, Slot};
 pub use crate::subnet_id::SubnetId;
+pub use crate::sync_aggregator_selection_data::SyncAggregatorSelectionData;
+pub use crate::sync_committee_contribution::SyncCommitteeContribution;
+pub use crate::sync_committee_message::SyncCommitteeMessage;
+pub use crate::sync_selection_proof::SyncSelectionProof;
 pub use crate::validator::Validator;
 pub use crate::voluntary_exit::VoluntaryExit;
 
-pub type CommitteeIndex = u64;
 pub type Hash256 = H256;
 pub type Address = H160;
 pub type ForkVersion = [u8; 4];
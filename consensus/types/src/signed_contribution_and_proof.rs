@@ -0,0 +1,26 @@
+use super::{ContributionAndProof, EthSpec, Signature};
+use crate::test_utils::TestRandom;
+use serde_derive::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use test_random_derive::TestRandom;
+use tree_hash_derive::TreeHash;
+
+/// A signed `ContributionAndProof`, published on the `sync_committee_contribution_and_proof`
+/// gossipsub topic.
+///
+/// Spec v1.1.0 (Altair)
+#[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Encode, Decode, TestRandom, TreeHash)]
+#[serde(bound = "T: EthSpec")]
+pub struct SignedContributionAndProof<T: EthSpec> {
+    pub message: ContributionAndProof<T>,
+    pub signature: Signature,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    ssz_and_tree_hash_tests!(SignedContributionAndProof<MainnetEthSpec>);
+}
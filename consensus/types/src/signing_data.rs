@@ -23,3 +23,5 @@ pub trait SignedRoot: TreeHash {
         .tree_hash_root()
     }
 }
+
+impl SignedRoot for Hash256 {}
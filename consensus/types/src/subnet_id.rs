@@ -1,18 +1,41 @@
 //! Identifies each shard by an integer identifier.
-use crate::{AttestationData, ChainSpec, CommitteeIndex, EthSpec, Slot};
+use crate::test_utils::TestRandom;
+use crate::{AttestationData, ChainSpec, CommitteeIndex, EthSpec, IndexOutOfBounds, Slot};
+use rand::RngCore;
 use safe_arith::{ArithError, SafeArith};
 use serde_derive::{Deserialize, Serialize};
+use ssz::{ssz_encode, Decode, DecodeError, Encode};
+use std::fmt;
 use std::ops::{Deref, DerefMut};
 
+/// The attestation subnet on which an attestation is published.
+///
+/// It is serialized to JSON as a quoted integer.
 #[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct SubnetId(u64);
+#[serde(transparent)]
+pub struct SubnetId(#[serde(with = "serde_utils::quoted_u64")] u64);
+
+impl_display!(SubnetId);
+impl_ssz!(SubnetId);
 
 impl SubnetId {
     pub fn new(id: u64) -> Self {
         id.into()
     }
 
+    /// Returns an error if `id` is not less than `ATTESTATION_SUBNET_COUNT`.
+    pub fn new_checked(id: u64, spec: &ChainSpec) -> Result<Self, IndexOutOfBounds> {
+        if id < spec.attestation_subnet_count {
+            Ok(Self(id))
+        } else {
+            Err(IndexOutOfBounds {
+                index: id,
+                limit: spec.attestation_subnet_count,
+            })
+        }
+    }
+
     /// Compute the subnet for an attestation with `attestation_data` where each slot in the
     /// attestation epoch contains `committee_count_per_slot` committees.
     pub fn compute_subnet_for_attestation_data<T: EthSpec>(
@@ -22,7 +45,7 @@ impl SubnetId {
     ) -> Result<SubnetId, ArithError> {
         Self::compute_subnet::<T>(
             attestation_data.slot,
-            attestation_data.index,
+            attestation_data.index.into(),
             committee_count_per_slot,
             spec,
         )
@@ -43,7 +66,7 @@ impl SubnetId {
             committee_count_at_slot.safe_mul(slots_since_epoch_start)?;
 
         Ok(committees_since_epoch_start
-            .safe_add(committee_index)?
+            .safe_add(*committee_index)?
             .safe_rem(spec.attestation_subnet_count)?
             .into())
     }
@@ -74,3 +97,50 @@ impl Into<u64> for SubnetId {
         self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MainnetEthSpec;
+
+    #[test]
+    fn bounds() {
+        let spec = MainnetEthSpec::default_spec();
+        assert_eq!(spec.attestation_subnet_count, 64);
+
+        assert_eq!(SubnetId::new_checked(63, &spec), Ok(SubnetId::new(63)));
+        assert_eq!(
+            SubnetId::new_checked(64, &spec),
+            Err(IndexOutOfBounds {
+                index: 64,
+                limit: 64
+            })
+        );
+    }
+
+    #[test]
+    fn compute_subnet_is_in_bounds() {
+        let spec = MainnetEthSpec::default_spec();
+        let slots_per_epoch = MainnetEthSpec::slots_per_epoch();
+
+        for slot in 0..slots_per_epoch {
+            for index in 0..spec.max_committees_per_slot as u64 {
+                let subnet_id = SubnetId::compute_subnet::<MainnetEthSpec>(
+                    Slot::new(slot),
+                    CommitteeIndex::new(index),
+                    spec.max_committees_per_slot as u64,
+                    &spec,
+                )
+                .unwrap();
+                assert!(SubnetId::new_checked(*subnet_id, &spec).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn serde() {
+        let subnet_id = SubnetId::new(7);
+        assert_eq!(serde_json::to_string(&subnet_id).unwrap(), "\"7\"");
+        assert_eq!(serde_json::from_str::<SubnetId>("7").unwrap(), subnet_id);
+    }
+}
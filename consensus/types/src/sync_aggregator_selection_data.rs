@@ -0,0 +1,26 @@
+use crate::test_utils::TestRandom;
+use crate::{SignedRoot, Slot};
+use serde_derive::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use test_random_derive::TestRandom;
+use tree_hash_derive::TreeHash;
+
+/// The message signed by a sync committee member to prove that it may aggregate the messages of
+/// a subcommittee.
+///
+/// Spec v1.1.0 (Altair)
+#[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Encode, Decode, TreeHash, TestRandom)]
+pub struct SyncAggregatorSelectionData {
+    pub slot: Slot,
+    pub subcommittee_index: u64,
+}
+
+impl SignedRoot for SyncAggregatorSelectionData {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ssz_and_tree_hash_tests!(SyncAggregatorSelectionData);
+}
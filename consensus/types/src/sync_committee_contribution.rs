@@ -0,0 +1,28 @@
+use super::{AggregateSignature, BitVector, EthSpec, Hash256, Slot};
+use crate::test_utils::TestRandom;
+use serde_derive::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use test_random_derive::TestRandom;
+use tree_hash_derive::TreeHash;
+
+/// The aggregate of the `SyncCommitteeMessage`s of a sync subcommittee for the same block.
+///
+/// Spec v1.1.0 (Altair)
+#[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Encode, Decode, TreeHash, TestRandom)]
+#[serde(bound = "T: EthSpec")]
+pub struct SyncCommitteeContribution<T: EthSpec> {
+    pub slot: Slot,
+    pub beacon_block_root: Hash256,
+    pub subcommittee_index: u64,
+    pub aggregation_bits: BitVector<T::SyncSubcommitteeSize>,
+    pub signature: AggregateSignature,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    ssz_and_tree_hash_tests!(SyncCommitteeContribution<MainnetEthSpec>);
+}
@@ -0,0 +1,27 @@
+use crate::test_utils::TestRandom;
+use crate::{Hash256, Signature, Slot};
+use serde_derive::{Deserialize, Serialize};
+use ssz_derive::{Decode, Encode};
+use test_random_derive::TestRandom;
+use tree_hash_derive::TreeHash;
+
+/// A validator's vote for the head of the chain, made when it is a member of the sync committee.
+///
+/// The `signature` is over `beacon_block_root` with `Domain::SyncCommittee`.
+///
+/// Spec v1.1.0 (Altair)
+#[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Encode, Decode, TreeHash, TestRandom)]
+pub struct SyncCommitteeMessage {
+    pub slot: Slot,
+    pub beacon_block_root: Hash256,
+    pub validator_index: u64,
+    pub signature: Signature,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ssz_and_tree_hash_tests!(SyncCommitteeMessage);
+}
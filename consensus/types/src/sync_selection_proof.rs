@@ -0,0 +1,49 @@
+use crate::{ChainSpec, EthSpec, Signature, Unsigned};
+use eth2_hashing::hash;
+use safe_arith::{ArithError, SafeArith};
+use ssz::Encode;
+use std::cmp;
+use std::convert::TryInto;
+
+/// A signature over a `SyncAggregatorSelectionData`, which elects its signer to aggregate the
+/// messages of a sync subcommittee.
+#[cfg_attr(feature = "arbitrary-fuzz", derive(arbitrary::Arbitrary))]
+#[derive(PartialEq, Debug, Clone)]
+pub struct SyncSelectionProof(Signature);
+
+impl SyncSelectionProof {
+    /// Returns the "modulo" used for determining if a `SyncSelectionProof` elects an aggregator.
+    pub fn modulo<T: EthSpec>(spec: &ChainSpec) -> Result<u64, ArithError> {
+        Ok(cmp::max(
+            1,
+            T::SyncSubcommitteeSize::to_u64()
+                .safe_div(spec.target_aggregators_per_sync_subcommittee)?,
+        ))
+    }
+
+    pub fn is_aggregator<T: EthSpec>(&self, spec: &ChainSpec) -> Result<bool, ArithError> {
+        let signature_hash = hash(&self.0.as_ssz_bytes());
+        let signature_hash_int = u64::from_le_bytes(
+            signature_hash[0..8]
+                .as_ref()
+                .try_into()
+                .expect("first 8 bytes of signature should always convert to fixed array"),
+        );
+
+        signature_hash_int
+            .safe_rem(Self::modulo::<T>(spec)?)
+            .map(|rem| rem == 0)
+    }
+}
+
+impl Into<Signature> for SyncSelectionProof {
+    fn into(self) -> Signature {
+        self.0
+    }
+}
+
+impl From<Signature> for SyncSelectionProof {
+    fn from(sig: Signature) -> Self {
+        Self(sig)
+    }
+}
@@ -69,6 +69,8 @@ pub enum Error {
     InvalidPbkdf2Param,
     InvalidScryptParam,
     IncorrectIvSize { expected: usize, len: usize },
+    InvalidChecksumLen { len: usize, expected: usize },
+    InvalidPublicKey,
     ScryptInvalidParams(InvalidParams),
     ScryptInvaidOutputLen(InvalidOutputLen),
 }
@@ -115,6 +117,12 @@ impl<'a> KeystoreBuilder<'a> {
         self
     }
 
+    /// Build the keystore with the supplied `uuid` instead of a random one.
+    pub fn uuid(mut self, uuid: Uuid) -> Self {
+        self.uuid = uuid;
+        self
+    }
+
     /// Consumes `self`, returning a `Keystore`.
     pub fn build(self) -> Result<Keystore, Error> {
         Keystore::encrypt(
@@ -211,6 +219,46 @@ impl Keystore {
         Ok(keypair)
     }
 
+    /// Checks that the keystore is well-formed, without requiring the password.
+    ///
+    /// This detects keystores which could never be decrypted (e.g., a truncated cipher message or
+    /// invalid KDF parameters) or which declare an invalid public key. A keystore which passes
+    /// this check may still fail to decrypt with an incorrect password.
+    pub fn validate(&self) -> Result<(), Error> {
+        let crypto = &self.json.crypto;
+
+        validate_kdf_params(&crypto.kdf.params)?;
+
+        if crypto.checksum.message.len() != HASH_SIZE {
+            return Err(Error::InvalidChecksumLen {
+                len: crypto.checksum.message.len(),
+                expected: HASH_SIZE,
+            });
+        }
+
+        match &crypto.cipher.params {
+            Cipher::Aes128Ctr(params) => {
+                if params.iv.len() != IV_SIZE {
+                    return Err(Error::IncorrectIvSize {
+                        expected: IV_SIZE,
+                        len: params.iv.len(),
+                    });
+                }
+            }
+        }
+
+        if crypto.cipher.message.len() != SECRET_KEY_LEN {
+            return Err(Error::InvalidSecretKeyLen {
+                len: crypto.cipher.message.len(),
+                expected: SECRET_KEY_LEN,
+            });
+        }
+
+        self.public_key()
+            .map(|_| ())
+            .ok_or_else(|| Error::InvalidPublicKey)
+    }
+
     /// Returns the UUID for the keystore.
     pub fn uuid(&self) -> &Uuid {
         &self.json.uuid
@@ -376,8 +424,36 @@ fn generate_checksum(derived_key: &DerivedKey, cipher_message: &[u8]) -> [u8; HA
 
 /// Derive a private key from the given `password` using the given `kdf` (key derivation function).
 fn derive_key(password: &[u8], kdf: &Kdf) -> Result<DerivedKey, Error> {
+    validate_kdf_params(kdf)?;
+
     let mut dk = DerivedKey::zero();
 
+    match &kdf {
+        Kdf::Pbkdf2(params) => {
+            pbkdf2::<Hmac<Sha256>>(
+                password,
+                params.salt.as_bytes(),
+                params.c,
+                dk.as_mut_bytes(),
+            );
+        }
+        Kdf::Scrypt(params) => {
+            scrypt(
+                password,
+                params.salt.as_bytes(),
+                &ScryptParams::new(log2_int(params.n) as u8, params.r, params.p)
+                    .map_err(Error::ScryptInvalidParams)?,
+                dk.as_mut_bytes(),
+            )
+            .map_err(Error::ScryptInvaidOutputLen)?;
+        }
+    }
+
+    Ok(dk)
+}
+
+/// Returns an error if the parameters of `kdf` are not suitable for deriving a key.
+fn validate_kdf_params(kdf: &Kdf) -> Result<(), Error> {
     match &kdf {
         Kdf::Pbkdf2(params) => {
             // RFC2898 declares that `c` must be a "positive integer" and the `crypto` crate panics
@@ -394,13 +470,6 @@ fn derive_key(password: &[u8], kdf: &Kdf) -> Result<DerivedKey, Error> {
             if params.c == 0 || params.dklen != DKLEN {
                 return Err(Error::InvalidPbkdf2Param);
             }
-
-            pbkdf2::<Hmac<Sha256>>(
-                password,
-                params.salt.as_bytes(),
-                params.c,
-                dk.as_mut_bytes(),
-            );
         }
         Kdf::Scrypt(params) => {
             // RFC7914 declares that all these parameters must be greater than 1:
@@ -423,19 +492,10 @@ fn derive_key(password: &[u8], kdf: &Kdf) -> Result<DerivedKey, Error> {
             if params.n != 2u32.pow(log2_int(params.n)) {
                 return Err(Error::InvalidScryptParam);
             }
-
-            scrypt(
-                password,
-                params.salt.as_bytes(),
-                &ScryptParams::new(log2_int(params.n) as u8, params.r, params.p)
-                    .map_err(Error::ScryptInvalidParams)?,
-                dk.as_mut_bytes(),
-            )
-            .map_err(Error::ScryptInvaidOutputLen)?;
         }
     }
 
-    Ok(dk)
+    Ok(())
 }
 
 /// Compute floor of log2 of a u32.
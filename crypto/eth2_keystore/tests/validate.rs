@@ -0,0 +1,108 @@
+#![cfg(test)]
+
+use eth2_keystore::{Error, Keystore};
+
+const N: &str = "262144";
+const CHECKSUM: &str = "149aafa27b041f3523c53d7acba1905fa6b1c90f9fef137568101f44b531a3cb";
+const IV: &str = "264daa3f303d7259501c93d997d84fe6";
+const CIPHER_MESSAGE: &str = "54ecc8863c0550351eee5720f3be6a5d4a016025aa91cd6436cfec938d6a8d30";
+const PUBKEY: &str = "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07";
+
+/// Returns the EIP-2335 scrypt test vector, with the given fields substituted.
+fn vector(n: &str, checksum: &str, iv: &str, cipher_message: &str, pubkey: &str) -> Keystore {
+    let json = format!(
+        r#"
+            {{
+            "crypto": {{
+                "kdf": {{
+                    "function": "scrypt",
+                    "params": {{
+                        "dklen": 32,
+                        "n": {},
+                        "p": 1,
+                        "r": 8,
+                        "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+                    }},
+                    "message": ""
+                }},
+                "checksum": {{
+                    "function": "sha256",
+                    "params": {{}},
+                    "message": "{}"
+                }},
+                "cipher": {{
+                    "function": "aes-128-ctr",
+                    "params": {{
+                        "iv": "{}"
+                    }},
+                    "message": "{}"
+                }}
+            }},
+            "pubkey": "{}",
+            "uuid": "1d85ae20-35c5-4611-98e8-aa14a633906f",
+            "path": "",
+            "version": 4
+        }}
+        "#,
+        n, checksum, iv, cipher_message, pubkey
+    );
+
+    Keystore::from_json_str(&json).unwrap()
+}
+
+#[test]
+fn valid() {
+    assert_eq!(
+        vector(N, CHECKSUM, IV, CIPHER_MESSAGE, PUBKEY).validate(),
+        Ok(())
+    );
+}
+
+#[test]
+fn invalid_kdf_params() {
+    assert_eq!(
+        vector("3", CHECKSUM, IV, CIPHER_MESSAGE, PUBKEY).validate(),
+        Err(Error::InvalidScryptParam)
+    );
+}
+
+#[test]
+fn short_checksum() {
+    assert_eq!(
+        vector(N, &CHECKSUM[2..], IV, CIPHER_MESSAGE, PUBKEY).validate(),
+        Err(Error::InvalidChecksumLen {
+            len: 31,
+            expected: 32
+        })
+    );
+}
+
+#[test]
+fn short_iv() {
+    assert_eq!(
+        vector(N, CHECKSUM, &IV[2..], CIPHER_MESSAGE, PUBKEY).validate(),
+        Err(Error::IncorrectIvSize {
+            expected: 16,
+            len: 15
+        })
+    );
+}
+
+#[test]
+fn short_cipher_message() {
+    assert_eq!(
+        vector(N, CHECKSUM, IV, &CIPHER_MESSAGE[2..], PUBKEY).validate(),
+        Err(Error::InvalidSecretKeyLen {
+            len: 31,
+            expected: 32
+        })
+    );
+}
+
+#[test]
+fn invalid_pubkey() {
+    assert_eq!(
+        vector(N, CHECKSUM, IV, CIPHER_MESSAGE, &PUBKEY[2..]).validate(),
+        Err(Error::InvalidPublicKey)
+    );
+}
@@ -10,6 +10,7 @@ mod interop_genesis;
 mod new_testnet;
 mod parse_hex;
 mod refund_deposit_contract;
+mod replay_block;
 mod skip_slots;
 mod transition_blocks;
 
@@ -131,6 +132,49 @@ fn main() {
                         .help("Path to output a SSZ file."),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("replay-block")
+                .about(
+                    "Re-executes a block against its pre-state, reporting the time taken by \
+                    each stage of the state transition. Optionally lists the fields which differ \
+                    from an expected post-state.",
+                )
+                .arg(
+                    Arg::with_name("pre-state")
+                        .long("pre-state")
+                        .value_name("BEACON_STATE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Path to a SSZ file of the pre-state."),
+                )
+                .arg(
+                    Arg::with_name("block")
+                        .long("block")
+                        .value_name("BEACON_BLOCK")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Path to a SSZ file of the signed block to apply to pre-state."),
+                )
+                .arg(
+                    Arg::with_name("expected-post-state")
+                        .long("expected-post-state")
+                        .value_name("BEACON_STATE")
+                        .takes_value(true)
+                        .help("Path to a SSZ file of the expected post-state, to compare against."),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .value_name("SSZ_FILE")
+                        .takes_value(true)
+                        .help("Path to output a SSZ file of the post-state."),
+                )
+                .arg(
+                    Arg::with_name("no-signature-verification")
+                        .long("no-signature-verification")
+                        .help("Skip the verification of the block's signatures."),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("pretty-hex")
                 .about("Parses SSZ encoded as ASCII 0x-prefixed hex")
@@ -555,6 +599,9 @@ fn run<T: EthSpec>(
         }
         ("transition-blocks", Some(matches)) => run_transition_blocks::<T>(matches)
             .map_err(|e| format!("Failed to transition blocks: {}", e)),
+        ("replay-block", Some(matches)) => {
+            replay_block::run::<T>(matches).map_err(|e| format!("Failed to replay block: {}", e))
+        }
         ("skip-slots", Some(matches)) => {
             skip_slots::run::<T>(matches).map_err(|e| format!("Failed to skip slots: {}", e))
         }
@@ -0,0 +1,211 @@
+//! Re-executes a block against its pre-state, reporting the time taken by each stage of the state
+//! transition and, optionally, how the result differs from an expected post-state.
+use crate::transition_blocks::load_from_ssz;
+use clap::ArgMatches;
+use ssz::Encode;
+use state_processing::{per_block_processing, per_slot_processing, BlockSignatureStrategy};
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use types::{BeaconState, EthSpec, SignedBeaconBlock};
+
+/// The maximum number of differing validator indices which are listed.
+const MAX_LISTED_INDICES: usize = 16;
+
+pub fn run<T: EthSpec>(matches: &ArgMatches) -> Result<(), String> {
+    let pre_state_path: PathBuf = clap_utils::parse_required(matches, "pre-state")?;
+    let block_path: PathBuf = clap_utils::parse_required(matches, "block")?;
+    let expected_path: Option<PathBuf> =
+        clap_utils::parse_optional(matches, "expected-post-state")?;
+    let output_path: Option<PathBuf> = clap_utils::parse_optional(matches, "output")?;
+    let signature_strategy = if matches.is_present("no-signature-verification") {
+        BlockSignatureStrategy::NoVerification
+    } else {
+        BlockSignatureStrategy::VerifyBulk
+    };
+
+    info!("Using {} spec", T::spec_name());
+    info!("Pre-state path: {:?}", pre_state_path);
+    info!("Block path: {:?}", block_path);
+
+    let spec = &T::default_spec();
+    let mut timings = Timings::default();
+
+    let (mut state, block) = timings.time("load pre-state and block", || {
+        let state: BeaconState<T> = load_from_ssz(pre_state_path)?;
+        let block: SignedBeaconBlock<T> = load_from_ssz(block_path)?;
+        Ok((state, block))
+    })?;
+
+    if block.slot() < state.slot {
+        return Err(format!(
+            "Block slot {} is prior to pre-state slot {}",
+            block.slot(),
+            state.slot
+        ));
+    }
+
+    timings.time("build caches", || {
+        state
+            .build_all_caches(spec)
+            .map_err(|e| format!("Unable to build caches: {:?}", e))
+    })?;
+
+    let slot_stage = format!("per-slot processing ({} slots)", block.slot() - state.slot);
+    timings.time(&slot_stage, || {
+        while state.slot < block.slot() {
+            per_slot_processing(&mut state, None, spec)
+                .map_err(|e| format!("Failed to advance from slot {}: {:?}", state.slot, e))?;
+        }
+        Ok(())
+    })?;
+
+    timings.time("rebuild caches", || {
+        state
+            .build_all_caches(spec)
+            .map_err(|e| format!("Unable to build caches: {:?}", e))
+    })?;
+
+    timings.time("per-block processing", || {
+        per_block_processing(&mut state, &block, None, signature_strategy, spec)
+            .map_err(|e| format!("State transition failed: {:?}", e))
+    })?;
+
+    let state_root = timings.time("state root", || {
+        state
+            .update_tree_hash_cache()
+            .map_err(|e| format!("Unable to compute state root: {:?}", e))
+    })?;
+
+    timings.log();
+
+    if state_root == block.message.state_root {
+        info!("State root matches the block: {:?}", state_root);
+    } else {
+        warn!(
+            "State root {:?} does not match the block's state root {:?}",
+            state_root, block.message.state_root
+        );
+    }
+
+    if let Some(path) = expected_path {
+        let expected: BeaconState<T> = load_from_ssz(path)?;
+        log_differences(&state, &expected);
+    }
+
+    if let Some(path) = output_path {
+        File::create(&path)
+            .and_then(|mut file| file.write_all(&state.as_ssz_bytes()))
+            .map_err(|e| format!("Unable to write post-state to {:?}: {:?}", path, e))?;
+        info!("Post-state written to {:?}", path);
+    }
+
+    Ok(())
+}
+
+/// The duration of each stage of the replay, in order.
+#[derive(Default)]
+struct Timings(Vec<(String, Duration)>);
+
+impl Timings {
+    fn time<R, F>(&mut self, stage: &str, f: F) -> Result<R, String>
+    where
+        F: FnOnce() -> Result<R, String>,
+    {
+        let start = Instant::now();
+        let result = f();
+        self.0.push((stage.to_string(), start.elapsed()));
+        result
+    }
+
+    fn log(&self) {
+        for (stage, duration) in &self.0 {
+            info!("{:>10.3}ms {}", duration.as_secs_f64() * 1000.0, stage);
+        }
+        let total: Duration = self.0.iter().map(|(_, duration)| *duration).sum();
+        info!("{:>10.3}ms total", total.as_secs_f64() * 1000.0);
+    }
+}
+
+/// Logs each top-level field of `state` which differs from `expected`, listing the indices of the
+/// differing validators and balances.
+fn log_differences<T: EthSpec>(state: &BeaconState<T>, expected: &BeaconState<T>) {
+    macro_rules! differing_fields {
+        ($($field: ident),*) => {
+            vec![$((stringify!($field), state.$field != expected.$field)),*]
+                .into_iter()
+                .filter(|(_, differs)| *differs)
+                .map(|(field, _)| field)
+                .collect::<Vec<_>>()
+        };
+    }
+
+    let fields = differing_fields!(
+        genesis_time,
+        genesis_validators_root,
+        slot,
+        fork,
+        latest_block_header,
+        block_roots,
+        state_roots,
+        historical_roots,
+        eth1_data,
+        eth1_data_votes,
+        eth1_deposit_index,
+        validators,
+        balances,
+        randao_mixes,
+        slashings,
+        previous_epoch_attestations,
+        current_epoch_attestations,
+        justification_bits,
+        previous_justified_checkpoint,
+        current_justified_checkpoint,
+        finalized_checkpoint
+    );
+
+    if fields.is_empty() {
+        info!("Post-state matches the expected post-state");
+        return;
+    }
+
+    for field in fields {
+        match field {
+            "validators" => warn!(
+                "Field differs: validators at indices {}",
+                differing_indices(&state.validators, &expected.validators)
+            ),
+            "balances" => warn!(
+                "Field differs: balances at indices {}",
+                differing_indices(&state.balances, &expected.balances)
+            ),
+            _ => warn!("Field differs: {}", field),
+        }
+    }
+}
+
+/// Describes the indices at which `a` and `b` differ, including any difference in length.
+fn differing_indices<V: PartialEq>(a: &[V], b: &[V]) -> String {
+    let indices = a
+        .iter()
+        .zip(b)
+        .enumerate()
+        .filter(|(_, (a, b))| a != b)
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+
+    let mut description = indices
+        .iter()
+        .take(MAX_LISTED_INDICES)
+        .map(|i| i.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if indices.len() > MAX_LISTED_INDICES {
+        description.push_str(&format!(" and {} more", indices.len() - MAX_LISTED_INDICES));
+    }
+    if a.len() != b.len() {
+        description.push_str(&format!(" (length {} vs expected {})", a.len(), b.len()));
+    }
+    description
+}
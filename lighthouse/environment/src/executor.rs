@@ -1,27 +1,151 @@
 use crate::metrics;
 use futures::channel::mpsc::Sender;
 use futures::prelude::*;
+use parking_lot::Mutex;
 use slog::{debug, trace};
+use std::collections::HashMap;
+use std::sync::Arc;
 use tokio::runtime::Handle;
 
+/// The reason a shutdown was requested, which determines the exit code of the process.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShutdownReason {
+    /// The process should exit successfully (e.g., the user pressed Ctrl-C).
+    Success(&'static str),
+    /// The process should exit with an error code (e.g., a critical internal error occurred).
+    Failure(&'static str),
+}
+
+impl ShutdownReason {
+    /// A human-readable description of the reason.
+    pub fn message(&self) -> &'static str {
+        match self {
+            ShutdownReason::Success(msg) | ShutdownReason::Failure(msg) => msg,
+        }
+    }
+
+    /// The exit code that the process should return.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ShutdownReason::Success(_) => 0,
+            ShutdownReason::Failure(_) => 1,
+        }
+    }
+}
+
+/// Groups of tasks which are shut down together.
+///
+/// Stages are shut down in the order of `SHUTDOWN_STAGES`, with each stage being given a chance to
+/// complete before the next is signalled. This allows, for example, the HTTP API to stop serving
+/// requests before the networking stack that some of those requests depend upon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShutdownStage {
+    /// Servers accepting requests from outside the process (e.g., the HTTP and websocket APIs).
+    Api,
+    /// Tasks without any particular ordering requirements (e.g., notifiers and timers).
+    General,
+    /// The sync manager, which is stopped before the network it sends requests through.
+    Sync,
+    /// The networking stack (e.g., the libp2p swarm and the beacon processor).
+    Network,
+    /// The eth1 service.
+    Eth1,
+    /// The store migrator, which finishes any in-progress migration once nothing else is writing
+    /// to the database.
+    Store,
+}
+
+/// The order in which shutdown stages are signalled.
+pub const SHUTDOWN_STAGES: [ShutdownStage; 6] = [
+    ShutdownStage::Api,
+    ShutdownStage::General,
+    ShutdownStage::Sync,
+    ShutdownStage::Network,
+    ShutdownStage::Eth1,
+    ShutdownStage::Store,
+];
+
+/// The exit futures for each shutdown stage.
+pub(crate) type StageExits = Arc<HashMap<ShutdownStage, exit_future::Exit>>;
+
+/// Keeps track of the tasks which are currently running, by shutdown stage and name.
+#[derive(Clone, Default)]
+pub struct TaskRegistry {
+    tasks: Arc<Mutex<HashMap<ShutdownStage, HashMap<&'static str, usize>>>>,
+}
+
+impl TaskRegistry {
+    /// Record a new running task, which is considered running until the returned guard is dropped.
+    fn register(&self, stage: ShutdownStage, name: &'static str) -> TaskGuard {
+        *self
+            .tasks
+            .lock()
+            .entry(stage)
+            .or_default()
+            .entry(name)
+            .or_default() += 1;
+
+        TaskGuard {
+            registry: self.clone(),
+            stage,
+            name,
+        }
+    }
+
+    /// Returns the names (and counts) of the tasks still running in the given `stage`.
+    pub fn running(&self, stage: ShutdownStage) -> Vec<(&'static str, usize)> {
+        self.tasks
+            .lock()
+            .get(&stage)
+            .map(|tasks| tasks.iter().map(|(name, count)| (*name, *count)).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Marks a task as no longer running when dropped.
+struct TaskGuard {
+    registry: TaskRegistry,
+    stage: ShutdownStage,
+    name: &'static str,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        let mut tasks = self.registry.tasks.lock();
+        if let Some(stage_tasks) = tasks.get_mut(&self.stage) {
+            if let Some(count) = stage_tasks.get_mut(self.name) {
+                *count = count.saturating_sub(1);
+                if *count == 0 {
+                    stage_tasks.remove(self.name);
+                }
+            }
+        }
+    }
+}
+
 /// A wrapper over a runtime handle which can spawn async and blocking tasks.
 #[derive(Clone)]
 pub struct TaskExecutor {
     /// The handle to the runtime on which tasks are spawned
     pub handle: Handle,
-    /// The receiver exit future which on receiving shuts down the task
-    pub(crate) exit: exit_future::Exit,
+    /// The exit futures for every shutdown stage. Tasks are shut down when the exit future for
+    /// `self.stage` fires.
+    pub(crate) exits: StageExits,
+    /// The shutdown stage of tasks spawned by this executor.
+    pub(crate) stage: ShutdownStage,
+    /// Tracks running tasks so that shutdown can wait for them to complete.
+    pub(crate) registry: TaskRegistry,
     /// Sender given to tasks, so that if they encounter a state in which execution cannot
     /// continue they can request that everything shuts down.
     ///
     /// The task must provide a reason for shutting down.
-    pub(crate) signal_tx: Sender<&'static str>,
+    pub(crate) signal_tx: Sender<ShutdownReason>,
 
     pub(crate) log: slog::Logger,
 }
 
 impl TaskExecutor {
-    /// Create a new task executor.
+    /// Create a new task executor, where every shutdown stage is triggered by `exit`.
     ///
     /// Note: this function is mainly useful in tests. A `TaskExecutor` should be normally obtained from
     /// a [`RuntimeContext`](struct.RuntimeContext.html)
@@ -29,23 +153,37 @@ impl TaskExecutor {
         handle: Handle,
         exit: exit_future::Exit,
         log: slog::Logger,
-        signal_tx: Sender<&'static str>,
+        signal_tx: Sender<ShutdownReason>,
     ) -> Self {
+        let exits = SHUTDOWN_STAGES
+            .iter()
+            .map(|stage| (*stage, exit.clone()))
+            .collect();
+
         Self {
             handle,
-            exit,
+            exits: Arc::new(exits),
+            stage: ShutdownStage::General,
+            registry: TaskRegistry::default(),
             signal_tx,
             log,
         }
     }
 
+    /// Returns a copy of `self` which spawns tasks in the given shutdown `stage`.
+    pub fn with_shutdown_stage(mut self, stage: ShutdownStage) -> Self {
+        self.stage = stage;
+        self
+    }
+
     /// Spawn a future on the tokio runtime wrapped in an `exit_future::Exit`. The task is canceled
     /// when the corresponding exit_future `Signal` is fired/dropped.
     ///
     /// This function generates prometheus metrics on number of tasks and task duration.
     pub fn spawn(&self, task: impl Future<Output = ()> + Send + 'static, name: &'static str) {
-        let exit = self.exit.clone();
+        let exit = self.exit();
         let log = self.log.clone();
+        let guard = self.registry.register(self.stage, name);
 
         if let Some(int_gauge) = metrics::get_int_gauge(&metrics::ASYNC_TASKS_COUNT, &[name]) {
             // Task is shutdown before it completes if `exit` receives
@@ -58,6 +196,7 @@ impl TaskExecutor {
                     }
                 }
                 int_gauge_1.dec();
+                drop(guard);
                 futures::future::ready(())
             });
 
@@ -75,15 +214,21 @@ impl TaskExecutor {
     /// This is useful in cases where the future to be spawned needs to do additional cleanup work when
     /// the task is completed/canceled (e.g. writing local variables to disk) or the task is created from
     /// some framework which does its own cleanup (e.g. a hyper server).
+    ///
+    /// The task is still registered with its shutdown stage, so shutdown will wait for the cleanup
+    /// to complete.
     pub fn spawn_without_exit(
         &self,
         task: impl Future<Output = ()> + Send + 'static,
         name: &'static str,
     ) {
+        let guard = self.registry.register(self.stage, name);
+
         if let Some(int_gauge) = metrics::get_int_gauge(&metrics::ASYNC_TASKS_COUNT, &[name]) {
             let int_gauge_1 = int_gauge.clone();
             let future = task.then(move |_| {
                 int_gauge_1.dec();
+                drop(guard);
                 futures::future::ready(())
             });
 
@@ -98,7 +243,7 @@ impl TaskExecutor {
     where
         F: FnOnce() + Send + 'static,
     {
-        let exit = self.exit.clone();
+        let exit = self.exit();
         let log = self.log.clone();
 
         if let Some(metric) = metrics::get_histogram(&metrics::BLOCKING_TASKS_HISTOGRAM, &[name]) {
@@ -106,6 +251,7 @@ impl TaskExecutor {
             {
                 let int_gauge_1 = int_gauge.clone();
                 let timer = metric.start_timer();
+                let guard = self.registry.register(self.stage, name);
                 let join_handle = self.handle.spawn_blocking(task);
 
                 let future = future::select(join_handle, exit).then(move |either| {
@@ -119,6 +265,7 @@ impl TaskExecutor {
                     }
                     timer.observe_duration();
                     int_gauge_1.dec();
+                    drop(guard);
                     futures::future::ready(())
                 });
 
@@ -128,18 +275,58 @@ impl TaskExecutor {
         }
     }
 
+    /// Spawn a blocking task on a dedicated tokio thread pool, returning a future which resolves
+    /// to its result.
+    ///
+    /// Unlike [spawn_blocking](#method.spawn_blocking) the task is not wrapped in an exit future,
+    /// since the caller awaits its result. It is still registered with its shutdown stage and
+    /// generates the same prometheus metrics.
+    pub fn spawn_blocking_handle<F, R>(
+        &self,
+        task: F,
+        name: &'static str,
+    ) -> impl Future<Output = Result<R, tokio::task::JoinError>>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let timer = metrics::get_histogram(&metrics::BLOCKING_TASKS_HISTOGRAM, &[name])
+            .map(|metric| metric.start_timer());
+        let int_gauge = metrics::get_int_gauge(&metrics::BLOCKING_TASKS_COUNT, &[name]);
+        if let Some(int_gauge) = &int_gauge {
+            int_gauge.inc();
+        }
+        let guard = self.registry.register(self.stage, name);
+        let log = self.log.clone();
+
+        self.handle.spawn_blocking(task).map(move |result| {
+            trace!(log, "Blocking task completed"; "task" => name);
+            if let Some(timer) = timer {
+                timer.observe_duration();
+            }
+            if let Some(int_gauge) = int_gauge {
+                int_gauge.dec();
+            }
+            drop(guard);
+            result
+        })
+    }
+
     /// Returns the underlying runtime handle.
     pub fn runtime_handle(&self) -> Handle {
         self.handle.clone()
     }
 
-    /// Returns a copy of the `exit_future::Exit`.
+    /// Returns a copy of the `exit_future::Exit` for the shutdown stage of this executor.
     pub fn exit(&self) -> exit_future::Exit {
-        self.exit.clone()
+        self.exits
+            .get(&self.stage)
+            .cloned()
+            .expect("exits are defined for all shutdown stages")
     }
 
     /// Get a channel to request shutting down.
-    pub fn shutdown_sender(&self) -> Sender<&'static str> {
+    pub fn shutdown_sender(&self) -> Sender<ShutdownReason> {
         self.signal_tx.clone()
     }
 
@@ -15,21 +15,30 @@ use futures::channel::{
 };
 use futures::{future, StreamExt};
 
-pub use executor::TaskExecutor;
-use slog::{info, o, Drain, Level, Logger};
+pub use executor::{ShutdownReason, ShutdownStage, TaskExecutor, SHUTDOWN_STAGES};
+use executor::{StageExits, TaskRegistry};
+use overflow_drain::OverflowCountingDrain;
+use slog::{debug, info, o, warn, Drain, Level, Logger};
 use sloggers::{null::NullLoggerBuilder, Build};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fs::{rename as FsRename, OpenOptions};
 use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::runtime::{Builder as RuntimeBuilder, Runtime};
 use types::{EthSpec, InteropEthSpec, MainnetEthSpec, MinimalEthSpec};
 mod executor;
 mod metrics;
+mod overflow_drain;
 
 pub const ETH2_CONFIG_FILENAME: &str = "eth2-spec.toml";
 const LOG_CHANNEL_SIZE: usize = 2048;
+/// The maximum time to wait for the tasks of each shutdown stage to complete.
+pub const DEFAULT_SHUTDOWN_STAGE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often to check whether the tasks of a shutdown stage have completed.
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
 /// Builds an `Environment`.
 pub struct EnvironmentBuilder<E: EthSpec> {
@@ -119,7 +128,8 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
     ///
     /// The logger is "async" because it has a dedicated thread that accepts logs and then
     /// asynchronously flushes them to stdout/files/etc. This means the thread that raised the log
-    /// does not have to wait for the logs to be flushed.
+    /// does not have to wait for the logs to be flushed. Logs raised while `LOG_CHANNEL_SIZE` logs
+    /// are waiting to be flushed are dropped and counted by the `log_overflow_total` metric.
     pub fn async_logger(
         mut self,
         debug_level: &str,
@@ -130,9 +140,7 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
             match format.to_uppercase().as_str() {
                 "JSON" => {
                     let drain = slog_json::Json::default(std::io::stdout()).fuse();
-                    slog_async::Async::new(drain)
-                        .chan_size(LOG_CHANNEL_SIZE)
-                        .build()
+                    OverflowCountingDrain::new(drain, LOG_CHANNEL_SIZE)
                 }
                 _ => return Err("Logging format provided is not supported".to_string()),
             }
@@ -141,9 +149,7 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
             let decorator =
                 logging::AlignedTermDecorator::new(decorator, logging::MAX_MESSAGE_WIDTH);
             let drain = slog_term::FullFormat::new(decorator).build().fuse();
-            slog_async::Async::new(drain)
-                .chan_size(LOG_CHANNEL_SIZE)
-                .build()
+            OverflowCountingDrain::new(drain, LOG_CHANNEL_SIZE)
         };
 
         let drain = match debug_level {
@@ -197,9 +203,7 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
             match format.to_uppercase().as_str() {
                 "JSON" => {
                     let drain = slog_json::Json::default(file).fuse();
-                    slog_async::Async::new(drain)
-                        .chan_size(LOG_CHANNEL_SIZE)
-                        .build()
+                    OverflowCountingDrain::new(drain, LOG_CHANNEL_SIZE)
                 }
                 _ => return Err("Logging format provided is not supported".to_string()),
             }
@@ -208,9 +212,7 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
             let decorator =
                 logging::AlignedTermDecorator::new(decorator, logging::MAX_MESSAGE_WIDTH);
             let drain = slog_term::FullFormat::new(decorator).build().fuse();
-            slog_async::Async::new(drain)
-                .chan_size(LOG_CHANNEL_SIZE)
-                .build()
+            OverflowCountingDrain::new(drain, LOG_CHANNEL_SIZE)
         };
 
         let drain = match debug_level {
@@ -272,7 +274,13 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
 
     /// Consumes the builder, returning an `Environment`.
     pub fn build(self) -> Result<Environment<E>, String> {
-        let (signal, exit) = exit_future::signal();
+        let mut signals = HashMap::new();
+        let mut exits = HashMap::new();
+        for stage in SHUTDOWN_STAGES.iter() {
+            let (signal, exit) = exit_future::signal();
+            signals.insert(*stage, signal);
+            exits.insert(*stage, exit);
+        }
         let (signal_tx, signal_rx) = channel(1);
         Ok(Environment {
             runtime: self
@@ -280,8 +288,9 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
                 .ok_or_else(|| "Cannot build environment without runtime".to_string())?,
             signal_tx,
             signal_rx: Some(signal_rx),
-            signal: Some(signal),
-            exit,
+            signals,
+            exits: Arc::new(exits),
+            registry: TaskRegistry::default(),
             log: self
                 .log
                 .ok_or_else(|| "Cannot build environment without log".to_string())?,
@@ -312,7 +321,9 @@ impl<E: EthSpec> RuntimeContext<E> {
             executor: TaskExecutor {
                 handle: self.executor.handle.clone(),
                 signal_tx: self.executor.signal_tx.clone(),
-                exit: self.executor.exit.clone(),
+                exits: self.executor.exits.clone(),
+                stage: self.executor.stage,
+                registry: self.executor.registry.clone(),
                 log: self.executor.log.new(o!("service" => service_name)),
             },
             eth_spec_instance: self.eth_spec_instance.clone(),
@@ -336,11 +347,14 @@ impl<E: EthSpec> RuntimeContext<E> {
 pub struct Environment<E: EthSpec> {
     runtime: Runtime,
     /// Receiver side of an internal shutdown signal.
-    signal_rx: Option<Receiver<&'static str>>,
+    signal_rx: Option<Receiver<ShutdownReason>>,
     /// Sender to request shutting down.
-    signal_tx: Sender<&'static str>,
-    signal: Option<exit_future::Signal>,
-    exit: exit_future::Exit,
+    signal_tx: Sender<ShutdownReason>,
+    /// The exit signal for each shutdown stage, removed once fired.
+    signals: HashMap<ShutdownStage, exit_future::Signal>,
+    exits: StageExits,
+    /// All tasks spawned by executors created from this environment.
+    registry: TaskRegistry,
     log: Logger,
     eth_spec_instance: E,
     pub eth2_config: Eth2Config,
@@ -360,7 +374,9 @@ impl<E: EthSpec> Environment<E> {
     pub fn core_context(&mut self) -> RuntimeContext<E> {
         RuntimeContext {
             executor: TaskExecutor {
-                exit: self.exit.clone(),
+                exits: self.exits.clone(),
+                stage: ShutdownStage::General,
+                registry: self.registry.clone(),
                 signal_tx: self.signal_tx.clone(),
                 handle: self.runtime().handle().clone(),
                 log: self.log.clone(),
@@ -374,7 +390,9 @@ impl<E: EthSpec> Environment<E> {
     pub fn service_context(&mut self, service_name: String) -> RuntimeContext<E> {
         RuntimeContext {
             executor: TaskExecutor {
-                exit: self.exit.clone(),
+                exits: self.exits.clone(),
+                stage: ShutdownStage::General,
+                registry: self.registry.clone(),
                 signal_tx: self.signal_tx.clone(),
                 handle: self.runtime().handle().clone(),
                 log: self.log.new(o!("service" => service_name)),
@@ -386,8 +404,9 @@ impl<E: EthSpec> Environment<E> {
 
     /// Block the current thread until a shutdown signal is received.
     ///
-    /// This can be either the user Ctrl-C'ing or a task requesting to shutdown.
-    pub fn block_until_shutdown_requested(&mut self) -> Result<(), String> {
+    /// This can be either the user Ctrl-C'ing or a task requesting to shutdown. The returned
+    /// `ShutdownReason` determines the exit code of the process.
+    pub fn block_until_shutdown_requested(&mut self) -> Result<ShutdownReason, String> {
         // future of a task requesting to shutdown
         let mut rx = self
             .signal_rx
@@ -413,11 +432,47 @@ impl<E: EthSpec> Environment<E> {
             .block_on(future::select(inner_shutdown, ctrlc_oneshot))
         {
             future::Either::Left((Ok(reason), _)) => {
-                info!(self.log, "Internal shutdown received"; "reason" => reason);
-                Ok(())
+                info!(self.log, "Internal shutdown received"; "reason" => reason.message());
+                Ok(reason)
             }
             future::Either::Left((Err(e), _)) => Err(e.into()),
-            future::Either::Right((x, _)) => x.map_err(|e| format!("Ctrlc oneshot failed: {}", e)),
+            future::Either::Right((x, _)) => x
+                .map(|()| ShutdownReason::Success("Received SIGINT"))
+                .map_err(|e| format!("Ctrlc oneshot failed: {}", e)),
+        }
+    }
+
+    /// Shut down all spawned services, one stage at a time in the order of `SHUTDOWN_STAGES`.
+    ///
+    /// After signalling a stage, waits up to `stage_timeout` for its tasks to complete before
+    /// moving on to the next stage. Tasks which are still running after the timeout are logged.
+    pub fn shutdown(&mut self, stage_timeout: Duration) {
+        for stage in SHUTDOWN_STAGES.iter() {
+            if let Some(signal) = self.signals.remove(stage) {
+                debug!(self.log, "Shutting down stage"; "stage" => format!("{:?}", stage));
+                let _ = signal.fire();
+            }
+
+            let registry = self.registry.clone();
+            let deadline = Instant::now() + stage_timeout;
+            let running = self.runtime().block_on(async move {
+                loop {
+                    let running = registry.running(*stage);
+                    if running.is_empty() || Instant::now() >= deadline {
+                        break running;
+                    }
+                    tokio::time::delay_for(SHUTDOWN_POLL_INTERVAL).await;
+                }
+            });
+
+            if !running.is_empty() {
+                warn!(
+                    self.log,
+                    "Tasks failed to shut down in time";
+                    "stage" => format!("{:?}", stage),
+                    "tasks" => format!("{:?}", running),
+                );
+            }
         }
     }
 
@@ -427,9 +482,10 @@ impl<E: EthSpec> Environment<E> {
             .shutdown_timeout(std::time::Duration::from_secs(2))
     }
 
-    /// Fire exit signal which shuts down all spawned services
+    /// Fire exit signal which shuts down all spawned services, without waiting for them to
+    /// complete.
     pub fn fire_signal(&mut self) {
-        if let Some(signal) = self.signal.take() {
+        for (_, signal) in self.signals.drain() {
             let _ = signal.fire();
         }
     }
@@ -18,4 +18,8 @@ lazy_static! {
         "Time taken by blocking tasks",
         &["blocking_task_hist"]
     );
+    pub static ref LOG_OVERFLOW_TOTAL: Result<IntCounter> = try_create_int_counter(
+        "log_overflow_total",
+        "Count of log records dropped because the logging channel was full"
+    );
 }
@@ -0,0 +1,43 @@
+//! An asynchronous log drain which drops, rather than waits on, records that arrive while its
+//! channel is full.
+use crate::metrics;
+use slog::{Drain, OwnedKVList, Record};
+use slog_async::{AsyncCore, AsyncError};
+
+/// Passes records to a dedicated logging thread through a channel of `chan_size` records.
+///
+/// Records which arrive while the channel is full are dropped and counted by the
+/// `log_overflow_total` metric, so that a burst of logs never blocks the thread which raised
+/// them.
+pub struct OverflowCountingDrain {
+    core: AsyncCore,
+}
+
+impl OverflowCountingDrain {
+    pub fn new<D>(drain: D, chan_size: usize) -> Self
+    where
+        D: Drain<Ok = (), Err = slog::Never> + Send + 'static,
+    {
+        Self {
+            core: AsyncCore::custom(drain)
+                .chan_size(chan_size)
+                .blocking(false)
+                .build(),
+        }
+    }
+}
+
+impl Drain for OverflowCountingDrain {
+    type Ok = ();
+    type Err = AsyncError;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<Self::Ok, Self::Err> {
+        match self.core.log(record, values) {
+            Err(AsyncError::Full) => {
+                metrics::inc_counter(&metrics::LOG_OVERFLOW_TOTAL);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
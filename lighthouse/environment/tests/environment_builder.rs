@@ -1,8 +1,11 @@
 #![cfg(test)]
 
-use environment::EnvironmentBuilder;
+use environment::{EnvironmentBuilder, ShutdownStage};
 use eth2_testnet_config::Eth2TestnetConfig;
+use parking_lot::Mutex;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use types::{Epoch, MainnetEthSpec, YamlConfig};
 
 fn builder() -> EnvironmentBuilder<MainnetEthSpec> {
@@ -42,3 +45,50 @@ mod setup_eth2_config {
         }
     }
 }
+
+mod shutdown {
+    use super::*;
+
+    #[test]
+    fn stages_shut_down_in_order() {
+        let mut environment = builder().build().expect("should build environment");
+        let executor = environment.core_context().executor;
+        let stopped = Arc::new(Mutex::new(vec![]));
+
+        // Spawn in reverse order to ensure spawn order is irrelevant.
+        for stage in [
+            ShutdownStage::Store,
+            ShutdownStage::Eth1,
+            ShutdownStage::Network,
+            ShutdownStage::Sync,
+            ShutdownStage::Api,
+        ]
+        .iter()
+        {
+            let executor = executor.clone().with_shutdown_stage(*stage);
+            let exit = executor.exit();
+            let stopped = stopped.clone();
+            let stage = *stage;
+            executor.spawn_without_exit(
+                async move {
+                    let _ = exit.await;
+                    stopped.lock().push(stage);
+                },
+                "test",
+            );
+        }
+
+        environment.shutdown(Duration::from_secs(1));
+
+        assert_eq!(
+            *stopped.lock(),
+            vec![
+                ShutdownStage::Api,
+                ShutdownStage::Sync,
+                ShutdownStage::Network,
+                ShutdownStage::Eth1,
+                ShutdownStage::Store
+            ]
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Loads the flags of the `beacon_node` and `validator_client` subcommands from a YAML file and
+//! from environment variables, so that long lists of flags need not be kept on the command line.
+//!
+//! Each flag takes its value from the first of these which sets it:
+//!
+//! 1. The command line.
+//! 2. An environment variable named after the flag, e.g. `LIGHTHOUSE_HTTP_PORT` for `--http-port`.
+//! 3. The file given with `--config-file`, e.g. `http-port: 5052`.
+//! 4. The default value of the flag.
+//!
+//! With `--dump-config`, the flags set by any of the first three are printed as a config file
+//! instead of running the subcommand.
+use clap::{App, ArgMatches};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs::File;
+
+pub const CONFIG_FILE_FLAG: &str = "config-file";
+pub const DUMP_CONFIG_FLAG: &str = "dump-config";
+/// The prefix of the environment variables which set flags.
+const ENV_PREFIX: &str = "LIGHTHOUSE_";
+/// The subcommands whose flags may be set by a config file or the environment.
+const SUBCOMMANDS: &[&str] = &["beacon_node", "validator_client"];
+
+/// Flags and their values, where `None` is the value of a flag which takes no value.
+type Flags = BTreeMap<String, Option<String>>;
+
+/// Parses the command line of the process with `app`, adding the flags set by the environment
+/// and the config file which are not on the command line.
+///
+/// Prints the effective flags and exits the process if `--dump-config` is present.
+pub fn get_matches<'a>(app: App<'a, '_>) -> Result<ArgMatches<'a>, String> {
+    let args = std::env::args_os().collect::<Vec<_>>();
+    let matches = app.clone().get_matches_from(args.clone());
+
+    let added = match matches.subcommand() {
+        (name, Some(sub_matches)) if SUBCOMMANDS.contains(&name) => {
+            let mut flags = Flags::new();
+            if let Some(path) = sub_matches.value_of(CONFIG_FILE_FLAG) {
+                flags.extend(load_config_file(path)?);
+            }
+            flags.extend(env_flags(std::env::vars()));
+
+            flags
+                .into_iter()
+                .filter(|(name, _)| {
+                    matches.occurrences_of(name) == 0 && sub_matches.occurrences_of(name) == 0
+                })
+                .collect::<Flags>()
+        }
+        (_, Some(sub_matches))
+            if sub_matches.is_present(CONFIG_FILE_FLAG)
+                || sub_matches.is_present(DUMP_CONFIG_FLAG) =>
+        {
+            return Err(format!(
+                "--{} and --{} are only supported by the {} subcommands",
+                CONFIG_FILE_FLAG,
+                DUMP_CONFIG_FLAG,
+                SUBCOMMANDS.join(" and ")
+            ));
+        }
+        _ => Flags::new(),
+    };
+
+    let cli_flag_names = long_flag_names(&args);
+    let matches = if added.is_empty() {
+        matches
+    } else {
+        // Flags of the top-level command are given before the subcommand, the others after it.
+        let (top_level, sub_level): (Vec<_>, Vec<_>) =
+            to_args(&added).into_iter().partition(|arg| {
+                app.clone()
+                    .get_matches_from_safe(vec![args[0].clone(), arg.clone()])
+                    .is_ok()
+            });
+        let args = std::iter::once(args[0].clone())
+            .chain(top_level)
+            .chain(args.into_iter().skip(1))
+            .chain(sub_level)
+            .collect::<Vec<_>>();
+        app.get_matches_from_safe(args).map_err(|e| {
+            format!(
+                "Invalid flag from the environment or --{}: {}",
+                CONFIG_FILE_FLAG, e.message
+            )
+        })?
+    };
+
+    if let (_, Some(sub_matches)) = matches.subcommand() {
+        if sub_matches.is_present(DUMP_CONFIG_FLAG) {
+            let names = cli_flag_names.iter().chain(added.keys());
+            let yaml = serde_yaml::to_string(&effective_flags(sub_matches, names))
+                .map_err(|e| format!("Unable to serialize config: {:?}", e))?;
+            println!("{}", yaml);
+            std::process::exit(0);
+        }
+    }
+
+    Ok(matches)
+}
+
+/// Reads the flags from a YAML file which maps the long name of each flag to its value.
+fn load_config_file(path: &str) -> Result<Flags, String> {
+    let file = File::open(path).map_err(|e| format!("Unable to open {}: {:?}", path, e))?;
+    let values: BTreeMap<String, Value> =
+        serde_yaml::from_reader(file).map_err(|e| format!("Unable to parse {}: {:?}", path, e))?;
+
+    let mut flags = Flags::new();
+    for (name, value) in values {
+        if name == CONFIG_FILE_FLAG || name == DUMP_CONFIG_FLAG {
+            continue;
+        }
+        if let Some(value) = flag_value(&value)
+            .map_err(|e| format!("Invalid value for {} in {}: {}", name, path, e))?
+        {
+            flags.insert(name, value);
+        }
+    }
+    Ok(flags)
+}
+
+/// Converts a YAML value to the value of a flag.
+///
+/// Returns `None` if the flag is not set, i.e. it is `false` or null. `true` sets a flag which
+/// takes no value and a list is joined with commas.
+fn flag_value(value: &Value) -> Result<Option<Option<String>>, String> {
+    match value {
+        Value::Null | Value::Bool(false) => Ok(None),
+        Value::Bool(true) => Ok(Some(None)),
+        Value::Sequence(values) => {
+            let values = values
+                .iter()
+                .map(|value| match scalar(value) {
+                    Some(s) => Ok(s),
+                    None => Err("lists may only contain strings and numbers".to_string()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Some(Some(values.join(","))))
+        }
+        value => scalar(value)
+            .map(|s| Some(Some(s)))
+            .ok_or_else(|| "expected a string, number, boolean or list".to_string()),
+    }
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the flags set by `LIGHTHOUSE_`-prefixed variables, where the flag name is the rest of
+/// the variable name in lower case with `_` replaced by `-`.
+///
+/// A value of `true` sets a flag which takes no value and `false` leaves it unset.
+fn env_flags<I: Iterator<Item = (String, String)>>(vars: I) -> Flags {
+    vars.filter_map(|(key, value)| {
+        let name = key
+            .strip_prefix(ENV_PREFIX)?
+            .to_lowercase()
+            .replace('_', "-");
+        if name.is_empty() || name == CONFIG_FILE_FLAG || name == DUMP_CONFIG_FLAG {
+            return None;
+        }
+        match value.as_str() {
+            "false" => None,
+            "true" => Some((name, None)),
+            _ => Some((name, Some(value))),
+        }
+    })
+    .collect()
+}
+
+/// Converts flags to command line arguments.
+fn to_args(flags: &Flags) -> Vec<OsString> {
+    flags
+        .iter()
+        .map(|(name, value)| match value {
+            Some(value) => format!("--{}={}", name, value).into(),
+            None => format!("--{}", name).into(),
+        })
+        .collect()
+}
+
+/// Returns the names of the flags given in their long form in `args`.
+fn long_flag_names(args: &[OsString]) -> Vec<String> {
+    args.iter()
+        .filter_map(|arg| arg.to_str()?.strip_prefix("--"))
+        .filter_map(|flag| flag.split('=').next())
+        .filter(|name| !name.is_empty() && *name != CONFIG_FILE_FLAG && *name != DUMP_CONFIG_FLAG)
+        .map(String::from)
+        .collect()
+}
+
+/// Returns the value of each of `names` in `matches`, in the form of a config file.
+fn effective_flags<'a, I: Iterator<Item = &'a String>>(
+    matches: &ArgMatches,
+    names: I,
+) -> BTreeMap<String, Value> {
+    names
+        .filter_map(|name| {
+            let value = match matches.values_of(name) {
+                Some(values) => Value::String(values.collect::<Vec<_>>().join(",")),
+                None if matches.is_present(name) => Value::Bool(true),
+                None => return None,
+            };
+            Some((name.clone(), value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yaml_values() {
+        let yaml = "
+            http: true
+            http-port: 5052
+            staking: false
+            boot-nodes:
+              - enr:-a
+              - enr:-b
+            graffiti: ~
+            testnet: medalla
+        ";
+        let values: BTreeMap<String, Value> = serde_yaml::from_str(yaml).unwrap();
+        let flags = values
+            .iter()
+            .filter_map(|(name, value)| Some((name.clone(), flag_value(value).unwrap()?)))
+            .collect::<Flags>();
+
+        let args = to_args(&flags);
+        assert_eq!(
+            args,
+            vec![
+                OsString::from("--boot-nodes=enr:-a,enr:-b"),
+                OsString::from("--http"),
+                OsString::from("--http-port=5052"),
+                OsString::from("--testnet=medalla"),
+            ]
+        );
+
+        let nested: Value = serde_yaml::from_str("port: 1").unwrap();
+        assert!(flag_value(&nested).is_err());
+    }
+
+    #[test]
+    fn env_names() {
+        let vars = vec![
+            ("LIGHTHOUSE_HTTP_PORT", "5052"),
+            ("LIGHTHOUSE_STAKING", "true"),
+            ("LIGHTHOUSE_DISABLE_UPNP", "false"),
+            ("LIGHTHOUSE_", "1"),
+            ("LIGHTHOUSE_CONFIG_FILE", "/tmp/config.yaml"),
+            ("HOME", "/root"),
+        ];
+        let flags = env_flags(
+            vars.into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string())),
+        );
+
+        let mut expected = Flags::new();
+        expected.insert("http-port".to_string(), Some("5052".to_string()));
+        expected.insert("staking".to_string(), None);
+        assert_eq!(flags, expected);
+    }
+
+    #[test]
+    fn long_flags() {
+        let args = [
+            "lighthouse",
+            "-d",
+            "/tmp",
+            "bn",
+            "--http",
+            "--port=9000",
+            "--dump-config",
+        ]
+        .iter()
+        .map(OsString::from)
+        .collect::<Vec<_>>();
+        assert_eq!(long_flag_names(&args), vec!["http", "port"]);
+    }
+}
@@ -0,0 +1,171 @@
+//! Runs an ephemeral beacon node and validator client in a single process.
+//!
+//! The chain starts from an interop genesis state at the time the command is run, with all the
+//! genesis validators managed by the validator client using the insecure, deterministic interop
+//! keys. All data is stored in temporary directories which are removed on shutdown.
+//!
+//! Intended for developers who want to test against the HTTP API of a live chain without running
+//! a testnet. Not for production.
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use node_test_rig::{
+    testing_client_config, ClientGenesis, LocalBeaconNode, LocalValidatorClient, ValidatorConfig,
+    ValidatorFiles,
+};
+use slog::info;
+use std::net::Ipv4Addr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use types::EthSpec;
+
+pub const CMD: &str = "dev";
+pub const VALIDATOR_COUNT_FLAG: &str = "validator-count";
+pub const SECONDS_PER_SLOT_FLAG: &str = "seconds-per-slot";
+pub const HTTP_ADDRESS_FLAG: &str = "http-address";
+pub const HTTP_PORT_FLAG: &str = "http-port";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "Runs an ephemeral beacon node and validator client in this process, starting a new \
+             chain from an interop genesis state. All genesis validators use the insecure interop \
+             keys. Intended for testing applications against the HTTP API, never for production.",
+        )
+        .arg(
+            Arg::with_name(VALIDATOR_COUNT_FLAG)
+                .long(VALIDATOR_COUNT_FLAG)
+                .value_name("COUNT")
+                .help("The number of genesis validators, all run by the validator client.")
+                .takes_value(true)
+                .default_value("64"),
+        )
+        .arg(
+            Arg::with_name(SECONDS_PER_SLOT_FLAG)
+                .long(SECONDS_PER_SLOT_FLAG)
+                .value_name("SECONDS")
+                .help("The duration of each slot.")
+                .takes_value(true)
+                .default_value("1"),
+        )
+        .arg(
+            Arg::with_name(HTTP_ADDRESS_FLAG)
+                .long(HTTP_ADDRESS_FLAG)
+                .value_name("ADDRESS")
+                .help("Set the listen address for the beacon node HTTP API.")
+                .takes_value(true)
+                .default_value("127.0.0.1"),
+        )
+        .arg(
+            Arg::with_name(HTTP_PORT_FLAG)
+                .long(HTTP_PORT_FLAG)
+                .value_name("PORT")
+                .help("Set the listen TCP port for the beacon node HTTP API.")
+                .takes_value(true)
+                .default_value("5052"),
+        )
+}
+
+/// A beacon node and validator client running in this process.
+///
+/// The validator client is declared first so it is dropped before the beacon node it connects to.
+pub struct DevNode<E: EthSpec> {
+    pub validator_client: LocalValidatorClient<E>,
+    pub beacon_node: LocalBeaconNode<E>,
+}
+
+/// Starts a `DevNode` in `environment`, as configured by `matches`.
+pub fn run<E: EthSpec>(
+    matches: &ArgMatches,
+    environment: &mut Environment<E>,
+) -> Result<DevNode<E>, String> {
+    let validator_count: usize = clap_utils::parse_required(matches, VALIDATOR_COUNT_FLAG)?;
+    let seconds_per_slot: u64 = clap_utils::parse_required(matches, SECONDS_PER_SLOT_FLAG)?;
+    let http_address: Ipv4Addr = clap_utils::parse_required(matches, HTTP_ADDRESS_FLAG)?;
+    let http_port: u16 = clap_utils::parse_required(matches, HTTP_PORT_FLAG)?;
+
+    if validator_count == 0 {
+        return Err(format!(
+            "--{} must be greater than zero",
+            VALIDATOR_COUNT_FLAG
+        ));
+    }
+    if seconds_per_slot == 0 {
+        return Err(format!(
+            "--{} must be greater than zero",
+            SECONDS_PER_SLOT_FLAG
+        ));
+    }
+
+    // The spec must be modified before any contexts are created, since each holds its own copy.
+    environment.eth2_config.spec.milliseconds_per_slot = seconds_per_slot * 1_000;
+
+    let log = environment.core_context().log().clone();
+
+    info!(
+        log,
+        "Generating interop keystores";
+        "validator_count" => validator_count,
+    );
+    let indices = (0..validator_count).collect::<Vec<_>>();
+    let validator_files = ValidatorFiles::with_keystores(&indices)?;
+
+    let genesis_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("Unable to read system time: {:?}", e))?
+        .as_secs();
+
+    let mut beacon_config = testing_client_config();
+    beacon_config.genesis = ClientGenesis::Interop {
+        validator_count,
+        genesis_time,
+    };
+    beacon_config.network.disable_discovery = true;
+    beacon_config.rest_api.listen_address = http_address;
+    beacon_config.rest_api.port = http_port;
+    beacon_config.websocket_server.enabled = false;
+
+    let context = environment.core_context();
+    let beacon_node = environment
+        .runtime()
+        .block_on(LocalBeaconNode::production(context, beacon_config))
+        .map_err(|e| format!("Failed to start beacon node: {}", e))?;
+
+    let http_listen_addr = beacon_node
+        .client
+        .http_listen_addr()
+        .ok_or_else(|| "The beacon node HTTP API is not running".to_string())?;
+    let http_server = format!(
+        "http://{}:{}",
+        http_listen_addr.ip(),
+        http_listen_addr.port()
+    );
+
+    let validator_config = ValidatorConfig {
+        beacon_nodes: vec![http_server.clone()],
+        allow_unsynced_beacon_node: true,
+        ..ValidatorConfig::default()
+    };
+
+    let context = environment.core_context();
+    let validator_client = environment
+        .runtime()
+        .block_on(LocalValidatorClient::production_with_insecure_keypairs(
+            context,
+            validator_config,
+            validator_files,
+        ))
+        .map_err(|e| format!("Failed to start validator client: {}", e))?;
+
+    info!(
+        log,
+        "Dev node started";
+        "http_api" => http_server,
+        "seconds_per_slot" => seconds_per_slot,
+        "validator_count" => validator_count,
+        "genesis_time" => genesis_time,
+    );
+
+    Ok(DevNode {
+        validator_client,
+        beacon_node,
+    })
+}
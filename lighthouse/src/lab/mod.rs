@@ -0,0 +1,185 @@
+//! Runs several independent chains in a single process, for research and experimentation.
+//!
+//! Each chain is defined in a YAML file and has its own beacon node, validator client, data
+//! directory, spec and HTTP API port. The chains do not connect to each other or to any public
+//! network. All validators use the insecure, deterministic interop keys.
+//!
+//! Experimental, and never for production.
+mod supervisor;
+
+pub use supervisor::{LabChain, Supervisor};
+
+use clap::{App, Arg, ArgMatches};
+use environment::Environment;
+use serde_derive::Deserialize;
+use slog::info;
+use std::collections::HashSet;
+use std::fs::File;
+use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
+use types::EthSpec;
+
+pub const CMD: &str = "lab";
+pub const CONFIG_FLAG: &str = "config";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .about(
+            "EXPERIMENTAL. Runs several independent chains in this process, each with its own \
+             beacon node, validator client, data directory, spec and HTTP API port. All \
+             validators use the insecure interop keys. Intended for research, never for \
+             production.",
+        )
+        .arg(
+            Arg::with_name(CONFIG_FLAG)
+                .long(CONFIG_FLAG)
+                .value_name("FILE")
+                .help("A YAML file which defines the chains to run.")
+                .takes_value(true)
+                .required(true),
+        )
+}
+
+/// The contents of the file supplied to `--config`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LabConfig {
+    /// The listen address of the HTTP API of every chain.
+    #[serde(default = "default_http_address")]
+    pub http_address: Ipv4Addr,
+    /// The chains to run, which are started in order.
+    pub chains: Vec<ChainConfig>,
+}
+
+/// The definition of a single chain in a `LabConfig`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainConfig {
+    /// A unique name, which is added to the logs of the chain.
+    pub name: String,
+    /// The TCP port of the HTTP API of the beacon node.
+    pub http_port: u16,
+    /// The data directory of the beacon node. A temporary directory is used if omitted, which is
+    /// removed on shutdown.
+    #[serde(default)]
+    pub datadir: Option<PathBuf>,
+    /// A YAML chain config which replaces the spec given by `--spec` for this chain. It must use
+    /// the same preset constants as `--spec`.
+    #[serde(default)]
+    pub spec_file: Option<PathBuf>,
+    /// Overrides the slot duration of the spec.
+    #[serde(default)]
+    pub seconds_per_slot: Option<u64>,
+    /// The number of genesis validators, all run by the validator client of the chain.
+    #[serde(default = "default_validator_count")]
+    pub validator_count: usize,
+    /// The genesis time of the chain. Defaults to the time the chain is started.
+    ///
+    /// Ignored if the chain already exists in `datadir`.
+    #[serde(default)]
+    pub genesis_time: Option<u64>,
+}
+
+fn default_http_address() -> Ipv4Addr {
+    Ipv4Addr::LOCALHOST
+}
+
+fn default_validator_count() -> usize {
+    64
+}
+
+impl LabConfig {
+    /// Read and validate the config in the YAML file at `path`.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("Unable to open {:?}: {:?}", path, e))?;
+        let config: Self = serde_yaml::from_reader(file)
+            .map_err(|e| format!("Unable to parse {:?}: {:?}", path, e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Returns an error if the chains could not all run at once.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.chains.is_empty() {
+            return Err("The lab config must define at least one chain".to_string());
+        }
+
+        let mut names = HashSet::new();
+        let mut http_ports = HashSet::new();
+        let mut datadirs = HashSet::new();
+        for chain in &self.chains {
+            if chain.name.is_empty()
+                || !chain
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(format!(
+                    "Invalid chain name {:?}, only letters, digits, '-' and '_' are allowed",
+                    chain.name
+                ));
+            }
+            if !names.insert(&chain.name) {
+                return Err(format!("Duplicate chain name {:?}", chain.name));
+            }
+            if !http_ports.insert(chain.http_port) {
+                return Err(format!(
+                    "Chain {:?} uses the HTTP port {} of another chain",
+                    chain.name, chain.http_port
+                ));
+            }
+            if let Some(datadir) = &chain.datadir {
+                if !datadirs.insert(datadir) {
+                    return Err(format!(
+                        "Chain {:?} uses the datadir {:?} of another chain",
+                        chain.name, datadir
+                    ));
+                }
+            }
+            if chain.validator_count == 0 {
+                return Err(format!(
+                    "The validator_count of chain {:?} must be greater than zero",
+                    chain.name
+                ));
+            }
+            if chain.seconds_per_slot == Some(0) {
+                return Err(format!(
+                    "The seconds_per_slot of chain {:?} must be greater than zero",
+                    chain.name
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Starts every chain defined by the `--config` file in `matches`, returning the `Supervisor`
+/// which manages them.
+pub fn run<E: EthSpec>(
+    matches: &ArgMatches,
+    environment: &mut Environment<E>,
+) -> Result<Supervisor<E>, String> {
+    let config_path: PathBuf = clap_utils::parse_required(matches, CONFIG_FLAG)?;
+    let config = LabConfig::from_file(&config_path)?;
+
+    let log = environment.core_context().log().clone();
+    let mut supervisor = Supervisor::new(log.clone());
+
+    for chain_config in &config.chains {
+        let context = environment.service_context(chain_config.name.clone());
+        environment.runtime().block_on(supervisor.start_chain(
+            context,
+            chain_config,
+            config.http_address,
+        ))?;
+    }
+
+    info!(
+        log,
+        "Lab started";
+        "chains" => supervisor.chains().len(),
+    );
+
+    Ok(supervisor)
+}
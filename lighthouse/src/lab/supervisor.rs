@@ -0,0 +1,169 @@
+use super::ChainConfig;
+use beacon_node::{ProductionBeaconNode, ProductionClient};
+use environment::RuntimeContext;
+use node_test_rig::{
+    testing_client_config, ClientGenesis, LocalValidatorClient, ValidatorConfig, ValidatorFiles,
+};
+use slog::{info, Logger};
+use std::net::Ipv4Addr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tempdir::TempDir;
+use types::{EthSpec, YamlConfig};
+
+/// A chain run by a `Supervisor`.
+///
+/// The fields are declared in the order they must be dropped: the validator client before the
+/// beacon node it connects to, and the beacon node before its temporary data directory.
+pub struct LabChain<E: EthSpec> {
+    pub name: String,
+    pub http_api: String,
+    pub validator_client: LocalValidatorClient<E>,
+    pub beacon_node: ProductionClient<E>,
+    /// Held so that a temporary datadir is removed when the chain is dropped.
+    _temp_datadir: Option<TempDir>,
+}
+
+/// Starts and stops the chains of a lab.
+///
+/// Chains are stopped in the reverse of the order they were started when the `Supervisor` is
+/// dropped.
+pub struct Supervisor<E: EthSpec> {
+    chains: Vec<LabChain<E>>,
+    log: Logger,
+}
+
+impl<E: EthSpec> Supervisor<E> {
+    pub fn new(log: Logger) -> Self {
+        Self {
+            chains: vec![],
+            log,
+        }
+    }
+
+    /// The chains that have been started, in the order they were started.
+    pub fn chains(&self) -> &[LabChain<E>] {
+        &self.chains
+    }
+
+    /// Start a beacon node and validator client for the chain defined by `config`.
+    ///
+    /// The spec of `context` is replaced by the spec of the chain, so the `context` should not be
+    /// shared with any other chain.
+    pub async fn start_chain(
+        &mut self,
+        mut context: RuntimeContext<E>,
+        config: &ChainConfig,
+        http_address: Ipv4Addr,
+    ) -> Result<(), String> {
+        let mut spec = match &config.spec_file {
+            Some(spec_file) => YamlConfig::from_file(spec_file)?
+                .apply_to_chain_spec::<E>(&context.eth2_config.spec)
+                .ok_or_else(|| {
+                    format!(
+                        "The spec file of chain {:?} does not match the constants of --spec",
+                        config.name
+                    )
+                })?,
+            None => context.eth2_config.spec.clone(),
+        };
+        if let Some(seconds_per_slot) = config.seconds_per_slot {
+            spec.milliseconds_per_slot = seconds_per_slot * 1_000;
+        }
+        context.eth2_config.spec = spec;
+
+        let genesis_time = match config.genesis_time {
+            Some(genesis_time) => genesis_time,
+            None => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|e| format!("Unable to read system time: {:?}", e))?
+                .as_secs(),
+        };
+
+        let mut beacon_config = testing_client_config();
+        beacon_config.genesis = ClientGenesis::Interop {
+            validator_count: config.validator_count,
+            genesis_time,
+        };
+        beacon_config.network.disable_discovery = true;
+        beacon_config.rest_api.listen_address = http_address;
+        beacon_config.rest_api.port = config.http_port;
+        beacon_config.websocket_server.enabled = false;
+
+        let (datadir, temp_datadir) = match &config.datadir {
+            Some(datadir) => (datadir.clone(), None),
+            None => {
+                let temp_datadir = TempDir::new(&format!("lighthouse_lab_{}", config.name))
+                    .map_err(|e| format!("Unable to create temporary datadir: {:?}", e))?;
+                (temp_datadir.path().to_path_buf(), Some(temp_datadir))
+            }
+        };
+        beacon_config.network.network_dir = datadir.join("network");
+        beacon_config.data_dir = datadir.clone();
+
+        let beacon_node = ProductionBeaconNode::new(context.clone(), beacon_config)
+            .await
+            .map_err(|e| format!("Failed to start beacon node of {:?}: {}", config.name, e))?
+            .into_inner();
+
+        let http_listen_addr = beacon_node.http_listen_addr().ok_or_else(|| {
+            format!(
+                "The beacon node HTTP API of {:?} is not running",
+                config.name
+            )
+        })?;
+        let http_api = format!(
+            "http://{}:{}",
+            http_listen_addr.ip(),
+            http_listen_addr.port()
+        );
+
+        let indices = (0..config.validator_count).collect::<Vec<_>>();
+        let validator_files = ValidatorFiles::with_keystores(&indices)?;
+        let validator_config = ValidatorConfig {
+            beacon_nodes: vec![http_api.clone()],
+            allow_unsynced_beacon_node: true,
+            ..ValidatorConfig::default()
+        };
+        let validator_client = LocalValidatorClient::production_with_insecure_keypairs(
+            context,
+            validator_config,
+            validator_files,
+        )
+        .await
+        .map_err(|e| {
+            format!(
+                "Failed to start validator client of {:?}: {}",
+                config.name, e
+            )
+        })?;
+
+        info!(
+            self.log,
+            "Lab chain started";
+            "name" => &config.name,
+            "http_api" => &http_api,
+            "datadir" => format!("{:?}", datadir),
+            "validator_count" => config.validator_count,
+            "genesis_time" => genesis_time,
+        );
+
+        self.chains.push(LabChain {
+            name: config.name.clone(),
+            http_api,
+            validator_client,
+            beacon_node,
+            _temp_datadir: temp_datadir,
+        });
+
+        Ok(())
+    }
+}
+
+impl<E: EthSpec> Drop for Supervisor<E> {
+    fn drop(&mut self) {
+        while let Some(chain) = self.chains.pop() {
+            info!(self.log, "Stopping lab chain"; "name" => &chain.name);
+            drop(chain);
+        }
+    }
+}
@@ -1,7 +1,11 @@
+mod config_file;
+mod dev;
+mod lab;
+
 use beacon_node::ProductionBeaconNode;
 use clap::{App, Arg, ArgMatches};
 use env_logger::{Builder, Env};
-use environment::EnvironmentBuilder;
+use environment::{EnvironmentBuilder, ShutdownReason, DEFAULT_SHUTDOWN_STAGE_TIMEOUT};
 use eth2_testnet_config::{Eth2TestnetConfig, DEFAULT_HARDCODED_TESTNET};
 use lighthouse_version::VERSION;
 use slog::{crit, info, warn};
@@ -24,22 +28,19 @@ fn bls_library_name() -> &'static str {
 }
 
 fn main() {
+    let version = VERSION.replace("Lighthouse/", "");
+    let long_version = format!("{}\nBLS Library: {}", version, bls_library_name());
+
     // Parse the CLI parameters.
-    let matches = App::new("Lighthouse")
-        .version(VERSION.replace("Lighthouse/", "").as_str())
+    let app = App::new("Lighthouse")
+        .version(version.as_str())
         .author("Sigma Prime <contact@sigmaprime.io>")
         .setting(clap::AppSettings::ColoredHelp)
         .about(
             "Ethereum 2.0 client by Sigma Prime. Provides a full-featured beacon \
              node, a validator client and utilities for managing validator accounts.",
         )
-        .long_version(
-            format!(
-                "{}\n\
-                 BLS Library: {}",
-                 VERSION.replace("Lighthouse/", ""), bls_library_name()
-            ).as_str()
-        )
+        .long_version(long_version.as_str())
         .arg(
             Arg::with_name("spec")
                 .short("s")
@@ -118,11 +119,43 @@ fn main() {
                 .global(true)
 
         )
+        .arg(
+            Arg::with_name(config_file::CONFIG_FILE_FLAG)
+                .long(config_file::CONFIG_FILE_FLAG)
+                .value_name("FILE")
+                .help(
+                    "A YAML file mapping the long names of beacon_node or validator_client flags \
+                     to their values, e.g. `http-port: 5052`. Flags on the command line take \
+                     precedence, followed by environment variables named after the flag (e.g. \
+                     LIGHTHOUSE_HTTP_PORT), then the file, then the default values.",
+                )
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name(config_file::DUMP_CONFIG_FLAG)
+                .long(config_file::DUMP_CONFIG_FLAG)
+                .help(
+                    "Print the flags set by the command line, the environment and --config-file \
+                     as a config file, then exit.",
+                )
+                .takes_value(false)
+                .global(true),
+        )
         .subcommand(beacon_node::cli_app())
         .subcommand(boot_node::cli_app())
         .subcommand(validator_client::cli_app())
         .subcommand(account_manager::cli_app())
-        .get_matches();
+        .subcommand(dev::cli_app())
+        .subcommand(lab::cli_app());
+
+    let matches = match config_file::get_matches(app) {
+        Ok(matches) => matches,
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(1)
+        }
+    };
 
     // boot node subcommand circumvents the environment
     if let Some(bootnode_matches) = matches.subcommand_matches("boot_node") {
@@ -196,7 +229,11 @@ fn run<E: EthSpec>(
     if matches.is_present("testnet-dir") {
         optional_testnet_config = clap_utils::parse_testnet_dir(matches, "testnet-dir")?;
     };
-    if optional_testnet_config.is_none() {
+    // The dev and lab chains are created locally, so they use the spec given by `--spec` rather
+    // than that of a public testnet.
+    let local_chains = matches.subcommand_matches(dev::CMD).is_some()
+        || matches.subcommand_matches(lab::CMD).is_some();
+    if optional_testnet_config.is_none() && !local_chains {
         optional_testnet_config = Eth2TestnetConfig::hard_coded_default()?;
     }
 
@@ -229,6 +266,8 @@ fn run<E: EthSpec>(
     let optional_testnet_dir = clap_utils::parse_optional::<PathBuf>(matches, "testnet-dir")?;
 
     let testnet_name = match (optional_testnet, optional_testnet_dir) {
+        (None, None) if matches.subcommand_matches(dev::CMD).is_some() => "dev".to_string(),
+        (None, None) if matches.subcommand_matches(lab::CMD).is_some() => "lab".to_string(),
         (Some(testnet), None) => testnet,
         (None, Some(testnet_dir)) => format!("custom ({})", testnet_dir.display()),
         (None, None) => DEFAULT_HARDCODED_TESTNET.to_string(),
@@ -298,20 +337,43 @@ fn run<E: EthSpec>(
         None
     };
 
-    if beacon_node.is_none() && validator_client.is_none() {
+    let dev_node = if let Some(sub_matches) = matches.subcommand_matches(dev::CMD) {
+        Some(dev::run(sub_matches, &mut environment)?)
+    } else {
+        None
+    };
+
+    let lab_supervisor = if let Some(sub_matches) = matches.subcommand_matches(lab::CMD) {
+        Some(lab::run(sub_matches, &mut environment)?)
+    } else {
+        None
+    };
+
+    if beacon_node.is_none()
+        && validator_client.is_none()
+        && dev_node.is_none()
+        && lab_supervisor.is_none()
+    {
         crit!(log, "No subcommand supplied. See --help .");
         return Err("No subcommand supplied.".into());
     }
 
     // Block this thread until we get a ctrl-c or a task sends a shutdown signal.
-    environment.block_until_shutdown_requested()?;
-    info!(log, "Shutting down..");
+    let shutdown_reason = environment.block_until_shutdown_requested()?;
+    info!(log, "Shutting down.."; "reason" => shutdown_reason.message());
 
-    environment.fire_signal();
+    // Stop the services in order, giving each a chance to finish its work.
+    environment.shutdown(DEFAULT_SHUTDOWN_STAGE_TIMEOUT);
     drop(beacon_node);
     drop(validator_client);
+    drop(dev_node);
+    drop(lab_supervisor);
 
     // Shutdown the environment once all tasks have completed.
     environment.shutdown_on_idle();
-    Ok(())
+
+    match shutdown_reason {
+        ShutdownReason::Success(_) => Ok(()),
+        ShutdownReason::Failure(msg) => Err(msg.to_string()),
+    }
 }
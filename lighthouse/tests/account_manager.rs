@@ -2,22 +2,34 @@
 
 use account_manager::{
     validator::{
-        create::*,
+        create::{MNEMONIC_FLAG as MNEMONIC_PATH_FLAG, *},
+        delete::{self, CMD as DELETE_CMD},
+        disable::CMD as DISABLE_CMD,
+        enable::{self, CMD as ENABLE_CMD},
         import::{self, CMD as IMPORT_CMD},
-        CMD as VALIDATOR_CMD,
+        modify_password::{self, CMD as MODIFY_PASSWORD_CMD},
+        slashing_protection::PUBKEYS_FLAG,
+        threshold, CMD as VALIDATOR_CMD,
     },
     wallet::{
-        create::{CMD as CREATE_CMD, *},
+        create::{CMD as CREATE_CMD, MNEMONIC_FLAG, *},
         list::CMD as LIST_CMD,
         CMD as WALLET_CMD,
     },
     BASE_DIR_FLAG, CMD as ACCOUNT_CMD, *,
 };
 use account_utils::{
-    eth2_keystore::KeystoreBuilder,
-    validator_definitions::{SigningDefinition, ValidatorDefinition, ValidatorDefinitions},
+    eth2_keystore::{Keystore, KeystoreBuilder},
+    threshold::{recover_secret_key, KeyShare, SHARE_KEYSTORE_FILE, SHARE_PASSWORD_FILE},
+    validator_definitions::{
+        SigningDefinition, ValidatorDefinition, ValidatorDefinitions, NIMBUS_KEYSTORE_FILE,
+        NIMBUS_SECRETS_DIR,
+    },
     ZeroizeString,
 };
+use slashing_protection::{
+    interchange::Interchange, SlashingDatabase, SLASHING_PROTECTION_FILENAME,
+};
 use std::env;
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Write};
@@ -25,7 +37,7 @@ use std::path::{Path, PathBuf};
 use std::process::{Command, Output, Stdio};
 use std::str::from_utf8;
 use tempfile::{tempdir, TempDir};
-use types::Keypair;
+use types::{Hash256, Keypair, Slot};
 use validator_dir::ValidatorDir;
 
 // TODO: create tests for the `lighthouse account validator deposit` command. This involves getting
@@ -69,6 +81,15 @@ fn dir_child_count<P: AsRef<Path>>(dir: P) -> usize {
     fs::read_dir(dir).expect("should read dir").count()
 }
 
+/// Returns the number of directories in a directory, which excludes the validator definitions and
+/// deposit data files of a validator directory.
+fn subdir_count<P: AsRef<Path>>(dir: P) -> usize {
+    fs::read_dir(dir)
+        .expect("should read dir")
+        .filter(|entry| entry.as_ref().expect("should read entry").path().is_dir())
+        .count()
+}
+
 /// Uses `lighthouse account wallet list` to list all wallets.
 fn list_wallets<P: AsRef<Path>>(base_dir: P) -> Vec<String> {
     let output = output_result(
@@ -328,19 +349,19 @@ fn validator_create() {
     let wallet = TestWallet::new(base_dir.path(), "wally");
     wallet.create_expect_success();
 
-    assert_eq!(dir_child_count(validator_dir.path()), 0);
+    assert_eq!(subdir_count(validator_dir.path()), 0);
 
     let validator = TestValidator::new(validator_dir.path(), secrets_dir.path(), wallet);
 
     // Create a validator _without_ storing the withdraw key.
     validator.create_expect_success(COUNT_FLAG, 1, false);
 
-    assert_eq!(dir_child_count(validator_dir.path()), 1);
+    assert_eq!(subdir_count(validator_dir.path()), 1);
 
     // Create a validator storing the withdraw key.
     validator.create_expect_success(COUNT_FLAG, 1, true);
 
-    assert_eq!(dir_child_count(validator_dir.path()), 2);
+    assert_eq!(subdir_count(validator_dir.path()), 2);
 
     // Use the at-most flag with less validators then are in the directory.
     assert_eq!(
@@ -348,7 +369,7 @@ fn validator_create() {
         0
     );
 
-    assert_eq!(dir_child_count(validator_dir.path()), 2);
+    assert_eq!(subdir_count(validator_dir.path()), 2);
 
     // Use the at-most flag with the same number of validators that are in the directory.
     assert_eq!(
@@ -356,7 +377,7 @@ fn validator_create() {
         0
     );
 
-    assert_eq!(dir_child_count(validator_dir.path()), 2);
+    assert_eq!(subdir_count(validator_dir.path()), 2);
 
     // Use the at-most flag with two more number of validators than are in the directory.
     assert_eq!(
@@ -364,7 +385,7 @@ fn validator_create() {
         2
     );
 
-    assert_eq!(dir_child_count(validator_dir.path()), 4);
+    assert_eq!(subdir_count(validator_dir.path()), 4);
 
     // Create multiple validators with the count flag.
     assert_eq!(
@@ -372,7 +393,160 @@ fn validator_create() {
         2
     );
 
-    assert_eq!(dir_child_count(validator_dir.path()), 6);
+    assert_eq!(subdir_count(validator_dir.path()), 6);
+}
+
+/// Creates `count` validators with `lighthouse account validator create`, deriving their keys as
+/// specified by `key_args`, and returns their public keys.
+fn create_validators(
+    base_dir: &Path,
+    validator_dir: &Path,
+    secrets_dir: &Path,
+    count: usize,
+    key_args: &[String],
+) -> Vec<String> {
+    let output = output_result(
+        validator_cmd()
+            .arg(format!("--{}", BASE_DIR_FLAG))
+            .arg(base_dir.as_os_str())
+            .arg(CREATE_CMD)
+            .arg(format!("--{}", VALIDATOR_DIR_FLAG))
+            .arg(validator_dir.as_os_str())
+            .arg(format!("--{}", SECRETS_DIR_FLAG))
+            .arg(secrets_dir.as_os_str())
+            .arg(format!("--{}", COUNT_FLAG))
+            .arg(count.to_string())
+            .args(key_args),
+    )
+    .unwrap();
+
+    from_utf8(&output.stdout)
+        .expect("stdout is not utf8")
+        .lines()
+        .map(|line| {
+            let tab = line.find('\t').expect("line must have tab");
+            line[tab + 1..].to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn validator_create_from_mnemonic() {
+    let base_dir = tempdir().unwrap();
+    let wallet = TestWallet::new(base_dir.path(), "wally");
+    wallet.create_expect_success();
+    let wallet_args = vec![
+        format!("--{}", WALLET_NAME_FLAG),
+        wallet.name.clone(),
+        format!("--{}", WALLET_PASSWORD_FLAG),
+        wallet.password_path().to_str().unwrap().to_string(),
+    ];
+    let mnemonic_args = |at_index: u32| {
+        vec![
+            format!("--{}", MNEMONIC_PATH_FLAG),
+            wallet.mnemonic_path().to_str().unwrap().to_string(),
+            format!("--{}", AT_INDEX_FLAG),
+            at_index.to_string(),
+        ]
+    };
+
+    let validator_dir = tempdir().unwrap();
+    let secrets_dir = tempdir().unwrap();
+    let create = |count, key_args: &[String]| {
+        create_validators(
+            base_dir.path(),
+            validator_dir.path(),
+            secrets_dir.path(),
+            count,
+            key_args,
+        )
+    };
+
+    // The wallet derives its first two validators.
+    let from_wallet = create(2, &wallet_args);
+    assert_eq!(from_wallet.len(), 2);
+
+    // The mnemonic of the wallet derives the same validators, from the given index. They exist
+    // already, so they are created in other directories.
+    let other_validator_dir = tempdir().unwrap();
+    let other_secrets_dir = tempdir().unwrap();
+    let from_mnemonic = create_validators(
+        base_dir.path(),
+        other_validator_dir.path(),
+        other_secrets_dir.path(),
+        1,
+        &mnemonic_args(1),
+    );
+    assert_eq!(from_mnemonic, vec![from_wallet[1].clone()]);
+
+    // Creating at an index does not advance the wallet.
+    let at_index = create(
+        1,
+        &[
+            wallet_args.clone(),
+            vec![format!("--{}", AT_INDEX_FLAG), "5".into()],
+        ]
+        .concat(),
+    );
+    let next = create(1, &wallet_args);
+    let expected = create_validators(
+        base_dir.path(),
+        other_validator_dir.path(),
+        other_secrets_dir.path(),
+        1,
+        &mnemonic_args(2),
+    );
+    assert_eq!(next, expected);
+    assert_ne!(at_index, next);
+
+    // Every validator is registered with its password file.
+    let created = [from_wallet, at_index, next].concat();
+    let definitions = ValidatorDefinitions::open(validator_dir.path()).unwrap();
+    assert_eq!(definitions.as_slice().len(), created.len());
+    for (def, pubkey) in definitions.as_slice().iter().zip(&created) {
+        assert!(def.enabled);
+        assert_eq!(format!("{:?}", def.voting_public_key), *pubkey);
+        match &def.signing_definition {
+            SigningDefinition::LocalKeystore {
+                voting_keystore_password_path: Some(path),
+                voting_keystore_password: None,
+                ..
+            } => assert!(path.exists(), "password file should exist"),
+            _ => panic!("should use a local keystore with a password file"),
+        }
+    }
+
+    // The deposit data of the validators created at once is written in the format of the
+    // launchpad.
+    let deposit_dir = tempdir().unwrap();
+    let deposit_secrets_dir = tempdir().unwrap();
+    let pubkeys = create_validators(
+        base_dir.path(),
+        deposit_dir.path(),
+        deposit_secrets_dir.path(),
+        2,
+        &mnemonic_args(0),
+    );
+    let deposit_files = fs::read_dir(deposit_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.starts_with(DEPOSIT_DATA_FILE_PREFIX))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(deposit_files.len(), 1);
+    let deposits: Vec<serde_yaml::Value> =
+        serde_yaml::from_reader(File::open(&deposit_files[0]).unwrap()).unwrap();
+    let deposit_pubkeys = deposits
+        .iter()
+        .map(|deposit| format!("0x{}", deposit["pubkey"].as_str().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(deposit_pubkeys, pubkeys);
+    assert!(deposits
+        .iter()
+        .all(|deposit| deposit["amount"].as_u64().is_some()));
 }
 
 /// Returns the `lighthouse account validator import` command.
@@ -408,7 +582,7 @@ fn validator_import_launchpad() {
     File::create(src_dir.path().join(NOT_KEYSTORE_NAME)).unwrap();
 
     let mut child = validator_import_cmd()
-        .arg(format!("--{}", import::STDIN_PASSWORD_FLAG)) // Using tty does not work well with tests.
+        .arg(format!("--{}", import::STDIN_INPUTS_FLAG)) // Using tty does not work well with tests.
         .arg(format!("--{}", import::DIR_FLAG))
         .arg(src_dir.path().as_os_str())
         .arg(format!("--{}", VALIDATOR_DIR_FLAG))
@@ -455,6 +629,83 @@ fn validator_import_launchpad() {
 
     let expected_def = ValidatorDefinition {
         enabled: true,
+        enable_at: None,
+        disable_at: None,
+        graffiti: None,
+        suggested_fee_recipient: None,
+        voting_public_key: keystore.public_key().unwrap(),
+        signing_definition: SigningDefinition::LocalKeystore {
+            voting_keystore_path,
+            voting_keystore_password_path: None,
+            voting_keystore_password: Some(ZeroizeString::from(PASSWORD.to_string())),
+        },
+    };
+
+    assert!(
+        defs.as_slice() == &[expected_def],
+        "validator defs file should be accurate"
+    );
+}
+
+#[test]
+fn validator_import_nimbus() {
+    const PASSWORD: &str = "cats";
+
+    let src_dir = tempdir().unwrap();
+    let dst_dir = tempdir().unwrap();
+
+    let keypair = Keypair::random();
+    let keystore = KeystoreBuilder::new(&keypair, PASSWORD.as_bytes(), "".into())
+        .unwrap()
+        .build()
+        .unwrap();
+    let pubkey_dir_name = format!("0x{}", keystore.pubkey());
+
+    // Create a keystore and its password in the Nimbus layout.
+    let src_keystore_dir = src_dir.path().join("validators").join(&pubkey_dir_name);
+    fs::create_dir_all(&src_keystore_dir).unwrap();
+    File::create(src_keystore_dir.join(NIMBUS_KEYSTORE_FILE))
+        .map(|mut file| keystore.to_json_writer(&mut file).unwrap())
+        .unwrap();
+    fs::create_dir_all(src_dir.path().join(NIMBUS_SECRETS_DIR)).unwrap();
+    fs::write(
+        src_dir
+            .path()
+            .join(NIMBUS_SECRETS_DIR)
+            .join(&pubkey_dir_name),
+        PASSWORD,
+    )
+    .unwrap();
+
+    // The password is read from the secrets directory, so nothing is read from stdin.
+    output_result(
+        validator_import_cmd()
+            .arg(format!("--{}", import::STDIN_INPUTS_FLAG))
+            .arg(format!("--{}", import::DIR_FLAG))
+            .arg(src_dir.path().as_os_str())
+            .arg(format!("--{}", VALIDATOR_DIR_FLAG))
+            .arg(dst_dir.path().as_os_str())
+            .stdin(Stdio::null()),
+    )
+    .unwrap();
+
+    let voting_keystore_path = dst_dir
+        .path()
+        .join(&pubkey_dir_name)
+        .join(NIMBUS_KEYSTORE_FILE);
+    assert!(
+        voting_keystore_path.exists(),
+        "keystore should be present in dst dir"
+    );
+
+    let defs = ValidatorDefinitions::open(&dst_dir).unwrap();
+
+    let expected_def = ValidatorDefinition {
+        enabled: true,
+        enable_at: None,
+        disable_at: None,
+        graffiti: None,
+        suggested_fee_recipient: None,
         voting_public_key: keystore.public_key().unwrap(),
         signing_definition: SigningDefinition::LocalKeystore {
             voting_keystore_path,
@@ -468,3 +719,440 @@ fn validator_import_launchpad() {
         "validator defs file should be accurate"
     );
 }
+
+#[test]
+fn validator_import_web3signer() {
+    const URL: &str = "https://signer.example.com:9000";
+
+    let dst_dir = tempdir().unwrap();
+    let pubkeys = vec![Keypair::random().pk, Keypair::random().pk];
+    let pubkeys_arg = pubkeys
+        .iter()
+        .map(|pubkey| format!("{:?}", pubkey))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    // Importing the same validators twice should not define them twice.
+    for _ in 0..2 {
+        output_result(
+            validator_import_cmd()
+                .arg(format!("--{}", import::WEB3SIGNER_URL_FLAG))
+                .arg(URL)
+                .arg(format!("--{}", PUBKEYS_FLAG))
+                .arg(&pubkeys_arg)
+                .arg(format!("--{}", VALIDATOR_DIR_FLAG))
+                .arg(dst_dir.path().as_os_str())
+                .stdin(Stdio::null()),
+        )
+        .unwrap();
+    }
+
+    let defs = ValidatorDefinitions::open(&dst_dir).unwrap();
+    let expected_defs = pubkeys
+        .into_iter()
+        .map(|pubkey| ValidatorDefinition::new_web3signer(pubkey, URL.to_string()))
+        .collect::<Vec<_>>();
+
+    assert!(
+        defs.as_slice() == expected_defs.as_slice(),
+        "validator defs file should be accurate"
+    );
+    assert_eq!(
+        dir_child_count(dst_dir.path()),
+        1,
+        "only the validator defs file should be created"
+    );
+}
+
+/// Returns a `ValidatorDefinition` for a random validator, with a keystore that doesn't exist.
+fn random_validator_definition<P: AsRef<Path>>(validator_dir: P) -> ValidatorDefinition {
+    ValidatorDefinition {
+        enabled: true,
+        enable_at: None,
+        disable_at: None,
+        graffiti: None,
+        suggested_fee_recipient: None,
+        voting_public_key: Keypair::random().pk,
+        signing_definition: SigningDefinition::LocalKeystore {
+            voting_keystore_path: validator_dir.as_ref().join("voting-keystore.json"),
+            voting_keystore_password_path: None,
+            voting_keystore_password: None,
+        },
+    }
+}
+
+/// Returns the `enabled` flag of each validator in the definitions file in `validator_dir`.
+fn enabled_flags<P: AsRef<Path>>(validator_dir: P) -> Vec<bool> {
+    ValidatorDefinitions::open(validator_dir)
+        .unwrap()
+        .as_slice()
+        .iter()
+        .map(|def| def.enabled)
+        .collect()
+}
+
+#[test]
+fn validator_enable_disable() {
+    let validator_dir = tempdir().unwrap();
+
+    let mut defs = ValidatorDefinitions::default();
+    defs.push(random_validator_definition(validator_dir.path()));
+    defs.push(random_validator_definition(validator_dir.path()));
+    defs.save(validator_dir.path()).unwrap();
+
+    let first_pubkey = defs.as_slice()[0].voting_public_key.to_hex_string();
+
+    // Disable the first validator by a prefix of its public key.
+    output_result(
+        validator_cmd()
+            .arg(DISABLE_CMD)
+            .arg(&first_pubkey[..40])
+            .arg(format!("--{}", VALIDATOR_DIR_FLAG))
+            .arg(validator_dir.path().as_os_str()),
+    )
+    .unwrap();
+    assert_eq!(enabled_flags(validator_dir.path()), vec![false, true]);
+
+    // A pattern that matches no validators is an error, and changes nothing.
+    output_result(
+        validator_cmd()
+            .arg(DISABLE_CMD)
+            .arg("0xffff*ffff")
+            .arg(format!("--{}", VALIDATOR_DIR_FLAG))
+            .arg(validator_dir.path().as_os_str()),
+    )
+    .unwrap_err();
+    assert_eq!(enabled_flags(validator_dir.path()), vec![false, true]);
+
+    // Disable every validator with a pattern.
+    output_result(
+        validator_cmd()
+            .arg(DISABLE_CMD)
+            .arg("0x*")
+            .arg(format!("--{}", VALIDATOR_DIR_FLAG))
+            .arg(validator_dir.path().as_os_str()),
+    )
+    .unwrap();
+    assert_eq!(enabled_flags(validator_dir.path()), vec![false, false]);
+
+    // Enable them all again.
+    output_result(
+        validator_cmd()
+            .arg(ENABLE_CMD)
+            .arg(format!("--{}", enable::ALL_FLAG))
+            .arg(format!("--{}", VALIDATOR_DIR_FLAG))
+            .arg(validator_dir.path().as_os_str()),
+    )
+    .unwrap();
+    assert_eq!(enabled_flags(validator_dir.path()), vec![true, true]);
+}
+
+#[test]
+fn validator_modify_password() {
+    const OLD_PASSWORD: &str = "cats";
+    const NEW_PASSWORD: &str = "dogs";
+
+    let validator_dir = tempdir().unwrap();
+    let voting_keystore_path = validator_dir.path().join("voting-keystore.json");
+
+    let keypair = Keypair::random();
+    let keystore = KeystoreBuilder::new(&keypair, OLD_PASSWORD.as_bytes(), "".into())
+        .unwrap()
+        .build()
+        .unwrap();
+    File::create(&voting_keystore_path)
+        .map(|mut file| keystore.to_json_writer(&mut file).unwrap())
+        .unwrap();
+
+    let mut defs = ValidatorDefinitions::default();
+    defs.push(ValidatorDefinition {
+        enabled: true,
+        enable_at: None,
+        disable_at: None,
+        graffiti: None,
+        suggested_fee_recipient: None,
+        voting_public_key: keypair.pk.clone(),
+        signing_definition: SigningDefinition::LocalKeystore {
+            voting_keystore_path: voting_keystore_path.clone(),
+            voting_keystore_password_path: None,
+            voting_keystore_password: Some(ZeroizeString::from(OLD_PASSWORD.to_string())),
+        },
+    });
+    defs.save(validator_dir.path()).unwrap();
+
+    let mut child = validator_cmd()
+        .arg(MODIFY_PASSWORD_CMD)
+        .arg(keypair.pk.to_hex_string())
+        .arg(format!("--{}", modify_password::STDIN_INPUTS_FLAG))
+        .arg(format!("--{}", VALIDATOR_DIR_FLAG))
+        .arg(validator_dir.path().as_os_str())
+        .stderr(Stdio::piped())
+        .stdin(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stderr = child.stderr.as_mut().map(BufReader::new).unwrap().lines();
+    let stdin = child.stdin.as_mut().unwrap();
+
+    // The stored password is used, so only the new password is prompted for.
+    for prompt in &[
+        modify_password::NEW_PASSWORD_PROMPT,
+        modify_password::CONFIRM_PASSWORD_PROMPT,
+    ] {
+        loop {
+            if stderr.next().unwrap().unwrap() == *prompt {
+                break;
+            }
+        }
+        stdin
+            .write(format!("{}\n", NEW_PASSWORD).as_bytes())
+            .unwrap();
+    }
+
+    assert!(child.wait().unwrap().success());
+
+    let new_keystore = Keystore::from_json_file(&voting_keystore_path).unwrap();
+    assert_eq!(
+        new_keystore.uuid(),
+        keystore.uuid(),
+        "uuid should not change"
+    );
+    assert!(new_keystore
+        .decrypt_keypair(OLD_PASSWORD.as_bytes())
+        .is_err());
+    assert_eq!(
+        new_keystore
+            .decrypt_keypair(NEW_PASSWORD.as_bytes())
+            .unwrap()
+            .pk,
+        keypair.pk
+    );
+
+    let defs = ValidatorDefinitions::open(validator_dir.path()).unwrap();
+    match &defs.as_slice()[0].signing_definition {
+        SigningDefinition::LocalKeystore {
+            voting_keystore_password,
+            ..
+        } => assert!(
+            *voting_keystore_password == Some(ZeroizeString::from(NEW_PASSWORD.to_string())),
+            "stored password should be replaced"
+        ),
+        _ => panic!("expected a local keystore"),
+    }
+}
+
+#[test]
+fn validator_threshold_split_and_import() {
+    const PASSWORD: &str = "cats";
+
+    let src_dir = tempdir().unwrap();
+    let shares_dir = tempdir().unwrap();
+    let validator_dir = tempdir().unwrap();
+    let src_keystore = src_dir.path().join("voting-keystore.json");
+
+    let keypair = Keypair::random();
+    let keystore = KeystoreBuilder::new(&keypair, PASSWORD.as_bytes(), "".into())
+        .unwrap()
+        .build()
+        .unwrap();
+    File::create(&src_keystore)
+        .map(|mut file| keystore.to_json_writer(&mut file).unwrap())
+        .unwrap();
+
+    let mut child = validator_cmd()
+        .arg(threshold::CMD)
+        .arg(format!("--{}", threshold::STDIN_INPUTS_FLAG))
+        .arg(threshold::split::CMD)
+        .arg(format!("--{}", threshold::split::KEYSTORE_FLAG))
+        .arg(src_keystore.as_os_str())
+        .arg(format!("--{}", threshold::split::THRESHOLD_FLAG))
+        .arg("2")
+        .arg(format!("--{}", threshold::split::SHARES_FLAG))
+        .arg("3")
+        .arg(format!("--{}", threshold::split::OUTPUT_DIR_FLAG))
+        .arg(shares_dir.path().as_os_str())
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    writeln!(child.stdin.as_mut().unwrap(), "{}", PASSWORD).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "split should succeed: {}",
+        from_utf8(&output.stderr).unwrap()
+    );
+    assert_eq!(dir_child_count(shares_dir.path()), 3);
+
+    // Any two shares recover the original key.
+    let key_shares = [1, 3]
+        .iter()
+        .map(|index| {
+            let share_dir = shares_dir.path().join(format!("share-{}", index));
+            let password = fs::read(share_dir.join(SHARE_PASSWORD_FILE)).unwrap();
+            KeyShare {
+                index: *index,
+                keypair: Keystore::from_json_file(share_dir.join(SHARE_KEYSTORE_FILE))
+                    .unwrap()
+                    .decrypt_keypair(&password)
+                    .unwrap(),
+            }
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        recover_secret_key(&key_shares).unwrap().public_key(),
+        keypair.pk
+    );
+
+    // The stored password is used, so nothing is read from stdin.
+    output_result(
+        validator_cmd()
+            .arg(threshold::CMD)
+            .arg(format!("--{}", VALIDATOR_DIR_FLAG))
+            .arg(validator_dir.path().as_os_str())
+            .arg(threshold::import::CMD)
+            .arg(format!("--{}", threshold::import::SHARE_DIR_FLAG))
+            .arg(shares_dir.path().join("share-2").as_os_str())
+            .stdin(Stdio::null()),
+    )
+    .unwrap();
+
+    let defs = ValidatorDefinitions::open(validator_dir.path()).unwrap();
+    let def = &defs.as_slice()[0];
+    assert_eq!(defs.as_slice().len(), 1);
+    assert!(!def.enabled, "share should be disabled");
+    assert_eq!(def.voting_public_key, keypair.pk);
+    match &def.signing_definition {
+        SigningDefinition::ThresholdShare {
+            voting_keystore_path,
+            share_index,
+            threshold,
+            co_signers,
+            ..
+        } => {
+            assert!(voting_keystore_path.exists());
+            assert_eq!(*share_index, 2);
+            assert_eq!(*threshold, 2);
+            assert_eq!(
+                co_signers
+                    .iter()
+                    .map(|co_signer| co_signer.share_index)
+                    .collect::<Vec<_>>(),
+                vec![1, 3]
+            );
+        }
+        _ => panic!("expected a threshold share"),
+    }
+
+    // The share cannot be enabled, since the validator client cannot sign with it.
+    output_result(
+        validator_cmd()
+            .arg(ENABLE_CMD)
+            .arg(keypair.pk.to_hex_string())
+            .arg(format!("--{}", VALIDATOR_DIR_FLAG))
+            .arg(validator_dir.path().as_os_str()),
+    )
+    .unwrap_err();
+    output_result(
+        validator_cmd()
+            .arg(ENABLE_CMD)
+            .arg(format!("--{}", enable::ALL_FLAG))
+            .arg(format!("--{}", VALIDATOR_DIR_FLAG))
+            .arg(validator_dir.path().as_os_str()),
+    )
+    .unwrap();
+    assert_eq!(enabled_flags(validator_dir.path()), vec![false]);
+}
+
+#[test]
+fn validator_delete() {
+    let validator_dir = tempdir().unwrap();
+
+    let keypair = Keypair::random();
+    let keystore_dir = validator_dir.path().join(keypair.pk.to_hex_string());
+    fs::create_dir(&keystore_dir).unwrap();
+
+    let mut defs = ValidatorDefinitions::default();
+    defs.push(random_validator_definition(&keystore_dir));
+    defs.as_mut_slice()[0].voting_public_key = keypair.pk.clone();
+    defs.push(ValidatorDefinition::new_web3signer(
+        Keypair::random().pk,
+        "http://localhost:9000".to_string(),
+    ));
+    defs.save(validator_dir.path()).unwrap();
+    File::create(keystore_dir.join("voting-keystore.json")).unwrap();
+
+    let db =
+        SlashingDatabase::create(&validator_dir.path().join(SLASHING_PROTECTION_FILENAME)).unwrap();
+    db.set_genesis_validators_root(Hash256::repeat_byte(1))
+        .unwrap();
+    db.register_validator(&keypair.pk).unwrap();
+    db.check_and_insert_block_signing_root(&keypair.pk, Slot::new(1), Hash256::repeat_byte(2))
+        .unwrap();
+    drop(db);
+
+    let run_delete = |pubkey: &str, confirmation: Option<&str>| {
+        let mut cmd = validator_cmd();
+        cmd.arg(DELETE_CMD)
+            .arg(pubkey)
+            .arg(format!("--{}", VALIDATOR_DIR_FLAG))
+            .arg(validator_dir.path().as_os_str())
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped());
+        if confirmation.is_none() {
+            cmd.arg(format!("--{}", YES_I_KNOW_FLAG));
+        }
+        let mut child = cmd.spawn().unwrap();
+        if let Some(confirmation) = confirmation {
+            writeln!(child.stdin.as_mut().unwrap(), "{}", confirmation).unwrap();
+        }
+        child.wait_with_output().unwrap().status.success()
+    };
+
+    // Retyping a different public key aborts, and changes nothing.
+    let other_pubkey = defs.as_slice()[1].voting_public_key.to_hex_string();
+    assert!(!run_delete(
+        &keypair.pk.to_hex_string()[..40],
+        Some(&other_pubkey)
+    ));
+    assert_eq!(
+        ValidatorDefinitions::open(validator_dir.path())
+            .unwrap()
+            .as_slice()
+            .len(),
+        2
+    );
+    assert!(keystore_dir.exists());
+
+    assert!(run_delete(
+        &keypair.pk.to_hex_string()[..40],
+        Some(&keypair.pk.to_hex_string())
+    ));
+    let remaining = ValidatorDefinitions::open(validator_dir.path()).unwrap();
+    assert_eq!(remaining.as_slice().len(), 1);
+    assert_ne!(remaining.as_slice()[0].voting_public_key, keypair.pk);
+    assert!(
+        !keystore_dir.exists(),
+        "validator directory should be removed"
+    );
+
+    let archive_dir = validator_dir.path().join(delete::ARCHIVE_DIR);
+    assert_eq!(dir_child_count(&archive_dir), 1);
+    let archive = fs::read_dir(&archive_dir)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let interchange = Interchange::from_json_reader(File::open(archive).unwrap()).unwrap();
+    assert_eq!(interchange.data.len(), 1);
+    assert_eq!(interchange.data[0].pubkey, keypair.pk);
+    assert_eq!(interchange.data[0].signed_blocks.len(), 1);
+
+    // With `--yes-i-know` nothing is read from stdin.
+    assert!(run_delete(&other_pubkey, None));
+    assert!(ValidatorDefinitions::open(validator_dir.path())
+        .unwrap()
+        .as_slice()
+        .is_empty());
+}
@@ -387,3 +387,50 @@ fn invalid_prev_surrounds_new_multiple() {
     }
     .run()
 }
+
+#[test]
+fn batch_records_safe_attestations_only() {
+    let dir = tempfile::tempdir().unwrap();
+    let slashing_db =
+        SlashingDatabase::create(&dir.path().join("slashing_protection.sqlite")).unwrap();
+    let registered = [pubkey(0), pubkey(1)];
+    slashing_db.register_validators(registered.iter()).unwrap();
+    let unregistered = pubkey(2);
+
+    let first = attestation_data_builder(0, 1);
+    let double_vote = AttestationData {
+        beacon_block_root: Hash256::from_low_u64_be(1),
+        ..first.clone()
+    };
+    let next = attestation_data_builder(1, 2);
+
+    let results = slashing_db
+        .check_and_insert_attestation_batch(vec![
+            (&registered[0], &first, DEFAULT_DOMAIN),
+            (&registered[0], &double_vote, DEFAULT_DOMAIN),
+            (&unregistered, &first, DEFAULT_DOMAIN),
+            (&registered[1], &first, DEFAULT_DOMAIN),
+            (&registered[0], &next, DEFAULT_DOMAIN),
+            (&registered[0], &next, DEFAULT_DOMAIN),
+        ])
+        .unwrap();
+
+    assert_eq!(
+        results,
+        vec![
+            Ok(Safe::Valid),
+            Err(NotSafe::InvalidAttestation(InvalidAttestation::DoubleVote(
+                signed_att(&first)
+            ))),
+            Err(NotSafe::UnregisteredValidator(unregistered.clone())),
+            Ok(Safe::Valid),
+            Ok(Safe::Valid),
+            Ok(Safe::SameData),
+        ]
+    );
+
+    let summaries = slashing_db.validator_summaries(None).unwrap();
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[0].num_signed_attestations, 2);
+    assert_eq!(summaries[1].num_signed_attestations, 1);
+}
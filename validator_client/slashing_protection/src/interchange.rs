@@ -0,0 +1,272 @@
+//! Types for the slashing protection interchange format, allowing slashing protection data to be
+//! moved between clients.
+//!
+//! Implements version 5 of the format, as specified by EIP-3076. Files produced by the draft
+//! version 4 format (in either its "minimal" or "complete" flavour) are converted to version 5
+//! when they are read.
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashSet;
+use types::{Epoch, Hash256, PublicKey, Slot};
+
+/// The last version of the draft format, which is still accepted on import.
+pub const LEGACY_INTERCHANGE_FORMAT_VERSION: u64 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterchangeMetadata {
+    #[serde(with = "serde_utils::quoted_u64::require_quotes")]
+    pub interchange_format_version: u64,
+    pub genesis_validators_root: Hash256,
+}
+
+/// The signing history of a single validator.
+///
+/// Either list may be empty. Records without a signing root are treated as lower bounds on import:
+/// no message at or below them will be signed, even if its signing root is unknown.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterchangeData {
+    pub pubkey: PublicKey,
+    pub signed_blocks: Vec<SignedBlock>,
+    pub signed_attestations: Vec<SignedAttestation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignedBlock {
+    #[serde(with = "serde_utils::quoted_u64::require_quotes")]
+    pub slot: Slot,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_root: Option<Hash256>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignedAttestation {
+    #[serde(with = "serde_utils::quoted_u64::require_quotes")]
+    pub source_epoch: Epoch,
+    #[serde(with = "serde_utils::quoted_u64::require_quotes")]
+    pub target_epoch: Epoch,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_root: Option<Hash256>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Interchange {
+    pub metadata: InterchangeMetadata,
+    pub data: Vec<InterchangeData>,
+}
+
+impl Interchange {
+    /// Parse an interchange file, converting from the legacy format if necessary.
+    pub fn from_json_str(json: &str) -> Result<Self, serde_json::Error> {
+        Self::from_json_value(serde_json::from_str(json)?)
+    }
+
+    /// Parse an interchange file, converting from the legacy format if necessary.
+    pub fn from_json_reader(reader: impl std::io::Read) -> Result<Self, serde_json::Error> {
+        Self::from_json_value(serde_json::from_reader(reader)?)
+    }
+
+    fn from_json_value(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        match serde_json::from_value::<Self>(value.clone()) {
+            Ok(interchange) => Ok(interchange),
+            Err(e) => match serde_json::from_value::<LegacyInterchange>(value) {
+                Ok(legacy) => legacy.into_interchange(),
+                // Report the error for the current format, which is more likely to be relevant.
+                Err(_) => Err(e),
+            },
+        }
+    }
+
+    pub fn write_to(&self, writer: impl std::io::Write) -> Result<(), serde_json::Error> {
+        serde_json::to_writer(writer, self)
+    }
+
+    /// Do these two `Interchange`s contain the same data (ignoring ordering)?
+    pub fn equiv(&self, other: &Self) -> bool {
+        // Ignore the ordering of records for each validator, as well as the ordering of
+        // validators themselves.
+        let normalize = |data: &[InterchangeData]| {
+            data.iter()
+                .cloned()
+                .map(|mut record| {
+                    record
+                        .signed_blocks
+                        .sort_by_key(|block| (block.slot, block.signing_root));
+                    record
+                        .signed_attestations
+                        .sort_by_key(|att| (att.source_epoch, att.target_epoch, att.signing_root));
+                    record
+                })
+                .collect::<HashSet<_>>()
+        };
+        self.metadata == other.metadata && normalize(&self.data) == normalize(&other.data)
+    }
+
+    /// The number of validators with records in this interchange.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The draft (version 4) interchange format, retained only so that old files can be imported.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LegacyInterchange {
+    metadata: LegacyInterchangeMetadata,
+    data: LegacyInterchangeData,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum LegacyInterchangeFormat {
+    Minimal,
+    Complete,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LegacyInterchangeMetadata {
+    interchange_format: LegacyInterchangeFormat,
+    #[serde(with = "serde_utils::quoted_u64")]
+    interchange_format_version: u64,
+    genesis_validators_root: Hash256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LegacyMinimalInterchangeData {
+    pubkey: PublicKey,
+    #[serde(default, with = "serde_utils::quoted_u64_option")]
+    last_signed_block_slot: Option<Slot>,
+    #[serde(default, with = "serde_utils::quoted_u64_option")]
+    last_signed_attestation_source_epoch: Option<Epoch>,
+    #[serde(default, with = "serde_utils::quoted_u64_option")]
+    last_signed_attestation_target_epoch: Option<Epoch>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LegacyCompleteInterchangeData {
+    pubkey: PublicKey,
+    signed_blocks: Vec<LegacySignedBlock>,
+    signed_attestations: Vec<LegacySignedAttestation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LegacySignedBlock {
+    #[serde(with = "serde_utils::quoted_u64")]
+    slot: Slot,
+    signing_root: Option<Hash256>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LegacySignedAttestation {
+    #[serde(with = "serde_utils::quoted_u64")]
+    source_epoch: Epoch,
+    #[serde(with = "serde_utils::quoted_u64")]
+    target_epoch: Epoch,
+    signing_root: Option<Hash256>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+enum LegacyInterchangeData {
+    Minimal(Vec<LegacyMinimalInterchangeData>),
+    Complete(Vec<LegacyCompleteInterchangeData>),
+}
+
+impl LegacyInterchange {
+    /// Convert to the current format.
+    ///
+    /// Each minimal record becomes a single block and attestation without a signing root, which
+    /// will act as a lower bound when imported. A validator that has never proposed or attested
+    /// may omit those fields (or set them to `null`), in which case no record is created.
+    fn into_interchange(self) -> Result<Interchange, serde_json::Error> {
+        let version = self.metadata.interchange_format_version;
+        if version != LEGACY_INTERCHANGE_FORMAT_VERSION {
+            return Err(serde::de::Error::custom(format!(
+                "unsupported legacy interchange format version: {}",
+                version
+            )));
+        }
+
+        let data = match (self.metadata.interchange_format, self.data) {
+            (LegacyInterchangeFormat::Minimal, LegacyInterchangeData::Minimal(records)) => records
+                .into_iter()
+                .map(|record| InterchangeData {
+                    pubkey: record.pubkey,
+                    signed_blocks: record
+                        .last_signed_block_slot
+                        .map(|slot| SignedBlock {
+                            slot,
+                            signing_root: None,
+                        })
+                        .into_iter()
+                        .collect(),
+                    signed_attestations: match (
+                        record.last_signed_attestation_source_epoch,
+                        record.last_signed_attestation_target_epoch,
+                    ) {
+                        (Some(source_epoch), Some(target_epoch)) => vec![SignedAttestation {
+                            source_epoch,
+                            target_epoch,
+                            signing_root: None,
+                        }],
+                        _ => vec![],
+                    },
+                })
+                .collect(),
+            (LegacyInterchangeFormat::Complete, LegacyInterchangeData::Complete(records)) => {
+                records
+                    .into_iter()
+                    .map(|record| InterchangeData {
+                        pubkey: record.pubkey,
+                        signed_blocks: record
+                            .signed_blocks
+                            .into_iter()
+                            .map(|block| SignedBlock {
+                                slot: block.slot,
+                                signing_root: block.signing_root,
+                            })
+                            .collect(),
+                        signed_attestations: record
+                            .signed_attestations
+                            .into_iter()
+                            .map(|att| SignedAttestation {
+                                source_epoch: att.source_epoch,
+                                target_epoch: att.target_epoch,
+                                signing_root: att.signing_root,
+                            })
+                            .collect(),
+                    })
+                    .collect()
+            }
+            // An empty data list parses as either variant.
+            (_, LegacyInterchangeData::Minimal(records)) if records.is_empty() => vec![],
+            (_, LegacyInterchangeData::Complete(records)) if records.is_empty() => vec![],
+            (format, _) => {
+                return Err(serde::de::Error::custom(format!(
+                    "legacy interchange data does not match format: {:?}",
+                    format
+                )))
+            }
+        };
+
+        Ok(Interchange {
+            metadata: InterchangeMetadata {
+                interchange_format_version: crate::SUPPORTED_INTERCHANGE_FORMAT_VERSION,
+                genesis_validators_root: self.metadata.genesis_validators_root,
+            },
+            data,
+        })
+    }
+}
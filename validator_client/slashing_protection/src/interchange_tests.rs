@@ -0,0 +1,629 @@
+#![cfg(test)]
+
+use crate::interchange::{
+    Interchange, InterchangeData, InterchangeMetadata, SignedAttestation as InterchangeAttestation,
+    SignedBlock as InterchangeBlock,
+};
+use crate::test_utils::pubkey;
+use crate::*;
+use tempfile::tempdir;
+use types::{Epoch, Hash256, Slot};
+
+fn genesis_validators_root() -> Hash256 {
+    Hash256::from_low_u64_be(42)
+}
+
+fn metadata() -> InterchangeMetadata {
+    InterchangeMetadata {
+        interchange_format_version: SUPPORTED_INTERCHANGE_FORMAT_VERSION,
+        genesis_validators_root: genesis_validators_root(),
+    }
+}
+
+fn new_db() -> (tempfile::TempDir, SlashingDatabase) {
+    let dir = tempdir().unwrap();
+    let slashing_db_file = dir.path().join("slashing_protection.sqlite");
+    let slashing_db = SlashingDatabase::create(&slashing_db_file).unwrap();
+    (dir, slashing_db)
+}
+
+/// Populate a database with a few validators and a mix of known and unknown signing roots.
+fn populated_db() -> (tempfile::TempDir, SlashingDatabase) {
+    let (dir, slashing_db) = new_db();
+    let num_validators = 3;
+
+    for i in 0..num_validators {
+        let pk = pubkey(i);
+        slashing_db.register_validator(&pk).unwrap();
+
+        for slot in 1..=4 {
+            let signing_root = if slot % 2 == 0 {
+                Hash256::zero()
+            } else {
+                Hash256::from_low_u64_be(slot * 100 + i as u64)
+            };
+            slashing_db
+                .check_and_insert_block_signing_root(&pk, Slot::new(slot), signing_root)
+                .unwrap();
+        }
+
+        for target in 1..=4 {
+            let signing_root = Hash256::from_low_u64_be(target * 1000 + i as u64);
+            slashing_db
+                .check_and_insert_attestation_signing_root(
+                    &pk,
+                    Epoch::new(target - 1),
+                    Epoch::new(target),
+                    signing_root,
+                )
+                .unwrap();
+        }
+    }
+
+    (dir, slashing_db)
+}
+
+#[test]
+fn export_empty() {
+    let (_dir, slashing_db) = new_db();
+
+    let exported = slashing_db
+        .export_interchange_info(genesis_validators_root(), None)
+        .unwrap();
+
+    assert_eq!(exported.metadata, metadata());
+    assert!(exported.is_empty());
+}
+
+#[test]
+fn export_import_round_trip() {
+    let (_dir1, slashing_db) = populated_db();
+
+    let exported = slashing_db
+        .export_interchange_info(genesis_validators_root(), None)
+        .unwrap();
+    assert_eq!(exported.len(), 3);
+
+    let (_dir2, fresh_db) = new_db();
+    let report = fresh_db
+        .import_interchange_info(&exported, genesis_validators_root())
+        .unwrap();
+    assert!(report.is_success());
+    assert_eq!(report.imported.len(), 3);
+    for imported in &report.imported {
+        assert_eq!(imported.num_blocks, 4);
+        assert_eq!(imported.num_attestations, 4);
+        assert_eq!(imported.num_skipped, 0);
+    }
+
+    let re_exported = fresh_db
+        .export_interchange_info(genesis_validators_root(), None)
+        .unwrap();
+    assert!(exported.equiv(&re_exported));
+
+    // Importing the same data again skips every record.
+    let report = fresh_db
+        .import_interchange_info(&exported, genesis_validators_root())
+        .unwrap();
+    assert!(report.is_success());
+    for imported in &report.imported {
+        assert_eq!(imported.num_blocks + imported.num_attestations, 0);
+        assert_eq!(imported.num_skipped, 8);
+    }
+}
+
+#[test]
+fn export_omits_unknown_signing_roots() {
+    let (_dir, slashing_db) = populated_db();
+
+    let exported = slashing_db
+        .export_interchange_info(genesis_validators_root(), None)
+        .unwrap();
+
+    for record in exported.data {
+        for block in record.signed_blocks {
+            assert_eq!(block.signing_root.is_none(), block.slot % 2 == 0);
+        }
+    }
+}
+
+#[test]
+fn export_selected_pubkeys() {
+    let (_dir, slashing_db) = populated_db();
+    let selected = vec![pubkey(2), pubkey(0)];
+
+    let exported = slashing_db
+        .export_interchange_info(genesis_validators_root(), Some(&selected))
+        .unwrap();
+
+    let pubkeys = exported
+        .data
+        .into_iter()
+        .map(|r| r.pubkey)
+        .collect::<Vec<_>>();
+    assert_eq!(pubkeys, selected);
+}
+
+#[test]
+fn export_unregistered_pubkey() {
+    let (_dir, slashing_db) = populated_db();
+    let selected = vec![pubkey(0), pubkey(10)];
+
+    match slashing_db.export_interchange_info(genesis_validators_root(), Some(&selected)) {
+        Err(InterchangeError::NotSafe(NotSafe::UnregisteredValidator(pk))) => {
+            assert_eq!(pk, pubkey(10))
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn import_wrong_genesis_validators_root() {
+    let (_dir, slashing_db) = populated_db();
+    let exported = slashing_db
+        .export_interchange_info(genesis_validators_root(), None)
+        .unwrap();
+
+    let (_dir2, fresh_db) = new_db();
+    assert!(matches!(
+        fresh_db.import_interchange_info(&exported, Hash256::from_low_u64_be(1)),
+        Err(InterchangeError::GenesisValidatorsMismatch { .. })
+    ));
+}
+
+#[test]
+fn import_unsupported_version() {
+    let mut interchange = Interchange {
+        metadata: metadata(),
+        data: vec![],
+    };
+    interchange.metadata.interchange_format_version = SUPPORTED_INTERCHANGE_FORMAT_VERSION + 1;
+
+    let (_dir, slashing_db) = new_db();
+    assert!(matches!(
+        slashing_db.import_interchange_info(&interchange, genesis_validators_root()),
+        Err(InterchangeError::UnsupportedVersion(_))
+    ));
+}
+
+#[test]
+fn import_legacy_minimal() {
+    let pk = pubkey(0);
+    let json = format!(
+        r#"{{
+            "metadata": {{
+                "interchange_format": "minimal",
+                "interchange_format_version": 4,
+                "genesis_validators_root": "{:?}"
+            }},
+            "data": [
+                {{
+                    "pubkey": "{}",
+                    "last_signed_block_slot": 10,
+                    "last_signed_attestation_source_epoch": 3,
+                    "last_signed_attestation_target_epoch": 4
+                }}
+            ]
+        }}"#,
+        genesis_validators_root(),
+        pk.to_hex_string()
+    );
+    let interchange = Interchange::from_json_str(&json).unwrap();
+    assert_eq!(interchange.metadata, metadata());
+
+    let (_dir, slashing_db) = new_db();
+    slashing_db
+        .import_interchange_info(&interchange, genesis_validators_root())
+        .unwrap();
+
+    assert!(matches!(
+        slashing_db.check_and_insert_block_signing_root(
+            &pk,
+            Slot::new(10),
+            Hash256::from_low_u64_be(1)
+        ),
+        Err(NotSafe::InvalidBlock(InvalidBlock::DoubleBlockProposal(_)))
+    ));
+    assert!(matches!(
+        slashing_db.check_and_insert_attestation_signing_root(
+            &pk,
+            Epoch::new(2),
+            Epoch::new(5),
+            Hash256::from_low_u64_be(1)
+        ),
+        Err(NotSafe::InvalidAttestation(
+            InvalidAttestation::SourceLessThanLowerBound { .. }
+        ))
+    ));
+}
+
+#[test]
+fn import_legacy_minimal_quoted_and_missing() {
+    let pk = pubkey(0);
+    let json = format!(
+        r#"{{
+            "metadata": {{
+                "interchange_format": "minimal",
+                "interchange_format_version": "4",
+                "genesis_validators_root": "{:?}"
+            }},
+            "data": [
+                {{
+                    "pubkey": "{}",
+                    "last_signed_block_slot": null,
+                    "last_signed_attestation_source_epoch": "3",
+                    "last_signed_attestation_target_epoch": "4"
+                }}
+            ]
+        }}"#,
+        genesis_validators_root(),
+        pk.to_hex_string()
+    );
+    let interchange = Interchange::from_json_str(&json).unwrap();
+
+    assert_eq!(interchange.data.len(), 1);
+    assert!(interchange.data[0].signed_blocks.is_empty());
+    assert_eq!(
+        interchange.data[0].signed_attestations,
+        vec![InterchangeAttestation {
+            source_epoch: Epoch::new(3),
+            target_epoch: Epoch::new(4),
+            signing_root: None,
+        }]
+    );
+}
+
+#[test]
+fn import_unknown_roots_set_lower_bound() {
+    let pk = pubkey(0);
+    let interchange = Interchange {
+        metadata: metadata(),
+        data: vec![InterchangeData {
+            pubkey: pk.clone(),
+            signed_blocks: vec![InterchangeBlock {
+                slot: Slot::new(10),
+                signing_root: None,
+            }],
+            signed_attestations: vec![InterchangeAttestation {
+                source_epoch: Epoch::new(5),
+                target_epoch: Epoch::new(6),
+                signing_root: None,
+            }],
+        }],
+    };
+
+    let (_dir, slashing_db) = new_db();
+    slashing_db
+        .import_interchange_info(&interchange, genesis_validators_root())
+        .unwrap();
+
+    assert_eq!(
+        slashing_db.check_and_insert_block_signing_root(
+            &pk,
+            Slot::new(9),
+            Hash256::from_low_u64_be(1)
+        ),
+        Err(NotSafe::InvalidBlock(
+            InvalidBlock::SlotViolatesLowerBound {
+                block_slot: Slot::new(9),
+                bound_slot: Slot::new(10),
+            }
+        ))
+    );
+    assert_eq!(
+        slashing_db.check_and_insert_attestation_signing_root(
+            &pk,
+            Epoch::new(5),
+            Epoch::new(5),
+            Hash256::from_low_u64_be(1)
+        ),
+        Err(NotSafe::InvalidAttestation(
+            InvalidAttestation::TargetLessThanOrEqLowerBound {
+                target_epoch: Epoch::new(5),
+                bound_epoch: Epoch::new(6),
+            }
+        ))
+    );
+    assert_eq!(
+        slashing_db.check_and_insert_block_signing_root(
+            &pk,
+            Slot::new(11),
+            Hash256::from_low_u64_be(1)
+        ),
+        Ok(Safe::Valid)
+    );
+    assert_eq!(
+        slashing_db.check_and_insert_attestation_signing_root(
+            &pk,
+            Epoch::new(6),
+            Epoch::new(7),
+            Hash256::from_low_u64_be(1)
+        ),
+        Ok(Safe::Valid)
+    );
+
+    // Re-importing the same data is harmless.
+    slashing_db
+        .import_interchange_info(&interchange, genesis_validators_root())
+        .unwrap();
+}
+
+#[test]
+fn import_empty_records() {
+    let interchange = Interchange {
+        metadata: metadata(),
+        data: vec![InterchangeData {
+            pubkey: pubkey(0),
+            signed_blocks: vec![],
+            signed_attestations: vec![],
+        }],
+    };
+
+    let (_dir, slashing_db) = new_db();
+    slashing_db
+        .import_interchange_info(&interchange, genesis_validators_root())
+        .unwrap();
+
+    let exported = slashing_db
+        .export_interchange_info(genesis_validators_root(), None)
+        .unwrap();
+    assert!(exported.equiv(&interchange));
+}
+
+#[test]
+fn import_slashable_validator_is_rejected() {
+    let slashable = InterchangeData {
+        pubkey: pubkey(0),
+        signed_blocks: vec![
+            InterchangeBlock {
+                slot: Slot::new(1),
+                signing_root: Some(Hash256::from_low_u64_be(1)),
+            },
+            InterchangeBlock {
+                slot: Slot::new(1),
+                signing_root: Some(Hash256::from_low_u64_be(2)),
+            },
+        ],
+        signed_attestations: vec![InterchangeAttestation {
+            source_epoch: Epoch::new(0),
+            target_epoch: Epoch::new(1),
+            signing_root: None,
+        }],
+    };
+    let valid = InterchangeData {
+        pubkey: pubkey(1),
+        signed_blocks: vec![InterchangeBlock {
+            slot: Slot::new(1),
+            signing_root: Some(Hash256::from_low_u64_be(3)),
+        }],
+        signed_attestations: vec![],
+    };
+    let interchange = Interchange {
+        metadata: metadata(),
+        data: vec![slashable, valid.clone()],
+    };
+
+    let (_dir, slashing_db) = new_db();
+    let report = slashing_db
+        .import_interchange_info(&interchange, genesis_validators_root())
+        .unwrap();
+
+    assert!(!report.is_success());
+    assert_eq!(report.rejected.len(), 1);
+    assert_eq!(report.rejected[0].pubkey, pubkey(0));
+    assert!(matches!(
+        report.rejected[0].reason,
+        NotSafe::InvalidBlock(_)
+    ));
+    assert_eq!(report.imported.len(), 1);
+    assert_eq!(report.imported[0].pubkey, pubkey(1));
+
+    // Nothing from the rejected validator should have been persisted, not even its
+    // registration.
+    let exported = slashing_db
+        .export_interchange_info(genesis_validators_root(), None)
+        .unwrap();
+    assert!(exported.equiv(&Interchange {
+        metadata: metadata(),
+        data: vec![valid],
+    }));
+}
+
+#[test]
+fn import_records_genesis_validators_root() {
+    let (_dir, slashing_db) = new_db();
+    assert_eq!(slashing_db.genesis_validators_root(), Ok(None));
+
+    let interchange = Interchange {
+        metadata: metadata(),
+        data: vec![],
+    };
+    slashing_db
+        .import_interchange_info(&interchange, genesis_validators_root())
+        .unwrap();
+    assert_eq!(
+        slashing_db.genesis_validators_root(),
+        Ok(Some(genesis_validators_root()))
+    );
+
+    // Data from another network is refused.
+    let other_root = Hash256::from_low_u64_be(1);
+    let other_interchange = Interchange {
+        metadata: InterchangeMetadata {
+            genesis_validators_root: other_root,
+            ..metadata()
+        },
+        data: vec![],
+    };
+    assert!(matches!(
+        slashing_db.import_interchange_info(&other_interchange, other_root),
+        Err(InterchangeError::NotSafe(
+            NotSafe::GenesisValidatorsRootMismatch { .. }
+        ))
+    ));
+    assert_eq!(
+        slashing_db.check_genesis_validators_root(other_root),
+        Err(NotSafe::GenesisValidatorsRootMismatch {
+            database: genesis_validators_root(),
+            network: other_root,
+        })
+    );
+
+    // Unless the network is explicitly changed.
+    slashing_db.set_genesis_validators_root(other_root).unwrap();
+    slashing_db
+        .import_interchange_info(&other_interchange, other_root)
+        .unwrap();
+    slashing_db
+        .check_genesis_validators_root(other_root)
+        .unwrap();
+}
+
+#[test]
+fn json_round_trip() {
+    let (_dir, slashing_db) = populated_db();
+    let exported = slashing_db
+        .export_interchange_info(genesis_validators_root(), None)
+        .unwrap();
+
+    let mut json = vec![];
+    exported.write_to(&mut json).unwrap();
+    let decoded = Interchange::from_json_reader(json.as_slice()).unwrap();
+
+    assert_eq!(decoded, exported);
+}
+
+#[test]
+fn json_requires_quoted_integers() {
+    let json = format!(
+        r#"{{
+            "metadata": {{
+                "interchange_format_version": 5,
+                "genesis_validators_root": "{:?}"
+            }},
+            "data": []
+        }}"#,
+        genesis_validators_root()
+    );
+    assert!(Interchange::from_json_str(&json).is_err());
+    assert!(Interchange::from_json_str(&json.replace("5", "\"5\"")).is_ok());
+}
+
+#[test]
+fn prune_raises_lower_bound() {
+    let (_dir, slashing_db) = populated_db();
+    let pk = pubkey(0);
+
+    slashing_db
+        .prune(Some(&[pk.clone()]), Slot::new(3), Epoch::new(3))
+        .unwrap();
+
+    let summaries = slashing_db.validator_summaries(None).unwrap();
+    assert_eq!(summaries.len(), 3);
+    assert_eq!(summaries[0].num_signed_blocks, 2);
+    assert_eq!(summaries[0].num_signed_attestations, 2);
+    assert_eq!(
+        summaries[0].lower_bound,
+        LowerBound {
+            block_proposal_slot: Some(Slot::new(2)),
+            attestation_source_epoch: Some(Epoch::new(1)),
+            attestation_target_epoch: Some(Epoch::new(2)),
+        }
+    );
+    // Unselected validators are untouched.
+    assert_eq!(summaries[1].num_signed_blocks, 4);
+    assert_eq!(summaries[1].lower_bound, LowerBound::default());
+
+    // Messages conflicting with the pruned records are still refused.
+    assert!(matches!(
+        slashing_db.check_and_insert_block_signing_root(
+            &pk,
+            Slot::new(1),
+            Hash256::from_low_u64_be(1)
+        ),
+        Err(NotSafe::InvalidBlock(
+            InvalidBlock::SlotViolatesLowerBound { .. }
+        ))
+    ));
+    assert!(matches!(
+        slashing_db.check_and_insert_attestation_signing_root(
+            &pk,
+            Epoch::new(0),
+            Epoch::new(2),
+            Hash256::from_low_u64_be(1)
+        ),
+        Err(NotSafe::InvalidAttestation(
+            InvalidAttestation::SourceLessThanLowerBound { .. }
+        ))
+    ));
+}
+
+#[test]
+fn export_includes_lower_bound() {
+    let (_dir, slashing_db) = populated_db();
+    let pk = pubkey(0);
+
+    // Prune every record, leaving only the lower bound.
+    slashing_db
+        .prune(Some(&[pk.clone()]), Slot::new(5), Epoch::new(5))
+        .unwrap();
+    let lower_bound = slashing_db
+        .validator_summaries(Some(&[pk.clone()]))
+        .unwrap()[0]
+        .lower_bound;
+
+    let exported = slashing_db
+        .export_interchange_info(genesis_validators_root(), Some(&[pk.clone()]))
+        .unwrap();
+    assert_eq!(
+        exported.data,
+        vec![InterchangeData {
+            pubkey: pk.clone(),
+            signed_blocks: vec![InterchangeBlock {
+                slot: Slot::new(4),
+                signing_root: None,
+            }],
+            signed_attestations: vec![InterchangeAttestation {
+                source_epoch: Epoch::new(3),
+                target_epoch: Epoch::new(4),
+                signing_root: None,
+            }],
+        }]
+    );
+
+    let (_dir2, fresh_db) = new_db();
+    fresh_db
+        .import_interchange_info(&exported, genesis_validators_root())
+        .unwrap();
+    assert_eq!(
+        fresh_db.validator_summaries(None).unwrap()[0].lower_bound,
+        lower_bound
+    );
+
+    // Re-exporting doesn't duplicate the lower bound.
+    let re_exported = fresh_db
+        .export_interchange_info(genesis_validators_root(), None)
+        .unwrap();
+    assert!(re_exported.equiv(&exported));
+}
+
+#[test]
+fn validator_summaries() {
+    let (_dir, slashing_db) = populated_db();
+
+    let summaries = slashing_db.validator_summaries(Some(&[pubkey(1)])).unwrap();
+    assert_eq!(
+        summaries,
+        vec![ValidatorSummary {
+            pubkey: pubkey(1),
+            num_signed_blocks: 4,
+            max_block_slot: Some(Slot::new(4)),
+            num_signed_attestations: 4,
+            max_source_epoch: Some(Epoch::new(3)),
+            max_target_epoch: Some(Epoch::new(4)),
+            lower_bound: LowerBound::default(),
+        }]
+    );
+
+    slashing_db.integrity_check().unwrap();
+}
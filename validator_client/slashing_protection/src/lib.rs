@@ -1,18 +1,38 @@
+#[macro_use]
+extern crate lazy_static;
+
 mod attestation_tests;
 mod block_tests;
+pub mod interchange;
+mod interchange_tests;
+mod lower_bound;
+pub mod metrics;
 mod parallel_tests;
+mod remote;
+mod schema;
 mod signed_attestation;
 mod signed_block;
+mod signing_root_tests;
 mod slashing_database;
 mod test_utils;
+mod validate_tests;
 
+use crate::interchange::Interchange;
+pub use crate::lower_bound::LowerBound;
+pub use crate::remote::RemoteSlashingProtection;
+pub use crate::schema::SCHEMA_VERSION;
 pub use crate::signed_attestation::{InvalidAttestation, SignedAttestation};
 pub use crate::signed_block::{InvalidBlock, SignedBlock};
-pub use crate::slashing_database::SlashingDatabase;
+pub use crate::slashing_database::{
+    ImportReport, ImportedValidator, Inconsistency, InterchangeError, RejectedValidator,
+    SlashingDatabase, ValidatorSummary, SLASHING_PROTECTION_FILENAME,
+    SUPPORTED_INTERCHANGE_FORMAT_VERSION,
+};
 use rusqlite::Error as SQLError;
 use std::io::{Error as IOError, ErrorKind};
+use std::path::PathBuf;
 use std::string::ToString;
-use types::{Hash256, PublicKey};
+use types::{AttestationData, BeaconBlockHeader, Hash256, PublicKey};
 
 /// The attestation or block is not safe to sign.
 ///
@@ -25,6 +45,27 @@ pub enum NotSafe {
     IOError(ErrorKind),
     SQLError(String),
     SQLPoolError(String),
+    /// Another process has the database open for writing, as indicated by the lock file.
+    DatabaseLocked(PathBuf),
+    /// The database schema is newer than this software supports, or is invalid.
+    UnsupportedSchemaVersion {
+        version: u64,
+        supported: u64,
+    },
+    /// The database must be opened for writing to upgrade its schema before it can be read.
+    SchemaMigrationRequired {
+        version: u64,
+        current: u64,
+    },
+    /// The database holds slashing protection data for a different network.
+    GenesisValidatorsRootMismatch {
+        database: Hash256,
+        network: Hash256,
+    },
+    /// A remote slashing protection service refused to approve the message, giving its reason.
+    RemoteRefused(String),
+    /// A remote slashing protection service could not be reached, or gave an invalid response.
+    RemoteError(String),
 }
 
 /// The attestation or block is safe to sign, and will not cause the signer to be slashed.
@@ -36,6 +77,54 @@ pub enum Safe {
     Valid,
 }
 
+/// A source of slashing protection, which refuses to approve slashable messages.
+///
+/// Implementations must check and record each message atomically, so that concurrent checks can
+/// never approve two conflicting messages. The `SlashingDatabase` is the default implementation,
+/// whilst the `RemoteSlashingProtection` allows protection to be shared between validator clients.
+pub trait SlashingProtection: Send + Sync {
+    /// Register validators, which is required before any of their messages can be checked.
+    fn register_validators(&self, public_keys: &[PublicKey]) -> Result<(), NotSafe>;
+
+    /// Check a block proposal for slash safety, and if it is safe, record it.
+    fn check_and_insert_block_proposal(
+        &self,
+        validator_pubkey: &PublicKey,
+        block_header: &BeaconBlockHeader,
+        domain: Hash256,
+    ) -> Result<Safe, NotSafe>;
+
+    /// Check an attestation for slash safety, and if it is safe, record it.
+    fn check_and_insert_attestation(
+        &self,
+        validator_pubkey: &PublicKey,
+        attestation: &AttestationData,
+        domain: Hash256,
+    ) -> Result<Safe, NotSafe>;
+
+    /// Update the global metrics registry with information about the protection, if any.
+    fn scrape_for_metrics(&self) {}
+
+    /// Export the records of the `selected_pubkeys` (or of every validator if `None`) in the
+    /// interchange format.
+    fn export_interchange_info(
+        &self,
+        _genesis_validators_root: Hash256,
+        _selected_pubkeys: Option<&[PublicKey]>,
+    ) -> Result<Interchange, InterchangeError> {
+        Err(InterchangeError::Unsupported)
+    }
+
+    /// Import records in the interchange format, registering their validators.
+    fn import_interchange_info(
+        &self,
+        _interchange: &Interchange,
+        _genesis_validators_root: Hash256,
+    ) -> Result<ImportReport, InterchangeError> {
+        Err(InterchangeError::Unsupported)
+    }
+}
+
 /// Safely parse a `Hash256` from the given `column` of an SQLite `row`.
 fn hash256_from_row(column: usize, row: &rusqlite::Row) -> rusqlite::Result<Hash256> {
     use rusqlite::{types::Type, Error};
@@ -0,0 +1,43 @@
+use std::cmp::max;
+use types::{Epoch, Slot};
+
+/// Minimum values that a validator's future messages must exceed.
+///
+/// Lower bounds are established when importing records whose signing roots are unknown, and allow
+/// the database to refuse messages conflicting with history that it doesn't hold in full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LowerBound {
+    /// No block with a slot less than or equal to this may be signed.
+    pub block_proposal_slot: Option<Slot>,
+    /// No attestation with a source epoch less than this may be signed.
+    pub attestation_source_epoch: Option<Epoch>,
+    /// No attestation with a target epoch less than or equal to this may be signed.
+    pub attestation_target_epoch: Option<Epoch>,
+}
+
+impl LowerBound {
+    /// Combine two lower bounds, keeping the maximum of each field.
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            block_proposal_slot: max(self.block_proposal_slot, other.block_proposal_slot),
+            attestation_source_epoch: max(
+                self.attestation_source_epoch,
+                other.attestation_source_epoch,
+            ),
+            attestation_target_epoch: max(
+                self.attestation_target_epoch,
+                other.attestation_target_epoch,
+            ),
+        }
+    }
+
+    /// Parse an SQLite row of `(block_proposal_slot, attestation_source_epoch,
+    /// attestation_target_epoch)`.
+    pub fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            block_proposal_slot: row.get(0)?,
+            attestation_source_epoch: row.get(1)?,
+            attestation_target_epoch: row.get(2)?,
+        })
+    }
+}
@@ -0,0 +1,132 @@
+//! Prometheus metrics for the slashing protection database.
+//!
+//! Check latencies and prevented signings are recorded as messages are checked, whilst the
+//! per-validator gauges are only updated by `scrape_for_metrics`.
+use crate::{NotSafe, SlashingDatabase};
+
+pub use lighthouse_metrics::*;
+
+lazy_static! {
+    pub static ref SLASHING_PROTECTION_REGISTERED_VALIDATORS: Result<IntGauge> =
+        try_create_int_gauge(
+            "slashing_protection_registered_validators",
+            "Number of validators registered in the slashing protection database"
+        );
+    pub static ref SLASHING_PROTECTION_SIGNED_BLOCKS: Result<IntGaugeVec> =
+        try_create_int_gauge_vec(
+            "slashing_protection_signed_blocks",
+            "Number of block proposals recorded for each validator",
+            &["pubkey"]
+        );
+    pub static ref SLASHING_PROTECTION_SIGNED_ATTESTATIONS: Result<IntGaugeVec> =
+        try_create_int_gauge_vec(
+            "slashing_protection_signed_attestations",
+            "Number of attestations recorded for each validator",
+            &["pubkey"]
+        );
+    pub static ref SLASHING_PROTECTION_BLOCK_CHECK_TIMES: Result<Histogram> = try_create_histogram(
+        "slashing_protection_block_check_seconds",
+        "Time taken to check and record a block proposal"
+    );
+    pub static ref SLASHING_PROTECTION_ATTESTATION_CHECK_TIMES: Result<Histogram> =
+        try_create_histogram(
+            "slashing_protection_attestation_check_seconds",
+            "Time taken to check and record an attestation"
+        );
+    pub static ref SLASHING_PROTECTION_PREVENTED_BLOCKS: Result<IntCounter> =
+        try_create_int_counter(
+            "slashing_protection_prevented_blocks_total",
+            "Number of slashable block proposals refused by the slashing protection database"
+        );
+    pub static ref SLASHING_PROTECTION_PREVENTED_ATTESTATIONS: Result<IntCounter> =
+        try_create_int_counter(
+            "slashing_protection_prevented_attestations_total",
+            "Number of slashable attestations refused by the slashing protection database"
+        );
+}
+
+/// Count `not_safe` if it prevented a slashable message from being signed, returning it unchanged.
+pub fn observe_not_safe(not_safe: NotSafe) -> NotSafe {
+    match not_safe {
+        NotSafe::InvalidBlock(_) => inc_counter(&SLASHING_PROTECTION_PREVENTED_BLOCKS),
+        NotSafe::InvalidAttestation(_) => inc_counter(&SLASHING_PROTECTION_PREVENTED_ATTESTATIONS),
+        _ => {}
+    }
+    not_safe
+}
+
+/// Updates the global metrics registry with the contents of `slashing_db`.
+///
+/// The metrics are left unchanged if the database can't be read.
+pub fn scrape_for_metrics(slashing_db: &SlashingDatabase) {
+    let summaries = match slashing_db.validator_summaries(None) {
+        Ok(summaries) => summaries,
+        Err(_) => return,
+    };
+
+    set_gauge(
+        &SLASHING_PROTECTION_REGISTERED_VALIDATORS,
+        summaries.len() as i64,
+    );
+    for summary in &summaries {
+        let pubkey = summary.pubkey.to_hex_string();
+        set_int_gauge(
+            &SLASHING_PROTECTION_SIGNED_BLOCKS,
+            &[&pubkey],
+            summary.num_signed_blocks as i64,
+        );
+        set_int_gauge(
+            &SLASHING_PROTECTION_SIGNED_ATTESTATIONS,
+            &[&pubkey],
+            summary.num_signed_attestations as i64,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::pubkey;
+    use tempfile::tempdir;
+    use types::{Hash256, Slot};
+
+    fn prevented_blocks() -> i64 {
+        SLASHING_PROTECTION_PREVENTED_BLOCKS.as_ref().unwrap().get()
+    }
+
+    #[test]
+    fn counts_prevented_blocks() {
+        let dir = tempdir().unwrap();
+        let slashing_db = SlashingDatabase::create(&dir.path().join("db.sqlite")).unwrap();
+        let pk = pubkey(0);
+        slashing_db.register_validator(&pk).unwrap();
+
+        slashing_db
+            .check_and_insert_block_signing_root(&pk, Slot::new(10), Hash256::from_low_u64_be(1))
+            .unwrap();
+
+        // Other tests may also be counting prevented blocks, so only check for an increase.
+        let before = prevented_blocks();
+        slashing_db
+            .check_and_insert_block_signing_root(&pk, Slot::new(10), Hash256::from_low_u64_be(2))
+            .unwrap_err();
+        assert!(prevented_blocks() > before);
+    }
+
+    #[test]
+    fn scrape_records_per_validator() {
+        let dir = tempdir().unwrap();
+        let slashing_db = SlashingDatabase::create(&dir.path().join("db.sqlite")).unwrap();
+        let pk = pubkey(0);
+        slashing_db.register_validator(&pk).unwrap();
+        slashing_db
+            .check_and_insert_block_signing_root(&pk, Slot::new(10), Hash256::from_low_u64_be(1))
+            .unwrap();
+
+        scrape_for_metrics(&slashing_db);
+
+        let signed_blocks =
+            get_int_gauge(&SLASHING_PROTECTION_SIGNED_BLOCKS, &[&pk.to_hex_string()]).unwrap();
+        assert_eq!(signed_blocks.get(), 1);
+    }
+}
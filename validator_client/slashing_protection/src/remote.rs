@@ -0,0 +1,263 @@
+//! Slashing protection provided by a remote service over HTTP.
+//!
+//! The service is expected to check and record each message atomically, in the same manner as
+//! the `SlashingDatabase`, so that many validator clients may share one source of protection.
+use crate::{NotSafe, Safe, SlashingProtection};
+use reqwest::{blocking::Client, StatusCode};
+use serde_derive::{Deserialize, Serialize};
+use std::time::Duration;
+use types::{AttestationData, BeaconBlockHeader, Epoch, Hash256, PublicKey, SignedRoot, Slot};
+
+/// The time to wait for the remote service to respond to a single request.
+pub const REMOTE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize)]
+struct RegisterValidatorsRequest {
+    public_keys: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct BlockRequest {
+    public_key: String,
+    slot: Slot,
+    signing_root: Hash256,
+}
+
+#[derive(Serialize)]
+struct AttestationRequest {
+    public_key: String,
+    source_epoch: Epoch,
+    target_epoch: Epoch,
+    signing_root: Hash256,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum CheckStatus {
+    Valid,
+    SameData,
+}
+
+#[derive(Deserialize)]
+struct CheckResponse {
+    status: CheckStatus,
+}
+
+#[derive(Deserialize)]
+struct RefusalResponse {
+    reason: String,
+}
+
+/// A client for a remote slashing protection service.
+///
+/// The service must offer the following endpoints, all of which accept JSON:
+///
+/// - `POST /slashing-protection/validators`: register the given `public_keys`.
+/// - `POST /slashing-protection/block`: check and record a block proposal.
+/// - `POST /slashing-protection/attestation`: check and record an attestation.
+///
+/// Checks respond `200 OK` with a `status` of `valid` or `same_data`, or `412 Precondition
+/// Failed` with the `reason` the message was refused.
+#[derive(Debug, Clone)]
+pub struct RemoteSlashingProtection {
+    client: Client,
+    url: String,
+}
+
+impl RemoteSlashingProtection {
+    /// Create a client for the service at `url`, e.g. `http://localhost:9000`.
+    pub fn new(url: &str) -> Result<Self, NotSafe> {
+        let client = Client::builder()
+            .timeout(REMOTE_TIMEOUT)
+            .build()
+            .map_err(|e| NotSafe::RemoteError(format!("Unable to build client: {:?}", e)))?;
+        Ok(Self {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// The URL of the remote service.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn post<T: serde::Serialize>(
+        &self,
+        path: &str,
+        body: &T,
+    ) -> Result<reqwest::blocking::Response, NotSafe> {
+        self.client
+            .post(&format!("{}/slashing-protection/{}", self.url, path))
+            .json(body)
+            .send()
+            .map_err(|e| NotSafe::RemoteError(format!("Request failed: {:?}", e)))
+    }
+
+    /// Post a check request to `path`, and interpret the response of the service.
+    fn check<T: serde::Serialize>(&self, path: &str, body: &T) -> Result<Safe, NotSafe> {
+        let response = self.post(path, body)?;
+
+        match response.status() {
+            StatusCode::OK => {
+                let check: CheckResponse = response
+                    .json()
+                    .map_err(|e| NotSafe::RemoteError(format!("Invalid response: {:?}", e)))?;
+                Ok(match check.status {
+                    CheckStatus::Valid => Safe::Valid,
+                    CheckStatus::SameData => Safe::SameData,
+                })
+            }
+            StatusCode::PRECONDITION_FAILED => {
+                let refusal: RefusalResponse = response
+                    .json()
+                    .map_err(|e| NotSafe::RemoteError(format!("Invalid response: {:?}", e)))?;
+                Err(NotSafe::RemoteRefused(refusal.reason))
+            }
+            status => Err(NotSafe::RemoteError(format!(
+                "Unexpected status: {}",
+                status
+            ))),
+        }
+    }
+}
+
+impl SlashingProtection for RemoteSlashingProtection {
+    fn register_validators(&self, public_keys: &[PublicKey]) -> Result<(), NotSafe> {
+        let request = RegisterValidatorsRequest {
+            public_keys: public_keys.iter().map(PublicKey::to_hex_string).collect(),
+        };
+        let response = self.post("validators", &request)?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(NotSafe::RemoteError(format!(
+                "Unable to register validators: {}",
+                response.status()
+            )))
+        }
+    }
+
+    fn check_and_insert_block_proposal(
+        &self,
+        validator_pubkey: &PublicKey,
+        block_header: &BeaconBlockHeader,
+        domain: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        let request = BlockRequest {
+            public_key: validator_pubkey.to_hex_string(),
+            slot: block_header.slot,
+            signing_root: block_header.signing_root(domain),
+        };
+        self.check("block", &request)
+    }
+
+    fn check_and_insert_attestation(
+        &self,
+        validator_pubkey: &PublicKey,
+        attestation: &AttestationData,
+        domain: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        let request = AttestationRequest {
+            public_key: validator_pubkey.to_hex_string(),
+            source_epoch: attestation.source.epoch,
+            target_epoch: attestation.target.epoch,
+            signing_root: attestation.signing_root(domain),
+        };
+        self.check("attestation", &request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Response, Server};
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+
+    /// Serves `status` and `body` in response to every request, returning the URL of the server.
+    ///
+    /// Must be called from within a runtime.
+    fn mock_service(status: StatusCode, body: &'static str) -> String {
+        let make_service = make_service_fn(move |_| async move {
+            Ok::<_, Infallible>(service_fn(move |_| async move {
+                let mut response = Response::new(Body::from(body));
+                *response.status_mut() = status;
+                Ok::<_, Infallible>(response)
+            }))
+        });
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let url = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+        url
+    }
+
+    fn block_header() -> BeaconBlockHeader {
+        BeaconBlockHeader {
+            slot: Slot::new(1),
+            proposer_index: 0,
+            parent_root: Hash256::zero(),
+            state_root: Hash256::zero(),
+            body_root: Hash256::zero(),
+        }
+    }
+
+    /// The client blocks, so within a runtime it must be used from the blocking thread pool, as
+    /// done by the validator client.
+    #[tokio::test(threaded_scheduler)]
+    async fn checks_within_runtime() {
+        let approving =
+            RemoteSlashingProtection::new(&mock_service(StatusCode::OK, r#"{"status": "valid"}"#))
+                .unwrap();
+        let refusing = RemoteSlashingProtection::new(&mock_service(
+            StatusCode::PRECONDITION_FAILED,
+            r#"{"reason": "double vote"}"#,
+        ))
+        .unwrap();
+
+        let approved = tokio::task::spawn_blocking(move || {
+            approving.check_and_insert_block_proposal(
+                &crate::test_utils::pubkey(0),
+                &block_header(),
+                Hash256::zero(),
+            )
+        })
+        .await
+        .unwrap();
+        assert_eq!(approved, Ok(Safe::Valid));
+
+        let refused = tokio::task::spawn_blocking(move || {
+            refusing.check_and_insert_block_proposal(
+                &crate::test_utils::pubkey(0),
+                &block_header(),
+                Hash256::zero(),
+            )
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            refused,
+            Err(NotSafe::RemoteRefused("double vote".to_string()))
+        );
+    }
+
+    #[test]
+    fn trims_trailing_slash() {
+        let remote = RemoteSlashingProtection::new("http://localhost:9000/").unwrap();
+        assert_eq!(remote.url(), "http://localhost:9000");
+    }
+
+    #[test]
+    fn unreachable_service_is_not_safe() {
+        // Nothing listens on port 1, so the request must fail rather than approve the block.
+        let remote = RemoteSlashingProtection::new("http://127.0.0.1:1").unwrap();
+        let result = remote.check_and_insert_block_proposal(
+            &crate::test_utils::pubkey(0),
+            &block_header(),
+            Hash256::zero(),
+        );
+        assert!(matches!(result, Err(NotSafe::RemoteError(_))));
+    }
+}
@@ -0,0 +1,346 @@
+//! Creation and migration of the tables of the slashing protection database.
+//!
+//! The version of the schema is stored in the `metadata` table. Databases created before the
+//! table was introduced are version 1. When a database is opened for writing it is upgraded to
+//! `SCHEMA_VERSION` by applying each of the `MIGRATIONS` it lacks, in a single transaction. A
+//! database with a newer schema than this software supports is never opened, as it may hold
+//! slashing protection data that this software would ignore.
+use crate::NotSafe;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+/// A function which upgrades the schema by one version.
+type Migration = fn(&Transaction) -> Result<(), NotSafe>;
+
+/// The migrations between schema versions, where `MIGRATIONS[i]` upgrades a database from version
+/// `i + 1` to `i + 2`.
+///
+/// Never modify an existing migration, add a new one instead.
+const MIGRATIONS: &[Migration] = &[migrate_v1_to_v2, migrate_v2_to_v3, migrate_v3_to_v4];
+
+/// The version of the schema created by `create_tables` and produced by `migrate`.
+pub const SCHEMA_VERSION: u64 = MIGRATIONS.len() as u64 + 1;
+
+/// Create all tables at the latest schema version.
+pub fn create_tables(txn: &Transaction) -> Result<(), NotSafe> {
+    txn.execute(
+        "CREATE TABLE validators (
+            id INTEGER PRIMARY KEY,
+            public_key BLOB NOT NULL
+        )",
+        params![],
+    )?;
+
+    txn.execute(
+        "CREATE TABLE signed_blocks (
+            validator_id INTEGER NOT NULL,
+            slot INTEGER NOT NULL,
+            signing_root BLOB NOT NULL,
+            FOREIGN KEY(validator_id) REFERENCES validators(id)
+            UNIQUE (validator_id, slot)
+        )",
+        params![],
+    )?;
+
+    txn.execute(
+        "CREATE TABLE signed_attestations (
+            validator_id INTEGER,
+            source_epoch INTEGER NOT NULL,
+            target_epoch INTEGER NOT NULL,
+            signing_root BLOB NOT NULL,
+            FOREIGN KEY(validator_id) REFERENCES validators(id)
+            UNIQUE (validator_id, target_epoch)
+        )",
+        params![],
+    )?;
+
+    create_lower_bounds_table(txn)?;
+    create_public_key_index(txn)?;
+    create_network_table(txn)?;
+    set_schema_version(txn, SCHEMA_VERSION)
+}
+
+/// Upgrade the database to `SCHEMA_VERSION`, returning the version it was upgraded from.
+///
+/// All migrations are applied in `txn`, so if any of them fails the database is left unchanged.
+/// Error if the database has a newer schema than `SCHEMA_VERSION`, or an invalid version.
+pub fn migrate(txn: &Transaction) -> Result<u64, NotSafe> {
+    let version = schema_version(txn)?;
+    check_supported(version)?;
+
+    for migration in &MIGRATIONS[version as usize - 1..] {
+        migration(txn)?;
+    }
+    if version != SCHEMA_VERSION {
+        set_schema_version(txn, SCHEMA_VERSION)?;
+    }
+
+    Ok(version)
+}
+
+/// Error if the database cannot be used without first being migrated, or if its schema is not
+/// supported.
+///
+/// Used when opening the database read-only, which prevents migrations.
+pub fn check_current(conn: &Connection) -> Result<(), NotSafe> {
+    let version = schema_version(conn)?;
+    check_supported(version)?;
+
+    if version < SCHEMA_VERSION {
+        Err(NotSafe::SchemaMigrationRequired {
+            version,
+            current: SCHEMA_VERSION,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Read the schema version from the `metadata` table, or 1 if the table does not exist.
+pub fn schema_version(conn: &Connection) -> Result<u64, NotSafe> {
+    let has_metadata = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'metadata'",
+            params![],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+
+    if has_metadata {
+        let version: i64 = conn.query_row(
+            "SELECT schema_version FROM metadata WHERE id = 0",
+            params![],
+            |row| row.get(0),
+        )?;
+        Ok(version as u64)
+    } else {
+        Ok(1)
+    }
+}
+
+fn check_supported(version: u64) -> Result<(), NotSafe> {
+    if version > SCHEMA_VERSION || version == 0 {
+        Err(NotSafe::UnsupportedSchemaVersion {
+            version,
+            supported: SCHEMA_VERSION,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn set_schema_version(txn: &Transaction, version: u64) -> Result<(), NotSafe> {
+    txn.execute(
+        "CREATE TABLE IF NOT EXISTS metadata (
+            id INTEGER PRIMARY KEY CHECK (id = 0),
+            schema_version INTEGER NOT NULL
+        )",
+        params![],
+    )?;
+    txn.execute(
+        "INSERT OR REPLACE INTO metadata (id, schema_version) VALUES (0, ?1)",
+        params![version as i64],
+    )?;
+    Ok(())
+}
+
+/// Create the `lower_bounds` table, if it does not already exist.
+fn create_lower_bounds_table(txn: &Transaction) -> Result<(), NotSafe> {
+    txn.execute(
+        "CREATE TABLE IF NOT EXISTS lower_bounds (
+            validator_id INTEGER PRIMARY KEY,
+            block_proposal_slot INTEGER,
+            attestation_source_epoch INTEGER,
+            attestation_target_epoch INTEGER,
+            FOREIGN KEY(validator_id) REFERENCES validators(id)
+        )",
+        params![],
+    )?;
+    Ok(())
+}
+
+/// Index the public keys of validators, which are used to look up a validator's ID every time
+/// one of its messages is checked.
+fn create_public_key_index(txn: &Transaction) -> Result<(), NotSafe> {
+    txn.execute(
+        "CREATE INDEX validators_public_key ON validators (public_key)",
+        params![],
+    )?;
+    Ok(())
+}
+
+/// Create the `network` table, which records the genesis validators root of the network that the
+/// database protects.
+fn create_network_table(txn: &Transaction) -> Result<(), NotSafe> {
+    txn.execute(
+        "CREATE TABLE network (
+            id INTEGER PRIMARY KEY CHECK (id = 0),
+            genesis_validators_root BLOB NOT NULL
+        )",
+        params![],
+    )?;
+    Ok(())
+}
+
+/// Version 2 adds the `lower_bounds` table.
+///
+/// Some version 1 databases already have the table, as it was previously created whenever the
+/// database was opened.
+fn migrate_v1_to_v2(txn: &Transaction) -> Result<(), NotSafe> {
+    create_lower_bounds_table(txn)
+}
+
+/// Version 3 adds an index on the public keys of validators.
+fn migrate_v2_to_v3(txn: &Transaction) -> Result<(), NotSafe> {
+    create_public_key_index(txn)
+}
+
+/// Version 4 adds the `network` table.
+///
+/// The genesis validators root is unknown for existing databases, and is recorded the next time
+/// the database is used.
+fn migrate_v3_to_v4(txn: &Transaction) -> Result<(), NotSafe> {
+    create_network_table(txn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::pubkey;
+    use crate::SlashingDatabase;
+    use std::path::Path;
+    use tempfile::tempdir;
+    use types::{Hash256, Slot};
+
+    /// Create a database with the version 1 schema, holding a single validator and block.
+    fn create_v1_db(path: &Path) {
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE validators (
+                id INTEGER PRIMARY KEY,
+                public_key BLOB NOT NULL
+            );
+            CREATE TABLE signed_blocks (
+                validator_id INTEGER NOT NULL,
+                slot INTEGER NOT NULL,
+                signing_root BLOB NOT NULL,
+                FOREIGN KEY(validator_id) REFERENCES validators(id)
+                UNIQUE (validator_id, slot)
+            );
+            CREATE TABLE signed_attestations (
+                validator_id INTEGER,
+                source_epoch INTEGER NOT NULL,
+                target_epoch INTEGER NOT NULL,
+                signing_root BLOB NOT NULL,
+                FOREIGN KEY(validator_id) REFERENCES validators(id)
+                UNIQUE (validator_id, target_epoch)
+            );",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO validators (public_key) VALUES (?1)",
+            params![pubkey(0).to_hex_string()],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO signed_blocks (validator_id, slot, signing_root) VALUES (1, 5, ?1)",
+            params![Hash256::from_low_u64_be(1).as_bytes()],
+        )
+        .unwrap();
+    }
+
+    fn set_version(path: &Path, version: u64) {
+        let mut conn = Connection::open(path).unwrap();
+        let txn = conn.transaction().unwrap();
+        set_schema_version(&txn, version).unwrap();
+        txn.commit().unwrap();
+    }
+
+    fn read_version(path: &Path) -> u64 {
+        schema_version(&Connection::open(path).unwrap()).unwrap()
+    }
+
+    fn has_public_key_index(path: &Path) -> bool {
+        Connection::open(path)
+            .unwrap()
+            .query_row(
+                "SELECT 1 FROM sqlite_master WHERE type = 'index' AND name = 'validators_public_key'",
+                params![],
+                |_| Ok(()),
+            )
+            .optional()
+            .unwrap()
+            .is_some()
+    }
+
+    #[test]
+    fn create_sets_current_version() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("db.sqlite");
+        drop(SlashingDatabase::create(&file).unwrap());
+
+        assert_eq!(read_version(&file), SCHEMA_VERSION);
+        assert!(has_public_key_index(&file));
+    }
+
+    #[test]
+    fn open_migrates_v1() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("db.sqlite");
+        create_v1_db(&file);
+        assert_eq!(read_version(&file), 1);
+
+        let db = SlashingDatabase::open(&file).unwrap();
+        assert_eq!(read_version(&file), SCHEMA_VERSION);
+        assert!(has_public_key_index(&file));
+        assert_eq!(db.genesis_validators_root(), Ok(None));
+
+        // Existing records are preserved and still protect the validator.
+        let summaries = db.validator_summaries(None).unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].max_block_slot, Some(Slot::new(5)));
+        db.check_and_insert_block_signing_root(&pubkey(0), Slot::new(5), Hash256::zero())
+            .unwrap_err();
+
+        // Re-opening an up-to-date database is a no-op.
+        drop(db);
+        SlashingDatabase::open(&file).unwrap();
+        assert_eq!(read_version(&file), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn open_refuses_newer_version() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("db.sqlite");
+        drop(SlashingDatabase::create(&file).unwrap());
+        set_version(&file, SCHEMA_VERSION + 1);
+
+        let expected = NotSafe::UnsupportedSchemaVersion {
+            version: SCHEMA_VERSION + 1,
+            supported: SCHEMA_VERSION,
+        };
+        assert_eq!(SlashingDatabase::open(&file).unwrap_err(), expected);
+        assert_eq!(
+            SlashingDatabase::open_read_only(&file).unwrap_err(),
+            expected
+        );
+        // The version is left untouched.
+        assert_eq!(read_version(&file), SCHEMA_VERSION + 1);
+    }
+
+    #[test]
+    fn open_read_only_requires_migration() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("db.sqlite");
+        create_v1_db(&file);
+
+        assert_eq!(
+            SlashingDatabase::open_read_only(&file).unwrap_err(),
+            NotSafe::SchemaMigrationRequired {
+                version: 1,
+                current: SCHEMA_VERSION,
+            }
+        );
+        assert_eq!(read_version(&file), 1);
+    }
+}
@@ -0,0 +1,266 @@
+#![cfg(test)]
+
+//! Tests for re-signing messages identical to those already recorded, e.g. after a restart.
+
+use crate::interchange::{
+    Interchange, InterchangeData, InterchangeMetadata, SignedAttestation as InterchangeAttestation,
+};
+use crate::test_utils::pubkey;
+use crate::*;
+use tempfile::{tempdir, TempDir};
+use types::{Epoch, Hash256, Slot};
+
+fn root(i: u64) -> Hash256 {
+    Hash256::from_low_u64_be(i)
+}
+
+fn new_db() -> (TempDir, SlashingDatabase) {
+    let dir = tempdir().unwrap();
+    let slashing_db = SlashingDatabase::create(&dir.path().join("db.sqlite")).unwrap();
+    slashing_db.register_validator(&pubkey(0)).unwrap();
+    (dir, slashing_db)
+}
+
+fn num_signed_blocks(slashing_db: &SlashingDatabase) -> u64 {
+    slashing_db.validator_summaries(None).unwrap()[0].num_signed_blocks
+}
+
+fn num_signed_attestations(slashing_db: &SlashingDatabase) -> u64 {
+    slashing_db.validator_summaries(None).unwrap()[0].num_signed_attestations
+}
+
+#[test]
+fn block_same_signing_root() {
+    let (_dir, slashing_db) = new_db();
+    let pk = pubkey(0);
+
+    assert_eq!(
+        slashing_db.check_and_insert_block_signing_root(&pk, Slot::new(10), root(1)),
+        Ok(Safe::Valid)
+    );
+    assert_eq!(
+        slashing_db.check_and_insert_block_signing_root(&pk, Slot::new(10), root(1)),
+        Ok(Safe::SameData)
+    );
+    assert_eq!(num_signed_blocks(&slashing_db), 1);
+}
+
+#[test]
+fn block_same_signing_root_after_restart() {
+    let dir = tempdir().unwrap();
+    let file = dir.path().join("db.sqlite");
+    let pk = pubkey(0);
+
+    let slashing_db = SlashingDatabase::create(&file).unwrap();
+    slashing_db.register_validator(&pk).unwrap();
+    slashing_db
+        .check_and_insert_block_signing_root(&pk, Slot::new(10), root(1))
+        .unwrap();
+    drop(slashing_db);
+
+    let slashing_db = SlashingDatabase::open(&file).unwrap();
+    assert_eq!(
+        slashing_db.check_and_insert_block_signing_root(&pk, Slot::new(10), root(1)),
+        Ok(Safe::SameData)
+    );
+}
+
+#[test]
+fn block_different_signing_root() {
+    let (_dir, slashing_db) = new_db();
+    let pk = pubkey(0);
+
+    slashing_db
+        .check_and_insert_block_signing_root(&pk, Slot::new(10), root(1))
+        .unwrap();
+    assert_eq!(
+        slashing_db.check_and_insert_block_signing_root(&pk, Slot::new(10), root(2)),
+        Err(NotSafe::InvalidBlock(InvalidBlock::DoubleBlockProposal(
+            SignedBlock::new(Slot::new(10), root(1))
+        )))
+    );
+    assert_eq!(num_signed_blocks(&slashing_db), 1);
+}
+
+#[test]
+fn block_unknown_signing_root_never_matches() {
+    let (_dir, slashing_db) = new_db();
+    let pk = pubkey(0);
+
+    slashing_db
+        .check_and_insert_block_signing_root(&pk, Slot::new(10), Hash256::zero())
+        .unwrap();
+
+    for signing_root in &[Hash256::zero(), root(1)] {
+        assert!(matches!(
+            slashing_db.check_and_insert_block_signing_root(&pk, Slot::new(10), *signing_root),
+            Err(NotSafe::InvalidBlock(InvalidBlock::DoubleBlockProposal(_)))
+        ));
+    }
+}
+
+#[test]
+fn attestation_same_signing_root() {
+    let (_dir, slashing_db) = new_db();
+    let pk = pubkey(0);
+
+    assert_eq!(
+        slashing_db.check_and_insert_attestation_signing_root(
+            &pk,
+            Epoch::new(1),
+            Epoch::new(2),
+            root(1)
+        ),
+        Ok(Safe::Valid)
+    );
+    assert_eq!(
+        slashing_db.check_and_insert_attestation_signing_root(
+            &pk,
+            Epoch::new(1),
+            Epoch::new(2),
+            root(1)
+        ),
+        Ok(Safe::SameData)
+    );
+    assert_eq!(num_signed_attestations(&slashing_db), 1);
+}
+
+#[test]
+fn attestation_same_signing_root_after_restart() {
+    let dir = tempdir().unwrap();
+    let file = dir.path().join("db.sqlite");
+    let pk = pubkey(0);
+
+    let slashing_db = SlashingDatabase::create(&file).unwrap();
+    slashing_db.register_validator(&pk).unwrap();
+    slashing_db
+        .check_and_insert_attestation_signing_root(&pk, Epoch::new(1), Epoch::new(2), root(1))
+        .unwrap();
+    drop(slashing_db);
+
+    let slashing_db = SlashingDatabase::open(&file).unwrap();
+    assert_eq!(
+        slashing_db.check_and_insert_attestation_signing_root(
+            &pk,
+            Epoch::new(1),
+            Epoch::new(2),
+            root(1)
+        ),
+        Ok(Safe::SameData)
+    );
+}
+
+#[test]
+fn attestation_different_signing_root() {
+    let (_dir, slashing_db) = new_db();
+    let pk = pubkey(0);
+
+    slashing_db
+        .check_and_insert_attestation_signing_root(&pk, Epoch::new(1), Epoch::new(2), root(1))
+        .unwrap();
+    assert_eq!(
+        slashing_db.check_and_insert_attestation_signing_root(
+            &pk,
+            Epoch::new(1),
+            Epoch::new(2),
+            root(2)
+        ),
+        Err(NotSafe::InvalidAttestation(InvalidAttestation::DoubleVote(
+            SignedAttestation::new(Epoch::new(1), Epoch::new(2), root(1))
+        )))
+    );
+    assert_eq!(num_signed_attestations(&slashing_db), 1);
+}
+
+#[test]
+fn attestation_same_signing_root_different_source() {
+    let (_dir, slashing_db) = new_db();
+    let pk = pubkey(0);
+
+    slashing_db
+        .check_and_insert_attestation_signing_root(&pk, Epoch::new(1), Epoch::new(2), root(1))
+        .unwrap();
+    assert!(matches!(
+        slashing_db.check_and_insert_attestation_signing_root(
+            &pk,
+            Epoch::new(0),
+            Epoch::new(2),
+            root(1)
+        ),
+        Err(NotSafe::InvalidAttestation(InvalidAttestation::DoubleVote(
+            _
+        )))
+    ));
+}
+
+#[test]
+fn attestation_unknown_signing_root_never_matches() {
+    let (_dir, slashing_db) = new_db();
+    let pk = pubkey(0);
+
+    slashing_db
+        .check_and_insert_attestation_signing_root(
+            &pk,
+            Epoch::new(1),
+            Epoch::new(2),
+            Hash256::zero(),
+        )
+        .unwrap();
+
+    for signing_root in &[Hash256::zero(), root(1)] {
+        assert!(matches!(
+            slashing_db.check_and_insert_attestation_signing_root(
+                &pk,
+                Epoch::new(1),
+                Epoch::new(2),
+                *signing_root
+            ),
+            Err(NotSafe::InvalidAttestation(InvalidAttestation::DoubleVote(
+                _
+            )))
+        ));
+    }
+}
+
+#[test]
+fn attestation_same_signing_root_below_lower_bound() {
+    let (_dir, slashing_db) = new_db();
+    let pk = pubkey(0);
+
+    slashing_db
+        .check_and_insert_attestation_signing_root(&pk, Epoch::new(1), Epoch::new(2), root(1))
+        .unwrap();
+
+    // Record a later attestation with an unknown signing root, raising the lower bound above the
+    // first attestation.
+    let interchange = Interchange {
+        metadata: InterchangeMetadata {
+            interchange_format_version: SUPPORTED_INTERCHANGE_FORMAT_VERSION,
+            genesis_validators_root: Hash256::zero(),
+        },
+        data: vec![InterchangeData {
+            pubkey: pk.clone(),
+            signed_blocks: vec![],
+            signed_attestations: vec![InterchangeAttestation {
+                source_epoch: Epoch::new(2),
+                target_epoch: Epoch::new(3),
+                signing_root: None,
+            }],
+        }],
+    };
+    slashing_db
+        .import_interchange_info(&interchange, Hash256::zero())
+        .unwrap();
+
+    // Re-signing the first attestation is still safe.
+    assert_eq!(
+        slashing_db.check_and_insert_attestation_signing_root(
+            &pk,
+            Epoch::new(1),
+            Epoch::new(2),
+            root(1)
+        ),
+        Ok(Safe::SameData)
+    );
+    assert_eq!(num_signed_attestations(&slashing_db), 2);
+}
@@ -1,32 +1,87 @@
+use crate::interchange::{
+    Interchange, InterchangeData, InterchangeMetadata, SignedAttestation as InterchangeAttestation,
+    SignedBlock as InterchangeBlock,
+};
+use crate::lower_bound::LowerBound;
+use crate::metrics;
+use crate::schema;
 use crate::signed_attestation::InvalidAttestation;
 use crate::signed_block::InvalidBlock;
-use crate::{NotSafe, Safe, SignedAttestation, SignedBlock};
+use crate::{hash256_from_row, NotSafe, Safe, SignedAttestation, SignedBlock, SlashingProtection};
+use fs2::FileExt;
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::{params, OptionalExtension, Transaction, TransactionBehavior};
+use rusqlite::{params, OpenFlags, OptionalExtension, Transaction, TransactionBehavior};
 use std::fs::{File, OpenOptions};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
-use types::{AttestationData, BeaconBlockHeader, Hash256, PublicKey, SignedRoot};
+use types::{AttestationData, BeaconBlockHeader, Epoch, Hash256, PublicKey, SignedRoot, Slot};
 
 type Pool = r2d2::Pool<SqliteConnectionManager>;
 
-/// We set the pool size to 1 for compatibility with locking_mode=EXCLUSIVE.
+/// The maximum number of connections to the database.
 ///
-/// This is perhaps overkill in the presence of exclusive transactions, but has
-/// the added bonus of preventing other processes from trying to use our slashing database.
-pub const POOL_SIZE: u32 = 1;
+/// All writes happen inside exclusive transactions, so the number of connections does not affect
+/// safety; multiple connections simply allow reads to proceed concurrently.
+pub const POOL_SIZE: u32 = 4;
 #[cfg(not(test))]
 pub const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
 #[cfg(test)]
 pub const CONNECTION_TIMEOUT: Duration = Duration::from_millis(100);
+/// The time to wait for another connection (possibly in another process) to release its lock on
+/// the database, before failing with `SQLITE_BUSY`.
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// The extension of the lock file that guards the database against multiple writers.
+pub const LOCK_FILE_EXTENSION: &str = "lock";
+
+/// Supported version of the interchange format.
+pub const SUPPORTED_INTERCHANGE_FORMAT_VERSION: u64 = 5;
+/// The file name of the slashing protection database within the validator data directory.
+pub const SLASHING_PROTECTION_FILENAME: &str = "slashing_protection.sqlite";
+
+/// An exclusive lock held by the process which has the database open for writing.
+///
+/// The lock is an advisory lock held on a file alongside the database, which the operating system
+/// releases when the process exits. Hence a crashed process never leaves the database locked.
+#[derive(Debug)]
+struct WriterLock {
+    _file: File,
+}
+
+impl WriterLock {
+    /// Obtain the writer lock for the database at `db_path`.
+    ///
+    /// Error if another process already holds the lock.
+    fn acquire(db_path: &Path) -> Result<Self, NotSafe> {
+        let lock_path = Self::path(db_path);
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .open(&lock_path)?;
+        file.try_lock_exclusive()
+            .map_err(|_| NotSafe::DatabaseLocked(lock_path))?;
+        Ok(Self { _file: file })
+    }
+
+    /// The path of the lock file for the database at `db_path`.
+    fn path(db_path: &Path) -> PathBuf {
+        db_path.with_extension(LOCK_FILE_EXTENSION)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct SlashingDatabase {
     conn_pool: Pool,
+    /// The writer lock, which is `None` if the database was opened read-only.
+    ///
+    /// Shared between clones so that the lock is released only when the last clone is dropped.
+    _writer_lock: Option<Arc<WriterLock>>,
 }
 
 impl SlashingDatabase {
     /// Open an existing database at the given `path`, or create one if none exists.
+    ///
+    /// Error if another process has the database open for writing.
     pub fn open_or_create(path: &Path) -> Result<Self, NotSafe> {
         if path.exists() {
             Self::open(path)
@@ -37,8 +92,11 @@ impl SlashingDatabase {
 
     /// Create a slashing database at the given path.
     ///
-    /// Error if a database (or any file) already exists at `path`.
+    /// Error if a database (or any file) already exists at `path`, or if another process has the
+    /// database open for writing.
     pub fn create(path: &Path) -> Result<Self, NotSafe> {
+        let writer_lock = WriterLock::acquire(path)?;
+
         let file = OpenOptions::new()
             .write(true)
             .read(true)
@@ -46,54 +104,64 @@ impl SlashingDatabase {
             .open(path)?;
 
         Self::set_db_file_permissions(&file)?;
-        let conn_pool = Self::open_conn_pool(path)?;
-        let conn = conn_pool.get()?;
-
-        conn.execute(
-            "CREATE TABLE validators (
-                id INTEGER PRIMARY KEY,
-                public_key BLOB NOT NULL
-            )",
-            params![],
-        )?;
+        let conn_pool = Self::open_conn_pool(path, OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+        let mut conn = conn_pool.get()?;
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+        schema::create_tables(&txn)?;
+        txn.commit()?;
 
-        conn.execute(
-            "CREATE TABLE signed_blocks (
-                validator_id INTEGER NOT NULL,
-                slot INTEGER NOT NULL,
-                signing_root BLOB NOT NULL,
-                FOREIGN KEY(validator_id) REFERENCES validators(id)
-                UNIQUE (validator_id, slot)
-            )",
-            params![],
-        )?;
+        Ok(Self {
+            conn_pool,
+            _writer_lock: Some(Arc::new(writer_lock)),
+        })
+    }
 
-        conn.execute(
-            "CREATE TABLE signed_attestations (
-                validator_id INTEGER,
-                source_epoch INTEGER NOT NULL,
-                target_epoch INTEGER NOT NULL,
-                signing_root BLOB NOT NULL,
-                FOREIGN KEY(validator_id) REFERENCES validators(id)
-                UNIQUE (validator_id, target_epoch)
-            )",
-            params![],
-        )?;
+    /// Open an existing `SlashingDatabase` from disk, upgrading its schema if necessary.
+    ///
+    /// Error if another process has the database open for writing, or if the database has a newer
+    /// schema than this software supports.
+    pub fn open(path: &Path) -> Result<Self, NotSafe> {
+        let writer_lock = WriterLock::acquire(path)?;
+        let conn_pool = Self::open_conn_pool(&path, OpenFlags::SQLITE_OPEN_READ_WRITE)?;
 
-        Ok(Self { conn_pool })
+        let mut conn = conn_pool.get()?;
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+        schema::migrate(&txn)?;
+        txn.commit()?;
+        drop(conn);
+
+        Ok(Self {
+            conn_pool,
+            _writer_lock: Some(Arc::new(writer_lock)),
+        })
     }
 
-    /// Open an existing `SlashingDatabase` from disk.
-    pub fn open(path: &Path) -> Result<Self, NotSafe> {
-        let conn_pool = Self::open_conn_pool(&path)?;
-        Ok(Self { conn_pool })
+    /// Open an existing `SlashingDatabase` from disk without the ability to modify it.
+    ///
+    /// This is safe to use whilst another process (e.g., a running validator client) has the
+    /// database open for writing, and is intended for tasks like exporting slashing protection
+    /// data.
+    ///
+    /// Error if the database has an older schema which must first be upgraded by opening it for
+    /// writing, or a newer schema than this software supports.
+    pub fn open_read_only(path: &Path) -> Result<Self, NotSafe> {
+        if !path.exists() {
+            return Err(NotSafe::IOError(std::io::ErrorKind::NotFound));
+        }
+        let conn_pool = Self::open_conn_pool(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        schema::check_current(&*conn_pool.get()?)?;
+        Ok(Self {
+            conn_pool,
+            _writer_lock: None,
+        })
     }
 
     /// Open a new connection pool with all of the necessary settings and tweaks.
-    fn open_conn_pool(path: &Path) -> Result<Pool, NotSafe> {
+    fn open_conn_pool(path: &Path, flags: OpenFlags) -> Result<Pool, NotSafe> {
+        let read_only = flags.contains(OpenFlags::SQLITE_OPEN_READ_ONLY);
         let manager = SqliteConnectionManager::file(path)
-            .with_flags(rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE)
-            .with_init(Self::apply_pragmas);
+            .with_flags(flags)
+            .with_init(move |conn| Self::apply_pragmas(conn, read_only));
         let conn_pool = Pool::builder()
             .max_size(POOL_SIZE)
             .connection_timeout(CONNECTION_TIMEOUT)
@@ -104,13 +172,23 @@ impl SlashingDatabase {
 
     /// Apply the necessary settings to an SQLite connection.
     ///
-    /// Most importantly, put the database into exclusive locking mode, so that threads are forced
-    /// to serialise all DB access (to prevent slashable data being checked and signed in parallel).
-    /// The exclusive locking mode also has the benefit of applying to other processes, so multiple
-    /// Lighthouse processes trying to access the same database will also be blocked.
-    fn apply_pragmas(conn: &mut rusqlite::Connection) -> Result<(), rusqlite::Error> {
+    /// The database uses write-ahead logging, which allows readers (e.g., an export from another
+    /// process) to proceed whilst a write is in progress. Writes are serialised by performing them
+    /// in exclusive transactions (to prevent slashable data being checked and signed in parallel),
+    /// with the busy timeout allowing them to wait for one another rather than failing.
+    fn apply_pragmas(
+        conn: &mut rusqlite::Connection,
+        read_only: bool,
+    ) -> Result<(), rusqlite::Error> {
         conn.pragma_update(None, "foreign_keys", &true)?;
-        conn.pragma_update(None, "locking_mode", &"EXCLUSIVE")?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // Changing the journal mode returns the new mode, so it must be run as a query. The mode
+        // is stored in the database file, so read-only connections leave it unchanged.
+        if !read_only {
+            conn.query_row("PRAGMA journal_mode = WAL", params![], |row| {
+                row.get::<_, String>(0)
+            })?;
+        }
         Ok(())
     }
 
@@ -138,21 +216,33 @@ impl SlashingDatabase {
     }
 
     /// Register multiple validators with the slashing protection database.
+    ///
+    /// Validators that are already registered are skipped.
     pub fn register_validators<'a>(
         &self,
         public_keys: impl Iterator<Item = &'a PublicKey>,
     ) -> Result<(), NotSafe> {
         let mut conn = self.conn_pool.get()?;
-        let txn = conn.transaction()?;
-        {
-            let mut stmt = txn.prepare("INSERT INTO validators (public_key) VALUES (?1)")?;
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+        self.register_validators_in_txn(public_keys, &txn)?;
+        txn.commit()?;
+        Ok(())
+    }
 
-            for pubkey in public_keys {
+    /// Register multiple validators inside the given transaction.
+    ///
+    /// The caller must commit the transaction for the changes to be persisted.
+    fn register_validators_in_txn<'a>(
+        &self,
+        public_keys: impl Iterator<Item = &'a PublicKey>,
+        txn: &Transaction,
+    ) -> Result<(), NotSafe> {
+        let mut stmt = txn.prepare("INSERT INTO validators (public_key) VALUES (?1)")?;
+        for pubkey in public_keys {
+            if self.get_validator_id_opt(txn, pubkey)?.is_none() {
                 stmt.execute(&[pubkey.to_hex_string()])?;
             }
         }
-        txn.commit()?;
-
         Ok(())
     }
 
@@ -160,14 +250,145 @@ impl SlashingDatabase {
     ///
     /// This is NOT the same as a validator index, and depends on the ordering that validators
     /// are registered with the slashing protection database (and may vary between machines).
-    fn get_validator_id(txn: &Transaction, public_key: &PublicKey) -> Result<i64, NotSafe> {
-        txn.query_row(
-            "SELECT id FROM validators WHERE public_key = ?1",
-            params![&public_key.to_hex_string()],
-            |row| row.get(0),
-        )
-        .optional()?
-        .ok_or_else(|| NotSafe::UnregisteredValidator(public_key.clone()))
+    fn get_validator_id(&self, txn: &Transaction, public_key: &PublicKey) -> Result<i64, NotSafe> {
+        self.get_validator_id_opt(txn, public_key)?
+            .ok_or_else(|| NotSafe::UnregisteredValidator(public_key.clone()))
+    }
+
+    /// Optional version of `get_validator_id`.
+    fn get_validator_id_opt(
+        &self,
+        txn: &Transaction,
+        public_key: &PublicKey,
+    ) -> Result<Option<i64>, NotSafe> {
+        Ok(txn
+            .query_row(
+                "SELECT id FROM validators WHERE public_key = ?1",
+                params![&public_key.to_hex_string()],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Get the genesis validators root of the network that the database protects, if one has been
+    /// recorded.
+    pub fn genesis_validators_root(&self) -> Result<Option<Hash256>, NotSafe> {
+        let conn = self.conn_pool.get()?;
+        Ok(conn
+            .query_row(
+                "SELECT genesis_validators_root FROM network WHERE id = 0",
+                params![],
+                |row| hash256_from_row(0, row),
+            )
+            .optional()?)
+    }
+
+    /// Check that the database protects the network with `genesis_validators_root`, recording it
+    /// if no network has been recorded yet.
+    ///
+    /// Error if the database protects a different network. Signing or importing messages for one
+    /// network with the slashing protection data of another is unsafe, as neither network's data
+    /// protects against slashing on the other.
+    pub fn check_genesis_validators_root(
+        &self,
+        genesis_validators_root: Hash256,
+    ) -> Result<(), NotSafe> {
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+        self.check_genesis_validators_root_txn(&txn, genesis_validators_root)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn check_genesis_validators_root_txn(
+        &self,
+        txn: &Transaction,
+        genesis_validators_root: Hash256,
+    ) -> Result<(), NotSafe> {
+        let recorded = txn
+            .query_row(
+                "SELECT genesis_validators_root FROM network WHERE id = 0",
+                params![],
+                |row| hash256_from_row(0, row),
+            )
+            .optional()?;
+
+        match recorded {
+            Some(database) if database != genesis_validators_root => {
+                Err(NotSafe::GenesisValidatorsRootMismatch {
+                    database,
+                    network: genesis_validators_root,
+                })
+            }
+            Some(_) => Ok(()),
+            None => self.set_genesis_validators_root_txn(txn, genesis_validators_root),
+        }
+    }
+
+    /// Record `genesis_validators_root` as the network that the database protects, replacing any
+    /// previously recorded network.
+    ///
+    /// This should only be used when the user has explicitly asked to move the database to a new
+    /// network.
+    pub fn set_genesis_validators_root(
+        &self,
+        genesis_validators_root: Hash256,
+    ) -> Result<(), NotSafe> {
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+        self.set_genesis_validators_root_txn(&txn, genesis_validators_root)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn set_genesis_validators_root_txn(
+        &self,
+        txn: &Transaction,
+        genesis_validators_root: Hash256,
+    ) -> Result<(), NotSafe> {
+        txn.execute(
+            "INSERT OR REPLACE INTO network (id, genesis_validators_root) VALUES (0, ?1)",
+            params![genesis_validators_root.as_bytes()],
+        )?;
+        Ok(())
+    }
+
+    /// Get the lower bound for a validator, which is empty if none has been recorded.
+    fn get_lower_bound(&self, txn: &Transaction, validator_id: i64) -> Result<LowerBound, NotSafe> {
+        Ok(txn
+            .query_row(
+                "SELECT block_proposal_slot, attestation_source_epoch, attestation_target_epoch
+                 FROM lower_bounds
+                 WHERE validator_id = ?1",
+                params![validator_id],
+                LowerBound::from_row,
+            )
+            .optional()?
+            .unwrap_or_default())
+    }
+
+    /// Raise the lower bound for a validator so that it is at least `new_bound`.
+    ///
+    /// Lower bounds never decrease.
+    fn update_lower_bound(
+        &self,
+        txn: &Transaction,
+        validator_id: i64,
+        new_bound: LowerBound,
+    ) -> Result<(), NotSafe> {
+        let bound = self.get_lower_bound(txn, validator_id)?.merge(new_bound);
+        txn.execute(
+            "INSERT OR REPLACE INTO lower_bounds
+             (validator_id, block_proposal_slot, attestation_source_epoch, attestation_target_epoch)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                validator_id,
+                bound.block_proposal_slot,
+                bound.attestation_source_epoch,
+                bound.attestation_target_epoch
+            ],
+        )?;
+        Ok(())
     }
 
     /// Check a block proposal from `validator_pubkey` for slash safety.
@@ -175,10 +396,10 @@ impl SlashingDatabase {
         &self,
         txn: &Transaction,
         validator_pubkey: &PublicKey,
-        block_header: &BeaconBlockHeader,
-        domain: Hash256,
+        slot: Slot,
+        signing_root: Hash256,
     ) -> Result<Safe, NotSafe> {
-        let validator_id = Self::get_validator_id(txn, validator_pubkey)?;
+        let validator_id = self.get_validator_id(txn, validator_pubkey)?;
 
         let existing_block = txn
             .prepare(
@@ -186,15 +407,13 @@ impl SlashingDatabase {
                  FROM signed_blocks
                  WHERE validator_id = ?1 AND slot = ?2",
             )?
-            .query_row(
-                params![validator_id, block_header.slot],
-                SignedBlock::from_row,
-            )
+            .query_row(params![validator_id, slot], SignedBlock::from_row)
             .optional()?;
 
         if let Some(existing_block) = existing_block {
-            if existing_block.signing_root == block_header.signing_root(domain) {
-                // Same slot and same hash -> we're re-broadcasting a previously signed block
+            if known_signing_root(existing_block.signing_root) == Some(signing_root) {
+                // Same slot and same hash -> we're re-signing a previously signed block. An
+                // unknown signing root can't be shown to match, so it is never the same data.
                 Ok(Safe::SameData)
             } else {
                 // Same epoch but not the same hash -> it's a DoubleBlockProposal
@@ -203,7 +422,16 @@ impl SlashingDatabase {
                 )))
             }
         } else {
-            Ok(Safe::Valid)
+            // No existing block at this slot, check that the slot exceeds the lower bound.
+            match self.get_lower_bound(txn, validator_id)?.block_proposal_slot {
+                Some(bound_slot) if slot <= bound_slot => Err(NotSafe::InvalidBlock(
+                    InvalidBlock::SlotViolatesLowerBound {
+                        block_slot: slot,
+                        bound_slot,
+                    },
+                )),
+                _ => Ok(Safe::Valid),
+            }
         }
     }
 
@@ -212,12 +440,10 @@ impl SlashingDatabase {
         &self,
         txn: &Transaction,
         validator_pubkey: &PublicKey,
-        attestation: &AttestationData,
-        domain: Hash256,
+        att_source_epoch: Epoch,
+        att_target_epoch: Epoch,
+        att_signing_root: Hash256,
     ) -> Result<Safe, NotSafe> {
-        let att_source_epoch = attestation.source.epoch;
-        let att_target_epoch = attestation.target.epoch;
-
         // Although it's not required to avoid slashing, we disallow attestations
         // which are obviously invalid by virtue of their source epoch exceeding their target.
         if att_source_epoch > att_target_epoch {
@@ -226,7 +452,38 @@ impl SlashingDatabase {
             ));
         }
 
-        let validator_id = Self::get_validator_id(txn, validator_pubkey)?;
+        // 0. Re-signing an attestation identical to one already recorded is safe, and common when
+        //    retrying after a network error. Check for it with a single indexed lookup, before
+        //    the lower bound and surrounding votes. An unknown signing root can't be shown to
+        //    match, so it never takes this path.
+        if known_signing_root(att_signing_root).is_some() {
+            let same_attestation = txn
+                .prepare_cached(
+                    "SELECT 1
+                     FROM signed_attestations
+                     INNER JOIN validators ON signed_attestations.validator_id = validators.id
+                     WHERE validators.public_key = ?1
+                        AND signed_attestations.target_epoch = ?2
+                        AND signed_attestations.source_epoch = ?3
+                        AND signed_attestations.signing_root = ?4",
+                )?
+                .query_row(
+                    params![
+                        &validator_pubkey.to_hex_string(),
+                        att_target_epoch,
+                        att_source_epoch,
+                        att_signing_root.as_bytes()
+                    ],
+                    |_| Ok(()),
+                )
+                .optional()?;
+
+            if same_attestation.is_some() {
+                return Ok(Safe::SameData);
+            }
+        }
+
+        let validator_id = self.get_validator_id(txn, validator_pubkey)?;
 
         // 1. Check for a double vote. Namely, an existing attestation with the same target epoch,
         //    and a different signing root.
@@ -244,8 +501,11 @@ impl SlashingDatabase {
 
         if let Some(existing_attestation) = same_target_att {
             // If the new attestation is identical to the existing attestation, then we already
-            // know that it is safe, and can return immediately.
-            if existing_attestation.signing_root == attestation.signing_root(domain) {
+            // know that it is safe, and can return immediately. An unknown signing root can't be
+            // shown to match, so it is never the same data.
+            if existing_attestation.source_epoch == att_source_epoch
+                && known_signing_root(existing_attestation.signing_root) == Some(att_signing_root)
+            {
                 return Ok(Safe::SameData);
             // Otherwise if the hashes are different, this is a double vote.
             } else {
@@ -255,7 +515,33 @@ impl SlashingDatabase {
             }
         }
 
-        // 2. Check that no previous vote is surrounding `attestation`.
+        // 2. Check that the attestation doesn't conflict with the lower bound, which represents
+        //    attestations we know were signed but whose details are unknown.
+        let lower_bound = self.get_lower_bound(txn, validator_id)?;
+
+        if let Some(bound_epoch) = lower_bound.attestation_source_epoch {
+            if att_source_epoch < bound_epoch {
+                return Err(NotSafe::InvalidAttestation(
+                    InvalidAttestation::SourceLessThanLowerBound {
+                        source_epoch: att_source_epoch,
+                        bound_epoch,
+                    },
+                ));
+            }
+        }
+
+        if let Some(bound_epoch) = lower_bound.attestation_target_epoch {
+            if att_target_epoch <= bound_epoch {
+                return Err(NotSafe::InvalidAttestation(
+                    InvalidAttestation::TargetLessThanOrEqLowerBound {
+                        target_epoch: att_target_epoch,
+                        bound_epoch,
+                    },
+                ));
+            }
+        }
+
+        // 3. Check that no previous vote is surrounding `attestation`.
         // If there is a surrounding attestation, we only return the most recent one.
         let surrounding_attestation = txn
             .prepare(
@@ -277,7 +563,7 @@ impl SlashingDatabase {
             ));
         }
 
-        // 3. Check that no previous vote is surrounded by `attestation`.
+        // 4. Check that no previous vote is surrounded by `attestation`.
         // If there is a surrounded attestation, we only return the most recent one.
         let surrounded_attestation = txn
             .prepare(
@@ -311,19 +597,15 @@ impl SlashingDatabase {
         &self,
         txn: &Transaction,
         validator_pubkey: &PublicKey,
-        block_header: &BeaconBlockHeader,
-        domain: Hash256,
+        slot: Slot,
+        signing_root: Hash256,
     ) -> Result<(), NotSafe> {
-        let validator_id = Self::get_validator_id(txn, validator_pubkey)?;
+        let validator_id = self.get_validator_id(txn, validator_pubkey)?;
 
         txn.execute(
             "INSERT INTO signed_blocks (validator_id, slot, signing_root)
              VALUES (?1, ?2, ?3)",
-            params![
-                validator_id,
-                block_header.slot,
-                block_header.signing_root(domain).as_bytes()
-            ],
+            params![validator_id, slot, signing_root.as_bytes()],
         )?;
         Ok(())
     }
@@ -336,19 +618,20 @@ impl SlashingDatabase {
         &self,
         txn: &Transaction,
         validator_pubkey: &PublicKey,
-        attestation: &AttestationData,
-        domain: Hash256,
+        att_source_epoch: Epoch,
+        att_target_epoch: Epoch,
+        att_signing_root: Hash256,
     ) -> Result<(), NotSafe> {
-        let validator_id = Self::get_validator_id(txn, validator_pubkey)?;
+        let validator_id = self.get_validator_id(txn, validator_pubkey)?;
 
         txn.execute(
             "INSERT INTO signed_attestations (validator_id, source_epoch, target_epoch, signing_root)
              VALUES (?1, ?2, ?3, ?4)",
             params![
                 validator_id,
-                attestation.source.epoch,
-                attestation.target.epoch,
-                attestation.signing_root(domain).as_bytes()
+                att_source_epoch,
+                att_target_epoch,
+                att_signing_root.as_bytes()
             ],
         )?;
         Ok(())
@@ -360,22 +643,54 @@ impl SlashingDatabase {
     /// to prevent concurrent checks and inserts from resulting in slashable data being inserted.
     ///
     /// This is the safe, externally-callable interface for checking block proposals.
+    ///
+    /// Re-signing a block identical to one already recorded (i.e., with the same slot and signing
+    /// root) is safe, and returns `Safe::SameData` without modifying the database.
     pub fn check_and_insert_block_proposal(
         &self,
         validator_pubkey: &PublicKey,
         block_header: &BeaconBlockHeader,
         domain: Hash256,
     ) -> Result<Safe, NotSafe> {
+        self.check_and_insert_block_signing_root(
+            validator_pubkey,
+            block_header.slot,
+            block_header.signing_root(domain),
+        )
+    }
+
+    /// As for `check_and_insert_block_proposal` but without requiring the whole `BeaconBlockHeader`.
+    pub fn check_and_insert_block_signing_root(
+        &self,
+        validator_pubkey: &PublicKey,
+        slot: Slot,
+        signing_root: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        let _timer = metrics::start_timer(&metrics::SLASHING_PROTECTION_BLOCK_CHECK_TIMES);
+
         let mut conn = self.conn_pool.get()?;
         let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+        let safe = self
+            .check_and_insert_block_signing_root_txn(validator_pubkey, slot, signing_root, &txn)
+            .map_err(metrics::observe_not_safe)?;
+        txn.commit()?;
+        Ok(safe)
+    }
 
-        let safe = self.check_block_proposal(&txn, validator_pubkey, block_header, domain)?;
+    /// Transactional variant of `check_and_insert_block_signing_root`.
+    fn check_and_insert_block_signing_root_txn(
+        &self,
+        validator_pubkey: &PublicKey,
+        slot: Slot,
+        signing_root: Hash256,
+        txn: &Transaction,
+    ) -> Result<Safe, NotSafe> {
+        let safe = self.check_block_proposal(txn, validator_pubkey, slot, signing_root)?;
 
         if safe != Safe::SameData {
-            self.insert_block_proposal(&txn, validator_pubkey, block_header, domain)?;
+            self.insert_block_proposal(txn, validator_pubkey, slot, signing_root)?;
         }
 
-        txn.commit()?;
         Ok(safe)
     }
 
@@ -385,24 +700,863 @@ impl SlashingDatabase {
     /// to prevent concurrent checks and inserts from resulting in slashable data being inserted.
     ///
     /// This is the safe, externally-callable interface for checking attestations.
+    ///
+    /// Re-signing an attestation identical to one already recorded (i.e., with the same epochs and
+    /// signing root) is safe, and returns `Safe::SameData` without modifying the database.
     pub fn check_and_insert_attestation(
         &self,
         validator_pubkey: &PublicKey,
         attestation: &AttestationData,
         domain: Hash256,
     ) -> Result<Safe, NotSafe> {
+        self.check_and_insert_attestation_signing_root(
+            validator_pubkey,
+            attestation.source.epoch,
+            attestation.target.epoch,
+            attestation.signing_root(domain),
+        )
+    }
+
+    /// As for `check_and_insert_attestation` but without requiring the whole `AttestationData`.
+    pub fn check_and_insert_attestation_signing_root(
+        &self,
+        validator_pubkey: &PublicKey,
+        att_source_epoch: Epoch,
+        att_target_epoch: Epoch,
+        att_signing_root: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        let _timer = metrics::start_timer(&metrics::SLASHING_PROTECTION_ATTESTATION_CHECK_TIMES);
+
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+        let safe = self
+            .check_and_insert_attestation_signing_root_txn(
+                validator_pubkey,
+                att_source_epoch,
+                att_target_epoch,
+                att_signing_root,
+                &txn,
+            )
+            .map_err(metrics::observe_not_safe)?;
+        txn.commit()?;
+        Ok(safe)
+    }
+
+    /// Check and record many attestations, for any number of validators, in a single transaction.
+    ///
+    /// Each attestation is checked as if by `check_and_insert_attestation`, including against
+    /// the attestations preceding it in `attestations`, and its result is returned in the
+    /// corresponding position of the output. An attestation which is not safe to sign is not
+    /// recorded, but doesn't prevent the others from being recorded.
+    ///
+    /// Avoids the overhead of a transaction per attestation, which dominates the cost of checking
+    /// when signing for many validators at once. An error is only returned for the batch as a
+    /// whole if the transaction can't be opened or committed, in which case nothing is recorded.
+    pub fn check_and_insert_attestation_batch<'a>(
+        &self,
+        attestations: impl IntoIterator<Item = (&'a PublicKey, &'a AttestationData, Hash256)>,
+    ) -> Result<Vec<Result<Safe, NotSafe>>, NotSafe> {
         let mut conn = self.conn_pool.get()?;
         let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
 
-        let safe = self.check_attestation(&txn, validator_pubkey, attestation, domain)?;
+        let results = attestations
+            .into_iter()
+            .map(|(validator_pubkey, attestation, domain)| {
+                self.check_and_insert_attestation_signing_root_txn(
+                    validator_pubkey,
+                    attestation.source.epoch,
+                    attestation.target.epoch,
+                    attestation.signing_root(domain),
+                    &txn,
+                )
+                .map_err(metrics::observe_not_safe)
+            })
+            .collect();
+
+        txn.commit()?;
+        Ok(results)
+    }
+
+    /// Transactional variant of `check_and_insert_attestation_signing_root`.
+    fn check_and_insert_attestation_signing_root_txn(
+        &self,
+        validator_pubkey: &PublicKey,
+        att_source_epoch: Epoch,
+        att_target_epoch: Epoch,
+        att_signing_root: Hash256,
+        txn: &Transaction,
+    ) -> Result<Safe, NotSafe> {
+        let safe = self.check_attestation(
+            txn,
+            validator_pubkey,
+            att_source_epoch,
+            att_target_epoch,
+            att_signing_root,
+        )?;
 
         if safe != Safe::SameData {
-            self.insert_attestation(&txn, validator_pubkey, attestation, domain)?;
+            self.insert_attestation(
+                txn,
+                validator_pubkey,
+                att_source_epoch,
+                att_target_epoch,
+                att_signing_root,
+            )?;
         }
 
-        txn.commit()?;
         Ok(safe)
     }
+
+    /// Import slashing protection from another client in the interchange format.
+    ///
+    /// Records with a known signing root are checked for slash safety against the existing
+    /// contents of the database before being inserted. Records already in the database or covered
+    /// by the validator's lower bound are skipped. Records without a signing root additionally
+    /// raise the validator's lower bound, so that nothing conflicting with them can be signed in
+    /// future.
+    ///
+    /// Each validator is imported atomically: if any of its records is slashable then none of its
+    /// records are imported, and it is listed as rejected in the returned report. The records of
+    /// other validators are unaffected. Any other error aborts the import, leaving the database
+    /// unchanged.
+    ///
+    /// The database is checked to protect the network with `genesis_validators_root`, as for
+    /// `check_genesis_validators_root`.
+    pub fn import_interchange_info(
+        &self,
+        interchange: &Interchange,
+        genesis_validators_root: Hash256,
+    ) -> Result<ImportReport, InterchangeError> {
+        let version = interchange.metadata.interchange_format_version;
+        if version != SUPPORTED_INTERCHANGE_FORMAT_VERSION {
+            return Err(InterchangeError::UnsupportedVersion(version));
+        }
+
+        if genesis_validators_root != interchange.metadata.genesis_validators_root {
+            return Err(InterchangeError::GenesisValidatorsMismatch {
+                client: genesis_validators_root,
+                interchange_file: interchange.metadata.genesis_validators_root,
+            });
+        }
+
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+        self.check_genesis_validators_root_txn(&txn, genesis_validators_root)?;
+        let mut report = ImportReport::default();
+
+        for record in &interchange.data {
+            txn.execute_batch("SAVEPOINT import_record")?;
+
+            match self.import_interchange_record(record, &txn) {
+                Ok(imported) => {
+                    txn.execute_batch("RELEASE import_record")?;
+                    report.imported.push(imported);
+                }
+                Err(InterchangeError::NotSafe(reason @ NotSafe::InvalidBlock(_)))
+                | Err(InterchangeError::NotSafe(reason @ NotSafe::InvalidAttestation(_))) => {
+                    txn.execute_batch("ROLLBACK TO import_record; RELEASE import_record")?;
+                    report.rejected.push(RejectedValidator {
+                        pubkey: record.pubkey.clone(),
+                        reason,
+                    });
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        txn.commit()?;
+        Ok(report)
+    }
+
+    /// Import the records for a single validator, within the given transaction.
+    fn import_interchange_record(
+        &self,
+        record: &InterchangeData,
+        txn: &Transaction,
+    ) -> Result<ImportedValidator, InterchangeError> {
+        self.register_validators_in_txn(std::iter::once(&record.pubkey), txn)?;
+        let validator_id = self.get_validator_id(txn, &record.pubkey)?;
+
+        let mut imported = ImportedValidator {
+            pubkey: record.pubkey.clone(),
+            num_blocks: 0,
+            num_attestations: 0,
+            num_skipped: 0,
+        };
+        let mut new_bound = LowerBound::default();
+
+        for block in &record.signed_blocks {
+            let result = self.check_and_insert_block_signing_root_txn(
+                &record.pubkey,
+                block.slot,
+                block.signing_root.unwrap_or_else(Hash256::zero),
+                txn,
+            );
+            match result {
+                Ok(Safe::Valid) => imported.num_blocks += 1,
+                Ok(Safe::SameData) => imported.num_skipped += 1,
+                // Records already covered by the lower bound don't need to be stored.
+                Err(NotSafe::InvalidBlock(InvalidBlock::SlotViolatesLowerBound { .. })) => {
+                    imported.num_skipped += 1
+                }
+                // Records without a signing root are enforced by the lower bound instead.
+                Err(NotSafe::InvalidBlock(_)) if block.signing_root.is_none() => {
+                    imported.num_skipped += 1
+                }
+                Err(e) => return Err(e.into()),
+            }
+
+            if block.signing_root.is_none() {
+                new_bound = new_bound.merge(LowerBound {
+                    block_proposal_slot: Some(block.slot),
+                    ..LowerBound::default()
+                });
+            }
+        }
+
+        for attestation in &record.signed_attestations {
+            let result = self.check_and_insert_attestation_signing_root_txn(
+                &record.pubkey,
+                attestation.source_epoch,
+                attestation.target_epoch,
+                attestation.signing_root.unwrap_or_else(Hash256::zero),
+                txn,
+            );
+            match result {
+                Ok(Safe::Valid) => imported.num_attestations += 1,
+                Ok(Safe::SameData) => imported.num_skipped += 1,
+                Err(NotSafe::InvalidAttestation(InvalidAttestation::SourceExceedsTarget)) => {
+                    return Err(NotSafe::InvalidAttestation(
+                        InvalidAttestation::SourceExceedsTarget,
+                    )
+                    .into())
+                }
+                // Records already covered by the lower bound don't need to be stored.
+                Err(NotSafe::InvalidAttestation(
+                    InvalidAttestation::SourceLessThanLowerBound { .. },
+                ))
+                | Err(NotSafe::InvalidAttestation(
+                    InvalidAttestation::TargetLessThanOrEqLowerBound { .. },
+                )) => imported.num_skipped += 1,
+                // Records without a signing root are enforced by the lower bound instead.
+                Err(NotSafe::InvalidAttestation(_)) if attestation.signing_root.is_none() => {
+                    imported.num_skipped += 1
+                }
+                Err(e) => return Err(e.into()),
+            }
+
+            if attestation.signing_root.is_none() {
+                new_bound = new_bound.merge(LowerBound {
+                    attestation_source_epoch: Some(attestation.source_epoch),
+                    attestation_target_epoch: Some(attestation.target_epoch),
+                    ..LowerBound::default()
+                });
+            }
+        }
+
+        if new_bound != LowerBound::default() {
+            self.update_lower_bound(txn, validator_id, new_bound)?;
+        }
+
+        Ok(imported)
+    }
+
+    /// Export the contents of the database in the interchange format.
+    ///
+    /// If `selected_pubkeys` is `Some`, only the records for those validators are exported,
+    /// otherwise the records for every registered validator are exported. It is an error to
+    /// select a validator that is not registered.
+    ///
+    /// Signing roots which are unknown (stored as zero) are omitted from the output. Each
+    /// validator's lower bound is exported as a block and attestation without signing roots, so
+    /// that a database populated from the export refuses the same messages.
+    pub fn export_interchange_info(
+        &self,
+        genesis_validators_root: Hash256,
+        selected_pubkeys: Option<&[PublicKey]>,
+    ) -> Result<Interchange, InterchangeError> {
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction()?;
+
+        let data = self
+            .selected_validators(&txn, selected_pubkeys)?
+            .into_iter()
+            .map(|(validator_id, pubkey)| {
+                let mut signed_blocks = txn
+                    .prepare(
+                        "SELECT slot, signing_root
+                         FROM signed_blocks
+                         WHERE validator_id = ?1
+                         ORDER BY slot ASC",
+                    )?
+                    .query_map(params![validator_id], SignedBlock::from_row)?
+                    .map(|result| {
+                        result.map(|block| InterchangeBlock {
+                            slot: block.slot,
+                            signing_root: known_signing_root(block.signing_root),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let mut signed_attestations = txn
+                    .prepare(
+                        "SELECT source_epoch, target_epoch, signing_root
+                         FROM signed_attestations
+                         WHERE validator_id = ?1
+                         ORDER BY target_epoch ASC",
+                    )?
+                    .query_map(params![validator_id], SignedAttestation::from_row)?
+                    .map(|result| {
+                        result.map(|att| InterchangeAttestation {
+                            source_epoch: att.source_epoch,
+                            target_epoch: att.target_epoch,
+                            signing_root: known_signing_root(att.signing_root),
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                // Represent the lower bound as records without signing roots, which raise the
+                // lower bound to the same values when imported. They're listed last so that they
+                // don't cause records with known signing roots at the bound to be refused.
+                let lower_bound = self.get_lower_bound(&txn, validator_id)?;
+
+                if let Some(slot) = lower_bound.block_proposal_slot {
+                    let covered = signed_blocks
+                        .iter()
+                        .any(|block| block.signing_root.is_none() && block.slot >= slot);
+                    if !covered {
+                        signed_blocks.push(InterchangeBlock {
+                            slot,
+                            signing_root: None,
+                        });
+                    }
+                }
+
+                if let (Some(source_epoch), Some(target_epoch)) = (
+                    lower_bound.attestation_source_epoch,
+                    lower_bound.attestation_target_epoch,
+                ) {
+                    let covered = signed_attestations.iter().any(|att| {
+                        att.signing_root.is_none()
+                            && att.source_epoch >= source_epoch
+                            && att.target_epoch >= target_epoch
+                    });
+                    if !covered {
+                        signed_attestations.push(InterchangeAttestation {
+                            source_epoch,
+                            target_epoch,
+                            signing_root: None,
+                        });
+                    }
+                }
+
+                Ok(InterchangeData {
+                    pubkey,
+                    signed_blocks,
+                    signed_attestations,
+                })
+            })
+            .collect::<Result<Vec<_>, InterchangeError>>()?;
+
+        let metadata = InterchangeMetadata {
+            interchange_format_version: SUPPORTED_INTERCHANGE_FORMAT_VERSION,
+            genesis_validators_root,
+        };
+
+        Ok(Interchange { metadata, data })
+    }
+
+    /// Delete the blocks signed before `prune_slot` and the attestations with target epochs
+    /// before `prune_epoch`.
+    ///
+    /// The lower bound of each validator is raised to cover the deleted records, so that pruning
+    /// never makes it possible to sign a message that would previously have been refused.
+    ///
+    /// Validators are selected as for `export_interchange_info`.
+    pub fn prune(
+        &self,
+        selected_pubkeys: Option<&[PublicKey]>,
+        prune_slot: Slot,
+        prune_epoch: Epoch,
+    ) -> Result<(), InterchangeError> {
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+
+        for (validator_id, _) in self.selected_validators(&txn, selected_pubkeys)? {
+            let block_proposal_slot = txn.query_row(
+                "SELECT MAX(slot) FROM signed_blocks WHERE validator_id = ?1 AND slot < ?2",
+                params![validator_id, prune_slot],
+                |row| row.get(0),
+            )?;
+            let (attestation_source_epoch, attestation_target_epoch) = txn.query_row(
+                "SELECT MAX(source_epoch), MAX(target_epoch)
+                 FROM signed_attestations
+                 WHERE validator_id = ?1 AND target_epoch < ?2",
+                params![validator_id, prune_epoch],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?;
+
+            self.update_lower_bound(
+                &txn,
+                validator_id,
+                LowerBound {
+                    block_proposal_slot,
+                    attestation_source_epoch,
+                    attestation_target_epoch,
+                },
+            )?;
+
+            txn.execute(
+                "DELETE FROM signed_blocks WHERE validator_id = ?1 AND slot < ?2",
+                params![validator_id, prune_slot],
+            )?;
+            txn.execute(
+                "DELETE FROM signed_attestations WHERE validator_id = ?1 AND target_epoch < ?2",
+                params![validator_id, prune_epoch],
+            )?;
+        }
+
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Summarise the signing history of each validator.
+    ///
+    /// Validators are selected as for `export_interchange_info`.
+    pub fn validator_summaries(
+        &self,
+        selected_pubkeys: Option<&[PublicKey]>,
+    ) -> Result<Vec<ValidatorSummary>, InterchangeError> {
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction()?;
+
+        self.selected_validators(&txn, selected_pubkeys)?
+            .into_iter()
+            .map(|(validator_id, pubkey)| {
+                let (num_signed_blocks, max_block_slot) = txn.query_row(
+                    "SELECT COUNT(*), MAX(slot) FROM signed_blocks WHERE validator_id = ?1",
+                    params![validator_id],
+                    |row| Ok((row.get::<_, i64>(0)? as u64, row.get(1)?)),
+                )?;
+                let (num_signed_attestations, max_source_epoch, max_target_epoch) = txn.query_row(
+                    "SELECT COUNT(*), MAX(source_epoch), MAX(target_epoch)
+                         FROM signed_attestations
+                         WHERE validator_id = ?1",
+                    params![validator_id],
+                    |row| Ok((row.get::<_, i64>(0)? as u64, row.get(1)?, row.get(2)?)),
+                )?;
+
+                Ok(ValidatorSummary {
+                    pubkey,
+                    num_signed_blocks,
+                    max_block_slot,
+                    num_signed_attestations,
+                    max_source_epoch,
+                    max_target_epoch,
+                    lower_bound: self.get_lower_bound(&txn, validator_id)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Run SQLite's integrity check over the database file.
+    pub fn integrity_check(&self) -> Result<(), NotSafe> {
+        let conn = self.conn_pool.get()?;
+        let problems = conn
+            .prepare("PRAGMA integrity_check")?
+            .query_map(params![], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if problems.len() == 1 && problems[0] == "ok" {
+            Ok(())
+        } else {
+            Err(NotSafe::SQLError(format!(
+                "Integrity check failed: {}",
+                problems.join(", ")
+            )))
+        }
+    }
+
+    /// Scan the database for records which are inconsistent with one another.
+    ///
+    /// A database written only by this software should never contain an inconsistency, but one
+    /// restored from a backup or modified by hand might. An inconsistent database may not protect
+    /// its validators from being slashed, so it should be repaired or replaced before use.
+    pub fn validate(&self) -> Result<Vec<Inconsistency>, InterchangeError> {
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction()?;
+        let mut inconsistencies = vec![];
+
+        for table in &["signed_blocks", "signed_attestations", "lower_bounds"] {
+            let orphans = txn
+                .prepare(&format!(
+                    "SELECT validator_id, COUNT(*)
+                     FROM {}
+                     WHERE validator_id IS NULL
+                        OR validator_id NOT IN (SELECT id FROM validators)
+                     GROUP BY validator_id",
+                    table
+                ))?
+                .query_map(params![], |row| {
+                    Ok(Inconsistency::OrphanedRecords {
+                        table: *table,
+                        validator_id: row.get(0)?,
+                        count: row.get::<_, i64>(1)? as u64,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            inconsistencies.extend(orphans);
+        }
+
+        let duplicates = txn
+            .prepare("SELECT public_key FROM validators GROUP BY public_key HAVING COUNT(*) > 1")?
+            .query_and_then(params![], |row| {
+                let pubkey_str: String = row.get(0)?;
+                Ok(Inconsistency::DuplicateValidator {
+                    pubkey: pubkey_from_hex_str(&pubkey_str)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, InterchangeError>>()?;
+        inconsistencies.extend(duplicates);
+
+        for (validator_id, pubkey) in self.selected_validators(&txn, None)? {
+            let source_exceeds_target = txn
+                .prepare(
+                    "SELECT source_epoch, target_epoch, signing_root
+                     FROM signed_attestations
+                     WHERE validator_id = ?1 AND source_epoch > target_epoch
+                     ORDER BY target_epoch ASC",
+                )?
+                .query_map(params![validator_id], SignedAttestation::from_row)?
+                .map(|result| {
+                    result.map(|attestation| Inconsistency::SourceExceedsTarget {
+                        pubkey: pubkey.clone(),
+                        attestation,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            inconsistencies.extend(source_exceeds_target);
+
+            let surrounding_votes = txn
+                .prepare(
+                    "SELECT a.source_epoch, a.target_epoch, a.signing_root,
+                            b.source_epoch, b.target_epoch, b.signing_root
+                     FROM signed_attestations a, signed_attestations b
+                     WHERE a.validator_id = ?1
+                       AND b.validator_id = ?1
+                       AND a.source_epoch < b.source_epoch
+                       AND b.target_epoch < a.target_epoch
+                     ORDER BY a.target_epoch ASC, b.target_epoch ASC",
+                )?
+                .query_map(params![validator_id], |row| {
+                    let surrounding =
+                        SignedAttestation::new(row.get(0)?, row.get(1)?, hash256_from_row(2, row)?);
+                    let surrounded =
+                        SignedAttestation::new(row.get(3)?, row.get(4)?, hash256_from_row(5, row)?);
+                    Ok(Inconsistency::SurroundingVote {
+                        pubkey: pubkey.clone(),
+                        surrounding,
+                        surrounded,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            inconsistencies.extend(surrounding_votes);
+
+            let lower_bound = self.get_lower_bound(&txn, validator_id)?;
+
+            if let (Some(source), Some(target)) = (
+                lower_bound.attestation_source_epoch,
+                lower_bound.attestation_target_epoch,
+            ) {
+                if source > target {
+                    inconsistencies.push(Inconsistency::InvalidLowerBound {
+                        pubkey: pubkey.clone(),
+                        lower_bound,
+                    });
+                }
+            }
+
+            // Records with unknown signing roots are only stored alongside a lower bound which
+            // covers them, so one which isn't covered indicates that the lower bound regressed.
+            let uncovered_blocks = txn
+                .prepare(
+                    "SELECT slot, signing_root
+                     FROM signed_blocks
+                     WHERE validator_id = ?1 AND signing_root = ?2
+                     ORDER BY slot ASC",
+                )?
+                .query_map(
+                    params![validator_id, Hash256::zero().as_bytes()],
+                    SignedBlock::from_row,
+                )?
+                .filter(|result| {
+                    result.as_ref().map_or(true, |block| {
+                        lower_bound
+                            .block_proposal_slot
+                            .map_or(true, |bound| block.slot > bound)
+                    })
+                })
+                .map(|result| {
+                    result.map(|block| Inconsistency::BlockAboveLowerBound {
+                        pubkey: pubkey.clone(),
+                        block,
+                        lower_bound,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            inconsistencies.extend(uncovered_blocks);
+
+            let uncovered_attestations = txn
+                .prepare(
+                    "SELECT source_epoch, target_epoch, signing_root
+                     FROM signed_attestations
+                     WHERE validator_id = ?1 AND signing_root = ?2
+                     ORDER BY target_epoch ASC",
+                )?
+                .query_map(
+                    params![validator_id, Hash256::zero().as_bytes()],
+                    SignedAttestation::from_row,
+                )?
+                .filter(|result| {
+                    result.as_ref().map_or(true, |attestation| {
+                        lower_bound
+                            .attestation_source_epoch
+                            .map_or(true, |bound| attestation.source_epoch > bound)
+                            || lower_bound
+                                .attestation_target_epoch
+                                .map_or(true, |bound| attestation.target_epoch > bound)
+                    })
+                })
+                .map(|result| {
+                    result.map(|attestation| Inconsistency::AttestationAboveLowerBound {
+                        pubkey: pubkey.clone(),
+                        attestation,
+                        lower_bound,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            inconsistencies.extend(uncovered_attestations);
+        }
+
+        Ok(inconsistencies)
+    }
+
+    /// Get the IDs and public keys of the selected validators, or all validators if
+    /// `selected_pubkeys` is `None`.
+    ///
+    /// Error if any of the selected validators is not registered.
+    fn selected_validators(
+        &self,
+        txn: &Transaction,
+        selected_pubkeys: Option<&[PublicKey]>,
+    ) -> Result<Vec<(i64, PublicKey)>, InterchangeError> {
+        if let Some(pubkeys) = selected_pubkeys {
+            pubkeys
+                .iter()
+                .map(|pubkey| Ok((self.get_validator_id(txn, pubkey)?, pubkey.clone())))
+                .collect()
+        } else {
+            txn.prepare("SELECT id, public_key FROM validators ORDER BY id ASC")?
+                .query_and_then(params![], |row| {
+                    let validator_id: i64 = row.get(0)?;
+                    let pubkey_str: String = row.get(1)?;
+                    Ok((validator_id, pubkey_from_hex_str(&pubkey_str)?))
+                })?
+                .collect()
+        }
+    }
+}
+
+impl SlashingProtection for SlashingDatabase {
+    fn register_validators(&self, public_keys: &[PublicKey]) -> Result<(), NotSafe> {
+        SlashingDatabase::register_validators(self, public_keys.iter())
+    }
+
+    fn check_and_insert_block_proposal(
+        &self,
+        validator_pubkey: &PublicKey,
+        block_header: &BeaconBlockHeader,
+        domain: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        SlashingDatabase::check_and_insert_block_proposal(
+            self,
+            validator_pubkey,
+            block_header,
+            domain,
+        )
+    }
+
+    fn check_and_insert_attestation(
+        &self,
+        validator_pubkey: &PublicKey,
+        attestation: &AttestationData,
+        domain: Hash256,
+    ) -> Result<Safe, NotSafe> {
+        SlashingDatabase::check_and_insert_attestation(self, validator_pubkey, attestation, domain)
+    }
+
+    fn scrape_for_metrics(&self) {
+        metrics::scrape_for_metrics(self)
+    }
+
+    fn export_interchange_info(
+        &self,
+        genesis_validators_root: Hash256,
+        selected_pubkeys: Option<&[PublicKey]>,
+    ) -> Result<Interchange, InterchangeError> {
+        SlashingDatabase::export_interchange_info(self, genesis_validators_root, selected_pubkeys)
+    }
+
+    fn import_interchange_info(
+        &self,
+        interchange: &Interchange,
+        genesis_validators_root: Hash256,
+    ) -> Result<ImportReport, InterchangeError> {
+        SlashingDatabase::import_interchange_info(self, interchange, genesis_validators_root)
+    }
+}
+
+/// The outcome of `SlashingDatabase::import_interchange_info`.
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportReport {
+    /// Validators whose records were imported.
+    pub imported: Vec<ImportedValidator>,
+    /// Validators whose records were not imported because they are slashable.
+    pub rejected: Vec<RejectedValidator>,
+}
+
+impl ImportReport {
+    /// Returns `true` if no validators were rejected.
+    pub fn is_success(&self) -> bool {
+        self.rejected.is_empty()
+    }
+}
+
+/// A validator whose records were imported.
+#[derive(Debug, PartialEq)]
+pub struct ImportedValidator {
+    pub pubkey: PublicKey,
+    /// The number of blocks inserted into the database.
+    pub num_blocks: usize,
+    /// The number of attestations inserted into the database.
+    pub num_attestations: usize,
+    /// The number of records not inserted because they were already present in the database or
+    /// are covered by the validator's lower bound.
+    pub num_skipped: usize,
+}
+
+/// A validator whose records were not imported.
+#[derive(Debug, PartialEq)]
+pub struct RejectedValidator {
+    pub pubkey: PublicKey,
+    /// The reason the first offending record could not be imported.
+    pub reason: NotSafe,
+}
+
+/// A summary of the signing history of a single validator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidatorSummary {
+    pub pubkey: PublicKey,
+    pub num_signed_blocks: u64,
+    pub max_block_slot: Option<Slot>,
+    pub num_signed_attestations: u64,
+    pub max_source_epoch: Option<Epoch>,
+    pub max_target_epoch: Option<Epoch>,
+    pub lower_bound: LowerBound,
+}
+
+/// An inconsistency found by `SlashingDatabase::validate`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inconsistency {
+    /// Records referring to a validator ID which is not in the `validators` table.
+    OrphanedRecords {
+        table: &'static str,
+        validator_id: Option<i64>,
+        count: u64,
+    },
+    /// The same public key is registered under more than one validator ID.
+    DuplicateValidator { pubkey: PublicKey },
+    /// An attestation has a source epoch greater than its target epoch.
+    SourceExceedsTarget {
+        pubkey: PublicKey,
+        attestation: SignedAttestation,
+    },
+    /// One attestation surrounds another signed by the same validator.
+    SurroundingVote {
+        pubkey: PublicKey,
+        surrounding: SignedAttestation,
+        surrounded: SignedAttestation,
+    },
+    /// A lower bound has a source epoch greater than its target epoch.
+    InvalidLowerBound {
+        pubkey: PublicKey,
+        lower_bound: LowerBound,
+    },
+    /// A block with an unknown signing root is not covered by the validator's lower bound.
+    BlockAboveLowerBound {
+        pubkey: PublicKey,
+        block: SignedBlock,
+        lower_bound: LowerBound,
+    },
+    /// An attestation with an unknown signing root is not covered by the validator's lower bound.
+    AttestationAboveLowerBound {
+        pubkey: PublicKey,
+        attestation: SignedAttestation,
+        lower_bound: LowerBound,
+    },
+}
+
+/// Map the all-zeros signing root used to represent an unknown signing root to `None`.
+fn known_signing_root(signing_root: Hash256) -> Option<Hash256> {
+    Some(signing_root).filter(|root| !root.is_zero())
+}
+
+/// Parse a public key from the `0x`-prefixed hex string stored in the `validators` table.
+fn pubkey_from_hex_str(pubkey_str: &str) -> Result<PublicKey, InterchangeError> {
+    pubkey_str
+        .strip_prefix("0x")
+        .ok_or_else(|| InterchangeError::InvalidPubkey(pubkey_str.to_string()))
+        .and_then(|hex_str| {
+            hex::decode(hex_str).map_err(|e| InterchangeError::InvalidPubkey(format!("{:?}", e)))
+        })
+        .and_then(|bytes| {
+            PublicKey::deserialize(&bytes)
+                .map_err(|e| InterchangeError::InvalidPubkey(format!("{:?}", e)))
+        })
+}
+
+/// Errors that may arise while importing or exporting interchange data.
+#[derive(Debug)]
+pub enum InterchangeError {
+    UnsupportedVersion(u64),
+    GenesisValidatorsMismatch {
+        interchange_file: Hash256,
+        client: Hash256,
+    },
+    InvalidPubkey(String),
+    SQLError(String),
+    SQLPoolError(String),
+    NotSafe(NotSafe),
+    /// The source of slashing protection does not support the interchange format.
+    Unsupported,
+}
+
+impl From<NotSafe> for InterchangeError {
+    fn from(error: NotSafe) -> Self {
+        InterchangeError::NotSafe(error)
+    }
+}
+
+impl From<rusqlite::Error> for InterchangeError {
+    fn from(error: rusqlite::Error) -> Self {
+        InterchangeError::SQLError(error.to_string())
+    }
+}
+
+impl From<r2d2::Error> for InterchangeError {
+    fn from(error: r2d2::Error) -> Self {
+        InterchangeError::SQLPoolError(format!("{:?}", error))
+    }
 }
 
 #[cfg(test)]
@@ -418,15 +1572,76 @@ mod tests {
         assert!(SlashingDatabase::open(&file).is_err());
     }
 
-    // Due to the exclusive locking, trying to use an already open database should error.
+    // Due to the writer lock, trying to open an already open database for writing should error.
     #[test]
     fn double_open_error() {
         let dir = tempdir().unwrap();
         let file = dir.path().join("db.sqlite");
         let _db1 = SlashingDatabase::create(&file).unwrap();
 
-        let db2 = SlashingDatabase::open(&file).unwrap();
-        db2.register_validator(&pubkey(0)).unwrap_err();
+        assert!(matches!(
+            SlashingDatabase::open(&file),
+            Err(NotSafe::DatabaseLocked(_))
+        ));
+    }
+
+    // A read-only handle can be opened alongside the writer, and sees its writes.
+    #[test]
+    fn open_read_only_while_open() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("db.sqlite");
+        let db1 = SlashingDatabase::create(&file).unwrap();
+        db1.register_validator(&pubkey(0)).unwrap();
+
+        let db2 = SlashingDatabase::open_read_only(&file).unwrap();
+        assert!(is_registered(&db2, &pubkey(0)));
+        db2.register_validator(&pubkey(1)).unwrap_err();
+    }
+
+    fn is_registered(db: &SlashingDatabase, public_key: &PublicKey) -> bool {
+        let mut conn = db.conn_pool.get().unwrap();
+        let txn = conn.transaction().unwrap();
+        db.get_validator_id_opt(&txn, public_key).unwrap().is_some()
+    }
+
+    // The writer lock is held alongside the database until the last clone is dropped.
+    #[test]
+    fn writer_lock_released_by_last_clone() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("db.sqlite");
+        let db1 = SlashingDatabase::create(&file).unwrap();
+        let db2 = db1.clone();
+        assert!(WriterLock::path(&file).exists());
+
+        drop(db1);
+        assert!(matches!(
+            SlashingDatabase::open(&file),
+            Err(NotSafe::DatabaseLocked(_))
+        ));
+
+        drop(db2);
+        SlashingDatabase::open(&file).unwrap();
+    }
+
+    // A reader is not blocked by a write in progress, and only sees committed writes.
+    #[test]
+    fn read_during_write_transaction() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("db.sqlite");
+        let db1 = SlashingDatabase::create(&file).unwrap();
+        db1.register_validator(&pubkey(0)).unwrap();
+        let db2 = SlashingDatabase::open_read_only(&file).unwrap();
+
+        let mut conn = db1.conn_pool.get().unwrap();
+        let txn = conn
+            .transaction_with_behavior(TransactionBehavior::Exclusive)
+            .unwrap();
+        db1.register_validators_in_txn(std::iter::once(&pubkey(1)), &txn)
+            .unwrap();
+        assert!(!is_registered(&db2, &pubkey(1)));
+
+        txn.commit().unwrap();
+        assert!(is_registered(&db2, &pubkey(1)));
     }
 
     // Attempting to create the same database twice should error.
@@ -455,10 +1670,10 @@ mod tests {
                 true
             );
             assert_eq!(
-                conn.pragma_query_value(None, "locking_mode", |row| { row.get::<_, String>(0) })
+                conn.pragma_query_value(None, "journal_mode", |row| { row.get::<_, String>(0) })
                     .unwrap()
                     .to_uppercase(),
-                "EXCLUSIVE"
+                "WAL"
             );
         };
 
@@ -91,6 +91,37 @@ impl StreamTest<AttestationData> {
                 i
             );
         }
+
+        self.run_batch();
+    }
+
+    /// Check that processing all the cases in a single batch gives the same results as
+    /// processing them one at a time.
+    fn run_batch(&self) {
+        let dir = tempdir().unwrap();
+        let slashing_db_file = dir.path().join("slashing_protection.sqlite");
+        let slashing_db = SlashingDatabase::create(&slashing_db_file).unwrap();
+
+        for pubkey in &self.registered_validators {
+            slashing_db.register_validator(pubkey).unwrap();
+        }
+
+        let results = slashing_db
+            .check_and_insert_attestation_batch(
+                self.cases
+                    .iter()
+                    .map(|test| (&test.pubkey, &test.data, test.domain)),
+            )
+            .unwrap();
+
+        assert_eq!(results.len(), self.cases.len());
+        for (i, (result, test)) in results.into_iter().zip(&self.cases).enumerate() {
+            assert_eq!(
+                result, test.expected,
+                "attestation {} not processed as expected in batch",
+                i
+            );
+        }
     }
 }
 
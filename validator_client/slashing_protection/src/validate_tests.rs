@@ -0,0 +1,178 @@
+#![cfg(test)]
+
+//! Tests for `SlashingDatabase::validate`, which writes inconsistent records directly to the
+//! database, as they can't be produced through its API.
+
+use crate::test_utils::pubkey;
+use crate::*;
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+use tempfile::{tempdir, TempDir};
+use types::{Epoch, Hash256, Slot};
+
+fn root(i: u64) -> Hash256 {
+    Hash256::from_low_u64_be(i)
+}
+
+fn new_db() -> (TempDir, PathBuf, SlashingDatabase) {
+    let dir = tempdir().unwrap();
+    let file = dir.path().join("db.sqlite");
+    let slashing_db = SlashingDatabase::create(&file).unwrap();
+    slashing_db.register_validator(&pubkey(0)).unwrap();
+    (dir, file, slashing_db)
+}
+
+fn insert_attestation(file: &Path, validator_id: i64, source: u64, target: u64, root: Hash256) {
+    Connection::open(file)
+        .unwrap()
+        .execute(
+            "INSERT INTO signed_attestations (validator_id, source_epoch, target_epoch, signing_root)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![validator_id, source as i64, target as i64, root.as_bytes()],
+        )
+        .unwrap();
+}
+
+#[test]
+fn consistent_database() {
+    let (_dir, _file, slashing_db) = new_db();
+    let pk = pubkey(0);
+
+    slashing_db
+        .check_and_insert_block_signing_root(&pk, Slot::new(10), root(1))
+        .unwrap();
+    slashing_db
+        .check_and_insert_attestation_signing_root(&pk, Epoch::new(1), Epoch::new(2), root(2))
+        .unwrap();
+    slashing_db
+        .check_and_insert_attestation_signing_root(&pk, Epoch::new(2), Epoch::new(3), root(3))
+        .unwrap();
+    slashing_db
+        .prune(None, Slot::new(16), Epoch::new(3))
+        .unwrap();
+
+    assert_eq!(slashing_db.validate().unwrap(), vec![]);
+}
+
+#[test]
+fn source_exceeds_target() {
+    let (_dir, file, slashing_db) = new_db();
+    insert_attestation(&file, 1, 3, 2, root(1));
+
+    assert_eq!(
+        slashing_db.validate().unwrap(),
+        vec![Inconsistency::SourceExceedsTarget {
+            pubkey: pubkey(0),
+            attestation: SignedAttestation::new(Epoch::new(3), Epoch::new(2), root(1)),
+        }]
+    );
+}
+
+#[test]
+fn surrounding_vote() {
+    let (_dir, file, slashing_db) = new_db();
+    slashing_db
+        .check_and_insert_attestation_signing_root(
+            &pubkey(0),
+            Epoch::new(2),
+            Epoch::new(3),
+            root(1),
+        )
+        .unwrap();
+    insert_attestation(&file, 1, 1, 4, root(2));
+
+    assert_eq!(
+        slashing_db.validate().unwrap(),
+        vec![Inconsistency::SurroundingVote {
+            pubkey: pubkey(0),
+            surrounding: SignedAttestation::new(Epoch::new(1), Epoch::new(4), root(2)),
+            surrounded: SignedAttestation::new(Epoch::new(2), Epoch::new(3), root(1)),
+        }]
+    );
+}
+
+#[test]
+fn orphaned_records_and_duplicate_validator() {
+    let (_dir, file, slashing_db) = new_db();
+    insert_attestation(&file, 7, 1, 2, root(1));
+    insert_attestation(&file, 7, 2, 3, root(2));
+    Connection::open(&file)
+        .unwrap()
+        .execute(
+            "INSERT INTO validators (public_key) VALUES (?1)",
+            params![pubkey(0).to_hex_string()],
+        )
+        .unwrap();
+
+    assert_eq!(
+        slashing_db.validate().unwrap(),
+        vec![
+            Inconsistency::OrphanedRecords {
+                table: "signed_attestations",
+                validator_id: Some(7),
+                count: 2,
+            },
+            Inconsistency::DuplicateValidator { pubkey: pubkey(0) },
+        ]
+    );
+}
+
+#[test]
+fn unknown_signing_roots_above_lower_bound() {
+    let (_dir, file, slashing_db) = new_db();
+    let pk = pubkey(0);
+
+    slashing_db
+        .check_and_insert_block_signing_root(&pk, Slot::new(10), Hash256::zero())
+        .unwrap();
+    insert_attestation(&file, 1, 1, 2, Hash256::zero());
+    Connection::open(&file)
+        .unwrap()
+        .execute(
+            "INSERT INTO lower_bounds
+             (validator_id, block_proposal_slot, attestation_source_epoch, attestation_target_epoch)
+             VALUES (1, 5, 1, 2)",
+            params![],
+        )
+        .unwrap();
+
+    let lower_bound = LowerBound {
+        block_proposal_slot: Some(Slot::new(5)),
+        attestation_source_epoch: Some(Epoch::new(1)),
+        attestation_target_epoch: Some(Epoch::new(2)),
+    };
+    assert_eq!(
+        slashing_db.validate().unwrap(),
+        vec![Inconsistency::BlockAboveLowerBound {
+            pubkey: pk,
+            block: SignedBlock::new(Slot::new(10), Hash256::zero()),
+            lower_bound,
+        }]
+    );
+}
+
+#[test]
+fn invalid_lower_bound() {
+    let (_dir, file, slashing_db) = new_db();
+    Connection::open(&file)
+        .unwrap()
+        .execute(
+            "INSERT INTO lower_bounds
+             (validator_id, block_proposal_slot, attestation_source_epoch, attestation_target_epoch)
+             VALUES (1, NULL, 4, 3)",
+            params![],
+        )
+        .unwrap();
+
+    assert_eq!(
+        slashing_db.validate().unwrap(),
+        vec![Inconsistency::InvalidLowerBound {
+            pubkey: pubkey(0),
+            lower_bound: LowerBound {
+                block_proposal_slot: None,
+                attestation_source_epoch: Some(Epoch::new(4)),
+                attestation_target_epoch: Some(Epoch::new(3)),
+            },
+        }]
+    );
+}
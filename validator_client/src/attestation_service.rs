@@ -1,11 +1,16 @@
 use crate::{
+    beacon_node_fallback::BeaconNodeFallback,
     duties_service::{DutiesService, DutyAndProof},
+    metrics,
+    postmortem::{publish_outcome, DutyKind, DutyTrace, Postmortems},
     validator_store::ValidatorStore,
 };
 use environment::RuntimeContext;
+use futures::channel::oneshot;
+use futures::future::join_all;
 use futures::StreamExt;
-use remote_beacon_node::{PublishStatus, RemoteBeaconNode};
-use slog::{crit, debug, error, info, trace};
+use remote_beacon_node::PublishStatus;
+use slog::{crit, debug, error, info, trace, warn};
 use slot_clock::SlotClock;
 use std::collections::HashMap;
 use std::ops::Deref;
@@ -18,8 +23,10 @@ pub struct AttestationServiceBuilder<T, E: EthSpec> {
     duties_service: Option<DutiesService<T, E>>,
     validator_store: Option<ValidatorStore<T, E>>,
     slot_clock: Option<T>,
-    beacon_node: Option<RemoteBeaconNode<E>>,
+    beacon_nodes: Option<Arc<BeaconNodeFallback<T, E>>>,
     context: Option<RuntimeContext<E>>,
+    attestation_cutoff_percent: Option<u64>,
+    postmortems: Postmortems,
 }
 
 impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
@@ -28,8 +35,10 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
             duties_service: None,
             validator_store: None,
             slot_clock: None,
-            beacon_node: None,
+            beacon_nodes: None,
             context: None,
+            attestation_cutoff_percent: None,
+            postmortems: Postmortems::default(),
         }
     }
 
@@ -48,8 +57,8 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
         self
     }
 
-    pub fn beacon_node(mut self, beacon_node: RemoteBeaconNode<E>) -> Self {
-        self.beacon_node = Some(beacon_node);
+    pub fn beacon_nodes(mut self, beacon_nodes: Arc<BeaconNodeFallback<T, E>>) -> Self {
+        self.beacon_nodes = Some(beacon_nodes);
         self
     }
 
@@ -58,6 +67,16 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
         self
     }
 
+    pub fn attestation_cutoff_percent(mut self, percent: Option<u64>) -> Self {
+        self.attestation_cutoff_percent = percent;
+        self
+    }
+
+    pub fn postmortems(mut self, postmortems: Postmortems) -> Self {
+        self.postmortems = postmortems;
+        self
+    }
+
     pub fn build(self) -> Result<AttestationService<T, E>, String> {
         Ok(AttestationService {
             inner: Arc::new(Inner {
@@ -70,12 +89,14 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
                 slot_clock: self
                     .slot_clock
                     .ok_or_else(|| "Cannot build AttestationService without slot_clock")?,
-                beacon_node: self
-                    .beacon_node
-                    .ok_or_else(|| "Cannot build AttestationService without beacon_node")?,
+                beacon_nodes: self
+                    .beacon_nodes
+                    .ok_or_else(|| "Cannot build AttestationService without beacon_nodes")?,
                 context: self
                     .context
                     .ok_or_else(|| "Cannot build AttestationService without runtime_context")?,
+                attestation_cutoff_percent: self.attestation_cutoff_percent,
+                postmortems: self.postmortems,
             }),
         })
     }
@@ -86,8 +107,11 @@ pub struct Inner<T, E: EthSpec> {
     duties_service: DutiesService<T, E>,
     validator_store: ValidatorStore<T, E>,
     slot_clock: T,
-    beacon_node: RemoteBeaconNode<E>,
+    beacon_nodes: Arc<BeaconNodeFallback<T, E>>,
     context: RuntimeContext<E>,
+    attestation_cutoff_percent: Option<u64>,
+    /// The postmortems of missed attestations.
+    postmortems: Postmortems,
 }
 
 /// Attempts to produce attestations for all known validators 1/3rd of the way through each slot.
@@ -117,7 +141,14 @@ impl<T, E: EthSpec> Deref for AttestationService<T, E> {
 
 impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
     /// Starts the service which periodically produces attestations.
-    pub fn start_update_service(self, spec: &ChainSpec) -> Result<(), String> {
+    ///
+    /// Once `warm_up_rx` reports that the duties of the current epoch are known, attestations are
+    /// also produced for the slot in which the service started, if it is not already too late.
+    pub fn start_update_service(
+        self,
+        warm_up_rx: oneshot::Receiver<()>,
+        spec: &ChainSpec,
+    ) -> Result<(), String> {
         let log = self.context.log().clone();
 
         let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
@@ -142,6 +173,14 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
 
         let executor = self.context.executor.clone();
 
+        if let Some(start_slot) = self.slot_clock.now() {
+            executor.spawn(
+                self.clone()
+                    .attest_after_warm_up(start_slot, warm_up_rx, slot_duration),
+                "attestation_warm_up",
+            );
+        }
+
         let interval_fut = async move {
             while interval.next().await.is_some() {
                 let log = self.context.log();
@@ -165,6 +204,77 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
         Ok(())
     }
 
+    /// Produce attestations for `start_slot`, the slot in which the service started, which would
+    /// otherwise be missed whilst waiting for the first tick of the interval.
+    ///
+    /// Waits for the duties of the current epoch to be known and for the usual attestation time
+    /// of 1/3rd of the way through the slot. Attestations are skipped if `start_slot` has already
+    /// passed the aggregation time at 2/3rds of the slot, since they would likely arrive too late
+    /// to be useful.
+    async fn attest_after_warm_up(
+        self,
+        start_slot: Slot,
+        warm_up_rx: oneshot::Receiver<()>,
+        slot_duration: Duration,
+    ) {
+        let log = self.context.log().clone();
+
+        // The sender is only dropped without sending if the duties service failed to start.
+        if warm_up_rx.await.is_err() {
+            return;
+        }
+
+        let num_duties = self.duties_service.attesters(start_slot).len();
+        if num_duties == 0 {
+            debug!(
+                log,
+                "No attestation duties in start slot";
+                "slot" => start_slot.as_u64(),
+            );
+            return;
+        }
+
+        let elapsed = match (
+            self.slot_clock.now(),
+            self.slot_clock.duration_to_next_slot(),
+        ) {
+            (Some(slot), Some(duration_to_next_slot)) if slot == start_slot => {
+                slot_duration.checked_sub(duration_to_next_slot)
+            }
+            _ => None,
+        };
+
+        match elapsed {
+            Some(elapsed) if elapsed < slot_duration * 2 / 3 => {
+                if let Some(delay) = (slot_duration / 3).checked_sub(elapsed) {
+                    delay_until(Instant::now() + delay).await;
+                }
+
+                info!(
+                    log,
+                    "Attesting in start slot";
+                    "slot" => start_slot.as_u64(),
+                    "validators" => num_duties,
+                );
+
+                if let Err(e) = self.spawn_attestation_tasks(slot_duration) {
+                    crit!(
+                        log,
+                        "Failed to spawn attestation tasks";
+                        "error" => e
+                    )
+                }
+            }
+            _ => warn!(
+                log,
+                "Skipping attestations in start slot";
+                "reason" => "duties were fetched too late in the slot",
+                "slot" => start_slot.as_u64(),
+                "validators" => num_duties,
+            ),
+        }
+    }
+
     /// For each each required attestation, spawn a new task that downloads, signs and uploads the
     /// attestation to the beacon node.
     fn spawn_attestation_tasks(&self, slot_duration: Duration) -> Result<(), String> {
@@ -246,18 +356,34 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
         // Step 1.
         //
         // Download, sign and publish an `Attestation` for each validator.
-        let attestation_opt = self
-            .produce_and_publish_attestations(slot, committee_index, &validator_duties)
-            .await
-            .map_err(move |e| {
-                crit!(
-                    log,
-                    "Error during attestation routine";
-                    "error" => format!("{:?}", e),
-                    "committee_index" => committee_index,
-                    "slot" => slot.as_u64(),
-                )
-            })?;
+        let mut trace = DutyTrace::new(
+            DutyKind::Attestation,
+            slot,
+            Some(committee_index),
+            validator_duties
+                .iter()
+                .map(|duty| duty.validator_pubkey().clone())
+                .collect(),
+            self.beacon_nodes.topology(),
+            &self.slot_clock,
+        );
+        let attestation_result = self
+            .produce_and_publish_attestations(slot, committee_index, &validator_duties, &mut trace)
+            .await;
+        if let Err(e) = &attestation_result {
+            trace.missed(e.as_str());
+        }
+        self.postmortems.record_if_missed(trace, log);
+
+        let attestation_opt = attestation_result.map_err(move |e| {
+            crit!(
+                log,
+                "Error during attestation routine";
+                "error" => format!("{:?}", e),
+                "committee_index" => committee_index,
+                "slot" => slot.as_u64(),
+            )
+        })?;
 
         // Step 2.
         //
@@ -300,11 +426,15 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
     ///
     /// Only one `Attestation` is downloaded from the BN. It is then cloned and signed by each
     /// validator and the list of individually-signed `Attestation` objects is returned to the BN.
+    ///
+    /// The steps taken are recorded in `trace`, which is marked as missed if the attestations are
+    /// not published successfully.
     async fn produce_and_publish_attestations(
         &self,
         slot: Slot,
         committee_index: CommitteeIndex,
         validator_duties: &[DutyAndProof],
+        trace: &mut DutyTrace,
     ) -> Result<Option<Attestation<E>>, String> {
         let log = self.context.log();
 
@@ -318,78 +448,126 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
             .ok_or_else(|| "Unable to determine current slot from clock".to_string())?
             .epoch(E::slots_per_epoch());
 
-        let attestation = self
-            .beacon_node
-            .http
-            .validator()
-            .produce_attestation(slot, committee_index)
-            .await
-            .map_err(|e| format!("Failed to produce attestation: {:?}", e))?;
+        let timer = trace.start_step();
+        let attestation_result = self
+            .beacon_nodes
+            .first_success("produce_attestation", |beacon_node| async move {
+                beacon_node
+                    .http
+                    .validator()
+                    .produce_attestation(slot, committee_index)
+                    .await
+            })
+            .await;
+        trace.finish_step_with(timer, "produce_attestation", &attestation_result);
+        let attestation =
+            attestation_result.map_err(|e| format!("Failed to produce attestation: {:?}", e))?;
+
+        // The beacon node may have been slow to produce the attestation, so check the cutoff
+        // immediately before signing.
+        if self.is_past_attestation_cutoff(slot) {
+            trace.missed("Attestation cutoff passed before signing");
+            metrics::inc_counter_by(
+                &metrics::ATTESTATIONS_SKIPPED_AFTER_CUTOFF,
+                validator_duties.len() as i64,
+            );
+            warn!(
+                log,
+                "Skipping attestations after cutoff";
+                "cutoff_percent" => self.attestation_cutoff_percent,
+                "count" => validator_duties.len(),
+                "committee_index" => committee_index,
+                "slot" => slot.as_u64(),
+            );
+            return Ok(None);
+        }
 
         // For each validator in `validator_duties`, clone the `attestation` and add
         // their signature.
         //
         // If any validator is unable to sign, they are simply skipped.
-        let signed_attestations = validator_duties
-            .iter()
-            .filter_map(|duty| {
-                // Ensure that all required fields are present in the validator duty.
-                let (
-                    duty_slot,
-                    duty_committee_index,
-                    validator_committee_position,
-                    _,
-                    committee_count_at_slot,
-                ) = if let Some(tuple) = duty.attestation_duties() {
-                    tuple
-                } else {
-                    crit!(
-                        log,
-                        "Missing validator duties when signing";
-                        "duties" => format!("{:?}", duty)
-                    );
-                    return None;
-                };
+        let timer = trace.start_step();
+        let attestation = &attestation;
+        let signing_futures = validator_duties.iter().map(|duty| async move {
+            // Ensure that all required fields are present in the validator duty.
+            let (
+                duty_slot,
+                duty_committee_index,
+                validator_committee_position,
+                _,
+                committee_count_at_slot,
+            ) = if let Some(tuple) = duty.attestation_duties() {
+                tuple
+            } else {
+                crit!(
+                    log,
+                    "Missing validator duties when signing";
+                    "duties" => format!("{:?}", duty)
+                );
+                return None;
+            };
 
-                // Ensure that the attestation matches the duties.
-                if duty_slot != attestation.data.slot
-                    || duty_committee_index != attestation.data.index
-                {
-                    crit!(
-                        log,
-                        "Inconsistent validator duties during signing";
-                        "validator" => format!("{:?}", duty.validator_pubkey()),
-                        "duty_slot" => duty_slot,
-                        "attestation_slot" => attestation.data.slot,
-                        "duty_index" => duty_committee_index,
-                        "attestation_index" => attestation.data.index,
-                    );
-                    return None;
-                }
+            // Ensure that the attestation matches the duties.
+            if duty_slot != attestation.data.slot || *duty_committee_index != attestation.data.index
+            {
+                crit!(
+                    log,
+                    "Inconsistent validator duties during signing";
+                    "validator" => format!("{:?}", duty.validator_pubkey()),
+                    "duty_slot" => duty_slot,
+                    "attestation_slot" => attestation.data.slot,
+                    "duty_index" => duty_committee_index,
+                    "attestation_index" => attestation.data.index,
+                );
+                return None;
+            }
 
-                let mut attestation = attestation.clone();
-                let subnet_id = SubnetId::compute_subnet_for_attestation_data::<E>(
-                    &attestation.data,
-                    committee_count_at_slot,
-                    &self.context.eth2_config().spec,
+            let mut attestation = attestation.clone();
+            let subnet_id = SubnetId::compute_subnet_for_attestation_data::<E>(
+                &attestation.data,
+                committee_count_at_slot,
+                &self.context.eth2_config().spec,
+            )
+            .map_err(|e| {
+                error!(
+                    log,
+                    "Failed to compute subnet id to publish attestation: {:?}", e
                 )
-                .map_err(|e| {
-                    error!(
-                        log,
-                        "Failed to compute subnet id to publish attestation: {:?}", e
-                    )
-                })
-                .ok()?;
-                self.validator_store
-                    .sign_attestation(
-                        duty.validator_pubkey(),
-                        validator_committee_position,
-                        &mut attestation,
-                        current_epoch,
-                    )
-                    .map(|_| (attestation, subnet_id))
             })
+            .ok()?;
+            self.validator_store
+                .sign_attestation(
+                    duty.validator_pubkey(),
+                    validator_committee_position,
+                    &mut attestation,
+                    current_epoch,
+                )
+                .await
+                .map(|_| (attestation, subnet_id))
+                .ok()
+        });
+        let signed_attestations = join_all(signing_futures)
+            .await
+            .into_iter()
+            .flatten()
             .collect::<Vec<_>>();
+        // Signing is dominated by the check of the slashing protection database.
+        trace.finish_step(
+            timer,
+            "check_slashing_protection_and_sign",
+            format!(
+                "signed {} of {}",
+                signed_attestations.len(),
+                validator_duties.len()
+            ),
+        );
+        if signed_attestations.len() < validator_duties.len() {
+            trace.missed(format!(
+                "{} of {} attestations were not signed",
+                validator_duties.len() - signed_attestations.len(),
+                validator_duties.len()
+            ));
+        }
 
         // If there are any signed attestations, publish them to the BN. Otherwise,
         // just return early.
@@ -397,11 +575,38 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
             let num_attestations = signed_attestations.len();
             let beacon_block_root = attestation.0.data.beacon_block_root;
 
-            self.beacon_node
-                .http
-                .validator()
-                .publish_attestations(signed_attestations)
-                .await
+            let timer = trace.start_step();
+            let signed_attestations = &signed_attestations;
+            let publish_result = self
+                .beacon_nodes
+                .broadcast("publish_attestations", |beacon_node| async move {
+                    beacon_node
+                        .http
+                        .validator()
+                        .publish_attestations(signed_attestations.clone())
+                        .await
+                })
+                .await;
+            trace.finish_step(
+                timer,
+                "publish_attestations",
+                publish_outcome(&publish_result),
+            );
+            metrics::observe_publish(
+                &metrics::PUBLISHED_ATTESTATIONS,
+                num_attestations,
+                &publish_result,
+            );
+            if let Ok(status) = &publish_result {
+                if *status != PublishStatus::Valid {
+                    trace.missed(format!(
+                        "Beacon node did not accept the attestations: {:?}",
+                        status
+                    ));
+                }
+            }
+
+            publish_result
                 .map_err(|e| format!("Failed to publish attestation: {:?}", e))
                 .map(move |publish_status| match publish_status {
                     PublishStatus::Valid => info!(
@@ -438,6 +643,16 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
         }
     }
 
+    /// Returns `true` if `attestation_cutoff_percent` of `slot` has elapsed.
+    fn is_past_attestation_cutoff(&self, slot: Slot) -> bool {
+        self.attestation_cutoff_percent.map_or(false, |percent| {
+            let cutoff = self.slot_clock.slot_duration() * percent as u32 / 100;
+            self.slot_clock
+                .duration_since_start_of(slot)
+                .map_or(false, |elapsed| elapsed >= cutoff)
+        })
+    }
+
     /// Performs the second step of the attesting process: downloading an aggregated `Attestation`,
     /// converting it into a `SignedAggregateAndProof` and returning it to the BN.
     ///
@@ -458,52 +673,77 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
     ) -> Result<(), String> {
         let log = self.context.log();
 
+        // Avoid a request to the beacon node if none of the validators were elected to
+        // aggregate.
+        if validator_duties
+            .iter()
+            .all(|duty_and_proof| duty_and_proof.selection_proof.is_none())
+        {
+            return Ok(());
+        }
+
+        let attestation_data = &attestation.data;
         let aggregated_attestation = self
-            .beacon_node
-            .http
-            .validator()
-            .produce_aggregate_attestation(&attestation.data)
+            .beacon_nodes
+            .first_success("produce_aggregate", |beacon_node| async move {
+                beacon_node
+                    .http
+                    .validator()
+                    .produce_aggregate_attestation(attestation_data)
+                    .await
+            })
             .await
             .map_err(|e| format!("Failed to produce an aggregate attestation: {:?}", e))?;
 
         // For each validator, clone the `aggregated_attestation` and convert it into
         // a `SignedAggregateAndProof`
-        let signed_aggregate_and_proofs = validator_duties
-            .iter()
-            .filter_map(|duty_and_proof| {
-                // Do not produce a signed aggregator for validators that are not
-                // subscribed aggregators.
-                let selection_proof = duty_and_proof.selection_proof.as_ref()?.clone();
-
-                let (duty_slot, duty_committee_index, _, validator_index, _) =
-                    duty_and_proof.attestation_duties().or_else(|| {
-                        crit!(log, "Missing duties when signing aggregate");
-                        None
-                    })?;
-
-                let pubkey = &duty_and_proof.duty.validator_pubkey;
-                let slot = attestation.data.slot;
-                let committee_index = attestation.data.index;
-
-                if duty_slot != slot || duty_committee_index != committee_index {
-                    crit!(log, "Inconsistent validator duties during signing");
-                    return None;
-                }
+        let attestation = &attestation;
+        let aggregated_attestation = &aggregated_attestation;
+        let signing_futures = validator_duties.iter().map(|duty_and_proof| async move {
+            // Do not produce a signed aggregator for validators that are not
+            // subscribed aggregators.
+            let selection_proof = duty_and_proof.selection_proof.as_ref()?.clone();
+
+            let (duty_slot, duty_committee_index, _, validator_index, _) =
+                duty_and_proof.attestation_duties().or_else(|| {
+                    crit!(log, "Missing duties when signing aggregate");
+                    None
+                })?;
 
-                if let Some(signed_aggregate_and_proof) =
-                    self.validator_store.produce_signed_aggregate_and_proof(
-                        pubkey,
-                        validator_index,
-                        aggregated_attestation.clone(),
-                        selection_proof,
-                    )
-                {
-                    Some(signed_aggregate_and_proof)
-                } else {
-                    crit!(log, "Failed to sign attestation");
+            let pubkey = &duty_and_proof.duty.validator_pubkey;
+            let slot = attestation.data.slot;
+            let committee_index = attestation.data.index;
+
+            if duty_slot != slot || *duty_committee_index != committee_index {
+                crit!(log, "Inconsistent validator duties during signing");
+                return None;
+            }
+
+            match self
+                .validator_store
+                .produce_signed_aggregate_and_proof(
+                    pubkey,
+                    validator_index,
+                    aggregated_attestation.clone(),
+                    selection_proof,
+                )
+                .await
+            {
+                Ok(signed_aggregate_and_proof) => Some(signed_aggregate_and_proof),
+                Err(e) => {
+                    crit!(
+                        log,
+                        "Failed to sign attestation";
+                        "error" => format!("{:?}", e)
+                    );
                     None
                 }
-            })
+            }
+        });
+        let signed_aggregate_and_proofs = join_all(signing_futures)
+            .await
+            .into_iter()
+            .flatten()
             .collect::<Vec<_>>();
 
         // If there any signed aggregates and proofs were produced, publish them to the
@@ -511,12 +751,23 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
         if let Some(first) = signed_aggregate_and_proofs.first().cloned() {
             let attestation = first.message.aggregate;
 
-            let publish_status = self
-                .beacon_node
-                .http
-                .validator()
-                .publish_aggregate_and_proof(signed_aggregate_and_proofs)
-                .await
+            let signed_aggregate_and_proofs = &signed_aggregate_and_proofs;
+            let publish_result = self
+                .beacon_nodes
+                .broadcast("publish_aggregates", |beacon_node| async move {
+                    beacon_node
+                        .http
+                        .validator()
+                        .publish_aggregate_and_proof(signed_aggregate_and_proofs.clone())
+                        .await
+                })
+                .await;
+            metrics::observe_publish(
+                &metrics::PUBLISHED_AGGREGATES,
+                signed_aggregate_and_proofs.len(),
+                &publish_result,
+            );
+            let publish_status = publish_result
                 .map_err(|e| format!("Failed to publish aggregate and proofs: {:?}", e))?;
             match publish_status {
                 PublishStatus::Valid => info!(
@@ -0,0 +1,345 @@
+//! Allows the validator client to use several beacon nodes, so that duties are still performed
+//! whilst one of them is offline or syncing.
+//!
+//! Requests are sent to the beacon nodes in order of their health, and then in the order in which
+//! they were given. Signed objects are broadcast to every beacon node.
+use crate::is_synced::is_synced;
+use crate::metrics;
+use futures::future::join_all;
+use parking_lot::RwLock;
+use remote_beacon_node::RemoteBeaconNode;
+use slog::{debug, Logger};
+use slot_clock::SlotClock;
+use std::fmt;
+use std::future::Future;
+use types::EthSpec;
+
+/// The health of a beacon node, as of the last time it was checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CandidateStatus {
+    Synced,
+    NotSynced,
+    /// The beacon node has not been checked yet.
+    Unknown,
+    Offline,
+}
+
+impl fmt::Display for CandidateStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CandidateStatus::Synced => write!(f, "synced"),
+            CandidateStatus::NotSynced => write!(f, "syncing"),
+            CandidateStatus::Unknown => write!(f, "unknown"),
+            CandidateStatus::Offline => write!(f, "offline"),
+        }
+    }
+}
+
+/// The errors of a request which failed on every beacon node, with the URL of each node.
+pub struct AllErrored<E>(pub Vec<(String, E)>);
+
+impl<E: fmt::Debug> fmt::Debug for AllErrored<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "No beacon nodes");
+        }
+        let errors = self
+            .0
+            .iter()
+            .map(|(url, e)| format!("{}: {:?}", url, e))
+            .collect::<Vec<_>>();
+        write!(f, "All beacon nodes failed: {}", errors.join(", "))
+    }
+}
+
+struct CandidateBeaconNode<E: EthSpec> {
+    beacon_node: RemoteBeaconNode<E>,
+    status: RwLock<CandidateStatus>,
+}
+
+/// An ordered set of beacon nodes, ranked by their health.
+pub struct BeaconNodeFallback<T, E: EthSpec> {
+    candidates: Vec<CandidateBeaconNode<E>>,
+    slot_clock: T,
+    log: Logger,
+}
+
+impl<T: SlotClock, E: EthSpec> BeaconNodeFallback<T, E> {
+    /// Creates a set of the `beacon_nodes`, in order of preference.
+    pub fn new(beacon_nodes: Vec<RemoteBeaconNode<E>>, slot_clock: T, log: Logger) -> Self {
+        Self {
+            candidates: beacon_nodes
+                .into_iter()
+                .map(|beacon_node| CandidateBeaconNode {
+                    beacon_node,
+                    status: RwLock::new(CandidateStatus::Unknown),
+                })
+                .collect(),
+            slot_clock,
+            log,
+        }
+    }
+
+    /// Checks whether each beacon node is online and synced.
+    pub async fn update_all_candidates(&self) {
+        join_all(self.candidates.iter().map(|candidate| async move {
+            let status =
+                match is_synced(&candidate.beacon_node, &self.slot_clock, Some(&self.log)).await {
+                    Ok(true) => CandidateStatus::Synced,
+                    Ok(false) => CandidateStatus::NotSynced,
+                    Err(_) => CandidateStatus::Offline,
+                };
+            *candidate.status.write() = status;
+        }))
+        .await;
+    }
+
+    pub fn num_total(&self) -> usize {
+        self.candidates.len()
+    }
+
+    pub fn num_synced(&self) -> usize {
+        self.candidates
+            .iter()
+            .filter(|candidate| *candidate.status.read() == CandidateStatus::Synced)
+            .count()
+    }
+
+    /// Returns the URL and status of each beacon node, in order of preference, for logging.
+    pub fn topology(&self) -> String {
+        self.candidates
+            .iter()
+            .map(|candidate| {
+                format!(
+                    "{} ({})",
+                    candidate.beacon_node.http.server_url(),
+                    candidate.status.read()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Returns the beacon node which requests are sent to first.
+    pub fn preferred(&self) -> Option<RemoteBeaconNode<E>> {
+        self.ranked()
+            .first()
+            .map(|candidate| candidate.beacon_node.clone())
+    }
+
+    /// The beacon nodes, healthiest first. Beacon nodes of the same health are kept in the order
+    /// in which they were given.
+    fn ranked(&self) -> Vec<&CandidateBeaconNode<E>> {
+        let mut ranked = self.candidates.iter().collect::<Vec<_>>();
+        ranked.sort_by_key(|candidate| *candidate.status.read());
+        ranked
+    }
+
+    /// Runs `func` on each beacon node in turn, returning the first success.
+    ///
+    /// The response times and errors of each beacon node are recorded under the `request` label.
+    pub async fn first_success<F, R, O, Err>(
+        &self,
+        request: &'static str,
+        func: F,
+    ) -> Result<O, AllErrored<Err>>
+    where
+        F: Fn(RemoteBeaconNode<E>) -> R,
+        R: Future<Output = Result<O, Err>>,
+        Err: fmt::Debug,
+    {
+        let mut errors = vec![];
+        for candidate in self.ranked() {
+            let url = candidate.beacon_node.http.server_url().to_string();
+            match timed(request, func(candidate.beacon_node.clone())).await {
+                Ok(output) => return Ok(output),
+                Err(e) => {
+                    debug!(
+                        self.log,
+                        "Request to beacon node failed";
+                        "error" => format!("{:?}", e),
+                        "request" => request,
+                        "endpoint" => &url,
+                    );
+                    errors.push((url, e));
+                }
+            }
+        }
+        Err(AllErrored(errors))
+    }
+
+    /// Runs `func` on every beacon node at once, returning the success of the healthiest beacon
+    /// node which succeeded.
+    ///
+    /// The response times and errors of each beacon node are recorded under the `request` label.
+    pub async fn broadcast<F, R, O, Err>(
+        &self,
+        request: &'static str,
+        func: F,
+    ) -> Result<O, AllErrored<Err>>
+    where
+        F: Fn(RemoteBeaconNode<E>) -> R,
+        R: Future<Output = Result<O, Err>>,
+        Err: fmt::Debug,
+    {
+        let ranked = self.ranked();
+        let results = join_all(
+            ranked
+                .iter()
+                .map(|candidate| timed(request, func(candidate.beacon_node.clone()))),
+        )
+        .await;
+
+        let mut output = None;
+        let mut errors = vec![];
+        for (candidate, result) in ranked.into_iter().zip(results) {
+            match result {
+                Ok(o) => {
+                    output.get_or_insert(o);
+                }
+                Err(e) => {
+                    let url = candidate.beacon_node.http.server_url().to_string();
+                    debug!(
+                        self.log,
+                        "Broadcast to beacon node failed";
+                        "error" => format!("{:?}", e),
+                        "request" => request,
+                        "endpoint" => &url,
+                    );
+                    errors.push((url, e));
+                }
+            }
+        }
+        output.ok_or_else(|| AllErrored(errors))
+    }
+}
+
+/// Records the response time of a request to a beacon node, and whether it failed.
+async fn timed<R, O, Err>(request: &'static str, future: R) -> Result<O, Err>
+where
+    R: Future<Output = Result<O, Err>>,
+{
+    let timer = metrics::start_timer_vec(&metrics::BEACON_NODE_REQUEST_TIMES, &[request]);
+    let result = future.await;
+    metrics::stop_timer(timer);
+    if result.is_err() {
+        metrics::inc_counter_vec(&metrics::BEACON_NODE_REQUEST_ERRORS, &[request]);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::o;
+    use slot_clock::TestingSlotClock;
+    use std::time::Duration;
+    use types::{MinimalEthSpec, Slot};
+
+    fn fallback(urls: &[&str]) -> BeaconNodeFallback<TestingSlotClock, MinimalEthSpec> {
+        let slot_clock = TestingSlotClock::new(
+            Slot::new(0),
+            Duration::from_secs(0),
+            Duration::from_secs(12),
+        );
+        BeaconNodeFallback::new(
+            urls.iter()
+                .map(|url| RemoteBeaconNode::new(url.to_string()).unwrap())
+                .collect(),
+            slot_clock,
+            Logger::root(slog::Discard, o!()),
+        )
+    }
+
+    fn set_status(
+        fallback: &BeaconNodeFallback<TestingSlotClock, MinimalEthSpec>,
+        status: &[CandidateStatus],
+    ) {
+        for (candidate, status) in fallback.candidates.iter().zip(status) {
+            *candidate.status.write() = *status;
+        }
+    }
+
+    #[tokio::test]
+    async fn healthiest_beacon_node_first() {
+        let fallback = fallback(&["http://a/", "http://b/", "http://c/"]);
+        set_status(
+            &fallback,
+            &[
+                CandidateStatus::Offline,
+                CandidateStatus::NotSynced,
+                CandidateStatus::Synced,
+            ],
+        );
+        assert_eq!(fallback.num_synced(), 1);
+        assert_eq!(
+            fallback.topology(),
+            "http://a/ (offline), http://b/ (syncing), http://c/ (synced)"
+        );
+
+        let result = fallback
+            .first_success("test", |beacon_node| async move {
+                match beacon_node.http.server_url() {
+                    "http://c/" => Err("failed"),
+                    url => Ok(url.to_string()),
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), "http://b/");
+    }
+
+    #[tokio::test]
+    async fn broadcast_to_all_beacon_nodes() {
+        let fallback = fallback(&["http://a/", "http://b/"]);
+        set_status(
+            &fallback,
+            &[CandidateStatus::NotSynced, CandidateStatus::Synced],
+        );
+
+        let visited = RwLock::new(vec![]);
+        let result = fallback
+            .broadcast("test", |beacon_node| {
+                let visited = &visited;
+                async move {
+                    let url = beacon_node.http.server_url().to_string();
+                    visited.write().push(url.clone());
+                    Ok::<_, ()>(url)
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), "http://b/");
+        assert_eq!(visited.read().len(), 2);
+
+        let errors = fallback
+            .first_success("test", |_| async { Err::<(), _>("failed") })
+            .await
+            .unwrap_err();
+        assert_eq!(errors.0.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn requests_are_timed_and_failures_counted() {
+        let fallback = fallback(&["http://a/", "http://b/"]);
+        set_status(
+            &fallback,
+            &[CandidateStatus::Synced, CandidateStatus::Synced],
+        );
+
+        // The request name is unique to this test, as the metrics are global.
+        let request = "metrics_test";
+        let result = fallback
+            .first_success(request, |beacon_node| async move {
+                match beacon_node.http.server_url() {
+                    "http://a/" => Err("failed"),
+                    url => Ok(url.to_string()),
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), "http://b/");
+
+        let errors = metrics::get_int_counter(&metrics::BEACON_NODE_REQUEST_ERRORS, &[request]);
+        assert_eq!(errors.unwrap().get(), 1);
+        let times = metrics::get_histogram(&metrics::BEACON_NODE_REQUEST_TIMES, &[request]);
+        assert_eq!(times.unwrap().get_sample_count(), 2);
+    }
+}
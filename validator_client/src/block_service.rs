@@ -1,21 +1,28 @@
-use crate::validator_store::ValidatorStore;
+use crate::beacon_node_fallback::BeaconNodeFallback;
+use crate::graffiti::{GraffitiContext, GraffitiRotation};
+use crate::postmortem::{publish_outcome, DutyKind, DutyTrace, Postmortems};
+use crate::{metrics, validator_store::ValidatorStore};
 use environment::RuntimeContext;
 use futures::channel::mpsc::Receiver;
 use futures::{StreamExt, TryFutureExt};
-use remote_beacon_node::{PublishStatus, RemoteBeaconNode};
+use remote_beacon_node::PublishStatus;
 use slog::{crit, debug, error, info, trace, warn};
 use slot_clock::SlotClock;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use types::{EthSpec, Graffiti, PublicKey, Slot};
+use std::time::Duration;
+use types::{EthSpec, PublicKey, Slot};
 
 /// Builds a `BlockService`.
 pub struct BlockServiceBuilder<T, E: EthSpec> {
     validator_store: Option<ValidatorStore<T, E>>,
     slot_clock: Option<Arc<T>>,
-    beacon_node: Option<RemoteBeaconNode<E>>,
+    beacon_nodes: Option<Arc<BeaconNodeFallback<T, E>>>,
     context: Option<RuntimeContext<E>>,
-    graffiti: Option<Graffiti>,
+    graffiti: GraffitiRotation,
+    block_cutoff: Option<Duration>,
+    postmortems: Postmortems,
 }
 
 impl<T: SlotClock + 'static, E: EthSpec> BlockServiceBuilder<T, E> {
@@ -23,9 +30,11 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockServiceBuilder<T, E> {
         Self {
             validator_store: None,
             slot_clock: None,
-            beacon_node: None,
+            beacon_nodes: None,
             context: None,
-            graffiti: None,
+            graffiti: GraffitiRotation::default(),
+            block_cutoff: None,
+            postmortems: Postmortems::default(),
         }
     }
 
@@ -39,8 +48,8 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockServiceBuilder<T, E> {
         self
     }
 
-    pub fn beacon_node(mut self, beacon_node: RemoteBeaconNode<E>) -> Self {
-        self.beacon_node = Some(beacon_node);
+    pub fn beacon_nodes(mut self, beacon_nodes: Arc<BeaconNodeFallback<T, E>>) -> Self {
+        self.beacon_nodes = Some(beacon_nodes);
         self
     }
 
@@ -49,11 +58,21 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockServiceBuilder<T, E> {
         self
     }
 
-    pub fn graffiti(mut self, graffiti: Option<Graffiti>) -> Self {
+    pub fn graffiti(mut self, graffiti: GraffitiRotation) -> Self {
         self.graffiti = graffiti;
         self
     }
 
+    pub fn block_cutoff(mut self, block_cutoff: Option<Duration>) -> Self {
+        self.block_cutoff = block_cutoff;
+        self
+    }
+
+    pub fn postmortems(mut self, postmortems: Postmortems) -> Self {
+        self.postmortems = postmortems;
+        self
+    }
+
     pub fn build(self) -> Result<BlockService<T, E>, String> {
         Ok(BlockService {
             inner: Arc::new(Inner {
@@ -63,13 +82,16 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockServiceBuilder<T, E> {
                 slot_clock: self
                     .slot_clock
                     .ok_or_else(|| "Cannot build BlockService without slot_clock")?,
-                beacon_node: self
-                    .beacon_node
-                    .ok_or_else(|| "Cannot build BlockService without beacon_node")?,
+                beacon_nodes: self
+                    .beacon_nodes
+                    .ok_or_else(|| "Cannot build BlockService without beacon_nodes")?,
                 context: self
                     .context
                     .ok_or_else(|| "Cannot build BlockService without runtime_context")?,
                 graffiti: self.graffiti,
+                proposals: AtomicUsize::new(0),
+                block_cutoff: self.block_cutoff,
+                postmortems: self.postmortems,
             }),
         })
     }
@@ -79,9 +101,14 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockServiceBuilder<T, E> {
 pub struct Inner<T, E: EthSpec> {
     validator_store: ValidatorStore<T, E>,
     slot_clock: Arc<T>,
-    beacon_node: RemoteBeaconNode<E>,
+    beacon_nodes: Arc<BeaconNodeFallback<T, E>>,
     context: RuntimeContext<E>,
-    graffiti: Option<Graffiti>,
+    graffiti: GraffitiRotation,
+    /// The number of blocks produced so far, which selects the next graffiti in the rotation.
+    proposals: AtomicUsize,
+    block_cutoff: Option<Duration>,
+    /// The postmortems of missed block proposals.
+    postmortems: Postmortems,
 }
 
 /// Attempts to produce attestations for any block producer(s) at the start of the epoch.
@@ -105,10 +132,17 @@ impl<T, E: EthSpec> Deref for BlockService<T, E> {
     }
 }
 
+/// A local validator which is assigned to propose a block.
+#[derive(Debug, Clone)]
+pub struct BlockProposer {
+    pub pubkey: PublicKey,
+    pub validator_index: Option<u64>,
+}
+
 /// Notification from the duties service that we should try to produce a block.
 pub struct BlockServiceNotification {
     pub slot: Slot,
-    pub block_proposers: Vec<PublicKey>,
+    pub block_proposers: Vec<BlockProposer>,
 }
 
 impl<T: SlotClock + 'static, E: EthSpec> BlockService<T, E> {
@@ -186,28 +220,54 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockService<T, E> {
             )
         }
 
-        proposers.into_iter().for_each(|validator_pubkey| {
+        proposers.into_iter().for_each(|proposer| {
             let service = self.clone();
             let log = log.clone();
             self.inner.context.executor.runtime_handle().spawn(
-                service
-                    .publish_block(slot, validator_pubkey)
-                    .map_err(move |e| {
-                        crit!(
-                            log,
-                            "Error whilst producing block";
-                            "message" => e
-                        )
-                    }),
+                service.publish_block(slot, proposer).map_err(move |e| {
+                    crit!(
+                        log,
+                        "Error whilst producing block";
+                        "message" => e
+                    )
+                }),
             );
         });
 
         Ok(())
     }
 
-    /// Produce a block at the given slot for validator_pubkey
-    async fn publish_block(self, slot: Slot, validator_pubkey: PublicKey) -> Result<(), String> {
+    /// Produce a block at the given slot for the proposer, recording a postmortem if it is missed.
+    async fn publish_block(self, slot: Slot, proposer: BlockProposer) -> Result<(), String> {
+        let mut trace = DutyTrace::new(
+            DutyKind::Block,
+            slot,
+            None,
+            vec![proposer.pubkey.clone()],
+            self.beacon_nodes.topology(),
+            self.slot_clock.as_ref(),
+        );
+
+        let result = self
+            .produce_and_publish_block(slot, proposer, &mut trace)
+            .await;
+        if let Err(e) = &result {
+            trace.missed(e.as_str());
+        }
+        self.postmortems.record_if_missed(trace, self.context.log());
+
+        result
+    }
+
+    /// Produce a block at the given slot for the proposer, recording the steps taken in `trace`.
+    async fn produce_and_publish_block(
+        &self,
+        slot: Slot,
+        proposer: BlockProposer,
+        trace: &mut DutyTrace,
+    ) -> Result<(), String> {
         let log = self.context.log();
+        let validator_pubkey = proposer.pubkey;
 
         let current_slot = self
             .slot_clock
@@ -217,28 +277,90 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockService<T, E> {
         let randao_reveal = self
             .validator_store
             .randao_reveal(&validator_pubkey, slot.epoch(E::slots_per_epoch()))
-            .ok_or_else(|| "Unable to produce randao reveal".to_string())?;
-
-        let block = self
-            .beacon_node
-            .http
-            .validator()
-            .produce_block(slot, randao_reveal, self.graffiti)
             .await
+            .map_err(|e| format!("Unable to produce randao reveal: {:?}", e))?;
+
+        // The graffiti of the validator takes precedence over the rotation.
+        let graffiti_context = GraffitiContext {
+            validator_index: proposer.validator_index,
+            slot,
+        };
+        let graffiti = match self.validator_store.graffiti(&validator_pubkey) {
+            Some(template) => Some(template.render(&graffiti_context)),
+            None => self.graffiti.graffiti(
+                self.proposals.fetch_add(1, Ordering::Relaxed),
+                &graffiti_context,
+            ),
+        };
+
+        let fee_recipient = self.validator_store.fee_recipient(&validator_pubkey);
+        let gas_limit = self.validator_store.gas_limit();
+
+        let timer = trace.start_step();
+        let randao_reveal = &randao_reveal;
+        let block_result = self
+            .beacon_nodes
+            .first_success("produce_block", |beacon_node| async move {
+                beacon_node
+                    .http
+                    .validator()
+                    .produce_block(
+                        slot,
+                        randao_reveal.clone(),
+                        graffiti,
+                        fee_recipient,
+                        gas_limit,
+                    )
+                    .await
+            })
+            .await;
+        trace.finish_step_with(timer, "produce_block", &block_result);
+        let block = block_result
             .map_err(|e| format!("Error from beacon node when producing block: {:?}", e))?;
 
+        // Check the cutoff before signing, so that the block is not recorded in the slashing
+        // protection database, and again before publishing.
+        if self.is_past_block_cutoff(slot, "before signing") {
+            trace.missed("Block cutoff passed before signing");
+            return Ok(());
+        }
+
+        // Signing is dominated by the check of the slashing protection database.
+        let timer = trace.start_step();
         let signed_block = self
             .validator_store
             .sign_block(&validator_pubkey, block, current_slot)
-            .ok_or_else(|| "Unable to sign block".to_string())?;
+            .await;
+        trace.finish_step_with(timer, "check_slashing_protection_and_sign", &signed_block);
+        let signed_block = signed_block.map_err(|e| format!("Unable to sign block: {:?}", e))?;
 
-        let publish_status = self
-            .beacon_node
-            .http
-            .validator()
-            .publish_block(signed_block.clone())
-            .await
+        if self.is_past_block_cutoff(slot, "before publishing") {
+            trace.missed("Block cutoff passed before publishing");
+            return Ok(());
+        }
+
+        let timer = trace.start_step();
+        let signed_block = &signed_block;
+        let publish_result = self
+            .beacon_nodes
+            .broadcast("publish_block", |beacon_node| async move {
+                beacon_node
+                    .http
+                    .validator()
+                    .publish_block(signed_block.clone())
+                    .await
+            })
+            .await;
+        trace.finish_step(timer, "publish_block", publish_outcome(&publish_result));
+        metrics::observe_publish(&metrics::PUBLISHED_BLOCKS, 1, &publish_result);
+        let publish_status = publish_result
             .map_err(|e| format!("Error from beacon node when publishing block: {:?}", e))?;
+        if publish_status != PublishStatus::Valid {
+            trace.missed(format!(
+                "Beacon node did not accept the block: {:?}",
+                publish_status
+            ));
+        }
 
         match publish_status {
             PublishStatus::Valid => info!(
@@ -259,4 +381,29 @@ impl<T: SlotClock + 'static, E: EthSpec> BlockService<T, E> {
 
         Ok(())
     }
+
+    /// Returns `true` if `block_cutoff` of `slot` has elapsed, logging and recording the skipped
+    /// block if so.
+    fn is_past_block_cutoff(&self, slot: Slot, stage: &str) -> bool {
+        let block_cutoff = match self.block_cutoff {
+            Some(block_cutoff) => block_cutoff,
+            None => return false,
+        };
+
+        match self.slot_clock.duration_since_start_of(slot) {
+            Some(elapsed) if elapsed >= block_cutoff => {
+                metrics::inc_counter(&metrics::BLOCKS_SKIPPED_AFTER_CUTOFF);
+                warn!(
+                    self.context.log(),
+                    "Skipping block after cutoff";
+                    "stage" => stage,
+                    "elapsed_ms" => elapsed.as_millis(),
+                    "cutoff_ms" => block_cutoff.as_millis(),
+                    "slot" => slot.as_u64(),
+                );
+                true
+            }
+            _ => false,
+        }
+    }
 }
@@ -12,7 +12,9 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("server")
                 .long("server")
                 .value_name("NETWORK_ADDRESS")
-                .help("Address to connect to BeaconNode.")
+                .help("Address to connect to BeaconNode. Several comma-delimited addresses may be \
+                       given, in order of preference. Requests are sent to synced beacon nodes \
+                       first, and signed blocks and attestations are published to all of them.")
                 .default_value(&DEFAULT_HTTP_SERVER)
                 .takes_value(true),
         )
@@ -52,6 +54,27 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 will need to be manually added to the validator_definitions.yml file."
             )
         )
+        .arg(
+            Arg::with_name("force-network-change")
+            .long("force-network-change")
+            .help(
+                "If present, allow the slashing protection database to be used on a network other \
+                than the one it was previously used on. The database will be associated with the \
+                network of the beacon node. DO NOT use this flag unless you are certain the \
+                database contains no data from another network that you still need."
+            )
+        )
+        .arg(
+            Arg::with_name("slashing-protection-url")
+                .long("slashing-protection-url")
+                .value_name("URL")
+                .help(
+                    "The URL of a remote slashing protection service to use instead of the local \
+                    slashing protection database, allowing protection to be shared between many \
+                    validator clients. The service must check and record every message atomically."
+                )
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("allow-unsynced")
                 .long("allow-unsynced")
@@ -64,8 +87,143 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
         .arg(
             Arg::with_name("graffiti")
                 .long("graffiti")
-                .help("Specify your custom graffiti to be included in blocks.")
+                .help(
+                    "Specify your custom graffiti to be included in blocks. It may contain the \
+                    variables {validator_index}, {slot} and {version}, which are substituted \
+                    for each block. If provided multiple times, each block uses the next \
+                    graffiti in turn.",
+                )
                 .value_name("GRAFFITI")
                 .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+        )
+        .arg(
+            Arg::with_name("suggested-fee-recipient")
+                .long("suggested-fee-recipient")
+                .value_name("ADDRESS")
+                .help(
+                    "The execution address, e.g. 0x00000000219ab540356cbb839cbe05303d7705fa, \
+                    which the fees of blocks are paid to, for validators which do not set their \
+                    own suggested_fee_recipient. Sent to the beacon node when producing blocks."
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("gas-limit")
+                .long("gas-limit")
+                .value_name("GAS")
+                .help(
+                    "The gas limit to request for the execution payloads of blocks. Sent to the \
+                    beacon node when producing blocks."
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("attestation-cutoff")
+                .long("attestation-cutoff")
+                .value_name("PERCENT")
+                .help(
+                    "If present, attestations are not signed or published once this percentage \
+                    of their slot has elapsed, since they are unlikely to be included in time to \
+                    earn a reward. Disabled by default."
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("block-cutoff-ms")
+                .long("block-cutoff-ms")
+                .value_name("MILLISECONDS")
+                .help(
+                    "If present, a block is not signed or published once this many milliseconds \
+                    of its slot have elapsed, since it is unlikely to become canonical. Disabled \
+                    by default."
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("ntp-server")
+                .long("ntp-server")
+                .value_name("HOST")
+                .help(
+                    "If present, the local clock is compared with the NTP server at this host, \
+                    e.g. pool.ntp.org, once per epoch. It is always compared with the head of \
+                    the beacon nodes."
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("max-clock-drift-ms")
+                .long("max-clock-drift-ms")
+                .value_name("MILLISECONDS")
+                .help(
+                    "A warning is logged if the local clock has drifted from the time of the \
+                    network by more than this many milliseconds."
+                )
+                .default_value("500")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("pause-signing-on-clock-drift")
+                .long("pause-signing-on-clock-drift")
+                .help(
+                    "If present, nothing is signed whilst the local clock has drifted by more \
+                    than --max-clock-drift-ms, since messages would likely be too early or too \
+                    late to be rewarded. Whilst paused, the clock is checked every slot. If \
+                    neither the beacon nodes nor the NTP server respond, signing stays paused."
+                )
+                .takes_value(false),
+        )
+        /* HTTP API related arguments */
+        .arg(
+            Arg::with_name("http")
+                .long("http")
+                .help("Enable the HTTP API for managing validators whilst the validator client \
+                       runs, which follows the keymanager API standard. Requests must carry the \
+                       token in api-token.txt in the datadir, which is generated if it does not \
+                       exist. Disabled by default.")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("http-address")
+                .long("http-address")
+                .value_name("ADDRESS")
+                .help("Set the listen address for the HTTP API server. Anyone with the API \
+                       token is able to delete validators, so do not expose it publicly.")
+                .default_value("127.0.0.1")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("http-port")
+                .long("http-port")
+                .value_name("PORT")
+                .help("Set the listen TCP port for the HTTP API server.")
+                .default_value("5062")
+                .takes_value(true),
+        )
+        /* Metrics related arguments */
+        .arg(
+            Arg::with_name("metrics")
+                .long("metrics")
+                .help("Enable the Prometheus metrics HTTP server, which also serves the \
+                       postmortems of recently missed duties at /postmortems. Disabled by \
+                       default.")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("metrics-address")
+                .long("metrics-address")
+                .value_name("ADDRESS")
+                .help("Set the listen address for the Prometheus metrics HTTP server.")
+                .default_value("127.0.0.1")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("metrics-port")
+                .long("metrics-port")
+                .value_name("PORT")
+                .help("Set the listen TCP port for the Prometheus metrics HTTP server.")
+                .default_value("5064")
+                .takes_value(true),
         )
 }
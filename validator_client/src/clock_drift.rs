@@ -0,0 +1,422 @@
+//! Detects when the local clock has drifted from the time of the network, which causes duties to
+//! be performed too early or too late to earn rewards.
+//!
+//! Once per epoch, and every slot whilst signing is paused, the local clock is compared with:
+//!
+//! - The head of the beacon nodes, which must not be for a slot which has not yet started.
+//! - An NTP server, if one is given with `--ntp-server`.
+//!
+//! If neither can be reached, signing is neither paused nor resumed, since there is no evidence
+//! that the clock has drifted or recovered. The head of the beacon nodes can only show that the
+//! clock is behind, so signing which was paused by the NTP server is only resumed once the NTP
+//! server shows that the clock has recovered.
+use crate::{metrics, ProductionValidatorClient};
+use futures::channel::oneshot;
+use futures::StreamExt;
+use slog::{debug, info, warn};
+use slot_clock::SlotClock;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::{interval_at, Duration, Instant};
+use types::EthSpec;
+
+/// The seconds between the NTP epoch, 1900, and the Unix epoch, 1970.
+const NTP_UNIX_OFFSET_SECS: u64 = 2_208_988_800;
+/// The port that NTP servers listen on.
+const NTP_PORT: u16 = 123;
+/// The time to wait for the NTP server to respond.
+const NTP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The time source which showed that the local clock had drifted, causing signing to be paused.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PauseReason {
+    /// The head of the beacon nodes was for a slot which had not yet started.
+    BeaconNode,
+    /// The NTP server's clock differed from the local clock.
+    Ntp,
+}
+
+/// Spawns a service which checks the drift of the local clock once per epoch, warning if it
+/// exceeds `config.max_clock_drift`.
+///
+/// If `config.pause_signing_on_clock_drift` is set, nothing is signed whilst the drift is
+/// exceeded. The clock is then checked every slot, so that signing resumes soon after it
+/// recovers.
+pub fn spawn_clock_drift_check<T: EthSpec>(
+    client: &ProductionValidatorClient<T>,
+) -> Result<(), String> {
+    let context = client.context.service_context("clock_drift".into());
+    let executor = context.executor.clone();
+    let ntp_executor = executor.clone();
+    let duties_service = client.duties_service.clone();
+    let slot_clock = duties_service.slot_clock.clone();
+    let validator_store = duties_service.validator_store.clone();
+    let beacon_nodes = client.beacon_nodes.clone();
+    let ntp_server = client.config.ntp_server.clone();
+    let max_drift = client.config.max_clock_drift;
+    let pause_signing = client.config.pause_signing_on_clock_drift;
+
+    let slot_duration = Duration::from_millis(context.eth2_config.spec.milliseconds_per_slot);
+    let duration_to_next_slot = slot_clock
+        .duration_to_next_slot()
+        .ok_or_else(|| "clock_drift unable to determine time to next slot")?;
+
+    // Check half way through a slot, once the block of the slot is likely to be the head.
+    let start_instant = Instant::now() + duration_to_next_slot + (slot_duration / 2);
+    let mut interval = interval_at(start_instant, slot_duration);
+
+    let interval_fut = async move {
+        let log = context.log();
+        let mut last_checked_epoch = None;
+        let mut pause_reason = None;
+
+        while interval.next().await.is_some() {
+            let epoch = slot_clock
+                .now()
+                .map(|slot| slot.epoch(T::slots_per_epoch()));
+            if epoch.is_some()
+                && epoch == last_checked_epoch
+                && !validator_store.is_signing_paused()
+            {
+                continue;
+            }
+            last_checked_epoch = epoch;
+
+            // A head for a slot which has not started shows that the local clock is behind by at
+            // least the time until the slot starts. It never shows that the clock is ahead.
+            let mut head_behind_ms = None;
+            let head = beacon_nodes
+                .first_success("get_head", |beacon_node| async move {
+                    beacon_node.http.beacon().get_head().await
+                })
+                .await;
+            match head {
+                Ok(head) => {
+                    if let (Some(now), Some(head_start)) =
+                        (slot_clock.now_duration(), slot_clock.start_of(head.slot))
+                    {
+                        let behind_ms = head_start.as_millis() as i64 - now.as_millis() as i64;
+                        head_behind_ms = Some(behind_ms.max(0));
+                    }
+                }
+                Err(e) => debug!(
+                    log,
+                    "Unable to get head for clock drift check";
+                    "error" => format!("{:?}", e),
+                ),
+            }
+
+            let mut ntp_offset_ms = None;
+            if let Some(server) = ntp_server.clone() {
+                let (tx, rx) = oneshot::channel();
+                ntp_executor.spawn_blocking(
+                    move || {
+                        let _ = tx.send(ntp_offset_ms(&server, NTP_TIMEOUT));
+                    },
+                    "clock_drift_ntp",
+                );
+                match rx.await {
+                    Ok(Ok(offset_ms)) => ntp_offset_ms = Some(offset_ms),
+                    Ok(Err(e)) => warn!(
+                        log,
+                        "Unable to query NTP server";
+                        "error" => e,
+                        "server" => ntp_server.as_deref(),
+                    ),
+                    Err(_) => (),
+                }
+            }
+
+            // The larger of the two measurements is reported.
+            let drift_ms = match (head_behind_ms, ntp_offset_ms) {
+                (Some(behind_ms), Some(offset_ms)) if behind_ms > offset_ms.abs() => {
+                    Some(behind_ms)
+                }
+                (_, Some(offset_ms)) => Some(offset_ms),
+                (behind_ms, None) => behind_ms,
+            };
+
+            // Without a time source, signing stays as it is: it is not resumed until the clock is
+            // known to have recovered, and not paused for beacon nodes which may just be offline.
+            let drift_ms = match drift_ms {
+                Some(drift_ms) => drift_ms,
+                None => {
+                    warn!(
+                        log,
+                        "Unable to check the local clock";
+                        "msg" => "no beacon node or NTP server responded",
+                        "signing_paused" => validator_store.is_signing_paused(),
+                    );
+                    continue;
+                }
+            };
+            metrics::set_gauge(&metrics::CLOCK_DRIFT, drift_ms);
+
+            let exceeded = drift_ms.abs() as u128 > max_drift.as_millis();
+            if exceeded {
+                warn!(
+                    log,
+                    "Local clock has drifted";
+                    "msg" => "check that the system clock is synchronized, e.g. with NTP",
+                    "signing_paused" => pause_signing,
+                    "max_drift_ms" => max_drift.as_millis() as u64,
+                    "drift_ms" => drift_ms,
+                );
+            }
+
+            if pause_signing {
+                let was_paused = pause_reason.is_some();
+                pause_reason = next_pause_reason(
+                    pause_reason,
+                    head_behind_ms,
+                    ntp_offset_ms,
+                    max_drift.as_millis() as i64,
+                );
+                validator_store.set_signing_paused(pause_reason.is_some());
+                if was_paused && pause_reason.is_none() {
+                    info!(
+                        log,
+                        "Local clock has recovered, resuming signing";
+                        "drift_ms" => drift_ms,
+                    );
+                } else if pause_reason == Some(PauseReason::Ntp) && !exceeded {
+                    info!(
+                        log,
+                        "Signing remains paused until the NTP server is reachable";
+                        "msg" => "the beacon nodes can not show that the clock is no longer ahead",
+                        "server" => ntp_server.as_deref(),
+                    );
+                }
+            }
+        }
+    };
+
+    executor.spawn(interval_fut, "clock_drift");
+    Ok(())
+}
+
+/// Returns the reason signing is paused after a check which measured the local clock to be
+/// `head_behind_ms` behind the head of the beacon nodes and `ntp_offset_ms` from the NTP server,
+/// given the reason it was `paused` before the check.
+///
+/// A pause is only lifted by a measurement which could have detected its cause. The head of the
+/// beacon nodes never shows that the local clock is ahead, so it is no evidence that a clock which
+/// the NTP server showed to be ahead has recovered.
+fn next_pause_reason(
+    paused: Option<PauseReason>,
+    head_behind_ms: Option<i64>,
+    ntp_offset_ms: Option<i64>,
+    max_drift_ms: i64,
+) -> Option<PauseReason> {
+    let exceeds = |drift_ms: i64| drift_ms.abs() > max_drift_ms;
+
+    if ntp_offset_ms.map_or(false, exceeds) {
+        Some(PauseReason::Ntp)
+    } else if head_behind_ms.map_or(false, exceeds) {
+        Some(PauseReason::BeaconNode)
+    } else {
+        match paused {
+            Some(PauseReason::Ntp) if ntp_offset_ms.is_none() => paused,
+            Some(PauseReason::BeaconNode)
+                if ntp_offset_ms.is_none() && head_behind_ms.is_none() =>
+            {
+                paused
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Queries the NTP server at `server` with SNTP, returning the offset of its clock from the local
+/// clock in milliseconds. The offset is positive if the local clock is behind.
+fn ntp_offset_ms(server: &str, timeout: Duration) -> Result<i64, String> {
+    let address = (server, NTP_PORT)
+        .to_socket_addrs()
+        .map_err(|e| format!("Unable to resolve {}: {:?}", server, e))?
+        .next()
+        .ok_or_else(|| format!("No address for {}", server))?;
+    let bind_address = if address.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let socket = UdpSocket::bind(bind_address).map_err(|e| format!("{:?}", e))?;
+    socket
+        .set_read_timeout(Some(timeout))
+        .map_err(|e| format!("{:?}", e))?;
+    socket.connect(address).map_err(|e| format!("{:?}", e))?;
+
+    // Version 4 in client mode (`0x23`), with the time of the request as the transmit timestamp.
+    let mut request = [0; 48];
+    request[0] = 0x23;
+    let sent = unix_now();
+    let transmit_timestamp = to_ntp_timestamp(sent);
+    request[40..48].copy_from_slice(&transmit_timestamp);
+    socket.send(&request).map_err(|e| format!("{:?}", e))?;
+
+    let mut response = [0; 48];
+    let len = socket.recv(&mut response).map_err(|e| format!("{:?}", e))?;
+    let received = unix_now();
+
+    let (server_received, server_sent) = parse_ntp_response(&response[..len], &transmit_timestamp)?;
+
+    Ok(clock_offset_ms(
+        sent,
+        server_received,
+        server_sent,
+        received,
+    ))
+}
+
+/// Returns the times at which the server received the request and sent the `response`.
+///
+/// The response must be in server mode and echo the `transmit_timestamp` of the request as its
+/// origin timestamp, so that a stale or spoofed response is not mistaken for the answer.
+fn parse_ntp_response(
+    response: &[u8],
+    transmit_timestamp: &[u8; 8],
+) -> Result<(Duration, Duration), String> {
+    if response.len() < 48 || response[0] & 0b111 != 4 {
+        return Err("Invalid response".to_string());
+    }
+    if response[24..32] != transmit_timestamp[..] {
+        return Err("Response is not for the request".to_string());
+    }
+    let server_received = from_ntp_timestamp(&response[32..40])
+        .ok_or_else(|| "Invalid receive timestamp".to_string())?;
+    let server_sent = from_ntp_timestamp(&response[40..48])
+        .ok_or_else(|| "Invalid transmit timestamp".to_string())?;
+
+    Ok((server_received, server_sent))
+}
+
+/// The offset of the server clock from the local clock, given the local times at which the
+/// request was `sent` and the response `received`, and the server times in between.
+fn clock_offset_ms(
+    sent: Duration,
+    server_received: Duration,
+    server_sent: Duration,
+    received: Duration,
+) -> i64 {
+    let ms = |duration: Duration| duration.as_millis() as i64;
+    ((ms(server_received) - ms(sent)) + (ms(server_sent) - ms(received))) / 2
+}
+
+fn unix_now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// Encodes the time since the Unix epoch as an NTP timestamp, which is the seconds since the NTP
+/// epoch and a 32-bit fraction of a second.
+fn to_ntp_timestamp(unix: Duration) -> [u8; 8] {
+    let secs = (unix.as_secs() + NTP_UNIX_OFFSET_SECS) as u32;
+    let fraction = ((u64::from(unix.subsec_nanos()) << 32) / 1_000_000_000) as u32;
+    let mut bytes = [0; 8];
+    bytes[..4].copy_from_slice(&secs.to_be_bytes());
+    bytes[4..].copy_from_slice(&fraction.to_be_bytes());
+    bytes
+}
+
+/// Decodes an NTP timestamp as the time since the Unix epoch, if it is after the Unix epoch.
+fn from_ntp_timestamp(bytes: &[u8]) -> Option<Duration> {
+    let mut secs = [0; 4];
+    let mut fraction = [0; 4];
+    secs.copy_from_slice(bytes.get(..4)?);
+    fraction.copy_from_slice(bytes.get(4..8)?);
+
+    let secs = u64::from(u32::from_be_bytes(secs)).checked_sub(NTP_UNIX_OFFSET_SECS)?;
+    let nanos = (u64::from(u32::from_be_bytes(fraction)) * 1_000_000_000) >> 32;
+    Some(Duration::new(secs, nanos as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ntp_timestamps() {
+        let unix = Duration::from_millis(1_600_000_000_250);
+        let bytes = to_ntp_timestamp(unix);
+        assert_eq!(
+            from_ntp_timestamp(&bytes).unwrap().as_millis(),
+            1_600_000_000_250
+        );
+        assert_eq!(from_ntp_timestamp(&[0; 8]), None);
+    }
+
+    #[test]
+    fn clock_offsets() {
+        let secs = Duration::from_secs;
+        // The local clock is 10 seconds behind, with a round trip of 2 seconds.
+        assert_eq!(
+            clock_offset_ms(secs(100), secs(111), secs(111), secs(102)),
+            10_000
+        );
+        // The local clock is 5 seconds ahead.
+        assert_eq!(
+            clock_offset_ms(secs(100), secs(96), secs(96), secs(102)),
+            -5_000
+        );
+    }
+
+    #[test]
+    fn ntp_responses_must_answer_the_request() {
+        let transmit_timestamp = to_ntp_timestamp(Duration::from_secs(1_600_000_000));
+        let mut response = [0; 48];
+        response[0] = 0x24;
+        response[24..32].copy_from_slice(&transmit_timestamp);
+        response[32..40].copy_from_slice(&to_ntp_timestamp(Duration::from_secs(1_600_000_010)));
+        response[40..48].copy_from_slice(&to_ntp_timestamp(Duration::from_secs(1_600_000_011)));
+
+        assert_eq!(
+            parse_ntp_response(&response, &transmit_timestamp),
+            Ok((
+                Duration::from_secs(1_600_000_010),
+                Duration::from_secs(1_600_000_011)
+            ))
+        );
+
+        let other_request = to_ntp_timestamp(Duration::from_secs(1_600_000_001));
+        assert!(parse_ntp_response(&response, &other_request).is_err());
+        assert!(parse_ntp_response(&response[..47], &transmit_timestamp).is_err());
+
+        // A client mode packet is not a response.
+        response[0] = 0x23;
+        assert!(parse_ntp_response(&response, &transmit_timestamp).is_err());
+    }
+
+    #[test]
+    fn pauses_are_lifted_by_their_time_source() {
+        use PauseReason::*;
+        let max = 500;
+
+        // Either source may pause signing.
+        assert_eq!(next_pause_reason(None, Some(0), Some(-600), max), Some(Ntp));
+        assert_eq!(
+            next_pause_reason(None, Some(600), None, max),
+            Some(BeaconNode)
+        );
+        assert_eq!(next_pause_reason(None, Some(0), Some(100), max), None);
+
+        // The head can not show that a clock which is ahead has recovered.
+        assert_eq!(next_pause_reason(Some(Ntp), Some(0), None, max), Some(Ntp));
+        assert_eq!(next_pause_reason(Some(Ntp), None, None, max), Some(Ntp));
+        assert_eq!(next_pause_reason(Some(Ntp), Some(0), Some(100), max), None);
+
+        // A clock which was behind the head may be shown to have recovered by either source.
+        assert_eq!(
+            next_pause_reason(Some(BeaconNode), Some(0), None, max),
+            None
+        );
+        assert_eq!(
+            next_pause_reason(Some(BeaconNode), None, Some(0), max),
+            None
+        );
+        assert_eq!(
+            next_pause_reason(Some(BeaconNode), None, None, max),
+            Some(BeaconNode)
+        );
+    }
+}
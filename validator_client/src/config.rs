@@ -1,14 +1,21 @@
+use crate::graffiti::{GraffitiRotation, GraffitiTemplate};
 use clap::ArgMatches;
 use clap_utils::{parse_optional, parse_path_with_default_in_home_dir};
 use serde_derive::{Deserialize, Serialize};
+use std::net::Ipv4Addr;
 use std::path::PathBuf;
-use types::{Graffiti, GRAFFITI_BYTES_LEN};
+use std::str::FromStr;
+use std::time::Duration;
+use types::Address;
 
 pub const DEFAULT_HTTP_SERVER: &str = "http://localhost:5052/";
 pub const DEFAULT_DATA_DIR: &str = ".lighthouse/validators";
 pub const DEFAULT_SECRETS_DIR: &str = ".lighthouse/secrets";
+pub const DEFAULT_METRICS_PORT: u16 = 5064;
+pub const DEFAULT_HTTP_API_PORT: u16 = 5062;
+pub const DEFAULT_MAX_CLOCK_DRIFT_MS: u64 = 500;
 /// Path to the slashing protection database within the datadir.
-pub const SLASHING_PROTECTION_FILENAME: &str = "slashing_protection.sqlite";
+pub use slashing_protection::SLASHING_PROTECTION_FILENAME;
 
 /// Stores the core configuration for this validator instance.
 #[derive(Clone, Serialize, Deserialize)]
@@ -17,10 +24,10 @@ pub struct Config {
     pub data_dir: PathBuf,
     /// The directory containing the passwords to unlock validator keystores.
     pub secrets_dir: PathBuf,
-    /// The http endpoint of the beacon node API.
+    /// The http endpoints of the beacon node APIs, in order of preference.
     ///
     /// Should be similar to `http://localhost:8080`
-    pub http_server: String,
+    pub beacon_nodes: Vec<String>,
     /// If true, the validator client will still poll for duties and produce blocks even if the
     /// beacon node is not synced at startup.
     pub allow_unsynced_beacon_node: bool,
@@ -28,8 +35,45 @@ pub struct Config {
     pub strict_lockfiles: bool,
     /// If true, don't scan the validators dir for new keystores.
     pub disable_auto_discover: bool,
-    /// Graffiti to be inserted everytime we create a block.
-    pub graffiti: Option<Graffiti>,
+    /// If true, associate the slashing protection database with the network of the beacon node,
+    /// even if it was previously used on a different network.
+    pub force_network_change: bool,
+    /// The URL of a remote slashing protection service.
+    ///
+    /// If `None`, the local slashing protection database in `data_dir` is used.
+    pub slashing_protection_url: Option<String>,
+    /// Graffiti to be inserted everytime we create a block, rendered from the next template in
+    /// the rotation.
+    ///
+    /// If empty, the beacon node chooses the graffiti.
+    pub graffiti: GraffitiRotation,
+    /// The address which the fees of blocks are paid to, for validators without their own
+    /// `suggested_fee_recipient`.
+    pub default_fee_recipient: Option<Address>,
+    /// The gas limit requested for execution payloads.
+    pub gas_limit: Option<u64>,
+    /// If set, attestations are not signed once this percentage of their slot has elapsed.
+    pub attestation_cutoff_percent: Option<u64>,
+    /// If set, blocks are not signed or published once this much of their slot has elapsed.
+    pub block_cutoff: Option<Duration>,
+    /// The host of an NTP server to compare the local clock with.
+    pub ntp_server: Option<String>,
+    /// The drift of the local clock above which a warning is logged.
+    pub max_clock_drift: Duration,
+    /// If true, nothing is signed whilst the local clock has drifted more than `max_clock_drift`.
+    pub pause_signing_on_clock_drift: bool,
+    /// If true, serve Prometheus metrics over HTTP.
+    pub metrics_enabled: bool,
+    /// The address the metrics server listens on.
+    pub metrics_listen_address: Ipv4Addr,
+    /// The port the metrics server listens on.
+    pub metrics_port: u16,
+    /// If true, serve the API for managing validators over HTTP.
+    pub http_api_enabled: bool,
+    /// The address the API server listens on.
+    pub http_api_listen_address: Ipv4Addr,
+    /// The port the API server listens on.
+    pub http_api_port: u16,
 }
 
 impl Default for Config {
@@ -44,11 +88,26 @@ impl Default for Config {
         Self {
             data_dir,
             secrets_dir,
-            http_server: DEFAULT_HTTP_SERVER.to_string(),
+            beacon_nodes: vec![DEFAULT_HTTP_SERVER.to_string()],
             allow_unsynced_beacon_node: false,
             strict_lockfiles: false,
             disable_auto_discover: false,
-            graffiti: None,
+            force_network_change: false,
+            slashing_protection_url: None,
+            graffiti: GraffitiRotation::default(),
+            default_fee_recipient: None,
+            gas_limit: None,
+            attestation_cutoff_percent: None,
+            block_cutoff: None,
+            ntp_server: None,
+            max_clock_drift: Duration::from_millis(DEFAULT_MAX_CLOCK_DRIFT_MS),
+            pause_signing_on_clock_drift: false,
+            metrics_enabled: false,
+            metrics_listen_address: Ipv4Addr::new(127, 0, 0, 1),
+            metrics_port: DEFAULT_METRICS_PORT,
+            http_api_enabled: false,
+            http_api_listen_address: Ipv4Addr::new(127, 0, 0, 1),
+            http_api_port: DEFAULT_HTTP_API_PORT,
         }
     }
 }
@@ -72,38 +131,112 @@ impl Config {
             ));
         }
 
-        if let Some(server) = parse_optional(cli_args, "server")? {
-            config.http_server = server;
+        if let Some(servers) = parse_optional::<String>(cli_args, "server")? {
+            config.beacon_nodes = servers
+                .split(',')
+                .filter(|server| !server.is_empty())
+                .map(String::from)
+                .collect();
+            if config.beacon_nodes.is_empty() {
+                return Err("--server must contain at least one address".to_string());
+            }
         }
 
         config.allow_unsynced_beacon_node = cli_args.is_present("allow-unsynced");
         config.strict_lockfiles = cli_args.is_present("strict-lockfiles");
         config.disable_auto_discover = cli_args.is_present("disable-auto-discover");
+        config.force_network_change = cli_args.is_present("force-network-change");
+        config.slashing_protection_url = parse_optional(cli_args, "slashing-protection-url")?;
 
         if let Some(secrets_dir) = parse_optional(cli_args, "secrets-dir")? {
             config.secrets_dir = secrets_dir;
         }
 
-        if let Some(input_graffiti) = cli_args.value_of("graffiti") {
-            let graffiti_bytes = input_graffiti.as_bytes();
-            if graffiti_bytes.len() > GRAFFITI_BYTES_LEN {
+        if let Some(percent) = parse_optional::<u64>(cli_args, "attestation-cutoff")? {
+            if percent == 0 || percent > 100 {
                 return Err(format!(
-                    "Your graffiti is too long! {} bytes maximum!",
-                    GRAFFITI_BYTES_LEN
+                    "--attestation-cutoff must be between 1 and 100, not {}",
+                    percent
                 ));
-            } else {
-                // Default graffiti to all 0 bytes.
-                let mut graffiti = Graffiti::default();
+            }
+            config.attestation_cutoff_percent = Some(percent);
+        }
 
-                // Copy the provided bytes over.
-                //
-                // Panic-free because `graffiti_bytes.len()` <= `GRAFFITI_BYTES_LEN`.
-                graffiti[..graffiti_bytes.len()].copy_from_slice(&graffiti_bytes);
+        config.block_cutoff =
+            parse_optional(cli_args, "block-cutoff-ms")?.map(Duration::from_millis);
 
-                config.graffiti = Some(graffiti);
-            }
+        config.ntp_server = parse_optional(cli_args, "ntp-server")?;
+
+        if let Some(max_drift) = parse_optional(cli_args, "max-clock-drift-ms")? {
+            config.max_clock_drift = Duration::from_millis(max_drift);
+        }
+
+        config.pause_signing_on_clock_drift = cli_args.is_present("pause-signing-on-clock-drift");
+
+        config.metrics_enabled = cli_args.is_present("metrics");
+
+        if let Some(address) = parse_optional(cli_args, "metrics-address")? {
+            config.metrics_listen_address = address;
+        }
+
+        if let Some(port) = parse_optional(cli_args, "metrics-port")? {
+            config.metrics_port = port;
+        }
+
+        config.http_api_enabled = cli_args.is_present("http");
+
+        if let Some(address) = parse_optional(cli_args, "http-address")? {
+            config.http_api_listen_address = address;
+        }
+
+        if let Some(port) = parse_optional(cli_args, "http-port")? {
+            config.http_api_port = port;
+        }
+
+        if let Some(templates) = cli_args.values_of("graffiti") {
+            let templates = templates
+                .map(GraffitiTemplate::from_str)
+                .collect::<Result<Vec<_>, _>>()?;
+            config.graffiti = GraffitiRotation::new(templates);
+        }
+
+        if let Some(address) = cli_args.value_of("suggested-fee-recipient") {
+            config.default_fee_recipient = Some(
+                parse_fee_recipient(address)
+                    .map_err(|e| format!("Invalid --suggested-fee-recipient: {}", e))?,
+            );
         }
 
+        config.gas_limit = parse_optional(cli_args, "gas-limit")?;
+
         Ok(config)
     }
 }
+
+/// Parses an execution address, which must be `0x` followed by 40 hex characters.
+pub fn parse_fee_recipient(s: &str) -> Result<Address, String> {
+    let hex = s
+        .strip_prefix("0x")
+        .ok_or_else(|| format!("{} does not start with 0x", s))?;
+    if hex.len() != 40 {
+        return Err(format!("{} is not 20 bytes long", s));
+    }
+    Address::from_str(hex).map_err(|e| format!("{} is not hex: {:?}", s, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fee_recipients() {
+        let address = "0x00000000219ab540356cbb839cbe05303d7705fa";
+        assert_eq!(
+            format!("{:?}", parse_fee_recipient(address).unwrap()),
+            address
+        );
+        assert!(parse_fee_recipient(&address[2..]).is_err());
+        assert!(parse_fee_recipient(&address[..40]).is_err());
+        assert!(parse_fee_recipient("0x00000000219ab540356cbb839cbe05303d7705fz").is_err());
+    }
+}
@@ -1,19 +1,23 @@
 use crate::{
-    block_service::BlockServiceNotification, is_synced::is_synced, validator_store::ValidatorStore,
+    beacon_node_fallback::BeaconNodeFallback,
+    block_service::{BlockProposer, BlockServiceNotification},
+    metrics,
+    validator_store::ValidatorStore,
 };
 use environment::RuntimeContext;
-use futures::channel::mpsc::Sender;
-use futures::{SinkExt, StreamExt};
+use futures::channel::{mpsc::Sender, oneshot};
+use futures::{pin_mut, select, SinkExt, StreamExt};
 use parking_lot::RwLock;
-use remote_beacon_node::{PublishStatus, RemoteBeaconNode};
+use remote_beacon_node::PublishStatus;
 use rest_types::{ValidatorDuty, ValidatorDutyBytes, ValidatorSubscription};
-use slog::{debug, error, trace, warn};
+use slog::{debug, error, info, trace, warn};
 use slot_clock::SlotClock;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::time::{interval_at, Duration, Instant};
+use tokio::time::{delay_for, interval, interval_at, Duration, Instant};
 use types::{ChainSpec, CommitteeIndex, Epoch, EthSpec, PublicKey, SelectionProof, Slot};
 
 /// Delay this period of time after the slot starts. This allows the node to process the new slot.
@@ -41,7 +45,7 @@ impl DutyAndProof {
     ///
     /// - `self.validator_pubkey` is not known in `validator_store`.
     /// - There's an arith error during computation.
-    pub fn compute_selection_proof<T: SlotClock + 'static, E: EthSpec>(
+    pub async fn compute_selection_proof<T: SlotClock + 'static, E: EthSpec>(
         &mut self,
         validator_store: &ValidatorStore<T, E>,
     ) -> Result<(), String> {
@@ -58,7 +62,8 @@ impl DutyAndProof {
 
         let selection_proof = validator_store
             .produce_selection_proof(&self.duty.validator_pubkey, slot)
-            .ok_or_else(|| "Failed to produce selection proof".to_string())?;
+            .await
+            .map_err(|e| format!("Failed to produce selection proof: {:?}", e))?;
 
         self.selection_proof = selection_proof
             .is_aggregator_from_modulo(modulo)
@@ -202,7 +207,7 @@ impl DutiesStore {
             .count()
     }
 
-    fn block_proposers(&self, slot: Slot, slots_per_epoch: u64) -> Vec<PublicKey> {
+    fn block_proposers(&self, slot: Slot, slots_per_epoch: u64) -> Vec<BlockProposer> {
         self.store
             .read()
             .iter()
@@ -213,7 +218,10 @@ impl DutiesStore {
 
                 validator_map.get(&epoch).and_then(|duties| {
                     if duties.duty.block_proposal_slots.as_ref()?.contains(&slot) {
-                        Some(duties.duty.validator_pubkey.clone())
+                        Some(BlockProposer {
+                            pubkey: duties.duty.validator_pubkey.clone(),
+                            validator_index: duties.duty.validator_index,
+                        })
                     } else {
                         None
                     }
@@ -254,64 +262,82 @@ impl DutiesStore {
         )
     }
 
-    fn insert<T: SlotClock + 'static, E: EthSpec>(
+    /// Returns `true` if duties equal to `duties`, ignoring proposal slots, are known for `epoch`.
+    fn is_known(&self, epoch: Epoch, duties: &DutyAndProof) -> bool {
+        self.store
+            .read()
+            .get(&duties.duty.validator_pubkey)
+            .and_then(|validator_map| validator_map.get(&epoch))
+            .map_or(false, |known_duties| {
+                known_duties.duty.eq_ignoring_proposal_slots(&duties.duty)
+            })
+    }
+
+    async fn insert<T: SlotClock + 'static, E: EthSpec>(
         &self,
         epoch: Epoch,
         mut duties: DutyAndProof,
         slots_per_epoch: u64,
         validator_store: &ValidatorStore<T, E>,
     ) -> Result<InsertOutcome, String> {
-        let mut store = self.store.write();
-
         if !duties_match_epoch(&duties.duty, epoch, slots_per_epoch) {
             return Ok(InsertOutcome::Invalid);
         }
 
-        // TODO: refactor with Entry.
+        // The selection proof is only computed for new or changed duties, and without holding the
+        // lock on the store, since signing may wait on a remote signer.
+        let mut has_selection_proof = false;
+        loop {
+            if !has_selection_proof && !self.is_known(epoch, &duties) {
+                duties.compute_selection_proof(validator_store).await?;
+                has_selection_proof = true;
+            }
 
-        if let Some(validator_map) = store.get_mut(&duties.duty.validator_pubkey) {
-            if let Some(known_duties) = validator_map.get_mut(&epoch) {
-                if known_duties.duty.eq_ignoring_proposal_slots(&duties.duty) {
-                    if known_duties.duty.block_proposal_slots == duties.duty.block_proposal_slots {
-                        Ok(InsertOutcome::Identical)
-                    } else if duties.duty.block_proposal_slots.is_some() {
-                        known_duties.duty.block_proposal_slots = duties.duty.block_proposal_slots;
-                        Ok(InsertOutcome::NewProposalSlots)
-                    } else {
-                        Ok(InsertOutcome::Invalid)
+            let mut store = self.store.write();
+
+            // TODO: refactor with Entry.
+
+            if let Some(validator_map) = store.get_mut(&duties.duty.validator_pubkey) {
+                if let Some(known_duties) = validator_map.get_mut(&epoch) {
+                    if known_duties.duty.eq_ignoring_proposal_slots(&duties.duty) {
+                        return if known_duties.duty.block_proposal_slots
+                            == duties.duty.block_proposal_slots
+                        {
+                            Ok(InsertOutcome::Identical)
+                        } else if duties.duty.block_proposal_slots.is_some() {
+                            known_duties.duty.block_proposal_slots =
+                                duties.duty.block_proposal_slots;
+                            Ok(InsertOutcome::NewProposalSlots)
+                        } else {
+                            Ok(InsertOutcome::Invalid)
+                        };
+                    } else if has_selection_proof {
+                        // Determine if a re-subscription is required.
+                        let should_resubscribe = !duties.subscription_eq(known_duties);
+
+                        // Replace the existing duties.
+                        *known_duties = duties;
+
+                        return Ok(InsertOutcome::Replaced { should_resubscribe });
                     }
-                } else {
-                    // Compute the selection proof.
-                    duties.compute_selection_proof(validator_store)?;
+                } else if has_selection_proof {
+                    validator_map.insert(epoch, duties);
 
-                    // Determine if a re-subscription is required.
-                    let should_resubscribe = !duties.subscription_eq(known_duties);
-
-                    // Replace the existing duties.
-                    *known_duties = duties;
-
-                    Ok(InsertOutcome::Replaced { should_resubscribe })
+                    return Ok(InsertOutcome::NewEpoch);
                 }
-            } else {
-                // Compute the selection proof.
-                duties.compute_selection_proof(validator_store)?;
+            } else if has_selection_proof {
+                let validator_pubkey = duties.duty.validator_pubkey.clone();
 
+                let mut validator_map = HashMap::new();
                 validator_map.insert(epoch, duties);
 
-                Ok(InsertOutcome::NewEpoch)
-            }
-        } else {
-            // Compute the selection proof.
-            duties.compute_selection_proof(validator_store)?;
-
-            let validator_pubkey = duties.duty.validator_pubkey.clone();
-
-            let mut validator_map = HashMap::new();
-            validator_map.insert(epoch, duties);
+                store.insert(validator_pubkey, validator_map);
 
-            store.insert(validator_pubkey, validator_map);
+                return Ok(InsertOutcome::NewValidator);
+            }
 
-            Ok(InsertOutcome::NewValidator)
+            // The known duties changed after they were checked, so the selection proof of these
+            // duties must now be computed.
         }
     }
 
@@ -328,7 +354,7 @@ impl DutiesStore {
 pub struct DutiesServiceBuilder<T, E: EthSpec> {
     validator_store: Option<ValidatorStore<T, E>>,
     slot_clock: Option<T>,
-    beacon_node: Option<RemoteBeaconNode<E>>,
+    beacon_nodes: Option<Arc<BeaconNodeFallback<T, E>>>,
     context: Option<RuntimeContext<E>>,
     allow_unsynced_beacon_node: bool,
 }
@@ -338,7 +364,7 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
         Self {
             validator_store: None,
             slot_clock: None,
-            beacon_node: None,
+            beacon_nodes: None,
             context: None,
             allow_unsynced_beacon_node: false,
         }
@@ -354,8 +380,8 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
         self
     }
 
-    pub fn beacon_node(mut self, beacon_node: RemoteBeaconNode<E>) -> Self {
-        self.beacon_node = Some(beacon_node);
+    pub fn beacon_nodes(mut self, beacon_nodes: Arc<BeaconNodeFallback<T, E>>) -> Self {
+        self.beacon_nodes = Some(beacon_nodes);
         self
     }
 
@@ -380,13 +406,14 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
                 slot_clock: self
                     .slot_clock
                     .ok_or_else(|| "Cannot build DutiesService without slot_clock")?,
-                beacon_node: self
-                    .beacon_node
-                    .ok_or_else(|| "Cannot build DutiesService without beacon_node")?,
+                beacon_nodes: self
+                    .beacon_nodes
+                    .ok_or_else(|| "Cannot build DutiesService without beacon_nodes")?,
                 context: self
                     .context
                     .ok_or_else(|| "Cannot build DutiesService without runtime_context")?,
                 allow_unsynced_beacon_node: self.allow_unsynced_beacon_node,
+                duties_stream_connected: AtomicBool::new(false),
             }),
         })
     }
@@ -395,13 +422,15 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
 /// Helper to minimise `Arc` usage.
 pub struct Inner<T, E: EthSpec> {
     store: Arc<DutiesStore>,
-    validator_store: ValidatorStore<T, E>,
+    pub(crate) validator_store: ValidatorStore<T, E>,
     pub(crate) slot_clock: T,
-    pub(crate) beacon_node: RemoteBeaconNode<E>,
+    pub(crate) beacon_nodes: Arc<BeaconNodeFallback<T, E>>,
     context: RuntimeContext<E>,
     /// If true, the duties service will poll for duties from the beacon node even if it is not
     /// synced.
     allow_unsynced_beacon_node: bool,
+    /// True whilst the beacon node is pushing duty changes to us via the duties stream.
+    duties_stream_connected: AtomicBool,
 }
 
 /// Maintains a store of the duties for all voting validators in the `validator_store`.
@@ -409,6 +438,9 @@ pub struct Inner<T, E: EthSpec> {
 /// Polls the beacon node at the start of each slot, collecting duties for the current and next
 /// epoch. The duties service notifies the block production service to run each time it completes,
 /// so it *must* be run every slot.
+///
+/// Whilst the beacon node is pushing duty changes via its duties stream, duties are only polled
+/// at the start of each epoch.
 pub struct DutiesService<T, E: EthSpec> {
     inner: Arc<Inner<T, E>>,
 }
@@ -445,11 +477,11 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
         self.store.attester_count(epoch)
     }
 
-    /// Returns the pubkeys of the validators which are assigned to propose in the given slot.
+    /// Returns the validators which are assigned to propose in the given slot.
     ///
     /// It is possible that multiple validators have an identical proposal slot, however that is
     /// likely the result of heavy forking (lol) or inconsistent beacon node connections.
-    pub fn block_proposers(&self, slot: Slot) -> Vec<PublicKey> {
+    pub fn block_proposers(&self, slot: Slot) -> Vec<BlockProposer> {
         self.store.block_proposers(slot, E::slots_per_epoch())
     }
 
@@ -459,9 +491,13 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
     }
 
     /// Start the service that periodically polls the beacon node for validator duties.
+    ///
+    /// The duties of the current and next epoch are fetched immediately, after which `warm_up_tx`
+    /// is notified, even if the duties could not be fetched.
     pub fn start_update_service(
         self,
         mut block_service_tx: Sender<BlockServiceNotification>,
+        warm_up_tx: oneshot::Sender<()>,
         spec: &ChainSpec,
     ) -> Result<(), String> {
         let duration_to_next_slot = self
@@ -478,20 +514,23 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
             )
         };
 
-        // Run an immediate update before starting the updater service.
+        // Run an immediate update before starting the updater service, so that duties early in
+        // the current epoch aren't missed whilst waiting for the first slot tick.
         let duties_service = self.clone();
-        let mut block_service_tx_clone = block_service_tx.clone();
+        let block_service_tx_clone = block_service_tx.clone();
         self.inner
             .context
             .executor
             .runtime_handle()
-            .spawn(async move { duties_service.do_update(&mut block_service_tx_clone).await });
+            .spawn(duties_service.warm_up(block_service_tx_clone, warm_up_tx));
 
         let executor = self.inner.context.executor.clone();
 
+        executor.spawn(self.clone().run_duties_stream(), "duties_stream");
+
         let interval_fut = async move {
             while interval.next().await.is_some() {
-                self.clone().do_update(&mut block_service_tx).await;
+                self.clone().do_update(&mut block_service_tx, false).await;
             }
         };
 
@@ -500,13 +539,42 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
         Ok(())
     }
 
+    /// Fetch the duties of the current and next epoch, regardless of the duties stream, and
+    /// notify `warm_up_tx` once they are known.
+    async fn warm_up(
+        self,
+        mut block_service_tx: Sender<BlockServiceNotification>,
+        warm_up_tx: oneshot::Sender<()>,
+    ) {
+        self.clone().do_update(&mut block_service_tx, true).await;
+
+        if let Some(slot) = self.slot_clock.now() {
+            let epoch = slot.epoch(E::slots_per_epoch());
+            info!(
+                self.context.log(),
+                "Fetched initial duties";
+                "current_epoch" => epoch.as_u64(),
+                "attesters" => self.attester_count(epoch),
+                "proposers" => self.proposer_count(epoch),
+            );
+        }
+
+        // Duties which couldn't be fetched are simply absent, so the receiver may always proceed.
+        let _ = warm_up_tx.send(());
+    }
+
     /// Attempt to download the duties of all managed validators for this epoch and the next.
-    async fn do_update(self, block_service_tx: &mut Sender<BlockServiceNotification>) {
+    ///
+    /// If `force_poll` is true, duties are polled even whilst the duties stream is connected.
+    async fn do_update(
+        self,
+        block_service_tx: &mut Sender<BlockServiceNotification>,
+        force_poll: bool,
+    ) {
         let log = self.context.log();
 
-        if !is_synced(&self.beacon_node, &self.slot_clock, None).await
-            && !self.allow_unsynced_beacon_node
-        {
+        self.beacon_nodes.update_all_candidates().await;
+        if self.beacon_nodes.num_synced() == 0 && !self.allow_unsynced_beacon_node {
             return;
         }
 
@@ -532,14 +600,22 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
             self.store.prune(prune_below);
         }
 
+        // Whilst the beacon node is pushing duty changes to us, we only need to poll at the start
+        // of each epoch.
+        let poll_duties = force_poll
+            || !self.duties_stream_connected.load(Ordering::Relaxed)
+            || slot % E::slots_per_epoch() == 0;
+
         // Update duties for the current epoch, but keep running if there's an error:
         // block production or the next epoch update could still succeed.
-        if let Err(e) = self.clone().update_epoch(current_epoch).await {
-            error!(
-                log,
-                "Failed to get current epoch duties";
-                "http_error" => format!("{:?}", e)
-            );
+        if poll_duties {
+            if let Err(e) = self.clone().update_epoch(current_epoch).await {
+                error!(
+                    log,
+                    "Failed to get current epoch duties";
+                    "http_error" => format!("{:?}", e)
+                );
+            }
         }
 
         // Notify the block service to produce a block.
@@ -558,23 +634,157 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
         };
 
         // Update duties for the next epoch.
-        if let Err(e) = self.clone().update_epoch(current_epoch + 1).await {
-            error!(
-                log,
-                "Failed to get next epoch duties";
-                "http_error" => format!("{:?}", e)
-            );
+        if poll_duties {
+            if let Err(e) = self.clone().update_epoch(current_epoch + 1).await {
+                error!(
+                    log,
+                    "Failed to get next epoch duties";
+                    "http_error" => format!("{:?}", e)
+                );
+            }
+        }
+    }
+
+    /// Keep the duties stream of the beacon node open, updating duties each time the beacon node
+    /// reports that they have changed. If the stream cannot be opened or is closed, duties are
+    /// polled every slot until it is re-opened, which is re-tried once per epoch.
+    async fn run_duties_stream(self) {
+        let log = self.context.log().clone();
+        let retry_delay = self.slot_clock.slot_duration() * E::slots_per_epoch() as u32;
+
+        loop {
+            match self.clone().follow_duties_stream().await {
+                Ok(()) => warn!(
+                    log,
+                    "Duties stream closed by beacon node";
+                    "info" => "polling for duties every slot"
+                ),
+                Err(e) => warn!(
+                    log,
+                    "Unable to follow duties stream";
+                    "error" => e,
+                    "info" => "polling for duties every slot"
+                ),
+            }
+
+            self.duties_stream_connected.store(false, Ordering::Relaxed);
+            delay_for(retry_delay).await;
+        }
+    }
+
+    /// Register all managed validators for the duties stream and update duties for each event
+    /// received, until the stream ends.
+    ///
+    /// Validators may be added, removed, enabled or disabled whilst the stream is open, so they are
+    /// registered again whenever they change, checked once per slot.
+    async fn follow_duties_stream(self) -> Result<(), String> {
+        let log = self.context.log().clone();
+        let beacon_node = self
+            .beacon_nodes
+            .preferred()
+            .ok_or_else(|| "No beacon nodes".to_string())?;
+        let validator = beacon_node.http.validator();
+
+        let mut registered = self.validator_store.voting_pubkeys();
+        // The beacon node issues the token which identifies this client's stream, and drops it
+        // when the stream closes.
+        let api_token = validator
+            .register_duties_stream(None, &registered)
+            .await
+            .map_err(|e| format!("Failed to register for duties stream: {:?}", e))?;
+        let events = validator
+            .stream_duties(&api_token)
+            .await
+            .map_err(|e| format!("Failed to open duties stream: {:?}", e))?;
+        pin_mut!(events);
+
+        self.duties_stream_connected.store(true, Ordering::Relaxed);
+        info!(log, "Connected to duties stream"; "endpoint" => beacon_node.http.server_url());
+
+        // Duties may have changed whilst the stream was being opened.
+        if let Some(slot) = self.slot_clock.now() {
+            self.clone()
+                .update_duties_after_change(slot.epoch(E::slots_per_epoch()))
+                .await;
+        }
+
+        let mut events = events.fuse();
+        let mut slots = interval(self.slot_clock.slot_duration()).fuse();
+        loop {
+            select! {
+                event = events.next() => {
+                    let event = match event {
+                        Some(Ok(event)) => event,
+                        Some(Err(e)) => return Err(format!("Duties stream failed: {:?}", e)),
+                        None => return Ok(()),
+                    };
+
+                    debug!(
+                        log,
+                        "Beacon node reported changed duties";
+                        "epoch" => event.epoch.as_u64(),
+                        "dependent_root" => format!("{:?}", event.dependent_root),
+                        "validators" => event.validator_indices.len(),
+                    );
+
+                    self.clone().update_duties_after_change(event.epoch).await;
+                }
+                _ = slots.next() => {
+                    let pubkeys = self.validator_store.voting_pubkeys();
+                    if same_validators(&pubkeys, &registered) {
+                        continue;
+                    }
+
+                    validator
+                        .register_duties_stream(Some(&api_token), &pubkeys)
+                        .await
+                        .map_err(|e| format!("Failed to register for duties stream: {:?}", e))?;
+                    debug!(
+                        log,
+                        "Registered changed validators for duties stream";
+                        "validators" => pubkeys.len(),
+                    );
+                    registered = pubkeys;
+
+                    // The stream only reports changes, so the duties of new validators must be
+                    // downloaded.
+                    if let Some(slot) = self.slot_clock.now() {
+                        self.clone()
+                            .update_duties_after_change(slot.epoch(E::slots_per_epoch()))
+                            .await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Update the duties for `epoch` and the next epoch, logging any errors.
+    async fn update_duties_after_change(self, epoch: Epoch) {
+        for &epoch in &[epoch, epoch + 1] {
+            if let Err(e) = self.clone().update_epoch(epoch).await {
+                error!(
+                    self.context.log(),
+                    "Failed to update changed duties";
+                    "http_error" => e,
+                    "epoch" => epoch.as_u64(),
+                );
+            }
         }
     }
 
     /// Attempt to download the duties of all managed validators for the given `epoch`.
     async fn update_epoch(self, epoch: Epoch) -> Result<(), String> {
         let pubkeys = self.validator_store.voting_pubkeys();
+        let pubkeys = &pubkeys;
         let all_duties = self
-            .beacon_node
-            .http
-            .validator()
-            .get_duties(epoch, pubkeys.as_slice())
+            .beacon_nodes
+            .first_success("get_duties", |beacon_node| async move {
+                beacon_node
+                    .http
+                    .validator()
+                    .get_duties(epoch, pubkeys.as_slice())
+                    .await
+            })
             .await
             .map_err(move |e| format!("Failed to get duties for epoch {}: {:?}", epoch, e))?;
 
@@ -589,72 +799,87 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
 
         // For each of the duties, attempt to insert them into our local store and build a
         // list of new or changed selections proofs for any aggregating validators.
-        let validator_subscriptions = all_duties
-            .into_iter()
-            .filter_map(|remote_duties| {
-                // Convert the remote duties into our local representation.
-                let duties: DutyAndProof = remote_duties
-                    .clone()
-                    .try_into()
-                    .map_err(|e| {
-                        error!(
-                            log,
-                            "Unable to convert remote duties";
-                            "error" => e
-                        )
-                    })
-                    .ok()?;
-
-                let validator_pubkey = duties.duty.validator_pubkey.clone();
+        let mut validator_subscriptions = vec![];
+        for remote_duties in all_duties {
+            // Convert the remote duties into our local representation.
+            let duties: DutyAndProof = match remote_duties.clone().try_into() {
+                Ok(duties) => duties,
+                Err(e) => {
+                    error!(
+                        log,
+                        "Unable to convert remote duties";
+                        "error" => e
+                    );
+                    continue;
+                }
+            };
 
-                // Attempt to update our local store.
-                let outcome = self
-                    .store
-                    .insert(epoch, duties, E::slots_per_epoch(), &self.validator_store)
-                    .map_err(|e| {
-                        error!(
-                            log,
-                            "Unable to store duties";
-                            "error" => e
-                        )
-                    })
-                    .ok()?;
+            let validator_pubkey = duties.duty.validator_pubkey.clone();
 
-                match &outcome {
-                    InsertOutcome::NewValidator => {
-                        debug!(
-                            log,
-                            "First duty assignment for validator";
-                            "proposal_slots" => format!("{:?}", &remote_duties.block_proposal_slots),
-                            "attestation_slot" => format!("{:?}", &remote_duties.attestation_slot),
-                            "validator" => format!("{:?}", &remote_duties.validator_pubkey)
-                        );
-                        new_validator += 1;
-                    }
-                    InsertOutcome::NewProposalSlots => new_proposal_slots += 1,
-                    InsertOutcome::NewEpoch => new_epoch += 1,
-                    InsertOutcome::Identical => identical += 1,
-                    InsertOutcome::Replaced { .. } => replaced += 1,
-                    InsertOutcome::Invalid => invalid += 1,
-                };
-
-                // The selection proof is computed on `store.insert`, so it's necessary to check
-                // with the store that the validator is an aggregator.
-                let is_aggregator = self.store.is_aggregator(&validator_pubkey, epoch)?;
-
-                if outcome.is_subscription_candidate() {
-                    Some(ValidatorSubscription {
-                        validator_index: remote_duties.validator_index?,
-                        attestation_committee_index: remote_duties.attestation_committee_index?,
-                        slot: remote_duties.attestation_slot?,
-                        committee_count_at_slot: remote_duties.committee_count_at_slot?,
+            // Attempt to update our local store.
+            let outcome = match self
+                .store
+                .insert(epoch, duties, E::slots_per_epoch(), &self.validator_store)
+                .await
+            {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    error!(
+                        log,
+                        "Unable to store duties";
+                        "error" => e
+                    );
+                    continue;
+                }
+            };
+
+            match &outcome {
+                InsertOutcome::NewValidator => {
+                    debug!(
+                        log,
+                        "First duty assignment for validator";
+                        "proposal_slots" => format!("{:?}", &remote_duties.block_proposal_slots),
+                        "attestation_slot" => format!("{:?}", &remote_duties.attestation_slot),
+                        "validator" => format!("{:?}", &remote_duties.validator_pubkey)
+                    );
+                    new_validator += 1;
+                }
+                InsertOutcome::NewProposalSlots => new_proposal_slots += 1,
+                InsertOutcome::NewEpoch => new_epoch += 1,
+                InsertOutcome::Identical => identical += 1,
+                InsertOutcome::Replaced { .. } => replaced += 1,
+                InsertOutcome::Invalid => invalid += 1,
+            };
+
+            // The selection proof is computed on `store.insert`, so it's necessary to check
+            // with the store that the validator is an aggregator.
+            let is_aggregator = match self.store.is_aggregator(&validator_pubkey, epoch) {
+                Some(is_aggregator) => is_aggregator,
+                None => continue,
+            };
+
+            if outcome.is_subscription_candidate() {
+                if let (
+                    Some(validator_index),
+                    Some(attestation_committee_index),
+                    Some(slot),
+                    Some(committee_count_at_slot),
+                ) = (
+                    remote_duties.validator_index,
+                    remote_duties.attestation_committee_index,
+                    remote_duties.attestation_slot,
+                    remote_duties.committee_count_at_slot,
+                ) {
+                    validator_subscriptions.push(ValidatorSubscription {
+                        validator_index,
+                        attestation_committee_index,
+                        slot,
+                        committee_count_at_slot,
                         is_aggregator,
-                    })
-                } else {
-                    None
+                    });
                 }
-            })
-            .collect::<Vec<_>>();
+            }
+        }
 
         if invalid > 0 {
             error!(
@@ -676,6 +901,17 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
             "epoch" => format!("{}", epoch)
         );
 
+        for (outcome, count) in &[
+            ("identical", identical),
+            ("new_epoch", new_epoch),
+            ("new_proposal_slots", new_proposal_slots),
+            ("new_validator", new_validator),
+            ("replaced", replaced),
+            ("invalid", invalid),
+        ] {
+            metrics::inc_counter_vec_by(&metrics::DUTIES_OBTAINED, &[*outcome], *count);
+        }
+
         if replaced > 0 {
             warn!(
                 log,
@@ -693,10 +929,16 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
 
             Ok(())
         } else {
-            self.beacon_node
-                .http
-                .validator()
-                .subscribe(validator_subscriptions)
+            // Each beacon node must be subscribed, since any of them may produce the aggregates.
+            let validator_subscriptions = &validator_subscriptions;
+            self.beacon_nodes
+                .broadcast("subscribe", |beacon_node| async move {
+                    beacon_node
+                        .http
+                        .validator()
+                        .subscribe(validator_subscriptions.clone())
+                        .await
+                })
                 .await
                 .map_err(|e| format!("Failed to subscribe validators: {:?}", e))
                 .map(move |status| {
@@ -732,3 +974,8 @@ fn duties_match_epoch(duties: &ValidatorDuty, epoch: Epoch, slots_per_epoch: u64
                 .all(|slot| slot.epoch(slots_per_epoch) == epoch)
         })
 }
+
+/// Returns `true` if `a` and `b` hold the same public keys, in any order.
+fn same_validators(a: &[PublicKey], b: &[PublicKey]) -> bool {
+    a.len() == b.len() && a.iter().collect::<HashSet<_>>() == b.iter().collect::<HashSet<_>>()
+}
@@ -0,0 +1,226 @@
+//! Graffiti which is rendered afresh for each block proposal.
+//!
+//! A template may contain the following variables, which are substituted at proposal time:
+//!
+//! - `{validator_index}`: the index of the proposer, or `unknown` if the beacon node did not
+//!   provide it.
+//! - `{slot}`: the slot of the block.
+//! - `{version}`: the version of the validator client, e.g. `v0.2.8`.
+//!
+//! Rendered graffiti which is longer than `GRAFFITI_BYTES_LEN` is truncated.
+use serde_derive::{Deserialize, Serialize};
+use std::str::FromStr;
+use types::{Graffiti, Slot, GRAFFITI_BYTES_LEN};
+
+const VALIDATOR_INDEX_VAR: &str = "validator_index";
+const SLOT_VAR: &str = "slot";
+const VERSION_VAR: &str = "version";
+
+/// The values which may be substituted into a `GraffitiTemplate`.
+pub struct GraffitiContext {
+    pub validator_index: Option<u64>,
+    pub slot: Slot,
+}
+
+/// A graffiti string which may contain `{variable}` placeholders.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GraffitiTemplate(String);
+
+impl FromStr for GraffitiTemplate {
+    type Err = String;
+
+    /// Returns an error if the template uses an unknown variable, or if its text is too long to
+    /// fit in a block even before any variables are substituted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut literal_len = 0;
+        for segment in segments(s) {
+            match segment {
+                Segment::Literal(text) => literal_len += text.len(),
+                Segment::Variable(VALIDATOR_INDEX_VAR)
+                | Segment::Variable(SLOT_VAR)
+                | Segment::Variable(VERSION_VAR) => (),
+                Segment::Variable(other) => {
+                    return Err(format!(
+                        "Unknown graffiti variable {{{}}}, expected {{{}}}, {{{}}} or {{{}}}",
+                        other, VALIDATOR_INDEX_VAR, SLOT_VAR, VERSION_VAR
+                    ))
+                }
+            }
+        }
+
+        if literal_len > GRAFFITI_BYTES_LEN {
+            return Err(format!(
+                "Your graffiti is too long! {} bytes maximum!",
+                GRAFFITI_BYTES_LEN
+            ));
+        }
+
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl GraffitiTemplate {
+    /// Substitutes the variables of the template, truncating the result to `GRAFFITI_BYTES_LEN`
+    /// without splitting a character.
+    pub fn render(&self, context: &GraffitiContext) -> Graffiti {
+        let mut rendered = String::new();
+        for segment in segments(&self.0) {
+            match segment {
+                Segment::Literal(text) => rendered.push_str(text),
+                Segment::Variable(VALIDATOR_INDEX_VAR) => match context.validator_index {
+                    Some(index) => rendered.push_str(&index.to_string()),
+                    None => rendered.push_str("unknown"),
+                },
+                Segment::Variable(SLOT_VAR) => rendered.push_str(&context.slot.to_string()),
+                Segment::Variable(VERSION_VAR) => {
+                    rendered.push_str(concat!("v", env!("CARGO_PKG_VERSION")))
+                }
+                // Unknown variables are rejected by `from_str`, but are kept verbatim should a
+                // template be built some other way.
+                Segment::Variable(other) => {
+                    rendered.push('{');
+                    rendered.push_str(other);
+                    rendered.push('}');
+                }
+            }
+        }
+
+        let mut len = std::cmp::min(rendered.len(), GRAFFITI_BYTES_LEN);
+        while !rendered.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        let mut graffiti = Graffiti::default();
+        graffiti[..len].copy_from_slice(&rendered.as_bytes()[..len]);
+        graffiti
+    }
+}
+
+/// The graffiti included in blocks, cycling through the templates in order, one per proposal.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraffitiRotation {
+    templates: Vec<GraffitiTemplate>,
+}
+
+impl GraffitiRotation {
+    pub fn new(templates: Vec<GraffitiTemplate>) -> Self {
+        Self { templates }
+    }
+
+    /// Returns the graffiti for the `proposal`-th block proposed by this validator client, or
+    /// `None` if no graffiti is configured and the beacon node should choose it.
+    pub fn graffiti(&self, proposal: usize, context: &GraffitiContext) -> Option<Graffiti> {
+        if self.templates.is_empty() {
+            return None;
+        }
+
+        Some(self.templates[proposal % self.templates.len()].render(context))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Literal(&'a str),
+    Variable(&'a str),
+}
+
+/// Splits a template into its literal text and `{variable}` placeholders. A `{` without a
+/// matching `}` is literal text.
+fn segments(mut s: &str) -> Vec<Segment> {
+    let mut segments = vec![];
+    while let Some(open) = s.find('{') {
+        match s[open..].find('}') {
+            Some(close) => {
+                if open > 0 {
+                    segments.push(Segment::Literal(&s[..open]));
+                }
+                segments.push(Segment::Variable(&s[open + 1..open + close]));
+                s = &s[open + close + 1..];
+            }
+            None => break,
+        }
+    }
+    if !s.is_empty() {
+        segments.push(Segment::Literal(s));
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> GraffitiContext {
+        GraffitiContext {
+            validator_index: Some(42),
+            slot: Slot::new(1234),
+        }
+    }
+
+    fn graffiti_str(graffiti: Graffiti) -> String {
+        String::from_utf8(graffiti.to_vec())
+            .unwrap()
+            .trim_end_matches('\0')
+            .to_string()
+    }
+
+    #[test]
+    fn render() {
+        let template = "pool {validator_index}@{slot} {version}"
+            .parse::<GraffitiTemplate>()
+            .unwrap();
+        assert_eq!(
+            graffiti_str(template.render(&context())),
+            format!("pool 42@1234 v{}", env!("CARGO_PKG_VERSION"))
+        );
+
+        let template = "{validator_index}".parse::<GraffitiTemplate>().unwrap();
+        let no_index = GraffitiContext {
+            validator_index: None,
+            ..context()
+        };
+        assert_eq!(graffiti_str(template.render(&no_index)), "unknown");
+
+        let template = "{ not closed".parse::<GraffitiTemplate>().unwrap();
+        assert_eq!(graffiti_str(template.render(&context())), "{ not closed");
+    }
+
+    #[test]
+    fn invalid_templates() {
+        assert!("{epoch}".parse::<GraffitiTemplate>().is_err());
+        assert!("x"
+            .repeat(GRAFFITI_BYTES_LEN)
+            .parse::<GraffitiTemplate>()
+            .is_ok());
+        assert!("x"
+            .repeat(GRAFFITI_BYTES_LEN + 1)
+            .parse::<GraffitiTemplate>()
+            .is_err());
+    }
+
+    #[test]
+    fn truncates_at_char_boundary() {
+        // The two bytes of `é` would start at the last byte of the graffiti.
+        let prefix = "x".repeat(GRAFFITI_BYTES_LEN - 3);
+        let template = format!("{}{{validator_index}}é", prefix)
+            .parse::<GraffitiTemplate>()
+            .unwrap();
+        assert_eq!(
+            graffiti_str(template.render(&context())),
+            format!("{}42", prefix)
+        );
+    }
+
+    #[test]
+    fn rotation() {
+        assert_eq!(GraffitiRotation::default().graffiti(0, &context()), None);
+
+        let rotation =
+            GraffitiRotation::new(vec!["a".parse().unwrap(), "b {slot}".parse().unwrap()]);
+        let rendered = (0..3)
+            .map(|i| graffiti_str(rotation.graffiti(i, &context()).unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(rendered, vec!["a", "b 1234", "a"]);
+    }
+}
@@ -0,0 +1,896 @@
+//! Serves an HTTP API for managing the validators of the validator client whilst it runs,
+//! following the keymanager API standard:
+//!
+//! - `/eth/v1/keystores`: list, import and delete local keystores. The slashing protection data of
+//!   deleted keystores is returned, so that they may be safely used by another client.
+//! - `/eth/v1/remotekeys`: list, import and delete validators whose messages are signed by a
+//!   Web3Signer.
+//! - `/eth/v1/validator/{pubkey}/graffiti` and `/eth/v1/validator/{pubkey}/feerecipient`: the
+//!   graffiti and fee recipient of each validator.
+//!
+//! Every request must be authorized with the token stored in `API_TOKEN_FILENAME`, as
+//! `Authorization: Bearer <token>`. The token is generated if the file does not exist.
+use crate::graffiti::GraffitiTemplate;
+use crate::initialized_validators::InitializedValidator;
+use crate::validator_store::ValidatorStore;
+use crate::Config;
+use account_utils::{
+    create_with_600_perms,
+    validator_definitions::{SigningDefinition, ValidatorDefinition},
+    ZeroizeString,
+};
+use environment::TaskExecutor;
+use eth2_keystore::Keystore;
+use futures::future::TryFutureExt;
+use hyper::header::AUTHORIZATION;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde_derive::{Deserialize, Serialize};
+use slashing_protection::{interchange::Interchange, InterchangeError};
+use slog::{info, warn, Logger};
+use slot_clock::SlotClock;
+use std::convert::Infallible;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use types::{Address, EthSpec, PublicKey};
+use validator_dir::VOTING_KEYSTORE_FILE;
+
+/// The file within the datadir which holds the API token.
+pub const API_TOKEN_FILENAME: &str = "api-token.txt";
+/// The prefix of each generated API token.
+const API_TOKEN_PREFIX: &str = "api-token-0x";
+
+/// The state shared by all requests.
+pub struct Context<T, E: EthSpec> {
+    pub api_token: String,
+    pub validator_store: ValidatorStore<T, E>,
+    /// The directory in which imported keystores are placed.
+    pub validators_dir: PathBuf,
+    pub log: Logger,
+}
+
+/// Reads the API token from `API_TOKEN_FILENAME` in `data_dir`, generating a new token if the
+/// file does not exist.
+pub fn load_or_create_api_token(data_dir: &Path) -> Result<String, String> {
+    let path = data_dir.join(API_TOKEN_FILENAME);
+    if path.exists() {
+        let token = fs::read_to_string(&path)
+            .map_err(|e| format!("Unable to read API token from {:?}: {:?}", path, e))?;
+        let token = token.trim();
+        if token.is_empty() {
+            return Err(format!("The API token in {:?} is empty", path));
+        }
+        Ok(token.to_string())
+    } else {
+        let mut bytes = [0; 32];
+        rand::thread_rng().fill(&mut bytes);
+        let token = format!("{}{}", API_TOKEN_PREFIX, hex::encode(bytes));
+        create_with_600_perms(&path, token.as_bytes())
+            .map_err(|e| format!("Unable to write API token to {:?}: {:?}", path, e))?;
+        Ok(token)
+    }
+}
+
+/// Start the API server, returning the address it is listening on.
+///
+/// The server shuts down when `executor` exits.
+pub fn start_server<T: SlotClock + 'static, E: EthSpec>(
+    config: &Config,
+    ctx: Arc<Context<T, E>>,
+    executor: TaskExecutor,
+) -> Result<SocketAddr, String> {
+    let log = executor.log().clone();
+    let request_executor = executor.clone();
+
+    let make_service = make_service_fn(move |_| {
+        let ctx = ctx.clone();
+        let executor = request_executor.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let ctx = ctx.clone();
+                let executor = executor.clone();
+                async move { Ok::<_, Infallible>(handle_request(req, ctx, &executor).await) }
+            }))
+        }
+    });
+
+    let bind_addr = SocketAddr::from((config.http_api_listen_address, config.http_api_port));
+    let server = Server::try_bind(&bind_addr)
+        .map_err(|e| format!("Unable to bind API server to {}: {}", bind_addr, e))?
+        .serve(make_service);
+
+    // This may be different to `bind_addr` if the bind port was 0 (this allows the OS to choose a
+    // free port).
+    let actual_listen_addr = server.local_addr();
+
+    let exit = executor.exit();
+    let inner_log = log.clone();
+    let server_future = server
+        .with_graceful_shutdown(async move {
+            let _ = exit.await;
+            info!(inner_log, "API HTTP service shutdown");
+        })
+        .map_err(move |e| warn!(log, "API HTTP server failed"; "error" => format!("{:?}", e)))
+        .unwrap_or_else(|_| ());
+
+    executor.spawn_without_exit(server_future, "http_api");
+
+    Ok(actual_listen_addr)
+}
+
+/// An error response, with the status code and message to return.
+type ApiError = (StatusCode, String);
+
+/// Authorizes and routes `req`.
+///
+/// Requests are handled on the blocking thread pool, since they may decrypt keystores or wait on
+/// the slashing protection.
+async fn handle_request<T: SlotClock + 'static, E: EthSpec>(
+    req: Request<Body>,
+    ctx: Arc<Context<T, E>>,
+    executor: &TaskExecutor,
+) -> Response<Body> {
+    let authorized = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |value| is_authorized(value, &ctx.api_token));
+    if !authorized {
+        return error_response((
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid API token".to_string(),
+        ));
+    }
+
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let result = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(body) => executor
+            .spawn_blocking_handle(
+                move || route(&method, &path, &body, &ctx),
+                "http_api_request",
+            )
+            .await
+            .unwrap_or_else(|e| Err(server_error("Request task failed", e))),
+        Err(e) => Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unable to read request body: {:?}", e),
+        )),
+    };
+
+    result.unwrap_or_else(error_response)
+}
+
+/// Returns true if the `Authorization` header holds the API token.
+///
+/// The token is compared in constant time, so that it cannot be guessed byte by byte.
+fn is_authorized(header: &str, api_token: &str) -> bool {
+    header.strip_prefix("Bearer ").map_or(false, |token| {
+        token.trim().as_bytes().ct_eq(api_token.as_bytes()).into()
+    })
+}
+
+fn route<T: SlotClock + 'static, E: EthSpec>(
+    method: &Method,
+    path: &str,
+    body: &[u8],
+    ctx: &Context<T, E>,
+) -> Result<Response<Body>, ApiError> {
+    match (method, path) {
+        (&Method::GET, "/eth/v1/keystores") => list_keystores(ctx),
+        (&Method::POST, "/eth/v1/keystores") => import_keystores(parse_body(body)?, ctx),
+        (&Method::DELETE, "/eth/v1/keystores") => delete_keystores(parse_body(body)?, ctx),
+        (&Method::GET, "/eth/v1/remotekeys") => list_remote_keys(ctx),
+        (&Method::POST, "/eth/v1/remotekeys") => import_remote_keys(parse_body(body)?, ctx),
+        (&Method::DELETE, "/eth/v1/remotekeys") => delete_remote_keys(parse_body(body)?, ctx),
+        _ => match validator_path(path) {
+            Some((pubkey, "graffiti")) => {
+                let pubkey = parse_pubkey(pubkey)?;
+                match *method {
+                    Method::GET => get_graffiti(&pubkey, ctx),
+                    Method::POST => set_graffiti(&pubkey, parse_body(body)?, ctx),
+                    Method::DELETE => delete_graffiti(&pubkey, ctx),
+                    _ => Err(method_not_allowed()),
+                }
+            }
+            Some((pubkey, "feerecipient")) => {
+                let pubkey = parse_pubkey(pubkey)?;
+                match *method {
+                    Method::GET => get_fee_recipient(&pubkey, ctx),
+                    Method::POST => set_fee_recipient(&pubkey, parse_body(body)?, ctx),
+                    Method::DELETE => delete_fee_recipient(&pubkey, ctx),
+                    _ => Err(method_not_allowed()),
+                }
+            }
+            _ => Err((StatusCode::NOT_FOUND, "Not found".to_string())),
+        },
+    }
+}
+
+/// Splits a path of the form `/eth/v1/validator/{pubkey}/{resource}`.
+fn validator_path(path: &str) -> Option<(&str, &str)> {
+    let mut parts = path.strip_prefix("/eth/v1/validator/")?.splitn(2, '/');
+    Some((parts.next()?, parts.next()?))
+}
+
+fn parse_pubkey(pubkey_str: &str) -> Result<PublicKey, ApiError> {
+    pubkey_str
+        .strip_prefix("0x")
+        .ok_or_else(|| format!("Public key must be 0x-prefixed: {}", pubkey_str))
+        .and_then(|hex_str| {
+            hex::decode(hex_str).map_err(|e| format!("Invalid hex in {}: {:?}", pubkey_str, e))
+        })
+        .and_then(|bytes| {
+            PublicKey::deserialize(&bytes)
+                .map_err(|e| format!("Invalid public key {}: {:?}", pubkey_str, e))
+        })
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+fn parse_body<T: DeserializeOwned>(body: &[u8]) -> Result<T, ApiError> {
+    serde_json::from_slice(body).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid request body: {}", e),
+        )
+    })
+}
+
+fn method_not_allowed() -> ApiError {
+    (
+        StatusCode::METHOD_NOT_ALLOWED,
+        "Method not allowed".to_string(),
+    )
+}
+
+fn server_error<D: std::fmt::Debug>(context: &str, e: D) -> ApiError {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("{}: {:?}", context, e),
+    )
+}
+
+/// The response to a list request.
+#[derive(Serialize)]
+struct DataResponse<T> {
+    data: T,
+}
+
+/// The outcome of importing or deleting one key.
+#[derive(Debug, PartialEq, Serialize)]
+struct KeyStatus {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+impl KeyStatus {
+    fn ok(status: &'static str) -> Self {
+        Self {
+            status,
+            message: None,
+        }
+    }
+
+    fn error(message: String) -> Self {
+        Self {
+            status: "error",
+            message: Some(message),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct KeystoreEntry {
+    validating_pubkey: PublicKey,
+    readonly: bool,
+}
+
+fn list_keystores<T: SlotClock + 'static, E: EthSpec>(
+    ctx: &Context<T, E>,
+) -> Result<Response<Body>, ApiError> {
+    let data = ctx
+        .validator_store
+        .initialized_validators()
+        .read()
+        .definitions()
+        .iter()
+        .filter(|def| {
+            matches!(
+                def.signing_definition,
+                SigningDefinition::LocalKeystore { .. }
+            )
+        })
+        .map(|def| KeystoreEntry {
+            validating_pubkey: def.voting_public_key.clone(),
+            readonly: false,
+        })
+        .collect::<Vec<_>>();
+
+    json_response(StatusCode::OK, &DataResponse { data })
+}
+
+#[derive(Deserialize)]
+struct ImportKeystoresRequest {
+    /// Each keystore, encoded as JSON.
+    keystores: Vec<String>,
+    passwords: Vec<ZeroizeString>,
+    /// Slashing protection data in the interchange format, encoded as JSON.
+    #[serde(default)]
+    slashing_protection: Option<String>,
+}
+
+fn import_keystores<T: SlotClock + 'static, E: EthSpec>(
+    request: ImportKeystoresRequest,
+    ctx: &Context<T, E>,
+) -> Result<Response<Body>, ApiError> {
+    if request.keystores.len() != request.passwords.len() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "{} keystores were given with {} passwords",
+                request.keystores.len(),
+                request.passwords.len()
+            ),
+        ));
+    }
+
+    // Import the slashing protection data before any keystore, so that a validator is never
+    // enabled without the protection it had with its previous client.
+    let mut rejected = vec![];
+    if let Some(json) = &request.slashing_protection {
+        let interchange = Interchange::from_json_str(json).map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid slashing protection data: {}", e),
+            )
+        })?;
+        let report = ctx
+            .validator_store
+            .slashing_protection()
+            .import_interchange_info(&interchange, ctx.validator_store.genesis_validators_root())
+            .map_err(|e| match e {
+                InterchangeError::Unsupported => (
+                    StatusCode::BAD_REQUEST,
+                    "Slashing protection data must be imported by the remote slashing \
+                     protection service"
+                        .to_string(),
+                ),
+                e => (
+                    StatusCode::BAD_REQUEST,
+                    format!("Unable to import slashing protection data: {:?}", e),
+                ),
+            })?;
+        rejected = report
+            .rejected
+            .into_iter()
+            .map(|validator| validator.pubkey)
+            .collect();
+    }
+
+    let data = request
+        .keystores
+        .iter()
+        .zip(request.passwords)
+        .map(|(json, password)| {
+            let keystore = match Keystore::from_json_str(json) {
+                Ok(keystore) => keystore,
+                Err(e) => return KeyStatus::error(format!("Invalid keystore: {:?}", e)),
+            };
+            match keystore.public_key() {
+                Some(pubkey) if rejected.contains(&pubkey) => KeyStatus::error(
+                    "The slashing protection data of the keystore was rejected".to_string(),
+                ),
+                Some(pubkey) => import_keystore(pubkey, &keystore, password, ctx)
+                    .unwrap_or_else(KeyStatus::error),
+                None => KeyStatus::error("Invalid public key in keystore".to_string()),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    json_response(StatusCode::OK, &DataResponse { data })
+}
+
+/// Places the keystore in the validators dir and enables its validator.
+fn import_keystore<T: SlotClock + 'static, E: EthSpec>(
+    pubkey: PublicKey,
+    keystore: &Keystore,
+    password: ZeroizeString,
+    ctx: &Context<T, E>,
+) -> Result<KeyStatus, String> {
+    let validators = ctx.validator_store.initialized_validators();
+    if validators.read().definition(&pubkey).is_some() {
+        return Ok(KeyStatus::ok("duplicate"));
+    }
+
+    // The keystore is placed in a directory that matches the name of the public key, as done by
+    // `lighthouse account validator import`.
+    let dir = ctx.validators_dir.join(format!("0x{}", keystore.pubkey()));
+    let path = dir.join(VOTING_KEYSTORE_FILE);
+    if path.exists() {
+        return Err(format!("A keystore already exists at {:?}", path));
+    }
+    let json = keystore
+        .to_json_string()
+        .map_err(|e| format!("Unable to encode keystore: {:?}", e))?;
+    fs::create_dir_all(&dir)
+        .and_then(|_| create_with_600_perms(&path, json.as_bytes()))
+        .map_err(|e| format!("Unable to write keystore to {:?}: {:?}", path, e))?;
+
+    let result = ValidatorDefinition::new_keystore_with_password(&path, Some(password))
+        .map_err(|e| format!("Unable to define validator: {:?}", e))
+        .and_then(|def| {
+            ctx.validator_store
+                .slashing_protection()
+                .register_validators(&[pubkey.clone()])
+                .map_err(|e| format!("Unable to register validator: {:?}", e))?;
+            // Decrypting the keystore is slow, so it is done before the lock on the validators is
+            // taken. Like every request, this runs on the blocking thread pool.
+            let strict_lockfiles = validators.read().strict_lockfiles();
+            let validator =
+                InitializedValidator::from_definition(def.clone(), strict_lockfiles, &ctx.log)
+                    .map_err(|e| format!("Unable to enable validator: {:?}", e))?;
+            validators
+                .write()
+                .add_initialized_definition(def, validator)
+                .map_err(|e| format!("Unable to enable validator: {:?}", e))
+        });
+
+    match result {
+        Ok(()) => {
+            info!(
+                ctx.log,
+                "Imported keystore via the API";
+                "voting_pubkey" => format!("{:?}", pubkey)
+            );
+            Ok(KeyStatus::ok("imported"))
+        }
+        Err(e) => {
+            remove_keystore(&path);
+            Err(e)
+        }
+    }
+}
+
+/// Removes a keystore file, and its directory if it is left empty.
+fn remove_keystore(path: &Path) {
+    let _ = fs::remove_file(path);
+    if let Some(dir) = path.parent() {
+        // Fails if the directory holds other files, which are kept.
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+#[derive(Deserialize)]
+struct DeleteKeysRequest {
+    pubkeys: Vec<PublicKey>,
+}
+
+#[derive(Serialize)]
+struct DeleteKeystoresResponse {
+    data: Vec<KeyStatus>,
+    /// Slashing protection data of the deleted keystores in the interchange format, encoded as
+    /// JSON.
+    slashing_protection: String,
+}
+
+fn delete_keystores<T: SlotClock + 'static, E: EthSpec>(
+    request: DeleteKeysRequest,
+    ctx: &Context<T, E>,
+) -> Result<Response<Body>, ApiError> {
+    let slashing_protection = ctx.validator_store.slashing_protection();
+    let genesis_validators_root = ctx.validator_store.genesis_validators_root();
+
+    // A keystore is never deleted without its slashing protection data being returned.
+    if let Err(InterchangeError::Unsupported) =
+        slashing_protection.export_interchange_info(genesis_validators_root, Some(&[]))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Keystores cannot be deleted whilst using remote slashing protection, since their \
+             slashing protection data cannot be exported"
+                .to_string(),
+        ));
+    }
+
+    let mut exported = vec![];
+    let mut data = vec![];
+    for pubkey in &request.pubkeys {
+        let status = {
+            let mut validators = ctx.validator_store.initialized_validators().write();
+            let is_local_keystore = validators.definition(pubkey).map(|def| {
+                matches!(
+                    def.signing_definition,
+                    SigningDefinition::LocalKeystore { .. }
+                )
+            });
+            match is_local_keystore {
+                Some(true) => match validators.remove_definition(pubkey) {
+                    Ok(Some(ValidatorDefinition {
+                        signing_definition:
+                            SigningDefinition::LocalKeystore {
+                                voting_keystore_path,
+                                ..
+                            },
+                        ..
+                    })) => {
+                        remove_keystore(&voting_keystore_path);
+                        info!(
+                            ctx.log,
+                            "Deleted keystore via the API";
+                            "voting_pubkey" => format!("{:?}", pubkey)
+                        );
+                        KeyStatus::ok("deleted")
+                    }
+                    Ok(_) => KeyStatus::ok("not_found"),
+                    Err(e) => KeyStatus::error(format!("Unable to remove validator: {:?}", e)),
+                },
+                Some(false) => {
+                    KeyStatus::error("The validator is not a local keystore".to_string())
+                }
+                // The validator is known only to the slashing protection, e.g. because it was
+                // deleted by an earlier request.
+                None if slashing_protection
+                    .export_interchange_info(
+                        genesis_validators_root,
+                        Some(std::slice::from_ref(pubkey)),
+                    )
+                    .is_ok() =>
+                {
+                    KeyStatus::ok("not_active")
+                }
+                None => KeyStatus::ok("not_found"),
+            }
+        };
+
+        if status.status == "deleted" || status.status == "not_active" {
+            exported.push(pubkey.clone());
+        }
+        data.push(status);
+    }
+
+    let interchange = slashing_protection
+        .export_interchange_info(genesis_validators_root, Some(&exported))
+        .map_err(|e| server_error("Unable to export slashing protection data", e))?;
+    let slashing_protection = serde_json::to_string(&interchange)
+        .map_err(|e| server_error("Unable to encode slashing protection data", e))?;
+
+    json_response(
+        StatusCode::OK,
+        &DeleteKeystoresResponse {
+            data,
+            slashing_protection,
+        },
+    )
+}
+
+#[derive(Serialize, Deserialize)]
+struct RemoteKey {
+    pubkey: PublicKey,
+    url: String,
+}
+
+#[derive(Serialize)]
+struct RemoteKeyEntry {
+    pubkey: PublicKey,
+    url: String,
+    readonly: bool,
+}
+
+fn list_remote_keys<T: SlotClock + 'static, E: EthSpec>(
+    ctx: &Context<T, E>,
+) -> Result<Response<Body>, ApiError> {
+    let data = ctx
+        .validator_store
+        .initialized_validators()
+        .read()
+        .definitions()
+        .iter()
+        .filter_map(|def| match &def.signing_definition {
+            SigningDefinition::Web3Signer { url, .. } => Some(RemoteKeyEntry {
+                pubkey: def.voting_public_key.clone(),
+                url: url.clone(),
+                readonly: false,
+            }),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    json_response(StatusCode::OK, &DataResponse { data })
+}
+
+#[derive(Deserialize)]
+struct ImportRemoteKeysRequest {
+    remote_keys: Vec<RemoteKey>,
+}
+
+fn import_remote_keys<T: SlotClock + 'static, E: EthSpec>(
+    request: ImportRemoteKeysRequest,
+    ctx: &Context<T, E>,
+) -> Result<Response<Body>, ApiError> {
+    let data = request
+        .remote_keys
+        .into_iter()
+        .map(|remote_key| {
+            let validators = ctx.validator_store.initialized_validators();
+            if validators.read().definition(&remote_key.pubkey).is_some() {
+                return KeyStatus::ok("duplicate");
+            }
+
+            let def =
+                ValidatorDefinition::new_web3signer(remote_key.pubkey.clone(), remote_key.url);
+            let result = ctx
+                .validator_store
+                .slashing_protection()
+                .register_validators(&[remote_key.pubkey.clone()])
+                .map_err(|e| format!("Unable to register validator: {:?}", e))
+                .and_then(|()| {
+                    validators
+                        .write()
+                        .add_definition(def)
+                        .map_err(|e| format!("Unable to enable validator: {:?}", e))
+                });
+
+            match result {
+                Ok(()) => {
+                    info!(
+                        ctx.log,
+                        "Imported remote key via the API";
+                        "voting_pubkey" => format!("{:?}", remote_key.pubkey)
+                    );
+                    KeyStatus::ok("imported")
+                }
+                Err(e) => KeyStatus::error(e),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    json_response(StatusCode::OK, &DataResponse { data })
+}
+
+fn delete_remote_keys<T: SlotClock + 'static, E: EthSpec>(
+    request: DeleteKeysRequest,
+    ctx: &Context<T, E>,
+) -> Result<Response<Body>, ApiError> {
+    let data = request
+        .pubkeys
+        .iter()
+        .map(|pubkey| {
+            let mut validators = ctx.validator_store.initialized_validators().write();
+            let is_remote_key = validators
+                .definition(pubkey)
+                .map(|def| matches!(def.signing_definition, SigningDefinition::Web3Signer { .. }));
+            match is_remote_key {
+                Some(true) => match validators.remove_definition(pubkey) {
+                    Ok(_) => {
+                        info!(
+                            ctx.log,
+                            "Deleted remote key via the API";
+                            "voting_pubkey" => format!("{:?}", pubkey)
+                        );
+                        KeyStatus::ok("deleted")
+                    }
+                    Err(e) => KeyStatus::error(format!("Unable to remove validator: {:?}", e)),
+                },
+                Some(false) => KeyStatus::error("The validator is not a remote key".to_string()),
+                None => KeyStatus::ok("not_found"),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    json_response(StatusCode::OK, &DataResponse { data })
+}
+
+#[derive(Serialize, Deserialize)]
+struct GraffitiData {
+    pubkey: PublicKey,
+    /// `None` if the validator uses the graffiti of the `--graffiti` flag.
+    graffiti: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SetGraffitiRequest {
+    graffiti: String,
+}
+
+fn get_graffiti<T: SlotClock + 'static, E: EthSpec>(
+    pubkey: &PublicKey,
+    ctx: &Context<T, E>,
+) -> Result<Response<Body>, ApiError> {
+    let graffiti = ctx
+        .validator_store
+        .initialized_validators()
+        .read()
+        .definition(pubkey)
+        .ok_or_else(|| unknown_validator(pubkey))?
+        .graffiti
+        .clone();
+
+    json_response(
+        StatusCode::OK,
+        &DataResponse {
+            data: GraffitiData {
+                pubkey: pubkey.clone(),
+                graffiti,
+            },
+        },
+    )
+}
+
+fn set_graffiti<T: SlotClock + 'static, E: EthSpec>(
+    pubkey: &PublicKey,
+    request: SetGraffitiRequest,
+    ctx: &Context<T, E>,
+) -> Result<Response<Body>, ApiError> {
+    request
+        .graffiti
+        .parse::<GraffitiTemplate>()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    update_definition(pubkey, ctx, |def| def.graffiti = Some(request.graffiti))?;
+    Ok(empty_response(StatusCode::ACCEPTED))
+}
+
+fn delete_graffiti<T: SlotClock + 'static, E: EthSpec>(
+    pubkey: &PublicKey,
+    ctx: &Context<T, E>,
+) -> Result<Response<Body>, ApiError> {
+    update_definition(pubkey, ctx, |def| def.graffiti = None)?;
+    Ok(empty_response(StatusCode::NO_CONTENT))
+}
+
+#[derive(Serialize, Deserialize)]
+struct FeeRecipientData {
+    pubkey: PublicKey,
+    ethaddress: Option<Address>,
+}
+
+#[derive(Deserialize)]
+struct SetFeeRecipientRequest {
+    ethaddress: Address,
+}
+
+fn get_fee_recipient<T: SlotClock + 'static, E: EthSpec>(
+    pubkey: &PublicKey,
+    ctx: &Context<T, E>,
+) -> Result<Response<Body>, ApiError> {
+    if ctx
+        .validator_store
+        .initialized_validators()
+        .read()
+        .definition(pubkey)
+        .is_none()
+    {
+        return Err(unknown_validator(pubkey));
+    }
+    let ethaddress = ctx.validator_store.fee_recipient(pubkey);
+
+    json_response(
+        StatusCode::OK,
+        &DataResponse {
+            data: FeeRecipientData {
+                pubkey: pubkey.clone(),
+                ethaddress,
+            },
+        },
+    )
+}
+
+fn set_fee_recipient<T: SlotClock + 'static, E: EthSpec>(
+    pubkey: &PublicKey,
+    request: SetFeeRecipientRequest,
+    ctx: &Context<T, E>,
+) -> Result<Response<Body>, ApiError> {
+    update_definition(pubkey, ctx, |def| {
+        def.suggested_fee_recipient = Some(request.ethaddress)
+    })?;
+    Ok(empty_response(StatusCode::ACCEPTED))
+}
+
+fn delete_fee_recipient<T: SlotClock + 'static, E: EthSpec>(
+    pubkey: &PublicKey,
+    ctx: &Context<T, E>,
+) -> Result<Response<Body>, ApiError> {
+    update_definition(pubkey, ctx, |def| def.suggested_fee_recipient = None)?;
+    Ok(empty_response(StatusCode::NO_CONTENT))
+}
+
+/// Modifies and saves the definition of the validator, which must exist.
+fn update_definition<T: SlotClock + 'static, E: EthSpec, F: FnOnce(&mut ValidatorDefinition)>(
+    pubkey: &PublicKey,
+    ctx: &Context<T, E>,
+    update: F,
+) -> Result<(), ApiError> {
+    let updated = ctx
+        .validator_store
+        .initialized_validators()
+        .write()
+        .update_definition(pubkey, update)
+        .map_err(|e| server_error("Unable to update validator", e))?;
+
+    if updated {
+        Ok(())
+    } else {
+        Err(unknown_validator(pubkey))
+    }
+}
+
+fn unknown_validator(pubkey: &PublicKey) -> ApiError {
+    (
+        StatusCode::NOT_FOUND,
+        format!("Unknown validator {:?}", pubkey),
+    )
+}
+
+#[derive(Serialize)]
+struct ErrorMessage {
+    message: String,
+}
+
+fn error_response((status, message): ApiError) -> Response<Body> {
+    json_response(status, &ErrorMessage { message }).unwrap_or_else(|(status, message)| {
+        let mut response = Response::new(Body::from(message));
+        *response.status_mut() = status;
+        response
+    })
+}
+
+fn json_response<T: serde::Serialize>(
+    status: StatusCode,
+    body: &T,
+) -> Result<Response<Body>, ApiError> {
+    let json =
+        serde_json::to_string(body).map_err(|e| server_error("Unable to encode response", e))?;
+    let mut response = Response::new(Body::from(json));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static("application/json"),
+    );
+    Ok(response)
+}
+
+fn empty_response(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn api_token_is_generated_once() {
+        let dir = TempDir::new("http_api").unwrap();
+        let token = load_or_create_api_token(dir.path()).unwrap();
+        assert!(token.starts_with(API_TOKEN_PREFIX));
+        assert_eq!(token.len(), API_TOKEN_PREFIX.len() + 64);
+        assert_eq!(load_or_create_api_token(dir.path()).unwrap(), token);
+
+        assert!(is_authorized(&format!("Bearer {}", token), &token));
+        assert!(!is_authorized(&token, &token));
+        assert!(!is_authorized("Bearer api-token-0x00", &token));
+        assert!(!is_authorized(&format!("Bearer {}0", token), &token));
+        assert!(!is_authorized("Bearer ", &token));
+    }
+
+    #[test]
+    fn validator_paths() {
+        assert_eq!(
+            validator_path("/eth/v1/validator/0xab/graffiti"),
+            Some(("0xab", "graffiti"))
+        );
+        assert_eq!(validator_path("/eth/v1/validator/0xab"), None);
+        assert_eq!(validator_path("/eth/v1/keystores"), None);
+
+        assert!(parse_pubkey("0xab").is_err());
+        assert!(parse_pubkey("ab").is_err());
+    }
+}
@@ -0,0 +1,103 @@
+//! Serves the Prometheus metrics of the validator client at `/metrics`, and the postmortems of
+//! recently missed duties as JSON at `/postmortems`.
+use crate::postmortem::Postmortems;
+use crate::Config;
+use environment::TaskExecutor;
+use futures::future::TryFutureExt;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use lighthouse_metrics::{Encoder, TextEncoder};
+use slashing_protection::SlashingProtection;
+use slog::{info, warn};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// Start the metrics server, returning the address it is listening on.
+///
+/// The server shuts down when `executor` exits.
+pub fn start_server(
+    config: &Config,
+    slashing_protection: Arc<dyn SlashingProtection>,
+    postmortems: Postmortems,
+    executor: TaskExecutor,
+) -> Result<SocketAddr, String> {
+    let log = executor.log().clone();
+
+    let make_service = make_service_fn(move |_| {
+        let slashing_protection = slashing_protection.clone();
+        let postmortems = postmortems.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let response = handle_request(req, slashing_protection.as_ref(), &postmortems);
+                async move { Ok::<_, Infallible>(response) }
+            }))
+        }
+    });
+
+    let bind_addr = SocketAddr::from((config.metrics_listen_address, config.metrics_port));
+    let server = Server::try_bind(&bind_addr)
+        .map_err(|e| format!("Unable to bind metrics server to {}: {}", bind_addr, e))?
+        .serve(make_service);
+
+    // This may be different to `bind_addr` if the bind port was 0 (this allows the OS to choose a
+    // free port).
+    let actual_listen_addr = server.local_addr();
+
+    let exit = executor.exit();
+    let inner_log = log.clone();
+    let server_future = server
+        .with_graceful_shutdown(async move {
+            let _ = exit.await;
+            info!(inner_log, "Metrics HTTP service shutdown");
+        })
+        .map_err(move |e| warn!(log, "Metrics HTTP server failed"; "error" => format!("{:?}", e)))
+        .unwrap_or_else(|_| ());
+
+    executor.spawn_without_exit(server_future, "http_metrics");
+
+    Ok(actual_listen_addr)
+}
+
+fn handle_request(
+    req: Request<Body>,
+    slashing_protection: &dyn SlashingProtection,
+    postmortems: &Postmortems,
+) -> Response<Body> {
+    match (req.method(), req.uri().path()) {
+        (&Method::GET, "/metrics") => metrics_response(slashing_protection),
+        (&Method::GET, "/postmortems") => match serde_json::to_string(&postmortems.get()) {
+            Ok(json) => response(StatusCode::OK, json),
+            Err(e) => response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Unable to encode postmortems: {:?}", e),
+            ),
+        },
+        _ => response(StatusCode::NOT_FOUND, "Not found".to_string()),
+    }
+}
+
+fn metrics_response(slashing_protection: &dyn SlashingProtection) -> Response<Body> {
+    // Metrics which aren't updated as they change must be scraped before encoding.
+    slashing_protection.scrape_for_metrics();
+
+    let mut buffer = vec![];
+    match TextEncoder::new()
+        .encode(&lighthouse_metrics::gather(), &mut buffer)
+        .map_err(|e| format!("{:?}", e))
+        .and_then(|()| String::from_utf8(buffer).map_err(|e| format!("{:?}", e)))
+    {
+        Ok(metrics) => response(StatusCode::OK, metrics),
+        Err(e) => response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Unable to encode metrics: {}", e),
+        ),
+    }
+}
+
+fn response(status: StatusCode, body: String) -> Response<Body> {
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response
+}
@@ -6,6 +6,8 @@
 //! The `InitializedValidators` struct in this file serves as the source-of-truth of which
 //! validators are managed by this validator client.
 
+use crate::signing_method::{LocalKeystore, RemoteSigner, SigningMethod};
+use crate::web3signer::{self, Web3Signer};
 use account_utils::{
     read_password, read_password_from_user,
     validator_definitions::{
@@ -17,8 +19,10 @@ use slog::{error, info, warn, Logger};
 use std::collections::HashMap;
 use std::fs::{self, File, OpenOptions};
 use std::io;
-use std::path::PathBuf;
-use types::{Keypair, PublicKey};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use types::{Epoch, Keypair, PublicKey};
 
 // Use TTY instead of stdin to capture passwords from users.
 const USE_STDIN: bool = false;
@@ -48,31 +52,28 @@ pub enum Error {
     UnableToReadVotingKeystorePassword(io::Error),
     /// There was an error updating the on-disk validator definitions file.
     UnableToSaveDefinitions(validator_definitions::Error),
+    /// There was an error reading the on-disk validator definitions file.
+    UnableToOpenDefinitions(validator_definitions::Error),
     /// It is not legal to try and initialize a disabled validator definition.
     UnableToInitializeDisabledValidator,
     /// It is not legal to try and initialize a disabled validator definition.
     PasswordUnknown(PathBuf),
     /// There was an error reading from stdin.
     UnableToReadPasswordFromUser(String),
-}
-
-/// A method used by a validator to sign messages.
-///
-/// Presently there is only a single variant, however we expect more variants to arise (e.g.,
-/// remote signing).
-pub enum SigningMethod {
-    /// A validator that is defined by an EIP-2335 keystore on the local filesystem.
-    LocalKeystore {
-        voting_keystore_path: PathBuf,
-        voting_keystore_lockfile_path: PathBuf,
-        voting_keystore: Keystore,
-        voting_keypair: Keypair,
-    },
+    /// The client for a remote signer could not be created.
+    UnableToInitializeWeb3Signer(web3signer::Error),
+    /// The validator client holds only a share of the key, and is unable to sign with it alone.
+    ThresholdSigningUnsupported,
+    /// A definition with the same voting public key already exists.
+    DuplicatePublicKey(PublicKey),
 }
 
 /// A validator that is ready to sign messages.
 pub struct InitializedValidator {
-    signing_method: SigningMethod,
+    voting_public_key: PublicKey,
+    signing_method: Arc<dyn SigningMethod>,
+    /// The lockfile of a local keystore, which is removed when `self` is dropped.
+    voting_keystore_lockfile_path: Option<PathBuf>,
 }
 
 impl InitializedValidator {
@@ -171,12 +172,47 @@ impl InitializedValidator {
                 }
 
                 Ok(Self {
-                    signing_method: SigningMethod::LocalKeystore {
-                        voting_keystore_path,
-                        voting_keystore_lockfile_path,
-                        voting_keystore,
-                        voting_keypair,
-                    },
+                    voting_public_key: voting_keypair.pk.clone(),
+                    signing_method: Arc::new(LocalKeystore { voting_keypair }),
+                    voting_keystore_lockfile_path: Some(voting_keystore_lockfile_path),
+                })
+            }
+            // A single share cannot produce a valid signature, and coordinating with the
+            // co-signers is left to other software.
+            SigningDefinition::ThresholdShare { .. } => Err(Error::ThresholdSigningUnsupported),
+            // Nothing is stored locally for a remote signer, so only the client is created.
+            SigningDefinition::Web3Signer {
+                url,
+                root_certificate_path,
+                client_identity_path,
+                client_identity_password,
+            } => {
+                let client_identity = client_identity_path.as_ref().map(|path| {
+                    (
+                        path.as_path(),
+                        client_identity_password
+                            .as_ref()
+                            .map_or("", |password| password.as_str()),
+                    )
+                });
+                let signer =
+                    Web3Signer::new(&url, root_certificate_path.as_deref(), client_identity)
+                        .map_err(Error::UnableToInitializeWeb3Signer)?;
+
+                info!(
+                    log,
+                    "Using remote signer";
+                    "url" => signer.url(),
+                    "voting_pubkey" => format!("{:?}", def.voting_public_key)
+                );
+
+                Ok(Self {
+                    voting_public_key: def.voting_public_key.clone(),
+                    signing_method: Arc::new(RemoteSigner {
+                        voting_public_key: def.voting_public_key,
+                        signer,
+                    }),
+                    voting_keystore_lockfile_path: None,
                 })
             }
         }
@@ -184,37 +220,31 @@ impl InitializedValidator {
 
     /// Returns the voting public key for this validator.
     pub fn voting_public_key(&self) -> &PublicKey {
-        match &self.signing_method {
-            SigningMethod::LocalKeystore { voting_keypair, .. } => &voting_keypair.pk,
-        }
+        &self.voting_public_key
     }
 
-    /// Returns the voting keypair for this validator.
-    pub fn voting_keypair(&self) -> &Keypair {
-        match &self.signing_method {
-            SigningMethod::LocalKeystore { voting_keypair, .. } => voting_keypair,
-        }
+    /// Returns the method by which this validator signs.
+    ///
+    /// It may be held without holding the lock on the validators, which must not be held whilst
+    /// waiting on a remote signer.
+    pub fn signing_method(&self) -> Arc<dyn SigningMethod> {
+        self.signing_method.clone()
     }
 }
 
 /// Custom drop implementation to allow for `LocalKeystore` to remove lockfiles.
 impl Drop for InitializedValidator {
     fn drop(&mut self) {
-        match &self.signing_method {
-            SigningMethod::LocalKeystore {
-                voting_keystore_lockfile_path,
-                ..
-            } => {
-                if voting_keystore_lockfile_path.exists() {
-                    if let Err(e) = fs::remove_file(&voting_keystore_lockfile_path) {
-                        eprintln!(
-                            "Failed to remove {:?}: {:?}",
-                            voting_keystore_lockfile_path, e
-                        )
-                    }
-                } else {
-                    eprintln!("Lockfile missing: {:?}", voting_keystore_lockfile_path)
+        if let Some(voting_keystore_lockfile_path) = &self.voting_keystore_lockfile_path {
+            if voting_keystore_lockfile_path.exists() {
+                if let Err(e) = fs::remove_file(&voting_keystore_lockfile_path) {
+                    eprintln!(
+                        "Failed to remove {:?}: {:?}",
+                        voting_keystore_lockfile_path, e
+                    )
                 }
+            } else {
+                eprintln!("Lockfile missing: {:?}", voting_keystore_lockfile_path)
             }
         }
     }
@@ -273,6 +303,10 @@ pub struct InitializedValidators {
     validators_dir: PathBuf,
     /// The canonical set of validators.
     validators: HashMap<PublicKey, InitializedValidator>,
+    /// The epoch at which the schedules of the definitions were last applied, if any.
+    schedule_epoch: Option<Epoch>,
+    /// The modification time of the definitions file when it was last read or written by `self`.
+    definitions_modified: Option<SystemTime>,
     /// For logging via `slog`.
     log: Logger,
 }
@@ -287,15 +321,37 @@ impl InitializedValidators {
     ) -> Result<Self, Error> {
         let mut this = Self {
             strict_lockfiles,
+            definitions_modified: definitions_file_modified(&validators_dir),
             validators_dir,
             definitions,
             validators: HashMap::default(),
+            schedule_epoch: None,
             log,
         };
+
+        // Shares are imported disabled, so make it clear that they are not being run.
+        for def in this.definitions.as_slice() {
+            if let SigningDefinition::ThresholdShare { share_index, .. } = def.signing_definition {
+                warn!(
+                    this.log,
+                    "Not running threshold share";
+                    "msg" => "signing with a share of a key is not yet supported",
+                    "share_index" => share_index,
+                    "validator" => format!("{:?}", def.voting_public_key)
+                );
+            }
+        }
+
         this.update_validators()?;
         Ok(this)
     }
 
+    /// Returns `true` if a validator may not be initialized whilst the lockfile of its keystore
+    /// exists.
+    pub fn strict_lockfiles(&self) -> bool {
+        self.strict_lockfiles
+    }
+
     /// The count of enabled validators contained in `self`.
     pub fn num_enabled(&self) -> usize {
         self.validators.len()
@@ -311,12 +367,132 @@ impl InitializedValidators {
         self.validators.iter().map(|(pubkey, _)| pubkey)
     }
 
-    /// Returns the voting `Keypair` for a given voting `PublicKey`, if that validator is known to
-    /// `self` **and** the validator is enabled.
-    pub fn voting_keypair(&self, voting_public_key: &PublicKey) -> Option<&Keypair> {
+    /// Returns the `InitializedValidator` for a given voting `PublicKey`, if that validator is
+    /// known to `self` **and** the validator is enabled.
+    pub fn validator(&self, voting_public_key: &PublicKey) -> Option<&InitializedValidator> {
+        self.validators.get(voting_public_key)
+    }
+
+    /// Returns all definitions, whether their validators are enabled or not.
+    pub fn definitions(&self) -> &[ValidatorDefinition] {
+        self.definitions.as_slice()
+    }
+
+    /// Returns the definition of the validator with the given voting `PublicKey`, if any.
+    pub fn definition(&self, voting_public_key: &PublicKey) -> Option<&ValidatorDefinition> {
+        self.definitions
+            .as_slice()
+            .iter()
+            .find(|def| def.voting_public_key == *voting_public_key)
+    }
+
+    /// Adds a new definition, initializes its validator if it is enabled and saves the
+    /// `ValidatorDefinitions` to file.
+    ///
+    /// ## Errors
+    ///
+    /// The definition is not kept if a definition with the same public key already exists or if
+    /// the validator cannot be initialized, e.g. because of a wrong password.
+    pub fn add_definition(&mut self, def: ValidatorDefinition) -> Result<(), Error> {
+        if self.definition(&def.voting_public_key).is_some() {
+            return Err(Error::DuplicatePublicKey(def.voting_public_key));
+        }
+
+        self.definitions.push(def);
+        if let Err(e) = self.update_validators() {
+            let index = self.definitions.as_slice().len() - 1;
+            self.definitions.remove(index);
+            return Err(e);
+        }
+
+        self.save_definitions()
+    }
+
+    /// Adds a new definition along with its `validator`, which the caller initialized from it, and
+    /// saves the `ValidatorDefinitions` to file.
+    ///
+    /// This allows a keystore to be decrypted without holding a lock on `self`.
+    ///
+    /// ## Errors
+    ///
+    /// The definition is not kept if a definition with the same public key already exists.
+    pub fn add_initialized_definition(
+        &mut self,
+        def: ValidatorDefinition,
+        validator: InitializedValidator,
+    ) -> Result<(), Error> {
+        if self.definition(&def.voting_public_key).is_some() {
+            return Err(Error::DuplicatePublicKey(def.voting_public_key));
+        }
+
+        info!(
+            self.log,
+            "Enabled validator";
+            "voting_pubkey" => format!("{:?}", def.voting_public_key)
+        );
+        self.definitions.push(def);
         self.validators
-            .get(voting_public_key)
-            .map(|v| v.voting_keypair())
+            .insert(validator.voting_public_key().clone(), validator);
+
+        self.save_definitions()
+    }
+
+    /// Removes the definition of the validator with the given voting `PublicKey`, disabling the
+    /// validator and saving the `ValidatorDefinitions` to file.
+    ///
+    /// Returns the removed definition, or `None` if there was no such definition. The lockfile of
+    /// a local keystore is removed, but the keystore itself is left on disk.
+    pub fn remove_definition(
+        &mut self,
+        voting_public_key: &PublicKey,
+    ) -> Result<Option<ValidatorDefinition>, Error> {
+        let index = match self
+            .definitions
+            .as_slice()
+            .iter()
+            .position(|def| def.voting_public_key == *voting_public_key)
+        {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+
+        let def = self.definitions.remove(index);
+        if self.validators.remove(voting_public_key).is_some() {
+            info!(
+                self.log,
+                "Removed validator";
+                "voting_pubkey" => format!("{:?}", voting_public_key)
+            );
+        }
+
+        self.save_definitions()?;
+
+        Ok(Some(def))
+    }
+
+    /// Modifies the definition of the validator with the given voting `PublicKey` with `update`
+    /// and saves the `ValidatorDefinitions` to file.
+    ///
+    /// Returns `false` if there is no such definition. `update` must not change whether the
+    /// validator is enabled, see `Self::set_validator_status`.
+    pub fn update_definition<F: FnOnce(&mut ValidatorDefinition)>(
+        &mut self,
+        voting_public_key: &PublicKey,
+        update: F,
+    ) -> Result<bool, Error> {
+        match self
+            .definitions
+            .as_mut_slice()
+            .iter_mut()
+            .find(|def| def.voting_public_key == *voting_public_key)
+        {
+            Some(def) => update(def),
+            None => return Ok(false),
+        }
+
+        self.save_definitions()?;
+
+        Ok(true)
     }
 
     /// Sets the `InitializedValidator` and `ValidatorDefinition` `enabled` values.
@@ -344,10 +520,156 @@ impl InitializedValidators {
 
         self.update_validators()?;
 
+        self.save_definitions()?;
+
+        Ok(())
+    }
+
+    /// Returns true if any definition is enabled or disabled automatically at some time, in
+    /// which case `Self::apply_schedule` should be called each slot.
+    pub fn has_schedule(&self) -> bool {
+        self.definitions
+            .as_slice()
+            .iter()
+            .any(ValidatorDefinition::is_scheduled)
+    }
+
+    /// Enables and disables validators according to the `enable_at` and `disable_at` times of
+    /// their definitions, given the current `epoch`.
+    ///
+    /// Returns the public keys of the newly enabled validators. The definitions on disk are not
+    /// modified.
+    pub fn apply_schedule(&mut self, epoch: Epoch) -> Result<Vec<PublicKey>, Error> {
+        self.schedule_epoch = Some(epoch);
+
+        let previously_enabled = self.validators.keys().cloned().collect::<Vec<_>>();
+        self.update_validators()?;
+
+        Ok(self
+            .validators
+            .keys()
+            .filter(|pubkey| !previously_enabled.contains(pubkey))
+            .cloned()
+            .collect())
+    }
+
+    /// Begins to reload the definitions from file if it was modified since it was last read or
+    /// written by `self`, returning `None` if it was not.
+    ///
+    /// The reload is completed by `DefinitionsReload::initialize_validators` followed by
+    /// `Self::complete_reload`, which allows the keystores to be decrypted without holding a lock
+    /// on `self`. The validators of new definitions are enabled and those whose definitions were
+    /// disabled or removed are disabled. The definitions of validators which were already enabled
+    /// are replaced, but their keystores are not reloaded.
+    ///
+    /// ## Errors
+    ///
+    /// A keystore password is never prompted for, since there may be no one to enter it.
+    pub fn begin_reload(&self) -> Result<Option<DefinitionsReload>, Error> {
+        let modified = definitions_file_modified(&self.validators_dir);
+        if modified.is_none() || modified == self.definitions_modified {
+            return Ok(None);
+        }
+
+        let definitions = ValidatorDefinitions::open(&self.validators_dir)
+            .map_err(Error::UnableToOpenDefinitions)?;
+
+        let unix_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut uninitialized = vec![];
+        for def in definitions.as_slice() {
+            if !def.is_enabled_at(unix_time, self.schedule_epoch)
+                || self.validators.contains_key(&def.voting_public_key)
+            {
+                continue;
+            }
+
+            if let SigningDefinition::LocalKeystore {
+                voting_keystore_path,
+                voting_keystore_password_path: None,
+                voting_keystore_password: None,
+            } = &def.signing_definition
+            {
+                return Err(Error::PasswordUnknown(voting_keystore_path.clone()));
+            }
+
+            uninitialized.push(def.clone());
+        }
+
+        Ok(Some(DefinitionsReload {
+            definitions,
+            modified,
+            previously_modified: self.definitions_modified,
+            unix_time,
+            schedule_epoch: self.schedule_epoch,
+            uninitialized,
+            validators: vec![],
+            strict_lockfiles: self.strict_lockfiles,
+            log: self.log.clone(),
+        }))
+    }
+
+    /// Replaces the definitions and validators of `self` with those of `reload`, returning the
+    /// public keys of the newly enabled validators.
+    ///
+    /// Returns `None` if the definitions of `self` were modified since the reload began, in which
+    /// case nothing is changed and the file is reloaded on the next call to `Self::begin_reload`.
+    pub fn complete_reload(&mut self, reload: DefinitionsReload) -> Option<Vec<PublicKey>> {
+        if self.definitions_modified != reload.previously_modified
+            || self.schedule_epoch != reload.schedule_epoch
+        {
+            return None;
+        }
+
+        let enabled = reload
+            .definitions
+            .as_slice()
+            .iter()
+            .filter(|def| def.is_enabled_at(reload.unix_time, reload.schedule_epoch))
+            .map(|def| &def.voting_public_key)
+            .collect::<Vec<_>>();
+
+        let log = &self.log;
+        self.validators.retain(|pubkey, _| {
+            let retained = enabled.contains(&pubkey);
+            if !retained {
+                info!(
+                    log,
+                    "Disabled validator";
+                    "voting_pubkey" => format!("{:?}", pubkey)
+                );
+            }
+            retained
+        });
+
+        let mut newly_enabled = vec![];
+        for validator in reload.validators {
+            let pubkey = validator.voting_public_key().clone();
+            if self.validators.contains_key(&pubkey) {
+                continue;
+            }
+            info!(
+                self.log,
+                "Enabled validator";
+                "voting_pubkey" => format!("{:?}", pubkey)
+            );
+            self.validators.insert(pubkey.clone(), validator);
+            newly_enabled.push(pubkey);
+        }
+
+        self.definitions = reload.definitions;
+        self.definitions_modified = reload.modified;
+
+        Some(newly_enabled)
+    }
+
+    /// Saves `self.definitions` to file.
+    fn save_definitions(&mut self) -> Result<(), Error> {
         self.definitions
             .save(&self.validators_dir)
             .map_err(Error::UnableToSaveDefinitions)?;
-
+        self.definitions_modified = definitions_file_modified(&self.validators_dir);
         Ok(())
     }
 
@@ -363,10 +685,16 @@ impl InitializedValidators {
     /// I.e., if there are two different definitions with the same public key then the second will
     /// be ignored.
     fn update_validators(&mut self) -> Result<(), Error> {
+        let unix_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
         for def in self.definitions.as_slice() {
-            if def.enabled {
+            if def.is_enabled_at(unix_time, self.schedule_epoch) {
                 match &def.signing_definition {
-                    SigningDefinition::LocalKeystore { .. } => {
+                    SigningDefinition::LocalKeystore { .. }
+                    | SigningDefinition::Web3Signer { .. }
+                    | SigningDefinition::ThresholdShare { .. } => {
                         if self.validators.contains_key(&def.voting_public_key) {
                             continue;
                         }
@@ -399,8 +727,7 @@ impl InitializedValidators {
                         }
                     }
                 }
-            } else {
-                self.validators.remove(&def.voting_public_key);
+            } else if self.validators.remove(&def.voting_public_key).is_some() {
                 info!(
                     self.log,
                     "Disabled validator";
@@ -411,3 +738,199 @@ impl InitializedValidators {
         Ok(())
     }
 }
+
+/// Validator definitions reloaded from file, which replace those of an `InitializedValidators`
+/// once their new validators are initialized.
+///
+/// See `InitializedValidators::begin_reload`.
+pub struct DefinitionsReload {
+    definitions: ValidatorDefinitions,
+    /// The modification time of the file when it was read.
+    modified: Option<SystemTime>,
+    /// The modification time of the file when it was last read or written by the
+    /// `InitializedValidators`, before this reload began.
+    previously_modified: Option<SystemTime>,
+    /// The time at which the schedules of the definitions were evaluated.
+    unix_time: Duration,
+    schedule_epoch: Option<Epoch>,
+    /// The definitions of enabled validators which are not yet initialized.
+    uninitialized: Vec<ValidatorDefinition>,
+    /// The validators initialized from `self.uninitialized`.
+    validators: Vec<InitializedValidator>,
+    strict_lockfiles: bool,
+    log: Logger,
+}
+
+impl DefinitionsReload {
+    /// Initializes the validators of new definitions, which may take a long time since their
+    /// keystores are decrypted.
+    ///
+    /// ## Errors
+    ///
+    /// Exits early if any validator cannot be initialized, in which case the reload must be
+    /// abandoned.
+    pub fn initialize_validators(&mut self) -> Result<(), Error> {
+        for def in self.uninitialized.drain(..) {
+            let voting_public_key = def.voting_public_key.clone();
+            match InitializedValidator::from_definition(def, self.strict_lockfiles, &self.log) {
+                Ok(validator) => self.validators.push(validator),
+                Err(e) => {
+                    error!(
+                        self.log,
+                        "Failed to initialize validator";
+                        "error" => format!("{:?}", e),
+                        "validator" => format!("{:?}", voting_public_key)
+                    );
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The public keys of the validators initialized by `Self::initialize_validators`.
+    pub fn new_validators(&self) -> Vec<PublicKey> {
+        self.validators
+            .iter()
+            .map(|validator| validator.voting_public_key().clone())
+            .collect()
+    }
+}
+
+/// Returns the modification time of the definitions file in `validators_dir`, if it exists.
+fn definitions_file_modified(validators_dir: &Path) -> Option<SystemTime> {
+    fs::metadata(validators_dir.join(CONFIG_FILENAME))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::o;
+    use tempdir::TempDir;
+
+    fn web3signer_definition() -> ValidatorDefinition {
+        ValidatorDefinition::new_web3signer(
+            Keypair::random().pk,
+            "http://localhost:9000".to_string(),
+        )
+    }
+
+    fn save_definitions(validators_dir: &Path, defs: &[ValidatorDefinition]) {
+        let mut definitions = ValidatorDefinitions::default();
+        for def in defs {
+            definitions.push(def.clone());
+        }
+        definitions.save(validators_dir).unwrap();
+    }
+
+    fn initialized_validators(validators_dir: &Path) -> InitializedValidators {
+        InitializedValidators::from_definitions(
+            ValidatorDefinitions::open(validators_dir).unwrap(),
+            validators_dir.to_path_buf(),
+            true,
+            Logger::root(slog::Discard, o!()),
+        )
+        .unwrap()
+    }
+
+    /// Reloads the definitions in the same steps as the `ValidatorStore`.
+    fn reload(validators: &mut InitializedValidators) -> Result<Option<Vec<PublicKey>>, Error> {
+        // The modification time may not change between writes in quick succession.
+        validators.definitions_modified = None;
+
+        let mut reload = match validators.begin_reload()? {
+            Some(reload) => reload,
+            None => return Ok(None),
+        };
+        reload.initialize_validators()?;
+        Ok(validators.complete_reload(reload))
+    }
+
+    #[test]
+    fn unmodified_file_is_not_reloaded() {
+        let dir = TempDir::new("reload").unwrap();
+        save_definitions(dir.path(), &[web3signer_definition()]);
+        let validators = initialized_validators(dir.path());
+
+        assert!(validators.begin_reload().unwrap().is_none());
+    }
+
+    #[test]
+    fn reload_adds_validators() {
+        let dir = TempDir::new("reload").unwrap();
+        let first = web3signer_definition();
+        let second = web3signer_definition();
+        save_definitions(dir.path(), &[first.clone()]);
+        let mut validators = initialized_validators(dir.path());
+
+        save_definitions(dir.path(), &[first, second.clone()]);
+        assert_eq!(
+            reload(&mut validators).unwrap(),
+            Some(vec![second.voting_public_key.clone()])
+        );
+        assert_eq!(validators.num_enabled(), 2);
+        assert!(validators.validator(&second.voting_public_key).is_some());
+    }
+
+    #[test]
+    fn reload_removes_validators() {
+        let dir = TempDir::new("reload").unwrap();
+        let first = web3signer_definition();
+        let mut second = web3signer_definition();
+        let third = web3signer_definition();
+        save_definitions(dir.path(), &[first.clone(), second.clone(), third.clone()]);
+        let mut validators = initialized_validators(dir.path());
+
+        second.enabled = false;
+        save_definitions(dir.path(), &[first, second.clone()]);
+        assert_eq!(reload(&mut validators).unwrap(), Some(vec![]));
+        assert_eq!(validators.num_enabled(), 1);
+        assert_eq!(validators.num_total(), 2);
+        assert!(validators.validator(&second.voting_public_key).is_none());
+        assert!(validators.validator(&third.voting_public_key).is_none());
+    }
+
+    #[test]
+    fn invalid_file_changes_nothing() {
+        let dir = TempDir::new("reload").unwrap();
+        let first = web3signer_definition();
+        save_definitions(dir.path(), &[first.clone()]);
+        let mut validators = initialized_validators(dir.path());
+
+        fs::write(dir.path().join(CONFIG_FILENAME), "not: [valid").unwrap();
+        assert!(matches!(
+            reload(&mut validators),
+            Err(Error::UnableToOpenDefinitions(_))
+        ));
+        assert_eq!(validators.num_enabled(), 1);
+        assert_eq!(validators.num_total(), 1);
+        assert!(validators.validator(&first.voting_public_key).is_some());
+    }
+
+    #[test]
+    fn failed_initialization_changes_nothing() {
+        let dir = TempDir::new("reload").unwrap();
+        let first = web3signer_definition();
+        let mut second = web3signer_definition();
+        save_definitions(dir.path(), &[first.clone()]);
+        let mut validators = initialized_validators(dir.path());
+
+        if let SigningDefinition::Web3Signer {
+            root_certificate_path,
+            ..
+        } = &mut second.signing_definition
+        {
+            *root_certificate_path = Some(dir.path().join("missing.pem"));
+        }
+        save_definitions(dir.path(), &[second]);
+        assert!(matches!(
+            reload(&mut validators),
+            Err(Error::UnableToInitializeWeb3Signer(_))
+        ));
+        assert_eq!(validators.num_enabled(), 1);
+        assert_eq!(validators.num_total(), 1);
+        assert!(validators.validator(&first.voting_public_key).is_some());
+    }
+}
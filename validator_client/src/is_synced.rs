@@ -1,4 +1,4 @@
-use remote_beacon_node::RemoteBeaconNode;
+use remote_beacon_node::{Error, RemoteBeaconNode};
 use rest_types::SyncingResponse;
 use slog::{debug, error, Logger};
 use slot_clock::SlotClock;
@@ -7,13 +7,11 @@ use types::EthSpec;
 /// A distance in slots.
 const SYNC_TOLERANCE: u64 = 4;
 
-/// Returns `true` if the beacon node is synced and ready for action.
+/// Returns `true` if the beacon node is synced and ready for action, or an error if the beacon
+/// node is unreachable.
 ///
-/// Returns `false` if:
-///
-///  - The beacon node is unreachable.
-///  - The beacon node indicates that it is syncing **AND** it is more than `SYNC_TOLERANCE` behind
-///  the highest known slot.
+/// Returns `false` if the beacon node indicates that it is syncing **AND** it is more than
+/// `SYNC_TOLERANCE` behind the highest known slot.
 ///
 ///  The second condition means the even if the beacon node thinks that it's syncing, we'll still
 ///  try to use it if it's close enough to the head.
@@ -21,7 +19,7 @@ pub async fn is_synced<T: SlotClock, E: EthSpec>(
     beacon_node: &RemoteBeaconNode<E>,
     slot_clock: &T,
     log_opt: Option<&Logger>,
-) -> bool {
+) -> Result<bool, Error> {
     let resp = match beacon_node.http.node().syncing_status().await {
         Ok(resp) => resp,
         Err(e) => {
@@ -29,21 +27,23 @@ pub async fn is_synced<T: SlotClock, E: EthSpec>(
                 error!(
                     log,
                     "Unable connect to beacon node";
-                    "error" => format!("{:?}", e)
+                    "error" => format!("{:?}", e),
+                    "endpoint" => beacon_node.http.server_url(),
                 )
             }
 
-            return false;
+            return Err(e);
         }
     };
 
-    match &resp {
+    let synced = match &resp {
         SyncingResponse {
             is_syncing: false, ..
         } => true,
         SyncingResponse {
             is_syncing: true,
             sync_status,
+            ..
         } => {
             if let Some(log) = log_opt {
                 debug!(
@@ -58,7 +58,7 @@ pub async fn is_synced<T: SlotClock, E: EthSpec>(
             } else {
                 // There's no good reason why we shouldn't be able to read the slot clock, so we'll
                 // indicate we're not synced if that's the case.
-                return false;
+                return Ok(false);
             };
 
             if sync_status.current_slot + SYNC_TOLERANCE >= now {
@@ -71,10 +71,12 @@ pub async fn is_synced<T: SlotClock, E: EthSpec>(
                         "msg" => "not receiving new duties",
                         "target_slot" => sync_status.highest_slot.as_u64(),
                         "current_slot" => sync_status.current_slot.as_u64(),
+                        "endpoint" => beacon_node.http.server_url(),
                     );
                 }
                 false
             }
         }
-    }
+    };
+    Ok(synced)
 }
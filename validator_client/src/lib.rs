@@ -1,34 +1,55 @@
+#[macro_use]
+extern crate lazy_static;
+
 mod attestation_service;
+mod beacon_node_fallback;
 mod block_service;
 mod cli;
+mod clock_drift;
 mod config;
 mod duties_service;
 mod fork_service;
+mod graffiti;
+mod http_api;
+mod http_metrics;
 mod initialized_validators;
 mod is_synced;
+mod metrics;
 mod notifier;
+mod postmortem;
+mod signing_method;
+mod sync_committee_service;
+mod validator_schedule;
 mod validator_store;
+mod web3signer;
 
 pub use cli::cli_app;
 pub use config::Config;
 
 use account_utils::validator_definitions::ValidatorDefinitions;
 use attestation_service::{AttestationService, AttestationServiceBuilder};
+use beacon_node_fallback::BeaconNodeFallback;
 use block_service::{BlockService, BlockServiceBuilder};
 use clap::ArgMatches;
+use clock_drift::spawn_clock_drift_check;
 use duties_service::{DutiesService, DutiesServiceBuilder};
-use environment::RuntimeContext;
+use environment::{RuntimeContext, ShutdownStage};
 use fork_service::{ForkService, ForkServiceBuilder};
-use futures::channel::mpsc;
+use futures::channel::{mpsc, oneshot};
 use initialized_validators::InitializedValidators;
 use notifier::spawn_notifier;
+use postmortem::Postmortems;
 use remote_beacon_node::RemoteBeaconNode;
+use slashing_protection::SlashingProtection;
 use slog::{error, info, Logger};
 use slot_clock::SlotClock;
 use slot_clock::SystemTimeSlotClock;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
+use sync_committee_service::{SyncCommitteeService, SyncCommitteeServiceBuilder};
 use tokio::time::{delay_for, Duration};
 use types::EthSpec;
+use validator_schedule::spawn_validator_schedule;
 use validator_store::ValidatorStore;
 
 /// The interval between attempts to contact the beacon node during startup.
@@ -43,6 +64,11 @@ pub struct ProductionValidatorClient<T: EthSpec> {
     fork_service: ForkService<SystemTimeSlotClock, T>,
     block_service: BlockService<SystemTimeSlotClock, T>,
     attestation_service: AttestationService<SystemTimeSlotClock, T>,
+    sync_committee_service: SyncCommitteeService<SystemTimeSlotClock, T>,
+    beacon_nodes: Arc<BeaconNodeFallback<SystemTimeSlotClock, T>>,
+    validator_store: ValidatorStore<SystemTimeSlotClock, T>,
+    slashing_protection: Arc<dyn SlashingProtection>,
+    postmortems: Postmortems,
     config: Config,
 }
 
@@ -66,7 +92,7 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
         info!(
             log,
             "Starting validator client";
-            "beacon_node" => &config.http_server,
+            "beacon_nodes" => format!("{:?}", config.beacon_nodes),
             "datadir" => format!("{:?}", config.data_dir),
         );
 
@@ -102,12 +128,17 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             "enabled" => validators.num_enabled(),
         );
 
-        let beacon_node =
-            RemoteBeaconNode::new_with_timeout(config.http_server.clone(), HTTP_TIMEOUT)
-                .map_err(|e| format!("Unable to init beacon node http client: {}", e))?;
+        let beacon_nodes = config
+            .beacon_nodes
+            .iter()
+            .map(|url| {
+                RemoteBeaconNode::new_with_timeout(url.clone(), HTTP_TIMEOUT)
+                    .map_err(|e| format!("Unable to init beacon node http client: {}", e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
         // TODO: check if all logs in wait_for_node are produed while awaiting
-        let beacon_node = wait_for_node(beacon_node, &log).await?;
+        let beacon_node = wait_for_node(&beacon_nodes, &log).await?;
         let eth2_config = beacon_node
             .http
             .spec()
@@ -180,9 +211,23 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             Duration::from_millis(context.eth2_config.spec.milliseconds_per_slot),
         );
 
+        let beacon_nodes = Arc::new(BeaconNodeFallback::new(
+            beacon_nodes,
+            slot_clock.clone(),
+            log.clone(),
+        ));
+        beacon_nodes.update_all_candidates().await;
+        info!(
+            log,
+            "Initialized beacon nodes";
+            "nodes" => beacon_nodes.topology(),
+            "synced" => beacon_nodes.num_synced(),
+            "total" => beacon_nodes.num_total(),
+        );
+
         let fork_service = ForkServiceBuilder::new()
             .slot_clock(slot_clock.clone())
-            .beacon_node(beacon_node.clone())
+            .beacon_nodes(beacon_nodes.clone())
             .runtime_context(context.service_context("fork".into()))
             .build()?;
 
@@ -192,6 +237,7 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             genesis_validators_root,
             context.eth2_config.spec.clone(),
             fork_service.clone(),
+            context.executor.clone(),
             log.clone(),
         )?;
 
@@ -201,12 +247,35 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             "voting_validators" => validator_store.num_voting_validators()
         );
 
-        validator_store.register_all_validators_for_slashing_protection()?;
+        validator_store
+            .register_all_validators_for_slashing_protection()
+            .await?;
+
+        // The schedule is applied before duties are first fetched, so that the validators of the
+        // current epoch are known from the start.
+        if validator_store.has_validator_schedule() {
+            let current_epoch = slot_clock
+                .now()
+                .ok_or_else(|| "Unable to read slot clock".to_string())?
+                .epoch(T::slots_per_epoch());
+            validator_store
+                .apply_validator_schedule(current_epoch)
+                .await?;
+            info!(
+                log,
+                "Applied validator schedule";
+                "enabled" => validator_store.num_voting_validators(),
+                "epoch" => current_epoch.as_u64(),
+            );
+        }
+
+        let slashing_protection = validator_store.slashing_protection().clone();
+        let postmortems = Postmortems::default();
 
         let duties_service = DutiesServiceBuilder::new()
             .slot_clock(slot_clock.clone())
             .validator_store(validator_store.clone())
-            .beacon_node(beacon_node.clone())
+            .beacon_nodes(beacon_nodes.clone())
             .runtime_context(context.service_context("duties".into()))
             .allow_unsynced_beacon_node(config.allow_unsynced_beacon_node)
             .build()?;
@@ -214,17 +283,28 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
         let block_service = BlockServiceBuilder::new()
             .slot_clock(slot_clock.clone())
             .validator_store(validator_store.clone())
-            .beacon_node(beacon_node.clone())
+            .beacon_nodes(beacon_nodes.clone())
             .runtime_context(context.service_context("block".into()))
-            .graffiti(config.graffiti)
+            .graffiti(config.graffiti.clone())
+            .block_cutoff(config.block_cutoff)
+            .postmortems(postmortems.clone())
+            .build()?;
+
+        let sync_committee_service = SyncCommitteeServiceBuilder::new()
+            .slot_clock(slot_clock.clone())
+            .validator_store(validator_store.clone())
+            .beacon_nodes(beacon_nodes.clone())
+            .runtime_context(context.service_context("sync_committee".into()))
             .build()?;
 
         let attestation_service = AttestationServiceBuilder::new()
             .duties_service(duties_service.clone())
             .slot_clock(slot_clock)
-            .validator_store(validator_store)
-            .beacon_node(beacon_node)
+            .validator_store(validator_store.clone())
+            .beacon_nodes(beacon_nodes.clone())
             .runtime_context(context.service_context("attestation".into()))
+            .attestation_cutoff_percent(config.attestation_cutoff_percent)
+            .postmortems(postmortems.clone())
             .build()?;
 
         Ok(Self {
@@ -233,6 +313,11 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             fork_service,
             block_service,
             attestation_service,
+            sync_committee_service,
+            beacon_nodes,
+            validator_store,
+            slashing_protection,
+            postmortems,
             config,
         })
     }
@@ -243,10 +328,15 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
         // whole epoch!
         let channel_capacity = T::slots_per_epoch() as usize;
         let (block_service_tx, block_service_rx) = mpsc::channel(channel_capacity);
+        // Notifies the attestation service once the duties of the current epoch are known.
+        let (warm_up_tx, warm_up_rx) = oneshot::channel();
+
+        spawn_validator_schedule(self)
+            .map_err(|e| format!("Unable to start validator schedule: {}", e))?;
 
         self.duties_service
             .clone()
-            .start_update_service(block_service_tx, &self.context.eth2_config.spec)
+            .start_update_service(block_service_tx, warm_up_tx, &self.context.eth2_config.spec)
             .map_err(|e| format!("Unable to start duties service: {}", e))?;
 
         self.fork_service
@@ -261,50 +351,106 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
 
         self.attestation_service
             .clone()
-            .start_update_service(&self.context.eth2_config.spec)
+            .start_update_service(warm_up_rx, &self.context.eth2_config.spec)
             .map_err(|e| format!("Unable to start attestation service: {}", e))?;
 
+        self.sync_committee_service
+            .clone()
+            .start_update_service(&self.context.eth2_config.spec)
+            .map_err(|e| format!("Unable to start sync committee service: {}", e))?;
+
         spawn_notifier(self).map_err(|e| format!("Failed to start notifier: {}", e))?;
 
+        spawn_clock_drift_check(self)
+            .map_err(|e| format!("Failed to start clock drift check: {}", e))?;
+
+        if self.config.http_api_enabled {
+            let ctx = Arc::new(http_api::Context {
+                api_token: http_api::load_or_create_api_token(&self.config.data_dir)?,
+                validator_store: self.validator_store.clone(),
+                validators_dir: self.config.data_dir.clone(),
+                log: self.context.log().clone(),
+            });
+            let listen_addr = http_api::start_server(
+                &self.config,
+                ctx,
+                self.context
+                    .executor
+                    .clone()
+                    .with_shutdown_stage(ShutdownStage::Api),
+            )?;
+            info!(
+                self.context.log(),
+                "HTTP API started";
+                "api_token_file" => format!(
+                    "{:?}",
+                    self.config.data_dir.join(http_api::API_TOKEN_FILENAME)
+                ),
+                "address" => format!("{}", listen_addr.ip()),
+                "port" => listen_addr.port(),
+            );
+        }
+
+        if self.config.metrics_enabled {
+            let listen_addr = http_metrics::start_server(
+                &self.config,
+                self.slashing_protection.clone(),
+                self.postmortems.clone(),
+                self.context.executor.clone(),
+            )?;
+            info!(
+                self.context.log(),
+                "Metrics HTTP server started";
+                "address" => format!("{}", listen_addr.ip()),
+                "port" => listen_addr.port(),
+            );
+        }
+
         Ok(())
     }
 }
 
-/// Request the version from the node, looping back and trying again on failure. Exit once the node
-/// has been contacted.
+/// Request the version from each node in turn, looping back and trying again on failure. Exit with
+/// the first node which has been contacted.
 async fn wait_for_node<E: EthSpec>(
-    beacon_node: RemoteBeaconNode<E>,
+    beacon_nodes: &[RemoteBeaconNode<E>],
     log: &Logger,
 ) -> Result<RemoteBeaconNode<E>, String> {
-    // Try to get the version string from the node, looping until success is returned.
-    loop {
-        let log = log.clone();
-        let result = beacon_node
-            .clone()
-            .http
-            .node()
-            .get_version()
-            .await
-            .map_err(|e| format!("{:?}", e));
-
-        match result {
-            Ok(version) => {
-                info!(
-                    log,
-                    "Connected to beacon node";
-                    "version" => version,
-                );
+    if beacon_nodes.is_empty() {
+        return Err("No beacon nodes were given".to_string());
+    }
 
-                return Ok(beacon_node);
-            }
-            Err(e) => {
-                error!(
-                    log,
-                    "Unable to connect to beacon node";
-                    "error" => format!("{:?}", e),
-                );
-                delay_for(RETRY_DELAY).await;
+    // Try to get the version string from the nodes, looping until success is returned.
+    loop {
+        for beacon_node in beacon_nodes {
+            let result = beacon_node
+                .http
+                .node()
+                .get_version()
+                .await
+                .map_err(|e| format!("{:?}", e));
+
+            match result {
+                Ok(version) => {
+                    info!(
+                        log,
+                        "Connected to beacon node";
+                        "version" => version,
+                        "endpoint" => beacon_node.http.server_url(),
+                    );
+
+                    return Ok(beacon_node.clone());
+                }
+                Err(e) => {
+                    error!(
+                        log,
+                        "Unable to connect to beacon node";
+                        "error" => format!("{:?}", e),
+                        "endpoint" => beacon_node.http.server_url(),
+                    );
+                }
             }
         }
+        delay_for(RETRY_DELAY).await;
     }
 }
@@ -0,0 +1,136 @@
+//! Prometheus metrics for the validator client.
+use remote_beacon_node::PublishStatus;
+
+pub use lighthouse_metrics::*;
+
+lazy_static! {
+    pub static ref ATTESTATIONS_SKIPPED_AFTER_CUTOFF: Result<IntCounter> = try_create_int_counter(
+        "vc_attestations_skipped_after_cutoff_total",
+        "Number of attestations that were not signed because the attestation cutoff had passed"
+    );
+    pub static ref BLOCKS_SKIPPED_AFTER_CUTOFF: Result<IntCounter> = try_create_int_counter(
+        "vc_blocks_skipped_after_cutoff_total",
+        "Number of block proposals that were not signed or published because the block cutoff \
+         had passed"
+    );
+
+    /*
+     * Duties
+     */
+    pub static ref DUTIES_OBTAINED: Result<IntCounterVec> = try_create_int_counter_vec(
+        "vc_duties_obtained_total",
+        "Number of validator duties obtained from the beacon node, by how they compared to the \
+         duties already known",
+        &["outcome"]
+    );
+
+    /*
+     * Signing
+     */
+    pub static ref SIGNED_BLOCKS: Result<IntCounter> = try_create_int_counter(
+        "vc_signed_blocks_total",
+        "Number of blocks signed"
+    );
+    pub static ref SIGNED_ATTESTATIONS: Result<IntCounter> = try_create_int_counter(
+        "vc_signed_attestations_total",
+        "Number of attestations signed"
+    );
+    pub static ref SIGNED_AGGREGATES: Result<IntCounter> = try_create_int_counter(
+        "vc_signed_aggregates_total",
+        "Number of aggregate and proofs signed"
+    );
+    pub static ref SIGNED_SYNC_COMMITTEE_MESSAGES: Result<IntCounter> = try_create_int_counter(
+        "vc_signed_sync_committee_messages_total",
+        "Number of sync committee messages signed"
+    );
+    pub static ref SIGNED_CONTRIBUTIONS: Result<IntCounter> = try_create_int_counter(
+        "vc_signed_contributions_total",
+        "Number of sync committee contribution and proofs signed"
+    );
+    pub static ref SIGNING_TIMES: Result<HistogramVec> = try_create_histogram_vec(
+        "vc_signing_seconds",
+        "Time taken to sign a message with a local keystore or remote signer",
+        &["type"]
+    );
+    pub static ref SLASHING_PROTECTION_CHECK_FAILURES: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "vc_slashing_protection_check_failures_total",
+            "Number of messages which were not signed because the slashing protection did not \
+             approve them",
+            &["type", "reason"]
+        );
+
+    /*
+     * Publishing
+     */
+    pub static ref PUBLISHED_BLOCKS: Result<IntCounterVec> = try_create_int_counter_vec(
+        "vc_published_blocks_total",
+        "Number of blocks published to the beacon nodes, by the response of the beacon nodes",
+        &["status"]
+    );
+    pub static ref PUBLISHED_ATTESTATIONS: Result<IntCounterVec> = try_create_int_counter_vec(
+        "vc_published_attestations_total",
+        "Number of attestations published to the beacon nodes, by the response of the beacon \
+         nodes",
+        &["status"]
+    );
+    pub static ref PUBLISHED_AGGREGATES: Result<IntCounterVec> = try_create_int_counter_vec(
+        "vc_published_aggregates_total",
+        "Number of aggregate and proofs published to the beacon nodes, by the response of the \
+         beacon nodes",
+        &["status"]
+    );
+    pub static ref PUBLISHED_SYNC_COMMITTEE_MESSAGES: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "vc_published_sync_committee_messages_total",
+            "Number of sync committee messages published to the beacon nodes, by the response of \
+             the beacon nodes",
+            &["status"]
+        );
+    pub static ref PUBLISHED_CONTRIBUTIONS: Result<IntCounterVec> = try_create_int_counter_vec(
+        "vc_published_contributions_total",
+        "Number of sync committee contribution and proofs published to the beacon nodes, by the \
+         response of the beacon nodes",
+        &["status"]
+    );
+
+    /*
+     * Beacon nodes
+     */
+    pub static ref BEACON_NODE_REQUEST_TIMES: Result<HistogramVec> = try_create_histogram_vec(
+        "vc_beacon_node_request_seconds",
+        "Time taken for a beacon node to respond to a request",
+        &["request"]
+    );
+    pub static ref BEACON_NODE_REQUEST_ERRORS: Result<IntCounterVec> =
+        try_create_int_counter_vec(
+            "vc_beacon_node_request_errors_total",
+            "Number of requests to a beacon node which failed",
+            &["request"]
+        );
+
+    /*
+     * Clock
+     */
+    pub static ref CLOCK_DRIFT: Result<IntGauge> = try_create_int_gauge(
+        "vc_clock_drift_milliseconds",
+        "Offset of the time of the network from the local clock, positive if the local clock \
+         is behind"
+    );
+}
+
+/// Counts `count` messages published with the given `result` in `counter`, labelled by the
+/// response of the beacon nodes.
+pub fn observe_publish<E>(
+    counter: &Result<IntCounterVec>,
+    count: usize,
+    result: &std::result::Result<PublishStatus, E>,
+) {
+    let status = match result {
+        Ok(PublishStatus::Valid) => "valid",
+        Ok(PublishStatus::Invalid(_)) => "invalid",
+        Ok(PublishStatus::Unknown) => "unknown",
+        Err(_) => "error",
+    };
+    inc_counter_vec_by(counter, &[status], count as i64);
+}
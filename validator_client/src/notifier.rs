@@ -1,6 +1,6 @@
-use crate::{is_synced::is_synced, ProductionValidatorClient};
+use crate::ProductionValidatorClient;
 use futures::StreamExt;
-use slog::{error, info};
+use slog::{error, info, warn};
 use slot_clock::SlotClock;
 use tokio::time::{interval_at, Duration, Instant};
 use types::EthSpec;
@@ -10,6 +10,7 @@ pub fn spawn_notifier<T: EthSpec>(client: &ProductionValidatorClient<T>) -> Resu
     let context = client.context.service_context("notifier".into());
     let executor = context.executor.clone();
     let duties_service = client.duties_service.clone();
+    let beacon_nodes = client.beacon_nodes.clone();
     let allow_unsynced_beacon_node = client.config.allow_unsynced_beacon_node;
 
     let slot_duration = Duration::from_millis(context.eth2_config.spec.milliseconds_per_slot);
@@ -26,14 +27,40 @@ pub fn spawn_notifier<T: EthSpec>(client: &ProductionValidatorClient<T>) -> Resu
         let log = context.log();
 
         while interval.next().await.is_some() {
-            if !is_synced(
-                &duties_service.beacon_node,
-                &duties_service.slot_clock,
-                Some(&log),
-            )
-            .await
-                && !allow_unsynced_beacon_node
+            let num_synced = beacon_nodes.num_synced();
+            let num_total = beacon_nodes.num_total();
+
+            if duties_service
+                .slot_clock
+                .now()
+                .map_or(false, |slot| slot % T::slots_per_epoch() == 0)
             {
+                if num_synced < num_total {
+                    warn!(
+                        log,
+                        "Some beacon nodes are unavailable";
+                        "nodes" => beacon_nodes.topology(),
+                        "synced" => num_synced,
+                        "total" => num_total,
+                    );
+                } else {
+                    info!(
+                        log,
+                        "Beacon node topology";
+                        "nodes" => beacon_nodes.topology(),
+                        "synced" => num_synced,
+                        "total" => num_total,
+                    );
+                }
+            }
+
+            if num_synced == 0 && !allow_unsynced_beacon_node {
+                error!(
+                    log,
+                    "No synced beacon nodes";
+                    "msg" => "not receiving new duties",
+                    "nodes" => beacon_nodes.topology(),
+                );
                 continue;
             }
 
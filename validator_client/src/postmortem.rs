@@ -0,0 +1,269 @@
+//! Records how a duty was performed, so that when an attestation or block proposal is missed the
+//! validator client can log why, rather than leaving the operator to guess from scattered logs.
+//!
+//! The last `MAX_POSTMORTEMS` postmortems are served by the metrics server at `/postmortems`.
+use parking_lot::RwLock;
+use remote_beacon_node::PublishStatus;
+use serde_derive::Serialize;
+use slog::{warn, Logger};
+use slot_clock::SlotClock;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Instant;
+use types::{PublicKey, Slot};
+
+/// The number of postmortems which are kept.
+pub const MAX_POSTMORTEMS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DutyKind {
+    Attestation,
+    Block,
+}
+
+/// A step in performing a duty, such as a request to the beacon node.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Step {
+    pub name: &'static str,
+    /// The milliseconds from the start of the slot until the step started.
+    pub start_ms: Option<u64>,
+    pub duration_ms: u64,
+    /// `ok`, or the error or status which the step ended with.
+    pub outcome: String,
+}
+
+/// How a missed duty was performed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Postmortem {
+    pub duty: DutyKind,
+    pub slot: Slot,
+    pub committee_index: Option<u64>,
+    pub validators: Vec<PublicKey>,
+    /// The beacon nodes which the duty was performed with, and their status, in order of
+    /// preference.
+    pub beacon_nodes: String,
+    /// The milliseconds from the start of the slot until the duty started.
+    pub start_ms: Option<u64>,
+    pub steps: Vec<Step>,
+    /// Why the duty was missed.
+    pub reason: String,
+}
+
+impl Postmortem {
+    /// Formats the steps on a single line, for logging.
+    fn steps_summary(&self) -> String {
+        self.steps
+            .iter()
+            .map(|step| {
+                let start = step
+                    .start_ms
+                    .map_or_else(|| "?".to_string(), |ms| ms.to_string());
+                format!(
+                    "{} at {}ms took {}ms: {}",
+                    step.name, start, step.duration_ms, step.outcome
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// The time at which a step started.
+pub struct StepTimer {
+    started: Instant,
+}
+
+/// Records the steps of a duty while it is performed.
+pub struct DutyTrace {
+    postmortem: Postmortem,
+    /// When the slot of the duty started, if known.
+    slot_start: Option<Instant>,
+    missed: bool,
+}
+
+impl DutyTrace {
+    pub fn new<T: SlotClock>(
+        duty: DutyKind,
+        slot: Slot,
+        committee_index: Option<u64>,
+        validators: Vec<PublicKey>,
+        beacon_nodes: String,
+        slot_clock: &T,
+    ) -> Self {
+        let now = Instant::now();
+        let slot_start = slot_clock
+            .duration_since_start_of(slot)
+            .and_then(|elapsed| now.checked_sub(elapsed));
+
+        let mut trace = Self {
+            postmortem: Postmortem {
+                duty,
+                slot,
+                committee_index,
+                validators,
+                beacon_nodes,
+                start_ms: None,
+                steps: vec![],
+                reason: String::new(),
+            },
+            slot_start,
+            missed: false,
+        };
+        trace.postmortem.start_ms = trace.ms_into_slot(now);
+        trace
+    }
+
+    pub fn start_step(&self) -> StepTimer {
+        StepTimer {
+            started: Instant::now(),
+        }
+    }
+
+    /// Records a step which started at `timer` and ended with `outcome`.
+    pub fn finish_step(&mut self, timer: StepTimer, name: &'static str, outcome: String) {
+        let step = Step {
+            name,
+            start_ms: self.ms_into_slot(timer.started),
+            duration_ms: timer.started.elapsed().as_millis() as u64,
+            outcome,
+        };
+        self.postmortem.steps.push(step);
+    }
+
+    /// Records a step which ended with `result`.
+    pub fn finish_step_with<T, E: Debug>(
+        &mut self,
+        timer: StepTimer,
+        name: &'static str,
+        result: &Result<T, E>,
+    ) {
+        let outcome = match result {
+            Ok(_) => "ok".to_string(),
+            Err(e) => format!("{:?}", e),
+        };
+        self.finish_step(timer, name, outcome)
+    }
+
+    /// Marks the duty as missed for `reason`. The first reason is kept.
+    pub fn missed(&mut self, reason: impl Into<String>) {
+        if !self.missed {
+            self.missed = true;
+            self.postmortem.reason = reason.into();
+        }
+    }
+
+    fn ms_into_slot(&self, instant: Instant) -> Option<u64> {
+        self.slot_start
+            .map(|start| instant.saturating_duration_since(start).as_millis() as u64)
+    }
+}
+
+/// The outcome of publishing to the beacon node: its status, or the error of the request.
+pub fn publish_outcome<E: Debug>(result: &Result<PublishStatus, E>) -> String {
+    match result {
+        Ok(status) => format!("{:?}", status),
+        Err(e) => format!("{:?}", e),
+    }
+}
+
+/// The postmortems of the most recently missed duties, oldest first.
+#[derive(Clone, Default)]
+pub struct Postmortems {
+    postmortems: Arc<RwLock<VecDeque<Postmortem>>>,
+}
+
+impl Postmortems {
+    /// Logs and keeps the postmortem of `trace` if its duty was missed.
+    pub fn record_if_missed(&self, trace: DutyTrace, log: &Logger) {
+        if !trace.missed {
+            return;
+        }
+        let postmortem = trace.postmortem;
+
+        warn!(
+            log,
+            "Missed duty postmortem";
+            "reason" => &postmortem.reason,
+            "steps" => postmortem.steps_summary(),
+            "start_ms" => postmortem.start_ms,
+            "beacon_nodes" => &postmortem.beacon_nodes,
+            "validators" => postmortem.validators.len(),
+            "committee_index" => postmortem.committee_index,
+            "slot" => postmortem.slot.as_u64(),
+            "duty" => format!("{:?}", postmortem.duty),
+        );
+
+        let mut postmortems = self.postmortems.write();
+        if postmortems.len() >= MAX_POSTMORTEMS {
+            postmortems.pop_front();
+        }
+        postmortems.push_back(postmortem);
+    }
+
+    /// Returns the kept postmortems, oldest first.
+    pub fn get(&self) -> Vec<Postmortem> {
+        self.postmortems.read().iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::o;
+    use slot_clock::TestingSlotClock;
+    use std::time::Duration;
+
+    fn trace(slot: u64) -> DutyTrace {
+        let slot_clock = TestingSlotClock::new(
+            Slot::new(0),
+            Duration::from_secs(0),
+            Duration::from_secs(12),
+        );
+        DutyTrace::new(
+            DutyKind::Attestation,
+            Slot::new(slot),
+            Some(1),
+            vec![],
+            "http://localhost:5052/ (synced)".to_string(),
+            &slot_clock,
+        )
+    }
+
+    #[test]
+    fn keeps_only_missed_duties() {
+        let log = Logger::root(slog::Discard, o!());
+        let postmortems = Postmortems::default();
+
+        let mut missed = trace(0);
+        let timer = missed.start_step();
+        missed.finish_step_with(timer, "produce_attestation", &Err::<(), _>("timeout"));
+        missed.missed("timeout");
+        missed.missed("a later reason");
+        postmortems.record_if_missed(missed, &log);
+        postmortems.record_if_missed(trace(1), &log);
+
+        let kept = postmortems.get();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].slot, Slot::new(0));
+        assert_eq!(kept[0].reason, "timeout");
+        assert_eq!(kept[0].steps[0].outcome, "\"timeout\"");
+    }
+
+    #[test]
+    fn keeps_the_latest_postmortems() {
+        let log = Logger::root(slog::Discard, o!());
+        let postmortems = Postmortems::default();
+
+        for slot in 0..MAX_POSTMORTEMS as u64 + 1 {
+            let mut trace = trace(slot);
+            trace.missed("missed");
+            postmortems.record_if_missed(trace, &log);
+        }
+
+        let kept = postmortems.get();
+        assert_eq!(kept.len(), MAX_POSTMORTEMS);
+        assert_eq!(kept[0].slot, Slot::new(1));
+    }
+}
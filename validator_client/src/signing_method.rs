@@ -0,0 +1,73 @@
+//! The methods by which validators sign messages, and the reasons a message may not be signed.
+//!
+//! A `SigningMethod` signs whatever it is given. The `ValidatorStore` checks the slashing
+//! protection before asking any method to sign a block or attestation, so that a new method
+//! cannot bypass it.
+use crate::web3signer::{self, Web3Signer};
+use futures::future::{self, BoxFuture};
+use slashing_protection::NotSafe;
+use types::{AttestationError, Epoch, Hash256, Keypair, PublicKey, Signature, Slot};
+
+/// Why a message was not signed.
+#[derive(Debug)]
+pub enum Error {
+    /// The validator is unknown or disabled.
+    UnknownValidator(PublicKey),
+    /// The fork is not yet known, so the domain of the message cannot be computed.
+    ForkUnknown,
+    /// The block is for a slot later than the current slot.
+    FutureSlot { slot: Slot, current_slot: Slot },
+    /// The attestation targets an epoch later than the current epoch.
+    FutureEpoch { epoch: Epoch, current_epoch: Epoch },
+    /// The message was signed before, so it is not signed again.
+    AlreadySigned,
+    /// The slashing protection refused the message, either because it may be slashable or
+    /// because the validator is not registered.
+    Refused(NotSafe),
+    /// The slashing protection check did not complete, e.g. because it panicked.
+    SlashingProtectionFailed(String),
+    /// The signature could not be produced, e.g. because a remote signer is unreachable.
+    SignerUnavailable(web3signer::Error),
+    /// Signing is paused because the local clock has drifted.
+    SigningPaused,
+    /// The signature could not be added to the attestation.
+    UnableToAddSignature(AttestationError),
+}
+
+/// A method by which a validator signs messages.
+///
+/// Signing is async, since a method may wait on a remote service.
+pub trait SigningMethod: Send + Sync {
+    /// Signs `signing_root` with the voting key of the validator.
+    ///
+    /// The slashing protection must be checked before this is called.
+    fn sign(&self, signing_root: Hash256) -> BoxFuture<'_, Result<Signature, Error>>;
+}
+
+/// Signs with a keypair decrypted from an EIP-2335 keystore on the local filesystem.
+pub struct LocalKeystore {
+    pub voting_keypair: Keypair,
+}
+
+impl SigningMethod for LocalKeystore {
+    fn sign(&self, signing_root: Hash256) -> BoxFuture<'_, Result<Signature, Error>> {
+        Box::pin(future::ready(Ok(self.voting_keypair.sk.sign(signing_root))))
+    }
+}
+
+/// Signs by sending the signing root to a remote Web3Signer, which holds the secret key.
+pub struct RemoteSigner {
+    pub voting_public_key: PublicKey,
+    pub signer: Web3Signer,
+}
+
+impl SigningMethod for RemoteSigner {
+    fn sign(&self, signing_root: Hash256) -> BoxFuture<'_, Result<Signature, Error>> {
+        Box::pin(async move {
+            self.signer
+                .sign(&self.voting_public_key, signing_root)
+                .await
+                .map_err(Error::SignerUnavailable)
+        })
+    }
+}
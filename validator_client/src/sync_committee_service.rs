@@ -0,0 +1,653 @@
+use crate::{beacon_node_fallback::BeaconNodeFallback, metrics, validator_store::ValidatorStore};
+use environment::RuntimeContext;
+use futures::future::join_all;
+use futures::StreamExt;
+use parking_lot::RwLock;
+use remote_beacon_node::{Error as RemoteBeaconNodeError, PublishStatus};
+use reqwest::StatusCode;
+use rest_types::SyncDuty;
+use slog::{crit, debug, error, info, trace};
+use slot_clock::SlotClock;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::ops::Deref;
+use std::sync::Arc;
+use tokio::time::{delay_until, interval_at, Duration, Instant};
+use types::{ChainSpec, Epoch, EthSpec, Fork, Hash256, PublicKey, Slot, SyncSelectionProof};
+
+/// Builds a `SyncCommitteeService`.
+pub struct SyncCommitteeServiceBuilder<T, E: EthSpec> {
+    validator_store: Option<ValidatorStore<T, E>>,
+    slot_clock: Option<T>,
+    beacon_nodes: Option<Arc<BeaconNodeFallback<T, E>>>,
+    context: Option<RuntimeContext<E>>,
+}
+
+impl<T: SlotClock + 'static, E: EthSpec> SyncCommitteeServiceBuilder<T, E> {
+    pub fn new() -> Self {
+        Self {
+            validator_store: None,
+            slot_clock: None,
+            beacon_nodes: None,
+            context: None,
+        }
+    }
+
+    pub fn validator_store(mut self, store: ValidatorStore<T, E>) -> Self {
+        self.validator_store = Some(store);
+        self
+    }
+
+    pub fn slot_clock(mut self, slot_clock: T) -> Self {
+        self.slot_clock = Some(slot_clock);
+        self
+    }
+
+    pub fn beacon_nodes(mut self, beacon_nodes: Arc<BeaconNodeFallback<T, E>>) -> Self {
+        self.beacon_nodes = Some(beacon_nodes);
+        self
+    }
+
+    pub fn runtime_context(mut self, context: RuntimeContext<E>) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    pub fn build(self) -> Result<SyncCommitteeService<T, E>, String> {
+        Ok(SyncCommitteeService {
+            inner: Arc::new(Inner {
+                validator_store: self
+                    .validator_store
+                    .ok_or_else(|| "Cannot build SyncCommitteeService without validator_store")?,
+                slot_clock: self
+                    .slot_clock
+                    .ok_or_else(|| "Cannot build SyncCommitteeService without slot_clock")?,
+                beacon_nodes: self
+                    .beacon_nodes
+                    .ok_or_else(|| "Cannot build SyncCommitteeService without beacon_nodes")?,
+                context: self
+                    .context
+                    .ok_or_else(|| "Cannot build SyncCommitteeService without runtime_context")?,
+                altair_fork_epoch: RwLock::new(None),
+                duties: RwLock::new(None),
+            }),
+        })
+    }
+}
+
+/// Helper to minimise `Arc` usage.
+pub struct Inner<T, E: EthSpec> {
+    validator_store: ValidatorStore<T, E>,
+    slot_clock: T,
+    beacon_nodes: Arc<BeaconNodeFallback<T, E>>,
+    context: RuntimeContext<E>,
+    /// The epoch of the Altair fork, as last reported by the fork schedule of the beacon nodes,
+    /// and the epoch in which the schedule was fetched.
+    altair_fork_epoch: RwLock<Option<(Epoch, Option<Epoch>)>>,
+    /// The sync committee duties of the local validators, and the epoch they were fetched for.
+    duties: RwLock<Option<(Epoch, Vec<SyncDuty>)>>,
+}
+
+/// Attempts to produce sync committee messages for all known validators 1/3rd of the way through
+/// each slot, and contributions for the elected aggregators 2/3rds of the way through.
+///
+/// Sync committees only exist from the Altair fork onwards. Until the beacon nodes report an
+/// Altair fork in their fork schedule, and that fork has been reached, the service does nothing.
+pub struct SyncCommitteeService<T, E: EthSpec> {
+    inner: Arc<Inner<T, E>>,
+}
+
+impl<T, E: EthSpec> Clone for SyncCommitteeService<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, E: EthSpec> Deref for SyncCommitteeService<T, E> {
+    type Target = Inner<T, E>;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner.deref()
+    }
+}
+
+impl<T: SlotClock + 'static, E: EthSpec> SyncCommitteeService<T, E> {
+    /// Starts the service which periodically produces sync committee messages.
+    pub fn start_update_service(self, spec: &ChainSpec) -> Result<(), String> {
+        let log = self.context.log().clone();
+
+        let slot_duration = Duration::from_millis(spec.milliseconds_per_slot);
+        let duration_to_next_slot = self
+            .slot_clock
+            .duration_to_next_slot()
+            .ok_or_else(|| "Unable to determine duration to next slot".to_string())?;
+
+        info!(
+            log,
+            "Sync committee service started";
+            "next_update_millis" => duration_to_next_slot.as_millis()
+        );
+
+        let mut interval = {
+            // Note: `interval_at` panics if `slot_duration` is 0
+            interval_at(
+                Instant::now() + duration_to_next_slot + slot_duration / 3,
+                slot_duration,
+            )
+        };
+
+        let executor = self.context.executor.clone();
+
+        let interval_fut = async move {
+            while interval.next().await.is_some() {
+                let log = self.context.log();
+
+                if let Err(e) = self.spawn_sync_committee_tasks(slot_duration) {
+                    crit!(
+                        log,
+                        "Failed to spawn sync committee tasks";
+                        "error" => e
+                    )
+                } else {
+                    trace!(
+                        log,
+                        "Spawned sync committee tasks";
+                    )
+                }
+            }
+        };
+
+        executor.spawn(interval_fut, "sync_committee_service");
+        Ok(())
+    }
+
+    /// Returns the epoch of the Altair fork, refreshing the fork schedule from the beacon nodes
+    /// once per epoch.
+    ///
+    /// Returns `None` whilst no beacon node reports an Altair fork.
+    async fn altair_fork_epoch(&self, epoch: Epoch) -> Option<Epoch> {
+        let cached = *self.altair_fork_epoch.read();
+        if let Some((fetched_at, altair_fork_epoch)) = cached {
+            if fetched_at == epoch {
+                return altair_fork_epoch;
+            }
+        }
+
+        let spec = &self.context.eth2_config().spec;
+        let altair_fork_epoch = match self
+            .beacon_nodes
+            .first_success("get_fork_schedule", |beacon_node| async move {
+                match beacon_node.http.config().get_fork_schedule().await {
+                    // Beacon nodes which predate the fork schedule endpoint only know of the
+                    // genesis fork, so this is not a failure of the beacon node.
+                    Err(RemoteBeaconNodeError::DidNotSucceed { status, .. })
+                        if status == StatusCode::NOT_FOUND =>
+                    {
+                        Ok(vec![])
+                    }
+                    other => other,
+                }
+            })
+            .await
+        {
+            Ok(schedule) => altair_fork_epoch(&schedule, spec),
+            Err(e) => {
+                debug!(
+                    self.context.log(),
+                    "Unable to fetch fork schedule";
+                    "error" => format!("{:?}", e),
+                );
+                None
+            }
+        };
+
+        *self.altair_fork_epoch.write() = Some((epoch, altair_fork_epoch));
+        altair_fork_epoch
+    }
+
+    /// Returns the sync committee duties of the local validators in `epoch`, downloading them
+    /// from the beacon nodes once per epoch.
+    async fn duties(&self, epoch: Epoch) -> Result<Vec<SyncDuty>, String> {
+        let cached = self.duties.read().clone();
+        if let Some((fetched_at, duties)) = cached {
+            if fetched_at == epoch {
+                return Ok(duties);
+            }
+        }
+
+        let pubkeys = &self.validator_store.voting_pubkeys();
+        let duties = self
+            .beacon_nodes
+            .first_success("get_sync_committee_duties", |beacon_node| async move {
+                beacon_node
+                    .http
+                    .validator()
+                    .get_sync_committee_duties(epoch, pubkeys)
+                    .await
+            })
+            .await
+            .map_err(|e| format!("Failed to download sync committee duties: {:?}", e))?;
+
+        *self.duties.write() = Some((epoch, duties.clone()));
+        Ok(duties)
+    }
+
+    /// Spawns a task that signs and publishes the sync committee messages of the current slot,
+    /// and then the contributions of the elected aggregators.
+    fn spawn_sync_committee_tasks(&self, slot_duration: Duration) -> Result<(), String> {
+        let slot = self
+            .slot_clock
+            .now()
+            .ok_or_else(|| "Failed to read slot clock".to_string())?;
+        let duration_to_next_slot = self
+            .slot_clock
+            .duration_to_next_slot()
+            .ok_or_else(|| "Unable to determine duration to next slot".to_string())?;
+
+        // Contributions are published 2/3rds of the way through the slot.
+        let contribution_instant = Instant::now()
+            + duration_to_next_slot
+                .checked_sub(slot_duration / 3)
+                .unwrap_or_else(|| Duration::from_secs(0));
+
+        self.inner.context.executor.runtime_handle().spawn(
+            self.clone()
+                .publish_messages_and_contributions(slot, contribution_instant),
+        );
+
+        Ok(())
+    }
+
+    /// Signs and publishes a sync committee message for each duty in `slot`, voting for the head
+    /// of the beacon nodes, and then waits for `contribution_instant` to publish the
+    /// contributions.
+    ///
+    /// Does nothing before the Altair fork.
+    async fn publish_messages_and_contributions(self, slot: Slot, contribution_instant: Instant) {
+        let log = self.context.log().clone();
+        let epoch = slot.epoch(E::slots_per_epoch());
+
+        if !is_sync_committee_active(self.altair_fork_epoch(epoch).await, epoch) {
+            trace!(
+                log,
+                "No sync committee duties before Altair";
+                "slot" => slot.as_u64(),
+            );
+            return;
+        }
+
+        let duties = match self.duties(epoch).await {
+            Ok(duties) if duties.is_empty() => return,
+            Ok(duties) => duties,
+            Err(e) => {
+                error!(
+                    log,
+                    "Unable to get sync committee duties";
+                    "error" => e,
+                    "epoch" => epoch.as_u64(),
+                );
+                return;
+            }
+        };
+
+        let beacon_block_root = match self
+            .beacon_nodes
+            .first_success("get_head", |beacon_node| async move {
+                beacon_node.http.beacon().get_head().await
+            })
+            .await
+        {
+            Ok(head) => head.block_root,
+            Err(e) => {
+                error!(
+                    log,
+                    "Unable to get head for sync committee messages";
+                    "error" => format!("{:?}", e),
+                    "slot" => slot.as_u64(),
+                );
+                return;
+            }
+        };
+
+        if let Err(e) = self
+            .produce_and_publish_messages(slot, beacon_block_root, &duties)
+            .await
+        {
+            crit!(
+                log,
+                "Error during sync committee message routine";
+                "error" => e,
+                "slot" => slot.as_u64(),
+            );
+        }
+
+        delay_until(contribution_instant).await;
+
+        if let Err(e) = self
+            .produce_and_publish_contributions(slot, beacon_block_root, &duties)
+            .await
+        {
+            crit!(
+                log,
+                "Error during sync committee contribution routine";
+                "error" => e,
+                "slot" => slot.as_u64(),
+            );
+        }
+    }
+
+    async fn produce_and_publish_messages(
+        &self,
+        slot: Slot,
+        beacon_block_root: Hash256,
+        duties: &[SyncDuty],
+    ) -> Result<(), String> {
+        let log = self.context.log();
+
+        let signing_futures = duties.iter().map(|duty| async move {
+            let pubkey = duty_pubkey(duty)?;
+            match self
+                .validator_store
+                .sign_sync_committee_message(&pubkey, duty.validator_index, slot, beacon_block_root)
+                .await
+            {
+                Ok(message) => Some(message),
+                Err(e) => {
+                    crit!(
+                        log,
+                        "Failed to sign sync committee message";
+                        "error" => format!("{:?}", e),
+                        "validator" => format!("{:?}", pubkey),
+                    );
+                    None
+                }
+            }
+        });
+        let messages = join_all(signing_futures)
+            .await
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let messages = &messages;
+        let publish_result = self
+            .beacon_nodes
+            .broadcast(
+                "publish_sync_committee_messages",
+                |beacon_node| async move {
+                    beacon_node
+                        .http
+                        .validator()
+                        .publish_sync_committee_messages(messages.clone())
+                        .await
+                },
+            )
+            .await;
+        metrics::observe_publish(
+            &metrics::PUBLISHED_SYNC_COMMITTEE_MESSAGES,
+            messages.len(),
+            &publish_result,
+        );
+        match publish_result
+            .map_err(|e| format!("Failed to publish sync committee messages: {:?}", e))?
+        {
+            PublishStatus::Valid => info!(
+                log,
+                "Successfully published sync committee messages";
+                "count" => messages.len(),
+                "head_block" => format!("{:?}", beacon_block_root),
+                "slot" => slot.as_u64(),
+            ),
+            PublishStatus::Invalid(msg) => crit!(
+                log,
+                "Published sync committee message was invalid";
+                "message" => msg,
+                "slot" => slot.as_u64(),
+            ),
+            PublishStatus::Unknown => {
+                crit!(
+                    log,
+                    "Unknown condition when publishing sync committee messages"
+                )
+            }
+        }
+
+        Ok(())
+    }
+
+    /// For each subcommittee in which a local validator was elected to aggregate, downloads the
+    /// contribution from the beacon nodes, signs it by each aggregator and publishes the result.
+    async fn produce_and_publish_contributions(
+        &self,
+        slot: Slot,
+        beacon_block_root: Hash256,
+        duties: &[SyncDuty],
+    ) -> Result<(), String> {
+        let log = self.context.log();
+        let spec = &self.context.eth2_config().spec;
+
+        let mut aggregators: HashMap<u64, Vec<(PublicKey, u64, SyncSelectionProof)>> =
+            HashMap::new();
+        for duty in duties {
+            let pubkey = match duty_pubkey(duty) {
+                Some(pubkey) => pubkey,
+                None => continue,
+            };
+            for subcommittee_index in subcommittee_indices::<E>(duty) {
+                let selection_proof = match self
+                    .validator_store
+                    .produce_sync_selection_proof(&pubkey, slot, subcommittee_index)
+                    .await
+                {
+                    Ok(proof) => proof,
+                    Err(e) => {
+                        crit!(
+                            log,
+                            "Failed to produce sync selection proof";
+                            "error" => format!("{:?}", e),
+                            "validator" => format!("{:?}", pubkey),
+                        );
+                        continue;
+                    }
+                };
+                if selection_proof
+                    .is_aggregator::<E>(spec)
+                    .map_err(|e| format!("Unable to determine aggregator: {:?}", e))?
+                {
+                    aggregators.entry(subcommittee_index).or_default().push((
+                        pubkey.clone(),
+                        duty.validator_index,
+                        selection_proof,
+                    ));
+                }
+            }
+        }
+
+        for (subcommittee_index, aggregators) in aggregators {
+            let contribution = self
+                .beacon_nodes
+                .first_success(
+                    "produce_sync_committee_contribution",
+                    |beacon_node| async move {
+                        beacon_node
+                            .http
+                            .validator()
+                            .produce_sync_committee_contribution(
+                                slot,
+                                subcommittee_index,
+                                beacon_block_root,
+                            )
+                            .await
+                    },
+                )
+                .await
+                .map_err(|e| format!("Failed to produce a sync committee contribution: {:?}", e))?;
+
+            let contribution = &contribution;
+            let signing_futures = aggregators.into_iter().map(
+                |(pubkey, aggregator_index, selection_proof)| async move {
+                    match self
+                        .validator_store
+                        .produce_signed_contribution_and_proof(
+                            &pubkey,
+                            aggregator_index,
+                            contribution.clone(),
+                            selection_proof,
+                        )
+                        .await
+                    {
+                        Ok(signed_contribution) => Some(signed_contribution),
+                        Err(e) => {
+                            crit!(
+                                log,
+                                "Failed to sign sync committee contribution";
+                                "error" => format!("{:?}", e),
+                                "validator" => format!("{:?}", pubkey),
+                            );
+                            None
+                        }
+                    }
+                },
+            );
+            let signed_contributions = join_all(signing_futures)
+                .await
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+
+            if signed_contributions.is_empty() {
+                continue;
+            }
+
+            let signed_contributions = &signed_contributions;
+            let publish_result = self
+                .beacon_nodes
+                .broadcast(
+                    "publish_contribution_and_proofs",
+                    |beacon_node| async move {
+                        beacon_node
+                            .http
+                            .validator()
+                            .publish_contribution_and_proofs(signed_contributions.clone())
+                            .await
+                    },
+                )
+                .await;
+            metrics::observe_publish(
+                &metrics::PUBLISHED_CONTRIBUTIONS,
+                signed_contributions.len(),
+                &publish_result,
+            );
+            match publish_result
+                .map_err(|e| format!("Failed to publish contribution and proofs: {:?}", e))?
+            {
+                PublishStatus::Valid => info!(
+                    log,
+                    "Successfully published sync committee contributions";
+                    "signatures" => contribution.aggregation_bits.num_set_bits(),
+                    "subcommittee_index" => subcommittee_index,
+                    "slot" => slot.as_u64(),
+                ),
+                PublishStatus::Invalid(msg) => crit!(
+                    log,
+                    "Published sync committee contribution was invalid";
+                    "message" => msg,
+                    "subcommittee_index" => subcommittee_index,
+                    "slot" => slot.as_u64(),
+                ),
+                PublishStatus::Unknown => crit!(
+                    log,
+                    "Unknown condition when publishing sync committee contributions"
+                ),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the epoch of the first fork in `schedule` which changes the fork version from genesis,
+/// which is the Altair fork.
+fn altair_fork_epoch(schedule: &[Fork], spec: &ChainSpec) -> Option<Epoch> {
+    schedule
+        .iter()
+        .find(|fork| fork.current_version != spec.genesis_fork_version)
+        .map(|fork| fork.epoch)
+}
+
+/// Returns `true` if validators have sync committee duties in `epoch`.
+fn is_sync_committee_active(altair_fork_epoch: Option<Epoch>, epoch: Epoch) -> bool {
+    altair_fork_epoch.map_or(false, |fork_epoch| epoch >= fork_epoch)
+}
+
+/// Returns the distinct subcommittees of the sync committee positions in `duty`.
+fn subcommittee_indices<E: EthSpec>(duty: &SyncDuty) -> Vec<u64> {
+    let mut indices = duty
+        .validator_sync_committee_indices
+        .iter()
+        .map(|index| index / E::sync_subcommittee_size() as u64)
+        .collect::<Vec<_>>();
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+/// Decompresses the public key of `duty`, returning `None` if the beacon node sent invalid bytes.
+fn duty_pubkey(duty: &SyncDuty) -> Option<PublicKey> {
+    (&duty.validator_pubkey).try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{MainnetEthSpec, PublicKeyBytes};
+
+    #[test]
+    fn inactive_before_altair() {
+        let spec = ChainSpec::mainnet();
+        let genesis = Fork {
+            previous_version: spec.genesis_fork_version,
+            current_version: spec.genesis_fork_version,
+            epoch: Epoch::new(0),
+        };
+        let altair = Fork {
+            previous_version: spec.genesis_fork_version,
+            current_version: [1, 0, 0, 0],
+            epoch: Epoch::new(10),
+        };
+
+        assert_eq!(altair_fork_epoch(&[], &spec), None);
+        assert_eq!(altair_fork_epoch(&[genesis.clone()], &spec), None);
+        assert_eq!(
+            altair_fork_epoch(&[genesis, altair], &spec),
+            Some(Epoch::new(10))
+        );
+
+        assert!(!is_sync_committee_active(None, Epoch::new(100)));
+        assert!(!is_sync_committee_active(
+            Some(Epoch::new(10)),
+            Epoch::new(9)
+        ));
+        assert!(is_sync_committee_active(
+            Some(Epoch::new(10)),
+            Epoch::new(10)
+        ));
+    }
+
+    #[test]
+    fn subcommittees_are_deduplicated() {
+        let duty = SyncDuty {
+            validator_pubkey: PublicKeyBytes::empty(),
+            validator_index: 0,
+            validator_sync_committee_indices: vec![0, 1, 130, 511],
+        };
+
+        assert_eq!(subcommittee_indices::<MainnetEthSpec>(&duty), vec![0, 1, 3]);
+    }
+}
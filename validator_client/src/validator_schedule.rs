@@ -0,0 +1,71 @@
+use crate::ProductionValidatorClient;
+use futures::StreamExt;
+use slog::{error, info};
+use slot_clock::SlotClock;
+use tokio::time::{interval_at, Duration, Instant};
+use types::EthSpec;
+
+/// Spawns a service which, at the start of each slot:
+///
+/// - Reloads the `validator_definitions.yml` file if it was modified, so that validators can be
+///   added, enabled and disabled without a restart.
+/// - Enables and disables validators according to the `enable_at` and `disable_at` times of their
+///   definitions.
+///
+/// The schedule of the current epoch is applied when the validator client is created.
+pub fn spawn_validator_schedule<T: EthSpec>(
+    client: &ProductionValidatorClient<T>,
+) -> Result<(), String> {
+    let context = client.context.service_context("validator_schedule".into());
+    let executor = context.executor.clone();
+    let duties_service = client.duties_service.clone();
+    let validator_store = duties_service.validator_store.clone();
+    let slot_clock = duties_service.slot_clock.clone();
+
+    let slot_duration = Duration::from_millis(context.eth2_config.spec.milliseconds_per_slot);
+    let duration_to_next_slot = slot_clock
+        .duration_to_next_slot()
+        .ok_or_else(|| "validator_schedule unable to determine time to next slot")?;
+    let mut interval = interval_at(Instant::now() + duration_to_next_slot, slot_duration);
+
+    let interval_fut = async move {
+        let log = context.log();
+
+        while interval.next().await.is_some() {
+            match validator_store.reload_validator_definitions().await {
+                Ok(true) => info!(
+                    log,
+                    "Reloaded validator definitions";
+                    "enabled" => validator_store.num_voting_validators(),
+                ),
+                Ok(false) => {}
+                Err(e) => error!(
+                    log,
+                    "Failed to reload validator definitions";
+                    "error" => e,
+                ),
+            }
+
+            if !validator_store.has_validator_schedule() {
+                continue;
+            }
+
+            if let Some(slot) = slot_clock.now() {
+                let epoch = slot.epoch(T::slots_per_epoch());
+                if let Err(e) = validator_store.apply_validator_schedule(epoch).await {
+                    error!(
+                        log,
+                        "Failed to apply validator schedule";
+                        "error" => e,
+                        "slot" => slot.as_u64(),
+                    );
+                }
+            } else {
+                error!(log, "Unable to read slot clock");
+            }
+        }
+    };
+
+    executor.spawn(interval_fut, "validator_schedule");
+    Ok(())
+}
@@ -1,18 +1,29 @@
 use crate::{
     config::{Config, SLASHING_PROTECTION_FILENAME},
     fork_service::ForkService,
+    graffiti::GraffitiTemplate,
     initialized_validators::InitializedValidators,
+    metrics,
+    signing_method::{Error as SigningError, SigningMethod},
 };
+use environment::TaskExecutor;
 use parking_lot::RwLock;
-use slashing_protection::{NotSafe, Safe, SlashingDatabase};
-use slog::{crit, error, warn, Logger};
+use slashing_protection::{
+    NotSafe, RemoteSlashingProtection, Safe, SlashingDatabase, SlashingProtection,
+};
+use slog::{crit, error, info, warn, Logger};
 use slot_clock::SlotClock;
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tempdir::TempDir;
+use tokio::task::JoinError;
 use types::{
-    Attestation, BeaconBlock, ChainSpec, Domain, Epoch, EthSpec, Fork, Hash256, Keypair, PublicKey,
-    SelectionProof, Signature, SignedAggregateAndProof, SignedBeaconBlock, SignedRoot, Slot,
+    Address, AggregateAndProof, Attestation, BeaconBlock, ChainSpec, ContributionAndProof, Domain,
+    Epoch, EthSpec, Fork, Hash256, Keypair, PublicKey, SelectionProof, Signature,
+    SignedAggregateAndProof, SignedBeaconBlock, SignedContributionAndProof, SignedRoot, Slot,
+    SyncAggregatorSelectionData, SyncCommitteeContribution, SyncCommitteeMessage,
+    SyncSelectionProof,
 };
 use validator_dir::ValidatorDir;
 
@@ -44,12 +55,17 @@ impl PartialEq for LocalValidator {
 #[derive(Clone)]
 pub struct ValidatorStore<T, E: EthSpec> {
     validators: Arc<RwLock<InitializedValidators>>,
-    slashing_protection: SlashingDatabase,
+    slashing_protection: Arc<dyn SlashingProtection>,
     genesis_validators_root: Hash256,
+    default_fee_recipient: Option<Address>,
+    gas_limit: Option<u64>,
+    /// If true, nothing is signed, since the local clock has drifted.
+    signing_paused: Arc<AtomicBool>,
     spec: Arc<ChainSpec>,
     log: Logger,
     temp_dir: Option<Arc<TempDir>>,
     fork_service: ForkService<T, E>,
+    executor: TaskExecutor,
     _phantom: PhantomData<E>,
 }
 
@@ -60,39 +76,212 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         genesis_validators_root: Hash256,
         spec: ChainSpec,
         fork_service: ForkService<T, E>,
+        executor: TaskExecutor,
         log: Logger,
     ) -> Result<Self, String> {
-        let slashing_db_path = config.data_dir.join(SLASHING_PROTECTION_FILENAME);
-        let slashing_protection =
-            SlashingDatabase::open_or_create(&slashing_db_path).map_err(|e| {
-                format!(
-                    "Failed to open or create slashing protection database: {:?}",
-                    e
-                )
-            })?;
+        let slashing_protection: Arc<dyn SlashingProtection> = match &config.slashing_protection_url
+        {
+            // The remote service is responsible for keeping the data of each network apart.
+            Some(url) => {
+                let remote = RemoteSlashingProtection::new(url).map_err(|e| {
+                    format!("Failed to initialize remote slashing protection: {:?}", e)
+                })?;
+                info!(
+                    log,
+                    "Using remote slashing protection";
+                    "url" => remote.url()
+                );
+                Arc::new(remote)
+            }
+            None => Arc::new(Self::open_slashing_database(
+                config,
+                genesis_validators_root,
+            )?),
+        };
 
         Ok(Self {
             validators: Arc::new(RwLock::new(validators)),
             slashing_protection,
             genesis_validators_root,
+            default_fee_recipient: config.default_fee_recipient,
+            gas_limit: config.gas_limit,
+            signing_paused: Arc::new(AtomicBool::new(false)),
             spec: Arc::new(spec),
             log,
             temp_dir: None,
             fork_service,
+            executor,
             _phantom: PhantomData,
         })
     }
 
+    /// Open the local slashing protection database, checking that it belongs to the network with
+    /// the given `genesis_validators_root`.
+    fn open_slashing_database(
+        config: &Config,
+        genesis_validators_root: Hash256,
+    ) -> Result<SlashingDatabase, String> {
+        let slashing_db_path = config.data_dir.join(SLASHING_PROTECTION_FILENAME);
+        let slashing_protection =
+            SlashingDatabase::open_or_create(&slashing_db_path).map_err(|e| match e {
+                NotSafe::DatabaseLocked(lock_file) => format!(
+                    "The slashing protection database is in use by another process \
+                     (locked via {:?}). Stop the other validator client before starting this one.",
+                    lock_file
+                ),
+                e => format!(
+                    "Failed to open or create slashing protection database: {:?}",
+                    e
+                ),
+            })?;
+
+        if config.force_network_change {
+            slashing_protection
+                .set_genesis_validators_root(genesis_validators_root)
+                .map_err(|e| {
+                    format!(
+                        "Failed to update the network of the slashing protection database: {:?}",
+                        e
+                    )
+                })?;
+        } else {
+            slashing_protection
+                .check_genesis_validators_root(genesis_validators_root)
+                .map_err(|e| match e {
+                    NotSafe::GenesisValidatorsRootMismatch { database, network } => format!(
+                        "The slashing protection database was used on a different network \
+                         (genesis validators root {:?}) to that of the beacon node ({:?}). \
+                         Use a separate --datadir for each network, or, if you are certain the \
+                         database holds no data you still need, use --force-network-change.",
+                        database, network
+                    ),
+                    e => format!(
+                        "Failed to check the network of the slashing protection database: {:?}",
+                        e
+                    ),
+                })?;
+        }
+
+        Ok(slashing_protection)
+    }
+
     /// Register all known validators with the slashing protection database.
     ///
     /// Registration is required to protect against a lost or missing slashing database,
     /// such as when relocating validator keys to a new machine.
-    pub fn register_all_validators_for_slashing_protection(&self) -> Result<(), String> {
-        self.slashing_protection
-            .register_validators(self.validators.read().iter_voting_pubkeys())
+    pub async fn register_all_validators_for_slashing_protection(&self) -> Result<(), String> {
+        self.register_validators(self.voting_pubkeys()).await
+    }
+
+    /// Registers `public_keys` with the slashing protection on the blocking thread pool, since
+    /// the slashing protection may wait on its database or on a remote service.
+    async fn register_validators(&self, public_keys: Vec<PublicKey>) -> Result<(), String> {
+        let slashing_protection = self.slashing_protection.clone();
+        self.executor
+            .spawn_blocking_handle(
+                move || slashing_protection.register_validators(&public_keys),
+                "register_validators",
+            )
+            .await
+            .map_err(|e| format!("Registration task failed: {:?}", e))?
             .map_err(|e| format!("Error while registering validators: {:?}", e))
     }
 
+    /// Returns true if any validator is enabled or disabled automatically at some time.
+    pub fn has_validator_schedule(&self) -> bool {
+        self.validators.read().has_schedule()
+    }
+
+    /// Enables and disables validators according to their schedules, registering any newly
+    /// enabled validators with the slashing protection database.
+    pub async fn apply_validator_schedule(&self, epoch: Epoch) -> Result<(), String> {
+        let enabled = self
+            .validators
+            .write()
+            .apply_schedule(epoch)
+            .map_err(|e| format!("Unable to apply validator schedule: {:?}", e))?;
+
+        if !enabled.is_empty() {
+            self.register_validators(enabled).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reloads the validator definitions if their file was modified, registering any newly
+    /// enabled validators with the slashing protection database.
+    ///
+    /// Returns `true` if the definitions were reloaded. The new validators are registered before
+    /// they are enabled, and nothing is changed if either fails, so that the file is reloaded
+    /// again on the next call.
+    pub async fn reload_validator_definitions(&self) -> Result<bool, String> {
+        let reload = self
+            .validators
+            .read()
+            .begin_reload()
+            .map_err(|e| format!("Unable to reload validator definitions: {:?}", e))?;
+        let mut reload = match reload {
+            Some(reload) => reload,
+            None => return Ok(false),
+        };
+
+        // Keystores are decrypted on the blocking thread pool, without holding the lock.
+        let reload = self
+            .executor
+            .spawn_blocking_handle(
+                move || reload.initialize_validators().map(|()| reload),
+                "reload_validator_definitions",
+            )
+            .await
+            .map_err(|e| format!("Reload task failed: {:?}", e))?
+            .map_err(|e| format!("Unable to reload validator definitions: {:?}", e))?;
+
+        let new_validators = reload.new_validators();
+        if !new_validators.is_empty() {
+            self.register_validators(new_validators).await?;
+        }
+
+        Ok(self.validators.write().complete_reload(reload).is_some())
+    }
+
+    pub fn slashing_protection(&self) -> &Arc<dyn SlashingProtection> {
+        &self.slashing_protection
+    }
+
+    /// Returns the validators, which may be modified to add and remove validators at runtime.
+    pub fn initialized_validators(&self) -> &Arc<RwLock<InitializedValidators>> {
+        &self.validators
+    }
+
+    pub fn genesis_validators_root(&self) -> Hash256 {
+        self.genesis_validators_root
+    }
+
+    /// Returns the graffiti of the given validator's blocks, if it has its own.
+    pub fn graffiti(&self, validator_pubkey: &PublicKey) -> Option<GraffitiTemplate> {
+        self.validators
+            .read()
+            .definition(validator_pubkey)?
+            .graffiti
+            .as_ref()?
+            .parse()
+            .ok()
+    }
+
+    /// Returns the address which the fees of the given validator's blocks are paid to, which is
+    /// its own if it has one.
+    pub fn fee_recipient(&self, validator_pubkey: &PublicKey) -> Option<Address> {
+        self.validators
+            .read()
+            .definition(validator_pubkey)
+            .and_then(|def| def.suggested_fee_recipient)
+            .or(self.default_fee_recipient)
+    }
+
+    pub fn gas_limit(&self) -> Option<u64> {
+        self.gas_limit
+    }
+
     pub fn voting_pubkeys(&self) -> Vec<PublicKey> {
         self.validators
             .read()
@@ -105,119 +294,256 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         self.validators.read().num_enabled()
     }
 
-    fn fork(&self) -> Option<Fork> {
-        if self.fork_service.fork().is_none() {
+    pub fn is_signing_paused(&self) -> bool {
+        self.signing_paused.load(Ordering::Relaxed)
+    }
+
+    /// Pauses or resumes the signing of all messages.
+    pub fn set_signing_paused(&self, paused: bool) {
+        self.signing_paused.store(paused, Ordering::Relaxed)
+    }
+
+    fn fork(&self) -> Result<Fork, SigningError> {
+        self.fork_service.fork().ok_or_else(|| {
             error!(
                 self.log,
                 "Unable to get Fork for signing";
             );
-        }
-        self.fork_service.fork()
+            SigningError::ForkUnknown
+        })
     }
 
-    pub fn randao_reveal(&self, validator_pubkey: &PublicKey, epoch: Epoch) -> Option<Signature> {
-        // TODO: check this against the slot clock to make sure it's not an early reveal?
+    /// Returns the `SigningMethod` of `validator_pubkey`, if it is an enabled validator.
+    ///
+    /// The lock is released on return, since a remote signer may be slow to respond.
+    fn signing_method(
+        &self,
+        validator_pubkey: &PublicKey,
+    ) -> Result<Arc<dyn SigningMethod>, SigningError> {
         self.validators
             .read()
-            .voting_keypair(validator_pubkey)
-            .and_then(|voting_keypair| {
-                let domain = self.spec.get_domain(
-                    epoch,
-                    Domain::Randao,
-                    &self.fork()?,
-                    self.genesis_validators_root,
-                );
-                let message = epoch.signing_root(domain);
+            .validator(validator_pubkey)
+            .map(|validator| validator.signing_method())
+            .ok_or_else(|| SigningError::UnknownValidator(validator_pubkey.clone()))
+    }
 
-                Some(voting_keypair.sk.sign(message))
-            })
+    /// Signs `signing_root` with the voting key of `validator_pubkey`, for messages which are
+    /// not checked by the slashing protection.
+    async fn sign(
+        &self,
+        validator_pubkey: &PublicKey,
+        signing_root: Hash256,
+        message_type: &'static str,
+    ) -> Result<Signature, SigningError> {
+        self.check_not_paused()?;
+        let signing_method = self.signing_method(validator_pubkey)?;
+        self.sign_with(
+            signing_method.as_ref(),
+            validator_pubkey,
+            signing_root,
+            message_type,
+        )
+        .await
     }
 
-    pub fn sign_block(
+    /// Signs `signing_root` with the `signing_method` of `validator_pubkey`.
+    ///
+    /// Blocks and attestations must be approved by `Self::check_slashing_protection` first.
+    async fn sign_with(
         &self,
+        signing_method: &dyn SigningMethod,
         validator_pubkey: &PublicKey,
-        block: BeaconBlock<E>,
-        current_slot: Slot,
-    ) -> Option<SignedBeaconBlock<E>> {
-        // Make sure the block slot is not higher than the current slot to avoid potential attacks.
-        if block.slot > current_slot {
-            warn!(
+        signing_root: Hash256,
+        message_type: &'static str,
+    ) -> Result<Signature, SigningError> {
+        let _timer = metrics::start_timer_vec(&metrics::SIGNING_TIMES, &[message_type]);
+        signing_method.sign(signing_root).await.map_err(|e| {
+            error!(
                 self.log,
-                "Not signing block with slot greater than current slot";
-                "block_slot" => block.slot.as_u64(),
-                "current_slot" => current_slot.as_u64()
+                "Unable to sign message";
+                "error" => format!("{:?}", e),
+                "type" => message_type,
+                "validator" => format!("{:?}", validator_pubkey)
             );
-            return None;
-        }
+            e
+        })
+    }
 
-        // Check for slashing conditions.
-        let fork = self.fork()?;
-        let domain = self.spec.get_domain(
-            block.epoch(),
-            Domain::BeaconProposer,
-            &fork,
-            self.genesis_validators_root,
-        );
+    /// Returns an error if signing is paused, which should be checked before the slashing
+    /// protection records a message which would then not be signed.
+    fn check_not_paused(&self) -> Result<(), SigningError> {
+        if self.is_signing_paused() {
+            Err(SigningError::SigningPaused)
+        } else {
+            Ok(())
+        }
+    }
 
-        let slashing_status = self.slashing_protection.check_and_insert_block_proposal(
-            validator_pubkey,
-            &block.block_header(),
-            domain,
-        );
+    /// Returns `Ok` if the slashing protection approved a message of `message_type` with
+    /// `slashing_status`, in which case the message has been recorded and may be signed.
+    ///
+    /// The check is run on the blocking thread pool, so it fails if that task failed.
+    fn check_slashing_protection(
+        &self,
+        message_type: &'static str,
+        slashing_status: Result<Result<Safe, NotSafe>, JoinError>,
+    ) -> Result<(), SigningError> {
+        let slashing_status = slashing_status.map_err(|e| {
+            crit!(
+                self.log,
+                "Slashing protection check failed";
+                "type" => message_type,
+                "error" => format!("{:?}", e)
+            );
+            SigningError::SlashingProtectionFailed(format!("{:?}", e))
+        })?;
 
         match slashing_status {
-            // We can safely sign this block.
-            Ok(Safe::Valid) => {
-                let validators = self.validators.read();
-                let voting_keypair = validators.voting_keypair(validator_pubkey)?;
-
-                Some(block.sign(
-                    &voting_keypair.sk,
-                    &fork,
-                    self.genesis_validators_root,
-                    &self.spec,
-                ))
-            }
+            Ok(Safe::Valid) => Ok(()),
             Ok(Safe::SameData) => {
+                metrics::inc_counter_vec(
+                    &metrics::SLASHING_PROTECTION_CHECK_FAILURES,
+                    &[message_type, "same_data"],
+                );
                 warn!(
                     self.log,
-                    "Skipping signing of previously signed block";
+                    "Skipping signing of previously signed message";
+                    "type" => message_type,
                 );
-                None
+                Err(SigningError::AlreadySigned)
             }
             Err(NotSafe::UnregisteredValidator(pk)) => {
+                metrics::inc_counter_vec(
+                    &metrics::SLASHING_PROTECTION_CHECK_FAILURES,
+                    &[message_type, "unregistered"],
+                );
                 warn!(
                     self.log,
-                    "Not signing block for unregistered validator";
+                    "Not signing message for unregistered validator";
                     "msg" => "Carefully consider running with --auto-register (see --help)",
+                    "type" => message_type,
                     "public_key" => format!("{:?}", pk)
                 );
-                None
+                Err(SigningError::Refused(NotSafe::UnregisteredValidator(pk)))
             }
             Err(e) => {
+                metrics::inc_counter_vec(
+                    &metrics::SLASHING_PROTECTION_CHECK_FAILURES,
+                    &[message_type, not_safe_reason(&e)],
+                );
                 crit!(
                     self.log,
-                    "Not signing slashable block";
+                    "Not signing slashable message";
+                    "type" => message_type,
                     "error" => format!("{:?}", e)
                 );
-                None
+                Err(SigningError::Refused(e))
             }
         }
     }
 
-    pub fn sign_attestation(
+    pub async fn randao_reveal(
+        &self,
+        validator_pubkey: &PublicKey,
+        epoch: Epoch,
+    ) -> Result<Signature, SigningError> {
+        // TODO: check this against the slot clock to make sure it's not an early reveal?
+        let domain = self.spec.get_domain(
+            epoch,
+            Domain::Randao,
+            &self.fork()?,
+            self.genesis_validators_root,
+        );
+        let message = epoch.signing_root(domain);
+
+        self.sign(validator_pubkey, message, "randao").await
+    }
+
+    pub async fn sign_block(
+        &self,
+        validator_pubkey: &PublicKey,
+        block: BeaconBlock<E>,
+        current_slot: Slot,
+    ) -> Result<SignedBeaconBlock<E>, SigningError> {
+        // Make sure the block slot is not higher than the current slot to avoid potential attacks.
+        if block.slot > current_slot {
+            warn!(
+                self.log,
+                "Not signing block with slot greater than current slot";
+                "block_slot" => block.slot.as_u64(),
+                "current_slot" => current_slot.as_u64()
+            );
+            return Err(SigningError::FutureSlot {
+                slot: block.slot,
+                current_slot,
+            });
+        }
+
+        // Resolve the signer before the slashing protection records the block, so that a block
+        // which cannot be signed is not recorded.
+        self.check_not_paused()?;
+        let signing_method = self.signing_method(validator_pubkey)?;
+        let fork = self.fork()?;
+        let domain = self.spec.get_domain(
+            block.epoch(),
+            Domain::BeaconProposer,
+            &fork,
+            self.genesis_validators_root,
+        );
+
+        let slashing_protection = self.slashing_protection.clone();
+        let pubkey = validator_pubkey.clone();
+        let block_header = block.block_header();
+        let slashing_status = self
+            .executor
+            .spawn_blocking_handle(
+                move || {
+                    slashing_protection.check_and_insert_block_proposal(
+                        &pubkey,
+                        &block_header,
+                        domain,
+                    )
+                },
+                "slashing_protection_block",
+            )
+            .await;
+        self.check_slashing_protection("block", slashing_status)?;
+
+        // We can safely sign this block.
+        let signature = self
+            .sign_with(
+                signing_method.as_ref(),
+                validator_pubkey,
+                block.signing_root(domain),
+                "block",
+            )
+            .await?;
+        metrics::inc_counter(&metrics::SIGNED_BLOCKS);
+
+        Ok(SignedBeaconBlock {
+            message: block,
+            signature,
+        })
+    }
+
+    pub async fn sign_attestation(
         &self,
         validator_pubkey: &PublicKey,
         validator_committee_position: usize,
         attestation: &mut Attestation<E>,
         current_epoch: Epoch,
-    ) -> Option<()> {
+    ) -> Result<(), SigningError> {
         // Make sure the target epoch is not higher than the current epoch to avoid potential attacks.
         if attestation.data.target.epoch > current_epoch {
-            return None;
+            return Err(SigningError::FutureEpoch {
+                epoch: attestation.data.target.epoch,
+                current_epoch,
+            });
         }
 
-        // Checking for slashing conditions.
+        // Resolve the signer before the slashing protection records the attestation.
+        self.check_not_paused()?;
+        let signing_method = self.signing_method(validator_pubkey)?;
         let fork = self.fork()?;
 
         let domain = self.spec.get_domain(
@@ -226,106 +552,518 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
             &fork,
             self.genesis_validators_root,
         );
-        let slashing_status = self.slashing_protection.check_and_insert_attestation(
-            validator_pubkey,
-            &attestation.data,
-            domain,
-        );
-
-        match slashing_status {
-            // We can safely sign this attestation.
-            Ok(Safe::Valid) => {
-                let validators = self.validators.read();
-                let voting_keypair = validators.voting_keypair(validator_pubkey)?;
-
-                attestation
-                    .sign(
-                        &voting_keypair.sk,
-                        validator_committee_position,
-                        &fork,
-                        self.genesis_validators_root,
-                        &self.spec,
+        let slashing_protection = self.slashing_protection.clone();
+        let pubkey = validator_pubkey.clone();
+        let attestation_data = attestation.data.clone();
+        let slashing_status = self
+            .executor
+            .spawn_blocking_handle(
+                move || {
+                    slashing_protection.check_and_insert_attestation(
+                        &pubkey,
+                        &attestation_data,
+                        domain,
                     )
-                    .map_err(|e| {
-                        error!(
-                            self.log,
-                            "Error whilst signing attestation";
-                            "error" => format!("{:?}", e)
-                        )
-                    })
-                    .ok()?;
-
-                Some(())
-            }
-            Ok(Safe::SameData) => {
-                warn!(
-                    self.log,
-                    "Skipping signing of previously signed attestation"
-                );
-                None
-            }
-            Err(NotSafe::UnregisteredValidator(pk)) => {
-                warn!(
-                    self.log,
-                    "Not signing attestation for unregistered validator";
-                    "msg" => "Carefully consider running with --auto-register (see --help)",
-                    "public_key" => format!("{:?}", pk)
-                );
-                None
-            }
-            Err(e) => {
-                crit!(
+                },
+                "slashing_protection_attestation",
+            )
+            .await;
+        self.check_slashing_protection("attestation", slashing_status)?;
+
+        // We can safely sign this attestation.
+        let signature = self
+            .sign_with(
+                signing_method.as_ref(),
+                validator_pubkey,
+                attestation.data.signing_root(domain),
+                "attestation",
+            )
+            .await?;
+
+        attestation
+            .add_signature(&signature, validator_committee_position)
+            .map_err(|e| {
+                error!(
                     self.log,
-                    "Not signing slashable attestation";
-                    "attestation" => format!("{:?}", attestation.data),
+                    "Error whilst signing attestation";
                     "error" => format!("{:?}", e)
                 );
-                None
-            }
-        }
+                SigningError::UnableToAddSignature(e)
+            })?;
+        metrics::inc_counter(&metrics::SIGNED_ATTESTATIONS);
+
+        Ok(())
     }
 
     /// Signs an `AggregateAndProof` for a given validator.
     ///
     /// The resulting `SignedAggregateAndProof` is sent on the aggregation channel and cannot be
     /// modified by actors other than the signing validator.
-    pub fn produce_signed_aggregate_and_proof(
+    pub async fn produce_signed_aggregate_and_proof(
         &self,
         validator_pubkey: &PublicKey,
         validator_index: u64,
         aggregate: Attestation<E>,
         selection_proof: SelectionProof,
-    ) -> Option<SignedAggregateAndProof<E>> {
-        let validators = self.validators.read();
-        let voting_keypair = &validators.voting_keypair(validator_pubkey)?;
-
-        Some(SignedAggregateAndProof::from_aggregate(
-            validator_index,
+    ) -> Result<SignedAggregateAndProof<E>, SigningError> {
+        let message = AggregateAndProof {
+            aggregator_index: validator_index,
             aggregate,
-            Some(selection_proof),
-            &voting_keypair.sk,
+            selection_proof: selection_proof.into(),
+        };
+        let domain = self.spec.get_domain(
+            message.aggregate.data.slot.epoch(E::slots_per_epoch()),
+            Domain::AggregateAndProof,
             &self.fork()?,
             self.genesis_validators_root,
-            &self.spec,
-        ))
+        );
+        let signature = self
+            .sign(
+                validator_pubkey,
+                message.signing_root(domain),
+                "aggregate_and_proof",
+            )
+            .await?;
+        metrics::inc_counter(&metrics::SIGNED_AGGREGATES);
+
+        Ok(SignedAggregateAndProof { message, signature })
     }
 
     /// Produces a `SelectionProof` for the `slot`, signed by with corresponding secret key to
     /// `validator_pubkey`.
-    pub fn produce_selection_proof(
+    pub async fn produce_selection_proof(
+        &self,
+        validator_pubkey: &PublicKey,
+        slot: Slot,
+    ) -> Result<SelectionProof, SigningError> {
+        let domain = self.spec.get_domain(
+            slot.epoch(E::slots_per_epoch()),
+            Domain::SelectionProof,
+            &self.fork()?,
+            self.genesis_validators_root,
+        );
+
+        self.sign(
+            validator_pubkey,
+            slot.signing_root(domain),
+            "selection_proof",
+        )
+        .await
+        .map(SelectionProof::from)
+    }
+
+    /// Signs a vote for `beacon_block_root` at `slot` as a member of the sync committee.
+    pub async fn sign_sync_committee_message(
         &self,
         validator_pubkey: &PublicKey,
+        validator_index: u64,
         slot: Slot,
-    ) -> Option<SelectionProof> {
-        let validators = self.validators.read();
-        let voting_keypair = &validators.voting_keypair(validator_pubkey)?;
+        beacon_block_root: Hash256,
+    ) -> Result<SyncCommitteeMessage, SigningError> {
+        let domain = self.spec.get_domain(
+            slot.epoch(E::slots_per_epoch()),
+            Domain::SyncCommittee,
+            &self.fork()?,
+            self.genesis_validators_root,
+        );
+        let signature = self
+            .sign(
+                validator_pubkey,
+                beacon_block_root.signing_root(domain),
+                "sync_committee_message",
+            )
+            .await?;
+        metrics::inc_counter(&metrics::SIGNED_SYNC_COMMITTEE_MESSAGES);
+
+        Ok(SyncCommitteeMessage {
+            slot,
+            beacon_block_root,
+            validator_index,
+            signature,
+        })
+    }
 
-        Some(SelectionProof::new::<E>(
+    /// Produces a `SyncSelectionProof` for the `subcommittee_index` at `slot`, signed by the
+    /// secret key corresponding to `validator_pubkey`.
+    pub async fn produce_sync_selection_proof(
+        &self,
+        validator_pubkey: &PublicKey,
+        slot: Slot,
+        subcommittee_index: u64,
+    ) -> Result<SyncSelectionProof, SigningError> {
+        let domain = self.spec.get_domain(
+            slot.epoch(E::slots_per_epoch()),
+            Domain::SyncCommitteeSelectionProof,
+            &self.fork()?,
+            self.genesis_validators_root,
+        );
+        let message = SyncAggregatorSelectionData {
             slot,
-            &voting_keypair.sk,
+            subcommittee_index,
+        };
+
+        self.sign(
+            validator_pubkey,
+            message.signing_root(domain),
+            "sync_selection_proof",
+        )
+        .await
+        .map(SyncSelectionProof::from)
+    }
+
+    /// Signs a `ContributionAndProof` for a given validator.
+    pub async fn produce_signed_contribution_and_proof(
+        &self,
+        validator_pubkey: &PublicKey,
+        aggregator_index: u64,
+        contribution: SyncCommitteeContribution<E>,
+        selection_proof: SyncSelectionProof,
+    ) -> Result<SignedContributionAndProof<E>, SigningError> {
+        let message = ContributionAndProof {
+            aggregator_index,
+            contribution,
+            selection_proof: selection_proof.into(),
+        };
+        let domain = self.spec.get_domain(
+            message.contribution.slot.epoch(E::slots_per_epoch()),
+            Domain::ContributionAndProof,
             &self.fork()?,
             self.genesis_validators_root,
-            &self.spec,
-        ))
+        );
+        let signature = self
+            .sign(
+                validator_pubkey,
+                message.signing_root(domain),
+                "contribution_and_proof",
+            )
+            .await?;
+        metrics::inc_counter(&metrics::SIGNED_CONTRIBUTIONS);
+
+        Ok(SignedContributionAndProof { message, signature })
+    }
+}
+
+/// The reason a message was refused by the slashing protection, as a metric label.
+fn not_safe_reason(not_safe: &NotSafe) -> &'static str {
+    match not_safe {
+        NotSafe::InvalidBlock(_) | NotSafe::InvalidAttestation(_) | NotSafe::RemoteRefused(_) => {
+            "slashable"
+        }
+        _ => "error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beacon_node_fallback::BeaconNodeFallback;
+    use crate::fork_service::ForkServiceBuilder;
+    use account_utils::validator_definitions::{ValidatorDefinition, ValidatorDefinitions};
+    use environment::{Environment, EnvironmentBuilder, RuntimeContext};
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Response, Server};
+    use slot_clock::TestingSlotClock;
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::time::Duration;
+    use types::{AggregateSignature, AttestationData, BitList, Checkpoint, MinimalEthSpec};
+
+    type E = MinimalEthSpec;
+    type Store = ValidatorStore<TestingSlotClock, E>;
+
+    fn build_env() -> Environment<E> {
+        EnvironmentBuilder::minimal()
+            .null_logger()
+            .expect("should build env logger")
+            .multi_threaded_tokio_runtime()
+            .expect("should start tokio runtime")
+            .build()
+            .expect("environment should build")
+    }
+
+    /// Serves the same signature to every request, like a Web3Signer which holds the key.
+    fn spawn_signer() -> String {
+        let signature = Keypair::random().sk.sign(Hash256::zero());
+        let body = format!("0x{}", hex::encode(signature.serialize().to_vec()));
+
+        let make_service = make_service_fn(move |_| {
+            let body = body.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |_| {
+                    let body = body.clone();
+                    async move { Ok::<_, Infallible>(Response::new(Body::from(body))) }
+                }))
+            }
+        });
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let url = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+        url
+    }
+
+    /// Builds a store with a single validator, which signs with the Web3Signer at `signer_url`.
+    fn build_store(
+        context: &RuntimeContext<E>,
+        dir: &TempDir,
+        signer_url: String,
+        fork: Option<Fork>,
+    ) -> (Store, PublicKey) {
+        let voting_public_key = Keypair::random().pk;
+        let mut definitions = ValidatorDefinitions::default();
+        definitions.push(ValidatorDefinition::new_web3signer(
+            voting_public_key.clone(),
+            signer_url,
+        ));
+        let validators = InitializedValidators::from_definitions(
+            definitions,
+            dir.path().to_path_buf(),
+            true,
+            context.log().clone(),
+        )
+        .unwrap();
+
+        let slot_clock =
+            TestingSlotClock::new(Slot::new(0), Duration::from_secs(0), Duration::from_secs(6));
+        let beacon_nodes = Arc::new(BeaconNodeFallback::new(
+            vec![],
+            slot_clock.clone(),
+            context.log().clone(),
+        ));
+        let mut fork_service = ForkServiceBuilder::new()
+            .slot_clock(slot_clock)
+            .beacon_nodes(beacon_nodes)
+            .runtime_context(context.clone());
+        if let Some(fork) = fork {
+            fork_service = fork_service.fork(fork);
+        }
+
+        let config = Config {
+            data_dir: dir.path().to_path_buf(),
+            ..Config::default()
+        };
+        let store = ValidatorStore::new(
+            validators,
+            &config,
+            Hash256::zero(),
+            E::default_spec(),
+            fork_service.build().unwrap(),
+            context.executor.clone(),
+            context.log().clone(),
+        )
+        .unwrap();
+
+        (store, voting_public_key)
+    }
+
+    fn block(slot: u64) -> BeaconBlock<E> {
+        let mut block = BeaconBlock::empty(&E::default_spec());
+        block.slot = Slot::new(slot);
+        block
+    }
+
+    fn attestation(source: u64, target: u64) -> Attestation<E> {
+        Attestation {
+            aggregation_bits: BitList::with_capacity(1).unwrap(),
+            data: AttestationData {
+                source: Checkpoint {
+                    epoch: Epoch::new(source),
+                    root: Hash256::zero(),
+                },
+                target: Checkpoint {
+                    epoch: Epoch::new(target),
+                    root: Hash256::zero(),
+                },
+                ..AttestationData::default()
+            },
+            signature: AggregateSignature::empty(),
+        }
+    }
+
+    #[test]
+    fn future_messages_are_not_signed() {
+        let mut env = build_env();
+        let context = env.core_context();
+        let dir = TempDir::new("validator_store").unwrap();
+
+        env.runtime().block_on(async move {
+            let (store, pubkey) =
+                build_store(&context, &dir, spawn_signer(), Some(Fork::default()));
+
+            assert!(matches!(
+                store.sign_block(&pubkey, block(2), Slot::new(1)).await,
+                Err(SigningError::FutureSlot { .. })
+            ));
+            assert!(matches!(
+                store
+                    .sign_attestation(&pubkey, 0, &mut attestation(0, 2), Epoch::new(1))
+                    .await,
+                Err(SigningError::FutureEpoch { .. })
+            ));
+        });
+    }
+
+    #[test]
+    fn paused_and_forkless_stores_do_not_sign() {
+        let mut env = build_env();
+        let context = env.core_context();
+        let dir = TempDir::new("validator_store").unwrap();
+
+        env.runtime().block_on(async move {
+            let (store, pubkey) = build_store(&context, &dir, spawn_signer(), None);
+            store
+                .register_all_validators_for_slashing_protection()
+                .await
+                .unwrap();
+
+            assert!(matches!(
+                store.sign_block(&pubkey, block(1), Slot::new(1)).await,
+                Err(SigningError::ForkUnknown)
+            ));
+            assert!(matches!(
+                store.randao_reveal(&pubkey, Epoch::new(0)).await,
+                Err(SigningError::ForkUnknown)
+            ));
+
+            store.set_signing_paused(true);
+            assert!(matches!(
+                store.sign_block(&pubkey, block(1), Slot::new(1)).await,
+                Err(SigningError::SigningPaused)
+            ));
+            assert!(matches!(
+                store.randao_reveal(&pubkey, Epoch::new(0)).await,
+                Err(SigningError::SigningPaused)
+            ));
+        });
+    }
+
+    /// A block for a validator without a signer must not be recorded by the slashing protection,
+    /// since it is never signed.
+    #[test]
+    fn unknown_validator_is_resolved_before_slashing_protection() {
+        let mut env = build_env();
+        let context = env.core_context();
+        let dir = TempDir::new("validator_store").unwrap();
+
+        env.runtime().block_on(async move {
+            let (store, _) = build_store(&context, &dir, spawn_signer(), Some(Fork::default()));
+            let unknown = Keypair::random().pk;
+            store
+                .slashing_protection()
+                .register_validators(&[unknown.clone()])
+                .unwrap();
+
+            assert!(matches!(
+                store.sign_block(&unknown, block(1), Slot::new(1)).await,
+                Err(SigningError::UnknownValidator(pubkey)) if pubkey == unknown
+            ));
+            assert_eq!(
+                store
+                    .slashing_protection()
+                    .check_and_insert_block_proposal(
+                        &unknown,
+                        &block(1).block_header(),
+                        Hash256::zero()
+                    )
+                    .unwrap(),
+                Safe::Valid
+            );
+        });
+    }
+
+    #[test]
+    fn slashing_protection_refusals() {
+        let mut env = build_env();
+        let context = env.core_context();
+        let dir = TempDir::new("validator_store").unwrap();
+
+        env.runtime().block_on(async move {
+            let (store, pubkey) =
+                build_store(&context, &dir, spawn_signer(), Some(Fork::default()));
+            // No other test signs a block or has one refused by slashing protection.
+            let signed_blocks = metrics::SIGNED_BLOCKS.as_ref().unwrap().get();
+            let refusals = |reason: &str| {
+                metrics::get_int_counter(
+                    &metrics::SLASHING_PROTECTION_CHECK_FAILURES,
+                    &["block", reason],
+                )
+                .unwrap()
+                .get()
+            };
+            let (unregistered, same_data, slashable) = (
+                refusals("unregistered"),
+                refusals("same_data"),
+                refusals("slashable"),
+            );
+
+            assert!(matches!(
+                store.sign_block(&pubkey, block(1), Slot::new(1)).await,
+                Err(SigningError::Refused(NotSafe::UnregisteredValidator(_)))
+            ));
+
+            store
+                .register_all_validators_for_slashing_protection()
+                .await
+                .unwrap();
+            store
+                .sign_block(&pubkey, block(1), Slot::new(1))
+                .await
+                .unwrap();
+            assert!(matches!(
+                store.sign_block(&pubkey, block(1), Slot::new(1)).await,
+                Err(SigningError::AlreadySigned)
+            ));
+
+            let mut conflicting = block(1);
+            conflicting.state_root = Hash256::repeat_byte(1);
+            assert!(matches!(
+                store.sign_block(&pubkey, conflicting, Slot::new(1)).await,
+                Err(SigningError::Refused(NotSafe::InvalidBlock(_)))
+            ));
+
+            assert_eq!(
+                metrics::SIGNED_BLOCKS.as_ref().unwrap().get(),
+                signed_blocks + 1
+            );
+            assert_eq!(refusals("unregistered"), unregistered + 1);
+            assert_eq!(refusals("same_data"), same_data + 1);
+            assert_eq!(refusals("slashable"), slashable + 1);
+
+            let failed = tokio::spawn(async { panic!("slashing protection panicked") }).await;
+            assert!(matches!(
+                store.check_slashing_protection("block", failed.map(|()| Ok(Safe::Valid))),
+                Err(SigningError::SlashingProtectionFailed(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn signer_and_signature_errors() {
+        let mut env = build_env();
+        let context = env.core_context();
+        let dir = TempDir::new("validator_store").unwrap();
+
+        env.runtime().block_on(async move {
+            // Nothing listens on this port.
+            let unreachable = "http://127.0.0.1:1".to_string();
+            let (store, pubkey) = build_store(&context, &dir, unreachable, Some(Fork::default()));
+            assert!(matches!(
+                store.randao_reveal(&pubkey, Epoch::new(0)).await,
+                Err(SigningError::SignerUnavailable(_))
+            ));
+
+            let dir = TempDir::new("validator_store").unwrap();
+            let (store, pubkey) =
+                build_store(&context, &dir, spawn_signer(), Some(Fork::default()));
+            store
+                .register_all_validators_for_slashing_protection()
+                .await
+                .unwrap();
+            assert!(matches!(
+                store
+                    .sign_attestation(&pubkey, 1, &mut attestation(0, 1), Epoch::new(1))
+                    .await,
+                Err(SigningError::UnableToAddSignature(_))
+            ));
+        });
     }
 }
@@ -0,0 +1,218 @@
+//! Signing by a remote Web3Signer instance over HTTP.
+//!
+//! The signer holds the secret keys of the validators, so this validator client only ever sends
+//! it signing roots. Slashing protection is still enforced by this validator client before any
+//! request is made.
+use reqwest::{header::CONTENT_TYPE, Certificate, Client, Identity, StatusCode};
+use serde_derive::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use types::{Hash256, PublicKey, Signature};
+
+/// The time to wait for the signer to respond to a single request.
+pub const WEB3SIGNER_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+pub enum Error {
+    /// A certificate or identity file could not be read.
+    UnableToReadFile(PathBuf, io::Error),
+    /// The root certificate is not a valid PEM certificate.
+    InvalidRootCertificate(reqwest::Error),
+    /// The client identity is not a valid PKCS#12 archive, or the password is wrong.
+    InvalidClientIdentity(reqwest::Error),
+    /// The HTTP client could not be built.
+    UnableToBuildClient(reqwest::Error),
+    /// The request could not be sent or its response could not be read.
+    RequestFailed(reqwest::Error),
+    /// The signer responded with a status other than `200 OK`.
+    UnexpectedStatus(StatusCode),
+    /// The signer responded with something other than a valid signature.
+    InvalidSignature(String),
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SigningRequest {
+    signing_root: Hash256,
+}
+
+#[derive(Deserialize)]
+struct SigningResponse {
+    signature: String,
+}
+
+/// A client for a remote Web3Signer.
+///
+/// Signatures are requested with `POST /api/v1/eth2/sign/<public key>`, where the body is a JSON
+/// object with the `signingRoot` to sign. The signer may respond with the hex-encoded signature
+/// as plain text, or as the `signature` field of a JSON object.
+#[derive(Debug, Clone)]
+pub struct Web3Signer {
+    client: Client,
+    url: String,
+}
+
+impl Web3Signer {
+    /// Create a client for the signer at `url`, e.g. `https://localhost:9000`.
+    ///
+    /// The certificate at `root_certificate_path` is trusted in addition to the system roots. If
+    /// `client_identity` is `Some`, the PKCS#12 archive at that path is decrypted with the given
+    /// password and presented to the signer.
+    pub fn new(
+        url: &str,
+        root_certificate_path: Option<&Path>,
+        client_identity: Option<(&Path, &str)>,
+    ) -> Result<Self, Error> {
+        let mut builder = Client::builder().timeout(WEB3SIGNER_TIMEOUT);
+
+        if let Some(path) = root_certificate_path {
+            let pem = read_file(path)?;
+            let certificate = Certificate::from_pem(&pem).map_err(Error::InvalidRootCertificate)?;
+            builder = builder.add_root_certificate(certificate);
+        }
+
+        if let Some((path, password)) = client_identity {
+            let der = read_file(path)?;
+            let identity =
+                Identity::from_pkcs12_der(&der, password).map_err(Error::InvalidClientIdentity)?;
+            builder = builder.identity(identity);
+        }
+
+        let client = builder.build().map_err(Error::UnableToBuildClient)?;
+
+        Ok(Self {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// The URL of the signer.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Request a signature over `signing_root` by the key of `voting_public_key`.
+    pub async fn sign(
+        &self,
+        voting_public_key: &PublicKey,
+        signing_root: Hash256,
+    ) -> Result<Signature, Error> {
+        let response = self
+            .client
+            .post(&format!(
+                "{}/api/v1/eth2/sign/{}",
+                self.url,
+                voting_public_key.to_hex_string()
+            ))
+            .json(&SigningRequest { signing_root })
+            .send()
+            .await
+            .map_err(Error::RequestFailed)?;
+
+        if response.status() != StatusCode::OK {
+            return Err(Error::UnexpectedStatus(response.status()));
+        }
+
+        let is_json = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map_or(false, |value| value.starts_with("application/json"));
+
+        let signature = if is_json {
+            response
+                .json::<SigningResponse>()
+                .await
+                .map_err(Error::RequestFailed)?
+                .signature
+        } else {
+            response.text().await.map_err(Error::RequestFailed)?
+        };
+
+        parse_signature(&signature)
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, Error> {
+    fs::read(path).map_err(|e| Error::UnableToReadFile(path.to_path_buf(), e))
+}
+
+/// Parse a `0x`-prefixed, hex-encoded signature, ignoring surrounding whitespace.
+fn parse_signature(signature: &str) -> Result<Signature, Error> {
+    let signature = signature.trim();
+    if !signature.starts_with("0x") {
+        return Err(Error::InvalidSignature("Missing 0x prefix".to_string()));
+    }
+    let bytes =
+        hex::decode(&signature[2..]).map_err(|e| Error::InvalidSignature(format!("{:?}", e)))?;
+    Signature::deserialize(&bytes).map_err(|e| Error::InvalidSignature(format!("{:?}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Response, Server};
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use types::Keypair;
+
+    #[test]
+    fn trims_trailing_slash() {
+        let signer = Web3Signer::new("http://localhost:9000/", None, None).unwrap();
+        assert_eq!(signer.url(), "http://localhost:9000");
+    }
+
+    #[test]
+    fn parses_signature() {
+        let keypair = Keypair::random();
+        let signature = keypair.sk.sign(Hash256::repeat_byte(42));
+        let encoded = format!(" 0x{}\n", hex::encode(signature.serialize().to_vec()));
+
+        assert_eq!(parse_signature(&encoded).unwrap(), signature);
+        assert!(parse_signature(encoded.trim().trim_start_matches("0x")).is_err());
+        assert!(parse_signature("0x1234").is_err());
+    }
+
+    /// The signer is awaited on the runtime, so it must not block.
+    #[tokio::test]
+    async fn signs_within_runtime() {
+        let keypair = Keypair::random();
+        let signing_root = Hash256::repeat_byte(42);
+        let signature = keypair.sk.sign(signing_root);
+        let body = format!("0x{}", hex::encode(signature.serialize().to_vec()));
+
+        let make_service = make_service_fn(move |_| {
+            let body = body.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |_| {
+                    let body = body.clone();
+                    async move { Ok::<_, Infallible>(Response::new(Body::from(body))) }
+                }))
+            }
+        });
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let signer =
+            Web3Signer::new(&format!("http://{}", server.local_addr()), None, None).unwrap();
+        tokio::spawn(server);
+
+        assert_eq!(
+            signer.sign(&keypair.pk, signing_root).await.unwrap(),
+            signature
+        );
+    }
+
+    #[test]
+    fn unreadable_root_certificate() {
+        match Web3Signer::new(
+            "https://localhost:9000",
+            Some(Path::new("/this/path/does/not/exist.pem")),
+            None,
+        ) {
+            Err(Error::UnableToReadFile(_, _)) => (),
+            _ => panic!("expected an error reading the root certificate"),
+        }
+    }
+}